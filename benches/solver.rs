@@ -0,0 +1,79 @@
+//! Solver benchmarks: `cargo bench --no-default-features`.
+//!
+//! Each phase is timed on its own against ropes and cloths of a few sizes,
+//! starting from a world that's already been stepped for a while so the
+//! constraints are under some load.
+
+use clothsim::World;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use glam::Vec2;
+
+const WARMUP_STEPS: usize = 60;
+
+fn rope(points: usize) -> World {
+    let mut world = World::new(points as f32 * 60.0);
+    world.add_rope(Vec2::ZERO, points);
+    world
+}
+
+fn cloth(side: usize) -> World {
+    let mut world = World::new(side as f32 * 60.0);
+    world.add_cloth(Vec2::ZERO, side, side, 3);
+    world
+}
+
+/// Every benchmarked world, named by shape and node count.
+fn worlds() -> Vec<(String, World)> {
+    let mut worlds = Vec::new();
+    for points in [10, 100, 1000] {
+        worlds.push((format!("rope/{}", points), rope(points)));
+    }
+    for side in [10, 32, 100] {
+        worlds.push((format!("cloth/{}", side * side), cloth(side)));
+    }
+
+    for (_, world) in worlds.iter_mut() {
+        for _ in 0..WARMUP_STEPS {
+            world.step(world.params.dt);
+        }
+    }
+    worlds
+}
+
+/// Benchmarks `phase` against a fresh copy of each world per batch.
+fn bench_phase(c: &mut Criterion, name: &str, phase: fn(&mut World)) {
+    let mut group = c.benchmark_group(name);
+    for (id, world) in worlds() {
+        group.bench_with_input(BenchmarkId::from_parameter(id), &world, |b, world| {
+            b.iter_batched_ref(|| world.clone(), phase, BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn integrate(c: &mut Criterion) {
+    bench_phase(c, "integrate", |world| {
+        world.apply_forces();
+        world.integrate(world.params.dt);
+    });
+}
+
+fn solve_constraints(c: &mut Criterion) {
+    bench_phase(c, "solve_constraints", World::solve_constraints);
+}
+
+fn collide_ground(c: &mut Criterion) {
+    bench_phase(c, "collide_ground", |world| {
+        let (ground_y, friction) = (world.ground_y, world.friction);
+        world.arena.iter_mut().for_each(|node| node.collide_ground(ground_y, &friction));
+    });
+}
+
+fn step(c: &mut Criterion) {
+    bench_phase(c, "step", |world| {
+        world.step(world.params.dt);
+    });
+}
+
+criterion_group!(benches, integrate, solve_constraints, collide_ground, step);
+criterion_main!(benches);
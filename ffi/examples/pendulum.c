@@ -0,0 +1,32 @@
+/* Hangs a short rope, steps it, and prints where the end ends up.
+ *
+ *   cargo build --release -p clothsim-ffi
+ *   cc examples/pendulum.c -Iinclude -L../target/release -lclothsim -lm -o pendulum
+ *   LD_LIBRARY_PATH=../target/release ./pendulum
+ */
+#include <stdio.h>
+
+#include "clothsim.h"
+
+int main(void) {
+    ClothsimWorld *world = clothsim_world_new(520.0f);
+    size_t previous = clothsim_add_node(world, 400.0f, 50.0f, 1.0f, true);
+    for (int i = 1; i < 6; i++) {
+        /* swung out sideways so it has somewhere to fall */
+        size_t node = clothsim_add_node(world, 400.0f + 50.0f * i, 50.0f, 1.0f, false);
+        clothsim_add_constraint(world, previous, node, 0.0f, 0.0f);
+        previous = node;
+    }
+
+    for (int step = 0; step < 300; step++) {
+        clothsim_step(world);
+    }
+
+    float positions[2 * 6];
+    size_t count = clothsim_get_positions(world, positions, 6);
+    printf("%zu nodes, end at (%.1f, %.1f), checksum %llx\n", count, positions[2 * (count - 1)],
+           positions[2 * (count - 1) + 1], (unsigned long long)clothsim_checksum(world));
+
+    clothsim_world_free(world);
+    return 0;
+}
@@ -0,0 +1,184 @@
+/* Generated by cbindgen from ffi/src/lib.rs; regenerate with `cbindgen --config cbindgen.toml -o include/clothsim.h`. */
+
+#ifndef CLOTHSIM_H
+#define CLOTHSIM_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* Returned in place of an index by calls given bad arguments. */
+#define CLOTHSIM_INVALID SIZE_MAX
+
+/**
+ * A simulation world. Only ever handled by pointer.
+ */
+typedef struct ClothsimWorld ClothsimWorld;
+
+/**
+ * Physics constants, as in `clothsim::Params`.
+ */
+typedef struct ClothsimParams {
+  float dt;
+  float gravity;
+  float drag;
+  /**
+   * Fraction of each constraint's error corrected per solver iteration.
+   */
+  float rigidity;
+  size_t solver_iterations;
+  /**
+   * Length at which new constraints break, in rest lengths.
+   */
+  float break_threshold;
+} ClothsimParams;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The params new worlds start with.
+ */
+struct ClothsimParams clothsim_params_default(void);
+
+/**
+ * Creates an empty world with the ground at `ground_y` (y points down).
+ */
+struct ClothsimWorld *clothsim_world_new(float ground_y);
+
+/**
+ * Frees a world. Null is ignored.
+ *
+ * # Safety
+ * `world` must be null or come from `clothsim_world_new`, and not be used afterwards.
+ */
+void clothsim_world_free(struct ClothsimWorld *world);
+
+/**
+ * The world's current params.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+struct ClothsimParams clothsim_world_params(const struct ClothsimWorld *world);
+
+/**
+ * Replaces the world's params, rescaling existing break thresholds if that one
+ * changed. Returns false, leaving the params as they were, if any is out of range.
+ *
+ * # Safety
+ * `world` must be a live world and `params` point to a `ClothsimParams`.
+ */
+bool clothsim_world_set_params(struct ClothsimWorld *world, const struct ClothsimParams *params);
+
+/**
+ * Adds a node, returning its index, or `CLOTHSIM_INVALID` if `mass` isn't positive.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+size_t clothsim_add_node(struct ClothsimWorld *world, float x, float y, float mass, bool fixed);
+
+/**
+ * Links nodes `a` and `b`, returning false if either doesn't exist, they're
+ * the same node, or the rest length isn't positive. A `rest_length` of 0 or
+ * less means the nodes' current distance, and a `break_threshold` of 0 or
+ * less means the params' `break_threshold` rest lengths.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+bool clothsim_add_constraint(struct ClothsimWorld *world,
+                             size_t a,
+                             size_t b,
+                             float rest_length,
+                             float break_threshold);
+
+/**
+ * Runs one step of the params' `dt`, returning how many constraints broke.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+size_t clothsim_step(struct ClothsimWorld *world);
+
+/**
+ * Removes constraints crossing the segment from (`x0`, `y0`) to (`x1`, `y1`),
+ * returning how many were cut.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+size_t clothsim_cut(struct ClothsimWorld *world, float x0, float y0, float x1, float y1);
+
+/**
+ * Number of nodes, which is one past the highest node index.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+size_t clothsim_node_count(const struct ClothsimWorld *world);
+
+/**
+ * Number of intact constraints.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+size_t clothsim_constraint_count(const struct ClothsimWorld *world);
+
+/**
+ * Copies node positions into `out` as x, y pairs, for at most `capacity`
+ * nodes, returning the total node count so callers can size the buffer.
+ *
+ * # Safety
+ * `world` must be a live world and `out` hold `2 * capacity` floats (or be null if `capacity` is 0).
+ */
+size_t clothsim_get_positions(const struct ClothsimWorld *world,
+                              float *out,
+                              size_t capacity);
+
+/**
+ * Copies each constraint's node indices into `out` as a, b pairs, for at
+ * most `capacity` constraints, returning the total constraint count.
+ *
+ * # Safety
+ * `world` must be a live world and `out` hold `2 * capacity` indices (or be null if `capacity` is 0).
+ */
+size_t clothsim_get_constraints(const struct ClothsimWorld *world,
+                                size_t *out,
+                                size_t capacity);
+
+/**
+ * Moves node `node` to (`x`, `y`) and stops it, for dragging from the host.
+ * Returns false if there's no such node.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+bool clothsim_set_position(struct ClothsimWorld *world, size_t node, float x, float y);
+
+/**
+ * Pins or releases node `node`. Returns false if there's no such node.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+bool clothsim_set_fixed(struct ClothsimWorld *world, size_t node, bool fixed);
+
+/**
+ * FNV-1a hash of the world's exact state, for checking two hosts stay in sync.
+ *
+ * # Safety
+ * `world` must be a live world.
+ */
+uint64_t clothsim_checksum(const struct ClothsimWorld *world);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CLOTHSIM_H */
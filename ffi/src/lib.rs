@@ -0,0 +1,266 @@
+//! C API for embedding the solver, declared in `include/clothsim.h`.
+//!
+//! A world is an opaque pointer from `clothsim_world_new`, freed with
+//! `clothsim_world_free`. Nodes are referred to by the index `clothsim_add_node`
+//! returned and constraints by the pair of nodes they link. Calls that can't
+//! take effect leave the world untouched and return `CLOTHSIM_INVALID` or false.
+//!
+//! This is a thin layer over `SimWorld`, which does all the checking.
+
+use clothsim::{NodeId, Param, Params, SimEvent, SimWorld, Vec2};
+use std::slice;
+
+/// Returned in place of an index by calls given bad arguments. The header
+/// defines it as `SIZE_MAX`, since cbindgen can't export `usize` constants.
+pub const CLOTHSIM_INVALID: usize = usize::MAX;
+
+/// A simulation world. Only ever handled by pointer.
+pub struct ClothsimWorld {
+    sim: SimWorld,
+}
+
+/// Physics constants, as in `clothsim::Params`.
+#[repr(C)]
+pub struct ClothsimParams {
+    pub dt: f32,
+    pub gravity: f32,
+    pub drag: f32,
+    /// Fraction of each constraint's error corrected per solver iteration.
+    pub rigidity: f32,
+    pub solver_iterations: usize,
+    /// Length at which new constraints break, in rest lengths.
+    pub break_threshold: f32,
+}
+
+impl From<Params> for ClothsimParams {
+    fn from(p: Params) -> Self {
+        Self {
+            dt: p.dt,
+            gravity: p.gravity,
+            drag: p.drag,
+            rigidity: p.rigidity,
+            solver_iterations: p.solver_iterations,
+            break_threshold: p.break_threshold,
+        }
+    }
+}
+
+/// The params new worlds start with.
+#[no_mangle]
+pub extern "C" fn clothsim_params_default() -> ClothsimParams {
+    Params::default().into()
+}
+
+/// Creates an empty world with the ground at `ground_y` (y points down).
+#[no_mangle]
+pub extern "C" fn clothsim_world_new(ground_y: f32) -> *mut ClothsimWorld {
+    Box::into_raw(Box::new(ClothsimWorld { sim: SimWorld::new(ground_y) }))
+}
+
+/// Frees a world. Null is ignored.
+///
+/// # Safety
+/// `world` must be null or come from `clothsim_world_new`, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_world_free(world: *mut ClothsimWorld) {
+    if !world.is_null() {
+        drop(Box::from_raw(world));
+    }
+}
+
+/// The world's current params.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_world_params(world: *const ClothsimWorld) -> ClothsimParams {
+    (*world).sim.params().into()
+}
+
+/// Replaces the world's params, rescaling existing break thresholds if that one
+/// changed. Returns false, leaving the params as they were, if any is out of range.
+///
+/// # Safety
+/// `world` must be a live world and `params` point to a `ClothsimParams`.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_world_set_params(
+    world: *mut ClothsimWorld,
+    params: *const ClothsimParams,
+) -> bool {
+    let p = &*params;
+    let sim = &mut (*world).sim;
+    let mut updated = sim.clone();
+    let valid = [
+        (Param::Dt, p.dt),
+        (Param::Gravity, p.gravity),
+        (Param::Drag, p.drag),
+        (Param::Rigidity, p.rigidity),
+        (Param::SolverIterations, p.solver_iterations as f32),
+        (Param::BreakThreshold, p.break_threshold),
+    ]
+    .into_iter()
+    .all(|(param, value)| updated.set_param(param, value).is_ok());
+    if valid {
+        *sim = updated;
+    }
+    valid
+}
+
+/// Adds a node, returning its index, or `CLOTHSIM_INVALID` if `mass` isn't positive.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_add_node(
+    world: *mut ClothsimWorld,
+    x: f32,
+    y: f32,
+    mass: f32,
+    fixed: bool,
+) -> usize {
+    match (*world).sim.add_node(Vec2::new(x, y), mass, fixed) {
+        Ok(NodeId(node)) => node,
+        Err(_) => CLOTHSIM_INVALID,
+    }
+}
+
+/// Links nodes `a` and `b`, returning false if either doesn't exist, they're
+/// the same node, or the rest length isn't positive. A `rest_length` of 0 or
+/// less means the nodes' current distance, and a `break_threshold` of 0 or
+/// less means the params' `break_threshold` rest lengths.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_add_constraint(
+    world: *mut ClothsimWorld,
+    a: usize,
+    b: usize,
+    rest_length: f32,
+    break_threshold: f32,
+) -> bool {
+    let rest_length = Some(rest_length).filter(|&length| length > 0.0);
+    let break_threshold = Some(break_threshold).filter(|&threshold| threshold > 0.0);
+    (*world).sim.add_constraint(NodeId(a), NodeId(b), rest_length, break_threshold).is_ok()
+}
+
+/// Runs one step of the params' `dt`, returning how many constraints broke.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_step(world: *mut ClothsimWorld) -> usize {
+    let events = (*world).sim.step();
+    events.iter().filter(|e| matches!(e, SimEvent::ConstraintBroken(_))).count()
+}
+
+/// Removes constraints crossing the segment from (`x0`, `y0`) to (`x1`, `y1`),
+/// returning how many were cut.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_cut(
+    world: *mut ClothsimWorld,
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+) -> usize {
+    (*world).sim.cut(Vec2::new(x0, y0), Vec2::new(x1, y1))
+}
+
+/// Number of nodes, which is one past the highest node index.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_node_count(world: *const ClothsimWorld) -> usize {
+    (*world).sim.node_count()
+}
+
+/// Number of intact constraints.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_constraint_count(world: *const ClothsimWorld) -> usize {
+    (*world).sim.constraint_count()
+}
+
+/// Copies node positions into `out` as x, y pairs, for at most `capacity`
+/// nodes, returning the total node count so callers can size the buffer.
+///
+/// # Safety
+/// `world` must be a live world and `out` hold `2 * capacity` floats (or be null if `capacity` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_get_positions(
+    world: *const ClothsimWorld,
+    out: *mut f32,
+    capacity: usize,
+) -> usize {
+    let sim = &(*world).sim;
+    if capacity > 0 {
+        let out = slice::from_raw_parts_mut(out, 2 * capacity);
+        for (node, pair) in sim.nodes().zip(out.chunks_exact_mut(2)) {
+            pair[0] = node.pos.x;
+            pair[1] = node.pos.y;
+        }
+    }
+    sim.node_count()
+}
+
+/// Copies each constraint's node indices into `out` as a, b pairs, for at
+/// most `capacity` constraints, returning the total constraint count.
+///
+/// # Safety
+/// `world` must be a live world and `out` hold `2 * capacity` indices (or be null if `capacity` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_get_constraints(
+    world: *const ClothsimWorld,
+    out: *mut usize,
+    capacity: usize,
+) -> usize {
+    let sim = &(*world).sim;
+    if capacity > 0 {
+        let out = slice::from_raw_parts_mut(out, 2 * capacity);
+        for (constraint, pair) in sim.constraints().zip(out.chunks_exact_mut(2)) {
+            pair[0] = constraint.a.0;
+            pair[1] = constraint.b.0;
+        }
+    }
+    sim.constraint_count()
+}
+
+/// Moves node `node` to (`x`, `y`) and stops it, for dragging from the host.
+/// Returns false if there's no such node.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_set_position(
+    world: *mut ClothsimWorld,
+    node: usize,
+    x: f32,
+    y: f32,
+) -> bool {
+    (*world).sim.set_position(NodeId(node), Vec2::new(x, y)).is_ok()
+}
+
+/// Pins or releases node `node`. Returns false if there's no such node.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_set_fixed(world: *mut ClothsimWorld, node: usize, fixed: bool) -> bool {
+    (*world).sim.set_fixed(NodeId(node), fixed).is_ok()
+}
+
+/// FNV-1a hash of the world's exact state, for checking two hosts stay in sync.
+///
+/// # Safety
+/// `world` must be a live world.
+#[no_mangle]
+pub unsafe extern "C" fn clothsim_checksum(world: *const ClothsimWorld) -> u64 {
+    (*world).sim.world().checksum()
+}
@@ -0,0 +1,229 @@
+//! Python bindings for the headless solver, built with `maturin develop`.
+//!
+//! Node and link data cross over as numpy arrays, one row per node or link,
+//! so whole-world reads and writes don't go through Python objects.
+
+use clothsim::{scene, NodeId, Param, Rng, SimEvent, SimWorld, SimWorldError, Vec2, World, TARGET_DIST};
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+/// A world of nodes and the constraints between them.
+#[pyclass(name = "World")]
+struct PyWorld {
+    sim: SimWorld,
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    #[pyo3(signature = (
+        ground_y = 520.0,
+        *,
+        dt = clothsim::DT,
+        gravity = clothsim::G,
+        drag = clothsim::DRAG,
+        rigidity = clothsim::RIGIDITY,
+        iterations = clothsim::SOLVER_ITERATIONS,
+        break_threshold = clothsim::BREAK_THRESHOLD,
+        seed = 0,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        ground_y: f32,
+        dt: f32,
+        gravity: f32,
+        drag: f32,
+        rigidity: f32,
+        iterations: usize,
+        break_threshold: f32,
+        seed: u64,
+    ) -> PyResult<Self> {
+        let mut world = World::new(ground_y);
+        world.rng = Rng::new(seed);
+        let mut sim = SimWorld::from_world(world).map_err(error)?;
+        for (param, value) in [
+            (Param::Dt, dt),
+            (Param::Gravity, gravity),
+            (Param::Drag, drag),
+            (Param::Rigidity, rigidity),
+            (Param::SolverIterations, iterations as f32),
+            (Param::BreakThreshold, break_threshold),
+        ] {
+            sim.set_param(param, value).map_err(error)?;
+        }
+        Ok(Self { sim })
+    }
+
+    /// Loads a world saved in the `clothsim-scene 1` text format.
+    #[staticmethod]
+    fn from_scene(text: &str) -> PyResult<Self> {
+        let world = scene::from_text(text).map_err(PyValueError::new_err)?;
+        let sim = SimWorld::from_world(world).map_err(error)?;
+        Ok(Self { sim })
+    }
+
+    /// The world in the `clothsim-scene 1` text format.
+    fn to_scene(&self) -> String {
+        scene::to_text(self.sim.world())
+    }
+
+    /// Sets one param by its `sim.toml` name, like `"gravity"` or `"iterations"`.
+    fn set_param(&mut self, name: &str, value: f32) -> PyResult<()> {
+        let param =
+            Param::from_name(name).ok_or_else(|| PyValueError::new_err(format!("no param {}", name)))?;
+        self.sim.set_param(param, value).map_err(error)
+    }
+
+    /// Adds a node, returning its index.
+    #[pyo3(signature = (x, y, mass = 1.0, fixed = false))]
+    fn add_node(&mut self, x: f32, y: f32, mass: f32, fixed: bool) -> PyResult<usize> {
+        let NodeId(node) = self.sim.add_node(Vec2::new(x, y), mass, fixed).map_err(error)?;
+        Ok(node)
+    }
+
+    /// Links nodes `a` and `b`. The rest length defaults to their current
+    /// distance and the break threshold to `break_threshold` rest lengths.
+    #[pyo3(signature = (a, b, rest_length = None, break_threshold = None))]
+    fn add_constraint(
+        &mut self,
+        a: usize,
+        b: usize,
+        rest_length: Option<f32>,
+        break_threshold: Option<f32>,
+    ) -> PyResult<()> {
+        self.sim.add_constraint(NodeId(a), NodeId(b), rest_length, break_threshold).map_err(error)
+    }
+
+    /// Hangs a rope of `nodes` nodes from a fixed node at (`x`, `y`).
+    fn add_rope(&mut self, x: f32, y: f32, nodes: usize) {
+        self.sim.add_rope(Vec2::new(x, y), nodes);
+    }
+
+    /// Hangs a `columns` by `rows` cloth from (`x`, `y`), pinned every `pin_every` nodes.
+    #[pyo3(signature = (x, y, columns, rows, pin_every = 1))]
+    fn add_cloth(&mut self, x: f32, y: f32, columns: usize, rows: usize, pin_every: usize) {
+        self.sim.add_cloth(Vec2::new(x, y), columns, rows, pin_every);
+    }
+
+    /// Runs `steps` steps, returning how many constraints broke.
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, py: Python<'_>, steps: usize) -> usize {
+        let sim = &mut self.sim;
+        py.detach(|| {
+            let mut broken = 0;
+            for _ in 0..steps {
+                let events = sim.step();
+                broken += events.iter().filter(|e| matches!(e, SimEvent::ConstraintBroken(_))).count();
+            }
+            broken
+        })
+    }
+
+    /// Removes constraints crossing the segment between the two points,
+    /// returning how many were cut.
+    fn cut(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> usize {
+        self.sim.cut(Vec2::new(x0, y0), Vec2::new(x1, y1))
+    }
+
+    /// Node positions as an `(n, 2)` float32 array.
+    fn positions<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        points(py, self.sim.nodes().map(|node| node.pos))
+    }
+
+    /// Overwrites node positions from an `(n, 2)` array, zeroing velocities.
+    fn set_positions(&mut self, positions: PyReadonlyArray2<f32>) -> PyResult<()> {
+        let positions = positions.as_array();
+        if positions.dim() != (self.sim.node_count(), 2) {
+            return Err(PyValueError::new_err(format!(
+                "expected shape ({}, 2), got {:?}",
+                self.sim.node_count(),
+                positions.shape()
+            )));
+        }
+        for (i, row) in positions.rows().into_iter().enumerate() {
+            self.sim.set_position(NodeId(i), Vec2::new(row[0], row[1])).map_err(error)?;
+        }
+        Ok(())
+    }
+
+    /// Node velocities as an `(n, 2)` float32 array.
+    fn velocities<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        points(py, self.sim.nodes().map(|node| node.vel))
+    }
+
+    /// Node masses as an `(n,)` float32 array.
+    fn masses<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f32>> {
+        let masses: Vec<f32> = self.sim.nodes().map(|node| node.mass).collect();
+        masses.into_pyarray(py)
+    }
+
+    /// Whether each node is pinned, as an `(n,)` bool array.
+    fn fixed<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<bool>> {
+        let fixed: Vec<bool> = self.sim.nodes().map(|node| node.fixed).collect();
+        fixed.into_pyarray(py)
+    }
+
+    /// Pins or releases node `node`.
+    fn set_fixed(&mut self, node: usize, fixed: bool) -> PyResult<()> {
+        self.sim.set_fixed(NodeId(node), fixed).map_err(error)
+    }
+
+    /// Node indices of each constraint, as an `(m, 2)` uint64 array.
+    fn links<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u64>> {
+        let flat: Vec<u64> = self.sim.constraints().flat_map(|c| [c.a.0 as u64, c.b.0 as u64]).collect();
+        Array2::from_shape_vec((self.sim.constraint_count(), 2), flat).unwrap().into_pyarray(py)
+    }
+
+    /// Break fraction of each constraint, as an `(m,)` float32 array.
+    fn strains<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f32>> {
+        let strains: Vec<f32> = self.sim.constraints().map(|c| c.break_fraction).collect();
+        strains.into_pyarray(py)
+    }
+
+    /// FNV-1a hash of the world's exact state, for comparing runs.
+    fn checksum(&self) -> u64 {
+        self.sim.world().checksum()
+    }
+
+    #[getter]
+    fn node_count(&self) -> usize {
+        self.sim.node_count()
+    }
+
+    #[getter]
+    fn constraint_count(&self) -> usize {
+        self.sim.constraint_count()
+    }
+
+    #[getter]
+    fn ground_y(&self) -> f32 {
+        self.sim.world().ground_y
+    }
+
+    fn __repr__(&self) -> String {
+        format!("World(nodes={}, constraints={})", self.sim.node_count(), self.sim.constraint_count())
+    }
+}
+
+fn error(err: SimWorldError) -> PyErr {
+    match err {
+        SimWorldError::NoSuchNode(_) => PyIndexError::new_err(err.to_string()),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
+
+fn points<'py>(py: Python<'py>, points: impl ExactSizeIterator<Item = Vec2>) -> Bound<'py, PyArray2<f32>> {
+    let rows = points.len();
+    let flat: Vec<f32> = points.flat_map(|p| [p.x, p.y]).collect();
+    Array2::from_shape_vec((rows, 2), flat).unwrap().into_pyarray(py)
+}
+
+#[pymodule]
+#[pyo3(name = "clothsim")]
+fn clothsim_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyWorld>()?;
+    module.add("TARGET_DIST", TARGET_DIST)?;
+    Ok(())
+}
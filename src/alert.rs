@@ -0,0 +1,77 @@
+use crate::event::SimEvent;
+use crate::world::World;
+use glam::Vec2;
+
+/// Break fraction past which a `StrainAlert` goes off unless told otherwise.
+pub const STRAIN_ALERT: f32 = 0.8;
+
+/// Which constraint set off a `StrainAlert`, and where and how close to breaking it was at the time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrainedLink {
+    /// The nodes it links.
+    pub a: usize,
+    pub b: usize,
+    pub pos: Vec2,
+    /// Its break fraction, from 0 at rest length to 1 at the threshold.
+    pub fraction: f32,
+}
+
+/// Watches for constraints close to breaking. Checked after each step, it
+/// goes off when the most strained constraint gets `fraction` of the way
+/// to its break threshold, and not again until they've all dropped back
+/// under, so a rope held taut warns once instead of every step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrainAlert {
+    pub fraction: f32,
+    /// Whether a constraint was past `fraction` at the last check.
+    pub raised: bool,
+}
+
+impl Default for StrainAlert {
+    fn default() -> Self {
+        Self { fraction: STRAIN_ALERT, raised: false }
+    }
+}
+
+impl StrainAlert {
+    /// Looks over `world`, returning a `SimEvent::StrainAlert` for its most
+    /// strained constraint if that just got to `fraction`.
+    pub fn check(&mut self, world: &World) -> Option<SimEvent> {
+        let worst = world.strains().into_iter().enumerate().max_by(|(_, x), (_, y)| x.total_cmp(y));
+        let over = worst.filter(|&(_, fraction)| fraction >= self.fraction);
+        let was_raised = std::mem::replace(&mut self.raised, over.is_some());
+        let (i, fraction) = over.filter(|_| !was_raised)?;
+
+        let constraint = &world.constraints[i];
+        let pos = world.arena[constraint.a].pos + constraint.offset(&world.arena, &world.wrap) / 2.0;
+        Some(SimEvent::StrainAlert(StrainedLink { a: constraint.a, b: constraint.b, pos, fraction }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::node::Node;
+
+    #[test]
+    fn goes_off_once_until_the_strain_drops() {
+        let mut world = World::new(1000.0);
+        world.arena.push(Node::with_pos_and_mass(Vec2::ZERO, 1.0));
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(90.0, 0.0), 1.0));
+        // 40 past its rest length of 50, out of 50 to the threshold
+        world.constraints.push(Constraint { a: 0, b: 1, rest_length: 50.0, break_threshold: 100.0 });
+
+        let mut alert = StrainAlert::default();
+        let Some(SimEvent::StrainAlert(link)) = alert.check(&world) else {
+            panic!("a link 80% of the way to breaking should set off an alert");
+        };
+        assert_eq!(link, StrainedLink { a: 0, b: 1, pos: Vec2::new(45.0, 0.0), fraction: 0.8 });
+        assert!(alert.check(&world).is_none());
+
+        world.arena[1].pos.x = 60.0;
+        assert!(alert.check(&world).is_none());
+        world.arena[1].pos.x = 90.0;
+        assert!(alert.check(&world).is_some());
+    }
+}
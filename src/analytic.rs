@@ -0,0 +1,224 @@
+//! Textbook solutions for the simplest scenes, a pendulum and a weight on a
+//! spring, to hold the solver's answers up against.
+
+use crate::world::World;
+use glam::Vec2;
+use std::f32::consts::TAU;
+
+/// One of the systems with a closed-form solution, picked out of a world by its shape.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Canonical {
+    /// A free `bob` swinging from a fixed `pivot` on a single constraint.
+    Pendulum { pivot: usize, bob: usize, length: f32 },
+    /// A free `bob` bouncing under a fixed `top` on a single band.
+    Spring { top: usize, bob: usize, rest_length: f32, stiffness: f32 },
+}
+
+impl Canonical {
+    /// Recognizes a world of just two nodes, one of them fixed, joined by one
+    /// constraint or one band and nothing else.
+    pub fn detect(world: &World) -> Option<Self> {
+        let [a, b] = world.arena.as_slice() else {
+            return None;
+        };
+        if a.fixed == b.fixed || !world.anchors.is_empty() || world.constraints.len() + world.bands.len() != 1 {
+            return None;
+        }
+        let (top, bob) = if a.fixed { (0, 1) } else { (1, 0) };
+        if let Some(constraint) = world.constraints.first() {
+            return Some(Canonical::Pendulum { pivot: top, bob, length: constraint.rest_length });
+        }
+        let band = world.bands[0];
+        Some(Canonical::Spring { top, bob, rest_length: band.rest_length, stiffness: band.stiffness })
+    }
+
+    /// The fixed node and the bob.
+    pub fn ends(&self) -> (usize, usize) {
+        match *self {
+            Canonical::Pendulum { pivot, bob, .. } => (pivot, bob),
+            Canonical::Spring { top, bob, .. } => (top, bob),
+        }
+    }
+
+    /// Where the system is in `world` and how fast it's getting there: the
+    /// pendulum's angle from hanging straight down, positive to the right, or
+    /// how far the spring is stretched past its rest length.
+    pub fn state(&self, world: &World) -> (f32, f32) {
+        let (top, bob) = self.ends();
+        let offset = world.wrap.delta(world.arena[top].pos, world.arena[bob].pos);
+        let vel = world.arena[bob].vel;
+        match *self {
+            Canonical::Pendulum { .. } => {
+                let angle = offset.x.atan2(offset.y);
+                (angle, (offset.y * vel.x - offset.x * vel.y) / offset.length_squared())
+            }
+            Canonical::Spring { rest_length, .. } => {
+                (offset.length() - rest_length, vel.dot(offset.normalize_or_zero()))
+            }
+        }
+    }
+
+    /// The motion the textbook gives the system from how it is in `world`,
+    /// under its gravity and drag and nothing else. The pendulum's period is
+    /// stretched for its amplitude, which the small-angle formula leaves out.
+    pub fn solve(&self, world: &World) -> Oscillator {
+        let (displacement, speed) = self.state(world);
+        let bob = &world.arena[self.ends().1];
+        let (gravity, decay) = (world.params.gravity, world.params.drag / (2.0 * bob.mass));
+        match *self {
+            Canonical::Pendulum { length, .. } => {
+                let natural = (gravity / length).sqrt();
+                let amplitude = displacement.hypot(speed / natural);
+                let stretch = 1.0 + amplitude.powi(2) / 16.0 + 11.0 * amplitude.powi(4) / 3072.0;
+                Oscillator::new(0.0, displacement, speed, natural / stretch, decay)
+            }
+            Canonical::Spring { stiffness, .. } => {
+                let equilibrium = bob.mass * gravity / stiffness;
+                let natural = (stiffness / bob.mass).sqrt();
+                Oscillator::new(equilibrium, displacement - equilibrium, speed, natural, decay)
+            }
+        }
+    }
+
+    /// Where the bob would be with the system at `displacement`, for drawing.
+    pub fn bob_pos(&self, world: &World, displacement: f32) -> Vec2 {
+        let top = world.arena[self.ends().0].pos;
+        match *self {
+            Canonical::Pendulum { length, .. } => top + Vec2::new(displacement.sin(), displacement.cos()) * length,
+            Canonical::Spring { rest_length, .. } => top + Vec2::new(0.0, rest_length + displacement),
+        }
+    }
+}
+
+/// A damped harmonic oscillator's motion: settling on `equilibrium`,
+/// swinging about it at `frequency` radians per unit of time, and dying
+/// away as `e^(-decay * t)`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Oscillator {
+    pub equilibrium: f32,
+    pub frequency: f32,
+    pub decay: f32,
+    /// `cos` and `sin` parts of the swing at time 0.
+    pub start: (f32, f32),
+}
+
+impl Oscillator {
+    /// Starting `offset` from `equilibrium` and moving at `speed`, with an
+    /// undamped frequency of `natural`. Damping slows the swing down a little.
+    pub fn new(equilibrium: f32, offset: f32, speed: f32, natural: f32, decay: f32) -> Self {
+        let frequency = (natural * natural - decay * decay).max(0.0).sqrt();
+        let sine = if frequency > 0.0 { (speed + decay * offset) / frequency } else { 0.0 };
+        Self { equilibrium, frequency, decay, start: (offset, sine) }
+    }
+
+    /// Time for one full swing, there and back.
+    pub fn period(&self) -> f32 {
+        TAU / self.frequency
+    }
+
+    pub fn at(&self, time: f32) -> f32 {
+        let (cos, sin) = self.start;
+        let phase = self.frequency * time;
+        self.equilibrium + (-self.decay * time).exp() * (cos * phase.cos() + sin * phase.sin())
+    }
+}
+
+/// When a simulated system passed through its expected equilibrium.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Crossing {
+    time: f32,
+    /// `Analytic::area` at the time.
+    area: f32,
+}
+
+/// A canonical system as simulated next to its textbook solution, timing
+/// the simulation's swings to compare their period and where they center.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analytic {
+    pub system: Canonical,
+    pub expected: Oscillator,
+    /// Simulated time since the comparison started.
+    pub time: f32,
+    /// Each time the system passed through the expected equilibrium, either way.
+    crossings: Vec<Crossing>,
+    /// How far past equilibrium the system was after the last step.
+    last_offset: f32,
+    /// The displacement integrated over time, for its average over whole swings.
+    area: f32,
+}
+
+impl Analytic {
+    /// Starts comparing `world` from now, if it's one of the canonical systems.
+    pub fn new(world: &World) -> Option<Self> {
+        let system = Canonical::detect(world)?;
+        let expected = system.solve(world);
+        Some(Self {
+            system,
+            expected,
+            time: 0.0,
+            crossings: Vec::new(),
+            last_offset: system.state(world).0 - expected.equilibrium,
+            area: 0.0,
+        })
+    }
+
+    /// Takes the system's state after a step of `dt`.
+    pub fn record(&mut self, world: &World, dt: f32) {
+        let displacement = self.system.state(world).0;
+        let offset = displacement - self.expected.equilibrium;
+        if offset != 0.0 && offset.signum() != self.last_offset.signum() {
+            // between the steps, where the line between them crosses zero
+            let before = self.last_offset / (self.last_offset - offset) * dt;
+            self.crossings.push(Crossing { time: self.time + before, area: self.area + displacement * before });
+        }
+        self.time += dt;
+        self.area += displacement * dt;
+        self.last_offset = offset;
+    }
+
+    /// The first crossing and the last one a whole number of swings after
+    /// it, with how many swings that is, once there's been at least one.
+    fn whole_swings(&self) -> Option<(Crossing, Crossing, usize)> {
+        let swings = self.crossings.len().checked_sub(1)? / 2;
+        (swings > 0).then(|| (self.crossings[0], self.crossings[2 * swings], swings))
+    }
+
+    /// The simulation's average time per full swing over those it's made so far.
+    pub fn measured_period(&self) -> Option<f32> {
+        let (first, last, swings) = self.whole_swings()?;
+        Some((last.time - first.time) / swings as f32)
+    }
+
+    /// Where the simulation's swings have centered on average over those it's made so far.
+    pub fn measured_equilibrium(&self) -> Option<f32> {
+        let (first, last, _) = self.whole_swings()?;
+        Some((last.area - first.area) / (last.time - first.time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+
+    #[test]
+    fn canonical_scenes_match_the_textbook() {
+        for build in [scene::pendulum, scene::spring] {
+            let mut world = build(1280.0, 720.0);
+            let mut analytic = Analytic::new(&world).expect("both scenes are canonical");
+            let dt = world.params.dt;
+            for _ in 0..400 {
+                world.step(dt);
+                analytic.record(&world, dt);
+            }
+            let expected = analytic.expected;
+            let period = analytic.measured_period().unwrap();
+            assert!((period / expected.period() - 1.0).abs() < 0.01, "{:?}: {}", analytic.system, period);
+            let equilibrium = analytic.measured_equilibrium().unwrap();
+            // a pendulum centers on hanging straight down, and the spring on its bob's weight over its stiffness
+            let tolerance = 0.05 * expected.equilibrium.max(1.0);
+            assert!((equilibrium - expected.equilibrium).abs() < tolerance, "{}", equilibrium);
+        }
+        assert!(Analytic::new(&scene::default_rope(1280.0, 720.0)).is_none());
+    }
+}
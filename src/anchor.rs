@@ -0,0 +1,126 @@
+use crate::math;
+use glam::Vec2;
+
+/// Where a kinematic anchor goes over time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnchorPath {
+    /// Back and forth between two points, starting at the first.
+    Line(Vec2, Vec2),
+    /// Round and round, starting at the rightmost point and turning clockwise
+    /// on screen (y points down) for positive speeds.
+    Circle { center: Vec2, radius: f32 },
+    /// Back and forth along the lines through the points, starting at the first.
+    Waypoints(Vec<Vec2>),
+    /// Swinging sinusoidally between `center - amplitude` and `center + amplitude`,
+    /// starting at the center and heading toward `center + amplitude`.
+    Oscillate { center: Vec2, amplitude: Vec2 },
+}
+
+impl AnchorPath {
+    /// The point `travelled` along the path, or `travelled` cycles into an oscillation.
+    pub fn at(&self, travelled: f32) -> Vec2 {
+        match self {
+            AnchorPath::Line(a, b) => along(&[*a, *b], travelled),
+            AnchorPath::Circle { center, radius } if *radius > 0.0 => {
+                *center + math::from_angle(travelled / radius) * *radius
+            }
+            AnchorPath::Circle { center, .. } => *center,
+            AnchorPath::Waypoints(points) => along(points, travelled),
+            AnchorPath::Oscillate { center, amplitude } => {
+                *center + *amplitude * math::sin(travelled * std::f32::consts::TAU)
+            }
+        }
+    }
+}
+
+/// A fixed node moved along a path at a steady speed, like a hoist or a swinging anchor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Anchor {
+    pub node: usize,
+    pub path: AnchorPath,
+    /// Distance along the path per unit of time, or for oscillations, cycles per unit of time.
+    pub speed: f32,
+    /// Distance along the path so far, or cycles for oscillations. Speed changes
+    /// only change how fast this grows, so the anchor never jumps.
+    pub travelled: f32,
+}
+
+impl Anchor {
+    pub fn new(node: usize, path: AnchorPath, speed: f32) -> Self {
+        Self { node, path, speed, travelled: 0.0 }
+    }
+
+    /// Moves `dt` further along the path.
+    pub fn advance(&mut self, dt: f32) {
+        self.travelled += self.speed * dt;
+    }
+
+    /// Where the anchor is now.
+    pub fn position(&self) -> Vec2 {
+        self.path.at(self.travelled)
+    }
+}
+
+/// The point `distance` along `points` when going back and forth along them forever.
+fn along(points: &[Vec2], distance: f32) -> Vec2 {
+    let length: f32 = points.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+    let Some(&first) = points.first() else { return Vec2::ZERO };
+    if length == 0.0 {
+        return first;
+    }
+
+    let mut remaining = distance.abs() % (2.0 * length);
+    if remaining > length {
+        remaining = 2.0 * length - remaining;
+    }
+    for pair in points.windows(2) {
+        let segment = pair[0].distance(pair[1]);
+        if remaining <= segment && segment > 0.0 {
+            return pair[0].lerp(pair[1], remaining / segment);
+        }
+        remaining -= segment;
+    }
+    points[points.len() - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(path: AnchorPath, elapsed: f32) -> Vec2 {
+        let mut anchor = Anchor::new(0, path, 10.0);
+        anchor.advance(elapsed);
+        anchor.position()
+    }
+
+    #[test]
+    fn lines_go_back_and_forth() {
+        let line = AnchorPath::Line(Vec2::ZERO, Vec2::new(100.0, 0.0));
+        assert_eq!(at(line.clone(), 0.0), Vec2::ZERO);
+        assert_eq!(at(line.clone(), 4.0), Vec2::new(40.0, 0.0));
+        assert_eq!(at(line.clone(), 13.0), Vec2::new(70.0, 0.0));
+        assert_eq!(at(line, 21.0), Vec2::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn waypoints_follow_every_leg() {
+        let points = vec![Vec2::ZERO, Vec2::new(0.0, 50.0), Vec2::new(50.0, 50.0)];
+        assert_eq!(at(AnchorPath::Waypoints(points.clone()), 7.0), Vec2::new(20.0, 50.0));
+        assert!(at(AnchorPath::Waypoints(points), 17.0).distance(Vec2::new(0.0, 30.0)) < 1e-4);
+    }
+
+    #[test]
+    fn circles_keep_their_radius() {
+        let circle = AnchorPath::Circle { center: Vec2::new(10.0, 10.0), radius: 20.0 };
+        assert_eq!(at(circle.clone(), 0.0), Vec2::new(30.0, 10.0));
+        assert!((at(circle, 3.3).distance(Vec2::new(10.0, 10.0)) - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn oscillations_complete_a_cycle_per_unit_of_speed() {
+        let swing = AnchorPath::Oscillate { center: Vec2::new(5.0, 5.0), amplitude: Vec2::new(10.0, 0.0) };
+        // at ten cycles per unit of time, a quarter cycle takes 0.025
+        assert!(at(swing.clone(), 0.025).distance(Vec2::new(15.0, 5.0)) < 1e-4);
+        assert!(at(swing, 0.075).distance(Vec2::new(-5.0, 5.0)) < 1e-4);
+    }
+}
@@ -0,0 +1,29 @@
+/// What the app is doing, which decides the systems that run each frame and what the mouse does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AppState {
+    /// The start menu, over a frozen scene.
+    Menu,
+    /// Stepping, with the knife and wind under the mouse.
+    Simulating,
+    /// Frozen, for looking around with the camera.
+    Paused,
+    /// Frozen, with the mouse moving nodes and pinning them in place.
+    Editor,
+}
+
+impl AppState {
+    /// Whether the simulation steps in this state.
+    pub fn steps(self) -> bool {
+        self == AppState::Simulating
+    }
+
+    /// One-line hint about what the mouse does, for the HUD, with `{cut}` and the like for the buttons; see `bindings`.
+    pub fn hint(self) -> &'static str {
+        match self {
+            AppState::Menu => "",
+            AppState::Simulating => "{cut} Click to Cut",
+            AppState::Paused => "Paused, Space to Resume",
+            AppState::Editor => "{grab} Drag to Move or Throw Nodes, {pin} Click to Pin",
+        }
+    }
+}
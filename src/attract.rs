@@ -0,0 +1,73 @@
+//! Attract mode, for leaving the demo running at an exhibit: once no one has
+//! touched it for a while, it plays through a loop of bundled replays with a
+//! caption over each, and hands back control as soon as anyone does.
+
+use crate::replay::Replay;
+
+/// Seconds to hold on the end of each replay before the next one starts.
+const HOLD: f64 = 3.0;
+
+/// Each bundled replay, recorded on an 800 by 600 view, with its caption, in the order they're played.
+const PLAYLIST: &[(&str, &str)] = &[
+    (
+        include_str!("../attract/rope.replay"),
+        "A rope is a chain of nodes, each link pulled back to its length every step",
+    ),
+    (
+        include_str!("../attract/cloth.replay"),
+        "Cloth is the same links woven into a grid: the wind pushes it, the knife cuts it",
+    ),
+    (
+        include_str!("../attract/zipline.replay"),
+        "A heavy weight slides down a rope, dragging a dip along with it",
+    ),
+    (
+        include_str!("../attract/resonance.replay"),
+        "Shaken at just the right speed, a hanging chain swings wider and wider",
+    ),
+    (
+        include_str!("../attract/pendulum.replay"),
+        "A pendulum, timed against the textbook's answer",
+    ),
+];
+
+/// Where attract mode is in its playlist.
+#[derive(Clone, Debug)]
+pub struct Attract {
+    /// The replay playing, by index into the playlist.
+    current: usize,
+    /// When the current replay finished, in seconds since the demo started, if it has.
+    finished_at: Option<f64>,
+    /// The seed the demo was on before, to go back to afterward.
+    pub seed: u64,
+}
+
+impl Attract {
+    pub fn new(seed: u64) -> Self {
+        Self { current: 0, finished_at: None, seed }
+    }
+
+    pub fn caption(&self) -> &'static str {
+        PLAYLIST[self.current].1
+    }
+
+    pub fn replay(&self) -> Replay {
+        Replay::from_text(PLAYLIST[self.current].0).expect("the bundled replays are valid")
+    }
+
+    /// Takes whether the current replay is still playing at `now`, moving on
+    /// to the next once it's been over for `HOLD` seconds and returning
+    /// whether it did.
+    pub fn advance(&mut self, playing: bool, now: f64) -> bool {
+        if playing {
+            return false;
+        }
+        let finished_at = *self.finished_at.get_or_insert(now);
+        if now - finished_at < HOLD {
+            return false;
+        }
+        self.current = (self.current + 1) % PLAYLIST.len();
+        self.finished_at = None;
+        true
+    }
+}
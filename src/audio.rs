@@ -0,0 +1,203 @@
+use egui_macroquad::macroquad::audio::{
+    load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+};
+use egui_macroquad::macroquad::time::get_time;
+
+const SAMPLE_RATE: u32 = 22050;
+/// Minimum seconds between two plays of the same effect, so that a swipe
+/// cutting ten links at once doesn't stack ten copies of the sound.
+const RETRIGGER_DELAY: f64 = 0.06;
+/// Base pitches of the creak loop layers, crossfaded by strain since
+/// sounds can't be repitched while playing.
+const CREAK_PITCHES: [f32; 4] = [45.0, 70.0, 105.0, 150.0];
+/// Strain level below which the creak is silent.
+const CREAK_THRESHOLD: f32 = 0.2;
+
+#[derive(Copy, Clone, Debug)]
+pub enum SoundEffect {
+    Cut,
+    Break,
+    Thud,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 3] = [SoundEffect::Cut, SoundEffect::Break, SoundEffect::Thud];
+
+    fn synthesize(self) -> Vec<f32> {
+        let mut noise = Noise(0x2545_f491);
+        match self {
+            // short bright hiss
+            SoundEffect::Cut => samples(0.12, |t| noise.next() * (-t * 40.0).exp() * 0.6),
+            // noisy crack over a falling tone
+            SoundEffect::Break => samples(0.25, |t| {
+                let tone = (t * std::f32::consts::TAU * (220.0 - t * 400.0)).sin();
+                (noise.next() * 0.7 + tone * 0.3) * (-t * 18.0).exp()
+            }),
+            // low damped sine
+            SoundEffect::Thud => samples(0.2, |t| {
+                (t * std::f32::consts::TAU * 70.0).sin() * (-t * 25.0).exp()
+            }),
+        }
+    }
+}
+
+pub struct Audio {
+    pub volume: f32,
+    pub muted: bool,
+    pub creak_enabled: bool,
+    sounds: Vec<Sound>,
+    creak_layers: Vec<Sound>,
+    creak_playing: bool,
+    last_played: [f64; SoundEffect::ALL.len()],
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            muted: false,
+            creak_enabled: false,
+            sounds: Vec::new(),
+            creak_layers: Vec::new(),
+            creak_playing: false,
+            last_played: [f64::NEG_INFINITY; SoundEffect::ALL.len()],
+        }
+    }
+}
+
+impl Audio {
+    /// Synthesizes and uploads all sound effects. Until this is called, `play` is silent.
+    pub async fn load(&mut self) {
+        let mut sounds = Vec::new();
+        for effect in SoundEffect::ALL {
+            match load_sound_from_bytes(&wav(&effect.synthesize())).await {
+                Ok(sound) => sounds.push(sound),
+                Err(_) => return,
+            }
+        }
+        self.sounds = sounds;
+
+        let mut creak_layers = Vec::new();
+        for pitch in CREAK_PITCHES {
+            match load_sound_from_bytes(&wav(&creak(pitch))).await {
+                Ok(sound) => creak_layers.push(sound),
+                Err(_) => return,
+            }
+        }
+        self.creak_layers = creak_layers;
+    }
+
+    /// Drives the creak loop from a strain level in [0, 1], where 1 is about to break.
+    /// Louder and higher pitched as the level rises.
+    pub fn set_creak_level(&mut self, level: f32) {
+        if self.creak_layers.is_empty() {
+            return;
+        }
+
+        if !self.creak_enabled {
+            if self.creak_playing {
+                self.creak_layers.iter().for_each(|&layer| stop_sound(layer));
+                self.creak_playing = false;
+            }
+            return;
+        }
+
+        if !self.creak_playing {
+            for &layer in self.creak_layers.iter() {
+                play_sound(layer, PlaySoundParams { looped: true, volume: 0.0 });
+            }
+            self.creak_playing = true;
+        }
+
+        let level = ((level - CREAK_THRESHOLD) / (1.0 - CREAK_THRESHOLD)).clamp(0.0, 1.0);
+        let volume = if self.muted { 0.0 } else { self.volume * level.powi(2) };
+
+        // crossfade between the two layers adjacent to `level`
+        let pos = level * (self.creak_layers.len() - 1) as f32;
+        for (i, &layer) in self.creak_layers.iter().enumerate() {
+            let weight = (1.0 - (pos - i as f32).abs()).max(0.0);
+            set_sound_volume(layer, volume * weight);
+        }
+    }
+
+    /// Plays `effect` scaled by `gain` in [0, 1] on top of the master volume.
+    pub fn play(&mut self, effect: SoundEffect, gain: f32) {
+        let Some(&sound) = self.sounds.get(effect as usize) else {
+            return;
+        };
+        if self.muted {
+            return;
+        }
+
+        let now = get_time();
+        let last_played = &mut self.last_played[effect as usize];
+        if now - *last_played < RETRIGGER_DELAY {
+            return;
+        }
+        *last_played = now;
+
+        play_sound(
+            sound,
+            PlaySoundParams {
+                looped: false,
+                volume: self.volume * gain.clamp(0.0, 1.0),
+            },
+        );
+    }
+}
+
+/// One second of stick-slip creaking at roughly `pitch` Hz. Every component has
+/// an integer frequency so the buffer loops without a click.
+fn creak(pitch: f32) -> Vec<f32> {
+    let pitch = pitch.round();
+    samples(1.0, |t| {
+        let phase = (t * pitch).fract();
+        let saw = 1.0 - 2.0 * phase;
+        let grain = (t * std::f32::consts::TAU * 5.0).sin().abs();
+        let wobble = 0.6 + 0.4 * (t * std::f32::consts::TAU * 3.0).sin();
+        saw * grain * wobble * 0.5
+    })
+}
+
+fn samples(duration: f32, mut f: impl FnMut(f32) -> f32) -> Vec<f32> {
+    let n = (duration * SAMPLE_RATE as f32) as usize;
+    (0..n).map(|i| f(i as f32 / SAMPLE_RATE as f32)).collect()
+}
+
+/// Encodes samples in [-1, 1] as a mono 16-bit PCM WAV file.
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+
+    for sample in samples {
+        let s = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+
+    out
+}
+
+/// xorshift white noise in [-1, 1]
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
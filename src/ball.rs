@@ -0,0 +1,109 @@
+use crate::constraint::Constraint;
+use crate::friction::Friction;
+use crate::geometry;
+use crate::node::Node;
+
+/// How bouncy balls fired into the world are, as a fraction of their speed kept.
+pub const BALL_RESTITUTION: f32 = 0.7;
+
+/// A node with a size, which constraints can't pass through, like a cannonball.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ball {
+    pub node: usize,
+    pub radius: f32,
+    /// Speed into a constraint at which the ball snaps it instead of bouncing
+    /// off, infinite for balls that never do. Only the ball's own speed counts,
+    /// so a constraint swung into a ball at rest doesn't break.
+    pub smash_speed: f32,
+}
+
+impl Ball {
+    /// Whether the ball overlaps `constraint` while moving into it at `smash_speed` or faster.
+    pub fn smashes(&self, constraint: &Constraint, arena: &[Node]) -> bool {
+        if constraint.a == self.node || constraint.b == self.node {
+            return false;
+        }
+        let (ball, a, b) = (arena[self.node], arena[constraint.a], arena[constraint.b]);
+        let t = geometry::nearest_on_segment(ball.pos, a.pos, b.pos);
+        let offset = ball.pos - a.pos.lerp(b.pos, t);
+        if offset.length() >= self.radius {
+            return false;
+        }
+        -ball.vel.dot(offset.normalize_or_zero()) >= self.smash_speed
+    }
+
+    /// Pushes the ball out of `constraint` if they overlap, moving the constraint's
+    /// ends the other way. Each side gives way in proportion to its inverse mass,
+    /// with the constraint's share split between its ends by how near the contact is,
+    /// and `friction` against the rope sliding over the ball.
+    pub fn collide(&self, constraint: &Constraint, arena: &mut [Node], friction: &Friction) {
+        if constraint.a == self.node || constraint.b == self.node {
+            return;
+        }
+        let (ball, a, b) = (arena[self.node], arena[constraint.a], arena[constraint.b]);
+        let t = geometry::nearest_on_segment(ball.pos, a.pos, b.pos);
+        let offset = ball.pos - a.pos.lerp(b.pos, t);
+        let distance = offset.length();
+        if distance >= self.radius || distance == 0.0 {
+            return;
+        }
+
+        let (a_weight, b_weight) = ((1.0 - t) * a.inverse_mass(), t * b.inverse_mass());
+        let total = ball.inverse_mass() + (1.0 - t) * a_weight + t * b_weight;
+        if total == 0.0 {
+            return;
+        }
+        let (normal, depth) = (offset / distance, self.radius - distance);
+        let rope_moved = (a.pos - a.last_pos).lerp(b.pos - b.last_pos, t);
+        let slip = (ball.pos - ball.last_pos) - rope_moved;
+        let push = (normal * depth - friction.grip(slip, normal, depth)) / total;
+        arena[self.node].add_offs(push * ball.inverse_mass());
+        arena[constraint.a].add_offs(-push * a_weight);
+        arena[constraint.b].add_offs(-push * b_weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn pushes_ball_and_rope_apart_by_mass() {
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(0.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(10.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(5.0, 1.0), 2.0),
+        ];
+        let constraint = Constraint { a: 0, b: 1, rest_length: 10.0, break_threshold: 50.0 };
+        let ball = Ball { node: 2, radius: 4.0, smash_speed: f32::INFINITY };
+        ball.collide(&constraint, &mut arena, &Friction::default());
+
+        // the ball and the middle of the rope close the 3 unit overlap between them
+        let gap = arena[2].pos.y - (arena[0].pos.y + arena[1].pos.y) / 2.0;
+        assert!((gap - 4.0).abs() < 1e-5, "{}", gap);
+        // pushing the middle of a rope of two unit masses is like pushing a mass of 2,
+        // as heavy as the ball, so they split the distance evenly
+        assert!((arena[2].pos.y - 2.5).abs() < 1e-5);
+        assert!((arena[0].pos.y + 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn smashes_only_when_moving_into_the_constraint_fast_enough() {
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(0.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(10.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(5.0, -1.0), 2.0),
+        ];
+        let constraint = Constraint { a: 0, b: 1, rest_length: 10.0, break_threshold: 50.0 };
+        let ball = Ball { node: 2, radius: 4.0, smash_speed: 20.0 };
+
+        arena[2].vel = Vec2::new(0.0, 30.0);
+        assert!(ball.smashes(&constraint, &arena));
+        arena[2].vel = Vec2::new(0.0, 10.0);
+        assert!(!ball.smashes(&constraint, &arena));
+        // moving away as fast doesn't count
+        arena[2].vel = Vec2::new(0.0, -30.0);
+        assert!(!ball.smashes(&constraint, &arena));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::node::Node;
+use crate::wrap::Wrap;
+use glam::Vec2;
+
+/// An elastic link, like a rubber band: a spring pulling its ends together
+/// while it's stretched past its rest length and slack otherwise. Unlike a
+/// constraint it gives way smoothly, storing energy as it stretches. One that
+/// `pushes` is a spring both ways, pushing its ends apart when squeezed too.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Band {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    /// Force per unit of stretch.
+    pub stiffness: f32,
+    /// Whether it pushes back when squeezed shorter than its rest length.
+    pub pushes: bool,
+    /// Force per unit of speed its ends move apart or together at, while it's
+    /// pulling or pushing, which keeps it from ringing.
+    pub damping: f32,
+}
+
+impl Band {
+    /// The pull on `a` toward `b`; `b` is pulled the other way just as hard.
+    pub fn force(&self, arena: &[Node], wrap: &Wrap) -> Vec2 {
+        let offset = wrap.delta(arena[self.a].pos, arena[self.b].pos);
+        let stretch = offset.length() - self.rest_length;
+        if stretch <= 0.0 && !self.pushes {
+            return Vec2::ZERO;
+        }
+        let direction = offset.normalize_or_zero();
+        let separating = (arena[self.b].vel - arena[self.a].vel).dot(direction);
+        direction * (stretch * self.stiffness + separating * self.damping)
+    }
+
+    /// Energy stored in the band by stretching it, none while it's slack.
+    pub fn energy(&self, arena: &[Node], wrap: &Wrap) -> f32 {
+        let offset = wrap.delta(arena[self.a].pos, arena[self.b].pos);
+        let stretch = offset.length() - self.rest_length;
+        let stretch = if self.pushes { stretch } else { stretch.max(0.0) };
+        0.5 * self.stiffness * stretch * stretch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulls_only_while_stretched() {
+        let mut arena = vec![Node::default(), Node::with_pos_and_mass(Vec2::new(30.0, 0.0), 1.0)];
+        let band = Band { a: 0, b: 1, rest_length: 20.0, stiffness: 2.0, pushes: false, damping: 0.0 };
+        assert_eq!(band.force(&arena, &Wrap::default()), Vec2::new(20.0, 0.0));
+
+        arena[1].pos.x = 10.0;
+        assert_eq!(band.force(&arena, &Wrap::default()), Vec2::ZERO);
+        let spring = Band { pushes: true, ..band };
+        assert_eq!(spring.force(&arena, &Wrap::default()), Vec2::new(-20.0, 0.0));
+    }
+}
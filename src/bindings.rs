@@ -0,0 +1,92 @@
+//! Which mouse button does what, for trackpads where holding the right
+//! button to cut is awkward and for left-handed players. Kept in
+//! `settings.toml` with the rest of the settings window, and named in the
+//! hints along the bottom so they always say which button to press.
+
+use egui_macroquad::macroquad::prelude::{is_mouse_button_down, is_mouse_button_pressed, MouseButton};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+}
+
+impl Button {
+    pub const ALL: [Button; 3] = [Button::Left, Button::Right, Button::Middle];
+
+    /// Its name in hints, like "Left" in "Left Drag to Move".
+    pub fn name(self) -> &'static str {
+        match self {
+            Button::Left => "Left",
+            Button::Right => "Right",
+            Button::Middle => "Middle",
+        }
+    }
+
+    pub fn down(self) -> bool {
+        is_mouse_button_down(self.into())
+    }
+
+    pub fn pressed(self) -> bool {
+        is_mouse_button_pressed(self.into())
+    }
+}
+
+impl From<Button> for MouseButton {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Left => MouseButton::Left,
+            Button::Right => MouseButton::Right,
+            Button::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseBindings {
+    /// Held to cut while simulating.
+    pub cut: Button,
+    /// Held for the cursor tool to blow or push, or `None` for it to act whenever the cursor moves.
+    pub tool: Option<Button>,
+    /// The editor's tools: dragging nodes, dropping wells and sinks, aiming, building, and measuring.
+    pub grab: Button,
+    /// Pins and unpins nodes in the editor, and stops building.
+    pub pin: Button,
+    /// Dragged to pan the camera.
+    pub pan: Button,
+}
+
+impl MouseBindings {
+    pub const RIGHT_HANDED: MouseBindings =
+        MouseBindings { cut: Button::Right, tool: None, grab: Button::Left, pin: Button::Right, pan: Button::Middle };
+    /// The right-handed buttons mirrored, so the knife is on the index finger of the left hand.
+    pub const LEFT_HANDED: MouseBindings =
+        MouseBindings { cut: Button::Left, tool: None, grab: Button::Right, pin: Button::Left, pan: Button::Middle };
+
+    /// Whether the cursor tool's button, if it has one, is held.
+    pub fn tool_down(&self) -> bool {
+        self.tool.is_none_or(Button::down)
+    }
+
+    /// Two actions that share a button and so happen together, if any do.
+    pub fn clash(&self) -> Option<&'static str> {
+        if self.cut == self.pan {
+            Some("Cutting and panning share a button")
+        } else if self.tool == Some(self.cut) || self.tool == Some(self.pan) {
+            Some("The cursor tool shares a button with cutting or panning, which turn it off")
+        } else if self.grab == self.pin {
+            Some("Grabbing and pinning share a button")
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for MouseBindings {
+    fn default() -> Self {
+        Self::RIGHT_HANDED
+    }
+}
@@ -0,0 +1,255 @@
+//! A small game on the solver: span a gap with links on a budget, then roll
+//! a heavy load across and see whether the bridge holds.
+
+use crate::constraint::Constraint;
+use crate::friction::Friction;
+use crate::node::Node;
+use crate::scene::GROUND_OFFSET;
+use crate::world::World;
+use crate::TARGET_DIST;
+use glam::Vec2;
+
+/// Width of the gap to span.
+const GAP: f32 = 6.0 * TARGET_DIST;
+/// How far below the road each bank's lower anchor is.
+const ANCHOR_DROP: f32 = TARGET_DIST;
+/// Length of road on each bank, which the load starts and finishes on.
+const ROAD: f32 = 2.0 * TARGET_DIST;
+/// How far below the road the load has to drop to count as fallen.
+const FALL_DEPTH: f32 = 4.0 * TARGET_DIST;
+/// Simulated time the load gets to make it across.
+const TEST_TIME: f32 = 120.0;
+
+pub const BRIDGE_BUDGET: f32 = 1000.0;
+/// What each node costs.
+pub const NODE_COST: f32 = 20.0;
+/// What each link costs per unit of length.
+pub const LINK_COST: f32 = 0.5;
+/// Longest link that can be built, so spanning the gap takes more than one.
+pub const MAX_LINK_LENGTH: f32 = 2.0 * TARGET_DIST;
+/// Length at which built links break, in rest lengths: they're made of
+/// something less forgiving than rope.
+pub const BRIDGE_BREAK_THRESHOLD: f32 = 1.25;
+pub const LOAD_MASS: f32 = 30.0;
+pub const LOAD_RADIUS: f32 = 12.0;
+/// Speed the load drives across at, on the flat.
+pub const LOAD_SPEED: f32 = 15.0;
+/// Most the load's engine can push per unit of its mass, enough to climb a slope of about one in three.
+const LOAD_PUSH: f32 = 6.0;
+
+/// A gap between two banks to build across, and the rules of the game.
+/// The banks come free: every node and link in the level as it's built is
+/// part of them, and everything added after counts against the budget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bridge {
+    pub budget: f32,
+    /// Nodes and links that were there to start with.
+    pub free_nodes: usize,
+    pub free_links: usize,
+    /// Height of the road on either bank.
+    pub road: f32,
+    /// Where the load starts, on the left bank's road.
+    pub start: Vec2,
+    /// How far right the load has to get, on the right bank's road.
+    pub finish: f32,
+}
+
+/// How a test ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The load made it across.
+    Crossed,
+    /// The load fell into the gap.
+    Fell,
+    /// The load was still on the bridge when time ran out, stuck in a sag.
+    Stuck,
+}
+
+impl Outcome {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Outcome::Crossed => "The load made it across",
+            Outcome::Fell => "The load fell into the gap",
+            Outcome::Stuck => "The load got stuck on the bridge",
+        }
+    }
+}
+
+/// A load rolling over a bridge, watched for how it ends.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BridgeTest {
+    /// The load, by index into the arena.
+    pub load: usize,
+    /// Links when the test started, to count the ones that broke.
+    pub links: usize,
+    /// Simulated time since the load set off.
+    pub time: f32,
+    pub outcome: Option<Outcome>,
+}
+
+impl Bridge {
+    /// A gap across the middle of a `width` by `height` view, with a short
+    /// road and two fixed anchors, one on the road's edge and one below it,
+    /// on either bank.
+    pub fn level(width: f32, height: f32) -> (World, Self) {
+        let mut world = World::new(height - GROUND_OFFSET);
+        // the load slides rather than rolls, so friction would stop it dead
+        world.friction = Friction::NONE;
+        let road = height * 0.45;
+        let (left, right) = ((width - GAP) / 2.0, (width + GAP) / 2.0);
+        for (edge, outward) in [(left, -1.0), (right, 1.0)] {
+            let first = world.arena.len();
+            let anchors = [
+                Vec2::new(edge + outward * ROAD, road),
+                Vec2::new(edge, road),
+                Vec2::new(edge, road + ANCHOR_DROP),
+            ];
+            for pos in anchors {
+                world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pos, 1.0) });
+            }
+            let break_threshold = ROAD * world.params.break_threshold;
+            world.constraints.push(Constraint { a: first, b: first + 1, rest_length: ROAD, break_threshold });
+        }
+        let bridge = Self {
+            budget: BRIDGE_BUDGET,
+            free_nodes: world.arena.len(),
+            free_links: world.constraints.len(),
+            road,
+            start: Vec2::new(left - ROAD / 2.0, road - LOAD_RADIUS),
+            finish: right + ROAD / 2.0,
+        };
+        (world, bridge)
+    }
+
+    /// What everything built in `world` so far has cost.
+    pub fn cost(&self, world: &World) -> f32 {
+        let nodes = world.arena.len().saturating_sub(self.free_nodes) as f32 * NODE_COST;
+        let links = world.constraints.iter().skip(self.free_links).map(|link| link.rest_length * LINK_COST);
+        nodes + links.sum::<f32>()
+    }
+
+    /// Builds a link between nodes `a` and `b` at the length they are apart,
+    /// if it isn't too long and there's the budget for it and the `new_nodes`
+    /// it needs, returning whether it did.
+    pub fn build(&self, world: &mut World, a: usize, b: usize, new_nodes: usize) -> bool {
+        let rest_length = world.arena[a].pos.distance(world.arena[b].pos);
+        let cost = new_nodes as f32 * NODE_COST + rest_length * LINK_COST;
+        if a == b || rest_length > MAX_LINK_LENGTH || self.cost(world) + cost > self.budget {
+            return false;
+        }
+        let break_threshold = rest_length * BRIDGE_BREAK_THRESHOLD;
+        world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+        true
+    }
+
+    /// Sets the load off across the bridge, already up to speed.
+    pub fn launch(&self, world: &mut World) -> BridgeTest {
+        let load = world.add_ball(self.start, Vec2::new(LOAD_SPEED, 0.0), LOAD_MASS, LOAD_RADIUS);
+        world.arena[load].restitution = 0.0;
+        BridgeTest { load, links: world.constraints.len(), time: 0.0, outcome: None }
+    }
+
+    /// Points for a test that ended with `outcome`: 1000 for getting the load
+    /// across, plus up to 1000 more for how much of the budget was left, less
+    /// 100 for each link that broke on the way. Nothing if it didn't make it.
+    pub fn score(&self, outcome: Outcome, cost: f32, broken: usize) -> u32 {
+        if outcome != Outcome::Crossed {
+            return 0;
+        }
+        let saved = 1000.0 * (1.0 - cost / self.budget).max(0.0);
+        (1000.0 + saved - 100.0 * broken as f32).max(0.0).round() as u32
+    }
+}
+
+impl BridgeTest {
+    /// Pushes the load on towards `LOAD_SPEED`, like a truck's engine. It
+    /// never brakes, so it runs on down slopes. Call before each step.
+    pub fn drive(&self, world: &mut World) {
+        let load = &mut world.arena[self.load];
+        let push = ((LOAD_SPEED - load.vel.x) * load.mass).clamp(0.0, LOAD_PUSH * load.mass);
+        load.force.x += push + world.params.drag * load.vel.x.max(0.0);
+    }
+
+    /// Takes the world after a step of `dt`, returning how the test ended once it has.
+    pub fn record(&mut self, bridge: &Bridge, world: &World, dt: f32) -> Option<Outcome> {
+        if self.outcome.is_some() {
+            return self.outcome;
+        }
+        self.time += dt;
+        let load = world.arena[self.load].pos;
+        self.outcome = if load.x >= bridge.finish {
+            Some(Outcome::Crossed)
+        } else if load.y > bridge.road + FALL_DEPTH {
+            Some(Outcome::Fell)
+        } else if self.time >= TEST_TIME {
+            Some(Outcome::Stuck)
+        } else {
+            None
+        };
+        self.outcome
+    }
+
+    /// Links that have broken since the test started.
+    pub fn broken(&self, world: &World) -> usize {
+        self.links.saturating_sub(world.constraints.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a test on `world` to the end.
+    fn run(bridge: &Bridge, world: &mut World) -> (Outcome, BridgeTest) {
+        let mut test = bridge.launch(world);
+        loop {
+            let dt = world.params.dt;
+            test.drive(world);
+            world.step(dt);
+            if let Some(outcome) = test.record(bridge, world, dt) {
+                return (outcome, test);
+            }
+        }
+    }
+
+    #[test]
+    fn a_truss_carries_the_load_and_nothing_drops_it() {
+        let (empty, bridge) = Bridge::level(1280.0, 720.0);
+        assert_eq!(run(&bridge, &mut empty.clone()).0, Outcome::Fell);
+
+        // a deck across the top, a chord along the bottom, and diagonals between them
+        let mut world = empty;
+        let (deck_left, deck_right, low_left, low_right) = (1, 4, 2, 5);
+        let (left, right) = (world.arena[deck_left].pos, world.arena[deck_right].pos);
+        let panels = 4;
+        let mut deck = vec![deck_left];
+        let mut low = vec![low_left];
+        for i in 1..panels {
+            let pos = left.lerp(right, i as f32 / panels as f32);
+            world.arena.push(Node::with_pos_and_mass(pos, 1.0));
+            deck.push(world.arena.len() - 1);
+            world.arena.push(Node::with_pos_and_mass(pos + Vec2::new(0.0, ANCHOR_DROP), 1.0));
+            low.push(world.arena.len() - 1);
+        }
+        deck.push(deck_right);
+        low.push(low_right);
+        for i in 0..panels {
+            assert!(bridge.build(&mut world, deck[i], deck[i + 1], 0));
+            assert!(bridge.build(&mut world, low[i], low[i + 1], 0));
+            let (a, b) = if i < panels / 2 { (low[i], deck[i + 1]) } else { (deck[i], low[i + 1]) };
+            assert!(bridge.build(&mut world, a, b, 0));
+        }
+        for i in 1..panels {
+            assert!(bridge.build(&mut world, deck[i], low[i], 0));
+        }
+        let cost = bridge.cost(&world);
+        assert!(cost <= bridge.budget, "{}", cost);
+        // nothing longer than a link can be
+        assert!(!bridge.build(&mut world, deck_left, deck_right, 0));
+
+        let (outcome, test) = run(&bridge, &mut world);
+        assert_eq!(outcome, Outcome::Crossed, "{:?}", test);
+        let score = bridge.score(outcome, cost, test.broken(&world));
+        assert!(score > 1000, "{}", score);
+    }
+}
@@ -0,0 +1,114 @@
+//! A cap on how many nodes a world holds. Left running, a cannon, a sand
+//! spout, or a dispenser adds nodes without end, and every one costs the
+//! solver a little more each step until the frame rate sinks. With a budget,
+//! `World::spawn` adds nodes only while there's room, and once there isn't
+//! either reuses the oldest free node for the new one or refuses to add it,
+//! so the spawner waits.
+
+use crate::world::World;
+
+/// Most nodes, by default.
+pub const MAX_NODES: usize = 5000;
+
+/// What `World::spawn` does at the cap.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AtCap {
+    /// Moves the oldest free node to where the new one goes and makes it that.
+    #[default]
+    Recycle,
+    /// Adds nothing, so cannons don't fire, sand stops pouring, and
+    /// dispensers stop feeding until there's room again.
+    Refuse,
+}
+
+impl AtCap {
+    pub const ALL: [AtCap; 2] = [AtCap::Recycle, AtCap::Refuse];
+
+    /// The word for it in a scene file.
+    pub fn name(self) -> &'static str {
+        match self {
+            AtCap::Recycle => "recycle",
+            AtCap::Refuse => "refuse",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|at_cap| at_cap.name() == name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NodeBudget {
+    pub max_nodes: usize,
+    pub at_cap: AtCap,
+}
+
+impl Default for NodeBudget {
+    fn default() -> Self {
+        Self { max_nodes: MAX_NODES, at_cap: AtCap::Recycle }
+    }
+}
+
+impl NodeBudget {
+    /// The oldest node in `world` that can be reused: the first that isn't
+    /// fixed, has no link or band on it, and isn't on an anchor, rail, or
+    /// slider. Loose scraps, balls, and sand grains qualify, so the first
+    /// shots fired and grains poured are the first taken back.
+    pub fn recyclable(world: &World) -> Option<usize> {
+        let mut held = vec![false; world.arena.len()];
+        let links = world.constraints.iter().flat_map(|constraint| [constraint.a, constraint.b]);
+        let bands = world.bands.iter().flat_map(|band| [band.a, band.b]);
+        let anchors = world.anchors.iter().map(|anchor| anchor.node);
+        let rails = world.rails.iter().map(|rail| rail.node).chain(world.sliders.iter().map(|slider| slider.node));
+        for node in links.chain(bands).chain(anchors).chain(rails) {
+            held[node] = true;
+        }
+        (0..world.arena.len()).find(|&i| !held[i] && !world.arena[i].fixed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::SimEvent;
+    use crate::node::Node;
+    use crate::sand::Sand;
+    use glam::Vec2;
+
+    #[test]
+    fn spawns_past_the_cap_recycle_the_oldest_free_node_or_are_refused() {
+        let mut world = World::new(500.0);
+        world.add_rope(Vec2::ZERO, 3);
+        world.budget = Some(NodeBudget { max_nodes: 5, at_cap: AtCap::Recycle });
+        let first = world.spawn_ball(Vec2::new(100.0, 0.0), Vec2::ZERO, 1.0, 5.0);
+        let second = world.spawn_ball(Vec2::new(200.0, 0.0), Vec2::ZERO, 1.0, 5.0);
+        assert_eq!((first, second), (Some(3), Some(4)));
+
+        // the rope is all held, so the first ball is the one fired again
+        world.drain_events();
+        let third = world.spawn_ball(Vec2::new(300.0, 0.0), Vec2::X, 1.0, 5.0);
+        assert_eq!(third, Some(3));
+        assert_eq!(world.arena.len(), 5);
+        assert_eq!((world.arena[3].pos, world.arena[3].vel), (Vec2::new(300.0, 0.0), Vec2::X));
+        assert_eq!(world.balls.iter().filter(|ball| ball.node == 3).count(), 1);
+        let events = world.drain_events();
+        assert!(matches!(events[..], [SimEvent::NodeRecycled { node: 3, pos }] if pos == Vec2::new(100.0, 0.0)));
+
+        // refusing leaves things as they are, and sand waits to pour
+        world.budget = Some(NodeBudget { max_nodes: 5, at_cap: AtCap::Refuse });
+        assert_eq!(world.spawn(Node::with_pos_and_mass(Vec2::ZERO, 1.0)), None);
+        world.sand = Some(Sand { pouring: 3, ..Sand::new(Vec2::new(400.0, 0.0)) });
+        let dt = world.params.dt;
+        for _ in 0..100 {
+            world.step(dt);
+        }
+        assert_eq!(world.arena.len(), 5);
+        assert_eq!(world.sand.as_ref().unwrap().pouring, 3);
+
+        world.budget = None;
+        for _ in 0..100 {
+            world.step(dt);
+        }
+        assert_eq!(world.sand.as_ref().unwrap().grains.len(), 3);
+    }
+}
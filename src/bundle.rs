@@ -0,0 +1,205 @@
+//! Bundles: one file with everything it takes to show someone else the same
+//! setup, namely the world as a save has it, the colors, and where the camera
+//! is looking. The text starts with a `clothsim-bundle 1` header, then
+//! `camera <x> <y> <zoom>`, `palette` followed by each color as `r,g,b,a` in
+//! the settings file's order, and finally a `save` line followed by the world
+//! as `save::to_text` writes it.
+//!
+//! To go in a link, the text is encoded as URL-safe base64 and put after the
+//! `#` of the demo's page, which the browser build reads on starting.
+
+use crate::camera::Camera;
+use crate::palette::Palette;
+use clothsim::save;
+use clothsim::world::World;
+use egui_macroquad::macroquad::prelude::{Color, Vec2};
+use std::fmt::Write as _;
+
+const HEADER: &str = "clothsim-bundle 1";
+/// Where the browser build is hosted, for links to it.
+const PAGE: &str = "https://cloth.mikail-khan.com/";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A setup read back from a bundle.
+pub struct Bundle {
+    pub world: World,
+    pub palette: Palette,
+    pub camera_pos: Vec2,
+    pub zoom: f32,
+}
+
+/// Writes `world` as `palette` colors it and `camera` sees it.
+pub fn to_text(world: &World, palette: &Palette, camera: &Camera) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER).unwrap();
+    writeln!(out, "camera {} {} {}", camera.pos.x, camera.pos.y, camera.zoom).unwrap();
+    out += "palette";
+    for color in colors(palette) {
+        write!(out, " {},{},{},{}", color.r, color.g, color.b, color.a).unwrap();
+    }
+    out += "\nsave\n";
+    out += &save::to_text(world, 0);
+    out
+}
+
+/// Reads a bundle's text back.
+pub fn from_text(text: &str) -> Result<Bundle, String> {
+    if text.lines().next() != Some(HEADER) {
+        return Err(format!("missing \"{}\" header", HEADER));
+    }
+    let (mut camera_pos, mut zoom, mut palette) = (Vec2::ZERO, 1.0, Palette::default());
+    let mut lines = text.lines().enumerate().skip(1);
+    for (i, line) in lines.by_ref() {
+        let err = |what: &str| format!("line {}: {}", i + 1, what);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let num = |i: usize| -> Result<f32, String> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a number"))
+        };
+        match words.as_slice() {
+            [] => {}
+            ["camera", ..] => {
+                camera_pos = Vec2::new(num(1)?, num(2)?);
+                zoom = num(3)?;
+                if !(zoom.is_finite() && zoom > 0.0) {
+                    return Err(err("expected a positive zoom"));
+                }
+            }
+            ["palette", parts @ ..] => {
+                let mut colors = parts.iter().map(|word| parse_color(word).ok_or_else(|| err("expected r,g,b,a")));
+                for color in colors_mut(&mut palette) {
+                    *color = colors.next().ok_or_else(|| err("expected a color for each part"))??;
+                }
+            }
+            ["save"] => break,
+            _ => return Err(err("expected camera, palette, or save")),
+        }
+    }
+
+    let start = lines.clone().next().map_or(0, |(i, _)| i + 1);
+    let saved: Vec<&str> = lines.map(|(_, line)| line).collect();
+    let (world, _) = save::from_text(&saved.join("\n")).map_err(|e| format!("in the save from line {}: {}", start, e))?;
+    Ok(Bundle { world, palette, camera_pos, zoom })
+}
+
+/// A link to the browser build that opens on `text`.
+pub fn link(text: &str) -> String {
+    format!("{}#{}", PAGE, encode(text.as_bytes()))
+}
+
+/// Reads a bundle from a link to one, its encoded text, the text itself, or,
+/// outside the browser, the path of a bundle file.
+pub fn read(input: &str) -> Result<Bundle, String> {
+    let input = input.trim();
+    if input.starts_with(HEADER) {
+        return from_text(input);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if std::path::Path::new(input).is_file() {
+        return std::fs::read_to_string(input).map_err(|e| e.to_string()).and_then(|text| from_text(&text));
+    }
+    let encoded = input.rsplit('#').next().unwrap_or(input);
+    let text = String::from_utf8(decode(encoded)?).map_err(|_| "not a bundle's text".to_string())?;
+    from_text(&text)
+}
+
+/// What comes after the `#` of the page the browser build is running on, if anything.
+#[cfg(target_arch = "wasm32")]
+pub fn from_page() -> Option<String> {
+    // added by the plugin in docs/index.html
+    extern "C" {
+        fn clothsim_page_hash_len() -> u32;
+        fn clothsim_page_hash(buf: *mut u8, len: u32);
+    }
+    let len = unsafe { clothsim_page_hash_len() };
+    let mut buf = vec![0; len as usize];
+    unsafe { clothsim_page_hash(buf.as_mut_ptr(), len) };
+    String::from_utf8(buf).ok().filter(|hash| !hash.is_empty())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn from_page() -> Option<String> {
+    None
+}
+
+fn colors(p: &Palette) -> [Color; 11] {
+    let [h0, h1, h2, h3] = p.heatmap;
+    [p.background, p.rope, p.node, p.fixed_node, p.ground, p.text, p.accent, h0, h1, h2, h3]
+}
+
+fn colors_mut(p: &mut Palette) -> impl Iterator<Item = &mut Color> {
+    let named = [
+        &mut p.background,
+        &mut p.rope,
+        &mut p.node,
+        &mut p.fixed_node,
+        &mut p.ground,
+        &mut p.text,
+        &mut p.accent,
+    ];
+    named.into_iter().chain(p.heatmap.iter_mut())
+}
+
+fn parse_color(word: &str) -> Option<Color> {
+    let channels: Vec<f32> = word.split(',').map(|c| c.parse().ok()).collect::<Option<_>>()?;
+    match channels.as_slice() {
+        &[r, g, b, a] => Some(Color::new(r, g, b, a)),
+        _ => None,
+    }
+}
+
+/// URL-safe base64, without padding.
+fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &b)| bits | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(BASE64[(bits >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Undoes `encode`, also taking standard base64 and padding.
+fn decode(text: &str) -> Result<Vec<u8>, String> {
+    let sextets = text
+        .trim_end_matches('=')
+        .bytes()
+        .map(|c| match c {
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => BASE64.iter().position(|&d| d == c).map(|i| i as u32),
+        })
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(|| "not a bundle's link or code".to_string())?;
+    let mut out = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("the code is cut short".to_string());
+        }
+        let bits = chunk.iter().enumerate().fold(0, |bits, (i, &s)| bits | s << (18 - 6 * i));
+        out.extend(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trips() {
+        for text in ["", "a", "ab", "abc", "abcd", "clothsim-bundle 1\n\u{ff}?>"] {
+            assert_eq!(decode(&encode(text.as_bytes())).unwrap(), text.as_bytes());
+        }
+        assert_eq!(encode(b"a"), "YQ");
+        assert_eq!(encode(b"ab"), "YWI");
+        assert_eq!(encode(b"abc"), "YWJj");
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+        // standard base64, padded, reads the same
+        assert_eq!(decode("YQ==").unwrap(), b"a");
+        assert_eq!(decode("YWI=").unwrap(), b"ab");
+        assert_eq!(decode("+/8=").unwrap(), [0xfb, 0xff]);
+        assert!(decode("YWJjZ").is_err());
+        assert!(decode("YW J").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use clothsim::Rng;
+use egui_macroquad::macroquad::prelude::*;
+
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.0;
+const ZOOM_STEP: f32 = 1.1;
+const MAX_SHAKE_OFFSET: f32 = 20.0;
+const SHAKE_DECAY: f32 = 1.5;
+
+/// A 2D camera looking at `pos` in world space, with `zoom` screen pixels per world unit.
+pub struct Camera {
+    pub pos: Vec2,
+    pub zoom: f32,
+    pub shake_enabled: bool,
+    /// Shows the same region twice, in the left and right halves of the screen.
+    pub split: bool,
+    /// The part of the screen it draws into, if not all of it or half when `split`.
+    pub area: Option<Rect>,
+    /// In [0, 1], decays over time; the shake offset grows with its square.
+    trauma: f32,
+    shake_offset: Vec2,
+    shake_rng: Rng,
+    last_mouse_pos: Vec2,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            pos: Vec2::new(screen_width() / 2.0, screen_height() / 2.0),
+            zoom: 1.0,
+            shake_enabled: true,
+            split: false,
+            area: None,
+            trauma: 0.0,
+            shake_offset: Vec2::ZERO,
+            shake_rng: Rng::default(),
+            last_mouse_pos: mouse_position().into(),
+        }
+    }
+}
+
+impl Camera {
+    /// Pans with the mouse while `panning` and, if `scroll_zooms`, zooms around the cursor with the scroll wheel.
+    pub fn update(&mut self, panning: bool, scroll_zooms: bool) {
+        let mouse_pos: Vec2 = mouse_position().into();
+
+        if panning {
+            self.pos -= (mouse_pos - self.last_mouse_pos) / self.zoom;
+        }
+
+        let (_, scroll) = mouse_wheel();
+        if scroll_zooms && scroll != 0.0 {
+            let anchor = self.screen_to_world(mouse_pos);
+            let factor = if scroll > 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+            self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+            // keep the world point under the cursor fixed while zooming
+            self.pos += anchor - self.screen_to_world(mouse_pos);
+        }
+
+        self.last_mouse_pos = mouse_pos;
+
+        self.trauma = (self.trauma - SHAKE_DECAY * get_frame_time()).max(0.0);
+        self.shake_offset = if self.shake_enabled && self.trauma > 0.0 {
+            let dir = Vec2::new(self.shake_rng.range(-1.0, 1.0), self.shake_rng.range(-1.0, 1.0));
+            dir * self.trauma.powi(2) * MAX_SHAKE_OFFSET / self.zoom
+        } else {
+            Vec2::ZERO
+        };
+    }
+
+    /// Zooms by how much farther apart two fingers at `from` on screen are
+    /// at `to`, and pans so the world point between them stays between them.
+    pub fn pinch(&mut self, from: [Vec2; 2], to: [Vec2; 2]) {
+        let (before, after) = (from[0].distance(from[1]), to[0].distance(to[1]));
+        let anchor = self.screen_to_world((from[0] + from[1]) / 2.0);
+        if before > 0.0 && after > 0.0 {
+            self.zoom = (self.zoom * after / before).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+        self.pos += anchor - self.screen_to_world((to[0] + to[1]) / 2.0);
+    }
+
+    /// Moves `blend` of the way toward looking at `target` at `zoom`.
+    pub fn approach(&mut self, target: Vec2, zoom: f32, blend: f32) {
+        self.pos += (target - self.pos) * blend;
+        self.zoom += (zoom.clamp(MIN_ZOOM, MAX_ZOOM) - self.zoom) * blend;
+    }
+
+    pub fn add_shake(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// The camera for its area, or else the whole screen, or its left half when split.
+    pub fn camera2d(&self) -> Camera2D {
+        self.view_camera2d(0)
+    }
+
+    /// The camera for the left (0) or right (1) half of a split screen.
+    pub fn view_camera2d(&self, view: usize) -> Camera2D {
+        let size = self.view_size();
+        let viewport = match self.area {
+            // counted from the bottom of the screen
+            Some(area) => Some((area.x as i32, (screen_height() - area.bottom()) as i32, area.w as i32, area.h as i32)),
+            None => self.split.then_some(((size.x * view as f32) as i32, 0, size.x as i32, size.y as i32)),
+        };
+        Camera2D {
+            target: self.pos + self.shake_offset,
+            zoom: Vec2::new(2.0 * self.zoom / size.x, -2.0 * self.zoom / size.y),
+            viewport,
+            ..Camera2D::default()
+        }
+    }
+
+    /// Size of each view on screen.
+    fn view_size(&self) -> Vec2 {
+        if let Some(area) = self.area {
+            return area.size();
+        }
+        let divisions = if self.split { 2.0 } else { 1.0 };
+        Vec2::new(screen_width() / divisions, screen_height())
+    }
+
+    /// Top left corner of the view `point` is in.
+    fn view_corner(&self, point: Vec2) -> Vec2 {
+        match self.area {
+            Some(area) => area.point(),
+            None if point.x >= self.view_size().x => Vec2::new(self.view_size().x, 0.0),
+            None => Vec2::ZERO,
+        }
+    }
+
+    /// Maps `point` through whichever view it's in.
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        self.pos + (point - self.view_corner(point) - self.view_size() / 2.0) / self.zoom
+    }
+
+    /// Maps `point` into the whole screen, or the left view when split.
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        self.view_corner(Vec2::ZERO) + self.view_size() / 2.0 + (point - self.pos) * self.zoom
+    }
+
+    pub fn mouse_world_pos(&self) -> Vec2 {
+        self.screen_to_world(mouse_position().into())
+    }
+
+    /// The part of the world currently on screen, in each view when split.
+    pub fn visible_rect(&self) -> Rect {
+        let size = self.view_size() / self.zoom;
+        let top_left = self.pos - size / 2.0;
+        Rect::new(top_left.x, top_left.y, size.x, size.y)
+    }
+}
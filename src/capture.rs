@@ -0,0 +1,63 @@
+//! Saving the screen to numbered PNGs, for recording demos without a screen recorder.
+
+use crate::error::SimError;
+use egui_macroquad::macroquad::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Saves every `every`th frame to `dir/frame_00000.png` and onward until its time is up.
+pub struct FrameCapture {
+    pub dir: PathBuf,
+    every: u32,
+    /// `get_time` at which capturing stops.
+    until: f64,
+    frame: u32,
+    pub saved: u32,
+}
+
+impl FrameCapture {
+    pub fn start(dir: PathBuf, every: u32, seconds: f32) -> Result<Self, SimError> {
+        std::fs::create_dir_all(&dir).map_err(|source| SimError::Io {
+            action: "create",
+            path: dir.display().to_string(),
+            source,
+        })?;
+
+        Ok(Self {
+            dir,
+            every: every.max(1),
+            until: get_time() + seconds as f64,
+            frame: 0,
+            saved: 0,
+        })
+    }
+
+    /// Saves what's been drawn so far if this frame is one to keep, returning
+    /// false instead once the capture's time is up.
+    pub fn capture(&mut self) -> Result<bool, SimError> {
+        if get_time() >= self.until {
+            return Ok(false);
+        }
+
+        if self.frame.is_multiple_of(self.every) {
+            let path = self.dir.join(format!("frame_{:05}.png", self.saved));
+            save_png(&path, &get_screen_data()).map_err(|source| SimError::Io {
+                action: "save",
+                path: path.display().to_string(),
+                source,
+            })?;
+            self.saved += 1;
+        }
+        self.frame += 1;
+        Ok(true)
+    }
+}
+
+/// Like `Image::export_png`, which flips the bottom-up screen rows the same way,
+/// but returning errors rather than panicking.
+fn save_png(path: &Path, image: &Image) -> io::Result<()> {
+    let row = image.width as usize * 4;
+    let flipped: Vec<u8> = image.bytes.chunks_exact(row).rev().flatten().copied().collect();
+    image::save_buffer(path, &flipped, image.width as u32, image.height as u32, image::ColorType::Rgba8)
+        .map_err(io::Error::other)
+}
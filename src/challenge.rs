@@ -0,0 +1,135 @@
+//! Cut challenges: every structure hanging in a scene at the start, timed
+//! until the knife has cut them all down, with a count of the cuts it took.
+
+use crate::event::SimEvent;
+use crate::world::World;
+
+/// How a cut challenge on a scene is going.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Challenge {
+    /// Each structure's free nodes, by index into the arena: every group of
+    /// nodes linked together with some pinned and some free at the start.
+    structures: Vec<Vec<usize>>,
+    /// Knife cuts so far, counting each link cut or node split.
+    pub cuts: usize,
+    /// Structures with no free node still linked to a pinned one.
+    pub severed: usize,
+    /// Simulated time since the challenge started, stopping once it's finished.
+    pub time: f32,
+}
+
+impl Challenge {
+    pub fn new(world: &World) -> Self {
+        // label each node with the lowest node it's linked to, through any number of links
+        let mut group: Vec<usize> = (0..world.arena.len()).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in &world.constraints {
+                let lowest = group[constraint.a].min(group[constraint.b]);
+                for i in [constraint.a, constraint.b] {
+                    if group[i] != lowest {
+                        group[i] = lowest;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        let mut structures: Vec<Vec<usize>> = Vec::new();
+        let mut pinned = vec![false; world.arena.len()];
+        let mut members = vec![Vec::new(); world.arena.len()];
+        for (i, node) in world.arena.iter().enumerate() {
+            pinned[group[i]] |= node.fixed;
+            if !node.fixed {
+                members[group[i]].push(i);
+            }
+        }
+        for (root, free) in members.into_iter().enumerate() {
+            if pinned[root] && !free.is_empty() {
+                structures.push(free);
+            }
+        }
+        Self { structures, cuts: 0, severed: 0, time: 0.0 }
+    }
+
+    /// Structures there are to cut down.
+    pub fn structures(&self) -> usize {
+        self.structures.len()
+    }
+
+    /// Every structure's cut down, or there were none to begin with.
+    pub fn finished(&self) -> bool {
+        self.severed == self.structures.len()
+    }
+
+    /// Takes the world after a step of `dt`, with the step's events, and
+    /// returns whether that step finished the challenge.
+    pub fn record(&mut self, world: &World, events: &[SimEvent], dt: f32) -> bool {
+        if self.finished() {
+            return false;
+        }
+        self.time += dt;
+        self.cuts += events
+            .iter()
+            .filter(|event| matches!(event, SimEvent::ConstraintCut(_) | SimEvent::NodeSplit { .. }))
+            .count();
+
+        // spread out from the pins along every link left
+        let mut held: Vec<bool> = world.arena.iter().map(|node| node.fixed).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in &world.constraints {
+                if held[constraint.a] != held[constraint.b] {
+                    held[constraint.a] = true;
+                    held[constraint.b] = true;
+                    changed = true;
+                }
+            }
+        }
+        // a sink may have swallowed nodes, which are as good as cut down
+        let still_held = |free: &&Vec<usize>| free.iter().any(|&i| held.get(i).copied().unwrap_or(false));
+        self.severed = self.structures.len() - self.structures.iter().filter(still_held).count();
+        self.finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+    use glam::Vec2;
+
+    #[test]
+    fn finishes_once_every_structure_is_cut_down() {
+        // two ropes side by side, and a loose node that isn't anything to cut
+        let mut world = scene::default_rope(800.0, 600.0);
+        world.add_rope(Vec2::new(600.0, 100.0), 5);
+        world.add_ball(Vec2::new(400.0, 300.0), Vec2::ZERO, 1.0, 10.0);
+        let mut challenge = Challenge::new(&world);
+        assert_eq!(challenge.structures(), 2);
+        let dt = world.params.dt;
+
+        let cut_rope = |world: &mut World, challenge: &mut Challenge, top: usize| {
+            let between = world.arena[top].pos.lerp(world.arena[top + 1].pos, 0.5);
+            world.cut(between - Vec2::new(10.0, 0.0), between + Vec2::new(10.0, 0.0));
+            let events = world.drain_events();
+            challenge.record(world, &events, dt)
+        };
+        // cutting partway down the first rope leaves the rest of it hanging
+        let second = world.arena.iter().rposition(|node| node.fixed).unwrap();
+        assert!(!cut_rope(&mut world, &mut challenge, 2));
+        assert_eq!((challenge.cuts, challenge.severed), (1, 0));
+        assert!(!cut_rope(&mut world, &mut challenge, 0));
+        assert_eq!((challenge.cuts, challenge.severed), (2, 1));
+        assert!(cut_rope(&mut world, &mut challenge, second));
+        assert_eq!((challenge.cuts, challenge.severed), (3, 2));
+
+        // the clock stops once it's done
+        let time = challenge.time;
+        world.step(dt);
+        assert!(!challenge.record(&world, &[], dt));
+        assert_eq!(challenge.time, time);
+        assert!((time - 3.0 * dt).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,97 @@
+//! Electrostatics: nodes carrying a `charge` push each other away if their
+//! charges are alike and pull together if they're opposite, by Coulomb's law,
+//! so a charged cloth billows out from itself and a charged weight drags
+//! oppositely charged ropes toward it. Only pairs within `reach` of each other
+//! are counted, found with a spatial hash, so it stays quick in big scenes.
+
+use crate::node::Node;
+use crate::spatial_hash::SpatialHash;
+use crate::wrap::Wrap;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+/// How hard two nodes of unit charge push a unit of distance apart, which
+/// makes two a rest length apart push about as hard as gravity pulls on one.
+pub const COULOMB_STRENGTH: f32 = 50_000.0;
+/// Distance past which charges stop feeling each other, three rest lengths.
+pub const CHARGE_REACH: f32 = 150.0;
+
+/// Charged nodes pushing and pulling on each other.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Electrostatics {
+    /// The force between two unit charges a unit of distance apart.
+    pub strength: f32,
+    /// Distance past which charges stop feeling each other.
+    pub reach: f32,
+}
+
+impl Default for Electrostatics {
+    fn default() -> Self {
+        Self { strength: COULOMB_STRENGTH, reach: CHARGE_REACH }
+    }
+}
+
+impl Electrostatics {
+    /// The force on each node from every other charged one within reach,
+    /// softened at close range so overlapping nodes don't fling each other off.
+    pub fn forces(&self, arena: &[Node], wrap: &Wrap) -> Vec<Vec2> {
+        let mut forces = vec![Vec2::ZERO; arena.len()];
+        let charged: Vec<usize> = (0..arena.len()).filter(|&i| arena[i].charge != 0.0).collect();
+        let hash = SpatialHash::new(self.reach, charged.iter().map(|&i| arena[i].pos));
+        for (k, &i) in charged.iter().enumerate() {
+            // each pair once, from its first node
+            for j in hash.near(arena[i].pos, self.reach).filter(|&l| l > k).map(|l| charged[l]) {
+                let offset = wrap.delta(arena[i].pos, arena[j].pos);
+                let distance_squared = offset.length_squared();
+                if distance_squared > self.reach * self.reach {
+                    continue;
+                }
+                let softened = distance_squared + NODE_RADIUS * NODE_RADIUS;
+                let push = offset.normalize_or_zero() * self.strength * arena[i].charge * arena[j].charge / softened;
+                forces[i] -= push;
+                forces[j] += push;
+            }
+        }
+        forces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn like_charges_repel_and_opposite_ones_attract() {
+        let pair = |a: f32, b: f32, gap: f32| {
+            let mut world = World::new(10_000.0);
+            world.params.gravity = 0.0;
+            world.electrostatics = Some(Electrostatics::default());
+            for (x, charge) in [(100.0, a), (100.0 + gap, b)] {
+                world.arena.push(Node { charge, ..Node::with_pos_and_mass(Vec2::new(x, 100.0), 1.0) });
+            }
+            let dt = world.params.dt;
+            for _ in 0..10 {
+                world.step(dt);
+            }
+            world.arena[1].pos.x - world.arena[0].pos.x
+        };
+
+        assert!(pair(1.0, 1.0, 50.0) > 50.0);
+        assert!(pair(-1.0, -1.0, 50.0) > 50.0);
+        assert!(pair(1.0, -1.0, 50.0) < 50.0);
+        // uncharged, or out of reach, nothing happens
+        assert_eq!(pair(1.0, 0.0, 50.0), 50.0);
+        assert_eq!(pair(1.0, 1.0, CHARGE_REACH + 10.0), CHARGE_REACH + 10.0);
+
+        // the pull's equal and opposite
+        let mut arena = vec![Node::with_pos_and_mass(Vec2::ZERO, 1.0); 3];
+        arena[1].pos = Vec2::new(30.0, 40.0);
+        arena[0].charge = 2.0;
+        arena[1].charge = -1.0;
+        let forces = Electrostatics::default().forces(&arena, &Wrap::default());
+        assert_eq!(forces[0], -forces[1]);
+        assert!(forces[0].dot(arena[1].pos) > 0.0);
+        assert_eq!(forces[2], Vec2::ZERO);
+    }
+}
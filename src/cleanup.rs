@@ -0,0 +1,108 @@
+//! Clearing away nodes that are out of the scene for good. Cutting a cloth to
+//! shreds leaves scraps with no links at all, and with the ground out of the
+//! way, through wrapping off or a scene built far above it, anything cut loose
+//! falls forever; either way they cost a step each without anyone seeing them
+//! again. A world with `cleanup` on removes nodes that fall too far below the
+//! ground, and nodes that have gone without a link for long enough, queuing a
+//! `SimEvent::NodeDespawned` for each.
+
+use crate::world::World;
+
+/// How far below the ground nodes can fall before they're removed, by default.
+pub const CLEANUP_DEPTH: f32 = 1000.0;
+/// Seconds a node can go without a link before it's removed, by default.
+pub const CLEANUP_DELAY: f32 = 10.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cleanup {
+    /// How far below `ground_y` a node can be before it's removed; infinite to keep nodes however far they fall.
+    pub depth: f32,
+    /// Time a free node can go without any link or band before it's removed;
+    /// infinite to keep them. Balls, sand grains, and nodes on anchors, rails,
+    /// or sliders are meant to be on their own and are never counted.
+    pub delay: f32,
+    /// How long each node has been without a link, by index into the arena.
+    pub loose_for: Vec<f32>,
+}
+
+impl Default for Cleanup {
+    fn default() -> Self {
+        Self::new(CLEANUP_DEPTH, CLEANUP_DELAY)
+    }
+}
+
+impl Cleanup {
+    pub fn new(depth: f32, delay: f32) -> Self {
+        Self { depth, delay, loose_for: Vec::new() }
+    }
+
+    /// Counts `dt` more toward each loose node's delay, and returns the nodes
+    /// of `world` that are due to go, highest index first, so removing them
+    /// in order leaves the rest where they are. Fixed nodes never go.
+    pub fn due(&mut self, world: &World, dt: f32) -> Vec<usize> {
+        let mut held = vec![false; world.arena.len()];
+        let links = world.constraints.iter().flat_map(|constraint| [constraint.a, constraint.b]);
+        let bands = world.bands.iter().flat_map(|band| [band.a, band.b]);
+        let grains = world.sand.iter().flat_map(|sand| sand.grains.iter().copied());
+        let balls = world.balls.iter().map(|ball| ball.node);
+        let anchors = world.anchors.iter().map(|anchor| anchor.node);
+        let rails = world.rails.iter().map(|rail| rail.node).chain(world.sliders.iter().map(|slider| slider.node));
+        for node in links.chain(bands).chain(grains).chain(balls).chain(anchors).chain(rails) {
+            held[node] = true;
+        }
+
+        self.loose_for.resize(world.arena.len(), 0.0);
+        let floor = world.ground_y + self.depth;
+        let mut due = Vec::new();
+        for (i, node) in world.arena.iter().enumerate().rev().filter(|(_, node)| !node.fixed) {
+            self.loose_for[i] = if held[i] { 0.0 } else { self.loose_for[i] + dt };
+            if node.pos.y > floor || self.loose_for[i] > self.delay {
+                due.push(i);
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::SimEvent;
+    use crate::TARGET_DIST;
+    use glam::Vec2;
+
+    #[test]
+    fn scraps_and_fallen_nodes_are_cleared_away() {
+        let mut world = World::new(500.0);
+        world.cleanup = Some(Cleanup::new(f32::INFINITY, 1.0));
+        world.add_rope(Vec2::ZERO, 4);
+        world.add_rope(Vec2::new(100.0, 0.0), 3);
+        // cut the second rope loose entirely
+        world.constraints.retain(|constraint| constraint.a < 4);
+        let dt = world.params.dt;
+        let mut despawned = Vec::new();
+        for _ in 0..(1.0 / dt) as usize + 2 {
+            despawned.extend(world.step(dt).into_iter().filter_map(|event| match event {
+                SimEvent::NodeDespawned { node, .. } => Some(node),
+                _ => None,
+            }));
+        }
+        // its anchor is still fixed, and the first rope still holds together
+        assert_eq!(despawned, [6, 5]);
+        assert_eq!(world.arena.len(), 5);
+        assert_eq!(world.constraints.len(), 3);
+
+        // without a ground to land on, things fall until they're too far down
+        let mut world = World::new(0.0);
+        world.wrap.y = true;
+        (world.wrap.min, world.wrap.max) = (Vec2::new(0.0, -1000.0), Vec2::new(1000.0, 1_000_000.0));
+        world.cleanup = Some(Cleanup::new(TARGET_DIST, f32::INFINITY));
+        world.add_rope(Vec2::new(100.0, -100.0), 3);
+        world.constraints.pop();
+        for _ in 0..200 {
+            world.step(dt);
+        }
+        assert_eq!(world.arena.len(), 2);
+        assert_eq!(world.cleanup.as_ref().unwrap().loose_for.len(), 2);
+    }
+}
@@ -0,0 +1,297 @@
+use clap::{Args, Parser};
+use crate::error::SimError;
+use crate::sim_config;
+use crate::image_scene;
+use clothsim::scene::PinEdge;
+use clothsim::sim_world::Param;
+use clothsim::{rng, scene, Params, World};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Position-based rope and cloth simulation.
+#[derive(Parser, Debug)]
+#[command(name = "clothsim", version)]
+pub struct Cli {
+    /// Window width in pixels; also the view headless scenes are built for.
+    #[arg(long, default_value_t = 800)]
+    pub width: i32,
+    #[arg(long, default_value_t = 600)]
+    pub height: i32,
+    /// Built-in scene to start with.
+    #[arg(long, default_value = "rope", value_parser = builtin_scene)]
+    pub scene: String,
+    /// Start with the stress scene's cloth at about this many nodes, rather than its usual 10000.
+    #[arg(long, value_name = "NODES", conflicts_with_all = ["scene_file", "nodes_csv", "image"])]
+    #[arg(value_parser = clap::value_parser!(u32).range(4..))]
+    pub stress_nodes: Option<u32>,
+    /// Scene file to start with instead of a built-in scene.
+    #[arg(long, conflicts_with = "scene")]
+    pub scene_file: Option<PathBuf>,
+    /// Nodes CSV (x,y,mass,fixed) to build the starting scene from, with `--edges-csv`.
+    #[arg(long, conflicts_with_all = ["scene", "scene_file"], requires = "edges_csv")]
+    pub nodes_csv: Option<PathBuf>,
+    /// Edges CSV (a,b,rest,threshold) linking the nodes from `--nodes-csv`.
+    #[arg(long, requires = "nodes_csv")]
+    pub edges_csv: Option<PathBuf>,
+    /// PNG to turn into a cloth, with a node for each opaque patch.
+    #[arg(long, conflicts_with_all = ["scene", "scene_file", "nodes_csv"])]
+    pub image: Option<PathBuf>,
+    /// Edge of the image to hang it from: top, bottom, left, or right.
+    #[arg(long, default_value = "top", value_parser = pin_edge, requires = "image")]
+    pub pin_edge: PinEdge,
+    /// Seed for anything random in the simulation.
+    #[arg(long, default_value_t = rng::DEFAULT_SEED)]
+    pub seed: u64,
+    /// Run without a window, writing node positions after each step as CSV.
+    #[arg(long)]
+    pub headless: bool,
+    /// Steps to run in headless mode.
+    #[arg(long, default_value_t = 1000, requires = "headless")]
+    pub steps: usize,
+    /// File for headless output, stdout if omitted.
+    #[arg(long, requires = "headless")]
+    pub out: Option<PathBuf>,
+    /// Write one `step,checksum` row per step instead of node positions.
+    #[arg(long, requires = "headless")]
+    pub checksums: bool,
+    /// Solver iteration counts to sweep over, like 1,5,20. With any `--sweep-` list, headless mode runs
+    /// every combination of them and writes one row of stability and strain metrics per run.
+    #[arg(long, value_name = "LIST", value_delimiter = ',', requires = "headless")]
+    #[arg(conflicts_with_all = ["checksums", "dump_state", "script"])]
+    pub sweep_iters: Option<Vec<usize>>,
+    /// Rigidities to sweep over, like 0.5,1.
+    #[arg(long, value_name = "LIST", value_delimiter = ',', requires = "headless")]
+    #[arg(conflicts_with_all = ["checksums", "dump_state", "script"])]
+    pub sweep_rigidity: Option<Vec<f32>>,
+    /// Timesteps to sweep over, like 0.05,0.15,0.3.
+    #[arg(long, value_name = "LIST", value_delimiter = ',', requires = "headless")]
+    #[arg(conflicts_with_all = ["checksums", "dump_state", "script"])]
+    pub sweep_dt: Option<Vec<f32>>,
+    /// Evolve the scene's muscle settings for this many generations, scoring each walker by how far right it
+    /// gets in `--steps` steps, and write one `generation,best,mean` row per generation.
+    #[arg(long, value_name = "GENERATIONS", requires = "headless")]
+    #[arg(conflicts_with_all = ["checksums", "dump_state", "script", "sweep_iters", "sweep_rigidity", "sweep_dt"])]
+    pub evolve: Option<usize>,
+    /// Walkers in each generation with `--evolve`.
+    #[arg(long, default_value_t = 20, requires = "evolve")]
+    pub population: usize,
+    /// Save the scene with the farthest walker's muscle settings here, to watch with `--scene-file`.
+    #[arg(long, value_name = "PATH", requires = "evolve")]
+    pub best_scene: Option<PathBuf>,
+    /// Append one line of JSON per step to this file, with node states and severed links.
+    #[arg(long, value_name = "PATH")]
+    pub dump_state: Option<PathBuf>,
+    /// Bundle file to start with, as the Export window writes them, which sets the colors and view too.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["headless", "golden", "profile_out"])]
+    pub bundle: Option<PathBuf>,
+    /// Compare two save or scene files and print the first node or link that differs between them, then exit.
+    #[arg(long, num_args = 2, value_names = ["A", "B"], conflicts_with_all = ["headless", "golden", "profile_out"])]
+    pub diff: Option<Vec<PathBuf>>,
+    /// Steps to run both `--diff` worlds for, comparing them after each, to find the step they part ways at.
+    #[arg(long, default_value_t = 0, requires = "diff")]
+    pub diff_steps: usize,
+    /// How far apart positions, velocities, and lengths can be before `--diff` counts them as different.
+    #[arg(long, default_value_t = 1e-4, requires = "diff")]
+    pub tolerance: f32,
+    /// Rhai script to run on the scene: its `init()` once it's built, and `on_frame(t)` before every step.
+    #[cfg_attr(feature = "netplay", arg(conflicts_with_all = ["host", "join"]))]
+    #[arg(long, value_name = "PATH")]
+    pub script: Option<PathBuf>,
+    /// Seconds without input before the demo starts playing its bundled replays by itself, with captions,
+    /// until someone moves the mouse or presses a key. Zero starts it right away.
+    #[arg(long, value_name = "SECONDS", conflicts_with_all = ["headless", "golden", "profile_out"])]
+    pub attract_after: Option<f64>,
+    /// Render test scenes and compare them against the reference images in this directory.
+    #[arg(long, conflicts_with = "headless")]
+    pub golden: Option<PathBuf>,
+    /// Overwrite the reference images with the current renders.
+    #[arg(long, requires = "golden")]
+    pub update_golden: bool,
+    /// Run the starting scene for a fixed number of frames without input, then write each frame's time in
+    /// each phase, and a summary of them, to this JSON file.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["headless", "golden"])]
+    pub profile_out: Option<PathBuf>,
+    /// Frames to run with `--profile-out`.
+    #[arg(long, default_value_t = 600, requires = "profile_out")]
+    pub profile_frames: usize,
+    /// Swing cloth and a rope around in 3D, with a camera orbiting them, instead of the usual demo.
+    #[cfg(feature = "sim3d")]
+    #[arg(long = "3d", conflicts_with_all = ["headless", "golden", "profile_out"])]
+    pub three_d: bool,
+    /// Stream the world as JSON to WebSocket clients on this address, like 127.0.0.1:9001.
+    #[cfg(feature = "websocket")]
+    #[arg(long, value_name = "ADDR")]
+    pub serve: Option<String>,
+    /// Host a shared session on this address, like 0.0.0.0:9002, for others to `--join`.
+    #[cfg(feature = "netplay")]
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["headless", "join"])]
+    pub host: Option<String>,
+    /// Join the shared session hosted at this address.
+    #[cfg(feature = "netplay")]
+    #[arg(long, value_name = "ADDR", conflicts_with = "headless")]
+    pub join: Option<String>,
+    /// Listen for OSC messages controlling wind, gravity, and speed on this UDP address, like 0.0.0.0:9000.
+    #[cfg(feature = "osc")]
+    #[arg(long, value_name = "ADDR", conflicts_with = "headless")]
+    pub osc: Option<String>,
+    /// Play with a gamepad, read from this Linux joystick device or else /dev/input/js0.
+    #[cfg(feature = "gamepad")]
+    #[arg(long, value_name = "DEVICE", num_args = 0..=1, default_missing_value = "/dev/input/js0")]
+    #[arg(conflicts_with = "headless")]
+    pub gamepad: Option<String>,
+    #[command(flatten)]
+    pub params: ParamOverrides,
+}
+
+impl Cli {
+    pub fn scene_source(&self) -> SceneSource {
+        if let Some(nodes) = self.stress_nodes {
+            return SceneSource::Stress { nodes: nodes as usize };
+        }
+        if let Some(path) = &self.image {
+            return SceneSource::Image { path: path.clone(), pin: self.pin_edge };
+        }
+        match (&self.scene_file, &self.nodes_csv, &self.edges_csv) {
+            (Some(path), _, _) => SceneSource::File(path.clone()),
+            (None, Some(nodes), Some(edges)) => {
+                SceneSource::Csv { nodes: nodes.clone(), edges: edges.clone() }
+            }
+            _ => SceneSource::Builtin(self.scene.clone()),
+        }
+    }
+
+    /// Whether headless mode runs a parameter sweep instead of a single run.
+    pub fn sweeping(&self) -> bool {
+        self.sweep_iters.is_some() || self.sweep_rigidity.is_some() || self.sweep_dt.is_some()
+    }
+
+    /// `sim.toml`'s params with the command-line overrides on top.
+    pub fn params(&self) -> Result<Params, SimError> {
+        let mut params = sim_config::load()?;
+        self.params.apply(&mut params);
+        Ok(params)
+    }
+}
+
+/// Physics params to change from their defaults or `sim.toml`.
+#[derive(Args, Clone, Copy, Debug)]
+pub struct ParamOverrides {
+    /// Simulation timestep.
+    #[arg(long, value_parser = param(Param::Dt))]
+    pub dt: Option<f32>,
+    #[arg(long, value_parser = param(Param::Gravity))]
+    pub gravity: Option<f32>,
+    #[arg(long, value_parser = param(Param::Drag))]
+    pub drag: Option<f32>,
+    /// Fraction of each constraint's error corrected per solver iteration.
+    #[arg(long, value_parser = param(Param::Rigidity))]
+    pub rigidity: Option<f32>,
+    /// Constraint solver iterations per step.
+    #[arg(long = "iters", value_parser = iterations)]
+    pub solver_iterations: Option<usize>,
+    /// Length at which constraints break, in rest lengths.
+    #[arg(long, value_parser = param(Param::BreakThreshold))]
+    pub break_threshold: Option<f32>,
+}
+
+impl ParamOverrides {
+    pub fn apply(&self, params: &mut Params) {
+        let ParamOverrides { dt, gravity, drag, rigidity, solver_iterations, break_threshold } = *self;
+        params.dt = dt.unwrap_or(params.dt);
+        params.gravity = gravity.unwrap_or(params.gravity);
+        params.drag = drag.unwrap_or(params.drag);
+        params.rigidity = rigidity.unwrap_or(params.rigidity);
+        params.solver_iterations = solver_iterations.unwrap_or(params.solver_iterations);
+        params.break_threshold = break_threshold.unwrap_or(params.break_threshold);
+    }
+}
+
+/// Reads a value for `param`, turning away ones the solver can't run with.
+fn param(param: Param) -> impl Fn(&str) -> Result<f32, String> + Clone + Send + Sync + 'static {
+    move |text| {
+        let value: f32 = text.parse().map_err(|_| "expected a number".to_string())?;
+        if param.accepts(value) {
+            Ok(value)
+        } else {
+            Err(format!("must be {}", param.requirement()))
+        }
+    }
+}
+
+fn iterations(text: &str) -> Result<usize, String> {
+    let iterations = text.parse().ok().filter(|&iterations| iterations >= 1);
+    iterations.ok_or_else(|| "expected a whole number, at least 1".to_string())
+}
+
+fn builtin_scene(name: &str) -> Result<String, String> {
+    if scene::BUILTIN.iter().any(|(builtin, _)| *builtin == name) {
+        Ok(name.to_string())
+    } else {
+        let names: Vec<&str> = scene::BUILTIN.iter().map(|(name, _)| *name).collect();
+        Err(format!("expected one of {}", names.join(", ")))
+    }
+}
+
+fn pin_edge(name: &str) -> Result<PinEdge, String> {
+    PinEdge::from_name(name).ok_or_else(|| "expected top, bottom, left, or right".to_string())
+}
+
+/// Where a scene comes from, so it can be rebuilt on reset and named in replays.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneSource {
+    Builtin(String),
+    File(PathBuf),
+    /// Node and edge lists, as read by `scene::from_csv`.
+    Csv { nodes: PathBuf, edges: PathBuf },
+    /// A picture made into cloth by `image_scene::load`.
+    Image { path: PathBuf, pin: PinEdge },
+    /// The stress scene's cloth at about `nodes` nodes, as built by `scene::stress_cloth`.
+    Stress { nodes: usize },
+}
+
+impl SceneSource {
+    /// Builds the scene for a view of `width` by `height`, running with `params`.
+    pub fn build(&self, width: f32, height: f32, params: Params) -> Result<World, SimError> {
+        let error = |message: String| SimError::SceneLoad { scene: self.to_string(), message };
+        match self {
+            SceneSource::Builtin(name) => {
+                let mut world = scene::builtin(name, width, height)
+                    .ok_or_else(|| error("there's no built-in scene by that name".to_string()))?;
+                world.set_params(params);
+                Ok(world)
+            }
+            SceneSource::File(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+                let mut world = scene::from_text(&text).map_err(error)?;
+                // the file's break thresholds are explicit, so don't rescale them
+                world.params = params;
+                Ok(world)
+            }
+            SceneSource::Csv { nodes, edges } => {
+                let nodes = std::fs::read_to_string(nodes).map_err(|e| error(e.to_string()))?;
+                let edges = std::fs::read_to_string(edges).map_err(|e| error(e.to_string()))?;
+                scene::from_csv(&nodes, &edges, height - scene::GROUND_OFFSET, params).map_err(error)
+            }
+            SceneSource::Image { path, pin } => {
+                image_scene::load(path, *pin, width, height, params).map_err(error)
+            }
+            SceneSource::Stress { nodes } => {
+                let mut world = scene::stress_cloth(width, height, *nodes);
+                world.set_params(params);
+                Ok(world)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SceneSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneSource::Builtin(name) => write!(f, "{}", name),
+            SceneSource::File(path) => write!(f, "{}", path.display()),
+            SceneSource::Csv { nodes, edges } => write!(f, "{} and {}", nodes.display(), edges.display()),
+            SceneSource::Image { path, .. } => write!(f, "{}", path.display()),
+            SceneSource::Stress { nodes } => write!(f, "stress with {} nodes", nodes),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::cursor::CursorHistory;
+use clothsim::substeps::Substeps;
+use clothsim::xpbd::XPBD_COMPLIANCE;
+use clothsim::{Energy, Params, World, Xpbd};
+use egui_macroquad::egui;
+
+/// Fraction of the way each step's time moves the smoothed cost towards it.
+const COST_SMOOTHING: f64 = 0.05;
+
+/// A second copy of the scene, stepped alongside the main one from the same
+/// input but with its own params and solver, for comparing them side by side.
+pub struct Comparison {
+    pub world: World,
+    /// The cursor as this copy's previous steps saw it.
+    pub cursor: CursorHistory,
+    pub substeps: Substeps,
+    /// How each side has held up since the comparison started, left then right.
+    pub stats: [SideStats; 2],
+}
+
+impl Comparison {
+    /// Copies `world` to run with `params`, solving with XPBD at `compliance` if there is one.
+    pub fn new(world: &World, params: Params, compliance: Option<f32>) -> Self {
+        let stats = [SideStats::new(world); 2];
+        let mut world = world.clone();
+        world.set_params(params);
+        world.xpbd = compliance.map(Xpbd::new);
+        Self { world, cursor: CursorHistory::default(), substeps: Substeps::default(), stats }
+    }
+
+    /// Starts over as a copy of `world`, keeping this side's params and solver.
+    pub fn restart(&mut self, world: &World) {
+        let params = self.world.params;
+        *self = Self::new(world, params, self.compliance());
+    }
+
+    /// Switches to `params` if they've changed since the last step.
+    pub fn sync_params(&mut self, params: Params) {
+        if self.world.params != params {
+            self.world.set_params(params);
+        }
+    }
+
+    /// Switches to XPBD at `compliance`, or back to projection for `None`, if that's changed since the last step.
+    pub fn sync_solver(&mut self, compliance: Option<f32>) {
+        if self.compliance() != compliance {
+            self.world.xpbd = compliance.map(Xpbd::new);
+        }
+    }
+
+    fn compliance(&self) -> Option<f32> {
+        self.world.xpbd.as_ref().map(|xpbd| xpbd.compliance)
+    }
+
+    /// Takes both sides' state after a step, `left` being the main world, with
+    /// how many seconds each side's step took.
+    pub fn record(&mut self, left: &World, costs: [f64; 2]) {
+        self.stats[0].record(left, costs[0]);
+        self.stats[1].record(&self.world, costs[1]);
+    }
+}
+
+/// How one side of a comparison has held up: how far its links stretch, how
+/// far its energy has drifted, and how long it takes to step.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SideStats {
+    /// Total energy when the comparison started.
+    pub start_energy: f32,
+    pub energy: f32,
+    /// The most stretched link's stretch past its rest length, as a fraction of it.
+    pub stretch: f32,
+    /// The most `stretch` has been since the comparison started.
+    pub peak_stretch: f32,
+    /// Seconds per step, smoothed over the last several.
+    pub cost: f64,
+}
+
+impl SideStats {
+    fn new(world: &World) -> Self {
+        let energy = Energy::of(world).total();
+        Self { start_energy: energy, energy, ..Self::default() }
+    }
+
+    fn record(&mut self, world: &World, cost: f64) {
+        self.energy = Energy::of(world).total();
+        let strains = world.constraints.iter().map(|constraint| constraint.strain(&world.arena, &world.wrap));
+        self.stretch = strains.fold(0.0, f32::max);
+        self.peak_stretch = self.peak_stretch.max(self.stretch);
+        self.cost = if self.cost == 0.0 { cost } else { self.cost + (cost - self.cost) * COST_SMOOTHING };
+    }
+
+    /// A line for under the side's view.
+    pub fn describe(&self) -> String {
+        let drift = if self.start_energy != 0.0 { self.energy / self.start_energy - 1.0 } else { 0.0 };
+        format!(
+            "stretch {:.1}% (worst {:.1}%), energy {:+.1}%, {:.2} ms per step",
+            100.0 * self.stretch,
+            100.0 * self.peak_stretch,
+            100.0 * drift,
+            1000.0 * self.cost
+        )
+    }
+}
+
+/// Sliders for the params that matter most to stability.
+pub fn params_ui(params: &mut Params, ui: &mut egui::Ui) {
+    ui.add(egui::Slider::new(&mut params.solver_iterations, 1..=50).text("Iterations"));
+    ui.add(egui::Slider::new(&mut params.rigidity, 0.05..=1.0).text("Rigidity"));
+    ui.add(egui::Slider::new(&mut params.dt, 0.01..=0.5).text("dt"));
+    ui.add(egui::Slider::new(&mut params.drag, 0.0..=2.0).text("Drag"));
+    ui.add(egui::Slider::new(&mut params.gravity, 0.0..=50.0).text("Gravity"));
+}
+
+/// Radio buttons choosing between projecting by rigidity and XPBD, with a
+/// slider for XPBD's `compliance`.
+pub fn solver_ui(compliance: &mut Option<f32>, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.radio_value(compliance, None, "Projection");
+        if ui.radio(compliance.is_some(), "XPBD").clicked() && compliance.is_none() {
+            *compliance = Some(XPBD_COMPLIANCE);
+        }
+    });
+    if let Some(compliance) = compliance.as_mut() {
+        ui.add(egui::Slider::new(compliance, 0.0..=0.01).logarithmic(true).text("Compliance"));
+    }
+}
+
+/// A short description of the params and solver `world` steps with, for labelling a side.
+pub fn describe(world: &World) -> String {
+    let params = &world.params;
+    let solver = match world.xpbd.as_ref() {
+        Some(xpbd) => format!("XPBD at compliance {}", xpbd.compliance),
+        None => format!("rigidity {}", params.rigidity),
+    };
+    format!(
+        "{} iterations, {}, dt {}, drag {}, gravity {}",
+        params.solver_iterations, solver, params.dt, params.drag, params.gravity
+    )
+}
@@ -0,0 +1,135 @@
+use crate::node::Node;
+use crate::verlet;
+use crate::wrap::Wrap;
+use glam::Vec2;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Constraint {
+    pub a: usize,
+    pub b: usize,
+    /// Length the constraint pulls its nodes towards.
+    pub rest_length: f32,
+    /// Length at which the constraint breaks.
+    pub break_threshold: f32,
+}
+
+impl Constraint {
+    /// How close the constraint is to breaking, from 0 at rest length to 1 at the threshold.
+    pub fn break_fraction(&self, arena: &[Node], wrap: &Wrap) -> f32 {
+        let dist = self.offset(arena, wrap).length();
+        ((dist - self.rest_length) / (self.break_threshold - self.rest_length)).clamp(0.0, 1.0)
+    }
+
+    /// Stretch past the rest length as a fraction of it, zero when slack.
+    pub fn strain(&self, arena: &[Node], wrap: &Wrap) -> f32 {
+        let dist = self.offset(arena, wrap).length();
+        ((dist - self.rest_length) / self.rest_length).max(0.0)
+    }
+
+    /// How fast the constraint's ends are heading apart along it, in rest
+    /// lengths per unit time, negative while they close in. Free nodes' speed
+    /// is their `vel`, which between the solve and `differentiate` is still
+    /// what they were moved with before the constraint held them back, so
+    /// this is how hard the step jerked on it; fixed nodes' is how far they
+    /// moved over `dt`.
+    pub fn strain_rate(&self, arena: &[Node], wrap: &Wrap, dt: f32) -> f32 {
+        let vel = |node: &Node| if node.fixed { (node.pos - node.last_pos) / dt } else { node.vel };
+        let direction = self.offset(arena, wrap).normalize_or_zero();
+        (vel(&arena[self.b]) - vel(&arena[self.a])).dot(direction) / self.rest_length
+    }
+
+    /// How hard the constraint pulls on its ends, as the momentum times `dt`
+    /// the solver would move between them to take it back to its rest length:
+    /// positive while it's stretched and pulling them together, negative while
+    /// it's squashed and pushing them apart, at half strength like the solver.
+    /// Zero between two fixed nodes, which nothing can pull.
+    pub fn tension(&self, arena: &[Node], wrap: &Wrap) -> f32 {
+        let weights = arena[self.a].inverse_mass() + arena[self.b].inverse_mass();
+        if weights == 0.0 {
+            return 0.0;
+        }
+        let diff = self.offset(arena, wrap).length() - self.rest_length;
+        if diff < 0.0 {
+            0.5 * diff / weights
+        } else {
+            diff / weights
+        }
+    }
+
+    /// The offset from `a` to `b`, across the wrapped edges if that's shorter.
+    pub fn offset(&self, arena: &[Node], wrap: &Wrap) -> Vec2 {
+        wrap.delta(arena[self.a].pos, arena[self.b].pos)
+    }
+
+    /// Whether it's stretched to its break threshold.
+    pub fn snaps(&self, arena: &[Node], wrap: &Wrap) -> bool {
+        verlet::snaps(self.offset(arena, wrap), self.break_threshold)
+    }
+
+    pub fn solve(&self, arena: &mut [Node], rigidity: f32, wrap: &Wrap) {
+        let (a, b) = (&arena[self.a], &arena[self.b]);
+        let (a_weight, b_weight) = (a.inverse_mass(), b.inverse_mass());
+        let offset = self.offset(arena, wrap);
+        let (a_offs, b_offs) = verlet::correction(offset, self.rest_length, a_weight, b_weight, rigidity);
+
+        arena[self.a].add_offs(a_offs);
+        arena[self.b].add_offs(b_offs);
+    }
+}
+
+/// Which constraint was removed, and where and how stretched it was at the time.
+#[derive(Copy, Clone, Debug)]
+pub struct SeveredLink {
+    /// The nodes it linked.
+    pub a: usize,
+    pub b: usize,
+    pub pos: Vec2,
+    /// From `pos` to where `b` was, so `a` was the same distance the other way.
+    pub half: Vec2,
+    pub strain: f32,
+}
+
+impl SeveredLink {
+    pub fn new(constraint: &Constraint, arena: &[Node], wrap: &Wrap) -> Self {
+        let half = constraint.offset(arena, wrap) / 2.0;
+        Self {
+            a: constraint.a,
+            b: constraint.b,
+            pos: arena[constraint.a].pos + half,
+            half,
+            strain: constraint.strain(arena, wrap),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn jerks_snap_taut_links() {
+        let mut world = World::new(1000.0);
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(Vec2::ZERO, 1.0) });
+        // caught at the end of the rope, still falling at 100 after the solve
+        world.arena.push(Node { vel: Vec2::new(0.0, 100.0), ..Node::with_pos_and_mass(Vec2::new(0.0, 50.0), 1.0) });
+        world.constraints.push(Constraint { a: 0, b: 1, rest_length: 50.0, break_threshold: 250.0 });
+        assert_eq!(world.constraints[0].strain_rate(&world.arena, &world.wrap, 0.1), 2.0);
+
+        // nowhere near the break threshold, so only a break rate snaps it
+        world.break_overloaded(0.1);
+        assert_eq!(world.constraints.len(), 1);
+        world.break_rate = Some(3.0);
+        world.break_overloaded(0.1);
+        assert_eq!(world.constraints.len(), 1);
+        world.break_rate = Some(1.0);
+
+        // a slack rope going apart as fast is still slack, so it holds
+        world.arena[1].pos.y = 40.0;
+        world.break_overloaded(0.1);
+        assert_eq!(world.constraints.len(), 1);
+        world.arena[1].pos.y = 50.0;
+        world.break_overloaded(0.1);
+        assert!(world.constraints.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::friction::Friction;
+use crate::layer::{Layers, GROUND};
+use crate::math;
+use crate::node::Node;
+use crate::spatial_hash::SpatialHash;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+use std::collections::HashSet;
+
+/// How much more a node counts than one a unit of height above it when the
+/// two are pushed apart.
+pub const CONTACT_STACKING: f32 = 0.1;
+
+/// Contacts between nodes as circles, `NODE_RADIUS` across or their ball's
+/// radius, so free nodes pile up on each other and the ground like a ball
+/// pit. Nodes joined by a link or a band don't collide, and neither do nodes
+/// on layers that don't, or sand grains, which have their own contacts.
+///
+/// Contacts are solved on positions inside the solver loop like everything
+/// else, with two tricks from Macklin et al.'s "Unified Particle Physics for
+/// Real-Time Applications" to make stacks rest still instead of jittering:
+/// overlap already there at the start of the step is pushed out without
+/// giving the nodes any velocity, and lower nodes count as heavier, so the
+/// bottom of a pile holds up the top instead of being squashed by it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contacts {
+    /// How much more a node counts than one a unit of height above it, as
+    /// `e^(stacking * height)`. Zero weighs them by mass alone.
+    pub stacking: f32,
+}
+
+impl Default for Contacts {
+    fn default() -> Self {
+        Self { stacking: CONTACT_STACKING }
+    }
+}
+
+/// The nodes taking part in contacts this step and the pairs that don't touch.
+#[derive(Clone, Debug, Default)]
+pub struct Circles {
+    /// Each node by index into the arena, with its radius.
+    pub nodes: Vec<(usize, f32)>,
+    /// Pairs of nodes, lower index first, that are linked and so never collide.
+    pub linked: HashSet<(usize, usize)>,
+    /// Which nodes' layers collide, all of them unless set.
+    pub layers: Layers,
+}
+
+impl Circles {
+    /// Every node in `arena` as a circle of `NODE_RADIUS`, or of `radius` for
+    /// those in `radii`, except `skip`, which are left out.
+    pub fn new(
+        arena: &[Node],
+        radii: impl IntoIterator<Item = (usize, f32)>,
+        skip: &[usize],
+        linked: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Self {
+        let mut nodes: Vec<(usize, f32)> = (0..arena.len()).map(|i| (i, NODE_RADIUS)).collect();
+        for (i, radius) in radii {
+            nodes[i].1 = radius;
+        }
+        let skip: HashSet<usize> = skip.iter().copied().collect();
+        nodes.retain(|(i, _)| !skip.contains(i));
+        let linked = linked.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect();
+        Self { nodes, linked, layers: Layers::default() }
+    }
+
+    fn largest(&self) -> f32 {
+        self.nodes.iter().map(|&(_, radius)| radius).fold(0.0, f32::max)
+    }
+}
+
+impl Contacts {
+    /// Inverse masses for a pair of nodes, the lower one made heavier by `stacking`.
+    fn weights(&self, a: &Node, b: &Node) -> (f32, f32) {
+        // y grows downward, so a positive drop means `a` is the lower one
+        let drop = (a.pos.y - b.pos.y).clamp(-100.0, 100.0);
+        let scale = math::exp(self.stacking * drop / 2.0);
+        (a.inverse_mass() / scale, b.inverse_mass() * scale)
+    }
+
+    /// Pushes apart circles that already overlapped where they started the
+    /// step, and lifts those that started in the ground, moving both their
+    /// positions and their last positions so it doesn't give them any speed.
+    /// Takes `iterations` passes, like the solver.
+    pub fn stabilize(&self, arena: &mut [Node], circles: &Circles, floor: Option<f32>, iterations: usize) {
+        for _ in 0..iterations {
+            self.separate(arena, circles, floor, &Friction::NONE, true);
+        }
+    }
+
+    /// Pushes overlapping circles apart, and out of the ground below `floor` if
+    /// there is one and their layers collide with it, with `friction` against them sliding past each other.
+    pub fn solve(&self, arena: &mut [Node], circles: &Circles, floor: Option<f32>, friction: &Friction) {
+        self.separate(arena, circles, floor, friction, false);
+    }
+
+    fn separate(&self, arena: &mut [Node], circles: &Circles, floor: Option<f32>, friction: &Friction, start: bool) {
+        let at = |node: &Node| if start { node.last_pos } else { node.pos };
+        let shift = |node: &mut Node, offs: Vec2| {
+            if !node.fixed {
+                node.pos += offs;
+                if start {
+                    node.last_pos += offs;
+                }
+            }
+        };
+
+        let reach = 2.0 * circles.largest();
+        let hash = SpatialHash::new(reach, circles.nodes.iter().map(|&(i, _)| at(&arena[i])));
+        for (j, &(a, a_radius)) in circles.nodes.iter().enumerate() {
+            for k in hash.near(at(&arena[a]), reach).filter(|&k| k > j) {
+                let (b, b_radius) = circles.nodes[k];
+                let apart = !circles.layers.collide(arena[a].layer, arena[b].layer);
+                if apart || circles.linked.contains(&(a.min(b), a.max(b))) {
+                    continue;
+                }
+                let offset = at(&arena[a]) - at(&arena[b]);
+                let distance = offset.length();
+                let touching = a_radius + b_radius;
+                if distance >= touching || distance == 0.0 {
+                    continue;
+                }
+                let (a_weight, b_weight) = self.weights(&arena[a], &arena[b]);
+                let total = a_weight + b_weight;
+                if total == 0.0 {
+                    continue;
+                }
+                let (normal, depth) = (offset / distance, touching - distance);
+                let slip = (arena[a].pos - arena[a].last_pos) - (arena[b].pos - arena[b].last_pos);
+                let push = (normal * depth - friction.grip(slip, normal, depth)) / total;
+                shift(&mut arena[a], push * a_weight);
+                shift(&mut arena[b], -push * b_weight);
+            }
+        }
+
+        let Some(floor) = floor else {
+            return;
+        };
+        for &(i, radius) in circles.nodes.iter() {
+            let node = &mut arena[i];
+            if !circles.layers.hits(node.layer, GROUND) {
+                continue;
+            }
+            let depth = at(node).y - (floor - radius);
+            if depth > 0.0 {
+                let slip = node.pos - node.last_pos;
+                shift(node, Vec2::NEG_Y * depth - friction.grip(slip, Vec2::NEG_Y, depth));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn piles_rest_without_gaining_speed() {
+        // two nodes sunk into each other and the ground at the start of the step
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(0.0, 96.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(0.0, 88.0), 1.0),
+        ];
+        let circles = Circles::new(&arena, [], &[], []);
+        let contacts = Contacts::default();
+        contacts.stabilize(&mut arena, &circles, Some(100.0), 10);
+        for _ in 0..10 {
+            contacts.solve(&mut arena, &circles, Some(100.0), &Friction::default());
+        }
+
+        // they're pushed out onto the ground and each other without moving since the start of the step
+        assert!((arena[0].pos.y - (100.0 - NODE_RADIUS)).abs() < 1e-3, "{}", arena[0].pos);
+        assert!((arena[0].pos.y - arena[1].pos.y - 2.0 * NODE_RADIUS).abs() < 1e-3, "{}", arena[1].pos);
+        assert!(arena.iter().all(|node| node.pos.distance(node.last_pos) < 1e-3));
+
+        // the lower node counts as heavier, so it gives way less
+        let (lower, upper) = contacts.weights(&arena[0], &arena[1]);
+        assert!(lower < upper);
+
+        // linked nodes pass through each other
+        let circles = Circles::new(&arena, [], &[], [(1, 0)]);
+        arena[1].pos = arena[0].pos - Vec2::new(0.0, 4.0);
+        contacts.solve(&mut arena, &circles, None, &Friction::default());
+        assert_eq!(arena[1].pos, arena[0].pos - Vec2::new(0.0, 4.0));
+    }
+}
@@ -0,0 +1,70 @@
+//! Measures how quickly the solver pulls constraints back to their rest
+//! lengths, one iteration at a time, to show where more iterations stop
+//! paying for themselves.
+
+use crate::world::World;
+
+/// Solver iterations `measure` runs by default, well past what scenes need.
+pub const CONVERGENCE_ITERATIONS: usize = 40;
+
+/// How far the constraints are from their rest lengths, in units of distance.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Residual {
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl Residual {
+    pub fn of(world: &World) -> Self {
+        if world.constraints.is_empty() {
+            return Self::default();
+        }
+        let errors = world.constraints.iter().map(|constraint| {
+            (constraint.offset(&world.arena, &world.wrap).length() - constraint.rest_length).abs()
+        });
+        let (max, sum) = errors.fold((0.0, 0.0), |(max, sum), error: f32| (error.max(max), sum + error));
+        Self { max, mean: sum / world.constraints.len() as f32 }
+    }
+}
+
+/// Steps a copy of `world` up to its solve and runs `iterations` solver
+/// iterations on it, returning the residual after each.
+pub fn measure(world: &World, iterations: usize) -> Vec<Residual> {
+    let mut world = world.clone();
+    world.params.solver_iterations = iterations;
+    let dt = world.params.dt;
+    world.move_anchors(dt);
+    world.apply_forces();
+    world.blow(dt);
+    world.rain(dt);
+    world.pour_sand(dt);
+    world.integrate(dt);
+    world.sweep();
+    world.smash();
+
+    let mut residuals = Vec::with_capacity(iterations);
+    world.solve_constraints_with(|world| residuals.push(Residual::of(world)));
+    residuals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn residuals_shrink_with_each_iteration() {
+        let mut world = World::new(800.0);
+        world.add_cloth(Vec2::new(100.0, 50.0), 12, 8, 4);
+        // start it falling, so the first iterations have something to fix
+        for _ in 0..5 {
+            world.step(world.params.dt);
+        }
+
+        let residuals = measure(&world, 20);
+        assert_eq!(residuals.len(), 20);
+        let (first, last) = (residuals[0], residuals[19]);
+        assert!(last.max < first.max && last.mean < first.mean, "{:?} then {:?}", first, last);
+        assert!(residuals.iter().all(|residual| residual.mean <= residual.max));
+    }
+}
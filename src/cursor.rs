@@ -0,0 +1,68 @@
+use crate::replay::StepInput;
+use egui_macroquad::macroquad::prelude::Vec2;
+use std::collections::VecDeque;
+
+/// Steps of movement the knife keeps cutting along, so a fast swipe sampled at
+/// a low frame rate still catches rope that swings into where it just was.
+pub const KNIFE_TRAIL_STEPS: usize = 6;
+
+/// What a cursor did on earlier steps, which wind and the knife are measured against.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CursorHistory {
+    /// Where the cursor was for the previous step.
+    pub last: Option<Vec2>,
+    /// Where the knife has been on recent steps, oldest first and ending at
+    /// `last`, or empty if it wasn't cutting last step.
+    pub trail: Vec<Vec2>,
+    /// How many of `trail`'s points each of those steps added, oldest first.
+    pub trail_steps: VecDeque<usize>,
+    /// Where the cursor passed through between `last` and this step's
+    /// position, oldest first, when there's more to its path than a straight
+    /// line; see `motion`. Cleared once the step has run.
+    pub between: Vec<Vec2>,
+    /// The knife's radius last step.
+    pub knife_radius: f32,
+    /// Whether the knife has cut anything since it was last lifted, which
+    /// single-cut swipes stop at.
+    pub swipe_cut: bool,
+}
+
+impl CursorHistory {
+    /// The way the cursor went to get to `cursor` this step: from where it
+    /// was last step, if it was anywhere, through `between`.
+    pub fn moved(&self, cursor: Vec2) -> Vec<Vec2> {
+        self.last.into_iter().chain(self.between.iter().copied()).chain([cursor]).collect()
+    }
+
+    /// The polyline to cut along for a step with the knife at `cursor`: the
+    /// trail, or where the cursor was last step when the knife just started,
+    /// and on through `between`.
+    pub fn knife_path(&self, cursor: Vec2) -> Vec<Vec2> {
+        if self.trail.is_empty() {
+            return self.moved(cursor);
+        }
+        self.trail.iter().chain(&self.between).copied().chain([cursor]).collect()
+    }
+
+    /// Moves along once a step with `input` has run.
+    pub fn advance(&mut self, input: &StepInput) {
+        if input.knife {
+            self.trail = self.knife_path(input.cursor);
+            self.trail_steps.push_back(self.between.len() + 1);
+            if self.trail_steps.len() > KNIFE_TRAIL_STEPS {
+                self.trail_steps.pop_front();
+            }
+            // the steps kept, and the point they start from
+            let kept = self.trail_steps.iter().sum::<usize>() + 1;
+            let excess = self.trail.len().saturating_sub(kept);
+            self.trail.drain(..excess);
+        } else {
+            self.trail.clear();
+            self.trail_steps.clear();
+            self.swipe_cut = false;
+        }
+        self.between.clear();
+        self.last = Some(input.cursor);
+        self.knife_radius = input.knife_radius;
+    }
+}
@@ -0,0 +1,96 @@
+use crate::constraint::Constraint;
+use crate::node::Node;
+use crate::world::World;
+use crate::TARGET_DIST;
+use glam::Vec2;
+
+/// Rest length a newly dispensed link starts at.
+pub const START_LENGTH: f32 = 0.1 * TARGET_DIST;
+
+/// Feeds new rope out of a node, usually a fixed one. The newest node hangs
+/// from the dispenser on a link that lengthens as rope is fed, and once it's
+/// more than a full rest length long a new node takes its place at the dispenser.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dispenser {
+    /// The node rope comes out of.
+    pub anchor: usize,
+    /// Rope fed per unit of time.
+    pub speed: f32,
+    /// The newest node, linked to `anchor` by the link being fed, if there's rope yet.
+    pub feed: Option<usize>,
+}
+
+impl Dispenser {
+    pub fn new(anchor: usize, speed: f32) -> Self {
+        Self { anchor, speed, feed: None }
+    }
+
+    /// Feeds `speed * dt` more rope. New nodes are added with `World::spawn`
+    /// and links are only ever appended, so nothing else's index changes. With
+    /// no room for another node, the rope stops feeding until there is.
+    pub fn dispense(&mut self, world: &mut World, dt: f32) {
+        let anchor = world.arena[self.anchor].pos;
+        let link = self.feed.and_then(|feed| {
+            let ends = [(self.anchor, feed), (feed, self.anchor)];
+            world.constraints.iter().position(|c| ends.contains(&(c.a, c.b)))
+        });
+        let Some(link) = link else {
+            // nothing hanging yet, or the rope was cut at the dispenser
+            self.feed = self.spawn(world, anchor + Vec2::new(0.0, START_LENGTH), START_LENGTH);
+            return;
+        };
+
+        let full = TARGET_DIST + START_LENGTH;
+        let constraint = &mut world.constraints[link];
+        constraint.rest_length += self.speed * dt;
+        if constraint.rest_length < full {
+            return;
+        }
+
+        // the link is full length, so hand its end over to a new node at the
+        // dispenser, splitting it so the rope's total length doesn't jump
+        let extra = constraint.rest_length - full;
+        let feed = self.feed.unwrap();
+        let direction = (world.arena[feed].pos - anchor).try_normalize().unwrap_or(Vec2::Y);
+        let Some(new) = self.spawn(world, anchor + direction * START_LENGTH, START_LENGTH + extra) else {
+            world.constraints[link].rest_length = full;
+            return;
+        };
+        let constraint = &mut world.constraints[link];
+        constraint.rest_length = TARGET_DIST;
+        (constraint.a, constraint.b) = (new, feed);
+        self.feed = Some(new);
+    }
+
+    /// Spawns a node at `pos`, linked to the anchor with `rest_length`, and
+    /// returns its index, or None if the world has no room for it.
+    fn spawn(&self, world: &mut World, pos: Vec2, rest_length: f32) -> Option<usize> {
+        let node = world.spawn(Node::with_pos_and_mass(pos, 1.0))?;
+        let break_threshold = TARGET_DIST * world.params.break_threshold;
+        world.constraints.push(Constraint { a: self.anchor, b: node, rest_length, break_threshold });
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_a_chain_from_the_anchor() {
+        let mut world = World::new(500.0);
+        world.arena.push(Node { fixed: true, ..Node::default() });
+        let mut dispenser = Dispenser::new(0, TARGET_DIST);
+
+        // the first call hangs a node, then each unit of time adds a rest length
+        for _ in 0..=30 {
+            dispenser.dispense(&mut world, 0.1);
+        }
+        assert_eq!(world.arena.len(), 5);
+        let chain: Vec<(usize, usize)> = world.constraints.iter().map(|c| (c.a, c.b)).collect();
+        assert_eq!(chain, [(2, 1), (3, 2), (4, 3), (0, 4)]);
+        assert_eq!(dispenser.feed, Some(4));
+        let total: f32 = world.constraints.iter().map(|c| c.rest_length).sum();
+        assert!((total - (START_LENGTH + 3.0 * TARGET_DIST)).abs() < 1e-3, "{}", total);
+    }
+}
@@ -0,0 +1,129 @@
+//! Finding where two worlds that should match part ways, for hunting down
+//! nondeterminism and solver regressions. A checksum says two runs differ;
+//! this says which node or link went first, and by how much.
+
+use crate::world::World;
+use glam::Vec2;
+use std::fmt;
+
+/// The first thing found differing between two worlds, with how it reads in each.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    pub part: Part,
+    /// What about the part differs, like `pos` or `rest_length`.
+    pub field: &'static str,
+    pub a: String,
+    pub b: String,
+}
+
+/// Where in a world a difference is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Part {
+    /// How many nodes there are.
+    Nodes,
+    Node(usize),
+    /// How many links there are.
+    Constraints,
+    Constraint(usize),
+    Rng,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.part {
+            Part::Nodes => write!(f, "node count")?,
+            Part::Node(i) => write!(f, "node {} {}", i, self.field)?,
+            Part::Constraints => write!(f, "link count")?,
+            Part::Constraint(i) => write!(f, "link {} {}", i, self.field)?,
+            Part::Rng => write!(f, "rng state")?,
+        }
+        write!(f, ": {} vs {}", self.a, self.b)
+    }
+}
+
+/// Compares nodes in order, then links, then the random number generator,
+/// and returns the first difference: positions, velocities, and lengths
+/// further apart than `tolerance`, or anything else not exactly equal. A NaN
+/// on either side is always a difference, so a world that blew up is caught.
+pub fn first_difference(a: &World, b: &World, tolerance: f32) -> Option<Difference> {
+    // written so a NaN fails them, while matching infinite thresholds pass
+    let close = |u: f32, v: f32| u == v || (u - v).abs() <= tolerance;
+    let near = |u: Vec2, v: Vec2| u == v || u.distance(v) <= tolerance;
+    let differ = |part, field, a: &dyn fmt::Display, b: &dyn fmt::Display| {
+        Some(Difference { part, field, a: a.to_string(), b: b.to_string() })
+    };
+    if a.arena.len() != b.arena.len() {
+        return differ(Part::Nodes, "count", &a.arena.len(), &b.arena.len());
+    }
+    for (i, (m, n)) in a.arena.iter().zip(&b.arena).enumerate() {
+        let vectors: [(&str, Vec2, Vec2); 3] =
+            [("pos", m.pos, n.pos), ("last_pos", m.last_pos, n.last_pos), ("vel", m.vel, n.vel)];
+        if let Some((field, u, v)) = vectors.into_iter().find(|(_, u, v)| !near(*u, *v)) {
+            return differ(Part::Node(i), field, &u, &v);
+        }
+        if !close(m.mass, n.mass) {
+            return differ(Part::Node(i), "mass", &m.mass, &n.mass);
+        }
+        if m.fixed != n.fixed {
+            return differ(Part::Node(i), "fixed", &m.fixed, &n.fixed);
+        }
+        if m.layer != n.layer {
+            return differ(Part::Node(i), "layer", &m.layer, &n.layer);
+        }
+    }
+
+    if a.constraints.len() != b.constraints.len() {
+        return differ(Part::Constraints, "count", &a.constraints.len(), &b.constraints.len());
+    }
+    for (i, (m, n)) in a.constraints.iter().zip(&b.constraints).enumerate() {
+        if (m.a, m.b) != (n.a, n.b) {
+            return differ(Part::Constraint(i), "ends", &format!("{}-{}", m.a, m.b), &format!("{}-{}", n.a, n.b));
+        }
+        if !close(m.rest_length, n.rest_length) {
+            return differ(Part::Constraint(i), "rest_length", &m.rest_length, &n.rest_length);
+        }
+        if !close(m.break_threshold, n.break_threshold) {
+            return differ(Part::Constraint(i), "break_threshold", &m.break_threshold, &n.break_threshold);
+        }
+    }
+
+    if a.rng != b.rng {
+        return differ(Part::Rng, "state", &a.rng.state(), &b.rng.state());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+
+    #[test]
+    fn finds_the_node_that_went_first() {
+        let mut a = scene::cloth(800.0, 600.0);
+        let mut b = a.clone();
+        assert_eq!(first_difference(&a, &b, 0.0), None);
+
+        b.arena[7].pos.x += 0.5;
+        b.arena[30].pos.x += 5.0;
+        let found = first_difference(&a, &b, 1e-3).unwrap();
+        assert_eq!((found.part, found.field), (Part::Node(7), "pos"));
+        assert_eq!(first_difference(&a, &b, 1.0).unwrap().part, Part::Node(30));
+
+        // a blown up node never matches, however loose the tolerance
+        b = a.clone();
+        b.arena[12].vel = Vec2::NAN;
+        let found = first_difference(&a, &b, f32::INFINITY).unwrap();
+        assert_eq!((found.part, found.field), (Part::Node(12), "vel"));
+        assert!(first_difference(&b, &b.clone(), 0.0).is_some());
+
+        b = a.clone();
+        b.constraints.pop();
+        assert_eq!(first_difference(&a, &b, 1.0).unwrap().part, Part::Constraints);
+
+        b = a.clone();
+        a.step(a.params.dt);
+        b.step(b.params.dt);
+        assert_eq!(first_difference(&a, &b, 0.0), None);
+    }
+}
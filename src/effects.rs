@@ -0,0 +1,103 @@
+//! Short-lived cosmetic effects, never fed back into the simulation. Each
+//! spark and strand is an entity in the demo's `Entities`, with the
+//! components saying what it is and how it moves, so a new kind of effect is a
+//! new mix of them, and `update` and `draw` are a system apiece over whatever
+//! has the parts they act on.
+
+use clothsim::Rng;
+use egui_macroquad::macroquad::prelude::*;
+
+const BURST_COUNT: usize = 12;
+const BURST_SPEED: f32 = 40.0;
+const PARTICLE_LIFETIME: f32 = 4.0;
+const PARTICLE_RADIUS: f32 = 2.5;
+const PARTICLE_DRAG: f32 = 0.9;
+const FRAY_STRANDS: usize = 5;
+/// Radians either side of straight on that a frayed end's strands splay out.
+const FRAY_SPREAD: f32 = 0.7;
+const STRAND_LENGTH: f32 = 7.0;
+const STRAND_LIFETIME: f32 = 0.8;
+
+/// Where an effect is.
+struct Pos(Vec2);
+
+/// How fast a particle drifts, slowed by `PARTICLE_DRAG`. Particles are drawn as dots.
+struct Vel(Vec2);
+
+/// Where a loose fiber sticking out of a frayed end points, as long as it
+/// gets. Strands are drawn growing out then fading.
+struct Reach(Vec2);
+
+/// How long an effect has been around, and how long it lasts before it's despawned.
+struct Age {
+    age: f32,
+    lifetime: f32,
+}
+
+impl Age {
+    fn new(lifetime: f32) -> Self {
+        Self { age: 0.0, lifetime }
+    }
+
+    /// How far through its life it is, from 0 when new to 1 when gone.
+    fn life(&self) -> f32 {
+        self.age / self.lifetime
+    }
+}
+
+/// Spawns effects into the entities it's given.
+#[derive(Default)]
+pub struct Effects {
+    rng: Rng,
+}
+
+impl Effects {
+    pub fn burst(&mut self, entities: &mut hecs::World, pos: Vec2) {
+        for _ in 0..BURST_COUNT {
+            let angle = self.rng.range(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range(0.3, 1.0) * BURST_SPEED;
+            let vel = Vec2::new(angle.cos(), angle.sin()) * speed;
+            entities.spawn((Pos(pos), Vel(vel), Age::new(PARTICLE_LIFETIME)));
+        }
+    }
+
+    /// Splays a few strands out of an end at `pos`, around the direction `toward`.
+    pub fn fray(&mut self, entities: &mut hecs::World, pos: Vec2, toward: Vec2) {
+        let heading = toward.y.atan2(toward.x);
+        for _ in 0..FRAY_STRANDS {
+            let angle = heading + self.rng.range(-FRAY_SPREAD, FRAY_SPREAD);
+            let length = self.rng.range(0.5, 1.0) * STRAND_LENGTH;
+            let reach = Vec2::new(angle.cos(), angle.sin()) * length;
+            entities.spawn((Pos(pos), Reach(reach), Age::new(STRAND_LIFETIME)));
+        }
+    }
+
+    pub fn update(&mut self, entities: &mut hecs::World, dt: f32) {
+        for (_, (pos, vel)) in entities.query_mut::<(&mut Pos, &mut Vel)>() {
+            pos.0 += vel.0 * dt;
+            vel.0 *= PARTICLE_DRAG.powf(dt);
+        }
+
+        let mut expired = Vec::new();
+        for (entity, age) in entities.query_mut::<&mut Age>() {
+            age.age += dt;
+            if age.age >= age.lifetime {
+                expired.push(entity);
+            }
+        }
+        for entity in expired {
+            entities.despawn(entity).expect("just found");
+        }
+    }
+
+    pub fn draw(entities: &hecs::World, color: Color) {
+        let faded = |age: &Age| Color { a: color.a * (1.0 - age.life()), ..color };
+        for (_, (pos, age)) in entities.query::<(&Pos, &Age)>().with::<&Vel>().iter() {
+            draw_circle(pos.0.x, pos.0.y, PARTICLE_RADIUS, faded(age));
+        }
+        for (_, (pos, reach, age)) in entities.query::<(&Pos, &Reach, &Age)>().iter() {
+            let end = pos.0 + reach.0 * (2.0 * age.life()).min(1.0);
+            draw_line(pos.0.x, pos.0.y, end.x, end.y, 1.0, faded(age));
+        }
+    }
+}
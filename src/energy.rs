@@ -0,0 +1,81 @@
+use crate::world::World;
+
+/// The mechanical energy of a world's free nodes, split by where it is.
+/// Fixed nodes are left out: nothing the solver does can change theirs.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Energy {
+    /// From the nodes' speed, `mv²/2`.
+    pub kinetic: f32,
+    /// From the nodes' height, measured up from the ground.
+    pub gravitational: f32,
+    /// Stored in stretched bands. Constraints are rigid, so they hold none.
+    pub elastic: f32,
+}
+
+impl Energy {
+    pub fn of(world: &World) -> Self {
+        let free = || world.arena.iter().filter(|node| !node.fixed);
+        Self {
+            kinetic: free().map(|node| 0.5 * node.mass * node.vel.length_squared()).sum(),
+            gravitational: free()
+                .map(|node| node.weight(world.params.gravity) * (world.ground_y - node.pos.y))
+                .sum(),
+            elastic: world.bands.iter().map(|band| band.energy(&world.arena, &world.wrap)).sum(),
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.kinetic + self.gravitational + self.elastic
+    }
+}
+
+/// How much energy the solver added and took away, summed over the steps
+/// it's recorded for. A lossless solver would leave both at zero; drag and
+/// other forces aren't counted, only what projecting constraints and
+/// collisions does to the energy the forces left.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct EnergyFlow {
+    pub injected: f32,
+    pub dissipated: f32,
+}
+
+impl EnergyFlow {
+    /// Adds the change from `before` the solver ran to `after` it.
+    pub fn record(&mut self, before: &Energy, after: &Energy) {
+        let change = after.total() - before.total();
+        if change > 0.0 {
+            self.injected += change;
+        } else {
+            self.dissipated -= change;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::band::Band;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    #[test]
+    fn splits_energy_and_tallies_changes() {
+        let mut world = World::new(100.0);
+        world.arena.push(Node { vel: Vec2::new(3.0, 4.0), ..Node::with_pos_and_mass(Vec2::new(0.0, 90.0), 2.0) });
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(Vec2::ZERO, 1.0) });
+        world.bands.push(Band { a: 0, b: 1, rest_length: 10.0, stiffness: 2.0, ..Band::default() });
+
+        let energy = Energy::of(&world);
+        assert_eq!(energy.kinetic, 25.0);
+        // only the free node counts, 10 above the ground
+        assert_eq!(energy.gravitational, 2.0 * world.params.gravity * 10.0);
+        // the band is 90 from end to end and stretched by 80
+        assert!((energy.elastic - 0.5 * 2.0 * 80.0 * 80.0).abs() < 0.1, "{}", energy.elastic);
+
+        let mut flow = EnergyFlow::default();
+        let slower = Energy { kinetic: 5.0, ..energy };
+        flow.record(&energy, &slower);
+        flow.record(&slower, &Energy { kinetic: 10.0, ..energy });
+        assert_eq!(flow, EnergyFlow { injected: 5.0, dissipated: 20.0 });
+    }
+}
@@ -0,0 +1,396 @@
+//! The demo's ECS. Everything in the scene is an entity in one `hecs::World`:
+//! a `Node`, `Link`, `Obstacle`, or `Emitter` for each object in
+//! `clothsim::World`, and the effects' sparks and strands alongside them.
+//! What the demo is doing with something (dragging it, inspecting it, tracing
+//! its path) is a component on its entity, so when the step takes nodes and
+//! links out, `sync` despawns their entities and whatever was on them goes too,
+//! instead of each piece of state renumbering its own indices.
+//!
+//! The solver's arrays stay the source of truth: `sync` follows them after
+//! every step and every frame, and the entities never feed back into a step.
+
+use crate::effects::Effects;
+use clothsim::world::index_after_removal;
+use clothsim::{SimEvent, StrainedLink, World};
+use egui_macroquad::macroquad::prelude::*;
+use hecs::{Component, Entity};
+
+/// How far past a link that no longer matches `sync` looks for the next one
+/// that does, before taking the constraint for a new one. Steps remove far
+/// fewer links than this at a time; a whole different scene swapped in just
+/// gets new entities.
+const LINK_LOOKAHEAD: usize = 64;
+
+/// The node at this index in `World::arena`, renumbered as nodes before it are removed.
+pub struct Node(pub usize);
+
+/// A constraint, by the entities of the nodes it links.
+pub struct Link {
+    pub ends: [Entity; 2],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Ball,
+    Platform,
+    Rail,
+    Slider,
+    Rotor,
+}
+
+impl ObstacleKind {
+    const ALL: [Self; 5] = [Self::Ball, Self::Platform, Self::Rail, Self::Slider, Self::Rotor];
+
+    fn count(self, world: &World) -> usize {
+        match self {
+            Self::Ball => world.balls.len(),
+            Self::Platform => world.platforms.len(),
+            Self::Rail => world.rails.len(),
+            Self::Slider => world.sliders.len(),
+            Self::Rotor => world.rotors.len(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Ball => "balls",
+            Self::Platform => "platforms",
+            Self::Rail => "rails",
+            Self::Slider => "sliders",
+            Self::Rotor => "rotors",
+        }
+    }
+}
+
+/// Something in the scene for nodes to hit or run along, one entity per
+/// entry in its `Vec` on `World`.
+pub struct Obstacle(pub ObstacleKind);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EmitterKind {
+    Dispenser,
+    Rain,
+    Well,
+    Sink,
+}
+
+impl EmitterKind {
+    const ALL: [Self; 4] = [Self::Dispenser, Self::Rain, Self::Well, Self::Sink];
+
+    fn count(self, world: &World) -> usize {
+        match self {
+            Self::Dispenser => world.dispensers.len(),
+            Self::Rain => usize::from(world.rain.is_some()),
+            Self::Well => world.wells.len(),
+            Self::Sink => world.sinks.len(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Dispenser => "dispensers",
+            Self::Rain => "rain clouds",
+            Self::Well => "wells",
+            Self::Sink => "sinks",
+        }
+    }
+}
+
+/// Something in the scene that adds nodes, takes them away, or pulls them in.
+pub struct Emitter(pub EmitterKind);
+
+/// The node the editor is dragging.
+#[derive(Default)]
+pub struct Dragged;
+
+/// The node last clicked in the editor, shown in the Node window.
+#[derive(Default)]
+pub struct Inspected;
+
+/// The node under the pointer in the Graph window.
+#[derive(Default)]
+pub struct Hovered;
+
+/// The node the Build tool's next link starts from.
+#[derive(Default)]
+pub struct BuildFrom;
+
+/// A node picked for its trajectory to be recorded, numbered in the order they were picked.
+struct Traced(u64);
+
+/// The link that last set off the strain alert, with the time it did.
+#[derive(Clone, Copy)]
+pub struct Strained {
+    pub link: StrainedLink,
+    pub since: f64,
+}
+
+#[derive(Default)]
+pub struct Entities {
+    entities: hecs::World,
+    /// Each node's entity, by its index in `World::arena`.
+    nodes: Vec<Entity>,
+    /// Each constraint's entity, by its index in `World::constraints`, with
+    /// the indices of the nodes it linked as of the last `sync`.
+    links: Vec<(Entity, [usize; 2])>,
+    obstacles: [Vec<Entity>; ObstacleKind::ALL.len()],
+    emitters: [Vec<Entity>; EmitterKind::ALL.len()],
+    /// How many nodes have been picked for tracing, to number the next.
+    traced: u64,
+    effects: Effects,
+}
+
+impl Entities {
+    /// Despawns everything, for a new scene. The next `sync` spawns its entities.
+    pub fn clear(&mut self) {
+        *self = Self { effects: std::mem::take(&mut self.effects), ..Self::default() };
+    }
+
+    /// Brings the entities in line with `world` after `events`: despawning
+    /// the nodes they removed, and then spawning and despawning for whatever
+    /// else came and went, which edits and restored snapshots do without
+    /// events.
+    pub fn sync(&mut self, world: &World, events: &[SimEvent]) {
+        for event in events {
+            match *event {
+                SimEvent::NodeConsumed { node, .. } | SimEvent::NodeDespawned { node, .. } => self.remove_node(node),
+                // it's a new node now, so let go of the old one
+                SimEvent::NodeRecycled { node, .. } => {
+                    if let Some(&entity) = self.nodes.get(node) {
+                        let _ = self.entities.remove_one::<Dragged>(entity);
+                        let _ = self.entities.remove_one::<Inspected>(entity);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fit(&mut self.entities, &mut self.nodes, world.arena.len(), Node);
+        self.sync_links(world);
+        for (kind, table) in ObstacleKind::ALL.into_iter().zip(self.obstacles.iter_mut()) {
+            fit(&mut self.entities, table, kind.count(world), |_| Obstacle(kind));
+        }
+        for (kind, table) in EmitterKind::ALL.into_iter().zip(self.emitters.iter_mut()) {
+            fit(&mut self.entities, table, kind.count(world), |_| Emitter(kind));
+        }
+    }
+
+    /// Despawns node `i` and renumbers the ones after it, as the step did.
+    /// Its links are left for `sync_links`, which finds them gone.
+    fn remove_node(&mut self, i: usize) {
+        if i >= self.nodes.len() {
+            return;
+        }
+        let entity = self.nodes.remove(i);
+        self.entities.despawn(entity).expect("in the table");
+        for (j, &entity) in self.nodes.iter().enumerate().skip(i) {
+            self.entities.get::<&mut Node>(entity).expect("every node has one").0 = j;
+        }
+        for (_, ends) in self.links.iter_mut() {
+            for end in ends.iter_mut() {
+                *end = index_after_removal(*end, i).unwrap_or(usize::MAX);
+            }
+        }
+    }
+
+    /// Matches the link entities to `world`'s constraints, which keep their
+    /// order as some are removed and others added at the end: a link keeps
+    /// its entity while a constraint between the same nodes is found for it.
+    fn sync_links(&mut self, world: &World) {
+        let old = std::mem::take(&mut self.links);
+        let mut next = 0;
+        for constraint in world.constraints.iter() {
+            let ends = [constraint.a, constraint.b];
+            let window = &old[next..(next + LINK_LOOKAHEAD).min(old.len())];
+            match window.iter().position(|&(_, old_ends)| old_ends == ends) {
+                Some(skipped) => {
+                    for &(entity, _) in &old[next..next + skipped] {
+                        self.entities.despawn(entity).expect("in the table");
+                    }
+                    self.links.push(old[next + skipped]);
+                    next += skipped + 1;
+                }
+                None => {
+                    let link = Link { ends: [self.node(constraint.a), self.node(constraint.b)] };
+                    self.links.push((self.entities.spawn((link,)), ends));
+                }
+            }
+        }
+        for &(entity, _) in &old[next..] {
+            self.entities.despawn(entity).expect("in the table");
+        }
+    }
+
+    /// Node `i`'s entity, spawning any missing up to it for nodes added since the last `sync`.
+    fn node(&mut self, i: usize) -> Entity {
+        let len = (i + 1).max(self.nodes.len());
+        fit(&mut self.entities, &mut self.nodes, len, Node);
+        self.nodes[i]
+    }
+
+    /// The node marked with `T`, if any.
+    pub fn selected<T: Component>(&self) -> Option<usize> {
+        self.entities.query::<&Node>().with::<&T>().iter().next().map(|(_, node)| node.0)
+    }
+
+    /// Moves the `T` mark to `node`, or takes it off whichever node has it.
+    pub fn select<T: Component + Default>(&mut self, node: Option<usize>) {
+        self.remove_all::<T>();
+        if let Some(i) = node {
+            let entity = self.node(i);
+            self.entities.insert_one(entity, T::default()).expect("just found");
+        }
+    }
+
+    /// Starts or stops tracing node `i`.
+    pub fn toggle_traced(&mut self, i: usize) {
+        let entity = self.node(i);
+        if self.entities.remove_one::<Traced>(entity).is_err() {
+            self.entities.insert_one(entity, Traced(self.traced)).expect("just found");
+            self.traced += 1;
+        }
+    }
+
+    /// The traced nodes, in the order they were picked.
+    pub fn traced(&self) -> Vec<usize> {
+        let mut traced: Vec<(u64, usize)> =
+            self.entities.query::<(&Node, &Traced)>().iter().map(|(_, (node, traced))| (traced.0, node.0)).collect();
+        traced.sort_unstable();
+        traced.into_iter().map(|(_, node)| node).collect()
+    }
+
+    pub fn untrace_all(&mut self) {
+        self.remove_all::<Traced>();
+    }
+
+    /// Puts the strain warning on `link`, taking it off whichever link had it.
+    /// Nothing has it if the link is already gone.
+    pub fn warn(&mut self, link: StrainedLink, since: f64) {
+        self.remove_all::<Strained>();
+        let same = |ends: &[usize; 2]| *ends == [link.a, link.b] || *ends == [link.b, link.a];
+        if let Some(&(entity, _)) = self.links.iter().find(|(_, ends)| same(ends)) {
+            self.entities.insert_one(entity, Strained { link, since }).expect("in the table");
+        }
+    }
+
+    /// The strain warning, with its link's ends as they're numbered now, while the link's still there.
+    pub fn strain_warning(&self) -> Option<Strained> {
+        let mut query = self.entities.query::<(&Link, &Strained)>();
+        let (_, (link, strained)) = query.iter().next()?;
+        let [a, b] = link.ends.map(|end| self.entities.get::<&Node>(end).expect("links outlive their nodes' entities").0);
+        Some(Strained { link: StrainedLink { a, b, ..strained.link }, ..*strained })
+    }
+
+    pub fn dismiss_warning(&mut self) {
+        self.remove_all::<Strained>();
+    }
+
+    /// Takes `T` off every entity that has it.
+    fn remove_all<T: Component>(&mut self) {
+        let marked: Vec<Entity> = self.entities.query_mut::<&T>().into_iter().map(|(entity, _)| entity).collect();
+        for entity in marked {
+            self.entities.remove_one::<T>(entity).expect("just found");
+        }
+    }
+
+    /// How many of each kind of thing were in the scene at the last `sync`,
+    /// leaving out the kinds of obstacle and emitter there are none of.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} nodes", self.nodes.len()), format!("{} links", self.links.len())];
+        let mut query = self.entities.query::<&Obstacle>();
+        let obstacles: Vec<ObstacleKind> = query.iter().map(|(_, obstacle)| obstacle.0).collect();
+        for kind in ObstacleKind::ALL {
+            let count = obstacles.iter().filter(|&&obstacle| obstacle == kind).count();
+            if count > 0 {
+                parts.push(format!("{} {}", count, kind.name()));
+            }
+        }
+        let mut query = self.entities.query::<&Emitter>();
+        let emitters: Vec<EmitterKind> = query.iter().map(|(_, emitter)| emitter.0).collect();
+        for kind in EmitterKind::ALL {
+            let count = emitters.iter().filter(|&&emitter| emitter == kind).count();
+            if count > 0 {
+                parts.push(format!("{} {}", count, kind.name()));
+            }
+        }
+        parts.join(", ")
+    }
+
+    pub fn burst(&mut self, pos: Vec2) {
+        self.effects.burst(&mut self.entities, pos);
+    }
+
+    /// Splays a few strands out of an end at `pos`, around the direction `toward`.
+    pub fn fray(&mut self, pos: Vec2, toward: Vec2) {
+        self.effects.fray(&mut self.entities, pos, toward);
+    }
+
+    /// Moves the effects on by `dt`, despawning the ones that have run their course.
+    pub fn update(&mut self, dt: f32) {
+        self.effects.update(&mut self.entities, dt);
+    }
+
+    /// Rings the traced nodes in `world`, and with a wider ring the one hovered
+    /// in the Graph window, then draws the effects over them.
+    pub fn draw(&self, world: &World, radius: f32, width: f32, color: Color) {
+        let ring = |node: &Node, radius: f32| {
+            if let Some(node) = world.arena.get(node.0) {
+                draw_circle_lines(node.pos.x, node.pos.y, radius, width, color);
+            }
+        };
+        for (_, node) in self.entities.query::<&Node>().with::<&Traced>().iter() {
+            ring(node, radius * 2.0);
+        }
+        for (_, node) in self.entities.query::<&Node>().with::<&Hovered>().iter() {
+            ring(node, radius * 3.0);
+        }
+        Effects::draw(&self.entities, color);
+    }
+}
+
+/// Spawns and despawns at the end of `table` until it has `len` entities,
+/// each new one made of `component` given its index.
+fn fit<C: Component>(entities: &mut hecs::World, table: &mut Vec<Entity>, len: usize, component: impl Fn(usize) -> C) {
+    for entity in table.drain(len.min(table.len())..) {
+        entities.despawn(entity).expect("in the table");
+    }
+    while table.len() < len {
+        table.push(entities.spawn((component(table.len()),)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clothsim::{Constraint, Node as SimNode};
+
+    #[test]
+    fn marks_follow_nodes_and_links_past_removals() {
+        let mut world = World::new(500.0);
+        world.arena.extend([0.0, 50.0, 100.0].map(|x| SimNode::with_pos_and_mass(Vec2::new(x, 0.0), 1.0)));
+        for (a, b) in [(0, 1), (1, 2)] {
+            world.constraints.push(Constraint { a, b, rest_length: 50.0, break_threshold: 100.0 });
+        }
+        let mut entities = Entities::default();
+        entities.sync(&world, &[]);
+        entities.select::<Inspected>(Some(2));
+        entities.toggle_traced(2);
+        entities.toggle_traced(0);
+        let link = StrainedLink { a: 1, b: 2, pos: Vec2::new(75.0, 0.0), fraction: 0.9 };
+        entities.warn(link, 0.0);
+
+        let gone = world.remove_node(0);
+        entities.sync(&world, &[SimEvent::NodeDespawned { node: 0, pos: gone.pos }]);
+        assert_eq!(entities.selected::<Inspected>(), Some(1));
+        assert_eq!(entities.traced(), [1]);
+        let warning = entities.strain_warning().unwrap();
+        assert_eq!((warning.link.a, warning.link.b), (0, 1));
+        assert_eq!(entities.summary(), "2 nodes, 1 links");
+
+        // the warned link goes with its end
+        let gone = world.remove_node(0);
+        entities.sync(&world, &[SimEvent::NodeDespawned { node: 0, pos: gone.pos }]);
+        assert!(entities.strain_warning().is_none());
+        assert_eq!(entities.selected::<Inspected>(), Some(0));
+    }
+}
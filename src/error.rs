@@ -1,2 +1,57 @@
+use std::{fmt, io};
+
 #[derive(Debug)]
-pub enum SimError {}
+pub enum SimError {
+    /// A constraint refers to a node that isn't in the arena.
+    InvalidConstraintIndex { constraint: usize, node: usize, nodes: usize },
+    /// A node's position stopped being finite, usually from too large a timestep.
+    Diverged { step: u64, node: usize },
+    /// A scene couldn't be built from `scene`, a built-in name or file path.
+    SceneLoad { scene: String, message: String },
+    /// A config file exists but couldn't be parsed.
+    Config { path: String, message: String },
+    /// A replay file's contents couldn't be parsed.
+    InvalidReplay { path: String, message: String },
+    /// A script didn't compile or failed while running.
+    Script { path: String, message: String },
+    /// The host of a shared session went away.
+    #[cfg(feature = "netplay")]
+    Disconnected { addr: String },
+    Io {
+        /// What was being done, like "read" or "save".
+        action: &'static str,
+        path: String,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimError::InvalidConstraintIndex { constraint, node, nodes } => write!(
+                f,
+                "constraint {} refers to node {}, but there are only {} nodes",
+                constraint, node, nodes
+            ),
+            SimError::Diverged { step, node } => {
+                write!(f, "node {} diverged at step {}; try a smaller dt or more iterations", node, step)
+            }
+            SimError::SceneLoad { scene, message } => write!(f, "couldn't load scene {}: {}", scene, message),
+            SimError::Config { path, message } => write!(f, "invalid {}: {}", path, message),
+            SimError::InvalidReplay { path, message } => write!(f, "invalid replay {}: {}", path, message),
+            SimError::Script { path, message } => write!(f, "script {}: {}", path, message),
+            #[cfg(feature = "netplay")]
+            SimError::Disconnected { addr } => write!(f, "lost the connection to the session at {}", addr),
+            SimError::Io { action, path, source } => write!(f, "failed to {} {}: {}", action, path, source),
+        }
+    }
+}
+
+impl std::error::Error for SimError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
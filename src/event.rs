@@ -0,0 +1,38 @@
+use crate::alert::StrainedLink;
+use crate::constraint::SeveredLink;
+use glam::Vec2;
+
+/// Something that happened during a step, queued on the world in the order it occurred.
+#[derive(Copy, Clone, Debug)]
+pub enum SimEvent {
+    /// A constraint stretched past its break threshold and snapped.
+    ConstraintBroken(SeveredLink),
+    /// A constraint crossed by a `World::cut` stroke.
+    ConstraintCut(SeveredLink),
+    /// A node slowed below `SLEEP_SPEED` after moving.
+    NodeSlept { node: usize },
+    /// `World::split_path` split `node` in two at `pos`, moving its links on
+    /// one side of the blade to the new node `copy`.
+    NodeSplit { node: usize, copy: usize, pos: Vec2 },
+    /// A link snapped and `node`, which was at `pos`, shattered, throwing off
+    /// the new node `fragment`, one event for each fragment after the first.
+    NodeShattered { node: usize, fragment: usize, pos: Vec2 },
+    /// A link was severed with `fray` on and `node`, one of its ends, which
+    /// was at `pos`, split in two, the new node `copy` taking the links
+    /// reaching across to the other side.
+    NodeFrayed { node: usize, copy: usize, pos: Vec2 },
+    /// A sink consumed `node`, which was at `pos`. It's gone from the world, and
+    /// nodes after it, in the world and in any later event, are one index lower.
+    NodeConsumed { node: usize, pos: Vec2 },
+    /// `World::cleanup` removed `node`, which was at `pos`, for falling too far
+    /// or going too long without a link. Later indices shift down as for `NodeConsumed`.
+    NodeDespawned { node: usize, pos: Vec2 },
+    /// `World::spawn` was at its budget's cap and took over `node`, which was
+    /// at `pos`, for a new node. Its index and every other node's stay the same.
+    NodeRecycled { node: usize, pos: Vec2 },
+    /// A node reached the ground, moving down at `speed`.
+    Collision { node: usize, pos: Vec2, speed: f32 },
+    /// A constraint got close to breaking. Raised by `StrainAlert::check`
+    /// between steps rather than by the world itself.
+    StrainAlert(StrainedLink),
+}
@@ -0,0 +1,208 @@
+//! Evolving walkers: a population of muscle settings for one body, each
+//! scored by how far it carries the body to the right in a fixed number of
+//! steps, with the best kept and bred into the next generation. Stepping is
+//! deterministic, so the same seed always evolves the same walkers.
+
+use crate::momentum::Momentum;
+use crate::muscle::Muscle;
+use crate::rng::Rng;
+use crate::world::World;
+
+/// Largest amplitude a muscle evolves to, the same as the Muscles window's slider allows.
+const MAX_AMPLITUDE: f32 = 0.5;
+const MIN_FREQUENCY: f32 = 0.005;
+const MAX_FREQUENCY: f32 = 0.5;
+/// How far a mutation moves each gene at most: amplitude and phase by this
+/// much, and frequency by this fraction of itself.
+const MUTATION: f32 = 0.1;
+
+/// One muscle's settings, the part of a walker that evolves.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gene {
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub phase: f32,
+}
+
+/// A gene for each of a body's muscles, in order.
+pub type Genome = Vec<Gene>;
+
+/// The settings `world`'s muscles have now.
+pub fn genome(world: &World) -> Genome {
+    let gene = |m: &Muscle| Gene { amplitude: m.amplitude, frequency: m.frequency, phase: m.phase };
+    world.muscles.iter().map(gene).collect()
+}
+
+/// Sets `world`'s muscles to `genome`.
+pub fn apply(world: &mut World, genome: &[Gene]) {
+    for (muscle, gene) in world.muscles.iter_mut().zip(genome) {
+        (muscle.amplitude, muscle.frequency, muscle.phase) = (gene.amplitude, gene.frequency, gene.phase);
+    }
+}
+
+/// How far `genome` carries `body`'s center of mass to the right in
+/// `steps` steps, or zero if the body flies apart.
+pub fn distance(body: &World, genome: &[Gene], steps: usize) -> f32 {
+    let mut world = body.clone();
+    apply(&mut world, genome);
+    let start = Momentum::of(&world).center_of_mass.x;
+    for _ in 0..steps {
+        world.step(world.params.dt);
+    }
+    let distance = Momentum::of(&world).center_of_mass.x - start;
+    if distance.is_finite() {
+        distance
+    } else {
+        0.0
+    }
+}
+
+/// How a generation did once all of it was scored.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Generation {
+    /// Counting from zero for the first population.
+    pub number: usize,
+    pub best: f32,
+    pub mean: f32,
+}
+
+/// A population of walkers for one body, evolving a walker at a time so it
+/// can be spread over frames.
+#[derive(Clone, Debug)]
+pub struct Evolution {
+    /// The body as every walker starts.
+    body: World,
+    steps: usize,
+    population: Vec<Genome>,
+    /// Distances for the walkers scored so far this generation, in order.
+    scores: Vec<f32>,
+    generation: usize,
+    /// The farthest walker so far, with its distance.
+    best: Option<(Genome, f32)>,
+    rng: Rng,
+}
+
+impl Evolution {
+    /// Starts with `body`'s own muscle settings and `size - 1` mutations of
+    /// them, each walker scored over `steps` steps.
+    pub fn new(body: World, size: usize, steps: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let first = genome(&body);
+        let mut population = vec![first.clone()];
+        population.extend((1..size.max(2)).map(|_| mutate(&first, &mut rng)));
+        Self { body, steps, population, scores: Vec::new(), generation: 0, best: None, rng }
+    }
+
+    pub fn body(&self) -> &World {
+        &self.body
+    }
+
+    /// The farthest walker so far, with its distance.
+    pub fn best(&self) -> Option<(&[Gene], f32)> {
+        self.best.as_ref().map(|(genome, distance)| (genome.as_slice(), *distance))
+    }
+
+    /// The generation being scored, and how many of it have been.
+    pub fn progress(&self) -> (usize, usize, usize) {
+        (self.generation, self.scores.len(), self.population.len())
+    }
+
+    /// Scores the next walker, breeding the next generation once the last
+    /// one in this one is scored and returning how this one did.
+    pub fn evaluate_next(&mut self) -> Option<Generation> {
+        let genome = &self.population[self.scores.len()];
+        let score = distance(&self.body, genome, self.steps);
+        if self.best.as_ref().is_none_or(|(_, best)| score > *best) {
+            self.best = Some((genome.clone(), score));
+        }
+        self.scores.push(score);
+        if self.scores.len() < self.population.len() {
+            return None;
+        }
+
+        let done = Generation {
+            number: self.generation,
+            best: self.scores.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            mean: self.scores.iter().sum::<f32>() / self.scores.len() as f32,
+        };
+        self.breed();
+        Some(done)
+    }
+
+    /// Scores every walker left in this generation and breeds the next.
+    pub fn run_generation(&mut self) -> Generation {
+        loop {
+            if let Some(generation) = self.evaluate_next() {
+                return generation;
+            }
+        }
+    }
+
+    /// Keeps the best quarter as they are, and fills the rest with mutated
+    /// crosses between them.
+    fn breed(&mut self) {
+        let mut ranked: Vec<usize> = (0..self.population.len()).collect();
+        ranked.sort_by(|&a, &b| self.scores[b].total_cmp(&self.scores[a]));
+        let keep = (ranked.len() / 4).max(1);
+        let elite: Vec<Genome> = ranked[..keep].iter().map(|&i| self.population[i].clone()).collect();
+
+        let mut next = elite.clone();
+        while next.len() < self.population.len() {
+            let pick = |rng: &mut Rng| &elite[(rng.next_u64() % elite.len() as u64) as usize];
+            let (mother, father) = (pick(&mut self.rng), pick(&mut self.rng));
+            let child: Genome = mother
+                .iter()
+                .zip(father)
+                .map(|(m, f)| if self.rng.next_f32() < 0.5 { *m } else { *f })
+                .collect();
+            next.push(mutate(&child, &mut self.rng));
+        }
+        self.population = next;
+        self.scores.clear();
+        self.generation += 1;
+    }
+}
+
+/// A copy of `genome` with every gene nudged a little at random.
+fn mutate(genome: &[Gene], rng: &mut Rng) -> Genome {
+    genome
+        .iter()
+        .map(|gene| Gene {
+            amplitude: (gene.amplitude + rng.range(-MUTATION, MUTATION)).clamp(0.0, MAX_AMPLITUDE),
+            frequency: (gene.frequency * (1.0 + rng.range(-MUTATION, MUTATION))).clamp(MIN_FREQUENCY, MAX_FREQUENCY),
+            phase: (gene.phase + rng.range(-MUTATION, MUTATION)).rem_euclid(1.0),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+
+    #[test]
+    fn keeps_its_farthest_walker_and_repeats_from_a_seed() {
+        let body = scene::crawler(1280.0, 720.0);
+        let steps = 400;
+        let start = distance(&body, &genome(&body), steps);
+
+        let evolve = || {
+            let mut evolution = Evolution::new(body.clone(), 8, steps, 7);
+            let generations: Vec<Generation> = (0..3).map(|_| evolution.run_generation()).collect();
+            (generations, evolution)
+        };
+        let (generations, evolution) = evolve();
+        assert_eq!(generations, evolve().0);
+        assert!(generations[0].best >= start);
+        for pair in generations.windows(2) {
+            assert!(pair[1].best >= pair[0].best, "{:?}", pair);
+            assert!(pair[1].mean <= pair[1].best);
+        }
+
+        // the best walker goes as far again when watched from the start
+        let (best, far) = evolution.best().unwrap();
+        assert_eq!(far, generations[2].best);
+        assert_eq!(distance(evolution.body(), best, steps), far);
+        assert_eq!(evolution.progress(), (3, 0, 8));
+    }
+}
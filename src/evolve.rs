@@ -0,0 +1,64 @@
+use crate::cli::Cli;
+use clothsim::evolution::{self, Evolution};
+use clothsim::{scene, Rng};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Evolves the starting scene's muscle settings for `--evolve` generations of
+/// `--population` walkers, writing one `generation,best,mean` CSV row per
+/// generation to `--out` or stdout, and the farthest walker to `--best-scene`
+/// if given. Returns the process exit code.
+pub fn run(cli: &Cli) -> i32 {
+    let world = cli
+        .params()
+        .and_then(|params| cli.scene_source().build(cli.width as f32, cli.height as f32, params));
+    let mut world = match world {
+        Ok(world) => world,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    if world.muscles.is_empty() {
+        eprintln!("the scene has no muscles to evolve");
+        return 1;
+    }
+    world.rng = Rng::new(cli.seed);
+
+    let out: Box<dyn Write> = match &cli.out {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("failed to create {}: {}", path.display(), e);
+                return 1;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let mut evolution = Evolution::new(world, cli.population, cli.steps, cli.seed);
+    let generations = cli.evolve.unwrap_or(0);
+    if let Err(e) = write_generations(&mut evolution, generations, out) {
+        eprintln!("failed to write output: {}", e);
+        return 1;
+    }
+
+    if let (Some(path), Some((best, _))) = (&cli.best_scene, evolution.best()) {
+        let mut world = evolution.body().clone();
+        evolution::apply(&mut world, best);
+        if let Err(e) = std::fs::write(path, scene::to_text(&world)) {
+            eprintln!("failed to write {}: {}", path.display(), e);
+            return 1;
+        }
+    }
+    0
+}
+
+fn write_generations(evolution: &mut Evolution, generations: usize, mut out: impl Write) -> io::Result<()> {
+    writeln!(out, "generation,best,mean")?;
+    for _ in 0..generations {
+        let generation = evolution.run_generation();
+        writeln!(out, "{},{},{}", generation.number, generation.best, generation.mean)?;
+    }
+    out.flush()
+}
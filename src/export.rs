@@ -0,0 +1,342 @@
+//! Writers for simulation output.
+
+use crate::event::SimEvent;
+use crate::world::{index_after_removal, World};
+use glam::Vec2;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// Long-format node positions, one row per node per step, so scenes whose
+/// node count changes over time still produce a rectangular table.
+pub struct CsvWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        writeln!(out, "step,node,x,y")?;
+        Ok(Self { out })
+    }
+
+    pub fn write_step(&mut self, step: usize, world: &World) -> io::Result<()> {
+        for (i, node) in world.arena.iter().enumerate() {
+            writeln!(self.out, "{},{},{},{}", step, i, node.pos.x, node.pos.y)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Positions and velocities of a chosen set of nodes over the last `capacity`
+/// steps, oldest first, for plotting things like pendulum periods.
+pub struct Trajectories {
+    /// Indices of the tracked nodes, in the order they were added.
+    pub nodes: Vec<usize>,
+    capacity: usize,
+    /// Sum of the timesteps recorded since the last clear.
+    time: f32,
+    samples: VecDeque<Sample>,
+}
+
+struct Sample {
+    step: u64,
+    time: f32,
+    /// `(node, pos, vel)` for each tracked node that existed at the time.
+    states: Vec<(usize, Vec2, Vec2)>,
+}
+
+impl Trajectories {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            capacity,
+            time: 0.0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Starts or stops tracking `node`. Samples already taken are kept.
+    pub fn toggle(&mut self, node: usize) {
+        match self.nodes.iter().position(|&tracked| tracked == node) {
+            Some(i) => {
+                self.nodes.remove(i);
+            }
+            None => self.nodes.push(node),
+        }
+    }
+
+    /// Stops tracking `node` and renumbers the rest after it's removed from the world.
+    /// Samples already taken keep the numbers they had.
+    pub fn node_removed(&mut self, node: usize) {
+        self.nodes = self.nodes.iter().filter_map(|&tracked| index_after_removal(tracked, node)).collect();
+    }
+
+    pub fn is_tracked(&self, node: usize) -> bool {
+        self.nodes.contains(&node)
+    }
+
+    /// Samples the tracked nodes after a step of length `dt`, dropping the oldest
+    /// sample once the buffer is full.
+    pub fn record(&mut self, step: u64, dt: f32, world: &World) {
+        self.time += dt;
+        let states = self
+            .nodes
+            .iter()
+            .filter_map(|&i| world.arena.get(i).map(|node| (i, node.pos, node.vel)))
+            .collect();
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample { step, time: self.time, states });
+    }
+
+    /// Number of steps sampled.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drops every sample and restarts the clock, keeping the tracked nodes.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.time = 0.0;
+    }
+
+    /// Writes `step,time,node,x,y,vx,vy` rows, one per tracked node per step.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "step,time,node,x,y,vx,vy")?;
+        for sample in self.samples.iter() {
+            for (node, pos, vel) in sample.states.iter() {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    sample.step, sample.time, node, pos.x, pos.y, vel.x, vel.y
+                )?;
+            }
+        }
+        out.flush()
+    }
+}
+
+/// Triangles spanned by the links: every 3-cycle, plus every 4-cycle with
+/// neither diagonal linked, split in two. For a cloth that's two triangles per
+/// intact grid square, so cut or torn squares drop out of the mesh.
+pub fn faces(world: &World) -> Vec<[usize; 3]> {
+    let mut neighbors = vec![Vec::new(); world.arena.len()];
+    for constraint in world.constraints.iter().filter(|constraint| constraint.a != constraint.b) {
+        neighbors[constraint.a].push(constraint.b);
+        neighbors[constraint.b].push(constraint.a);
+    }
+    for list in neighbors.iter_mut() {
+        list.sort_unstable();
+        list.dedup();
+    }
+    let linked = |a: usize, b: usize| neighbors[a].binary_search(&b).is_ok();
+
+    // each cycle is found once, from its lowest node `a` with neighbors `b < d`
+    let mut faces = Vec::new();
+    for (a, around_a) in neighbors.iter().enumerate() {
+        for (i, &b) in around_a.iter().enumerate().filter(|&(_, &b)| b > a) {
+            for &d in around_a[i + 1..].iter() {
+                if linked(b, d) {
+                    faces.push([a, b, d]);
+                    continue;
+                }
+                for &c in neighbors[b].iter().filter(|&&c| c > a && c != d) {
+                    if linked(c, d) && !linked(a, c) {
+                        faces.push([a, b, c]);
+                        faces.push([a, c, d]);
+                    }
+                }
+            }
+        }
+    }
+    faces
+}
+
+/// Writes node positions and `faces` as a Wavefront OBJ, with y flipped to point
+/// up and every face wound counter-clockwise so normals face the viewer.
+pub fn write_obj<W: Write>(world: &World, mut out: W) -> io::Result<()> {
+    let vertex = |i: usize| {
+        let pos = world.arena[i].pos;
+        // `0.0 -` rather than negating, so a node at y = 0 doesn't come out as -0
+        Vec2::new(pos.x, 0.0 - pos.y)
+    };
+
+    writeln!(out, "# clothsim, {} nodes", world.arena.len())?;
+    for i in 0..world.arena.len() {
+        let v = vertex(i);
+        writeln!(out, "v {} {} 0", v.x, v.y)?;
+    }
+    for [a, b, c] in faces(world) {
+        let (a, b, c) = if (vertex(b) - vertex(a)).perp_dot(vertex(c) - vertex(a)) < 0.0 {
+            (a, c, b)
+        } else {
+            (a, b, c)
+        };
+        // OBJ indices start at 1
+        writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1)?;
+    }
+    out.flush()
+}
+
+/// Writes the link graph in Graphviz DOT, with nodes labeled by index and pinned
+/// to their positions (y flipped) for `neato -n`, fixed nodes drawn as boxes,
+/// and each edge colored by `color` from its break fraction, e.g. `"#ff0000"`.
+pub fn write_dot<W: Write>(world: &World, mut out: W, color: impl Fn(f32) -> String) -> io::Result<()> {
+    writeln!(out, "// clothsim, {} nodes, {} links", world.arena.len(), world.constraints.len())?;
+    writeln!(out, "graph clothsim {{")?;
+    writeln!(out, "    node [shape=circle, fontsize=8, width=0.3, fixedsize=true];")?;
+    for (i, node) in world.arena.iter().enumerate() {
+        let shape = if node.fixed { ", shape=box" } else { "" };
+        writeln!(out, "    {} [pos=\"{},{}!\"{}];", i, node.pos.x, 0.0 - node.pos.y, shape)?;
+    }
+    for constraint in world.constraints.iter() {
+        let strain = constraint.break_fraction(&world.arena, &world.wrap);
+        writeln!(
+            out,
+            "    {} -- {} [color=\"{}\", tooltip=\"strain {:.3}\"];",
+            constraint.a,
+            constraint.b,
+            color(strain),
+            strain
+        )?;
+    }
+    writeln!(out, "}}")?;
+    out.flush()
+}
+
+/// Writes the world as one line of JSON: the step number, `ground_y`, a `nodes`
+/// array of `{"x", "y", "vx", "vy", "fixed"}` objects, and a `links` array of
+/// `{"a", "b", "strain"}` objects, with `strain` the break fraction. A `severed`
+/// array lists the constraints the step's `events` removed, as `{"a", "b", "x",
+/// "y", "strain", "cut"}` objects with the position and strain they were removed
+/// at. Non-finite numbers are written as `null`, since JSON has no NaN.
+pub fn write_json_state<W: Write>(
+    mut out: W,
+    step: u64,
+    world: &World,
+    events: &[SimEvent],
+) -> io::Result<()> {
+    write!(out, r#"{{"step":{},"ground_y":{},"nodes":["#, step, json_number(world.ground_y))?;
+    for (i, node) in world.arena.iter().enumerate() {
+        let comma = if i == 0 { "" } else { "," };
+        write!(
+            out,
+            r#"{}{{"x":{},"y":{},"vx":{},"vy":{},"fixed":{}}}"#,
+            comma,
+            json_number(node.pos.x),
+            json_number(node.pos.y),
+            json_number(node.vel.x),
+            json_number(node.vel.y),
+            node.fixed
+        )?;
+    }
+    write!(out, r#"],"links":["#)?;
+    for (i, constraint) in world.constraints.iter().enumerate() {
+        let comma = if i == 0 { "" } else { "," };
+        let strain = constraint.break_fraction(&world.arena, &world.wrap);
+        write!(out, r#"{}{{"a":{},"b":{},"strain":{}}}"#, comma, constraint.a, constraint.b, json_number(strain))?;
+    }
+    write!(out, r#"],"severed":["#)?;
+    let severed = events.iter().filter_map(|event| match event {
+        SimEvent::ConstraintBroken(link) => Some((link, false)),
+        SimEvent::ConstraintCut(link) => Some((link, true)),
+        _ => None,
+    });
+    for (i, (link, cut)) in severed.enumerate() {
+        let comma = if i == 0 { "" } else { "," };
+        write!(
+            out,
+            r#"{}{{"a":{},"b":{},"x":{},"y":{},"strain":{},"cut":{}}}"#,
+            comma,
+            link.a,
+            link.b,
+            json_number(link.pos.x),
+            json_number(link.pos.y),
+            json_number(link.strain),
+            cut
+        )?;
+    }
+    writeln!(out, "]}}")
+}
+
+fn json_number(x: f32) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2×2 cloth hanging from both top corners: 0 1 on top, 2 3 below.
+    fn square() -> World {
+        let mut world = World::new(500.0);
+        world.add_cloth(Vec2::ZERO, 2, 2, 1);
+        world
+    }
+
+    #[test]
+    fn obj_has_a_vertex_per_node_and_two_faces_per_square_facing_the_viewer() {
+        let mut world = square();
+        assert_eq!(faces(&world), [[0, 1, 3], [0, 3, 2]]);
+        let mut out = Vec::new();
+        write_obj(&world, &mut out).unwrap();
+        let expected = "# clothsim, 4 nodes\nv 0 0 0\nv 50 0 0\nv 0 -50 0\nv 50 -50 0\nf 1 4 2\nf 1 3 4\n";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        // a cut side takes the square's faces with it
+        world.cut(Vec2::new(40.0, 25.0), Vec2::new(60.0, 25.0));
+        assert!(faces(&world).is_empty());
+    }
+
+    #[test]
+    fn dot_pins_nodes_and_colors_links_by_strain() {
+        let mut world = square();
+        // halfway to breaking, at `break_threshold` five rest lengths
+        world.arena[2].pos.y = 150.0;
+        let mut out = Vec::new();
+        write_dot(&world, &mut out, |strain| format!("{:.2}", strain)).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("// clothsim, 4 nodes, 4 links\ngraph clothsim {\n"));
+        assert!(text.contains("\n    1 [pos=\"50,0!\", shape=box];\n    2 [pos=\"0,-150!\"];\n"));
+        assert!(text.contains("\n    0 -- 2 [color=\"0.50\", tooltip=\"strain 0.500\"];\n"));
+        assert!(text.contains("\n    0 -- 1 [color=\"0.00\", tooltip=\"strain 0.000\"];\n"));
+        assert!(text.ends_with("}\n"));
+    }
+
+    #[test]
+    fn json_state_lists_nodes_links_and_what_was_severed() {
+        let mut world = square();
+        world.arena[3].vel = Vec2::new(1.5, f32::NAN);
+        world.cut(Vec2::new(25.0, -10.0), Vec2::new(25.0, 10.0));
+        let events = std::mem::take(&mut world.events);
+        let mut out = Vec::new();
+        write_json_state(&mut out, 7, &world, &events).unwrap();
+        let expected = concat!(
+            r#"{"step":7,"ground_y":500,"nodes":["#,
+            r#"{"x":0,"y":0,"vx":0,"vy":0,"fixed":true},{"x":50,"y":0,"vx":0,"vy":0,"fixed":true},"#,
+            r#"{"x":0,"y":50,"vx":0,"vy":0,"fixed":false},{"x":50,"y":50,"vx":1.5,"vy":null,"fixed":false}],"#,
+            r#""links":[{"a":0,"b":2,"strain":0},{"a":2,"b":3,"strain":0},{"a":1,"b":3,"strain":0}],"#,
+            r#""severed":[{"a":0,"b":1,"x":25,"y":0,"strain":0,"cut":true}]}"#,
+            "\n"
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::node::Node;
+use crate::wind::WindBrush;
+use glam::Vec2;
+
+/// Side of each cell of the air's grid.
+pub const AIR_CELL: f32 = 25.0;
+/// Force per unit of speed between a node and the air around it.
+pub const AIR_DRAG: f32 = 0.5;
+/// Fraction of the air's speed lost per unit of time.
+pub const AIR_FADE: f32 = 0.1;
+/// Gauss-Seidel sweeps spent keeping the air from bunching up each step.
+const PRESSURE_ITERATIONS: usize = 20;
+
+/// A coarse grid of moving air, stepped like Stam's stable fluids: the flow
+/// carries itself along and is kept from bunching up or thinning out, so a
+/// stroke through it curls into swirls that keep going after the cursor has
+/// stopped. Nodes are dragged toward the air's velocity where they are, and
+/// the grid's edges are solid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fluid {
+    /// Top left corner of the grid.
+    pub origin: Vec2,
+    pub cell: f32,
+    pub columns: usize,
+    pub rows: usize,
+    /// Force per unit of speed between a node and the air.
+    pub drag: f32,
+    /// Fraction of the air's speed lost per unit of time.
+    pub fade: f32,
+    /// Velocity in the middle of each cell, row by row.
+    pub vel: Vec<Vec2>,
+}
+
+impl Fluid {
+    /// Still air in `columns` by `rows` cells of side `cell`, from `origin`.
+    pub fn new(origin: Vec2, columns: usize, rows: usize, cell: f32) -> Self {
+        Self {
+            origin,
+            cell,
+            columns,
+            rows,
+            drag: AIR_DRAG,
+            fade: AIR_FADE,
+            vel: vec![Vec2::ZERO; columns * rows],
+        }
+    }
+
+    /// Still air covering the rectangle from `min` to `max` in cells of `cell`.
+    pub fn covering(min: Vec2, max: Vec2, cell: f32) -> Self {
+        let size = ((max - min) / cell).ceil().max(Vec2::ONE);
+        Self::new(min, size.x as usize, size.y as usize, cell)
+    }
+
+    fn index(&self, column: usize, row: usize) -> usize {
+        row * self.columns + column
+    }
+
+    /// Middle of the cell at `column`, `row`.
+    pub fn center(&self, column: usize, row: usize) -> Vec2 {
+        self.origin + (Vec2::new(column as f32, row as f32) + 0.5) * self.cell
+    }
+
+    /// The middle of every cell with the air's velocity there, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.vel.iter().enumerate().map(|(i, &vel)| (self.center(i % self.columns, i / self.columns), vel))
+    }
+
+    /// The air's velocity at `pos`, blended between the nearest cells. Outside
+    /// the grid it's the velocity at the nearest edge.
+    pub fn sample(&self, pos: Vec2) -> Vec2 {
+        Self::sample_field(&self.vel, self.columns, self.rows, (pos - self.origin) / self.cell - 0.5)
+    }
+
+    /// Bilinear lookup of `field` at `at`, in cells from the middle of the first one.
+    fn sample_field(field: &[Vec2], columns: usize, rows: usize, at: Vec2) -> Vec2 {
+        let max = Vec2::new(columns as f32 - 1.0, rows as f32 - 1.0);
+        let at = at.clamp(Vec2::ZERO, max);
+        let (x0, y0) = (at.x.floor() as usize, at.y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(columns - 1), (y0 + 1).min(rows - 1));
+        let (tx, ty) = (at.x - x0 as f32, at.y - y0 as f32);
+        let row = |y: usize| field[y * columns + x0].lerp(field[y * columns + x1], tx);
+        row(y0).lerp(row(y1), ty)
+    }
+
+    /// Pushes the air under `brush` at `pos` along `delta`, the cursor
+    /// movement since the last step, as the brush would push a node of unit mass.
+    pub fn stir(&mut self, pos: Vec2, delta: Vec2, brush: &WindBrush, dt: f32) {
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let weight = brush.weight(self.center(column, row).distance(pos));
+                if weight > 0.0 {
+                    let i = self.index(column, row);
+                    self.vel[i] += delta * brush.strength * weight * dt;
+                }
+            }
+        }
+    }
+
+    /// Moves the air along for `dt`: it's made incompressible, carried along
+    /// by itself, made incompressible again, and slowed by `fade`.
+    pub fn advance(&mut self, dt: f32) {
+        self.project();
+        self.advect(dt);
+        self.project();
+        let keep = (1.0 - self.fade * dt).max(0.0);
+        self.vel.iter_mut().for_each(|vel| *vel *= keep);
+    }
+
+    /// Semi-Lagrangian advection: each cell takes the velocity from where the
+    /// air in it was `dt` ago, which can't blow up however large `dt` is.
+    fn advect(&mut self, dt: f32) {
+        let old = self.vel.clone();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let i = self.index(column, row);
+                let from = Vec2::new(column as f32, row as f32) - old[i] * dt / self.cell;
+                self.vel[i] = Self::sample_field(&old, self.columns, self.rows, from);
+            }
+        }
+        self.stop_at_edges();
+    }
+
+    /// Takes away the part of the flow that would pile air up or thin it out,
+    /// by solving for the pressure that evens it and following its gradient.
+    fn project(&mut self) {
+        let (columns, rows) = (self.columns, self.rows);
+        // neighbours past the edges are the edge cells themselves
+        let left = |x: usize| x.saturating_sub(1);
+        let right = |x: usize| (x + 1).min(columns - 1);
+        let up = |y: usize| y.saturating_sub(1);
+        let down = |y: usize| (y + 1).min(rows - 1);
+
+        let mut divergence = vec![0.0; columns * rows];
+        for y in 0..rows {
+            for x in 0..columns {
+                let spread = self.vel[self.index(right(x), y)].x - self.vel[self.index(left(x), y)].x
+                    + self.vel[self.index(x, down(y))].y
+                    - self.vel[self.index(x, up(y))].y;
+                divergence[self.index(x, y)] = 0.5 * spread;
+            }
+        }
+
+        let mut pressure = vec![0.0; columns * rows];
+        for _ in 0..PRESSURE_ITERATIONS {
+            for y in 0..rows {
+                for x in 0..columns {
+                    let around = pressure[self.index(left(x), y)]
+                        + pressure[self.index(right(x), y)]
+                        + pressure[self.index(x, up(y))]
+                        + pressure[self.index(x, down(y))];
+                    pressure[self.index(x, y)] = (around - divergence[self.index(x, y)]) / 4.0;
+                }
+            }
+        }
+
+        for y in 0..rows {
+            for x in 0..columns {
+                let gradient = Vec2::new(
+                    pressure[self.index(right(x), y)] - pressure[self.index(left(x), y)],
+                    pressure[self.index(x, down(y))] - pressure[self.index(x, up(y))],
+                );
+                let i = self.index(x, y);
+                self.vel[i] -= 0.5 * gradient;
+            }
+        }
+        self.stop_at_edges();
+    }
+
+    /// Stops air blowing through the solid edges of the grid.
+    fn stop_at_edges(&mut self) {
+        for row in 0..self.rows {
+            let (first, last) = (self.index(0, row), self.index(self.columns - 1, row));
+            self.vel[first].x = 0.0;
+            self.vel[last].x = 0.0;
+        }
+        for column in 0..self.columns {
+            let (first, last) = (self.index(column, 0), self.index(column, self.rows - 1));
+            self.vel[first].y = 0.0;
+            self.vel[last].y = 0.0;
+        }
+    }
+
+    /// Drags free nodes toward the air's velocity where they are.
+    pub fn blow(&self, arena: &mut [Node]) {
+        for node in arena.iter_mut().filter(|node| !node.fixed) {
+            node.force += (self.sample(node.pos) - node.vel) * self.drag;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strokes_keep_blowing_and_drag_nodes_along() {
+        let mut fluid = Fluid::new(Vec2::ZERO, 16, 16, 10.0);
+        let brush = WindBrush { radius: 30.0, strength: 10.0, falloff: 0.5 };
+        fluid.stir(Vec2::new(80.0, 80.0), Vec2::new(10.0, 0.0), &brush, 1.0);
+        for _ in 0..5 {
+            fluid.advance(0.1);
+        }
+
+        // the gust carries on where it was stirred, and swirls back around it
+        let gust = fluid.sample(Vec2::new(80.0, 80.0));
+        assert!(gust.x > 10.0, "{}", gust);
+        assert!(fluid.vel.iter().any(|vel| vel.x < 0.0));
+        assert!(fluid.vel.iter().all(|vel| vel.is_finite()));
+
+        let mut arena = [Node::with_pos_and_mass(Vec2::new(80.0, 80.0), 1.0)];
+        fluid.blow(&mut arena);
+        assert!((arena[0].force - gust * AIR_DRAG).length() < 1e-4, "{}", arena[0].force);
+    }
+}
@@ -0,0 +1,118 @@
+//! A force on every free node written as a formula, like
+//! `f = (-0.2*x, 10*sin(t))`, for trying out fields the built-in wind,
+//! wells, and magnet don't cover. Each part is a Rhai expression in the
+//! node's `x`, `y`, `vx`, `vy`, and `mass` and the simulated time `t`, with
+//! Rhai's math functions, so `sin`, `sqrt`, `abs` and the like all work.
+//! The `f =` is optional. A part that works out to NaN or infinity for any
+//! node is an error, like one that doesn't work out at all.
+
+use crate::script::number;
+use crate::world::World;
+use glam::Vec2;
+use rhai::{Dynamic, Engine, Scope, AST};
+
+pub struct ForceExpr {
+    engine: Engine,
+    fx: AST,
+    fy: AST,
+}
+
+impl ForceExpr {
+    /// Compiles `text`, returning what's wrong with it if it isn't a pair of expressions.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let text = text.strip_prefix('f').and_then(|rest| rest.trim_start().strip_prefix('=')).unwrap_or(text).trim();
+        let inner = text
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| "expected a force like (fx, fy)".to_string())?;
+        let parts = split_top_level(inner);
+        let [fx, fy] = parts.as_slice() else {
+            return Err(format!("expected two parts, fx and fy, got {}", parts.len()));
+        };
+        let engine = Engine::new();
+        let compile = |part: &str, name: &str| {
+            engine.compile_expression(part).map_err(|e| format!("{}: {}", name, e))
+        };
+        let (fx, fy) = (compile(fx, "fx")?, compile(fy, "fy")?);
+        Ok(Self { engine, fx, fy })
+    }
+
+    /// The force on each free node at time `t`, or the first error working one out.
+    pub fn forces(&self, world: &World, t: f32) -> Result<Vec<(usize, Vec2)>, String> {
+        let mut scope = Scope::new();
+        let eval = |scope: &mut Scope, ast: &AST, name: &str| {
+            let value: Dynamic = self.engine.eval_ast_with_scope(scope, ast).map_err(|e| format!("{}: {}", name, e))?;
+            let force = number(value).map_err(|e| format!("{}: {}", name, e))?;
+            // one NaN or infinite force would spread through the links to the whole world
+            if !force.is_finite() {
+                return Err(format!("{}: not finite", name));
+            }
+            Ok(force)
+        };
+        let free = world.arena.iter().enumerate().filter(|(_, node)| !node.fixed);
+        free.map(|(i, node)| {
+            scope.clear();
+            scope.push("x", node.pos.x).push("y", node.pos.y);
+            scope.push("vx", node.vel.x).push("vy", node.vel.y);
+            scope.push("mass", node.mass).push("t", t);
+            Ok((i, Vec2::new(eval(&mut scope, &self.fx, "fx")?, eval(&mut scope, &self.fy, "fy")?)))
+        })
+        .collect()
+    }
+
+    /// Adds the force at time `t` to each free node, or to none of them if working it out fails.
+    pub fn apply(&self, world: &mut World, t: f32) -> Result<(), String> {
+        for (i, force) in self.forces(world, t)? {
+            world.arena[i].force += force;
+        }
+        Ok(())
+    }
+}
+
+/// `text` split at the commas that aren't inside brackets, each part trimmed.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let (mut parts, mut depth, mut start) = (Vec::new(), 0, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn works_out_a_force_for_each_free_node() {
+        let mut world = World::new(1000.0);
+        world.add_rope(Vec2::new(10.0, 0.0), 3);
+        let expr = ForceExpr::parse("f = (-0.2*x, 10*sin(t) + max(y, 1.0))").unwrap();
+        let forces = expr.forces(&world, std::f32::consts::FRAC_PI_2).unwrap();
+        assert_eq!(forces.len(), 2);
+        let (i, force) = forces[1];
+        assert_eq!(i, 2);
+        assert!(force.abs_diff_eq(Vec2::new(-2.0, 10.0 + world.arena[2].pos.y), 1e-4));
+
+        assert!(ForceExpr::parse("(x, y, 1)").is_err_and(|e| e.contains("two parts")));
+        assert!(ForceExpr::parse("-0.2*x").is_err());
+        assert!(ForceExpr::parse("(x +, y)").is_err_and(|e| e.starts_with("fx")));
+        let unknown = ForceExpr::parse("(z, 0)").unwrap();
+        assert!(unknown.apply(&mut world, 0.0).is_err());
+        assert_eq!(world.arena[2].force, Vec2::ZERO);
+        let blows_up = ForceExpr::parse("(0, 1.0 / (x - 10.0))").unwrap();
+        assert_eq!(blows_up.apply(&mut world, 0.0), Err("fy: not finite".to_string()));
+        let nan = ForceExpr::parse("(sqrt(-mass), 0)").unwrap();
+        assert_eq!(nan.forces(&world, 0.0), Err("fx: not finite".to_string()));
+        assert_eq!(world.arena[2].force, Vec2::ZERO);
+    }
+}
@@ -0,0 +1,79 @@
+//! Warnings for when a phase of the frame takes more than its share of the
+//! time a frame has at 60 frames a second, for seeing where a big scene's
+//! time goes on the machine it's running on without reaching for the
+//! profiler. Phases are split up as `--profile-out` splits them, and timed
+//! over the last half second so one slow frame doesn't set one off.
+//!
+//! Only steps run on the main thread are timed, and nothing is in the
+//! browser, which has no clock to time them with.
+
+use crate::perf::{FrameTimes, Phase};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Whether phases can be timed on this platform.
+pub const AVAILABLE: bool = cfg!(not(target_arch = "wasm32"));
+
+/// Seconds a frame has at 60 frames a second.
+pub const FRAME_SECONDS: f64 = 1.0 / 60.0;
+
+/// Frames averaged over before warning, half a second's worth.
+const BUDGET_FRAMES: usize = 30;
+
+/// The share of `FRAME_SECONDS` each phase can take, by `Phase`. The solver
+/// gets the most, and the rest is left for the UI.
+const SHARES: [f64; Phase::ALL.len()] = [0.05, 0.05, 0.1, 0.35, 0.25];
+
+/// Splits the time as it passes between phases.
+pub struct Stopwatch(Option<Instant>);
+
+impl Stopwatch {
+    /// Starts timing if `on`, or else makes every lap a no-op.
+    pub fn start(on: bool) -> Self {
+        Self((on && AVAILABLE).then(Instant::now))
+    }
+
+    /// Adds the time since the last lap, or since it started, to `phase` in `times`.
+    pub fn lap(&mut self, times: &mut FrameTimes, phase: Phase) {
+        if let Some(last) = self.0.as_mut() {
+            let now = Instant::now();
+            times[phase as usize] += now.duration_since(*last).as_secs_f64();
+            *last = now;
+        }
+    }
+}
+
+/// The last few frames' times, to tell which phases are over budget.
+#[derive(Default)]
+pub struct FrameBudget {
+    recent: VecDeque<FrameTimes>,
+}
+
+impl FrameBudget {
+    pub fn record(&mut self, times: FrameTimes) {
+        if self.recent.len() == BUDGET_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(times);
+    }
+
+    pub fn clear(&mut self) {
+        self.recent.clear();
+    }
+
+    /// Each phase taking more than its share on average lately, with the
+    /// seconds it's taken a frame and the seconds it has.
+    pub fn over(&self) -> Vec<(Phase, f64, f64)> {
+        if self.recent.len() < BUDGET_FRAMES {
+            return Vec::new();
+        }
+        Phase::ALL
+            .into_iter()
+            .map(|phase| {
+                let spent = self.recent.iter().map(|times| times[phase as usize]).sum::<f64>();
+                (phase, spent / self.recent.len() as f64, SHARES[phase as usize] * FRAME_SECONDS)
+            })
+            .filter(|&(_, spent, budget)| spent > budget)
+            .collect()
+    }
+}
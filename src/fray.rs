@@ -0,0 +1,121 @@
+//! Frayed ends where links break or are cut. A severed link normally leaves
+//! its ends where they were, and in cloth with cross links an end can still
+//! be linked to both sides of the gap, holding the two pieces together at a
+//! single node. With `fray` on, such an end is split in two, the new node
+//! taking the links reaching across to the other side, and every end left
+//! springs back a little into its own piece.
+
+use crate::world::World;
+use glam::Vec2;
+
+/// How far a severed link's ends spring back over the step they come free, by default.
+pub const FRAY_RECOIL: f32 = 2.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fray {
+    /// How far each end moves back into its piece, which it keeps going at as
+    /// speed once the step's velocities are worked out.
+    pub recoil: f32,
+}
+
+impl Default for Fray {
+    fn default() -> Self {
+        Self { recoil: FRAY_RECOIL }
+    }
+}
+
+impl Fray {
+    /// The links on `node`, one end of a link to `other` that was just
+    /// severed, reaching past the middle of it to `other`'s side, by index
+    /// into `constraints`. Empty unless `node` also has links on its own side,
+    /// so only a node shared between the two sides is split.
+    pub fn across(world: &World, node: usize, other: usize) -> Vec<usize> {
+        let (pos, away) = (world.arena[node].pos, world.arena[other].pos - world.arena[node].pos);
+        let mut own_side = false;
+        let across: Vec<usize> = world
+            .constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| constraint.a == node || constraint.b == node)
+            .filter_map(|(i, constraint)| {
+                let offset = constraint.offset(&world.arena, &world.wrap);
+                let toward = if constraint.a == node { offset } else { -offset };
+                let past_middle = (pos + toward - (pos + away / 2.0)).dot(away) > 0.0;
+                own_side |= !past_middle;
+                past_middle.then_some(i)
+            })
+            .collect();
+        if own_side {
+            across
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Which way `node` springs back: toward the nodes it's still linked to,
+    /// or nowhere if it's loose or fixed.
+    pub fn heading(world: &World, node: usize) -> Vec2 {
+        if world.arena[node].fixed {
+            return Vec2::ZERO;
+        }
+        let toward = world.constraints.iter().filter_map(|constraint| {
+            let offset = constraint.offset(&world.arena, &world.wrap);
+            match (constraint.a == node, constraint.b == node) {
+                (true, _) => Some(offset.normalize_or_zero()),
+                (_, true) => Some(-offset.normalize_or_zero()),
+                _ => None,
+            }
+        });
+        toward.fold(Vec2::ZERO, |sum, toward| sum + toward).normalize_or_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::event::SimEvent;
+    use crate::group;
+    use crate::muscle::Muscle;
+    use crate::node::Node;
+    use crate::TARGET_DIST;
+
+    #[test]
+    fn a_node_holding_both_sides_splits_when_its_link_goes() {
+        // a square with one diagonal, 0-1 along the top and 2-3 along the bottom
+        let mut world = World::new(10_000.0);
+        world.fray = Some(Fray::default());
+        for pos in [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE] {
+            world.arena.push(Node::with_pos_and_mass(pos * TARGET_DIST, 1.0));
+        }
+        let link = |a, b| Constraint { a, b, rest_length: TARGET_DIST, break_threshold: f32::INFINITY };
+        world.constraints.extend([link(0, 1), link(0, 2), link(1, 3), link(2, 3), link(0, 3)]);
+        let diagonal = group::tag(&mut world, "diag", [(0, 3)]);
+        world.muscles.push(Muscle::new(3, 0, TARGET_DIST, 0.1, 1.0, 0.0));
+
+        // cutting the top leaves 0 holding the left side and, through the diagonal, the right
+        world.cut(Vec2::new(0.5, -0.5) * TARGET_DIST, Vec2::new(0.5, 0.2) * TARGET_DIST);
+        assert_eq!(world.constraints.len(), 4);
+        assert_eq!(world.arena.len(), 5);
+        assert_eq!((world.arena[0].mass, world.arena[4].mass), (0.5, 0.5));
+        let on = |node: usize| world.constraints.iter().filter(|c| c.a == node || c.b == node).count();
+        assert_eq!((on(0), on(4)), (1, 1));
+        // the copy took the diagonal, over to the right side
+        assert!(world.constraints.iter().any(|c| (c.a, c.b) == (4, 3)));
+        // along with the group and muscle naming it
+        assert_eq!(world.groups[diagonal].links, vec![(4, 3)]);
+        assert_eq!((world.muscles[0].a, world.muscles[0].b), (3, 4));
+        let frayed = world.drain_events().iter().filter(|e| matches!(e, SimEvent::NodeFrayed { node: 0, .. })).count();
+        assert_eq!(frayed, 1);
+        // and the ends sprang back into their own pieces
+        assert!(world.arena[0].pos.y > 0.0 && world.arena[1].pos.y > 0.0);
+        assert_eq!(group::break_all(&mut world, diagonal), 1);
+
+        // a rope's ends are its own already
+        let mut world = World::new(10_000.0);
+        world.fray = Some(Fray::default());
+        world.add_rope(Vec2::ZERO, 4);
+        world.cut(Vec2::new(-1.0, 1.5) * TARGET_DIST, Vec2::new(1.0, 1.5) * TARGET_DIST);
+        assert_eq!((world.arena.len(), world.constraints.len()), (4, 2));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{DYNAMIC_FRICTION, STATIC_FRICTION};
+use glam::Vec2;
+
+/// Coulomb friction where nodes touch the ground, walls, balls, and each
+/// other. Like the rest of the solver it works on positions: each contact
+/// looks at how far the two sides slid past each other this step, and takes
+/// it all away if that's less than `static_friction` times how deep they
+/// pressed together, or else `dynamic_friction` times that depth of it, so
+/// things at rest stay put and things sliding slow down.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Friction {
+    pub static_friction: f32,
+    pub dynamic_friction: f32,
+}
+
+impl Default for Friction {
+    fn default() -> Self {
+        Self { static_friction: STATIC_FRICTION, dynamic_friction: DYNAMIC_FRICTION }
+    }
+}
+
+impl Friction {
+    /// No friction at all, for surfaces that are meant to be slippery.
+    pub const NONE: Friction = Friction { static_friction: 0.0, dynamic_friction: 0.0 };
+
+    /// The part of `slip`, how far one side of a contact moved past the other
+    /// this step, that friction takes away at a contact `depth` deep along `normal`.
+    pub fn grip(&self, slip: Vec2, normal: Vec2, depth: f32) -> Vec2 {
+        let sideways = slip - normal * slip.dot(normal);
+        let length = sideways.length();
+        if length == 0.0 {
+            return Vec2::ZERO;
+        }
+        if length <= self.static_friction * depth {
+            return sideways;
+        }
+        sideways * (self.dynamic_friction * depth / length).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sticks_below_the_static_limit_and_slows_above_it() {
+        let friction = Friction { static_friction: 0.5, dynamic_friction: 0.25 };
+        let normal = Vec2::NEG_Y;
+
+        // pressing 2 deep holds up to 1 of slip entirely, and only the sideways part
+        assert_eq!(friction.grip(Vec2::new(0.8, 3.0), normal, 2.0), Vec2::new(0.8, 0.0));
+        // past that it only takes away 0.5 of it
+        assert_eq!(friction.grip(Vec2::new(4.0, 0.0), normal, 2.0), Vec2::new(0.5, 0.0));
+        assert_eq!(Friction::NONE.grip(Vec2::new(0.8, 0.0), normal, 2.0), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,115 @@
+//! Playing with a gamepad, for couches, TVs, and controllers built for
+//! accessibility. Reads a Linux joystick device like `/dev/input/js0`, which
+//! any pad the kernel knows shows up as, laid out like an Xbox controller:
+//!
+//! - left stick: moves a cursor around the screen
+//! - right trigger: cuts along the cursor's path
+//! - left trigger: the cursor tool, so wind or push
+//! - bumpers: pick the previous or next cursor tool
+//!
+//! The cursor takes over from the mouse when the pad is used, and gives it
+//! back when the mouse moves.
+
+use std::fs::File;
+use std::io::{self, Read};
+use tracing::{info, warn};
+
+/// Bytes in a joystick event: a timestamp, the value, its type, and which axis or button.
+const EVENT_SIZE: usize = 8;
+/// Set on the events sent when the device is opened, one for each axis and button as it starts out.
+const JS_EVENT_INIT: u8 = 0x80;
+const JS_EVENT_BUTTON: u8 = 0x01;
+const JS_EVENT_AXIS: u8 = 0x02;
+const LEFT_X: u8 = 0;
+const LEFT_Y: u8 = 1;
+const LEFT_TRIGGER: u8 = 2;
+const RIGHT_TRIGGER: u8 = 5;
+const LEFT_BUMPER: u8 = 4;
+const RIGHT_BUMPER: u8 = 5;
+/// Stick travel ignored around the middle, where worn sticks rest a little off center.
+const DEAD_ZONE: f32 = 0.15;
+/// How far in a trigger is pulled before it counts.
+const TRIGGER_THRESHOLD: f32 = 0.5;
+/// How fast a stick pushed all the way moves the cursor, in screen pixels a second.
+pub const CURSOR_SPEED: f32 = 600.0;
+/// Linux's `O_NONBLOCK`, so reads come back empty instead of waiting for the pad.
+#[cfg(unix)]
+const O_NONBLOCK: i32 = 0o4000;
+
+/// A bumper pressed since the last poll.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bumper {
+    Left,
+    Right,
+}
+
+pub struct Gamepad {
+    device: File,
+    /// The left stick, each axis from -1 to 1 with the dead zone taken out, and down positive like the screen.
+    pub stick: (f32, f32),
+    /// How far in each trigger is, from 0 to 1.
+    pub triggers: (f32, f32),
+}
+
+impl Gamepad {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let mut options = std::fs::OpenOptions::new();
+        options.read(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, O_NONBLOCK);
+        let device = options.open(path)?;
+        info!("reading the gamepad at {}", path);
+        Ok(Self { device, stick: (0.0, 0.0), triggers: (0.0, 0.0) })
+    }
+
+    /// Catches up with what the pad's done since the last poll. Returns the
+    /// bumpers pressed, in order, and whether anything was touched at all.
+    pub fn poll(&mut self) -> (Vec<Bumper>, bool) {
+        let (mut bumpers, mut used) = (Vec::new(), false);
+        let mut event = [0; EVENT_SIZE];
+        loop {
+            match self.device.read_exact(&mut event) {
+                Ok(()) => used |= self.apply(event, &mut bumpers),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return (bumpers, used),
+                Err(e) => {
+                    warn!("failed to read the gamepad: {}", e);
+                    return (bumpers, used);
+                }
+            }
+        }
+    }
+
+    /// Whether the right trigger is pulled far enough to cut.
+    pub fn cutting(&self) -> bool {
+        self.triggers.1 >= TRIGGER_THRESHOLD
+    }
+
+    /// Whether the left trigger is pulled far enough for the cursor tool.
+    pub fn using_tool(&self) -> bool {
+        self.triggers.0 >= TRIGGER_THRESHOLD
+    }
+
+    /// Takes in one event, adding to `bumpers` if it's one being pressed.
+    /// Returns whether it was the player's doing, not the device starting out.
+    fn apply(&mut self, event: [u8; EVENT_SIZE], bumpers: &mut Vec<Bumper>) -> bool {
+        let value = i16::from_le_bytes([event[4], event[5]]) as f32 / i16::MAX as f32;
+        let (kind, number) = (event[6], event[7]);
+        match (kind & !JS_EVENT_INIT, number) {
+            (JS_EVENT_AXIS, LEFT_X) => self.stick.0 = dead_zone(value),
+            (JS_EVENT_AXIS, LEFT_Y) => self.stick.1 = dead_zone(value),
+            // triggers rest all the way negative
+            (JS_EVENT_AXIS, LEFT_TRIGGER) => self.triggers.0 = (value + 1.0) / 2.0,
+            (JS_EVENT_AXIS, RIGHT_TRIGGER) => self.triggers.1 = (value + 1.0) / 2.0,
+            (JS_EVENT_BUTTON, LEFT_BUMPER) if value > 0.0 && kind & JS_EVENT_INIT == 0 => bumpers.push(Bumper::Left),
+            (JS_EVENT_BUTTON, RIGHT_BUMPER) if value > 0.0 && kind & JS_EVENT_INIT == 0 => bumpers.push(Bumper::Right),
+            _ => {}
+        }
+        kind & JS_EVENT_INIT == 0
+    }
+}
+
+/// `value` with the dead zone around the middle cut out and the rest stretched back over -1 to 1.
+fn dead_zone(value: f32) -> f32 {
+    let travel = ((value.abs() - DEAD_ZONE) / (1.0 - DEAD_ZONE)).clamp(0.0, 1.0);
+    travel.copysign(value)
+}
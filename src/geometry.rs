@@ -0,0 +1,190 @@
+//! 2D segment tests used by the knife, and sweeps for stopping fast nodes at what they'd hit.
+
+use glam::Vec2;
+
+/// Positive if `c` is to the left of the line from `a` to `b`, negative if to the
+/// right, and zero if the three points are collinear.
+pub fn orientation(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a)
+}
+
+/// Whether `p`, already known to be collinear with `a` and `b`, lies between them.
+fn within(p: Vec2, a: Vec2, b: Vec2) -> bool {
+    p.cmpge(a.min(b)).all() && p.cmple(a.max(b)).all()
+}
+
+/// Whether segments `ab` and `cd` share at least one point, counting touching
+/// endpoints and collinear overlaps. Either segment may have zero length.
+///
+/// https://stackoverflow.com/questions/3838329/how-can-i-check-if-two-segments-intersect
+pub fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let o1 = orientation(c, d, a);
+    let o2 = orientation(c, d, b);
+    let o3 = orientation(a, b, c);
+    let o4 = orientation(a, b, d);
+
+    let straddles = |p: f32, q: f32| (p > 0.0 && q < 0.0) || (p < 0.0 && q > 0.0);
+    if straddles(o1, o2) && straddles(o3, o4) {
+        return true;
+    }
+
+    (o1 == 0.0 && within(a, c, d))
+        || (o2 == 0.0 && within(b, c, d))
+        || (o3 == 0.0 && within(c, a, b))
+        || (o4 == 0.0 && within(d, a, b))
+}
+
+/// How far along segment `ab` the nearest point to `p` is, from 0 at `a` to 1 at `b`.
+pub fn nearest_on_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    if ab == Vec2::ZERO {
+        0.0
+    } else {
+        ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    }
+}
+
+/// Distance from `p` to the nearest point of segment `ab`, which may have zero length.
+pub fn point_segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    p.distance(a.lerp(b, nearest_on_segment(p, a, b)))
+}
+
+/// Distance between the nearest points of segments `ab` and `cd`, zero if they intersect.
+pub fn segment_distance(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> f32 {
+    if segments_intersect(a, b, c, d) {
+        return 0.0;
+    }
+    // without a crossing, the nearest points include an endpoint of one of them
+    point_segment_distance(a, c, d)
+        .min(point_segment_distance(b, c, d))
+        .min(point_segment_distance(c, a, b))
+        .min(point_segment_distance(d, a, b))
+}
+
+/// How far along segment `ab` it crosses segment `cd`, from 0 at `a` to 1 at
+/// `b`, or `None` if they don't cross or are parallel.
+pub fn segment_crossing(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Option<f32> {
+    let (r, s) = (b - a, d - c);
+    let denom = r.perp_dot(s);
+    if denom == 0.0 {
+        return None;
+    }
+    let t = (c - a).perp_dot(s) / denom;
+    let u = (c - a).perp_dot(r) / denom;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+/// How far along the path from `from` to `to` a point first comes within
+/// `radius` of `center`, from 0 to 1, or `None` if it never does or starts there.
+pub fn sweep_circle(from: Vec2, to: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let (path, start) = (to - from, from - center);
+    let a = path.length_squared();
+    let c = start.length_squared() - radius * radius;
+    if a == 0.0 || c <= 0.0 {
+        return None;
+    }
+    let b = start.dot(path);
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
+/// How far along the path from `from` to `to` a point first comes within
+/// `radius` of segment `ab`, from 0 to 1, or `None` if it never does or starts there.
+pub fn sweep_capsule(from: Vec2, to: Vec2, a: Vec2, b: Vec2, radius: f32) -> Option<f32> {
+    if point_segment_distance(from, a, b) <= radius {
+        return None;
+    }
+    let side = (b - a).perp().normalize_or_zero() * radius;
+    [
+        sweep_circle(from, to, a, radius),
+        sweep_circle(from, to, b, radius),
+        segment_crossing(from, to, a + side, b + side),
+        segment_crossing(from, to, a - side, b - side),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by(f32::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y)
+    }
+
+    #[test]
+    fn crossing() {
+        assert!(segments_intersect(v(0.0, 0.0), v(2.0, 2.0), v(0.0, 2.0), v(2.0, 0.0)));
+    }
+
+    #[test]
+    fn disjoint() {
+        assert!(!segments_intersect(v(0.0, 0.0), v(1.0, 0.0), v(0.0, 1.0), v(1.0, 1.0)));
+        // would cross if either were extended
+        assert!(!segments_intersect(v(0.0, 0.0), v(1.0, 1.0), v(3.0, 0.0), v(2.0, 1.0)));
+    }
+
+    #[test]
+    fn collinear_overlap() {
+        assert!(segments_intersect(v(0.0, 0.0), v(2.0, 0.0), v(1.0, 0.0), v(3.0, 0.0)));
+        assert!(segments_intersect(v(0.0, 0.0), v(3.0, 3.0), v(1.0, 1.0), v(2.0, 2.0)));
+    }
+
+    #[test]
+    fn collinear_apart() {
+        assert!(!segments_intersect(v(0.0, 0.0), v(1.0, 0.0), v(2.0, 0.0), v(3.0, 0.0)));
+    }
+
+    #[test]
+    fn touching_endpoints() {
+        assert!(segments_intersect(v(0.0, 0.0), v(1.0, 0.0), v(1.0, 0.0), v(1.0, 1.0)));
+        // an endpoint resting on the other segment's middle
+        assert!(segments_intersect(v(0.0, 0.0), v(2.0, 0.0), v(1.0, 0.0), v(1.0, 1.0)));
+    }
+
+    #[test]
+    fn zero_length_knife() {
+        let (a, b) = (v(0.0, 0.0), v(2.0, 0.0));
+        assert!(segments_intersect(a, b, v(1.0, 0.0), v(1.0, 0.0)));
+        assert!(segments_intersect(a, b, v(2.0, 0.0), v(2.0, 0.0)));
+        assert!(!segments_intersect(a, b, v(1.0, 1.0), v(1.0, 1.0)));
+        assert!(!segments_intersect(a, b, v(3.0, 0.0), v(3.0, 0.0)));
+    }
+
+    #[test]
+    fn both_points() {
+        assert!(segments_intersect(v(1.0, 1.0), v(1.0, 1.0), v(1.0, 1.0), v(1.0, 1.0)));
+        assert!(!segments_intersect(v(1.0, 1.0), v(1.0, 1.0), v(1.0, 2.0), v(1.0, 2.0)));
+    }
+
+    #[test]
+    fn distances() {
+        let (a, b) = (v(0.0, 0.0), v(2.0, 0.0));
+        assert_eq!(segment_distance(a, b, v(1.0, -1.0), v(1.0, 1.0)), 0.0);
+        assert_eq!(segment_distance(a, b, v(1.0, 1.0), v(1.0, 3.0)), 1.0);
+        // past an end, measured from the endpoint
+        assert_eq!(segment_distance(a, b, v(5.0, -4.0), v(5.0, 4.0)), 3.0);
+        assert_eq!(point_segment_distance(v(3.0, 4.0), a, a), 5.0);
+    }
+
+    #[test]
+    fn sweeps() {
+        // straight through a circle of radius 1 at 5, hitting it at 4 of 10
+        assert_eq!(sweep_circle(v(0.0, 0.0), v(10.0, 0.0), v(5.0, 0.0), 1.0), Some(0.4));
+        assert_eq!(sweep_circle(v(0.0, 0.0), v(3.0, 0.0), v(5.0, 0.0), 1.0), None);
+        assert_eq!(sweep_circle(v(5.0, 0.5), v(10.0, 0.0), v(5.0, 0.0), 1.0), None);
+
+        // across a thin wall from (5, -5) to (5, 5), 1 thick either side, around its rounded end, and past it
+        let (a, b) = (v(5.0, -5.0), v(5.0, 5.0));
+        assert_eq!(sweep_capsule(v(0.0, 0.0), v(10.0, 0.0), a, b, 1.0), Some(0.4));
+        let t = sweep_capsule(v(0.0, 6.0), v(10.0, 6.0), a, b, 1.5).unwrap();
+        assert!((t - (5.0 - 1.25_f32.sqrt()) / 10.0).abs() < 1e-5, "{}", t);
+        assert_eq!(sweep_capsule(v(0.0, 7.0), v(10.0, 7.0), a, b, 1.0), None);
+    }
+}
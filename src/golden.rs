@@ -0,0 +1,139 @@
+//! Golden-image checks for the renderer.
+//!
+//! `--golden DIR` renders a fixed set of scenes offscreen and compares each
+//! against `DIR/<case>.png`, failing if more than a sliver of pixels differ
+//! by more than a small tolerance. `--update-golden` writes the current
+//! renders instead, for when a rendering change is intentional. Rendering
+//! needs a GPU context, so this runs in a window rather than under `cargo test`.
+
+use crate::materials::RopeMaterials;
+use crate::palette::Palette;
+use crate::renderer::MacroquadRenderer;
+use clothsim::{rng, scene, Rng, SimRenderer};
+use egui_macroquad::macroquad::prelude::*;
+use std::path::Path;
+
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 600;
+/// Steps to run before rendering, so ropes and cloth are hanging under load.
+const STEPS: usize = 120;
+/// Largest per-channel difference that still counts as a match, to absorb driver rounding.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Fraction of pixels allowed to differ by more than the tolerance.
+const MAX_MISMATCHED: f32 = 0.001;
+
+struct Case {
+    name: &'static str,
+    scene: &'static str,
+    heatmap: bool,
+    /// Rope material; anything animated by `_Time` can't go here.
+    shader: &'static str,
+}
+
+// glow pulses with the clock, so it's left off everywhere
+const CASES: &[Case] = &[
+    Case { name: "rope", scene: "rope", heatmap: false, shader: "Flat" },
+    Case { name: "cloth", scene: "cloth", heatmap: false, shader: "Flat" },
+    Case { name: "cloth_heatmap", scene: "cloth", heatmap: true, shader: "Flat" },
+    Case { name: "cloth_strain_gradient", scene: "cloth", heatmap: false, shader: "Strain gradient" },
+];
+
+/// Renders or checks every case, returning the process exit code.
+pub fn run(dir: &Path, update: bool) -> i32 {
+    let palette = Palette::default();
+    let mut materials = RopeMaterials::default();
+    let mut failures = 0;
+
+    for case in CASES {
+        materials.select_by_name(case.shader);
+        let image = render(case, &palette, &materials);
+        let path = dir.join(format!("{}.png", case.name));
+
+        if update {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                eprintln!("failed to create {}: {}", dir.display(), e);
+                return 1;
+            }
+            image.export_png(&path.to_string_lossy());
+            println!("{}: wrote {}", case.name, path.display());
+            continue;
+        }
+
+        let result = match std::fs::read(&path) {
+            Ok(bytes) => compare(&image, &Image::from_file_with_format(&bytes, Some(ImageFormat::Png))),
+            Err(e) => Err(format!("can't read {} ({}), run with --update-golden to create it", path.display(), e)),
+        };
+        match result {
+            Ok(()) => println!("{}: ok", case.name),
+            Err(message) => {
+                println!("{}: FAILED, {}", case.name, message);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        println!("{} of {} golden images differ", failures, CASES.len());
+        1
+    } else {
+        0
+    }
+}
+
+/// Steps the case's scene from the default seed and draws it into an offscreen target.
+fn render(case: &Case, palette: &Palette, materials: &RopeMaterials) -> Image {
+    let (width, height) = (WIDTH as f32, HEIGHT as f32);
+    let mut world = scene::builtin(case.scene, width, height).expect("golden cases use built-in scenes");
+    world.rng = Rng::new(rng::DEFAULT_SEED);
+    for _ in 0..STEPS {
+        world.step(world.params.dt);
+    }
+
+    let target = render_target(WIDTH, HEIGHT);
+    let view = Rect::new(0.0, 0.0, width, height);
+    set_camera(&Camera2D {
+        render_target: Some(target),
+        ..Camera2D::from_display_rect(view)
+    });
+    clear_background(palette.background);
+
+    let mut renderer = MacroquadRenderer::new(palette, materials, view);
+    renderer.show_heatmap = case.heatmap;
+    renderer.draw_world(&world);
+    renderer.flush();
+
+    // switching cameras flushes the draws into the target
+    set_default_camera();
+    target.texture.get_texture_data()
+}
+
+/// Compares a fresh render against a reference loaded from disk. `export_png`
+/// flips rows on the way out, so the reference is read bottom-up.
+fn compare(render: &Image, reference: &Image) -> Result<(), String> {
+    if (reference.width, reference.height) != (render.width, render.height) {
+        return Err(format!(
+            "reference is {}x{}, render is {}x{}",
+            reference.width, reference.height, render.width, render.height
+        ));
+    }
+
+    let (width, height) = (render.width as usize, render.height as usize);
+    let (render, reference) = (render.get_image_data(), reference.get_image_data());
+    let mut mismatched = 0;
+    for y in 0..height {
+        for x in 0..width {
+            let a = render[y * width + x];
+            let b = reference[(height - 1 - y) * width + x];
+            if a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE) {
+                mismatched += 1;
+            }
+        }
+    }
+
+    let fraction = mismatched as f32 / (width * height) as f32;
+    if fraction > MAX_MISMATCHED {
+        Err(format!("{} pixels ({:.2}%) differ", mismatched, fraction * 100.0))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,377 @@
+//! The experimental GPU solve, for cloth too big to solve on the CPU every
+//! frame. Nodes live in float textures, one per pixel, and the integration and
+//! `clothsim::jacobi`'s relaxation of the links run as fragment shaders
+//! rendering from one texture into the other. Everything else in the step stays
+//! on the CPU, so positions go up to the GPU and come back down once a step.
+//!
+//! Scenes with anything the relaxation doesn't handle, and GPUs that can't
+//! render into float textures, are solved on the CPU as usual.
+
+use clothsim::jacobi::{self, Adjacency, MAX_LINKS, RELAXATION};
+use clothsim::{Vec2, World, NODE_RADIUS};
+use egui_macroquad::macroquad::miniquad::gl::*;
+use egui_macroquad::macroquad::miniquad::{
+    Bindings, Buffer, BufferLayout, BufferType, Context, FilterMode, PassAction, Pipeline, RenderPass, Shader,
+    ShaderMeta, Texture, TextureParams, UniformBlockLayout, UniformDesc, UniformType, VertexAttribute, VertexFormat,
+};
+use egui_macroquad::macroquad::window::get_internal_gl;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tracing::warn;
+
+/// Nodes per row of the textures, so 100k nodes take under 200 rows, and the
+/// table of links, `MAX_LINKS` times as wide, fits any GPU's textures.
+const TEXTURE_WIDTH: usize = 512;
+
+const VERTEX_SHADER: &str = r#"#version 100
+attribute vec2 position;
+
+varying highp vec2 uv;
+
+void main() {
+    uv = position * 0.5 + 0.5;
+    gl_Position = vec4(position, 0, 1);
+}"#;
+
+/// Each node is `(pos, last_pos)` in `state`, `(vel, force / mass)` in
+/// `motion`, and `(1 / mass, fixed)` in `mass`. Free nodes move on like
+/// `Node::integrate`, and fixed ones stay put.
+const INTEGRATE_SHADER: &str = r#"#version 100
+precision highp float;
+
+varying highp vec2 uv;
+
+uniform sampler2D state;
+uniform sampler2D motion;
+uniform sampler2D mass;
+uniform float dt;
+
+void main() {
+    vec4 node = texture2D(state, uv);
+    if (texture2D(mass, uv).y > 0.5) {
+        gl_FragColor = node;
+        return;
+    }
+    vec4 moving = texture2D(motion, uv);
+    vec2 vel = moving.xy + moving.zw * dt;
+    gl_FragColor = vec4(node.xy + vel * dt, node.xy);
+}"#;
+
+/// One iteration of `Adjacency::relax`. A node's links are the `MAX_LINKS`
+/// pixels of `links` in a row from `MAX_LINKS` times its column, each the
+/// texture coordinates of the node at the other end and the rest length,
+/// negative past the last one.
+const RELAX_SHADER: &str = r#"#version 100
+precision highp float;
+
+#define MAX_LINKS {max_links}
+
+varying highp vec2 uv;
+
+uniform sampler2D state;
+uniform sampler2D mass;
+uniform sampler2D links;
+uniform float rigidity;
+uniform float relaxation;
+uniform float ground;
+uniform float width;
+
+void main() {
+    vec4 node = texture2D(state, uv);
+    vec2 weights = texture2D(mass, uv).xy;
+    if (weights.y > 0.5) {
+        gl_FragColor = node;
+        return;
+    }
+    float first = floor(uv.x * width) * float(MAX_LINKS);
+    vec2 correction = vec2(0.0);
+    float count = 0.0;
+    for (int k = 0; k < MAX_LINKS; k++) {
+        vec4 link = texture2D(links, vec2((first + float(k) + 0.5) / (width * float(MAX_LINKS)), uv.y));
+        if (link.z < 0.0) {
+            break;
+        }
+        vec2 r = texture2D(state, link.xy).xy - node.xy;
+        float dist = length(r);
+        float other = texture2D(mass, link.xy).x;
+        vec2 offs = vec2(0.0);
+        if (dist > 0.0) {
+            offs = r / dist * (dist - link.z) * rigidity * weights.x / (weights.x + other);
+        }
+        if (dist < link.z) {
+            offs *= 0.5;
+        }
+        correction += offs;
+        count += 1.0;
+    }
+    vec2 pos = node.xy + correction * relaxation / max(count, 1.0);
+    pos.y = min(pos.y, ground);
+    gl_FragColor = vec4(pos, node.zw);
+}"#;
+
+#[repr(C)]
+struct IntegrateUniforms {
+    dt: f32,
+}
+
+#[repr(C)]
+struct RelaxUniforms {
+    rigidity: f32,
+    relaxation: f32,
+    ground: f32,
+    width: f32,
+}
+
+/// The textures for one world's nodes and links, rebuilt whenever its links change.
+struct Targets {
+    height: usize,
+    /// Which of the links it was built for, from `fingerprint`.
+    fingerprint: u64,
+    /// Rendered back and forth between, the latest in `state[current]`.
+    state: [Texture; 2],
+    passes: [RenderPass; 2],
+    current: usize,
+    motion: Texture,
+    mass: Texture,
+    links: Texture,
+}
+
+impl Targets {
+    fn new(ctx: &mut Context, nodes: usize, adjacency: &Adjacency, fingerprint: u64) -> Self {
+        let height = nodes.div_ceil(TEXTURE_WIDTH).max(1);
+        let state = [float_texture(ctx, TEXTURE_WIDTH, height), float_texture(ctx, TEXTURE_WIDTH, height)];
+        let passes = state.map(|texture| RenderPass::new(ctx, texture, None));
+        let links = float_texture(ctx, TEXTURE_WIDTH * MAX_LINKS, height);
+
+        let coords = |i: usize| {
+            let (column, row) = (i % TEXTURE_WIDTH, i / TEXTURE_WIDTH);
+            [(column as f32 + 0.5) / TEXTURE_WIDTH as f32, (row as f32 + 0.5) / height as f32]
+        };
+        let mut table = vec![[0.0, 0.0, -1.0, 0.0]; TEXTURE_WIDTH * MAX_LINKS * height];
+        for (i, node_links) in adjacency.links.iter().enumerate() {
+            for (k, &(other, rest_length)) in node_links.iter().enumerate() {
+                let [u, v] = coords(other);
+                table[i * MAX_LINKS + k] = [u, v, rest_length, 0.0];
+            }
+        }
+        upload(ctx, links, &table);
+
+        Self {
+            height,
+            fingerprint,
+            state,
+            passes,
+            current: 0,
+            motion: float_texture(ctx, TEXTURE_WIDTH, height),
+            mass: float_texture(ctx, TEXTURE_WIDTH, height),
+            links,
+        }
+    }
+
+    fn delete(&self, ctx: &mut Context) {
+        for pass in self.passes.iter() {
+            pass.delete(ctx);
+        }
+        for texture in self.state.iter().chain([&self.motion, &self.mass, &self.links]) {
+            texture.delete();
+        }
+    }
+
+    /// A pixel for each node, padded out to fill the textures.
+    fn pixels(&self, arena_len: usize, pixel: impl Fn(usize) -> [f32; 4]) -> Vec<[f32; 4]> {
+        (0..TEXTURE_WIDTH * self.height).map(|i| if i < arena_len { pixel(i) } else { [0.0; 4] }).collect()
+    }
+}
+
+pub struct GpuSolver {
+    integrate: Pipeline,
+    relax: Pipeline,
+    vertices: Buffer,
+    indices: Buffer,
+    targets: Option<Targets>,
+}
+
+impl GpuSolver {
+    /// Compiles the shaders, or returns None, for the caller to stay on the CPU,
+    /// if they don't compile or the GPU can't render into float textures.
+    pub fn new() -> Option<Self> {
+        let mut gl = unsafe { get_internal_gl() };
+        gl.flush();
+        let ctx = gl.quad_context;
+
+        let test = float_texture(ctx, 1, 1);
+        let pass = RenderPass::new(ctx, test, None);
+        ctx.begin_pass(pass, PassAction::Nothing);
+        let complete = unsafe { glCheckFramebufferStatus(GL_FRAMEBUFFER) } == GL_FRAMEBUFFER_COMPLETE;
+        ctx.end_render_pass();
+        pass.delete(ctx);
+        test.delete();
+        if !complete {
+            warn!("can't render into float textures, so the GPU solve is unavailable");
+            return None;
+        }
+
+        let pipeline = |ctx: &mut Context, fragment: &str, images: &[&str], uniforms: &[&str]| {
+            let meta = ShaderMeta {
+                images: images.iter().map(|name| name.to_string()).collect(),
+                uniforms: UniformBlockLayout {
+                    uniforms: uniforms.iter().map(|name| UniformDesc::new(name, UniformType::Float1)).collect(),
+                },
+            };
+            let shader = Shader::new(ctx, VERTEX_SHADER, fragment, meta)
+                .map_err(|err| warn!("GPU solve shader failed to compile: {:?}", err))
+                .ok()?;
+            let attributes = [VertexAttribute::new("position", VertexFormat::Float2)];
+            Some(Pipeline::new(ctx, &[BufferLayout::default()], &attributes, shader))
+        };
+        let integrate = pipeline(ctx, INTEGRATE_SHADER, &["state", "motion", "mass"], &["dt"])?;
+        let relax_shader = RELAX_SHADER.replace("{max_links}", &MAX_LINKS.to_string());
+        let relax = pipeline(
+            ctx,
+            &relax_shader,
+            &["state", "mass", "links"],
+            &["rigidity", "relaxation", "ground", "width"],
+        )?;
+
+        let corners: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0];
+        let vertices = Buffer::immutable(ctx, BufferType::VertexBuffer, &corners);
+        let indices = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2, 0, 2, 3]);
+        Some(Self { integrate, relax, vertices, indices, targets: None })
+    }
+
+    /// Integrates `world`'s nodes and relaxes its links `solver_iterations`
+    /// times on the GPU, in place of `World::integrate` and
+    /// `World::solve_constraints`. Returns false, leaving the world alone, if
+    /// it has anything `jacobi::solvable` rules out or a node with more than
+    /// `MAX_LINKS` links, for the caller to solve it on the CPU instead.
+    pub fn integrate_and_solve(&mut self, world: &mut World, dt: f32) -> bool {
+        if !jacobi::solvable(world) || world.arena.is_empty() {
+            return false;
+        }
+        let mut gl = unsafe { get_internal_gl() };
+        gl.flush();
+        let ctx = gl.quad_context;
+
+        let fingerprint = fingerprint(world);
+        if self.targets.as_ref().map(|targets| targets.fingerprint) != Some(fingerprint) {
+            if let Some(targets) = self.targets.take() {
+                targets.delete(ctx);
+            }
+            let Some(adjacency) = Adjacency::new(&world.constraints, world.arena.len()) else {
+                return false;
+            };
+            self.targets = Some(Targets::new(ctx, world.arena.len(), &adjacency, fingerprint));
+        }
+        let targets = self.targets.as_mut().unwrap();
+
+        let arena = &world.arena;
+        let state = targets.pixels(arena.len(), |i| {
+            let node = &arena[i];
+            [node.pos.x, node.pos.y, node.last_pos.x, node.last_pos.y]
+        });
+        upload(ctx, targets.state[targets.current], &state);
+        let motion = targets.pixels(arena.len(), |i| {
+            let node = &arena[i];
+            let acc = node.force / node.mass;
+            [node.vel.x, node.vel.y, acc.x, acc.y]
+        });
+        upload(ctx, targets.motion, &motion);
+        let mass = targets.pixels(arena.len(), |i| [1.0 / arena[i].mass, arena[i].fixed as u8 as f32, 0.0, 0.0]);
+        upload(ctx, targets.mass, &mass);
+
+        let run = |ctx: &mut Context, targets: &mut Targets, pipeline: &Pipeline, images: Vec<Texture>| {
+            let next = 1 - targets.current;
+            ctx.begin_pass(targets.passes[next], PassAction::Nothing);
+            ctx.apply_pipeline(pipeline);
+            let images = std::iter::once(targets.state[targets.current]).chain(images).collect();
+            ctx.apply_bindings(&Bindings { vertex_buffers: vec![self.vertices], index_buffer: self.indices, images });
+            next
+        };
+        let next = run(ctx, targets, &self.integrate, vec![targets.motion, targets.mass]);
+        ctx.apply_uniforms(&IntegrateUniforms { dt });
+        ctx.draw(0, 6, 1);
+        ctx.end_render_pass();
+        targets.current = next;
+
+        let uniforms = RelaxUniforms {
+            rigidity: world.params.rigidity,
+            relaxation: RELAXATION,
+            ground: world.ground_y - NODE_RADIUS,
+            width: TEXTURE_WIDTH as f32,
+        };
+        for _ in 0..world.params.solver_iterations {
+            let next = run(ctx, targets, &self.relax, vec![targets.mass, targets.links]);
+            ctx.apply_uniforms(&uniforms);
+            ctx.draw(0, 6, 1);
+            ctx.end_render_pass();
+            targets.current = next;
+        }
+
+        let mut solved = vec![[0.0f32; 4]; TEXTURE_WIDTH * targets.height];
+        ctx.begin_pass(targets.passes[targets.current], PassAction::Nothing);
+        unsafe {
+            glReadPixels(
+                0,
+                0,
+                TEXTURE_WIDTH as i32,
+                targets.height as i32,
+                GL_RGBA,
+                GL_FLOAT,
+                solved.as_mut_ptr() as *mut _,
+            );
+        }
+        ctx.end_render_pass();
+
+        for (node, [x, y, last_x, last_y]) in world.arena.iter_mut().zip(solved) {
+            if !node.fixed {
+                node.pos = Vec2::new(x, y);
+                node.last_pos = Vec2::new(last_x, last_y);
+            }
+        }
+        true
+    }
+}
+
+/// Which nodes the links join and how long they are, to tell when the table of links needs uploading again.
+fn fingerprint(world: &World) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    world.arena.len().hash(&mut hasher);
+    for constraint in world.constraints.iter() {
+        (constraint.a, constraint.b, constraint.rest_length.to_bits()).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// An RGBA texture of 32-bit floats, which miniquad has no format for, so it's
+/// made as a byte texture and given float storage behind miniquad's back.
+fn float_texture(ctx: &mut Context, width: usize, height: usize) -> Texture {
+    let (width, height) = (width as u32, height as u32);
+    let params = TextureParams { width, height, filter: FilterMode::Nearest, ..Default::default() };
+    let texture = Texture::new_render_texture(ctx, params);
+    with_texture_bound(ctx, texture, || unsafe {
+        let (width, height) = (width as i32, height as i32);
+        glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA32F as i32, width, height, 0, GL_RGBA, GL_FLOAT, std::ptr::null());
+    });
+    texture
+}
+
+/// Replaces all of a float texture with `pixels`, a row at a time from the bottom.
+fn upload(ctx: &mut Context, texture: Texture, pixels: &[[f32; 4]]) {
+    assert_eq!(pixels.len(), (texture.width * texture.height) as usize);
+    with_texture_bound(ctx, texture, || unsafe {
+        let (width, height) = (texture.width as i32, texture.height as i32);
+        glTexSubImage2D(GL_TEXTURE_2D, 0, 0, 0, width, height, GL_RGBA, GL_FLOAT, pixels.as_ptr() as *const _);
+    });
+}
+
+/// Runs `f` with `texture` bound to the first texture unit, with miniquad's
+/// record of what's bound cleared first so it rebinds what it needs after.
+fn with_texture_bound(ctx: &mut Context, texture: Texture, f: impl FnOnce()) {
+    ctx.commit_frame();
+    unsafe {
+        glActiveTexture(GL_TEXTURE0);
+        glBindTexture(GL_TEXTURE_2D, texture.gl_internal_id());
+    }
+    f();
+    unsafe { glBindTexture(GL_TEXTURE_2D, 0) };
+}
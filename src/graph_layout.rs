@@ -0,0 +1,123 @@
+//! A force-directed layout of a scene's links as an abstract graph, apart
+//! from where the nodes really are: linked nodes pull together like springs
+//! and every pair pushes apart, as in Fruchterman and Reingold's "Graph
+//! Drawing by Force-directed Placement", so the shape of the network shows
+//! through however tangled the scene is.
+
+use crate::world::World;
+use glam::Vec2;
+
+/// How far a node can move in the first step, in the unit square the layout starts in.
+const START_TEMPERATURE: f32 = 0.1;
+/// How much of its temperature the layout keeps each step.
+const COOLING: f32 = 0.95;
+/// Temperature below which nodes have stopped moving enough to bother.
+const SETTLED_TEMPERATURE: f32 = 1e-3;
+
+#[derive(Clone, Debug)]
+pub struct GraphLayout {
+    /// Where each node is drawn, by index into the arena.
+    pub positions: Vec<Vec2>,
+    /// The linked pairs of nodes the layout was made for.
+    edges: Vec<(usize, usize)>,
+    temperature: f32,
+}
+
+impl GraphLayout {
+    /// Starts from the nodes' real positions squeezed into a unit square, so
+    /// the layout keeps the scene's rough shape where the links allow.
+    pub fn new(world: &World) -> Self {
+        let start = (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+        let (min, max) = world.arena.iter().fold(start, |(min, max), node| (min.min(node.pos), max.max(node.pos)));
+        let scale = 1.0 / (max - min).max_element().max(1e-6);
+        // nodes on top of each other would have no direction to push apart in
+        let positions = world
+            .arena
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.pos - min) * scale + Vec2::new(i as f32, (i * 7 % 13) as f32) * 1e-4)
+            .collect();
+        Self { positions, edges: edges(world), temperature: START_TEMPERATURE }
+    }
+
+    /// Catches up with `world` after its links or nodes change, starting over
+    /// if nodes came or went and warming back up if only the links changed.
+    pub fn sync(&mut self, world: &World) {
+        if world.arena.len() != self.positions.len() {
+            *self = Self::new(world);
+        } else if edges(world) != self.edges {
+            self.edges = edges(world);
+            self.temperature = START_TEMPERATURE;
+        }
+    }
+
+    /// Whether it's stopped moving.
+    pub fn settled(&self) -> bool {
+        self.temperature < SETTLED_TEMPERATURE
+    }
+
+    /// Moves every node by the forces on it, at most as far as the temperature, and cools.
+    pub fn step(&mut self) {
+        let n = self.positions.len();
+        if n == 0 || self.settled() {
+            return;
+        }
+        // the ideal distance between nodes, spreading them evenly over a unit square
+        let k = (1.0 / n as f32).sqrt();
+        let mut moves = vec![Vec2::ZERO; n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let offset = self.positions[i] - self.positions[j];
+                let distance = offset.length().max(1e-6);
+                let push = offset / distance * (k * k / distance);
+                moves[i] += push;
+                moves[j] -= push;
+            }
+        }
+        for &(a, b) in self.edges.iter() {
+            let offset = self.positions[a] - self.positions[b];
+            let pull = offset * (offset.length() / k);
+            moves[a] -= pull;
+            moves[b] += pull;
+        }
+        for (pos, movement) in self.positions.iter_mut().zip(moves) {
+            *pos += movement.clamp_length_max(self.temperature);
+        }
+        self.temperature *= COOLING;
+    }
+}
+
+fn edges(world: &World) -> Vec<(usize, usize)> {
+    world.constraints.iter().map(|constraint| (constraint.a, constraint.b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulls_linked_nodes_together_and_settles() {
+        // two ropes hung right next to each other, but not linked
+        let mut world = World::new(1000.0);
+        world.add_rope(Vec2::new(100.0, 100.0), 5);
+        world.add_rope(Vec2::new(110.0, 100.0), 5);
+        let mut layout = GraphLayout::new(&world);
+        while !layout.settled() {
+            layout.step();
+        }
+        let gap = |a: usize, b: usize| layout.positions[a].distance(layout.positions[b]);
+        for i in 0..4 {
+            assert!(gap(i, i + 1) < gap(i, i + 5), "{} {}", gap(i, i + 1), gap(i, i + 5));
+        }
+
+        // cutting a link warms it back up, and a new node starts it over
+        let positions = layout.positions.clone();
+        world.constraints.remove(0);
+        layout.sync(&world);
+        assert!(!layout.settled());
+        assert_eq!(layout.positions, positions);
+        world.add_rope(Vec2::new(300.0, 100.0), 1);
+        layout.sync(&world);
+        assert_eq!(layout.positions.len(), 11);
+    }
+}
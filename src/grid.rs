@@ -0,0 +1,66 @@
+use crate::camera::Camera;
+use crate::palette::Palette;
+use egui_macroquad::macroquad::prelude::*;
+
+const BASE_SPACING: f32 = 50.0;
+const MIN_PIXEL_SPACING: f32 = 40.0;
+const LABEL_SIZE: f32 = 16.0;
+
+const GRID_ALPHA: f32 = 0.08;
+const AXIS_ALPHA: f32 = 0.3;
+const LABEL_ALPHA: f32 = 0.5;
+
+/// World units between grid lines, doubled or halved from `BASE_SPACING`
+/// until lines are at least `MIN_PIXEL_SPACING` apart on screen.
+fn spacing(camera: &Camera) -> f32 {
+    let mut spacing = BASE_SPACING;
+    while spacing * camera.zoom < MIN_PIXEL_SPACING {
+        spacing *= 2.0;
+    }
+    while spacing * camera.zoom >= MIN_PIXEL_SPACING * 2.0 {
+        spacing /= 2.0;
+    }
+    spacing
+}
+
+fn grid_lines(start: f32, end: f32, spacing: f32) -> impl Iterator<Item = f32> {
+    let first = (start / spacing).floor() as i64;
+    let last = (end / spacing).ceil() as i64;
+    (first..=last).map(move |i| i as f32 * spacing)
+}
+
+/// Draws grid lines and axes in `color`. Expects the world camera to be active.
+pub fn draw_grid(camera: &Camera, color: Color) {
+    let view = camera.visible_rect();
+    let spacing = spacing(camera);
+    let width = 1.0 / camera.zoom;
+    let grid_color = Palette::with_alpha(color, GRID_ALPHA);
+    let axis_color = Palette::with_alpha(color, AXIS_ALPHA);
+
+    for x in grid_lines(view.left(), view.right(), spacing) {
+        let c = if x == 0.0 { axis_color } else { grid_color };
+        draw_line(x, view.top(), x, view.bottom(), width, c);
+    }
+
+    for y in grid_lines(view.top(), view.bottom(), spacing) {
+        let c = if y == 0.0 { axis_color } else { grid_color };
+        draw_line(view.left(), y, view.right(), y, width, c);
+    }
+}
+
+/// Draws coordinate labels along the top and left edges, at `scale` times their size. Expects screen space.
+pub fn draw_grid_labels(camera: &Camera, scale: f32, color: Color) {
+    let view = camera.visible_rect();
+    let spacing = spacing(camera);
+    let label_color = Palette::with_alpha(color, LABEL_ALPHA);
+
+    for x in grid_lines(view.left(), view.right(), spacing) {
+        let screen_x = camera.world_to_screen(Vec2::new(x, 0.0)).x;
+        draw_text(&format!("{}", x), screen_x + 2.0, LABEL_SIZE * scale, LABEL_SIZE * scale, label_color);
+    }
+
+    for y in grid_lines(view.top(), view.bottom(), spacing) {
+        let screen_y = camera.world_to_screen(Vec2::new(0.0, y)).y;
+        draw_text(&format!("{}", y), 2.0, screen_y - 2.0, LABEL_SIZE * scale, label_color);
+    }
+}
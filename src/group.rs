@@ -0,0 +1,201 @@
+//! Named groups of links, tagged when a scene is built, so a set of them
+//! can be changed all at once: made rigid or springy, broken, frozen in
+//! place, or drawn in a color of their own. The truss bridge's hangers, for
+//! one, can be cut together to watch the deck drop.
+//!
+//! Like muscles, a group picks its links out by the nodes they join, so it
+//! holds on to them through cuts elsewhere and a stiffness change remaking a
+//! constraint as a band, and simply has nothing to act on where a link is
+//! gone. Nodes being removed renumber its pairs along with everything else,
+//! and a node split, shattered, or frayed hands its pairs on with its links.
+
+use crate::band::Band;
+use crate::constraint::{Constraint, SeveredLink};
+use crate::event::SimEvent;
+use crate::weave::Links;
+use crate::world::World;
+use glam::Vec2;
+
+/// Damping for bands a group's rigid links are remade as, which keeps them from ringing.
+const GROUP_BAND_DAMPING: f32 = 0.5;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkGroup {
+    /// What it's called, one word so it fits on a scene file's line.
+    pub name: String,
+    /// The node pairs its links join, either way round.
+    pub links: Vec<(usize, usize)>,
+    /// The color its links are drawn in over their usual one, as RGB, if it has one.
+    pub color: Option<[u8; 3]>,
+    /// Nodes `freeze` pinned, which `thaw` lets go of again. Ones that were
+    /// already pinned are left out, so thawing doesn't free them.
+    pub frozen: Vec<usize>,
+}
+
+impl LinkGroup {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), ..Self::default() }
+    }
+
+    /// Whether it has the link between `a` and `b`. Links that moved to a
+    /// split, shattered, or frayed node's pieces are still had under their new ends.
+    pub fn joins(&self, a: usize, b: usize) -> bool {
+        self.links.iter().any(|&pair| pair == (a, b) || pair == (b, a))
+    }
+
+    /// Indices into `world.constraints` of its rigid links.
+    pub fn constraints(&self, world: &World) -> Vec<usize> {
+        (0..world.constraints.len()).filter(|&i| self.joins(world.constraints[i].a, world.constraints[i].b)).collect()
+    }
+
+    /// Indices into `world.bands` of its springy links.
+    pub fn bands(&self, world: &World) -> Vec<usize> {
+        (0..world.bands.len()).filter(|&i| self.joins(world.bands[i].a, world.bands[i].b)).collect()
+    }
+
+    /// Every node its links join, in order, without repeats.
+    pub fn nodes(&self) -> Vec<usize> {
+        let mut nodes: Vec<usize> = self.links.iter().flat_map(|&(a, b)| [a, b]).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        !self.frozen.is_empty()
+    }
+}
+
+/// The index into `world.groups` of the group called `name`.
+pub fn find(world: &World, name: &str) -> Option<usize> {
+    world.groups.iter().position(|group| group.name == name)
+}
+
+/// Adds the links between each of `links`' pairs to the group called
+/// `name`, starting it if there isn't one. Returns its index.
+pub fn tag(world: &mut World, name: &str, links: impl IntoIterator<Item = (usize, usize)>) -> usize {
+    let i = find(world, name).unwrap_or_else(|| {
+        world.groups.push(LinkGroup::new(name));
+        world.groups.len() - 1
+    });
+    world.groups[i].links.extend(links);
+    i
+}
+
+/// Remakes group `group`'s links, constraints and bands alike, as `stiffness`
+/// says, at the rest lengths they had. Rigid ones break at the params' break
+/// threshold, and omitted ones are taken away without breaking. Returns how
+/// many links were remade.
+pub fn set_stiffness(world: &mut World, group: usize, stiffness: Links) -> usize {
+    let group = world.groups[group].clone();
+    let (constraints, kept): (Vec<Constraint>, Vec<Constraint>) =
+        world.constraints.iter().partition(|constraint| group.joins(constraint.a, constraint.b));
+    let (bands, kept_bands): (Vec<Band>, Vec<Band>) = world.bands.iter().partition(|band| group.joins(band.a, band.b));
+    world.constraints = kept;
+    world.bands = kept_bands;
+    let links = constraints.iter().map(|link| (link.a, link.b, link.rest_length, GROUP_BAND_DAMPING));
+    let links: Vec<_> = links.chain(bands.iter().map(|band| (band.a, band.b, band.rest_length, band.damping))).collect();
+    for &(a, b, rest_length, damping) in links.iter() {
+        match stiffness {
+            Links::Omitted => {}
+            Links::Rigid => {
+                let break_threshold = rest_length * world.params.break_threshold;
+                world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+            }
+            Links::Spring(stiffness) => world.bands.push(Band { a, b, rest_length, stiffness, pushes: false, damping }),
+        }
+    }
+    links.len()
+}
+
+/// Breaks every link in group `group`, queueing a `ConstraintBroken` for
+/// each rigid one as if it had snapped. Returns how many were broken.
+pub fn break_all(world: &mut World, group: usize) -> usize {
+    let group = &world.groups[group];
+    let (arena, wrap, events) = (&world.arena, &world.wrap, &mut world.events);
+    let before = world.constraints.len() + world.bands.len();
+    world.constraints.retain(|constraint| {
+        let broken = group.joins(constraint.a, constraint.b);
+        if broken {
+            events.push(SimEvent::ConstraintBroken(SeveredLink::new(constraint, arena, wrap)));
+        }
+        !broken
+    });
+    world.bands.retain(|band| !group.joins(band.a, band.b));
+    before - world.constraints.len() - world.bands.len()
+}
+
+/// Pins every free node group `group`'s links join, where it is, stopped.
+pub fn freeze(world: &mut World, group: usize) {
+    let mut frozen = Vec::new();
+    for i in world.groups[group].nodes() {
+        if let Some(node) = world.arena.get_mut(i).filter(|node| !node.fixed) {
+            node.fixed = true;
+            node.vel = Vec2::ZERO;
+            node.last_pos = node.pos;
+            frozen.push(i);
+        }
+    }
+    world.groups[group].frozen.extend(frozen);
+}
+
+/// Lets go of the nodes `freeze` pinned for group `group`.
+pub fn thaw(world: &mut World, group: usize) {
+    for i in std::mem::take(&mut world.groups[group].frozen) {
+        if let Some(node) = world.arena.get_mut(i) {
+            node.fixed = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use crate::TARGET_DIST;
+
+    #[test]
+    fn acts_on_its_links_alone() {
+        let mut world = World::new(10_000.0);
+        world.add_rope(Vec2::new(100.0, 100.0), 5);
+        world.add_rope(Vec2::new(300.0, 100.0), 5);
+        let second = tag(&mut world, "second", (5..9).map(|i| (i + 1, i)));
+        assert_eq!(tag(&mut world, "second", []), second);
+        assert_eq!(world.groups[second].constraints(&world).len(), 4);
+
+        assert_eq!(set_stiffness(&mut world, second, Links::Spring(3.0)), 4);
+        assert_eq!((world.constraints.len(), world.bands.len()), (4, 4));
+        assert!(world.bands.iter().all(|band| band.a >= 5 && band.stiffness == 3.0));
+
+        freeze(&mut world, second);
+        // the rope's top was pinned already, so thawing leaves it be
+        assert_eq!(world.groups[second].frozen, vec![6, 7, 8, 9]);
+        thaw(&mut world, second);
+        assert!(world.arena[5].fixed && !world.arena[6].fixed);
+
+        set_stiffness(&mut world, second, Links::Rigid);
+        assert_eq!(break_all(&mut world, second), 4);
+        assert_eq!(world.constraints.len(), 4);
+        assert!(world.constraints.iter().all(|constraint| constraint.b < 5));
+        let broken = world.events.iter().filter(|event| matches!(event, SimEvent::ConstraintBroken(_))).count();
+        assert_eq!(broken, 4);
+    }
+
+    #[test]
+    fn follows_its_links_onto_split_nodes() {
+        let mut world = World::new(10_000.0);
+        for x in [0.0, 1.0, 2.0] {
+            world.arena.push(Node::with_pos_and_mass(Vec2::new(x, 0.0) * TARGET_DIST, 1.0));
+        }
+        let link = |a, b| Constraint { a, b, rest_length: TARGET_DIST, break_threshold: f32::INFINITY };
+        world.constraints.extend([link(0, 1), link(1, 2)]);
+        let rope = tag(&mut world, "rope", [(0, 1), (1, 2)]);
+
+        // the blade passes right over the middle node, which splits rather than losing a link
+        world.split_path(&[Vec2::new(1.0, -1.0) * TARGET_DIST, Vec2::new(1.0, 1.0) * TARGET_DIST], 0.0);
+        assert_eq!((world.arena.len(), world.constraints.len()), (4, 2));
+        assert!(world.groups[rope].joins(3, 0) || world.groups[rope].joins(3, 2));
+        assert_eq!(world.groups[rope].constraints(&world), vec![0, 1]);
+        assert_eq!(break_all(&mut world, rope), 2);
+    }
+}
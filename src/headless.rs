@@ -0,0 +1,153 @@
+use crate::cli::Cli;
+use clothsim::export::{self, CsvWriter};
+use clothsim::script::Script;
+use clothsim::{Rng, SimEvent, World};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Runs the starting scene without a window, writing node positions per step as CSV
+/// to `--out` or stdout. With `--checksums`, writes one `step,checksum` row per step
+/// instead, for comparing runs. `--dump-state` adds a JSON line per step on top of
+/// either, and `--script` runs a script on the scene. Returns the process exit code.
+pub fn run(cli: &Cli) -> i32 {
+    let world = cli
+        .params()
+        .and_then(|params| cli.scene_source().build(cli.width as f32, cli.height as f32, params));
+    let mut world = match world {
+        Ok(world) => world,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    world.rng = Rng::new(cli.seed);
+
+    let mut script = match &cli.script {
+        Some(path) => match load_script(path, &mut world) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                eprintln!("script {}: {}", path.display(), e);
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    let out: Box<dyn Write> = match &cli.out {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("failed to create {}: {}", path.display(), e);
+                return 1;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let mut dump = match &cli.dump_state {
+        Some(path) => match File::options().create(true).append(true).open(path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("failed to open {}: {}", path.display(), e);
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    let result = if cli.checksums {
+        simulate_checksums(world, cli.steps, out, &mut script, &mut dump)
+    } else {
+        simulate(world, cli.steps, out, &mut script, &mut dump)
+    };
+    let result = result.and_then(|()| dump.map_or(Ok(()), |mut dump| dump.flush()).map_err(Failure::Write));
+
+    match result {
+        Ok(()) => 0,
+        Err(Failure::Write(e)) => {
+            eprintln!("failed to write output: {}", e);
+            1
+        }
+        Err(Failure::Script(message)) => {
+            let path = cli.script.as_deref().unwrap_or(Path::new("script"));
+            eprintln!("script {}: {}", path.display(), message);
+            1
+        }
+    }
+}
+
+/// Why a headless run stopped early.
+enum Failure {
+    Write(io::Error),
+    Script(String),
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Write(e)
+    }
+}
+
+fn load_script(path: &Path, world: &mut World) -> Result<Script, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut script = Script::new(&source)?;
+    script.init(world)?;
+    Ok(script)
+}
+
+/// Runs the script's `on_frame`, if there is one, then a step.
+fn step(world: &mut World, script: &mut Option<Script>) -> Result<Vec<SimEvent>, Failure> {
+    if let Some(script) = script.as_mut() {
+        script.on_frame(world, world.params.dt).map_err(Failure::Script)?;
+    }
+    Ok(world.step(world.params.dt))
+}
+
+/// Appends the step's state to the `--dump-state` file, if there is one.
+fn dump_step(dump: &mut Option<impl Write>, step: usize, world: &World, events: &[SimEvent]) -> io::Result<()> {
+    match dump {
+        Some(out) => export::write_json_state(out, step as u64, world, events),
+        None => Ok(()),
+    }
+}
+
+fn simulate(
+    mut world: World,
+    steps: usize,
+    out: impl Write,
+    script: &mut Option<Script>,
+    dump: &mut Option<impl Write>,
+) -> Result<(), Failure> {
+    let mut csv = CsvWriter::new(out)?;
+
+    csv.write_step(0, &world)?;
+    dump_step(dump, 0, &world, &[])?;
+    for i in 1..=steps {
+        let events = step(&mut world, script)?;
+        csv.write_step(i, &world)?;
+        dump_step(dump, i, &world, &events)?;
+    }
+
+    csv.finish()?;
+    Ok(())
+}
+
+fn simulate_checksums(
+    mut world: World,
+    steps: usize,
+    mut out: impl Write,
+    script: &mut Option<Script>,
+    dump: &mut Option<impl Write>,
+) -> Result<(), Failure> {
+    writeln!(out, "step,checksum")?;
+    writeln!(out, "0,{:016x}", world.checksum())?;
+    dump_step(dump, 0, &world, &[])?;
+    for i in 1..=steps {
+        let events = step(&mut world, script)?;
+        writeln!(out, "{},{:016x}", i, world.checksum())?;
+        dump_step(dump, i, &world, &events)?;
+    }
+
+    Ok(out.flush()?)
+}
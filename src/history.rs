@@ -0,0 +1,89 @@
+//! A rolling history of simulation states for scrubbing back through: a
+//! snapshot every so many steps, with the oldest dropped once it's full.
+
+use std::collections::VecDeque;
+
+/// The latest snapshots, oldest first, each with the step it was taken at.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    snapshots: VecDeque<(u64, T)>,
+    capacity: usize,
+    /// Steps between snapshots.
+    every: u64,
+}
+
+impl<T> History<T> {
+    /// Keeps up to `capacity` snapshots, one every `every` steps, so it goes
+    /// back `capacity * every` steps at most.
+    pub fn new(capacity: usize, every: u64) -> Self {
+        Self { snapshots: VecDeque::new(), capacity: capacity.max(1), every: every.max(1) }
+    }
+
+    /// Whether a snapshot is due at `step`, which it is once `every` steps
+    /// have passed since the last one, or there's none yet.
+    pub fn due(&self, step: u64) -> bool {
+        self.snapshots.back().is_none_or(|&(last, _)| step >= last + self.every)
+    }
+
+    /// Takes `snapshot` as the state at `step`, dropping the oldest if it's full.
+    pub fn push(&mut self, step: u64, snapshot: T) {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((step, snapshot));
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// The `i`th snapshot from the oldest, with its step.
+    pub fn get(&self, i: usize) -> Option<(u64, &T)> {
+        self.snapshots.get(i).map(|(step, snapshot)| (*step, snapshot))
+    }
+
+    /// Drops every snapshot after the `i`th, for carrying on from it: what came
+    /// after it won't happen again.
+    pub fn truncate_after(&mut self, i: usize) {
+        self.snapshots.truncate(i + 1);
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_and_forgets_what_comes_after_a_rewind() {
+        let mut history = History::new(3, 5);
+        for step in 0..30 {
+            if history.due(step) {
+                history.push(step, step * 10);
+            }
+        }
+        // a snapshot every five steps, only the last three kept
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.get(0), Some((15, &150)));
+        assert_eq!(history.get(2), Some((25, &250)));
+        assert!(!history.due(29));
+        assert!(history.due(30));
+
+        // going back to the oldest and carrying on from there
+        history.truncate_after(0);
+        assert_eq!(history.len(), 1);
+        assert!(!history.due(16));
+        assert!(history.due(20));
+        history.push(20, 0);
+        assert_eq!(history.get(1), Some((20, &0)));
+        history.clear();
+        assert!(history.is_empty() && history.due(0));
+    }
+}
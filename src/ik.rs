@@ -0,0 +1,155 @@
+//! Posing chains by dragging their free end, with FABRIK (forward and backward
+//! reaching inverse kinematics).
+
+use crate::world::World;
+use glam::Vec2;
+use std::collections::VecDeque;
+
+/// Most FABRIK passes per solve.
+pub const ITERATIONS: usize = 10;
+/// Distance from the target at which a solve stops early.
+pub const TOLERANCE: f32 = 0.01;
+
+/// The nodes along the way from a chain's root to `end`, and the rest lengths
+/// of the constraints between them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chain {
+    /// Root first, `end` last.
+    pub nodes: Vec<usize>,
+    /// `lengths[i]` is the rest length between `nodes[i]` and `nodes[i + 1]`.
+    pub lengths: Vec<f32>,
+    /// Whether the root is a fixed node. Otherwise it's the node furthest from
+    /// `end` by links, and free to follow.
+    pub anchored: bool,
+}
+
+impl Chain {
+    /// The shortest chain of links from `end` to a fixed node, or if none is
+    /// connected, to the node the most links away.
+    pub fn to(world: &World, end: usize) -> Self {
+        let mut neighbours = vec![Vec::new(); world.arena.len()];
+        for constraint in world.constraints.iter() {
+            neighbours[constraint.a].push((constraint.b, constraint.rest_length));
+            neighbours[constraint.b].push((constraint.a, constraint.rest_length));
+        }
+
+        // breadth first, so the first fixed node found is the fewest links away
+        let mut previous: Vec<Option<(usize, f32)>> = vec![None; world.arena.len()];
+        let mut visited = vec![false; world.arena.len()];
+        let mut queue = VecDeque::from([end]);
+        visited[end] = true;
+        let mut root = end;
+        while let Some(node) = queue.pop_front() {
+            root = node;
+            if node != end && world.arena[node].fixed {
+                break;
+            }
+            for &(next, length) in neighbours[node].iter() {
+                if !visited[next] {
+                    visited[next] = true;
+                    previous[next] = Some((node, length));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let (mut nodes, mut lengths) = (vec![root], Vec::new());
+        while let Some((next, length)) = previous[*nodes.last().unwrap()] {
+            nodes.push(next);
+            lengths.push(length);
+        }
+        Self { nodes, lengths, anchored: root != end && world.arena[root].fixed }
+    }
+
+    /// Moves the chain's nodes so its end reaches toward `target`, keeping each
+    /// link at its rest length and an anchored root where it is. The nodes are
+    /// left at rest.
+    pub fn reach(&self, world: &mut World, target: Vec2) {
+        let mut points: Vec<Vec2> = self.nodes.iter().map(|&i| world.arena[i].pos).collect();
+        fabrik(&mut points, &self.lengths, target, self.anchored);
+        for (&i, pos) in self.nodes.iter().zip(points) {
+            let node = &mut world.arena[i];
+            if !node.fixed {
+                node.pos = pos;
+                node.last_pos = pos;
+                node.vel = Vec2::ZERO;
+            }
+        }
+    }
+}
+
+/// Moves `points` so the last one is as close to `target` as it can be, keeping
+/// `lengths[i]` between `points[i]` and `points[i + 1]`. With `anchored`, the
+/// first point stays put; without, the rest just trail after the last one.
+pub fn fabrik(points: &mut [Vec2], lengths: &[f32], target: Vec2, anchored: bool) {
+    let Some(last) = points.len().checked_sub(1) else { return };
+    let root = points[0];
+    let reach: f32 = lengths.iter().sum();
+    let toward = |from: Vec2, to: Vec2, length: f32| from + (to - from).normalize_or_zero() * length;
+
+    if anchored && root.distance(target) >= reach {
+        // out of reach, so the best it can do is point straight at it
+        for i in 0..last {
+            points[i + 1] = toward(points[i], target, lengths[i]);
+        }
+        return;
+    }
+
+    for _ in 0..ITERATIONS {
+        points[last] = target;
+        for i in (0..last).rev() {
+            points[i] = toward(points[i + 1], points[i], lengths[i]);
+        }
+        if !anchored {
+            return;
+        }
+
+        points[0] = root;
+        for i in 0..last {
+            points[i + 1] = toward(points[i], points[i + 1], lengths[i]);
+        }
+        if points[last].distance(target) < TOLERANCE {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lengths_kept(points: &[Vec2], lengths: &[f32]) -> bool {
+        points.windows(2).zip(lengths).all(|(pair, length)| (pair[0].distance(pair[1]) - length).abs() < 1e-3)
+    }
+
+    #[test]
+    fn reaches_targets_within_reach() {
+        let mut points: Vec<Vec2> = (0..5).map(|i| Vec2::new(0.0, i as f32 * 10.0)).collect();
+        let lengths = [10.0; 4];
+        fabrik(&mut points, &lengths, Vec2::new(20.0, 20.0), true);
+        assert_eq!(points[0], Vec2::ZERO);
+        assert!(points[4].distance(Vec2::new(20.0, 20.0)) < TOLERANCE);
+        assert!(lengths_kept(&points, &lengths));
+    }
+
+    #[test]
+    fn points_at_targets_out_of_reach() {
+        let mut points: Vec<Vec2> = (0..3).map(|i| Vec2::new(0.0, i as f32 * 10.0)).collect();
+        fabrik(&mut points, &[10.0, 10.0], Vec2::new(100.0, 0.0), true);
+        assert!(points[2].distance(Vec2::new(20.0, 0.0)) < 1e-4);
+    }
+
+    #[test]
+    fn finds_the_nearest_fixed_node() {
+        let mut world = World::new(500.0);
+        world.add_rope(Vec2::ZERO, 4);
+        let chain = Chain::to(&world, 3);
+        assert_eq!(chain.nodes, vec![0, 1, 2, 3]);
+        assert!(chain.anchored);
+
+        world.arena[0].fixed = false;
+        let chain = Chain::to(&world, 2);
+        assert_eq!(chain.nodes, vec![0, 1, 2]);
+        assert!(!chain.anchored);
+    }
+}
@@ -0,0 +1,41 @@
+//! Turning a picture into a hanging cloth.
+
+use clothsim::scene::{self, PinEdge};
+use clothsim::{Params, World};
+use std::path::Path;
+
+/// Cells along the image's longer side; more than this gets slow to simulate.
+const MAX_CELLS: u32 = 40;
+/// Mean alpha, out of 255, above which a cell gets a node.
+const OPAQUE_ALPHA: u32 = 128;
+
+/// Downsamples the PNG at `path` to at most `MAX_CELLS` across and builds a cloth
+/// with a node per mostly-opaque cell, hanging from `pin`.
+pub fn load(path: &Path, pin: PinEdge, width: f32, height: f32, params: Params) -> Result<World, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+    let (image_width, image_height) = image.dimensions();
+    if image_width == 0 || image_height == 0 {
+        return Err("the image is empty".to_string());
+    }
+
+    let cell = image_width.max(image_height).div_ceil(MAX_CELLS);
+    let (columns, rows) = (image_width.div_ceil(cell), image_height.div_ceil(cell));
+    let mut mask = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let xs = column * cell..((column + 1) * cell).min(image_width);
+            let ys = row * cell..((row + 1) * cell).min(image_height);
+            let pixels = xs.len() as u32 * ys.len() as u32;
+            let alpha: u32 = ys
+                .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .map(|(x, y)| image.get_pixel(x, y)[3] as u32)
+                .sum();
+            mask.push(alpha >= OPAQUE_ALPHA * pixels);
+        }
+    }
+
+    if !mask.contains(&true) {
+        return Err("the image has no opaque pixels".to_string());
+    }
+    Ok(scene::from_mask(&mask, columns as usize, pin, width, height, params))
+}
@@ -0,0 +1,130 @@
+//! Jacobi-style relaxation of the links, for solving on the GPU. The usual
+//! solver moves the ends of each link in turn, so every link sees the ones
+//! solved before it; here every node gathers the corrections from all of its
+//! links at once, from where things were at the start of the iteration, and
+//! moves by their average, so each node can be worked out on its own, one
+//! pixel of a shader each. It takes more iterations to settle, which
+//! `RELAXATION` makes up some of by moving a bit further than the average.
+//!
+//! This is the same relaxation the demo's GPU path runs, on the CPU, along
+//! with the table of links it uploads, so the shader has something to be
+//! checked against.
+
+use crate::constraint::Constraint;
+use crate::node::Node;
+use crate::world::World;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+/// Most links a node can have, which is what the shader loops over; a cloth
+/// with shear links has eight.
+pub const MAX_LINKS: usize = 8;
+/// How much farther than the average of its corrections a node moves.
+pub const RELAXATION: f32 = 1.5;
+
+/// Each node's links, as the node at the other end and the rest length.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Adjacency {
+    pub links: Vec<Vec<(usize, f32)>>,
+}
+
+impl Adjacency {
+    /// The links of `nodes` nodes, or None if any node has more than `MAX_LINKS`.
+    pub fn new(constraints: &[Constraint], nodes: usize) -> Option<Self> {
+        let mut links = vec![Vec::new(); nodes];
+        for constraint in constraints {
+            links[constraint.a].push((constraint.b, constraint.rest_length));
+            links[constraint.b].push((constraint.a, constraint.rest_length));
+        }
+        links.iter().all(|links| links.len() <= MAX_LINKS).then_some(Self { links })
+    }
+
+    /// One iteration: every free node moves toward where its links would each
+    /// put it, by `rigidity` of the way and split by inverse mass like
+    /// `Constraint::solve`, and is then kept above the ground at `ground_y`.
+    pub fn relax(&self, arena: &mut [Node], rigidity: f32, ground_y: Option<f32>) {
+        let start: Vec<Node> = arena.to_vec();
+        for (node, links) in arena.iter_mut().zip(self.links.iter()) {
+            if node.fixed {
+                continue;
+            }
+            let weight = 1.0 / node.mass;
+            let correction = links
+                .iter()
+                .map(|&(other, rest_length)| {
+                    let r = start[other].pos - node.pos;
+                    let dist = r.length();
+                    let offs = r.normalize_or_zero() * (dist - rest_length) * rigidity * weight
+                        / (weight + 1.0 / start[other].mass);
+                    if dist < rest_length {
+                        offs * 0.5
+                    } else {
+                        offs
+                    }
+                })
+                .fold(Vec2::ZERO, |sum, offs| sum + offs);
+            node.pos += correction * RELAXATION / links.len().max(1) as f32;
+            if let Some(ground_y) = ground_y {
+                node.pos.y = node.pos.y.min(ground_y - NODE_RADIUS);
+            }
+        }
+    }
+}
+
+/// Whether `world` is only nodes, links, and ground, which is all the
+/// relaxation handles: no walls, wrapping, contacts, other solvers, or
+/// anything else the full solver moves nodes for.
+pub fn solvable(world: &World) -> bool {
+    !world.walls.any()
+        && !world.wrap.x
+        && !world.wrap.y
+        && world.contacts.is_none()
+        && world.xpbd.is_none()
+        && world.lod.is_none()
+        && world.multigrid.is_none()
+        && world.water.is_none()
+        && world.sand.is_none()
+        && world.balls.is_empty()
+        && world.rails.is_empty()
+        && world.sliders.is_empty()
+        && world.rotors.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TARGET_DIST;
+
+    #[test]
+    fn relaxation_pulls_a_stretched_rope_back_together() {
+        let mut world = World::new(10_000.0);
+        world.add_rope(Vec2::new(100.0, 100.0), 6);
+        assert!(solvable(&world));
+        for (i, node) in world.arena.iter_mut().enumerate() {
+            node.pos.y = 100.0 + 2.0 * TARGET_DIST * i as f32;
+        }
+        let adjacency = Adjacency::new(&world.constraints, world.arena.len()).unwrap();
+        assert_eq!(adjacency.links[0].len(), 1);
+        assert_eq!(adjacency.links[1].len(), 2);
+
+        for _ in 0..200 {
+            adjacency.relax(&mut world.arena, 1.0, Some(world.ground_y));
+        }
+        assert_eq!(world.arena[0].pos, Vec2::new(100.0, 100.0));
+        for constraint in world.constraints.iter() {
+            let length = world.arena[constraint.a].pos.distance(world.arena[constraint.b].pos);
+            assert!((length - constraint.rest_length).abs() < 0.01 * constraint.rest_length, "{}", length);
+        }
+
+        // a node with more links than the shader loops over can't be uploaded
+        let mut star = World::new(10_000.0);
+        star.add_rope(Vec2::ZERO, 1);
+        for i in 0..=MAX_LINKS {
+            star.add_rope(Vec2::new(i as f32 * TARGET_DIST, TARGET_DIST), 1);
+            star.constraints.push(Constraint { a: 0, b: i + 1, rest_length: TARGET_DIST, break_threshold: f32::MAX });
+        }
+        assert!(Adjacency::new(&star.constraints, star.arena.len()).is_none());
+        star.walls.left = true;
+        assert!(!solvable(&star));
+    }
+}
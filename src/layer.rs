@@ -0,0 +1,183 @@
+//! Layers for sorting a busy scene into parts: every node and rotor is on
+//! one, and each link is on its nodes'. A hidden layer isn't drawn and is out
+//! of reach of the knife, the wind, and the cursor, and each layer collides
+//! only with the layers it's set to, so two curtains hung one in front of the
+//! other can swing through each other, and either can be hidden to get at the
+//! one behind. Each layer also has its own say over which obstacles it hits,
+//! so debris on a layer of its own can fall through the cloth and still land
+//! on the ground, or a backdrop ignore the walls. Layers can also be drawn at a depth behind or in front of the
+//! rest, smaller and slower to pan the farther back they are, so the curtain in
+//! front reads as in front.
+
+/// How many layers there are, numbered from 0, which everything starts on.
+pub const LAYERS: usize = 8;
+/// Nearest a layer can be drawn, where it's twice its size.
+pub const MIN_DEPTH: f32 = -0.5;
+
+/// Bits of `Layer::obstacles`, one for each obstacle a layer can pass through.
+pub const GROUND: u8 = 1;
+pub const WALLS: u8 = 1 << 1;
+pub const WATER: u8 = 1 << 2;
+/// Every obstacle's bit, with its name.
+pub const OBSTACLES: [(u8, &str); 3] = [(GROUND, "Ground"), (WALLS, "Walls"), (WATER, "Water")];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub visible: bool,
+    /// A bit for each layer this one collides with, the lowest for layer 0.
+    pub collides_with: u8,
+    /// Which of `GROUND`, `WALLS`, and `WATER` it collides with. Rotors and
+    /// balls are on layers themselves, so they go by `collides_with`.
+    pub obstacles: u8,
+    /// How far behind the screen it's drawn, in distances from the viewer to
+    /// the screen, negative for in front. It only changes how it's drawn: it's
+    /// simulated, and cut and blown, where it really is.
+    pub depth: f32,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self { visible: true, collides_with: u8::MAX, obstacles: u8::MAX, depth: 0.0 }
+    }
+}
+
+impl Layer {
+    /// How big it's drawn at its depth, and how far it moves as the view pans, relative to the screen.
+    pub fn scale(&self) -> f32 {
+        1.0 / (1.0 + self.depth.max(MIN_DEPTH))
+    }
+}
+
+/// Every layer's settings, by number.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Layers(pub [Layer; LAYERS]);
+
+impl Layers {
+    pub fn visible(&self, layer: u8) -> bool {
+        self.0[layer as usize].visible
+    }
+
+    /// Whether things on layers `a` and `b` collide, which takes each set to collide with the other.
+    pub fn collide(&self, a: u8, b: u8) -> bool {
+        let bit = |layer: u8| 1u8 << layer;
+        self.0[a as usize].collides_with & bit(b) != 0 && self.0[b as usize].collides_with & bit(a) != 0
+    }
+
+    /// Whether things on `layer` collide with `obstacle`, one of `GROUND`, `WALLS`, or `WATER`.
+    pub fn hits(&self, layer: u8, obstacle: u8) -> bool {
+        self.0[layer as usize].obstacles & obstacle != 0
+    }
+
+    /// Whether something on `layer` collides with a link between nodes on
+    /// layers `a` and `b`, which it does if it collides with either end.
+    pub fn collide_link(&self, layer: u8, a: u8, b: u8) -> bool {
+        self.collide(layer, a) || self.collide(layer, b)
+    }
+
+    /// Every depth a layer's drawn at, farthest first, for drawing back to front.
+    pub fn depths(&self) -> Vec<f32> {
+        let mut depths: Vec<f32> = self.0.iter().map(|layer| layer.depth).collect();
+        depths.sort_by(|a, b| b.total_cmp(a));
+        depths.dedup();
+        depths
+    }
+
+    /// Sets layers `a` and `b` to collide with each other or not, both ways.
+    pub fn set_collide(&mut self, a: u8, b: u8, collide: bool) {
+        for (from, to) in [(a, b), (b, a)] {
+            let mask = &mut self.0[from as usize].collides_with;
+            if collide {
+                *mask |= 1 << to;
+            } else {
+                *mask &= !(1 << to);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contact::Contacts;
+    use crate::event::SimEvent;
+    use crate::node::Node;
+    use crate::scene;
+    use crate::walls::Walls;
+    use crate::world::World;
+    use crate::NODE_RADIUS;
+    use glam::Vec2;
+
+    #[test]
+    fn separate_layers_pass_through_and_hidden_ones_are_left_alone() {
+        // two loose nodes overlapping in midair, on layers that don't collide
+        let mut world = World::new(1000.0);
+        world.params.gravity = 0.0;
+        world.contacts = Some(Contacts::default());
+        world.layers.set_collide(0, 1, false);
+        assert!(!world.layers.collide(1, 0));
+        assert!(world.layers.collide(1, 1));
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(100.0, 100.0), 1.0));
+        world.arena.push(Node { layer: 1, ..Node::with_pos_and_mass(Vec2::new(100.0 + NODE_RADIUS, 100.0), 1.0) });
+        let dt = world.params.dt;
+        world.step(dt);
+        assert!(world.arena[0].pos.distance(world.arena[1].pos) < 2.0 * NODE_RADIUS);
+
+        world.layers.set_collide(0, 1, true);
+        world.step(dt);
+        assert!(world.arena[0].pos.distance(world.arena[1].pos) >= 2.0 * NODE_RADIUS - 1e-3);
+
+        // a rope on a hidden layer can't be cut or blown
+        let mut world = World::new(1000.0);
+        world.add_rope(Vec2::new(100.0, 100.0), 3);
+        world.arena.iter_mut().for_each(|node| node.layer = 2);
+        world.layers.0[2].visible = false;
+        let between = world.arena[0].pos.lerp(world.arena[1].pos, 0.5);
+        world.cut(between - Vec2::new(10.0, 0.0), between + Vec2::new(10.0, 0.0));
+        assert_eq!(world.constraints.len(), 2);
+        world.apply_wind(world.arena[2].pos, Vec2::new(50.0, 0.0), &Default::default());
+        assert_eq!(world.arena[2].force, Vec2::ZERO);
+
+        // and it all comes back from a scene file, depths and all
+        world.layers.0[3].depth = 1.5;
+        let loaded = scene::from_text(&scene::to_text(&world)).unwrap();
+        assert_eq!(loaded.layers, world.layers);
+        assert_eq!(loaded.layers.0[2], Layer { visible: false, ..Layer::default() });
+        assert!(loaded.arena.iter().all(|node| node.layer == 2));
+        assert_eq!(loaded.layers.depths(), [1.5, 0.0]);
+        assert_eq!(loaded.layers.0[3].scale(), 0.4);
+
+        world.layers.0[2].visible = true;
+        world.cut(between - Vec2::new(10.0, 0.0), between + Vec2::new(10.0, 0.0));
+        assert_eq!(world.constraints.len(), 1);
+    }
+
+    #[test]
+    fn layers_pass_through_the_obstacles_they_are_set_to() {
+        // three nodes dropped toward the ground and thrown at a wall
+        let mut world = World::new(200.0);
+        world.walls = Walls { max: Vec2::new(1000.0, 1000.0), left: true, ..Walls::default() };
+        world.layers.0[1].obstacles = WALLS | WATER;
+        world.layers.0[2].obstacles = GROUND;
+        for layer in 0..3 {
+            let node = Node::with_pos_and_mass(Vec2::new(50.0 + 10.0 * layer as f32, 100.0), 1.0);
+            world.arena.push(Node { layer, vel: Vec2::new(-200.0, 0.0), ..node });
+        }
+        let dt = world.params.dt;
+        let mut landed = Vec::new();
+        for _ in 0..200 {
+            landed.extend(world.step(dt).into_iter().filter_map(|event| match event {
+                SimEvent::Collision { node, .. } => Some(node),
+                _ => None,
+            }));
+        }
+
+        let ground = 200.0 - NODE_RADIUS;
+        assert!((world.arena[0].pos.y - ground).abs() < 1e-3 && world.arena[0].pos.x >= NODE_RADIUS - 1e-3);
+        assert!(world.arena[1].pos.y > 200.0 && world.arena[1].pos.x >= NODE_RADIUS - 1e-3);
+        assert!((world.arena[2].pos.y - ground).abs() < 1e-3 && world.arena[2].pos.x < 0.0);
+        assert!(!landed.contains(&1));
+
+        let loaded = scene::from_text(&scene::to_text(&world)).unwrap();
+        assert_eq!(loaded.layers, world.layers);
+    }
+}
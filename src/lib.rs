@@ -0,0 +1,158 @@
+//! Position-based rope and cloth solver.
+//!
+//! Everything here is independent of macroquad so it can run headless;
+//! the windowed demo in `main.rs` is built on top of it.
+
+pub mod alert;
+pub mod analytic;
+pub mod anchor;
+pub mod ball;
+pub mod band;
+pub mod bridge;
+pub mod budget;
+pub mod challenge;
+pub mod charge;
+pub mod cleanup;
+pub mod constraint;
+pub mod contact;
+pub mod convergence;
+pub mod dispenser;
+pub mod divergence;
+pub mod energy;
+pub mod event;
+pub mod evolution;
+pub mod export;
+pub mod fluid;
+pub mod fray;
+#[cfg(feature = "scripting")]
+pub mod force_expr;
+pub mod friction;
+pub mod geometry;
+pub mod group;
+pub mod graph_layout;
+pub mod history;
+pub mod ik;
+pub mod jacobi;
+pub mod layer;
+pub mod lod;
+pub mod magnet;
+pub mod material;
+pub mod math;
+pub mod momentum;
+pub mod multigrid;
+pub mod muscle;
+pub mod node;
+pub mod params;
+pub mod platform;
+pub mod puzzle;
+pub mod query;
+pub mod rail;
+pub mod rain;
+pub mod render;
+pub mod resize;
+pub mod rng;
+pub mod rotor;
+pub mod sand;
+pub mod save;
+pub mod scene;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod shading;
+pub mod shatter;
+pub mod sim_world;
+pub mod slider;
+pub mod solve_order;
+pub mod spatial_hash;
+pub mod stability;
+pub mod statics;
+pub mod substeps;
+pub mod threaded;
+pub mod tutorial;
+pub mod units;
+pub mod variance;
+pub mod verlet;
+pub mod walls;
+pub mod water;
+pub mod weave;
+pub mod well;
+pub mod winch;
+pub mod wind;
+pub mod world;
+#[cfg(feature = "sim3d")]
+pub mod world3d;
+pub mod wrap;
+pub mod xpbd;
+
+pub use alert::{StrainAlert, StrainedLink};
+pub use anchor::{Anchor, AnchorPath};
+pub use ball::Ball;
+pub use band::Band;
+pub use bridge::{Bridge, BridgeTest};
+pub use charge::Electrostatics;
+pub use constraint::{Constraint, SeveredLink};
+pub use contact::Contacts;
+pub use dispenser::Dispenser;
+pub use energy::{Energy, EnergyFlow};
+pub use event::SimEvent;
+pub use fluid::Fluid;
+pub use friction::Friction;
+pub use group::LinkGroup;
+pub use lod::Lod;
+pub use magnet::Magnet;
+pub use material::Material;
+pub use momentum::Momentum;
+pub use multigrid::Multigrid;
+pub use muscle::Muscle;
+pub use node::Node;
+pub use params::Params;
+pub use platform::Platform;
+pub use rail::{Curve, Rail};
+pub use rain::Rain;
+pub use render::{Mesh, MeshVertex, SimRenderer};
+pub use rng::Rng;
+pub use rotor::Rotor;
+pub use sand::Sand;
+pub use sim_world::{NodeId, Param, SimWorld, SimWorldError};
+pub use slider::Slider;
+pub use solve_order::SolveOrder;
+pub use threaded::SimThread;
+pub use walls::Walls;
+pub use water::Water;
+pub use weave::{Links, Weave};
+pub use well::{Sink, Well};
+pub use winch::Winch;
+pub use wind::WindBrush;
+pub use world::World;
+pub use wrap::Wrap;
+pub use xpbd::Xpbd;
+
+pub use glam::Vec2;
+
+pub const DT: f32 = 0.15;
+pub const G: f32 = 18.0;
+pub const NODE_RADIUS: f32 = 6.0;
+pub const TARGET_DIST: f32 = 50.0;
+pub const RIGIDITY: f32 = 1.0;
+pub const DRAG: f32 = 0.5;
+pub const SOLVER_ITERATIONS: usize = 5;
+/// Length at which constraints break, in rest lengths.
+pub const BREAK_THRESHOLD: f32 = 5.0;
+/// Strain rate at which taut constraints snap, in rest lengths per unit time, once switched on.
+pub const BREAK_RATE: f32 = 3.0;
+/// Speed below which a moving node counts as asleep.
+pub const SLEEP_SPEED: f32 = 0.5;
+/// Slip, as a multiple of how deep a contact presses, that friction stops entirely.
+pub const STATIC_FRICTION: f32 = 0.6;
+/// Slip, as a multiple of how deep a contact presses, that friction takes away once sliding.
+pub const DYNAMIC_FRICTION: f32 = 0.4;
+/// How far off a surface a node can be and still count as touching it for
+/// bouncing, since links can pull it slightly clear after it was pushed out,
+/// and stopping it where it hit on the way leaves it just short.
+pub const CONTACT_SLOP: f32 = 0.01;
+/// Distance from the blade within which `World::split_path` splits nodes.
+pub const SPLIT_REACH: f32 = TARGET_DIST / 4.0;
+
+pub const WIND_RADIUS: f32 = 30.0;
+pub const WIND_STRENGTH: f32 = 50.0;
+/// Fraction of the wind's radius it fades out over.
+pub const WIND_FALLOFF: f32 = 0.5;
@@ -0,0 +1,136 @@
+//! Translations of the text drawn over the scene: hints, the tutorial,
+//! challenge scores, and attract mode's captions. Each language is a table
+//! from the English text to its translation, so English needs no table and
+//! anything a table leaves out stays in English. Spanish is bundled, and
+//! `locales/<code>.toml` in the working directory adds a language or
+//! replaces a bundled one, like so:
+//!
+//! ```toml
+//! name = "Español"
+//!
+//! [strings]
+//! "Right Click to Cut" = "Clic derecho para cortar"
+//! ```
+//!
+//! Text with numbers in it names them in braces, like `{cuts}`, and the
+//! translation puts them wherever its grammar wants them.
+
+use crate::error::SimError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use tracing::warn;
+
+#[cfg(not(target_arch = "wasm32"))]
+const LOCALES_DIR: &str = "locales";
+
+/// Each bundled language's code and table.
+const BUNDLED: &[(&str, &str)] = &[("es", include_str!("../locales/es.toml"))];
+
+/// A locale file's layout.
+#[derive(Deserialize)]
+struct LocaleFile {
+    name: String,
+    #[serde(default)]
+    strings: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Locale {
+    /// What it's saved as in the settings, like "es", from its file's name.
+    pub code: String,
+    /// What it's called in its own language, for picking it.
+    pub name: String,
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn english() -> Self {
+        Self { code: "en".to_string(), name: "English".to_string(), strings: HashMap::new() }
+    }
+
+    /// Parses the table in `text`, which came from `path`, as the language `code`.
+    fn parse(code: &str, text: &str, path: &str) -> Result<Self, SimError> {
+        let file: LocaleFile =
+            toml::from_str(text).map_err(|e| SimError::Config { path: path.to_string(), message: e.to_string() })?;
+        Ok(Self { code: code.to_string(), name: file.name, strings: file.strings })
+    }
+
+    /// `text` in this language, or as it is if there's no translation.
+    pub fn tr<'a>(&'a self, text: &'a str) -> &'a str {
+        self.strings.get(text).map_or(text, String::as_str)
+    }
+
+    /// `text` translated, with each `{name}` in it replaced by its value in `args`.
+    pub fn fill(&self, text: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter().fold(self.tr(text).to_string(), |filled, (name, value)| {
+            filled.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+    }
+}
+
+/// The languages there are to pick from, and which one is picked.
+#[derive(Clone, Debug)]
+pub struct Locales {
+    pub locales: Vec<Locale>,
+    pub selected: usize,
+}
+
+impl Locales {
+    /// English, the bundled languages, and any in `locales/`, each of which
+    /// replaces a bundled one with the same code. Files that don't parse are
+    /// warned about and left out.
+    pub fn load() -> Self {
+        let mut locales = vec![Locale::english()];
+        for (code, text) in BUNDLED {
+            let path = format!("locales/{}.toml", code);
+            locales.push(Locale::parse(code, text, &path).expect("the bundled locales are valid"));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        for locale in Self::read_dir() {
+            match locales.iter_mut().find(|existing| existing.code == locale.code) {
+                Some(existing) => *existing = locale,
+                None => locales.push(locale),
+            }
+        }
+        Self { locales, selected: 0 }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_dir() -> Vec<Locale> {
+        let Ok(entries) = std::fs::read_dir(LOCALES_DIR) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .collect();
+        paths.sort();
+        let read = |path: &std::path::Path| -> Result<Locale, SimError> {
+            let display = path.display().to_string();
+            let text = std::fs::read_to_string(path).map_err(|source| SimError::Io {
+                action: "read",
+                path: display.clone(),
+                source,
+            })?;
+            let code = path.file_stem().unwrap_or_default().to_string_lossy();
+            Locale::parse(&code, &text, &display)
+        };
+        paths
+            .iter()
+            .filter_map(|path| read(path).map_err(|e| warn!("skipping a locale: {}", e)).ok())
+            .collect()
+    }
+
+    pub fn current(&self) -> &Locale {
+        &self.locales[self.selected]
+    }
+
+    /// Picks the language saved as `code`, keeping the current one if there's no such language.
+    pub fn select_by_code(&mut self, code: &str) {
+        match self.locales.iter().position(|locale| locale.code == code) {
+            Some(i) => self.selected = i,
+            None => warn!("no locale {}, keeping {}", code, self.current().code),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Level of detail for the solver: links far from where the user's looking
+//! get fewer solver iterations than the rest, so dozens of strands of hair or
+//! a fringe along a curtain's edge don't cost as much as the cloth near the
+//! cursor. Far strands stretch a little more, which is hard to see at a distance.
+
+use crate::constraint::Constraint;
+use crate::node::Node;
+use crate::TARGET_DIST;
+use glam::Vec2;
+
+/// How far from the focus links keep every iteration, by default.
+pub const LOD_RADIUS: f32 = 6.0 * TARGET_DIST;
+/// Iterations links past the radius get, by default.
+pub const FAR_ITERATIONS: usize = 2;
+
+/// Where the full detail is. It's set by whoever's watching, usually each
+/// step from the cursor, so scenes don't save it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lod {
+    pub focus: Vec2,
+    /// Links with both ends farther than this from `focus` are far.
+    pub radius: f32,
+    /// How many of the solver's iterations far links get, from the first.
+    pub far_iterations: usize,
+}
+
+impl Default for Lod {
+    fn default() -> Self {
+        Self { focus: Vec2::ZERO, radius: LOD_RADIUS, far_iterations: FAR_ITERATIONS }
+    }
+}
+
+impl Lod {
+    /// Whether each constraint is far from the focus.
+    pub fn far(&self, constraints: &[Constraint], arena: &[Node]) -> Vec<bool> {
+        let far = |i: usize| arena[i].pos.distance_squared(self.focus) > self.radius * self.radius;
+        constraints.iter().map(|constraint| far(constraint.a) && far(constraint.b)).collect()
+    }
+
+    /// Whether a constraint that's `far` is solved on the solver's `iteration`, counting from 0.
+    pub fn solves(&self, far: bool, iteration: usize) -> bool {
+        !far || iteration < self.far_iterations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn far_strands_get_fewer_iterations() {
+        let mut world = World::new(10_000.0);
+        world.params.solver_iterations = 10;
+        world.params.rigidity = 0.5;
+        let roots: Vec<usize> = (0..2)
+            .map(|i| {
+                world.add_rope(Vec2::new(100.0 + 1000.0 * i as f32, 100.0), 1);
+                world.arena.len() - 1
+            })
+            .collect();
+        world.add_fringe(&roots, 10, TARGET_DIST);
+        world.lod = Some(Lod { focus: Vec2::new(100.0, 100.0), ..Lod::default() });
+        let far = world.lod.unwrap().far(&world.constraints, &world.arena);
+        // all of the far strand, and the bottom three links of the near one
+        assert_eq!(far.iter().filter(|&&far| far).count(), 13);
+
+        let dt = world.params.dt;
+        for _ in 0..60 {
+            world.step(dt);
+        }
+        // the strand far from the focus sags farther under its own weight
+        let length = |strand: usize| world.arena[roots.len() + strand * 10 + 9].pos.y - 100.0;
+        assert!(length(1) > length(0) + 1.0, "{} {}", length(1), length(0));
+    }
+}
@@ -0,0 +1,152 @@
+//! A magnetic field over the whole scene, pushing on the nodes tagged
+//! `magnetic` and leaving the rest alone, so a scene can mix steel that a
+//! magnet drags around with rope and cloth that only hang there. The field is
+//! either the same everywhere or a dipole, like a bar magnet at a point.
+
+use crate::node::Node;
+use crate::{NODE_RADIUS, TARGET_DIST};
+use glam::Vec2;
+
+/// Points along a traced field line at most, so one circling forever stops.
+const MAX_LINE_POINTS: usize = 400;
+/// Field lines traced out of a dipole's north end.
+const DIPOLE_LINES: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Magnet {
+    /// The same force on every magnetic node.
+    Uniform { force: Vec2 },
+    /// A bar magnet at `center`, pointing its north end along `moment`. The
+    /// field a rest length off to its side is `-moment`, and it falls off with
+    /// the cube of the distance from there.
+    Dipole { center: Vec2, moment: Vec2 },
+}
+
+impl Default for Magnet {
+    fn default() -> Self {
+        Magnet::Uniform { force: Vec2::new(20.0, 0.0) }
+    }
+}
+
+impl Magnet {
+    /// The field at `pos`, which is the force on a magnetic node there.
+    pub fn field_at(&self, pos: Vec2) -> Vec2 {
+        match *self {
+            Magnet::Uniform { force } => force,
+            Magnet::Dipole { center, moment } => {
+                let offset = pos - center;
+                let along = offset.normalize_or_zero();
+                // softened at the center like charges are, so a node on top of it isn't flung off
+                let softened = offset.length_squared() + NODE_RADIUS * NODE_RADIUS;
+                // cubed distance as x * sqrt(x), since `sqrt` rounds the same everywhere and `powf` doesn't
+                let scaled = TARGET_DIST * TARGET_DIST / softened;
+                let falloff = scaled * scaled.sqrt();
+                (3.0 * moment.dot(along) * along - moment) * falloff
+            }
+        }
+    }
+
+    /// The push on `node`, zero if it's fixed or isn't magnetic.
+    pub fn force_on(&self, node: &Node) -> Vec2 {
+        if node.fixed || !node.magnetic {
+            return Vec2::ZERO;
+        }
+        self.field_at(node.pos)
+    }
+
+    /// Field lines through the rect from `min` to `max`, about `spacing` apart,
+    /// each as the points along it: parallel lines across the rect for a
+    /// uniform field, and loops out of a dipole's north end and back into its
+    /// south end.
+    pub fn field_lines(&self, min: Vec2, max: Vec2, spacing: f32) -> Vec<Vec<Vec2>> {
+        let middle = (min + max) / 2.0;
+        let reach = (max - min).length() / 2.0;
+        match *self {
+            Magnet::Uniform { force } => {
+                let along = force.normalize_or_zero();
+                if along == Vec2::ZERO {
+                    return Vec::new();
+                }
+                let across = along.perp();
+                let count = (reach / spacing).ceil() as i32;
+                // straight lines through the middle of the rect and either side of it, long enough to cross it
+                (-count..=count)
+                    .map(|k| middle + across * (k as f32 * spacing))
+                    .map(|through| vec![through - along * reach, through + along * reach])
+                    .collect()
+            }
+            Magnet::Dipole { center, moment } => {
+                if moment == Vec2::ZERO {
+                    return Vec::new();
+                }
+                let start = spacing / 2.0;
+                let step = spacing / 4.0;
+                // far enough out to show the lines that loop through the rect from off it
+                let bounds = reach + center.distance(middle);
+                (0..DIPOLE_LINES)
+                    .map(|i| {
+                        // half a step off the axis, so the lines come in pairs either side of it
+                        let angle = (i as f32 + 0.5) / DIPOLE_LINES as f32 * std::f32::consts::TAU;
+                        Vec2::new(angle.cos(), angle.sin())
+                    })
+                    .filter(|out| self.field_at(center + *out * start).dot(*out) > 0.0)
+                    .map(|out| {
+                        let mut pos = center + out * start;
+                        let mut line = vec![pos];
+                        while line.len() < MAX_LINE_POINTS {
+                            // midpoint steps, so lines close back on the magnet instead of spiraling
+                            let half = pos + self.field_at(pos).normalize_or_zero() * step / 2.0;
+                            pos += self.field_at(half).normalize_or_zero() * step;
+                            line.push(pos);
+                            if pos.distance(center) < start || pos.distance(center) > bounds {
+                                break;
+                            }
+                        }
+                        line
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn only_magnetic_nodes_feel_the_field() {
+        let mut world = World::new(10_000.0);
+        world.params.gravity = 0.0;
+        world.magnet = Some(Magnet::default());
+        world.arena.push(Node { magnetic: true, ..Node::with_pos_and_mass(Vec2::new(100.0, 100.0), 1.0) });
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(100.0, 200.0), 1.0));
+        let dt = world.params.dt;
+        for _ in 0..10 {
+            world.step(dt);
+        }
+        assert!(world.arena[0].pos.x > 100.0);
+        assert_eq!(world.arena[1].pos, Vec2::new(100.0, 200.0));
+
+        // a dipole pulls along its axis and pushes back beside it, weaker farther out
+        let dipole = Magnet::Dipole { center: Vec2::ZERO, moment: Vec2::new(10.0, 0.0) };
+        assert!(dipole.field_at(Vec2::new(TARGET_DIST, 0.0)).x > 0.0);
+        assert!(dipole.field_at(Vec2::new(0.0, TARGET_DIST)).x < 0.0);
+        let (near, far) = (dipole.field_at(Vec2::new(0.0, 50.0)), dipole.field_at(Vec2::new(0.0, 100.0)));
+        assert!(far.length() < near.length() / 4.0);
+
+        // its field lines leave the north end and come back around into the south end
+        let lines = dipole.field_lines(Vec2::splat(-500.0), Vec2::splat(500.0), 40.0);
+        assert_eq!(lines.len(), DIPOLE_LINES / 2);
+        for line in lines.iter() {
+            assert!(line[0].x > 0.0);
+            let end = line[line.len() - 1];
+            assert!(end.x < 0.0 && end.length() < 20.0, "{:?}", end);
+        }
+
+        // a uniform field's lines run straight across
+        let lines = Magnet::default().field_lines(Vec2::ZERO, Vec2::new(400.0, 300.0), 50.0);
+        assert!(lines.iter().all(|line| line[0].y == line[1].y && line[0].x < 0.0 && line[1].x > 400.0));
+    }
+}
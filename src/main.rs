@@ -1,21 +1,207 @@
+use clap::Parser;
+use cli::Cli;
 use egui_macroquad::macroquad;
 use macroquad::prelude::*;
-use main_state::MainState;
+use main_state::{MainState, STEPS_PER_FRAME};
 
+mod app_state;
+mod attract;
+mod audio;
+mod bindings;
+mod bundle;
+mod camera;
+mod capture;
+mod cli;
+mod comparison;
+mod cursor;
+mod diff;
+mod effects;
+mod entities;
 mod error;
+mod evolve;
+mod frame_budget;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod golden;
+mod gpu_solve;
+mod grid;
+mod headless;
+mod image_scene;
+mod locale;
 mod main_state;
+mod materials;
+mod measure;
+mod minimap;
+mod motion;
+#[cfg(feature = "netplay")]
+mod netplay;
+#[cfg(feature = "osc")]
+mod osc;
+mod palette;
+mod perf;
+mod pointer;
+mod profiler;
+mod renderer;
+mod replay;
+mod settings;
+mod sim_config;
+#[cfg(feature = "websocket")]
+mod stream;
+mod svg;
+mod sweep;
+mod time_scale;
+mod tool;
+mod touch;
+mod ui_scale;
+mod video;
+mod viewports;
+#[cfg(feature = "sim3d")]
+mod view3d;
 
-#[macroquad::main("Cloth")]
-async fn main() -> Result<(), error::SimError> {
+fn main() {
+    let cli = Cli::parse();
+
+    // stderr doesn't go anywhere in the browser
+    #[cfg(not(target_arch = "wasm32"))]
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    if cli.diff.is_some() {
+        std::process::exit(diff::run(&cli));
+    }
+
+    if cli.headless {
+        let code = if cli.sweeping() {
+            sweep::run(&cli)
+        } else if cli.evolve.is_some() {
+            evolve::run(&cli)
+        } else {
+            headless::run(&cli)
+        };
+        std::process::exit(code);
+    }
+
+    if let Some(dir) = cli.golden {
+        let conf = Conf {
+            window_title: "Cloth golden images".to_owned(),
+            window_width: golden::WIDTH as i32,
+            window_height: golden::HEIGHT as i32,
+            ..Default::default()
+        };
+        macroquad::Window::from_config(conf, async move {
+            next_frame().await;
+            std::process::exit(golden::run(&dir, cli.update_golden));
+        });
+        return;
+    }
+
+    if let Some(path) = cli.profile_out.clone() {
+        let conf = Conf {
+            window_title: "Cloth profile".to_owned(),
+            window_width: cli.width,
+            window_height: cli.height,
+            ..Default::default()
+        };
+        macroquad::Window::from_config(conf, async move {
+            next_frame().await;
+            std::process::exit(perf::run(&cli, &path).await);
+        });
+        return;
+    }
+
+    #[cfg(feature = "sim3d")]
+    if cli.three_d {
+        let conf = Conf {
+            window_title: "Cloth in 3D".to_owned(),
+            window_width: cli.width,
+            window_height: cli.height,
+            ..Default::default()
+        };
+        macroquad::Window::from_config(conf, async move { view3d::run(&cli).await });
+        return;
+    }
+
+    let conf = Conf {
+        window_title: "Cloth".to_owned(),
+        window_width: cli.width,
+        window_height: cli.height,
+        ..Default::default()
+    };
+    macroquad::Window::from_config(conf, async move {
+        if let Err(err) = run(cli).await {
+            error!("Error: {}", err);
+        }
+    });
+}
+
+async fn run(cli: Cli) -> Result<(), error::SimError> {
     next_frame().await;
 
-    let mut main_state = MainState::default();
+    let mut main_state = MainState::new(cli.scene_source(), cli.params, cli.seed);
+    main_state.load_audio().await;
+    main_state.attract_after = cli.attract_after;
+    if let Some(path) = cli.dump_state.as_ref() {
+        if let Err(err) = main_state.dump_state_to(&path.to_string_lossy()) {
+            main_state.report(err);
+        }
+    }
+    if let Some(path) = cli.bundle.as_ref() {
+        main_state.import_bundle(&path.to_string_lossy());
+    } else if let Some(code) = bundle::from_page() {
+        main_state.import_bundle(&code);
+    }
+    if let Some(path) = cli.script.as_ref() {
+        if let Err(err) = main_state.load_script(&path.to_string_lossy()) {
+            main_state.report(err);
+        }
+    }
+
+    #[cfg(feature = "netplay")]
+    if let Some(addr) = cli.host.as_deref() {
+        main_state.start_session(netplay::Session::host(addr));
+    } else if let Some(addr) = cli.join.as_deref() {
+        main_state.start_session(netplay::Session::join(addr));
+    }
+
+    #[cfg(feature = "osc")]
+    if let Some(addr) = cli.osc.as_deref() {
+        main_state.listen_for_osc(addr);
+    }
+
+    #[cfg(feature = "gamepad")]
+    if let Some(path) = cli.gamepad.as_deref() {
+        main_state.open_gamepad(path);
+    }
+
+    main_state.start_tutorial_on_first_run();
+
+    #[cfg(feature = "websocket")]
+    let server = cli.serve.as_deref().and_then(|addr| match stream::StateServer::start(addr) {
+        Ok(server) => Some(server),
+        Err(source) => {
+            main_state.report(error::SimError::Io { action: "listen on", path: addr.to_string(), source });
+            None
+        }
+    });
 
     loop {
+        main_state.handle_input();
         main_state.draw()?;
-        for _ in 0..2 {
-            main_state.update()?;
+        for _ in 0..STEPS_PER_FRAME {
+            if let Err(err) = main_state.update() {
+                main_state.report(err);
+            }
+        }
+        #[cfg(feature = "websocket")]
+        if let Some(server) = server.as_ref() {
+            main_state.broadcast(server);
         }
+        profiler::new_frame();
         next_frame().await;
     }
 }
@@ -1,237 +1,4737 @@
+use crate::app_state::AppState;
+use crate::attract::Attract;
+use crate::audio::{Audio, SoundEffect};
+use crate::bindings::{Button, MouseBindings};
+use crate::bundle;
+use crate::camera::Camera;
+use crate::capture::FrameCapture;
+use crate::cli::{ParamOverrides, SceneSource};
+use crate::comparison::{self, Comparison};
+use crate::cursor::CursorHistory;
+use crate::entities::{BuildFrom, Dragged, Entities, Hovered, Inspected, Strained};
 use crate::error::SimError;
+use crate::frame_budget::{self, FrameBudget, Stopwatch};
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{self, Bumper, Gamepad};
+use crate::gpu_solve::GpuSolver;
+use crate::grid;
+use crate::locale::{Locale, Locales};
+use crate::materials::RopeMaterials;
+use crate::measure::{self, End, Measurement};
+use crate::minimap::Minimap;
+use crate::motion::MouseMotion;
+#[cfg(feature = "netplay")]
+use crate::netplay::Session;
+#[cfg(feature = "osc")]
+use crate::osc::{Control, OscListener};
+use crate::palette::{self, Palette};
+use crate::perf::{FrameTimes, Phase};
+use crate::pointer::{Pointer, POINTER_SIZE};
+use crate::profiler::{self, profile_scope};
+use crate::renderer::{MacroquadRenderer, WELL_MARKER_RADIUS};
+use crate::replay::{self, Player, Recorder, Replay, StepInput};
+use crate::settings::Settings;
+use crate::svg::{self, SvgRenderer};
+use crate::sim_config::{self, SimConfigWatcher};
+use crate::time_scale::TimeScale;
+use crate::tool::{CursorTool, EditorTool};
+use crate::touch::{Finger, Fingers, Role};
+use crate::ui_scale::{self, draw_scaled_text, measure_scaled_text, MAX_UI_SCALE, MIN_UI_SCALE};
+use crate::video::VideoRecorder;
+use crate::viewports::{self, Follow, Viewport, MAX_VIEWPORTS};
+use clothsim::analytic::{Analytic, Canonical, Oscillator};
+use clothsim::challenge::Challenge;
+use clothsim::bridge::{Bridge, BridgeTest, LINK_COST, LOAD_RADIUS, MAX_LINK_LENGTH, NODE_COST};
+use clothsim::convergence::{self, Residual, CONVERGENCE_ITERATIONS};
+use clothsim::evolution::{self, Evolution, Gene, Generation};
+use clothsim::export::{self, Trajectories};
+use clothsim::ik::Chain;
+use clothsim::layer::{LAYERS, MIN_DEPTH, OBSTACLES};
+use clothsim::budget::{AtCap, NodeBudget};
+use clothsim::cleanup::Cleanup;
+use clothsim::material::{self, Material, MATERIALS};
+use clothsim::multigrid::Multigrid;
+use clothsim::shatter::Shatter;
+use clothsim::fray::Fray;
+use clothsim::solve_order::SolveOrder;
+use clothsim::variance;
+use clothsim::resize;
+use clothsim::stability::{self, Report, Trial};
+use clothsim::statics::{self, NotATruss, Settled, Truss};
+use clothsim::substeps::Substeps;
+use clothsim::scene::{FRINGE_LINK_LENGTH, FRINGE_STRAND_NODES};
+use clothsim::script::Script;
+use clothsim::shading;
+use clothsim::tutorial::{Lesson, Tutorial};
+use clothsim::xpbd::XPBD_COMPLIANCE;
+use clothsim::fluid::AIR_CELL;
+use clothsim::force_expr::ForceExpr;
+use clothsim::graph_layout::GraphLayout;
+use clothsim::group;
+use clothsim::history::History;
+use clothsim::puzzle::{self, Puzzle};
+use clothsim::query::Query;
+use clothsim::sand::{Sand, POUR_GRAINS};
+use clothsim::save;
+use clothsim::water::{Water, POUR_COUNT};
+use clothsim::weave::Links;
+use clothsim::{
+    AnchorPath, Contacts, Electrostatics, Energy, EnergyFlow, Fluid, Lod, Magnet, Momentum, Node, Params, Rain, Rng,
+    SeveredLink, SimEvent, SimRenderer, SimThread, Sink, StrainAlert, StrainedLink, Well, WindBrush, World,
+    NODE_RADIUS,
+};
+use egui_macroquad::egui;
+use egui_macroquad::egui::plot;
 use egui_macroquad::macroquad::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use tracing::{debug, debug_span, warn};
 
-const DT: f32 = 0.15;
-const G: f32 = 18.0;
-const NODE_RADIUS: f32 = 6.0;
-const ROPE_WIDTH: f32 = 4.0;
-const TARGET_DIST: f32 = 50.0;
-const RIGIDITY: f32 = 1.0;
-const DRAG: f32 = 0.5;
+const SHAKE_PER_STRAIN: f32 = 0.15;
+/// Impact speed at which the ground thud plays at full volume.
+const THUD_FULL_SPEED: f32 = 60.0;
+const THUD_MIN_SPEED: f32 = 5.0;
+/// Inline simulation steps run each frame.
+pub const STEPS_PER_FRAME: usize = 2;
+/// Fraction of the cursor's velocity a node thrown from the editor keeps.
+const FLING_SCALE: f32 = 0.8;
+/// Fastest a thrown node can leave the cursor, half a rest length per default step.
+const MAX_FLING_SPEED: f32 = 0.5 * clothsim::TARGET_DIST / clothsim::DT;
+/// Steps per second on the background thread, two per frame at 60 Hz like the inline loop.
+const THREAD_STEP_RATE: f32 = 120.0;
+/// Seconds an error toast stays up unless dismissed.
+const TOAST_SECONDS: f64 = 8.0;
+/// Seconds a strain warning stays up unless dismissed or replaced by the next.
+const STRAIN_WARNING_SECONDS: f64 = 5.0;
+/// Steps of trajectory kept, a little over a minute at two steps per frame.
+const TRAJECTORY_STEPS: usize = 8000;
+/// Frames of momentum the Momentum window plots.
+const MOMENTUM_FRAMES: usize = 600;
+/// Largest knife radius, reaching a rest length either side of the blade.
+const MAX_KNIFE_RADIUS: f32 = clothsim::TARGET_DIST;
+/// Knife radius change per notch of the scroll wheel.
+const KNIFE_RADIUS_STEP: f32 = 2.0;
+const DEFAULT_WELL_STRENGTH: f32 = 40.0;
+const DEFAULT_WELL_RADIUS: f32 = 200.0;
+const DEFAULT_SINK_STRENGTH: f32 = 60.0;
+const DEFAULT_SINK_RADIUS: f32 = 25.0;
+const DEFAULT_BALL_MASS: f32 = 20.0;
+const DEFAULT_BALL_RADIUS: f32 = 12.0;
+const DEFAULT_PUSH_RADIUS: f32 = 40.0;
+/// Launch speed per unit of distance dragged when aiming the cannon.
+const LAUNCH_SCALE: f32 = 1.0;
+/// Fastest a ball leaves the cannon, a rest length per default step.
+const MAX_LAUNCH_SPEED: f32 = clothsim::TARGET_DIST / clothsim::DT;
+/// Steps of flight the cannon's aim previews.
+const AIM_PREVIEW_STEPS: usize = 40;
+/// Force per unit of mass from a full-strength gust, from OSC or the keyboard, as strong as default gravity.
+const MAX_GUST: f32 = clothsim::G;
+/// Seconds an arrow key takes to build the wind from still to full strength.
+const WIND_KEY_SECONDS: f32 = 1.0;
+/// Numbered save slots, besides the autosave.
+const SAVE_SLOTS: usize = 5;
+const SAVES_DIR: &str = "saves";
+const AUTOSAVE_SECONDS: f64 = 60.0;
+/// Steps of input kept to write out with the state if a step fails.
+const CRASH_INPUTS: usize = 600;
+/// Walkers in each generation the Muscles window evolves, scored one a frame.
+const EVOLVE_POPULATION: usize = 20;
+/// Steps each walker the Muscles window evolves gets to show how far it goes.
+const EVOLVE_STEPS: usize = 2000;
+/// Snapshots the timeline keeps, one every `TIMELINE_EVERY` steps, so it goes back ten seconds at 60 steps a second.
+const TIMELINE_SNAPSHOTS: usize = 120;
+const TIMELINE_EVERY: u64 = 5;
+/// Steps of the timeline played back each second while rewinding, as fast as it ran.
+const REWIND_STEPS_PER_SECOND: f32 = 60.0;
+/// Width and height of the Graph window's drawing.
+const GRAPH_SIZE: f32 = 320.0;
+/// Layout steps the Graph window takes each frame until it settles.
+const GRAPH_STEPS_PER_FRAME: usize = 4;
+/// How close to a node in the Graph window, in points, the pointer has to be to pick it.
+const GRAPH_PICK_RADIUS: f32 = 8.0;
+/// Farthest back the Layers window puts a layer, where it's a fifth its size.
+const MAX_LAYER_DEPTH: f32 = 4.0;
+/// What a group is drawn in when the Groups window first gives it a color, a hazard orange.
+const GROUP_COLOR: [u8; 3] = [240, 130, 40];
 
-const NUM_POINTS: usize = 10;
+/// What the background thread needs for its next step.
+#[derive(Copy, Clone)]
+struct ThreadInput {
+    input: StepInput,
+    time_scale: f32,
+    params: Params,
+    gust: Vec2,
+    adaptive_timestep: bool,
+    lod: Option<Lod>,
+}
 
-#[derive(Copy, Clone, Debug)]
-pub struct Node {
-    last_pos: Vec2,
-    pos: Vec2,
-    vel: Vec2,
-    force: Vec2,
-    mass: f32,
-    fixed: bool,
+/// The bridge-building game: the level, the bridge as built before the
+/// last test, to go back to after it, and the test, if one has run.
+struct BridgeGame {
+    bridge: Bridge,
+    built: World,
+    test: Option<BridgeTest>,
+    /// What the last test that finished scored.
+    score: Option<u32>,
+    best: Option<u32>,
 }
 
-impl Default for Node {
-    fn default() -> Self {
-        Self {
-            pos: Default::default(),
-            last_pos: Default::default(),
-            vel: Default::default(),
-            force: Default::default(),
-            mass: 1.0,
-            fixed: Default::default(),
+impl BridgeGame {
+    /// Follows the test through a step of `dt`, scoring it once it's over.
+    fn record(&mut self, world: &World, dt: f32) {
+        let Some(test) = self.test.as_mut().filter(|test| test.outcome.is_none()) else {
+            return;
+        };
+        if let Some(outcome) = test.record(&self.bridge, world, dt) {
+            let score = self.bridge.score(outcome, self.bridge.cost(&self.built), test.broken(world));
+            self.score = Some(score);
+            self.best = self.best.max(Some(score));
         }
     }
 }
 
-impl Node {
-    pub fn with_pos_and_mass(pos: Vec2, mass: f32) -> Node {
-        Node {
-            pos,
-            last_pos: pos,
-            mass,
-            ..Node::default()
+/// A copy of the simulation state that `MainState::restore` can rewind to.
+#[derive(Clone)]
+pub struct Snapshot {
+    world: World,
+    step_count: u64,
+    cursor: CursorHistory,
+}
+
+pub struct MainState {
+    app_state: AppState,
+    world: World,
+    /// Where the scene comes from, for resets and recordings.
+    scene: SceneSource,
+    /// Seed the current scene was built with.
+    seed: u64,
+    /// How much to scatter the strength of links as scenes are built; see `vary_links`.
+    link_variance: f32,
+    /// Moves pins and the ground along with the window when it's resized; see `resize::reanchor`.
+    reanchor: bool,
+    /// The window's size as of the last frame, to notice it changing.
+    view_size: Vec2,
+    /// What to scale node radii, line widths, and text by, or `None` to follow the display; see `ui_scale`.
+    ui_scale: Option<f32>,
+    /// Draws a cursor for the tool in hand in place of the system's; see `pointer`.
+    tool_cursors: bool,
+    /// Which mouse button cuts, grabs, pins, and pans.
+    mouse: MouseBindings,
+    /// Params to step with. The background thread's world picks changes up on its next step.
+    params: Params,
+    /// Command-line params, which win over `sim.toml`.
+    param_overrides: ParamOverrides,
+    sim_config: SimConfigWatcher,
+    /// The cursor as previous steps saw it, for wind and knife strokes. The
+    /// background thread keeps its own, and this one only draws the knife's trail.
+    cursor: CursorHistory,
+    /// Fingers on a touch screen. The first goes through `cursor`, and the rest each keep their own.
+    fingers: Fingers,
+    mouse_motion: MouseMotion,
+    /// Where the mouse went this frame on the way to where it is, in screen space, for the frame's first step.
+    motion: Vec<Vec2>,
+    /// How far from its path the knife cuts, changed by scrolling while cutting.
+    knife_radius: f32,
+    /// Each knife swipe cuts only the first constraint it reaches.
+    single_cut: bool,
+    /// The knife splits nodes along its path instead, for clean slits in cloth.
+    split_nodes: bool,
+    camera: Camera,
+    /// Extra views down the right of the window, each with its own camera, unless comparing side by side.
+    viewports: Vec<Viewport>,
+    show_grid: bool,
+    /// Every node, link, obstacle, emitter, and effect as an entity, with
+    /// what the editor's doing with each as components on it.
+    entities: Entities,
+    time_scale: TimeScale,
+    audio: Audio,
+    ui_wants_pointer: bool,
+    /// The world as the editor's tools pick nodes out of it, remade each frame in the editor.
+    query: Query,
+    show_heatmap: bool,
+    show_glow: bool,
+    show_motion_blur: bool,
+    shade_cloth: bool,
+    show_field_lines: bool,
+    strain_warnings: bool,
+    strain_alert: StrainAlert,
+    rope_materials: RopeMaterials,
+    /// The language hints, the tutorial, and captions are drawn in.
+    locales: Locales,
+    palette: Palette,
+    /// What's in the settings file, to tell when it needs rewriting.
+    saved_settings: Settings,
+    /// Simulation steps since the scene was created.
+    step_count: u64,
+    show_checksum: bool,
+    budget_warnings: bool,
+    /// Time spent in each phase so far this frame, while watching the frame budget.
+    frame_times: FrameTimes,
+    frame_budget: FrameBudget,
+    /// Labels each node with its index and each link with the nodes it joins.
+    show_indices: bool,
+    show_minimap: bool,
+    /// The pointer was over the minimap, and not the UI, last frame.
+    over_minimap: bool,
+    /// Splits steps into substeps while nodes move fast.
+    adaptive_timestep: bool,
+    substeps: Substeps,
+    /// Gives links far from the cursor fewer solver iterations, out past `lod`'s radius.
+    level_of_detail: bool,
+    lod: Lod,
+    /// Solves on `gpu_solver` instead of the CPU, for scenes it can solve.
+    gpu_solve: bool,
+    /// None if the GPU can't run it.
+    gpu_solver: Option<GpuSolver>,
+    recorder: Option<Recorder>,
+    player: Option<Player>,
+    replay_path: String,
+    replay_status: String,
+    checkpoint: Option<Snapshot>,
+    /// Steps the world when physics runs in the background, with the input
+    /// and timestep for the next step. `world` is then an interpolated copy.
+    sim_thread: Option<SimThread<ThreadInput>>,
+    /// `step_count` when the background thread started.
+    thread_start_step: u64,
+    editor_tool: EditorTool,
+    cursor_tool: CursorTool,
+    wind_brush: WindBrush,
+    /// Size of the disc the push tool shoves nodes out of.
+    push_radius: f32,
+    /// Pull and reach of the next gravity well dropped.
+    well_strength: f32,
+    well_radius: f32,
+    /// Pull and size of the next sink dropped.
+    sink_strength: f32,
+    sink_radius: f32,
+    /// Mass and size of the cannon's next ball.
+    ball_mass: f32,
+    ball_radius: f32,
+    /// What the Build tool's nodes and links are made of.
+    build_material: Material,
+    /// The chain being posed, from its root to the dragged end.
+    posed_chain: Option<Chain>,
+    /// Winch power held down in the Winch window this frame, like the arrow keys.
+    winch_button: f32,
+    /// Whether the Dispenser window's feed button is held down this frame, like F.
+    dispense_button: bool,
+    /// Where the cannon drag started, which the ball is fired from.
+    aim_start: Option<Vec2>,
+    /// What the Measure tool has measured, drawn over the scene.
+    measurements: Vec<Measurement>,
+    /// Where the Measure tool's next measurement starts.
+    measure_from: Option<End>,
+    /// The dragging cursor's smoothed velocity in simulation time, given to the node on release.
+    drag_velocity: Vec2,
+    /// What the solver added and took away over the steps since the last frame was drawn.
+    energy_flow: EnergyFlow,
+    /// Errors on screen, with the time each was reported.
+    toasts: Vec<(String, f64)>,
+    show_profiler: bool,
+    /// The nodes traced in `entities`, sampled every step while `recording_trajectories`.
+    trajectories: Trajectories,
+    recording_trajectories: bool,
+    trajectory_path: String,
+    /// File name for exports of the current frame, without an extension.
+    export_name: String,
+    export_status: String,
+    /// A bundle's link, code, or file to import.
+    bundle_input: String,
+    capture: Option<FrameCapture>,
+    /// Where `--dump-state` writes each step's JSON, and the path for errors.
+    state_dump: Option<(BufWriter<File>, String)>,
+    capture_every: u32,
+    capture_seconds: f32,
+    /// Whether captured frames and video include the egui windows.
+    capture_ui: bool,
+    video: Option<VideoRecorder>,
+    video_fps: u32,
+    /// Output size as a fraction of the window's.
+    video_scale: f32,
+    /// Runs before every inline step, with its path for errors.
+    script: Option<(Script, String)>,
+    /// The shared session this window steps in lockstep with, if any.
+    #[cfg(feature = "netplay")]
+    session: Option<Session>,
+    #[cfg(feature = "osc")]
+    osc: Option<OscListener>,
+    #[cfg(feature = "gamepad")]
+    gamepad: Option<Gamepad>,
+    /// Where the gamepad's cursor is on screen, and where the mouse was when
+    /// the pad took over from it, while the pad's the one in use.
+    #[cfg(feature = "gamepad")]
+    pad_cursor: Option<(Vec2, Vec2)>,
+    /// Force per unit of mass on every free node, from OSC or the keyboard.
+    gust: Vec2,
+    /// The copy shown on the right of a split screen, stepped with `comparison_params`.
+    comparison: Option<Comparison>,
+    comparison_params: Params,
+    /// The right side's XPBD compliance, or `None` to project constraints by rigidity.
+    comparison_compliance: Option<f32>,
+    /// The last stability analysis, if it was run on this scene.
+    stability: Option<Report>,
+    /// Apply the analysis's recommendation as soon as it finishes.
+    auto_apply_stability: bool,
+    /// Mark the free nodes' center of mass.
+    show_center_of_mass: bool,
+    /// The free nodes' total momentum at each of the last `MOMENTUM_FRAMES` frames, oldest first.
+    momentum_history: VecDeque<Vec2>,
+    /// Measure how the solver converges on the current state every frame.
+    measure_convergence: bool,
+    /// The residual after each of `CONVERGENCE_ITERATIONS` iterations, last time it was measured.
+    convergence: Vec<Residual>,
+    /// The scene next to its textbook solution, if it's a lone pendulum or spring.
+    analytic: Option<Analytic>,
+    /// Draw where the textbook solution has the bob over the simulation.
+    show_analytic: bool,
+    bridge: Option<BridgeGame>,
+    /// Work out and label the force in each link as a truss every frame.
+    show_statics: bool,
+    /// The member forces, last time they were worked out.
+    statics: Option<Result<Truss, NotATruss>>,
+    /// How the world last settled, while it's still paused where it stopped
+    /// and `statics` holds what each link carries there.
+    settled: Option<Settled>,
+    /// The puzzle being played, by index into `puzzle::LEVELS`.
+    puzzle: Option<(usize, Puzzle)>,
+    /// Which of `puzzle::LEVELS` have been solved since the demo started.
+    solved_levels: Vec<bool>,
+    /// Seconds without input before attract mode starts, if it ever does.
+    pub attract_after: Option<f64>,
+    attract: Option<Attract>,
+    /// When there was last any input, in seconds since the demo started.
+    last_input_time: f64,
+    /// Where the mouse was last frame, in screen space, to tell when it moves.
+    last_mouse: Vec2,
+    /// How far across the free nodes' center of mass was when the scene started, to tell how far muscles crawled it.
+    crawl_start: f32,
+    /// What's typed in the Edges window's custom force field.
+    force_text: String,
+    /// The custom force acting on every free node, once applied.
+    force_expr: Option<ForceExpr>,
+    /// Why the custom force didn't parse or stopped.
+    force_error: Option<String>,
+    /// How stiff the Groups window makes a group's links when it makes them springy.
+    group_stiffness: f32,
+    /// Walkers being evolved from the scene's muscles, a walker a frame.
+    evolution: Option<Evolution>,
+    last_generation: Option<Generation>,
+    tutorial: Option<Tutorial>,
+    /// The tutorial's been finished or skipped, so it doesn't start on its own again.
+    tutorial_done: bool,
+    /// The cut challenge going on the scene, with the best time to beat from before it started.
+    challenge: Option<(Challenge, Option<f32>)>,
+    /// Fastest time to finish the cut challenge on each scene, by the scene's name.
+    best_times: BTreeMap<String, f32>,
+    /// The numbered slot F5 saves to and F9 loads from.
+    save_slot: usize,
+    autosave: bool,
+    /// When the game was last autosaved, in seconds since the demo started.
+    last_autosave: f64,
+    save_status: String,
+    /// The last `CRASH_INPUTS` steps' input, with the step each was for.
+    recent_inputs: VecDeque<(u64, StepInput)>,
+    /// The world as it was before the current step, for the crash dump. One
+    /// buffer overwritten every step, and never filled in the browser.
+    before_step: Option<World>,
+    /// The last few seconds of states, to scrub back through while paused.
+    timeline: History<Snapshot>,
+    /// The snapshot in `timeline` scrubbed back to, if it has been since the last step.
+    scrubbed: Option<usize>,
+    /// How far back along `timeline` rewinding has got, in snapshots from the
+    /// oldest, while Backspace is held to play it backwards.
+    rewinding: Option<f32>,
+    /// The Graph window's layout of the links, while it's shown.
+    graph: Option<GraphLayout>,
+}
+
+impl MainState {
+    pub async fn load_audio(&mut self) {
+        self.audio.load().await;
+    }
+
+    pub fn settings(&self) -> Settings {
+        Settings {
+            show_grid: self.show_grid,
+            camera_shake: self.camera.shake_enabled,
+            hit_stop: self.time_scale.hit_stop_enabled,
+            show_heatmap: self.show_heatmap,
+            show_glow: self.show_glow,
+            motion_blur: self.show_motion_blur,
+            shade_cloth: self.shade_cloth,
+            field_lines: self.show_field_lines,
+            strain_warnings: self.strain_warnings,
+            strain_alert: self.strain_alert.fraction,
+            rope_shader: self.rope_materials.selected_name().to_string(),
+            language: self.locales.current().code.clone(),
+            volume: self.audio.volume,
+            muted: self.audio.muted,
+            rope_creak: self.audio.creak_enabled,
+            palette: self.palette,
+            show_checksum: self.show_checksum,
+            budget_warnings: self.budget_warnings,
+            show_indices: self.show_indices,
+            show_minimap: self.show_minimap,
+            single_cut: self.single_cut,
+            split_nodes: self.split_nodes,
+            tutorial_done: self.tutorial_done,
+            best_times: self.best_times.clone(),
+            autosave: self.autosave,
+            adaptive_timestep: self.adaptive_timestep,
+            level_of_detail: self.level_of_detail,
+            gpu_solve: self.gpu_solve,
+            link_variance: self.link_variance,
+            reanchor: self.reanchor,
+            ui_scale: self.ui_scale,
+            tool_cursors: self.tool_cursors,
+            mouse: self.mouse,
+            background_physics: self.sim_thread.is_some(),
         }
     }
 
-    pub fn integrate(&mut self) {
-        if self.fixed {
-            return;
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.show_grid = settings.show_grid;
+        self.camera.shake_enabled = settings.camera_shake;
+        self.time_scale.hit_stop_enabled = settings.hit_stop;
+        self.show_heatmap = settings.show_heatmap;
+        self.show_glow = settings.show_glow;
+        self.show_motion_blur = settings.motion_blur;
+        self.shade_cloth = settings.shade_cloth;
+        self.show_field_lines = settings.field_lines;
+        self.strain_warnings = settings.strain_warnings;
+        self.strain_alert.fraction = settings.strain_alert;
+        self.rope_materials.select_by_name(&settings.rope_shader);
+        self.locales.select_by_code(&settings.language);
+        self.audio.volume = settings.volume;
+        self.audio.muted = settings.muted;
+        self.audio.creak_enabled = settings.rope_creak;
+        self.palette = settings.palette;
+        self.show_checksum = settings.show_checksum;
+        self.budget_warnings = settings.budget_warnings;
+        self.show_indices = settings.show_indices;
+        self.show_minimap = settings.show_minimap;
+        self.single_cut = settings.single_cut;
+        self.split_nodes = settings.split_nodes;
+        self.tutorial_done = settings.tutorial_done;
+        self.best_times = settings.best_times.clone();
+        self.autosave = settings.autosave;
+        self.adaptive_timestep = settings.adaptive_timestep;
+        self.level_of_detail = settings.level_of_detail;
+        self.gpu_solve = settings.gpu_solve;
+        self.link_variance = settings.link_variance;
+        self.reanchor = settings.reanchor;
+        self.ui_scale = settings.ui_scale;
+        self.tool_cursors = settings.tool_cursors;
+        self.mouse = settings.mouse;
+        self.set_background_physics(settings.background_physics);
+    }
+
+    /// Moves stepping onto a worker thread or back. Threads aren't available in the browser.
+    fn set_background_physics(&mut self, enabled: bool) {
+        if enabled && self.sim_thread.is_none() && cfg!(not(target_arch = "wasm32")) {
+            self.params = self.world.params;
+            let mut cursor = self.cursor.clone();
+            let mut substeps = self.substeps;
+            self.sim_thread = Some(SimThread::spawn(
+                self.world.clone(),
+                self.thread_input(),
+                THREAD_STEP_RATE,
+                move |world, input: &ThreadInput| {
+                    if world.params != input.params {
+                        world.set_params(input.params);
+                    }
+                    let dt = input.params.dt * input.time_scale;
+                    substeps = next_substeps(substeps, input.adaptive_timestep, world, dt);
+                    world.lod = input.lod;
+                    blow(world, input.gust);
+                    let cursors = std::slice::from_mut(&mut cursor);
+                    step_world(world, &[input.input], cursors, dt, substeps.count(), None, None);
+                },
+            ));
+            self.thread_start_step = self.step_count;
+        } else if let (false, Some(thread)) = (enabled, self.sim_thread.take()) {
+            let (world, steps) = thread.stop();
+            self.world = world;
+            self.step_count = self.thread_start_step + steps;
+            self.cursor = CursorHistory::default();
         }
+    }
 
-        let acc = self.force / self.mass;
+    fn thread_input(&self) -> ThreadInput {
+        let input = self.sample_input();
+        ThreadInput {
+            input,
+            time_scale: self.time_scale.scale(),
+            params: self.params,
+            gust: self.gust,
+            adaptive_timestep: self.adaptive_timestep,
+            lod: self.lod_around(input.cursor),
+        }
+    }
 
-        self.last_pos = self.pos;
-        self.vel = self.vel + acc * DT;
-        self.pos = self.pos + self.vel * DT;
+    /// The level of detail for a step with the cursor at `cursor`, if it's on.
+    /// Like params, it can't change under replays, which don't record it.
+    fn lod_around(&self, cursor: Vec2) -> Option<Lod> {
+        (self.level_of_detail && self.can_change_params()).then_some(Lod { focus: cursor, ..self.lod })
     }
 
-    pub fn differentiate(&mut self) {
-        if self.fixed {
+    /// Re-applies `sim.toml` when it changes, with the command-line overrides on top.
+    fn reload_params(&mut self) {
+        if !self.can_change_params() {
             return;
         }
 
-        self.vel = (self.pos - self.last_pos) / DT;
-        self.force = Vec2::ZERO;
+        match self.sim_config.poll(get_time()) {
+            Some(Ok(mut params)) => {
+                self.param_overrides.apply(&mut params);
+                self.set_params(params);
+            }
+            Some(Err(err)) => self.report(err),
+            None => {}
+        }
+    }
+
+    /// Changes the params, which the background thread picks up with its next input.
+    fn set_params(&mut self, params: Params) {
+        self.params = params;
+        if self.sim_thread.is_none() {
+            self.world.set_params(params);
+        }
+    }
+
+    /// Whether the params may change outside of a scene reset.
+    fn can_change_params(&self) -> bool {
+        // replays have to keep the params they were recorded with, and peers the ones they share
+        self.recorder.is_none() && self.player.is_none() && !self.in_session()
+    }
+
+    /// The editor changes the world directly, which replays, peers, comparisons, and the background thread can't follow.
+    fn can_edit(&self) -> bool {
+        self.recorder.is_none()
+            && self.player.is_none()
+            && self.sim_thread.is_none()
+            && !self.in_session()
+            && self.comparison.is_none()
+            && self.puzzle.is_none()
+    }
+
+    /// Makes room for the extra views, if there are any and the screen isn't
+    /// split for comparing, and moves each toward what it follows.
+    fn update_viewports(&mut self) {
+        let count = if self.camera.split { 0 } else { self.viewports.len() };
+        let (main, areas) = viewports::layout(count);
+        self.camera.area = (count > 0).then_some(main);
+        let (cursor, inspected) = (self.camera.mouse_world_pos(), self.entities.selected::<Inspected>());
+        for (viewport, area) in self.viewports.iter_mut().zip(areas) {
+            viewport.update(area, &self.world, inspected, cursor, get_frame_time());
+        }
     }
 
-    pub fn apply_gravity(&mut self) {
-        if self.fixed {
+    /// Moves the scene's pins and ground along with the window if it changed
+    /// size since the last frame. Recordings, replays, and shared sessions are
+    /// left alone, since moving things would throw them out of step.
+    fn follow_window_size(&mut self) {
+        let size = Vec2::new(screen_width(), screen_height());
+        if size == self.view_size {
             return;
         }
+        if self.reanchor && self.can_edit() {
+            resize::reanchor(&mut self.world, self.view_size, size);
+        }
+        self.view_size = size;
+    }
 
-        self.force += Vec2::new(0.0, G * self.mass);
+    /// Whether other windows share this simulation, so only their common input may change it.
+    fn in_session(&self) -> bool {
+        #[cfg(feature = "netplay")]
+        return self.session.is_some();
+        #[cfg(not(feature = "netplay"))]
+        false
     }
 
-    pub fn apply_drag(&mut self) {
-        if self.fixed {
+    fn set_app_state(&mut self, state: AppState) {
+        if state == AppState::Editor && !self.can_edit() {
             return;
         }
 
-        self.force += -self.vel * DRAG;
+        self.app_state = state;
+        self.entities.select::<Dragged>(None);
+        self.aim_start = None;
+        self.entities.select::<BuildFrom>(None);
+        self.posed_chain = None;
+        if let Some(thread) = self.sim_thread.as_ref() {
+            thread.set_paused(!state.steps());
+        }
+    }
+
+    /// Per-frame input that isn't part of the simulation step.
+    pub fn handle_input(&mut self) {
+        profile_scope!("handle_input");
+        // edits, undos, and scrubbing change the world between steps, without events
+        self.entities.sync(&self.world, &[]);
+        self.watch_for_attract();
+        self.motion = self.mouse_motion.take();
+        // a new first finger starts its strokes afresh, rather than from where the last one lifted
+        if self.fingers.update(&touches(), get_time()) && self.player.is_none() {
+            self.cursor = CursorHistory::default();
+        }
+        if let Some((from, to)) = self.fingers.pinch() {
+            self.camera.pinch(from, to);
+        }
+        if !self.ui_wants_pointer {
+            // scrolling while cutting sizes the knife instead of zooming
+            let cutting = self.mouse.cut.down();
+            let (_, scroll) = mouse_wheel();
+            if cutting && scroll != 0.0 {
+                let radius = self.knife_radius + scroll.signum() * KNIFE_RADIUS_STEP;
+                self.knife_radius = radius.clamp(0.0, MAX_KNIFE_RADIUS);
+            }
+            self.camera.update(self.mouse.pan.down(), !cutting);
+        }
+        // clicking or dragging on the minimap moves the view there
+        let clicked = self.over_minimap && is_mouse_button_down(MouseButton::Left);
+        if let Some(minimap) = self.minimap().filter(|_| clicked) {
+            self.camera.pos = minimap.to_world(mouse_position().into());
+        }
+        self.reload_params();
+        self.follow_window_size();
+        self.update_viewports();
+        #[cfg(feature = "netplay")]
+        self.poll_session();
+        #[cfg(feature = "osc")]
+        self.poll_osc();
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepad();
+
+        // hit-stop counts frames, so it would run out while paused
+        if self.app_state.steps() {
+            self.time_scale.tick();
+        }
+
+        if is_key_pressed(KeyCode::Space) {
+            match self.app_state {
+                AppState::Simulating => self.set_app_state(AppState::Paused),
+                AppState::Paused | AppState::Editor => self.set_app_state(AppState::Simulating),
+                AppState::Menu => {}
+            }
+        }
+
+        if is_key_pressed(KeyCode::E) {
+            match self.app_state {
+                AppState::Editor => self.set_app_state(AppState::Simulating),
+                _ => self.set_app_state(AppState::Editor),
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.set_app_state(AppState::Menu);
+        }
+
+        if self.app_state == AppState::Editor {
+            // once for the frame, however many times the tools and the cursor ask
+            self.query = Query::new(&self.world);
+        }
+        if self.app_state == AppState::Editor && self.can_edit() && !self.ui_wants_pointer {
+            self.edit();
+        }
+        // the editor changes the world between steps, so there are lessons to check for without one
+        self.follow_tutorial(&[]);
+
+        if is_key_pressed(KeyCode::G) {
+            self.show_grid = !self.show_grid;
+        }
+
+        if is_key_pressed(KeyCode::S) {
+            self.camera.shake_enabled = !self.camera.shake_enabled;
+        }
+
+        if is_key_pressed(KeyCode::H) {
+            self.time_scale.hit_stop_enabled = !self.time_scale.hit_stop_enabled;
+        }
+
+        if is_key_pressed(KeyCode::I) {
+            self.show_indices = !self.show_indices;
+        }
+
+        if is_key_pressed(KeyCode::M) {
+            self.show_minimap = !self.show_minimap;
+        }
+
+        self.steer_wind();
+
+        let can_rewind = self.can_rewind();
+        if is_key_pressed(KeyCode::C) && can_rewind {
+            self.checkpoint = Some(self.snapshot());
+        }
+
+        if cfg!(not(target_arch = "wasm32")) {
+            if is_key_pressed(KeyCode::F5) {
+                self.save_game(Some(self.save_slot));
+            }
+            if is_key_pressed(KeyCode::F9) && self.can_edit() {
+                self.load_game(Some(self.save_slot));
+            }
+            if self.autosave && get_time() - self.last_autosave >= AUTOSAVE_SECONDS {
+                self.save_game(None);
+            }
+        }
+
+        self.rewind();
+
+        if is_key_pressed(KeyCode::R) && self.can_scrub() {
+            if let Some(checkpoint) = self.checkpoint.take() {
+                self.restore(&checkpoint);
+                self.checkpoint = Some(checkpoint);
+                // what's on the timeline since then won't happen now
+                self.timeline.clear();
+                self.scrubbed = None;
+            }
+        }
     }
 
-    pub fn add_offs(&mut self, offs: Vec2) {
-        if !self.fixed {
-            self.pos += offs;
+    /// Steers the wind with Shift and the arrow keys, for playing without
+    /// sweeping the mouse: each arrow held blows harder that way, and it
+    /// keeps blowing once let go. K calms it.
+    fn steer_wind(&mut self) {
+        // like sim.toml changes, wind would throw replays and peers out of step
+        if !self.can_change_params() {
+            return;
+        }
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let arrows = [
+            (KeyCode::Left, -Vec2::X),
+            (KeyCode::Right, Vec2::X),
+            (KeyCode::Up, -Vec2::Y),
+            (KeyCode::Down, Vec2::Y),
+        ];
+        let held = arrows.into_iter().filter(|&(key, _)| shift && is_key_down(key));
+        let toward = held.fold(Vec2::ZERO, |sum, (_, way)| sum + way);
+        let change = toward * MAX_GUST * get_frame_time() / WIND_KEY_SECONDS;
+        self.gust = (self.gust + change).clamp_length_max(MAX_GUST);
+        if is_key_pressed(KeyCode::K) {
+            self.gust = Vec2::ZERO;
         }
     }
-}
 
-pub struct Constraint {
-    a: usize,
-    b: usize,
-    break_threshold: f32,
-}
+    /// Plays the timeline backwards while Backspace is held, pausing first, and
+    /// stops on the snapshot nearest where it's let go, to carry on from there
+    /// like one scrubbed back to. What's shown in between is only for looking.
+    fn rewind(&mut self) {
+        let held = is_key_down(KeyCode::Backspace) && self.can_scrub() && !self.timeline.is_empty();
+        let Some(at) = self.rewinding else {
+            if held && matches!(self.app_state, AppState::Simulating | AppState::Paused) {
+                self.set_app_state(AppState::Paused);
+                self.rewinding = Some(self.scrubbed.unwrap_or(self.timeline.len() - 1) as f32);
+            }
+            return;
+        };
+        if !held {
+            self.rewinding = None;
+            let i = (at.round() as usize).min(self.timeline.len() - 1);
+            let (_, snapshot) = self.timeline.get(i).expect("rewinding stays on the timeline");
+            let snapshot = snapshot.clone();
+            self.restore(&snapshot);
+            self.scrubbed = Some(i);
+            return;
+        }
 
-impl Constraint {
-    pub fn solve(&self, arena: &mut Vec<Node>) {
-        let (a_offs, b_offs) = {
-            let a = &arena[self.a];
-            let b = &arena[self.b];
+        let at = (at - get_frame_time() * REWIND_STEPS_PER_SECOND / TIMELINE_EVERY as f32).max(0.0);
+        self.rewinding = Some(at);
+        let (i, alpha) = (at.floor() as usize, at.fract());
+        let (step, older) = self.timeline.get(i).expect("rewinding stays on the timeline");
+        self.world = match self.timeline.get(i + 1) {
+            Some((_, newer)) => newer.world.blended_from(&older.world, alpha),
+            None => older.world.clone(),
+        };
+        self.step_count = step;
+    }
+
+    /// The background thread's world is only an interpolated copy, peers can't
+    /// rewind, and a comparison would need its own checkpoint.
+    fn can_rewind(&self) -> bool {
+        self.sim_thread.is_none() && !self.in_session() && self.comparison.is_none()
+    }
 
-            let r = b.pos - a.pos;
-            let dist = r.length();
+    /// Whether the world can go back in time, to a checkpoint or along the
+    /// timeline: going back mid-recording or playback would desync the
+    /// replay's step numbers.
+    fn can_scrub(&self) -> bool {
+        self.can_rewind() && self.recorder.is_none() && self.player.is_none()
+    }
 
-            let norm = r.normalize_or_zero();
-            let diff = dist - TARGET_DIST;
-            let mut offs = norm * diff * RIGIDITY / (a.mass + b.mass);
+    /// Starts attract mode after `attract_after` seconds without input, moves
+    /// it on through its playlist, and leaves it, back to the scene from
+    /// before, on any input at all.
+    fn watch_for_attract(&mut self) {
+        let Some(after) = self.attract_after else {
+            return;
+        };
+        let now = get_time();
+        let mouse = Vec2::from(mouse_position());
+        let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+        let touched = mouse != self.last_mouse
+            || buttons.into_iter().any(is_mouse_button_down)
+            || mouse_wheel() != (0.0, 0.0)
+            || get_last_key_pressed().is_some()
+            || !touches().is_empty();
+        self.last_mouse = mouse;
+        if touched {
+            self.last_input_time = now;
+            if let Some(attract) = self.attract.take() {
+                self.player = None;
+                match self.build_scene() {
+                    Ok(world) => self.reset_scene(world, attract.seed),
+                    Err(err) => self.report(err),
+                }
+            }
+            return;
+        }
 
-            if dist < TARGET_DIST {
-                offs *= 0.5;
+        let can_play = self.recorder.is_none() && self.sim_thread.is_none() && !self.in_session();
+        if self.attract.is_none() && now - self.last_input_time >= after && can_play {
+            let attract = Attract::new(self.seed);
+            self.play_replay(attract.replay());
+            self.attract = Some(attract);
+            self.set_app_state(AppState::Simulating);
+        } else if let Some(attract) = self.attract.as_mut() {
+            if attract.advance(self.player.is_some(), now) {
+                let replay = attract.replay();
+                self.play_replay(replay);
             }
+        }
+    }
 
-            (offs / a.mass, -offs / b.mass)
+    /// Builds `replay`'s scene and plays it back from the start.
+    fn play_replay(&mut self, replay: Replay) {
+        let mut world = match replay.scene.build(replay.view.x, replay.view.y, replay.params) {
+            Ok(world) => world,
+            Err(err) => return self.report(err),
         };
+        vary_links(&replay.scene, &mut world, replay.variance, replay.seed);
+        self.reset_scene(world, replay.seed);
+        self.replay_status = format!("Playing {} steps", replay.length);
+        if replay.math != clothsim::math::MODE {
+            self.replay_status += &format!("; recorded with {} math, so it may drift", replay.math);
+        }
+        self.player = Some(Player::new(replay));
+    }
 
-        arena[self.a].add_offs(a_offs);
-        arena[self.b].add_offs(b_offs);
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            world: self.world.clone(),
+            step_count: self.step_count,
+            cursor: self.cursor.clone(),
+        }
     }
-}
 
-pub struct MainState {
-    arena: Vec<Node>,
-    constraints: Vec<Constraint>,
-    last_mouse_pos: Vec2,
-}
+    /// The energy of the world's free nodes right now.
+    pub fn energy(&self) -> Energy {
+        Energy::of(&self.world)
+    }
 
-impl MainState {
-    pub fn apply_wind(&mut self) {
-        // disable wind when knife is on
-        if is_mouse_button_down(MouseButton::Right) {
-            return
+    /// A slider back through the timeline while paused, carrying on from
+    /// wherever it's left once the simulation runs again.
+    fn timeline_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(last) = self.timeline.len().checked_sub(1) else {
+            ui.label("Run the simulation to fill the timeline.");
+            return;
+        };
+        let paused = self.app_state == AppState::Paused;
+        let mut at = self.rewinding.map_or(self.scrubbed.unwrap_or(last), |at| at.round() as usize);
+        let slider = egui::Slider::new(&mut at, 0..=last).show_value(false);
+        if ui.add_enabled(paused && self.can_scrub() && self.rewinding.is_none(), slider).changed() {
+            let (_, snapshot) = self.timeline.get(at).expect("the slider stays on the timeline");
+            let snapshot = snapshot.clone();
+            self.restore(&snapshot);
+            self.scrubbed = Some(at);
+        }
+        let step = |i: usize| self.timeline.get(i).map_or(0, |(step, _)| step);
+        let back = (step(last) - step(at)) as f32 * self.world.params.dt;
+        ui.label(format!("Step {}, {:.2} time units before the latest", step(at), back));
+        if !paused {
+            ui.label("Pause to scrub back, or hold Backspace to play it backwards.");
+            ui.label("Running again from there forgets what came after.");
+        }
+    }
+
+    /// Rewinds the simulation to `snapshot`, leaving the camera, effects, and settings alone.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.world = snapshot.world.clone();
+        self.step_count = snapshot.step_count;
+        self.cursor = snapshot.cursor.clone();
+    }
+
+    /// Editor tools: left drag moves a node, right click pins or unpins one,
+    /// and shift click tracks one for trajectory recording. Nodes on hidden
+    /// layers can't be picked.
+    fn edit(&mut self) {
+        let mouse = self.camera.mouse_world_pos();
+        let hovered = self.hovered_node();
+
+        // a bridge is built, not pinned, moved, or shot at, and not while it's being tested
+        if self.bridge.is_some() || self.editor_tool == EditorTool::Build {
+            if self.bridge.as_ref().is_some_and(|game| game.test.is_some()) {
+                return;
+            }
+            if self.mouse.grab.pressed() {
+                self.build(mouse, hovered);
+            }
+            if self.mouse.pin.pressed() {
+                self.entities.select::<BuildFrom>(None);
+            }
+            return;
+        }
+
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let clicked = self.mouse.grab.pressed();
+        if clicked && self.editor_tool == EditorTool::Well {
+            self.toggle_well(mouse);
+        } else if clicked && self.editor_tool == EditorTool::Sink {
+            self.toggle_sink(mouse);
+        } else if clicked && self.editor_tool == EditorTool::Cannon {
+            self.aim_start = Some(mouse);
+        } else if clicked && self.editor_tool == EditorTool::Measure {
+            self.measure(mouse, hovered);
+        } else if clicked && self.editor_tool == EditorTool::Pose {
+            self.posed_chain = hovered.map(|i| Chain::to(&self.world, i));
+        } else if let (true, true, Some(i)) = (shift, clicked, hovered) {
+            self.entities.toggle_traced(i);
+        } else if clicked {
+            self.entities.select::<Dragged>(hovered);
+            if hovered.is_some() {
+                self.entities.select::<Inspected>(hovered);
+            }
+            self.drag_velocity = Vec2::ZERO;
+        }
+        if !self.mouse.grab.down() {
+            self.posed_chain = None;
+            if let Some(start) = self.aim_start.take() {
+                self.world.spawn_ball(start, launch_velocity(start, mouse), self.ball_mass, self.ball_radius);
+            }
+            // throw the node with the cursor's velocity, which it keeps once the simulation resumes
+            let dragged = self.entities.selected::<Dragged>();
+            self.entities.select::<Dragged>(None);
+            if let Some(node) = dragged.and_then(|i| self.world.arena.get_mut(i)) {
+                node.vel = (self.drag_velocity * FLING_SCALE).clamp_length_max(MAX_FLING_SPEED);
+            }
+        }
+
+        if let Some(chain) = self.posed_chain.as_ref() {
+            chain.reach(&mut self.world, mouse);
+        }
+        if let Some(node) = self.entities.selected::<Dragged>().and_then(|i| self.world.arena.get_mut(i)) {
+            // a frame of real time is `STEPS_PER_FRAME` steps of simulation time
+            let frame_time = STEPS_PER_FRAME as f32 * self.world.params.dt;
+            self.drag_velocity = self.drag_velocity.lerp((mouse - node.pos) / frame_time, 0.5);
+            node.pos = mouse;
+            node.last_pos = mouse;
+            node.vel = Vec2::ZERO;
+        }
+
+        if let (true, Some(i)) = (self.mouse.pin.pressed(), hovered) {
+            self.toggle_pin(i);
         }
+    }
+
+    /// The node under the cursor for the editor's tools to pick, if any.
+    fn hovered_node(&self) -> Option<usize> {
+        let mouse = self.camera.mouse_world_pos();
+        self.query.nodes_within(mouse, NODE_RADIUS * 2.0).first().copied()
+    }
 
-        let current_mouse_pos: Vec2 = mouse_position().into();
-        for node in self.arena.iter_mut() {
-            if (node.pos - current_mouse_pos).length() < 30.0 {
-                let f = current_mouse_pos - self.last_mouse_pos;
-                node.force += f * 50.0;
+    /// The Build tool's click at `pos`: on `hovered`, or a new node there if
+    /// nothing's hovered, it finishes the link being laid, or starts one if
+    /// there isn't one, so the next click carries on from it. Clicking the
+    /// link's start again stops laying. In the bridge game, it all comes out of
+    /// the budget, and links that are too long or cost too much aren't built.
+    fn build(&mut self, pos: Vec2, hovered: Option<usize>) {
+        let build_from = self.entities.selected::<BuildFrom>();
+        if hovered.is_some() && hovered == build_from {
+            self.entities.select::<BuildFrom>(None);
+            return;
+        }
+        let end = hovered.unwrap_or_else(|| {
+            let mass = if self.bridge.is_some() { 1.0 } else { self.build_material.node_mass() };
+            let drag = if self.bridge.is_some() { 0.0 } else { self.build_material.drag };
+            self.world.arena.push(Node { drag, ..Node::with_pos_and_mass(pos, mass) });
+            self.world.arena.len() - 1
+        });
+        let new_nodes = usize::from(hovered.is_none());
+        let built = match (build_from, self.bridge.as_ref()) {
+            (Some(start), Some(game)) => game.bridge.build(&mut self.world, start, end, new_nodes),
+            (None, Some(game)) => game.bridge.cost(&self.world) <= game.bridge.budget,
+            (Some(start), None) => {
+                let rest_length = self.world.arena[start].pos.distance(self.world.arena[end].pos);
+                self.build_material.link(&mut self.world, start, end, rest_length);
+                true
             }
+            (None, None) => true,
+        };
+        if built {
+            self.entities.select::<BuildFrom>(Some(end));
+        } else if hovered.is_none() {
+            self.world.arena.pop();
         }
     }
 
-    pub fn solve_constraints(&mut self) {
-        for _ in 0..5 {
-            for constraint in self.constraints.iter() {
-                constraint.solve(&mut self.arena);
+    /// Pins or unpins node `i`. Nodes pinned on a platform ride it, and unpinning lets them go.
+    fn toggle_pin(&mut self, i: usize) {
+        let node = &mut self.world.arena[i];
+        node.fixed = !node.fixed;
+        node.vel = Vec2::ZERO;
+        let pos = node.pos;
+        for platform in self.world.platforms.iter_mut() {
+            platform.riders.retain(|&(rider, _)| rider != i);
+        }
+        if !node.fixed {
+            return;
+        }
+        if let Some(platform) = self.world.platforms.iter_mut().find(|platform| platform.contains(pos)) {
+            let offset = pos - platform.position();
+            platform.riders.push((i, offset));
+        }
+    }
+
+    /// Removes the gravity well under `pos`, or drops a new one there if there isn't one.
+    fn toggle_well(&mut self, pos: Vec2) {
+        let wells = &mut self.world.wells;
+        match wells.iter().position(|well| well.pos.distance(pos) < WELL_MARKER_RADIUS) {
+            Some(i) => {
+                wells.remove(i);
             }
+            None => wells.push(Well { pos, strength: self.well_strength, radius: self.well_radius }),
         }
     }
 
-    pub fn update(&mut self) -> Result<(), SimError> {
-        self.arena.iter_mut().for_each(Node::apply_gravity);
-        self.arena.iter_mut().for_each(Node::apply_drag);
-        self.apply_wind();
-        self.arena.iter_mut().for_each(Node::integrate);
-        self.solve_constraints();
-        self.constraints.retain(|constraint| {
-            (self.arena[constraint.a].pos - self.arena[constraint.b].pos).length() < constraint.break_threshold
-        });
-        if is_mouse_button_down(MouseButton::Right) {
-            let mouse_pos: Vec2 = mouse_position().into();
-            self.constraints.retain(|constraint| {
-                // https://stackoverflow.com/questions/3838329/how-can-i-check-if-two-segments-intersect
-                let a = self.arena[constraint.a].pos;
-                let b = self.arena[constraint.b].pos;
-                let c = mouse_pos;
-                let d = self.last_mouse_pos;
-
-                fn ccw(a: Vec2, b: Vec2, c: Vec2) -> bool {
-                    (c.y-a.y) * (b.x-a.x) > (b.y-a.y) * (c.x-a.x)
-                }
-
-                let intersects = (ccw(a, c, d) != ccw(b, c, d)) && (ccw(a, b, c) != ccw(a, b, d));
-                !intersects
-            });
+    /// The Measure tool's click at `pos`, on `hovered` if there is one: it
+    /// ends the measurement being taken there, or starts one, or removes the
+    /// measurement under it instead if there's one there and none being taken.
+    fn measure(&mut self, pos: Vec2, hovered: Option<usize>) {
+        let end = hovered.map_or(End::Point(pos), End::Node);
+        let reach = NODE_RADIUS / self.camera.zoom;
+        match self.measure_from.take() {
+            Some(start) if start != end => self.measurements.push(Measurement { ends: [start, end] }),
+            Some(_) => {}
+            None => match self.measurements.iter().position(|m| m.near(&self.world, pos, reach)) {
+                Some(i) => {
+                    self.measurements.remove(i);
+                }
+                None => self.measure_from = Some(end),
+            },
+        }
+    }
+
+    /// The path a ball fired from `aim_start` to the cursor would take with nothing in its way.
+    fn aim_preview(&self) -> Option<Vec<Vec2>> {
+        let start = self.aim_start?;
+        let mut ball = Node::with_pos_and_mass(start, self.ball_mass);
+        ball.vel = launch_velocity(start, self.camera.mouse_world_pos());
+        let Params { gravity, drag, dt, .. } = self.world.params;
+        let path = (0..AIM_PREVIEW_STEPS).map(|_| {
+            ball.apply_gravity(gravity);
+            ball.apply_drag(drag);
+            ball.integrate(dt);
+            ball.differentiate(dt);
+            ball.pos
+        });
+        Some(std::iter::once(start).chain(path).collect())
+    }
+
+    /// Removes the sink under `pos`, or drops a new one there if there isn't one.
+    fn toggle_sink(&mut self, pos: Vec2) {
+        let sinks = &mut self.world.sinks;
+        match sinks.iter().position(|sink| sink.pos.distance(pos) < sink.radius.max(WELL_MARKER_RADIUS)) {
+            Some(i) => {
+                sinks.remove(i);
+            }
+            None => sinks.push(Sink { pos, strength: self.sink_strength, radius: self.sink_radius }),
         }
-        self.arena.iter_mut().for_each(Node::differentiate);
-        self.last_mouse_pos = mouse_position().into();
+    }
+
+    /// Rebuilds the scene at the current window size and params.
+    fn build_scene(&self) -> Result<World, SimError> {
+        let mut world = self.scene.build(screen_width(), screen_height(), self.params)?;
+        vary_links(&self.scene, &mut world, self.link_variance, self.seed);
+        Ok(world)
+    }
+
+    /// Starts over from `world`, as recordings and playback do.
+    fn reset_scene(&mut self, world: World, seed: u64) {
+        self.world = world;
+        self.world.rng = Rng::new(seed);
+        self.seed = seed;
+        self.cursor = CursorHistory::default();
+        self.step_count = 0;
+        self.time_scale.reset();
+        // node indices mean nothing in the new scene
+        self.entities.clear();
+        self.trajectories = Trajectories::new(TRAJECTORY_STEPS);
+        self.posed_chain = None;
+        self.measurements.clear();
+        self.measure_from = None;
+        self.strain_alert.raised = false;
+        self.frame_budget.clear();
+        if let Some((mut script, path)) = self.script.take() {
+            match script.init(&mut self.world) {
+                Ok(()) => self.script = Some((script, path)),
+                Err(message) => self.report(SimError::Script { path, message }),
+            }
+        }
+        if let Some(comparison) = self.comparison.as_mut() {
+            comparison.restart(&self.world);
+        }
+        self.stability = None;
+        self.analytic = Analytic::new(&self.world);
+        self.crawl_start = Momentum::of(&self.world).center_of_mass.x;
+        self.evolution = None;
+        self.bridge = None;
+        self.puzzle = None;
+        self.tutorial = None;
+        self.challenge = None;
+        self.recent_inputs.clear();
+        self.timeline.clear();
+        self.scrubbed = None;
+        self.rewinding = None;
+    }
 
+    /// Splits the screen to run a copy of the current world with `comparison_params` on the right.
+    fn set_comparing(&mut self, enabled: bool) {
+        self.comparison =
+            enabled.then(|| Comparison::new(&self.world, self.comparison_params, self.comparison_compliance));
+        self.camera.split = enabled;
+        self.entities.select::<Dragged>(None);
+    }
+
+    /// Compiles the script at `path` and runs its setup on the current scene.
+    pub fn load_script(&mut self, path: &str) -> Result<(), SimError> {
+        let source = std::fs::read_to_string(path).map_err(|source| SimError::Io {
+            action: "read",
+            path: path.to_string(),
+            source,
+        })?;
+        let error = |message| SimError::Script { path: path.to_string(), message };
+        let mut script = Script::new(&source).map_err(error)?;
+        script.init(&mut self.world).map_err(error)?;
+        self.script = Some((script, path.to_string()));
         Ok(())
     }
 
-    pub fn draw(&mut self) -> Result<(), SimError> {
-        for constraint in self.constraints.iter() {
-            let a = self.arena[constraint.a];
-            let b = self.arena[constraint.b];
-            draw_line(a.pos.x, a.pos.y, b.pos.x, b.pos.y, ROPE_WIDTH, WHITE);
+    /// Swaps in the world, colors, and view from a bundle's link, code, or
+    /// file, keeping the script like loading a save does.
+    pub fn import_bundle(&mut self, input: &str) {
+        let bundle = match bundle::read(input) {
+            Ok(bundle) => bundle,
+            Err(message) => return self.report(SimError::SceneLoad { scene: "bundle".to_string(), message }),
+        };
+        let script = self.script.take();
+        let (rng, params) = (bundle.world.rng, bundle.world.params);
+        self.reset_scene(bundle.world, self.seed);
+        self.script = script;
+        self.world.rng = rng;
+        self.params = params;
+        self.palette = bundle.palette;
+        // snapping there keeps the zoom in the range scrolling allows
+        self.camera.approach(bundle.camera_pos, bundle.zoom, 1.0);
+        self.export_status = "Imported the bundle".to_string();
+    }
+
+    /// Shares this window's simulation through `session`, reporting why if it couldn't start.
+    #[cfg(feature = "netplay")]
+    pub fn start_session(&mut self, session: Result<Session, SimError>) {
+        match session {
+            Ok(session) => {
+                // peers step inline, together, from input alone
+                self.set_background_physics(false);
+                self.recorder = None;
+                self.player = None;
+                self.session = Some(session);
+            }
+            Err(err) => self.report(err),
+        }
+    }
+
+    /// Handles session traffic, starting over whenever the host does.
+    #[cfg(feature = "netplay")]
+    fn poll_session(&mut self) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let (scene, params) = (&self.scene, self.params);
+        match session.poll(|| scene.build(screen_width(), screen_height(), params), self.seed) {
+            Ok(Some((world, seed))) => {
+                self.params = world.params;
+                self.reset_scene(world, seed);
+            }
+            Ok(None) => {}
+            Err(err @ SimError::Disconnected { .. }) => {
+                self.session = None;
+                self.report(err);
+            }
+            Err(err) => self.report(err),
+        }
+    }
+
+    #[cfg(feature = "osc")]
+    pub fn listen_for_osc(&mut self, addr: &str) {
+        match OscListener::bind(addr) {
+            Ok(osc) => self.osc = Some(osc),
+            Err(source) => self.report(SimError::Io { action: "listen on", path: addr.to_string(), source }),
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    pub fn open_gamepad(&mut self, path: &str) {
+        match Gamepad::open(path) {
+            Ok(pad) => self.gamepad = Some(pad),
+            Err(source) => self.report(SimError::Io { action: "open", path: path.to_string(), source }),
+        }
+    }
+
+    /// Moves the pad's cursor with the left stick and changes the cursor tool
+    /// with the bumpers. The pad takes the cursor over once it's touched, and
+    /// the mouse takes it back once it moves.
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepad(&mut self) {
+        let Some(pad) = self.gamepad.as_mut() else {
+            return;
+        };
+        let (bumpers, used) = pad.poll();
+        let mouse = Vec2::from(mouse_position());
+        self.pad_cursor = match self.pad_cursor {
+            Some((_, was)) if mouse != was => None,
+            None if used => Some((mouse, mouse)),
+            pad_cursor => pad_cursor,
+        };
+        if let Some((cursor, _)) = self.pad_cursor.as_mut() {
+            let moved = *cursor + Vec2::from(pad.stick) * gamepad::CURSOR_SPEED * get_frame_time();
+            *cursor = moved.clamp(Vec2::ZERO, Vec2::new(screen_width(), screen_height()));
+        }
+        for bumper in bumpers {
+            let tools = CursorTool::ALL;
+            let current = tools.iter().position(|&tool| tool == self.cursor_tool).unwrap_or(0);
+            let next = match bumper {
+                Bumper::Left => current + tools.len() - 1,
+                Bumper::Right => current + 1,
+            };
+            self.cursor_tool = tools[next % tools.len()];
+        }
+    }
+
+    /// Applies what arrived over OSC since last frame.
+    #[cfg(feature = "osc")]
+    fn poll_osc(&mut self) {
+        let Some(osc) = self.osc.as_ref() else {
+            return;
+        };
+        let controls = osc.poll();
+        // like sim.toml changes, these would throw replays and peers out of step
+        if !self.can_change_params() {
+            return;
+        }
+
+        for control in controls {
+            match control {
+                Control::Wind(strength) => self.gust.x = strength * MAX_GUST,
+                Control::Gravity(amount) => {
+                    self.set_params(Params { gravity: amount * 2.0 * clothsim::G, ..self.params });
+                }
+                Control::TimeScale(speed) => self.time_scale.speed = speed * 2.0,
+                // the background thread's world is only an interpolated copy
+                Control::Cut(height) if self.sim_thread.is_none() => {
+                    let view = self.camera.visible_rect();
+                    let y = view.y + view.h * height;
+                    self.world.cut(Vec2::new(view.x, y), Vec2::new(view.x + view.w, y));
+                }
+                Control::Cut(_) => {}
+            }
+        }
+    }
+
+    /// Calls the script's `on_frame`, dropping the script if it fails so the error isn't repeated.
+    fn run_script(&mut self, dt: f32) {
+        let Some((script, path)) = self.script.as_mut() else {
+            return;
+        };
+        if let Err(message) = script.on_frame(&mut self.world, dt) {
+            let err = SimError::Script { path: path.clone(), message };
+            self.script = None;
+            self.report(err);
+        }
+    }
+
+    /// The live mouse or touch state as simulation input. With several
+    /// fingers down, this is the first one's; see `finger_inputs` for the rest.
+    fn sample_input(&self) -> StepInput {
+        if let Some(first) = self.fingers.down.first() {
+            return self.finger_input(first);
+        }
+        #[cfg(feature = "gamepad")]
+        if let (Some(pad), Some((cursor, _))) = (self.gamepad.as_ref(), self.pad_cursor) {
+            // puzzles are solved with the knife alone, and the knife wins if both triggers are pulled
+            let tool = pad.using_tool() && !pad.cutting() && self.puzzle.is_none();
+            return StepInput {
+                cursor: self.camera.screen_to_world(cursor),
+                wind: tool && self.cursor_tool == CursorTool::Wind,
+                wind_brush: self.wind_brush,
+                knife: pad.cutting(),
+                knife_radius: self.knife_radius,
+                single_cut: self.single_cut,
+                split_nodes: self.split_nodes,
+                winch: self.winch_button,
+                dispense: self.dispense_button,
+                push_radius: if tool && self.cursor_tool == CursorTool::Push { self.push_radius } else { 0.0 },
+            };
+        }
+
+        let knife = self.mouse.cut.down();
+        let panning = self.mouse.pan.down();
+        // with Shift, the arrows steer the wind instead
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let winch = match (is_key_down(KeyCode::Up) && !shift, is_key_down(KeyCode::Down) && !shift) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => self.winch_button,
+        };
+        // the cursor tool is off while the knife is on, the camera is panning, or there's a puzzle to cut through,
+        // and waits for its button if it's been given one
+        let hovering = !knife && !panning && !self.ui_wants_pointer && self.puzzle.is_none();
+        let hovering = hovering && self.mouse.tool_down();
+        StepInput {
+            cursor: self.camera.mouse_world_pos(),
+            wind: hovering && self.cursor_tool == CursorTool::Wind,
+            wind_brush: self.wind_brush,
+            knife: knife && !self.ui_wants_pointer,
+            knife_radius: self.knife_radius,
+            single_cut: self.single_cut,
+            split_nodes: self.split_nodes,
+            winch,
+            dispense: is_key_down(KeyCode::F) || self.dispense_button,
+            push_radius: if hovering && self.cursor_tool == CursorTool::Push { self.push_radius } else { 0.0 },
+        }
+    }
+
+    /// Where the mouse went on its way to where it is for this step, in world
+    /// space, if this is the first step since it moved. Empty while a replay
+    /// records or plays, which keep one position a step, and while a finger
+    /// or the gamepad stands in for the mouse.
+    fn motion_between(&mut self) -> Vec<Vec2> {
+        let motion = std::mem::take(&mut self.motion);
+        let mouse = self.recorder.is_none() && self.player.is_none() && self.fingers.down.is_empty();
+        #[cfg(feature = "gamepad")]
+        let mouse = mouse && self.pad_cursor.is_none();
+        if !mouse {
+            return Vec::new();
+        }
+        motion.into_iter().map(|pos| self.camera.screen_to_world(pos)).collect()
+    }
+
+    /// What `finger` does this step: cut if it's the knife, or whatever the cursor tool does.
+    fn finger_input(&self, finger: &Finger) -> StepInput {
+        let active = finger.settled(get_time()) && !self.ui_wants_pointer;
+        // puzzles are solved with the knife alone
+        let tool = active && finger.role == Role::Tool && self.puzzle.is_none();
+        StepInput {
+            cursor: self.camera.screen_to_world(finger.pos),
+            wind: tool && self.cursor_tool == CursorTool::Wind,
+            wind_brush: self.wind_brush,
+            knife: active && finger.role == Role::Knife,
+            knife_radius: self.knife_radius,
+            single_cut: self.single_cut,
+            split_nodes: self.split_nodes,
+            winch: self.winch_button,
+            dispense: self.dispense_button,
+            push_radius: if tool && self.cursor_tool == CursorTool::Push { self.push_radius } else { 0.0 },
         }
+    }
 
-        for node in self.arena.iter() {
-            let c = if node.fixed { RED } else { WHITE };
-            draw_circle(node.pos.x, node.pos.y, NODE_RADIUS, c);
+    /// Input for each finger after the first. Replays and the background
+    /// thread take one input a step, so these only count while stepping here
+    /// with nothing recording or playing back.
+    fn finger_inputs(&self) -> Vec<StepInput> {
+        if self.recorder.is_some() || self.player.is_some() {
+            return Vec::new();
         }
+        self.fingers.down.iter().skip(1).map(|finger| self.finger_input(finger)).collect()
+    }
 
-        draw_text("Right Click to Cut", 10.0, screen_height() - 50.0, 36.0, WHITE);
+    /// Shows `err` in a toast until it times out or is dismissed.
+    pub fn report(&mut self, err: SimError) {
+        warn!("{}", err);
+        self.toasts.push((err.to_string(), get_time()));
+    }
 
+    /// Fails with `InvalidConstraintIndex` rather than letting a step panic on it.
+    fn check_constraints(&self) -> Result<(), SimError> {
+        let nodes = self.world.arena.len();
+        for (i, constraint) in self.world.constraints.iter().enumerate() {
+            if let Some(node) = [constraint.a, constraint.b].into_iter().find(|&node| node >= nodes) {
+                return Err(SimError::InvalidConstraintIndex { constraint: i, node, nodes });
+            }
+        }
         Ok(())
     }
-}
 
-impl Default for MainState {
-    fn default() -> Self {
-        let mut arena = Vec::new();
-        let mut constraints = Vec::new();
+    fn check_diverged(&self) -> Result<(), SimError> {
+        match self.world.arena.iter().position(|node| !node.pos.is_finite()) {
+            Some(node) => Err(SimError::Diverged { step: self.step_count, node }),
+            None => Ok(()),
+        }
+    }
 
-        let y_offs = screen_height() / 5.0;
+    /// Runs a step if the app state allows it, pausing on errors so they don't repeat every frame.
+    pub fn update(&mut self) -> Result<(), SimError> {
+        // walkers evolve on copies of the scene, whether or not it's running
+        if let Some(generation) = self.evolution.as_mut().and_then(Evolution::evaluate_next) {
+            self.last_generation = Some(generation);
+        }
+        // what rewinding shows is only for looking at, so it's never stepped from
+        if !self.app_state.steps() || self.rewinding.is_some() {
+            return Ok(());
+        }
 
-        let one_third = screen_width() / 3.0;
-        let two_thirds = screen_width() * 2.0 / 3.0;
+        // carrying on from a point scrubbed back to forgets what came after it
+        if let Some(i) = self.scrubbed.take() {
+            self.timeline.truncate_after(i);
+        }
+        // kept for the crash dump, should the step fail; the browser can't write one
+        let step = self.step_count;
+        #[cfg(not(target_arch = "wasm32"))]
+        match self.before_step.as_mut() {
+            Some(before) => before.copy_from(&self.world),
+            None => self.before_step = Some(self.world.clone()),
+        }
+        let result = self.step();
+        if let Err(err) = result.as_ref() {
+            self.set_app_state(AppState::Paused);
+            if let Some(before) = self.before_step.take() {
+                self.write_crash_dump(err, &before, step);
+                self.before_step = Some(before);
+            }
+        } else if self.can_scrub() && self.timeline.due(self.step_count) {
+            self.timeline.push(self.step_count, self.snapshot());
+        }
+        result
+    }
 
-        for i in 0..NUM_POINTS {
-            arena.push(Node::with_pos_and_mass(
-                Vec2::new(one_third, y_offs + TARGET_DIST * i as f32),
-                1.0 + (i as f32 / 20.0).powi(2) * 0.0,
-            ));
+    /// Saves `world`, as it was before the step `step` that failed with
+    /// `err`, to the saves directory, with the error and the input leading up
+    /// to it as comments at the end that loading skips.
+    fn write_crash_dump(&mut self, err: &SimError, world: &World, step: u64) {
+        let mut text = save::to_text(world, step);
+        text += &format!("# {} failed: {}\n", self.scene, err);
+        text += &format!("# seed {}, {} math\n", self.seed, clothsim::math::MODE);
+        text += "# input before it, as a replay writes it:\n";
+        for (step, input) in self.recent_inputs.iter() {
+            text += &format!("# {}\n", replay::input_line(*step, input));
+        }
 
-            if i == 0 {
-                arena[i].fixed = true;
+        let path = PathBuf::from(SAVES_DIR).join(format!("crash-step{}.save", step));
+        match std::fs::create_dir_all(SAVES_DIR).and_then(|()| std::fs::write(&path, text)) {
+            Ok(()) => {
+                warn!("wrote the state before the failed step to {}", path.display());
+                self.toasts.push((format!("Saved the state before it to {}", path.display()), get_time()));
             }
+            Err(source) => self.report(SimError::Io { action: "write", path: path.display().to_string(), source }),
+        }
+    }
 
-            if i > 0 {
-                constraints.push(Constraint {
-                    a: i - 1,
-                    b: i,
-                    break_threshold: TARGET_DIST * 5.0,
-                });
+    fn step(&mut self) -> Result<(), SimError> {
+        profile_scope!("update");
+        let _span = debug_span!("update", step = self.step_count).entered();
+
+        if let Some(thread) = self.sim_thread.as_ref() {
+            let dt = self.params.dt * self.time_scale.scale();
+            let input = self.thread_input();
+            remember_input(&mut self.recent_inputs, self.step_count, input.input);
+            self.cursor.advance(&input.input);
+            thread.set_input(input);
+            self.world = thread.interpolated();
+            self.step_count = self.thread_start_step + thread.steps();
+            let events = thread.drain_events();
+            self.react(events, dt);
+            return self.check_diverged();
+        }
+
+        self.check_constraints()?;
+
+        #[cfg(feature = "netplay")]
+        if self.session.is_some() {
+            return self.step_shared();
+        }
+
+        let input = match self.player.as_mut() {
+            Some(player) => {
+                let (input, hit_stop) = player.advance(self.step_count);
+                if let Some(enabled) = hit_stop {
+                    self.time_scale.hit_stop_enabled = enabled;
+                }
+                input
             }
+            None => self.sample_input(),
+        };
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(self.step_count, input, self.time_scale.hit_stop_enabled);
+        }
+        remember_input(&mut self.recent_inputs, self.step_count, input);
+
+        let dt = self.world.params.dt * self.time_scale.scale();
+        // like params, substeps can't change under replays, which don't record them
+        let adaptive = self.adaptive_timestep && self.can_change_params();
+        self.world.lod = self.lod_around(input.cursor);
+        self.run_script(dt);
+        blow(&mut self.world, self.gust);
+        self.apply_custom_force();
+        if let Some(test) = self.bridge.as_ref().and_then(|game| game.test) {
+            test.drive(&mut self.world);
         }
+        self.substeps = next_substeps(self.substeps, adaptive, &self.world, dt);
+        let substeps = self.substeps.count();
+        let started = get_time();
+        self.cursor.between = self.motion_between();
+        let inputs: Vec<StepInput> = std::iter::once(input).chain(self.finger_inputs()).collect();
+        let others = self.fingers.down.iter_mut().skip(1).take(inputs.len() - 1).map(|finger| &mut finger.cursor);
+        let mut cursors: Vec<CursorHistory> =
+            std::iter::once(&mut self.cursor).chain(others).map(std::mem::take).collect();
+        let times = self.watching_budget().then_some(&mut self.frame_times);
+        let gpu = self.gpu_solver.as_mut().filter(|_| self.gpu_solve);
+        let flow = step_world(&mut self.world, &inputs, &mut cursors, dt, substeps, gpu, times);
+        let mut cursors = cursors.into_iter();
+        self.cursor = cursors.next().unwrap_or_default();
+        for (finger, cursor) in self.fingers.down.iter_mut().skip(1).zip(cursors) {
+            finger.cursor = cursor;
+        }
+        let cost = get_time() - started;
+        self.energy_flow.injected += flow.injected;
+        self.energy_flow.dissipated += flow.dissipated;
+        if let Some(comparison) = self.comparison.as_mut() {
+            comparison.sync_params(self.comparison_params);
+            comparison.sync_solver(self.comparison_compliance);
+            let dt = self.comparison_params.dt * self.time_scale.scale();
+            blow(&mut comparison.world, self.gust);
+            comparison.substeps = next_substeps(comparison.substeps, adaptive, &comparison.world, dt);
+            let cursor = std::slice::from_mut(&mut comparison.cursor);
+            let started = get_time();
+            step_world(&mut comparison.world, &[input], cursor, dt, comparison.substeps.count(), None, None);
+            comparison.record(&self.world, [cost, get_time() - started]);
+            // effects and sounds follow the left side only
+            comparison.world.drain_events();
+        }
+        self.finish_step(dt);
+
+        if self.player.as_ref().is_some_and(|player| player.is_finished(self.step_count)) {
+            self.player = None;
+            self.replay_status = "Playback finished".to_string();
+        }
+
+        self.check_diverged()
+    }
+
+    /// Runs the next step once every peer's input for it has arrived.
+    #[cfg(feature = "netplay")]
+    fn step_shared(&mut self) -> Result<(), SimError> {
+        let input = self.sample_input();
+        remember_input(&mut self.recent_inputs, self.step_count, input);
+        let session = self.session.as_mut().expect("only called in a session");
+        session.send_input(self.step_count, input);
+        let Some((inputs, cursors)) = session.take_inputs(self.step_count) else {
+            return Ok(());
+        };
 
-        Self {
-            arena,
-            constraints,
-            last_mouse_pos: mouse_position().into(),
+        // hit-stop runs on each window's own frames, so peers step without it
+        let dt = self.world.params.dt;
+        let flow = step_world(&mut self.world, &inputs, cursors, dt, 1, None, None);
+        self.energy_flow.injected += flow.injected;
+        self.energy_flow.dissipated += flow.dissipated;
+        self.finish_step(dt);
+        self.check_diverged()
+    }
+
+    /// Records and reacts to the inline step that just ran.
+    fn finish_step(&mut self, dt: f32) {
+        self.step_count += 1;
+        if self.recording_trajectories {
+            self.trajectories.nodes = self.entities.traced();
+            self.trajectories.record(self.step_count, dt, &self.world);
+        }
+        if let Some(analytic) = self.analytic.as_mut() {
+            analytic.record(&self.world, dt);
+        }
+        if let Some(game) = self.bridge.as_mut() {
+            game.record(&self.world, dt);
+        }
+        if let Some((level, puzzle)) = self.puzzle.as_mut() {
+            self.solved_levels[*level] |= puzzle.record(&self.world, dt);
+        }
+        let events = self.world.drain_events();
+        self.dump_state(&events);
+        self.react(events, dt);
+    }
+
+    /// Sends the world to the server's clients, if there are any to send it to.
+    #[cfg(feature = "websocket")]
+    pub fn broadcast(&self, server: &crate::stream::StateServer) {
+        if server.has_clients() {
+            let mut json = Vec::new();
+            export::write_json_state(&mut json, self.step_count, &self.world, &[])
+                .expect("writing to a Vec can't fail");
+            server.broadcast(String::from_utf8(json).expect("the JSON is ASCII"));
+        }
+    }
+
+    /// Appends each step's state as a JSON line to `path` from now on.
+    pub fn dump_state_to(&mut self, path: &str) -> Result<(), SimError> {
+        let file = File::options().create(true).append(true).open(path).map_err(|source| SimError::Io {
+            action: "open",
+            path: path.to_string(),
+            source,
+        })?;
+        self.state_dump = Some((BufWriter::new(file), path.to_string()));
+        Ok(())
+    }
+
+    fn dump_state(&mut self, events: &[SimEvent]) {
+        let Some((out, path)) = self.state_dump.as_mut() else {
+            return;
+        };
+        // flushed every step, since the window can close without dropping anything
+        let result = export::write_json_state(&mut *out, self.step_count, &self.world, events);
+        if let Err(source) = result.and_then(|()| out.flush()) {
+            let err = SimError::Io { action: "write", path: path.clone(), source };
+            self.state_dump = None;
+            self.report(err);
+        }
+    }
+
+    /// Catches the entities up with a step, then presents it: effects and sounds.
+    fn react(&mut self, mut events: Vec<SimEvent>, dt: f32) {
+        self.entities.sync(&self.world, &events);
+        if self.strain_warnings {
+            events.extend(self.strain_alert.check(&self.world));
+        }
+        self.follow_tutorial(&events);
+        self.follow_challenge(&events, dt);
+        let (mut broken, mut cut) = (0, 0);
+        let mut impact_speed: f32 = 0.0;
+        for event in events {
+            match event {
+                SimEvent::ConstraintBroken(_) => broken += 1,
+                SimEvent::ConstraintCut(_) | SimEvent::NodeSplit { .. } => cut += 1,
+                SimEvent::Collision { speed, .. } => impact_speed = impact_speed.max(speed),
+                SimEvent::NodeSlept { .. }
+                | SimEvent::NodeShattered { .. }
+                | SimEvent::NodeFrayed { .. }
+                | SimEvent::NodeConsumed { .. }
+                | SimEvent::NodeDespawned { .. }
+                | SimEvent::NodeRecycled { .. }
+                | SimEvent::StrainAlert(_) => {}
+            }
+            self.on_event(event);
+        }
+        if impact_speed > THUD_MIN_SPEED {
+            self.audio.play(SoundEffect::Thud, impact_speed / THUD_FULL_SPEED);
+        }
+
+        let max_strain = self.world.strains().into_iter().fold(0.0, f32::max);
+        debug!(step = self.step_count, broken, cut, max_strain, impact_speed, "stepped");
+        self.audio.set_creak_level(max_strain);
+        self.entities.update(dt);
+    }
+
+    /// Reacts to one event from the step that just ran.
+    fn on_event(&mut self, event: SimEvent) {
+        match event {
+            SimEvent::ConstraintBroken(link) => {
+                self.entities.burst(link.pos);
+                self.fray_strands(&link);
+                self.camera.add_shake(link.strain * SHAKE_PER_STRAIN);
+                self.audio.play(SoundEffect::Break, 1.0);
+            }
+            SimEvent::ConstraintCut(link) => {
+                self.entities.burst(link.pos);
+                self.fray_strands(&link);
+                self.camera.add_shake(link.strain * SHAKE_PER_STRAIN);
+                self.time_scale.hit_stop();
+                self.audio.play(SoundEffect::Cut, 1.0);
+            }
+            SimEvent::NodeSplit { pos, .. } => {
+                self.entities.burst(pos);
+                self.time_scale.hit_stop();
+                self.audio.play(SoundEffect::Cut, 1.0);
+            }
+            SimEvent::NodeShattered { pos, .. } => self.entities.burst(pos),
+            SimEvent::NodeConsumed { pos, .. } => self.entities.burst(pos),
+            SimEvent::StrainAlert(link) => self.entities.warn(link, get_time()),
+            SimEvent::NodeDespawned { .. }
+            | SimEvent::NodeRecycled { .. }
+            | SimEvent::NodeSlept { .. }
+            | SimEvent::NodeFrayed { .. }
+            | SimEvent::Collision { .. } => {}
+        }
+    }
+
+    /// With fraying on, splays strands out of both ends of `link`, toward where it was.
+    fn fray_strands(&mut self, link: &SeveredLink) {
+        if self.world.fray.is_none() {
+            return;
+        }
+        // from where the ends were when it went, since nodes may have been renumbered since
+        for half in [-link.half, link.half] {
+            self.entities.fray(link.pos + half, -half);
+        }
+    }
+
+    pub fn draw(&mut self) -> Result<(), SimError> {
+        profile_scope!("draw");
+        let (palette, scale) = (self.palette, self.ui_scale());
+        let (radius, width) = (NODE_RADIUS * scale, 2.0 * scale);
+        clear_background(palette.background);
+        set_camera(&self.camera.camera2d());
+        let mut stopwatch = Stopwatch::start(self.watching_budget());
+        self.draw_view(&self.world, &self.camera);
+        stopwatch.lap(&mut self.frame_times, Phase::Draw);
+
+        self.entities.draw(&self.world, radius, width, palette.accent);
+        if let Some(analytic) = self.analytic.as_ref().filter(|_| self.show_analytic) {
+            self.draw_analytic(analytic);
+        }
+        if let Some(Ok(truss)) = self.statics.as_ref() {
+            self.draw_statics(truss);
+        }
+        self.draw_measurements(radius, width);
+        if let Some(Strained { link, .. }) = self.entities.strain_warning() {
+            let pos = self.strained_link_pos(&link);
+            draw_circle_lines(pos.x, pos.y, radius * 4.0, width, palette.heat(link.fraction));
+        }
+        let momentum = Momentum::of(&self.world);
+        if self.show_center_of_mass && momentum.mass > 0.0 {
+            let Vec2 { x, y } = momentum.center_of_mass;
+            draw_circle_lines(x, y, radius * 2.0, width, palette.accent);
+            draw_line(x - radius * 3.0, y, x + radius * 3.0, y, width, palette.accent);
+            draw_line(x, y - radius * 3.0, x, y + radius * 3.0, width, palette.accent);
+        }
+        if self.momentum_history.len() == MOMENTUM_FRAMES {
+            self.momentum_history.pop_front();
+        }
+        self.momentum_history.push_back(momentum.linear);
+        if let Some(path) = self.aim_preview() {
+            let (start, aim) = (path[0], self.camera.mouse_world_pos());
+            draw_circle_lines(start.x, start.y, self.ball_radius, width, palette.accent);
+            draw_line(start.x, start.y, aim.x, aim.y, width, palette.accent);
+            for pos in path.iter().step_by(2) {
+                draw_circle(pos.x, pos.y, width, palette.accent);
+            }
+        }
+
+        if let Some(game) = self.bridge.as_ref() {
+            self.draw_bridge(game);
+        }
+        if let Some((_, puzzle)) = self.puzzle.as_ref() {
+            self.draw_puzzle(puzzle);
+        }
+        if let Some(tutorial) = self.tutorial.as_ref() {
+            self.draw_tutorial(tutorial);
+        }
+        if let (Some(start), AppState::Editor) = (self.entities.selected::<BuildFrom>(), self.app_state) {
+            let (start, end) = (self.world.arena[start].pos, self.camera.mouse_world_pos());
+            let too_long = self.bridge.is_some() && start.distance(end) > MAX_LINK_LENGTH;
+            let color = if too_long { palette.heat(1.0) } else { palette.accent };
+            draw_line(start.x, start.y, end.x, end.y, width, color);
+        }
+
+        if let Some(comparison) = self.comparison.as_ref() {
+            set_camera(&self.camera.view_camera2d(1));
+            self.draw_view(&comparison.world, &self.camera);
+        }
+        for viewport in self.viewports.iter().filter(|_| !self.camera.split) {
+            set_camera(&viewport.camera.camera2d());
+            self.draw_view(&self.world, &viewport.camera);
+        }
+
+        set_default_camera();
+
+        if self.show_grid {
+            grid::draw_grid_labels(&self.camera, scale, palette.text);
+        }
+        for viewport in self.viewports.iter().filter(|_| !self.camera.split) {
+            if let Some(Rect { x, y, w, h }) = viewport.camera.area {
+                draw_rectangle_lines(x, y, w, h, width, palette.text);
+                draw_scaled_text(viewport.follow.name(), x + 8.0 * scale, y + 20.0 * scale, 20.0, scale, palette.text);
+            }
+        }
+        if let Some(Ok(truss)) = self.statics.as_ref() {
+            self.draw_statics_labels(truss);
+        }
+        self.draw_measurement_labels();
+        if self.show_indices {
+            self.draw_indices();
+        }
+        #[cfg(feature = "gamepad")]
+        if let Some((Vec2 { x, y }, _)) = self.pad_cursor {
+            draw_circle_lines(x, y, radius * 2.0, width, palette.accent);
+            draw_line(x - radius * 3.0, y, x + radius * 3.0, y, width, palette.accent);
+            draw_line(x, y - radius * 3.0, x, y + radius * 3.0, width, palette.accent);
+        }
+        if let Some(lesson) = self.tutorial.as_ref().and_then(Tutorial::lesson) {
+            draw_centered(self.tr(lesson.prompt()), 60.0 * scale, 32.0, scale, palette.accent);
+        }
+        if self.rewinding.is_some() {
+            let text = self.tr("Rewinding, let go of Backspace to stop");
+            draw_centered(text, 60.0 * scale, 32.0, scale, palette.accent);
+        }
+        if let Some((challenge, to_beat)) = self.challenge.as_ref() {
+            self.draw_challenge(challenge, *to_beat);
+        }
+        if let Some(minimap) = self.minimap() {
+            minimap.draw(&self.world, self.camera.visible_rect(), &palette);
+        }
+
+        if let Some(comparison) = self.comparison.as_ref() {
+            let middle = screen_width() / 2.0;
+            draw_line(middle, 0.0, middle, screen_height(), width, palette.text);
+            let sides = [(&self.world, &comparison.stats[0], 0.0), (&comparison.world, &comparison.stats[1], middle)];
+            for (world, stats, left) in sides {
+                for (text, y) in [(comparison::describe(world), 105.0), (stats.describe(), 130.0)] {
+                    let (x, y) = (left + (middle - measure_scaled_text(&text, 20.0, scale).width) / 2.0, y * scale);
+                    draw_scaled_text(&text, x, screen_height() - y, 20.0, scale, palette.text);
+                }
+            }
+        }
+
+        if self.gust != Vec2::ZERO {
+            self.draw_wind_gauge();
+        }
+
+        // attract mode is for passers-by, so it's just the caption, with no controls to get in the way
+        match self.attract.as_ref() {
+            Some(attract) => draw_caption(attract.caption(), self.locales.current(), scale, palette.text),
+            None => self.draw_hints(),
+        }
+
+        if self.show_checksum {
+            let text = format!("step {} checksum {:016x}", self.step_count, self.world.checksum());
+            let width = measure_scaled_text(&text, 24.0, scale).width;
+            draw_scaled_text(&text, screen_width() - width - 10.0, 24.0 * scale, 24.0, scale, palette.text);
+        }
+
+        #[cfg(feature = "netplay")]
+        if let Some(session) = self.session.as_ref() {
+            let text = session.status();
+            let width = measure_scaled_text(&text, 24.0, scale).width;
+            draw_scaled_text(&text, screen_width() - width - 10.0, 48.0 * scale, 24.0, scale, palette.text);
+        }
+
+        if self.watching_budget() {
+            self.frame_budget.record(std::mem::take(&mut self.frame_times));
+            self.draw_budget_warnings();
+        }
+
+        let pointer = self.pointer();
+        show_mouse(pointer.is_none());
+        if let Some(pointer) = pointer {
+            let size = POINTER_SIZE * scale;
+            pointer.draw(mouse_position().into(), size, palette.accent, palette.text);
+        }
+
+        if !self.capture_ui {
+            self.capture_frame();
+        }
+        if self.attract.is_none() {
+            self.draw_ui();
+        }
+        if self.capture_ui {
+            self.capture_frame();
+        }
+        // the steps after this frame are tallied for the next one
+        self.energy_flow = EnergyFlow::default();
+
+        Ok(())
+    }
+
+    /// Adds the Edges window's custom force, if there is one, stopping it with the error if it fails. Replays and
+    /// peers don't know about it, so like editing it waits until they're done.
+    fn apply_custom_force(&mut self) {
+        let t = self.step_count as f32 * self.world.params.dt;
+        let result = match self.force_expr.as_ref() {
+            Some(expr) if self.can_edit() => expr.apply(&mut self.world, t),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            (self.force_expr, self.force_error) = (None, Some(e));
+        }
+    }
+
+    /// Whether phases are being timed for the budget warnings, which the stress scene always wants.
+    fn watching_budget(&self) -> bool {
+        frame_budget::AVAILABLE
+            && (self.budget_warnings
+                || matches!(&self.scene, SceneSource::Stress { .. })
+                || self.scene == SceneSource::Builtin("stress".to_string()))
+    }
+
+    /// Lists the phases over budget down the right side, under the checksum and session status.
+    fn draw_budget_warnings(&self) {
+        let scale = self.ui_scale();
+        let over = self.frame_budget.over();
+        for (i, (phase, spent, budget)) in over.into_iter().enumerate() {
+            let text = self.locales.current().fill(
+                "{phase} over budget: {spent} of {budget} ms",
+                &[
+                    ("phase", &self.tr(phase.label())),
+                    ("spent", &format!("{:.1}", 1000.0 * spent)),
+                    ("budget", &format!("{:.1}", 1000.0 * budget)),
+                ],
+            );
+            let width = measure_scaled_text(&text, 24.0, scale).width;
+            let y = (72.0 + 24.0 * i as f32) * scale;
+            draw_scaled_text(&text, screen_width() - width - 10.0, y, 24.0, scale, self.palette.heat(1.0));
+        }
+    }
+
+    /// What to scale node radii, line widths, and text by: the one picked in the settings window, or the display's.
+    fn ui_scale(&self) -> f32 {
+        ui_scale::resolve(self.ui_scale)
+    }
+
+    /// The cursor to draw for what the mouse does where it is now, or `None`
+    /// for the system's, over windows and when there's no tool to show.
+    fn pointer(&self) -> Option<Pointer> {
+        let off_scene = self.ui_wants_pointer || self.over_minimap || self.attract.is_some();
+        if !self.tool_cursors || off_scene || !self.fingers.down.is_empty() {
+            return None;
+        }
+        match self.app_state {
+            AppState::Simulating if self.mouse.cut.down() => Some(Pointer::Blade),
+            // puzzles are solved with the knife alone
+            AppState::Simulating if self.puzzle.is_some() => None,
+            AppState::Simulating => match self.cursor_tool {
+                CursorTool::Off => None,
+                CursorTool::Wind => Some(Pointer::Fan),
+                CursorTool::Push => Some(Pointer::Hand),
+            },
+            AppState::Editor if !self.can_edit() => None,
+            // a right click pins or unpins it, with every tool but Build
+            AppState::Editor if self.editor_tool != EditorTool::Build && self.hovered_node().is_some() => {
+                Some(Pointer::Pin)
+            }
+            AppState::Editor if self.entities.selected::<Dragged>().is_some() => Some(Pointer::Hand),
+            AppState::Editor => match self.editor_tool {
+                EditorTool::Grab | EditorTool::Pose => Some(Pointer::Hand),
+                _ => None,
+            },
+            AppState::Paused | AppState::Menu => None,
+        }
+    }
+
+    /// The minimap, if it's on and the scene doesn't all fit on screen.
+    fn minimap(&self) -> Option<Minimap> {
+        if !self.show_minimap || self.camera.split || !self.viewports.is_empty() {
+            return None;
+        }
+        Minimap::new(&self.world, self.camera.visible_rect())
+    }
+
+    /// A dial in the top right corner with an arrow pointing the way the wind
+    /// blows, reaching the rim at full strength, and the strength in words.
+    fn draw_wind_gauge(&self) {
+        let (color, scale) = (self.palette.text, self.ui_scale());
+        let (radius, width) = (40.0 * scale, 2.0 * scale);
+        let center = Vec2::new(screen_width() - radius - 20.0 * scale, 80.0 * scale + radius);
+        draw_circle_lines(center.x, center.y, radius, width / 2.0, Palette::with_alpha(color, 0.3));
+        let tip = center + self.gust / MAX_GUST * radius;
+        draw_line(center.x, center.y, tip.x, tip.y, width * 2.0, self.palette.accent);
+        draw_circle(tip.x, tip.y, width * 2.0, self.palette.accent);
+        let percent = format!("{:.0}", self.gust.length() / MAX_GUST * 100.0);
+        let text = self.locales.current().fill("Wind {percent}%, K to calm", &[("percent", &percent)]);
+        let text_width = measure_scaled_text(&text, 20.0, scale).width;
+        let (x, y) = (screen_width() - text_width - 10.0 * scale, center.y + radius + 24.0 * scale);
+        draw_scaled_text(&text, x, y, 20.0, scale, color);
+    }
+
+    /// Which button does what, each picked from a list, and the usual layouts in a click.
+    fn mouse_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Right-handed").clicked() {
+                self.mouse = MouseBindings::RIGHT_HANDED;
+            }
+            if ui.button("Left-handed").clicked() {
+                self.mouse = MouseBindings::LEFT_HANDED;
+            }
+        });
+        let mouse = &mut self.mouse;
+        for (label, button) in
+            [("Cut", &mut mouse.cut), ("Grab", &mut mouse.grab), ("Pin", &mut mouse.pin), ("Pan", &mut mouse.pan)]
+        {
+            egui::ComboBox::from_label(label).selected_text(button.name()).show_ui(ui, |ui| {
+                for choice in Button::ALL {
+                    ui.selectable_value(button, choice, choice.name());
+                }
+            });
+        }
+        egui::ComboBox::from_label("Cursor tool")
+            .selected_text(mouse.tool.map_or("No button", Button::name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut mouse.tool, None, "No button");
+                for choice in Button::ALL {
+                    ui.selectable_value(&mut mouse.tool, Some(choice), choice.name());
+                }
+            })
+            .response
+            .on_hover_text("Held to blow or push, or no button for the cursor tool to act whenever the cursor moves");
+        if let Some(clash) = mouse.clash() {
+            ui.colored_label(ui.visuals().warn_fg_color, clash);
+        }
+    }
+
+    /// `text` in the language picked in the settings window; see `locale`.
+    fn tr<'a>(&'a self, text: &'a str) -> &'a str {
+        self.locales.current().tr(text)
+    }
+
+    /// `hint` translated, with the buttons bound to its `{cut}`, `{grab}`, `{pin}`, and `{pan}` named.
+    fn hint(&self, hint: &str) -> String {
+        let locale = self.locales.current();
+        let MouseBindings { cut, grab, pin, pan, .. } = self.mouse;
+        let [cut, grab, pin, pan] = [cut, grab, pin, pan].map(|button| locale.tr(button.name()));
+        locale.fill(hint, &[("cut", &cut), ("grab", &grab), ("pin", &pin), ("pan", &pan)])
+    }
+
+    /// What the current state or editor tool does, and the keys, along the bottom.
+    fn draw_hints(&self) {
+        let (color, scale) = (self.palette.text, self.ui_scale());
+        let hint = if self.app_state == AppState::Editor { self.editor_tool.hint() } else { self.app_state.hint() };
+        draw_scaled_text(&self.hint(hint), 10.0, screen_height() - 75.0 * scale, 36.0, scale, color);
+        draw_scaled_text(
+            &self.hint(concat!(
+                "{pan} Drag to Pan, Scroll to Zoom, Space: Pause, Backspace: Rewind, E: Edit, Esc: Menu, ",
+                "Shift+Arrows: Wind",
+            )),
+            10.0,
+            screen_height() - 45.0 * scale,
+            24.0,
+            scale,
+            color,
+        );
+        draw_scaled_text(
+            self.tr(concat!(
+                "G: Grid, S: Shake, H: Hit-Stop, I: Indices, M: Minimap, C: Checkpoint, R: Rewind, ",
+                "Scroll While Cutting: Knife Size",
+            )),
+            10.0,
+            screen_height() - 20.0 * scale,
+            24.0,
+            scale,
+            color,
+        );
+    }
+
+    /// Draws `world`, the grid behind it, and the knife's trail, as `camera` sees it.
+    fn draw_view(&self, world: &World, camera: &Camera) {
+        let scale = self.ui_scale();
+        if self.show_grid {
+            grid::draw_grid(camera, self.palette.text);
+        }
+
+        let mut renderer = MacroquadRenderer::new(&self.palette, &self.rope_materials, camera.visible_rect());
+        renderer.show_heatmap = self.show_heatmap;
+        renderer.show_glow = self.show_glow;
+        renderer.show_motion_blur = self.show_motion_blur;
+        renderer.show_field_lines = self.show_field_lines;
+        renderer.scale = scale;
+        if self.shade_cloth {
+            renderer.draw_cloth(&shading::shade(world));
+        }
+        renderer.draw_world(world);
+        renderer.flush();
+
+        if self.knife_radius > 0.0 && self.mouse.cut.down() {
+            let mouse = self.camera.mouse_world_pos();
+            draw_circle_lines(mouse.x, mouse.y, self.knife_radius, 1.5 * scale, self.palette.accent);
+        } else if self.app_state == AppState::Simulating {
+            let mouse = self.camera.mouse_world_pos();
+            match self.cursor_tool {
+                CursorTool::Off => {}
+                CursorTool::Wind => {
+                    let color = Palette::with_alpha(self.palette.accent, 0.3);
+                    draw_circle_lines(mouse.x, mouse.y, self.wind_brush.radius, scale, color);
+                }
+                CursorTool::Push => {
+                    draw_circle_lines(mouse.x, mouse.y, self.push_radius, 2.0 * scale, self.palette.accent)
+                }
+            }
+        }
+
+        #[cfg(feature = "netplay")]
+        if let Some(session) = self.session.as_ref() {
+            for cursor in session.cursors() {
+                draw_knife_trail(cursor, self.palette.accent);
+            }
+            return;
+        }
+        draw_knife_trail(&self.cursor, self.palette.accent);
+        for finger in self.fingers.down.iter().skip(1) {
+            draw_knife_trail(&finger.cursor, self.palette.accent);
+        }
+    }
+
+    fn draw_ui(&mut self) {
+        profile_scope!("draw_ui");
+        egui_macroquad::ui(|ctx| {
+            self.palette.apply_to_egui(ctx);
+
+            if self.app_state == AppState::Menu {
+                self.menu_ui(ctx);
+            }
+            self.toasts_ui(ctx);
+            self.strain_warning_ui(ctx);
+
+            if self.show_profiler {
+                self.show_profiler = profiler::window(ctx);
+            }
+            profiler::set_enabled(self.show_profiler);
+
+            let mut background_physics = self.sim_thread.is_some();
+            egui::Window::new("Settings").show(ctx, |ui| {
+                ui.checkbox(&mut self.show_grid, "Grid");
+                ui.checkbox(&mut self.camera.shake_enabled, "Camera shake");
+                ui.checkbox(&mut self.time_scale.hit_stop_enabled, "Hit-stop on cuts");
+                ui.add_enabled(!self.split_nodes, egui::Checkbox::new(&mut self.single_cut, "Single cut per swipe"));
+                ui.checkbox(&mut self.split_nodes, "Split cloth along cuts");
+                ui.checkbox(&mut self.show_heatmap, "Strain heatmap");
+                ui.checkbox(&mut self.show_glow, "Glow near failure");
+                ui.checkbox(&mut self.show_motion_blur, "Motion blur");
+                ui.checkbox(&mut self.shade_cloth, "Shaded cloth");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.strain_warnings, "Warn near breaking");
+                    ui.add_enabled(
+                        self.strain_warnings,
+                        egui::Slider::new(&mut self.strain_alert.fraction, 0.1..=1.0).text("of the way"),
+                    );
+                });
+
+                let materials = &mut self.rope_materials;
+                egui::ComboBox::from_label("Rope shader")
+                    .selected_text(materials.selected_name().to_owned())
+                    .show_ui(ui, |ui| {
+                        let names: Vec<String> = materials.names().map(str::to_owned).collect();
+                        for (i, name) in names.into_iter().enumerate() {
+                            ui.selectable_value(&mut materials.selected, i, name);
+                        }
+                    });
+
+                ui.separator();
+                ui.checkbox(&mut self.audio.muted, "Mute");
+                ui.add_enabled(
+                    !self.audio.muted,
+                    egui::Slider::new(&mut self.audio.volume, 0.0..=1.0).text("Volume"),
+                );
+                ui.checkbox(&mut self.audio.creak_enabled, "Rope creak");
+
+                ui.separator();
+                ui.checkbox(&mut self.show_checksum, "Show step checksum");
+                ui.checkbox(&mut self.show_indices, "Show node indices (I)");
+                ui.checkbox(&mut self.show_minimap, "Show minimap (M)")
+                    .on_hover_text("Shown in the corner while part of the scene is off screen");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.adaptive_timestep, "Adaptive timestep");
+                    // the background thread keeps its count to itself
+                    if self.adaptive_timestep && self.sim_thread.is_none() {
+                        ui.label(format!("{} substeps", self.substeps.count()));
+                    }
+                });
+                ui.checkbox(&mut self.level_of_detail, "Level of detail")
+                    .on_hover_text("Fewer solver iterations for links far from the cursor, for scenes full of strands");
+                if self.level_of_detail {
+                    ui.add(egui::Slider::new(&mut self.lod.radius, 50.0..=1000.0).text("Detail radius"));
+                    let iterations = self.world.params.solver_iterations.max(1);
+                    ui.add(egui::Slider::new(&mut self.lod.far_iterations, 1..=iterations).text("Far iterations"));
+                }
+                ui.checkbox(&mut self.reanchor, "Move pins with the window")
+                    .on_hover_text("Keeps pinned nodes and the ground in place in the window when it's resized");
+                ui.horizontal(|ui| {
+                    let mut follow = self.ui_scale.is_none();
+                    if ui.checkbox(&mut follow, "Scale with the display").changed() {
+                        self.ui_scale = (!follow).then(ui_scale::detected);
+                    }
+                    let mut scale = self.ui_scale();
+                    let slider = egui::Slider::new(&mut scale, MIN_UI_SCALE..=MAX_UI_SCALE).text("Drawing scale");
+                    if ui.add_enabled(!follow, slider).changed() {
+                        self.ui_scale = Some(scale);
+                    }
+                })
+                .response
+                .on_hover_text("Node radii, line widths, and text, which high-DPI displays otherwise shrink");
+                let locales = &mut self.locales;
+                egui::ComboBox::from_label("Language")
+                    .selected_text(locales.current().name.clone())
+                    .show_ui(ui, |ui| {
+                        for (i, locale) in locales.locales.iter().enumerate() {
+                            ui.selectable_value(&mut locales.selected, i, &locale.name);
+                        }
+                    })
+                    .response
+                    .on_hover_text("For hints, the tutorial, and captions; add more as locales/<code>.toml");
+                ui.checkbox(&mut self.tool_cursors, "Tool cursors")
+                    .on_hover_text("A blade, fan, hand, or pin in place of the arrow, for what the mouse will do");
+                ui.collapsing("Mouse buttons", |ui| self.mouse_ui(ui));
+                let spread = egui::Slider::new(&mut self.link_variance, 0.0..=variance::MAX_SPREAD);
+                ui.add(spread.text("Link strength variance"))
+                    .on_hover_text("Scatters how far each link stretches before it snaps, from the next scene built");
+                ui.add_enabled(
+                    self.gpu_solver.is_some() && self.sim_thread.is_none(),
+                    egui::Checkbox::new(&mut self.gpu_solve, "Solve on the GPU (experimental)"),
+                )
+                .on_hover_text("For huge cloth; scenes with more than nodes, links, and ground stay on the CPU")
+                .on_disabled_hover_text(match self.gpu_solver {
+                    Some(_) => "Not with physics on a background thread",
+                    None => "This GPU can't render into float textures",
+                });
+                if profiler::AVAILABLE {
+                    ui.checkbox(&mut self.show_profiler, "Profiler");
+                }
+                if frame_budget::AVAILABLE {
+                    ui.checkbox(&mut self.budget_warnings, "Warn when a phase runs over budget")
+                        .on_hover_text("Flags forces, integration, collisions, solving, or drawing taking too long");
+                }
+                // there are no threads in the browser
+                if cfg!(not(target_arch = "wasm32")) {
+                    ui.add_enabled(
+                        self.recorder.is_none()
+                            && self.player.is_none()
+                            && !self.in_session()
+                            && self.comparison.is_none(),
+                        egui::Checkbox::new(&mut background_physics, "Physics on a background thread"),
+                    );
+                }
+            });
+
+            egui::Window::new("Cursor")
+                .default_pos((10.0, 380.0))
+                .show(ctx, |ui| self.cursor_ui(ui));
+
+            if self.app_state == AppState::Editor {
+                egui::Window::new("Tools")
+                    .default_pos((10.0, 480.0))
+                    .show(ctx, |ui| self.tools_ui(ui));
+            }
+
+            self.winch_button = 0.0;
+            if !self.world.winches.is_empty() {
+                egui::Window::new("Winch")
+                    .default_pos((10.0, 600.0))
+                    .show(ctx, |ui| self.winch_ui(ui));
+            }
+
+            self.dispense_button = false;
+            if !self.world.dispensers.is_empty() {
+                egui::Window::new("Dispenser")
+                    .default_pos((10.0, 680.0))
+                    .show(ctx, |ui| self.dispenser_ui(ui));
+            }
+
+            if self.app_state == AppState::Editor
+                && self.entities.selected::<Inspected>().is_some_and(|i| i < self.world.arena.len())
+            {
+                egui::Window::new("Node")
+                    .default_pos((10.0, 560.0))
+                    .show(ctx, |ui| self.node_ui(ui));
+            }
+
+            egui::Window::new("Weather")
+                .default_pos((10.0, 460.0))
+                .show(ctx, |ui| self.weather_ui(ui));
+
+            egui::Window::new("Sand")
+                .default_pos((10.0, 500.0))
+                .show(ctx, |ui| self.sand_ui(ui));
+
+            egui::Window::new("Edges")
+                .default_pos((10.0, 420.0))
+                .show(ctx, |ui| self.edges_ui(ui));
+
+            if self.world.anchors.iter().any(|anchor| matches!(anchor.path, AnchorPath::Oscillate { .. })) {
+                egui::Window::new("Drive")
+                    .default_pos((10.0, 540.0))
+                    .show(ctx, |ui| self.drive_ui(ui));
+            }
+
+            if !self.world.muscles.is_empty() {
+                egui::Window::new("Muscles")
+                    .default_pos((10.0, 2040.0))
+                    .show(ctx, |ui| self.muscles_ui(ui));
+            }
+
+            if !self.world.groups.is_empty() {
+                egui::Window::new("Groups")
+                    .default_pos((10.0, 2100.0))
+                    .show(ctx, |ui| self.groups_ui(ui));
+            }
+
+            egui::Window::new("Replay")
+                .default_pos((10.0, 600.0))
+                .show(ctx, |ui| self.replay_ui(ui));
+
+            egui::Window::new("Trajectories")
+                .default_pos((10.0, 720.0))
+                .show(ctx, |ui| self.trajectory_ui(ui));
+
+            egui::Window::new("Export")
+                .default_pos((10.0, 840.0))
+                .show(ctx, |ui| self.export_ui(ui));
+
+            egui::Window::new("Compare")
+                .default_pos((10.0, 960.0))
+                .show(ctx, |ui| self.comparison_ui(ui));
+
+            egui::Window::new("Stability")
+                .default_pos((10.0, 1080.0))
+                .show(ctx, |ui| self.stability_ui(ui));
+
+            egui::Window::new("Convergence")
+                .default_pos((10.0, 1200.0))
+                .show(ctx, |ui| self.convergence_ui(ui));
+
+            egui::Window::new("Momentum")
+                .default_pos((10.0, 1320.0))
+                .show(ctx, |ui| self.momentum_ui(ui));
+
+            egui::Window::new("Bridge")
+                .default_pos((10.0, 1560.0))
+                .show(ctx, |ui| self.bridge_ui(ui));
+
+            egui::Window::new("Puzzles")
+                .default_pos((10.0, 1800.0))
+                .show(ctx, |ui| self.puzzle_ui(ui));
+
+            egui::Window::new("Tutorial")
+                .default_pos((10.0, 1920.0))
+                .show(ctx, |ui| self.tutorial_ui(ui));
+
+            egui::Window::new("Challenge")
+                .default_pos((10.0, 2160.0))
+                .show(ctx, |ui| self.challenge_ui(ui));
+
+            // no files to save to in the browser
+            if cfg!(not(target_arch = "wasm32")) {
+                egui::Window::new("Saves")
+                    .default_pos((10.0, 2280.0))
+                    .show(ctx, |ui| self.saves_ui(ui));
+            }
+
+            egui::Window::new("Layers")
+                .default_pos((10.0, 2400.0))
+                .show(ctx, |ui| self.layers_ui(ui));
+
+            egui::Window::new("Timeline")
+                .default_pos((10.0, 2520.0))
+                .show(ctx, |ui| self.timeline_ui(ui));
+
+            self.entities.select::<Hovered>(None);
+            egui::Window::new("Graph")
+                .default_pos((10.0, 2640.0))
+                .show(ctx, |ui| self.graph_ui(ui));
+
+            egui::Window::new("Views")
+                .default_pos((10.0, 2760.0))
+                .show(ctx, |ui| self.viewports_ui(ui));
+
+            egui::Window::new("Statics")
+                .default_pos((10.0, 1680.0))
+                .show(ctx, |ui| self.statics_ui(ui));
+
+            if self.analytic.is_some() {
+                egui::Window::new("Textbook")
+                    .default_pos((10.0, 1440.0))
+                    .show(ctx, |ui| self.analytic_ui(ui));
+            }
+
+            egui::Window::new("Appearance")
+                .default_pos((10.0, 330.0))
+                .show(ctx, |ui| {
+                    let preset_name = palette::PRESETS
+                        .iter()
+                        .find(|(_, preset)| *preset == self.palette)
+                        .map_or("Custom", |(name, _)| name);
+                    egui::ComboBox::from_label("Palette")
+                        .selected_text(preset_name)
+                        .show_ui(ui, |ui| {
+                            for (name, preset) in palette::PRESETS {
+                                ui.selectable_value(&mut self.palette, preset, name);
+                            }
+                        });
+
+                    ui.separator();
+                    self.palette.edit(ui);
+                });
+
+            let over_ui = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+            self.over_minimap = !over_ui && self.minimap().is_some_and(|map| map.contains(mouse_position().into()));
+            // the extra views down the right only show the world, so the main view's tools stop at their edge
+            let off_view = self.camera.area.is_some_and(|area| !area.contains(mouse_position().into()));
+            self.ui_wants_pointer = over_ui || self.over_minimap || off_view;
+
+            if background_physics != self.sim_thread.is_some() {
+                self.set_background_physics(background_physics);
+            }
+        });
+
+        egui_macroquad::draw();
+
+        // wait for drags to finish so a color picker doesn't rewrite the file every frame
+        let settings = self.settings();
+        if settings != self.saved_settings && !is_mouse_button_down(MouseButton::Left) {
+            // remember the attempt either way, so a failing save is only reported once per change
+            if let Err(err) = settings.save() {
+                self.report(err);
+            }
+            self.saved_settings = settings;
+        }
+    }
+}
+
+impl MainState {
+    fn toasts_ui(&mut self, ctx: &egui::Context) {
+        let now = get_time();
+        self.toasts.retain(|(_, shown_at)| now - shown_at < TOAST_SECONDS);
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let [r, g, b, _]: [u8; 4] = self.palette.heat(1.0).into();
+        let error_color = egui::Color32::from_rgb(r, g, b);
+        let mut dismissed = None;
+        egui::Area::new("error toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, (-10.0, -10.0))
+            .show(ctx, |ui| {
+                for (i, (message, _)) in self.toasts.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(error_color, message);
+                            if ui.small_button("x").clicked() {
+                                dismissed = Some(i);
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(i) = dismissed {
+            self.toasts.remove(i);
+        }
+    }
+
+    /// Says which link last got close to breaking, with a button that pans over to it.
+    fn strain_warning_ui(&mut self, ctx: &egui::Context) {
+        let Some(Strained { link, since }) = self.entities.strain_warning() else {
+            return;
+        };
+        if get_time() - since >= STRAIN_WARNING_SECONDS {
+            self.entities.dismiss_warning();
+            return;
+        }
+
+        let [r, g, b, _]: [u8; 4] = self.palette.heat(link.fraction).into();
+        let (mut show, mut dismissed) = (false, false);
+        egui::Area::new("strain warning")
+            .anchor(egui::Align2::CENTER_TOP, (0.0, 10.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let message = format!("A link is {:.0}% of the way to breaking", link.fraction * 100.0);
+                        ui.colored_label(egui::Color32::from_rgb(r, g, b), message);
+                        show = ui.button("Show").clicked();
+                        dismissed = ui.small_button("x").clicked();
+                    });
+                });
+            });
+
+        if show {
+            self.camera.pos = self.strained_link_pos(&link);
+        }
+        if dismissed {
+            self.entities.dismiss_warning();
+        }
+    }
+
+    /// Where the link that set off a strain warning is now, or was if it's gone.
+    fn strained_link_pos(&self, link: &StrainedLink) -> Vec2 {
+        let world = &self.world;
+        let same = |a: usize, b: usize| (a, b) == (link.a, link.b) || (b, a) == (link.a, link.b);
+        match world.constraints.iter().find(|constraint| same(constraint.a, constraint.b)) {
+            Some(constraint) => world.arena[constraint.a].pos + constraint.offset(&world.arena, &world.wrap) / 2.0,
+            None => link.pos,
+        }
+    }
+
+    fn menu_ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Cloth")
+            .anchor(egui::Align2::CENTER_CENTER, (0.0, 0.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if ui.button("Simulate").clicked() {
+                    self.set_app_state(AppState::Simulating);
+                }
+                if ui.add_enabled(self.can_edit(), egui::Button::new("Edit")).clicked() {
+                    self.set_app_state(AppState::Editor);
+                }
+                if ui.add_enabled(self.can_edit(), egui::Button::new("Reset scene")).clicked() {
+                    match self.build_scene() {
+                        Ok(world) => {
+                            self.reset_scene(world, self.seed);
+                            self.set_app_state(AppState::Simulating);
+                        }
+                        Err(err) => self.report(err),
+                    }
+                }
+            });
+    }
+
+    /// Replays step inline, so recording and playback are unavailable with background physics.
+    fn replay_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(&mut self.replay_path);
+        });
+
+        ui.horizontal(|ui| {
+            if let Some(recorder) = self.recorder.as_ref() {
+                if ui.button("Stop recording").clicked() {
+                    let length = recorder.replay.length;
+                    match save_replay(&self.replay_path, &recorder.replay) {
+                        Ok(()) => {
+                            self.replay_status = format!("Saved {} steps to {}", length, self.replay_path);
+                            self.recorder = None;
+                        }
+                        // keep recording, so the run isn't lost if another path works
+                        Err(err) => self.report(err),
+                    }
+                }
+            } else if ui
+                .add_enabled(self.sim_thread.is_none() && !self.in_session(), egui::Button::new("Record"))
+                .clicked()
+            {
+                match self.build_scene() {
+                    Ok(world) => {
+                        let view = Vec2::new(screen_width(), screen_height());
+                        let (scene, params) = (self.scene.clone(), world.params);
+                        self.player = None;
+                        self.reset_scene(world, self.seed);
+                        let mut recorder = Recorder::new(scene, view, params, self.seed);
+                        recorder.replay.variance = self.link_variance;
+                        self.recorder = Some(recorder);
+                        self.replay_status = "Recording from a fresh scene".to_string();
+                    }
+                    Err(err) => self.report(err),
+                }
+            }
+
+            let play_label = if self.player.is_some() { "Restart" } else { "Play" };
+            let can_play = self.recorder.is_none() && self.sim_thread.is_none() && !self.in_session();
+            if ui.add_enabled(can_play, egui::Button::new(play_label)).clicked() {
+                match load_replay(&self.replay_path) {
+                    Ok(replay) => self.play_replay(replay),
+                    Err(err) => self.report(err),
+                }
+            }
+
+            if self.player.is_some() && ui.button("Stop").clicked() {
+                self.player = None;
+                self.replay_status = "Playback stopped".to_string();
+            }
+        });
+
+        if let Some(player) = self.player.as_ref() {
+            let length = player.replay().length.max(1);
+            ui.add(egui::ProgressBar::new(self.step_count as f32 / length as f32));
+        }
+
+        if !self.replay_status.is_empty() {
+            ui.label(&self.replay_status);
+        }
+    }
+
+    /// Adds, removes, and points the extra views.
+    fn viewports_ui(&mut self, ui: &mut egui::Ui) {
+        if self.camera.split {
+            ui.label("Hidden while comparing side by side");
+        }
+        let mut removed = None;
+        for (i, viewport) in self.viewports.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source(("viewport", i))
+                    .selected_text(viewport.follow.name())
+                    .show_ui(ui, |ui| {
+                        for follow in Follow::ALL {
+                            ui.selectable_value(&mut viewport.follow, follow, follow.name());
+                        }
+                    });
+                let zoom = egui::Slider::new(&mut viewport.zoom, 0.5..=10.0).logarithmic(true).text("Zoom");
+                ui.add_enabled(viewport.follow != Follow::Scene, zoom);
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.viewports.remove(i);
+        }
+        if ui.add_enabled(self.viewports.len() < MAX_VIEWPORTS, egui::Button::new("Add view")).clicked() {
+            // the whole scene first, then close-ups
+            let follow = if self.viewports.is_empty() { Follow::Scene } else { Follow::Strained };
+            self.viewports.push(Viewport::new(follow));
+        }
+    }
+
+    /// The comparison steps inline next to the main world, so it's unavailable with
+    /// background physics, and in sessions, whose steps wait on the network.
+    fn comparison_ui(&mut self, ui: &mut egui::Ui) {
+        let mut comparing = self.comparison.is_some();
+        ui.add_enabled(
+            self.sim_thread.is_none() && !self.in_session(),
+            egui::Checkbox::new(&mut comparing, "Side by side"),
+        );
+        if comparing != self.comparison.is_some() {
+            self.set_comparing(comparing);
+        }
+
+        ui.label("Right side:");
+        comparison::solver_ui(&mut self.comparison_compliance, ui);
+        comparison::params_ui(&mut self.comparison_params, ui);
+        if ui.button("Copy left side's params").clicked() {
+            self.comparison_params = self.world.params;
+        }
+        if ui.add_enabled(self.comparison.is_some(), egui::Button::new("Restart both")).clicked() {
+            match self.build_scene() {
+                Ok(world) => self.reset_scene(world, self.seed),
+                Err(err) => self.report(err),
+            }
+        }
+    }
+
+    /// Analysis runs on a copy of the world, so it's always available, but applying
+    /// the result is held back wherever `sim.toml` changes would be.
+    fn stability_ui(&mut self, ui: &mut egui::Ui) {
+        let energy = self.energy();
+        ui.label(format!(
+            "Energy {:.0}: {:.0} kinetic, {:.0} gravitational, {:.0} elastic",
+            energy.total(),
+            energy.kinetic,
+            energy.gravitational,
+            energy.elastic
+        ));
+        if self.sim_thread.is_some() {
+            ui.label("The solver's losses aren't tracked on the background thread.");
+        } else {
+            let EnergyFlow { injected, dissipated } = self.energy_flow;
+            ui.label(format!("Solver this frame: +{:.1} added, -{:.1} lost", injected, dissipated));
+        }
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.button("Analyze").clicked() {
+                let report = stability::analyze(&self.world, self.seed);
+                if self.auto_apply_stability && self.can_change_params() {
+                    if let Some(trial) = report.recommended {
+                        self.set_params(trial.params);
+                    }
+                }
+                self.stability = Some(report);
+            }
+            ui.checkbox(&mut self.auto_apply_stability, "Apply automatically");
+        });
+
+        let Some(report) = self.stability.as_ref() else {
+            ui.label("Kicks copies of the scene to find a stable dt and iteration count.");
+            return;
+        };
+        ui.label(format!("Current: {}", describe_trial(&report.current)));
+        let recommended = match report.recommended {
+            Some(trial) if trial.params == self.params => {
+                ui.label("The current params are stable.");
+                return;
+            }
+            Some(trial) => trial,
+            None => {
+                ui.label("Nothing tried was stable; try lowering rigidity or gravity.");
+                return;
+            }
+        };
+        ui.label(format!(
+            "Recommended: {} iterations, dt {:.3}",
+            recommended.params.solver_iterations, recommended.params.dt
+        ));
+        ui.label(describe_trial(&recommended));
+        if ui.add_enabled(self.can_change_params(), egui::Button::new("Apply")).clicked() {
+            self.set_params(recommended.params);
+        }
+    }
+
+    /// Plots the largest and average distance of the constraints from their
+    /// rest lengths after each solver iteration, run well past the current count.
+    fn convergence_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.measure_convergence, "Measure every frame");
+        if self.measure_convergence {
+            self.convergence = convergence::measure(&self.world, CONVERGENCE_ITERATIONS);
+        }
+        if self.convergence.is_empty() {
+            ui.label("Runs the next step's solve on a copy to see where more iterations stop helping.");
+            return;
+        }
+
+        let iterations = self.world.params.solver_iterations;
+        if let Some(residual) = self.convergence.get(iterations.saturating_sub(1)) {
+            ui.label(format!(
+                "After {} iterations: {:.3} max, {:.4} mean",
+                iterations, residual.max, residual.mean
+            ));
+        }
+        let line = |value: fn(&Residual) -> f32| {
+            let points = self.convergence.iter().enumerate();
+            plot::Line::new(
+                points.map(|(i, residual)| [(i + 1) as f64, value(residual) as f64]).collect::<plot::PlotPoints>(),
+            )
+        };
+        plot::Plot::new("convergence")
+            .height(160.0)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .legend(plot::Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.line(line(|residual| residual.max).name("Max"));
+                plot_ui.line(line(|residual| residual.mean).name("Mean"));
+                plot_ui.vline(plot::VLine::new(iterations as f64).name("Current"));
+            });
+    }
+
+    /// The free nodes' momentum and center of mass, and how the momentum has
+    /// drifted over the last `MOMENTUM_FRAMES` frames.
+    fn momentum_ui(&mut self, ui: &mut egui::Ui) {
+        let momentum = Momentum::of(&self.world);
+        ui.label(format!("Momentum ({:.1}, {:.1})", momentum.linear.x, momentum.linear.y));
+        ui.label(format!("Center of mass ({:.0}, {:.0})", momentum.center_of_mass.x, momentum.center_of_mass.y));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_center_of_mass, "Show center of mass");
+            if ui.button("Clear").clicked() {
+                self.momentum_history.clear();
+            }
+        });
+
+        let line = |axis: fn(Vec2) -> f32| {
+            let points = self.momentum_history.iter().enumerate();
+            plot::Line::new(points.map(|(i, &linear)| [i as f64, axis(linear) as f64]).collect::<plot::PlotPoints>())
+        };
+        plot::Plot::new("momentum")
+            .height(160.0)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .legend(plot::Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.line(line(|linear| linear.x).name("Sideways"));
+                plot_ui.line(line(|linear| linear.y).name("Down"));
+            });
+    }
+
+    /// The bridge game: starting it, the budget, testing the bridge and the
+    /// score, and going back to building after.
+    fn bridge_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(game) = self.bridge.as_ref() else {
+            ui.label("Span a gap on a budget, then drive a heavy load across.");
+            if ui.add_enabled(self.can_edit(), egui::Button::new("Play")).clicked() {
+                self.start_bridge();
+            }
+            return;
+        };
+        let testing = game.test.is_some();
+        let cost = game.bridge.cost(if testing { &game.built } else { &self.world });
+        ui.label(format!("Spent {:.0} of {:.0}", cost, game.bridge.budget));
+        ui.label(format!(
+            "Nodes cost {:.0}, links {} per unit of length, and can't be longer than {:.0}",
+            NODE_COST, LINK_COST, MAX_LINK_LENGTH
+        ));
+        match (game.test.and_then(|test| test.outcome), game.score) {
+            (Some(outcome), Some(score)) => ui.label(format!("{}: {} points", outcome.describe(), score)),
+            _ if testing => ui.label("Testing"),
+            _ => ui.label("Click from the anchors on the banks to lay links across"),
+        };
+        if let Some(best) = game.best {
+            ui.label(format!("Best: {} points", best));
+        }
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!testing && self.can_edit(), egui::Button::new("Test")).clicked() {
+                self.test_bridge();
+            }
+            if ui.add_enabled(testing && self.can_edit(), egui::Button::new("Rebuild")).clicked() {
+                self.rebuild_bridge();
+            }
+            if ui.button("Quit").clicked() {
+                self.bridge = None;
+            }
+        });
+    }
+
+    /// The puzzle levels to pick from, and how the one being played is going.
+    fn puzzle_ui(&mut self, ui: &mut egui::Ui) {
+        // switching levels mid-puzzle is fine, but otherwise it takes the scene over like an edit
+        let can_start = self.puzzle.is_some() || self.can_edit();
+        let playing = self.puzzle.map(|(level, _)| level);
+        ui.horizontal_wrapped(|ui| {
+            for (i, (name, _)) in puzzle::LEVELS.iter().enumerate() {
+                let label = if self.solved_levels[i] { format!("{} (solved)", name) } else { name.to_string() };
+                let button = egui::SelectableLabel::new(playing == Some(i), label);
+                if ui.add_enabled(can_start, button).clicked() {
+                    self.start_level(i);
+                }
+            }
+        });
+        let Some((level, puzzle)) = self.puzzle else {
+            ui.label("Cut the ropes to land the payload in the target. No wind, no editing, just the knife.");
+            return;
+        };
+        ui.label(format!("Links cut: {}", puzzle.cuts));
+        if puzzle.landed {
+            ui.label("Landed!");
+        } else if puzzle.time_in_target > 0.0 {
+            ui.label("In the target...");
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Retry").clicked() {
+                self.start_level(level);
+            }
+            let next = level + 1;
+            if ui.add_enabled(puzzle.landed && next < puzzle::LEVELS.len(), egui::Button::new("Next")).clicked() {
+                self.start_level(next);
+            }
+            if ui.button("Quit").clicked() {
+                self.puzzle = None;
+            }
+        });
+    }
+
+    /// Loads puzzle `level` and sets it going.
+    fn start_level(&mut self, level: usize) {
+        let (mut world, puzzle) = (puzzle::LEVELS[level].1)(screen_width(), screen_height());
+        world.set_params(self.world.params);
+        self.reset_scene(world, self.seed);
+        self.puzzle = Some((level, puzzle));
+        self.set_app_state(AppState::Simulating);
+    }
+
+    /// The target, filled in once the payload has landed in it, and a ring
+    /// around the payload.
+    fn draw_puzzle(&self, puzzle: &Puzzle) {
+        let color = self.palette.accent;
+        let (min, size) = (puzzle.target.min, puzzle.target.max - puzzle.target.min);
+        if puzzle.landed {
+            draw_rectangle(min.x, min.y, size.x, size.y, Palette::with_alpha(color, 0.3));
+        }
+        draw_rectangle_lines(min.x, min.y, size.x, size.y, 2.0, color);
+        if let Some(payload) = self.world.arena.get(puzzle.payload) {
+            draw_circle_lines(payload.pos.x, payload.pos.y, puzzle::PAYLOAD_RADIUS + 4.0, 2.0, color);
+        }
+    }
+
+    /// Starts the tutorial if it's never been finished or skipped, unless
+    /// the demo was started to do something else.
+    pub fn start_tutorial_on_first_run(&mut self) {
+        if !self.tutorial_done && self.attract_after.is_none() && self.script.is_none() && self.can_edit() {
+            self.start_tutorial();
+        }
+    }
+
+    fn challenge_ui(&mut self, ui: &mut egui::Ui) {
+        let best = self.best_times.get(&self.scene.to_string()).copied();
+        let Some((challenge, to_beat)) = self.challenge.as_ref() else {
+            ui.label("Cut down everything hanging in the scene as fast as you can.");
+            if let Some(best) = best {
+                ui.label(format!("Best time on {}: {:.1}", self.scene, best));
+            }
+            if ui.add_enabled(self.can_edit(), egui::Button::new("Start")).clicked() {
+                self.start_challenge();
+            }
+            return;
+        };
+        ui.label(format!("Cuts: {}", challenge.cuts));
+        ui.label(format!("Cut down: {} of {}", challenge.severed, challenge.structures()));
+        ui.label(format!("Time: {:.1}", challenge.time));
+        if challenge.structures() == 0 {
+            ui.label("Nothing in this scene is hanging from a pin, so there's nothing to cut down.");
+        } else if challenge.finished() {
+            let beaten = to_beat.is_none_or(|to_beat| challenge.time < to_beat);
+            ui.label(if beaten { "New best time!" } else { "Done!" });
+        }
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.can_edit(), egui::Button::new("Restart")).clicked() {
+                self.start_challenge();
+            }
+            if ui.button("Stop").clicked() {
+                self.challenge = None;
+            }
+        });
+    }
+
+    /// Rebuilds the scene and starts the clock on cutting it down.
+    fn start_challenge(&mut self) {
+        match self.build_scene() {
+            Ok(world) => {
+                self.reset_scene(world, self.seed);
+                let to_beat = self.best_times.get(&self.scene.to_string()).copied();
+                self.challenge = Some((Challenge::new(&self.world), to_beat));
+                self.set_app_state(AppState::Simulating);
+            }
+            Err(err) => self.report(err),
+        }
+    }
+
+    /// Moves the challenge on by the step that just ran, with its `events`,
+    /// keeping its time if it just finished faster than ever on this scene.
+    fn follow_challenge(&mut self, events: &[SimEvent], dt: f32) {
+        let Some((challenge, _)) = self.challenge.as_mut() else {
+            return;
+        };
+        if challenge.record(&self.world, events, dt) && challenge.structures() > 0 {
+            let best = self.best_times.entry(self.scene.to_string()).or_insert(f32::INFINITY);
+            *best = best.min(challenge.time);
+        }
+    }
+
+    fn draw_challenge(&self, challenge: &Challenge, to_beat: Option<f32>) {
+        let locale = self.locales.current();
+        let (structures, time) = (challenge.structures(), format!("{:.1}", challenge.time));
+        let mut text = locale.fill(
+            "Cuts {cuts}   Cut down {severed} of {structures}   Time {time}",
+            &[("cuts", &challenge.cuts), ("severed", &challenge.severed), ("structures", &structures), ("time", &time)],
+        );
+        if let Some(to_beat) = to_beat {
+            text += &locale.fill("   Best {time}", &[("time", &format!("{:.1}", to_beat))]);
+        }
+        let color = if challenge.finished() { self.palette.accent } else { self.palette.text };
+        draw_centered(&text, 60.0 * self.ui_scale(), 28.0, self.ui_scale(), color);
+    }
+
+    fn saves_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for slot in 1..=SAVE_SLOTS {
+                let label = if save_path(Some(slot)).exists() { slot.to_string() } else { format!("{} (empty)", slot) };
+                ui.selectable_value(&mut self.save_slot, slot, label);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Save (F5)").clicked() {
+                self.save_game(Some(self.save_slot));
+            }
+            let can_load = self.can_edit() && save_path(Some(self.save_slot)).exists();
+            if ui.add_enabled(can_load, egui::Button::new("Load (F9)")).clicked() {
+                self.load_game(Some(self.save_slot));
+            }
+            let can_load = self.can_edit() && save_path(None).exists();
+            if ui.add_enabled(can_load, egui::Button::new("Load autosave")).clicked() {
+                self.load_game(None);
+            }
+        });
+        ui.checkbox(&mut self.autosave, "Autosave every minute");
+        if !self.save_status.is_empty() {
+            ui.label(&self.save_status);
+        }
+    }
+
+    /// Saves the world, its params, and the steps run so far to numbered
+    /// `slot`, or to the autosave for `None`.
+    fn save_game(&mut self, slot: Option<usize>) {
+        self.last_autosave = get_time();
+        let path = save_path(slot);
+        let text = save::to_text(&self.world, self.step_count);
+        let result = std::fs::create_dir_all(SAVES_DIR).and_then(|()| std::fs::write(&path, text));
+        match result {
+            Ok(()) => self.save_status = format!("Saved {} at step {}", path.display(), self.step_count),
+            Err(source) => {
+                self.report(SimError::Io { action: "save", path: path.display().to_string(), source });
+            }
+        }
+    }
+
+    /// Carries on from numbered `slot`, or the autosave for `None`.
+    fn load_game(&mut self, slot: Option<usize>) {
+        let path = save_path(slot);
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|source| SimError::Io { action: "read", path: path.display().to_string(), source })
+            .and_then(|text| {
+                save::from_text(&text)
+                    .map_err(|message| SimError::SceneLoad { scene: path.display().to_string(), message })
+            });
+        let (world, step) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => return self.report(err),
+        };
+        // the script set the saved world up already, so it carries on rather than starting over
+        let script = self.script.take();
+        let (rng, params) = (world.rng, world.params);
+        self.reset_scene(world, self.seed);
+        self.script = script;
+        self.world.rng = rng;
+        self.step_count = step;
+        self.params = params;
+        self.save_status = format!("Loaded {} at step {}", path.display(), step);
+    }
+
+    /// Sets up the tutorial's ropes and starts on its first lesson, with the
+    /// tools it teaches picked.
+    fn start_tutorial(&mut self) {
+        let (mut world, tutorial) = Tutorial::new(screen_width(), screen_height());
+        world.set_params(self.world.params);
+        self.reset_scene(world, self.seed);
+        self.tutorial = Some(tutorial);
+        self.cursor_tool = CursorTool::Wind;
+        self.editor_tool = EditorTool::Grab;
+        self.set_app_state(AppState::Simulating);
+    }
+
+    /// Moves the tutorial on past any lesson the world, and `events` from the
+    /// step just run, show is done, remembering once they all are.
+    fn follow_tutorial(&mut self, events: &[SimEvent]) {
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            if tutorial.record(&self.world, events) && tutorial.lesson().is_none() {
+                self.tutorial_done = true;
+            }
+        }
+    }
+
+    /// The lesson being taught, and a button to skip the rest or start over.
+    fn tutorial_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(tutorial) = self.tutorial.as_ref() else {
+            ui.label(self.tr("Try cutting, wind, grabbing, and pinning on three ropes, one at a time."));
+            if ui.add_enabled(self.can_edit(), egui::Button::new(self.tr("Start"))).clicked() {
+                self.start_tutorial();
+            }
+            return;
+        };
+        match tutorial.lesson() {
+            Some(lesson) => {
+                let (lesson_number, lessons) = (tutorial.done + 1, Lesson::ALL.len());
+                let locale = self.locales.current();
+                ui.label(locale.fill("{lesson} of {lessons}", &[("lesson", &lesson_number), ("lessons", &lessons)]));
+                ui.label(locale.tr(lesson.prompt()));
+                if ui.button(locale.tr("Skip")).clicked() {
+                    self.tutorial = None;
+                    self.tutorial_done = true;
+                }
+            }
+            None => {
+                ui.label(self.tr("That's all of them. Space lets go of anything the editor's holding still."));
+                if ui.button(self.tr("Close")).clicked() {
+                    self.tutorial = None;
+                }
+            }
+        }
+    }
+
+    /// Pulsing rings around the nodes the lesson being taught is about.
+    fn draw_tutorial(&self, tutorial: &Tutorial) {
+        let pulse = 0.5 + 0.5 * (get_time() * 4.0).sin() as f32;
+        let color = Palette::with_alpha(self.palette.accent, 0.4 + 0.6 * pulse);
+        for node in tutorial.highlighted().iter().filter_map(|&i| self.world.arena.get(i)) {
+            let scale = self.ui_scale();
+            draw_circle_lines(node.pos.x, node.pos.y, NODE_RADIUS * scale * (2.0 + pulse), 2.0 * scale, color);
+        }
+    }
+
+    /// Starts the bridge game on an empty gap, in the editor with the Build tool.
+    fn start_bridge(&mut self) {
+        let (mut world, bridge) = Bridge::level(screen_width(), screen_height());
+        world.set_params(self.world.params);
+        self.reset_scene(world, self.seed);
+        self.bridge = Some(BridgeGame { bridge, built: self.world.clone(), test: None, score: None, best: None });
+        // stress shows as color, which is half the fun
+        self.show_heatmap = true;
+        self.editor_tool = EditorTool::Build;
+        self.set_app_state(AppState::Editor);
+    }
+
+    /// Keeps the bridge as built to go back to, and sends the load across it.
+    fn test_bridge(&mut self) {
+        let Some(game) = self.bridge.as_mut() else {
+            return;
+        };
+        game.built = self.world.clone();
+        game.test = Some(game.bridge.launch(&mut self.world));
+        self.set_app_state(AppState::Simulating);
+    }
+
+    /// Goes back to the bridge as it was before the test, to work on it.
+    fn rebuild_bridge(&mut self) {
+        let Some(mut game) = self.bridge.take() else {
+            return;
+        };
+        game.test = None;
+        self.reset_scene(game.built.clone(), self.seed);
+        self.bridge = Some(game);
+        self.set_app_state(AppState::Editor);
+    }
+
+    /// Where the load starts and the line it has to get past.
+    fn draw_bridge(&self, game: &BridgeGame) {
+        let (start, finish, road) = (game.bridge.start, game.bridge.finish, game.bridge.road);
+        let color = self.palette.accent;
+        draw_line(finish, road, finish, road - 4.0 * LOAD_RADIUS, 2.0, color);
+        draw_triangle(
+            Vec2::new(finish, road - 4.0 * LOAD_RADIUS),
+            Vec2::new(finish + 2.0 * LOAD_RADIUS, road - 3.5 * LOAD_RADIUS),
+            Vec2::new(finish, road - 3.0 * LOAD_RADIUS),
+            color,
+        );
+        if game.test.is_none() {
+            draw_circle_lines(start.x, start.y, LOAD_RADIUS, 2.0, color);
+        }
+    }
+
+    /// Compares the simulated pendulum or spring with the textbook's, as
+    /// errors in its period and where it settles.
+    fn analytic_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(analytic) = self.analytic.as_ref() else {
+            return;
+        };
+        let expected = analytic.expected;
+        let error = |measured: f32, expected: f32| format!("{:+.1}%", (measured / expected - 1.0) * 100.0);
+        match analytic.system {
+            Canonical::Pendulum { length, .. } => {
+                ui.label(format!("Pendulum {:.0} long, swinging {:.1}° either way", length, self.swing_degrees()));
+            }
+            Canonical::Spring { stiffness, .. } => {
+                ui.label(format!("Spring of stiffness {}", stiffness));
+            }
+        }
+        match analytic.measured_period() {
+            Some(period) => ui.label(format!(
+                "Period: {:.2} expected, {:.2} simulated ({})",
+                expected.period(),
+                period,
+                error(period, expected.period())
+            )),
+            None => ui.label(format!("Period: {:.2} expected, timing the first swing", expected.period())),
+        };
+        match (analytic.system, analytic.measured_equilibrium()) {
+            (Canonical::Spring { .. }, Some(stretch)) => {
+                ui.label(format!(
+                    "Stretch at rest: {:.1} expected, {:.1} simulated ({})",
+                    expected.equilibrium,
+                    stretch,
+                    error(stretch, expected.equilibrium)
+                ));
+            }
+            (Canonical::Spring { .. }, None) => {
+                ui.label(format!("Stretch at rest: {:.1} expected", expected.equilibrium));
+            }
+            (Canonical::Pendulum { .. }, Some(angle)) => {
+                ui.label(format!("Centered {:.2}° off straight down", angle.to_degrees()));
+            }
+            (Canonical::Pendulum { .. }, None) => {}
+        }
+        if self.sim_thread.is_some() {
+            ui.label("The simulation isn't timed on the background thread.");
+        }
+
+        ui.separator();
+        ui.checkbox(&mut self.show_analytic, "Overlay the textbook motion");
+        if ui.button("Restart from here").clicked() {
+            self.analytic = Analytic::new(&self.world);
+        }
+    }
+
+    /// How far the textbook pendulum swings to either side now, in degrees.
+    fn swing_degrees(&self) -> f32 {
+        let Some(analytic) = self.analytic.as_ref() else {
+            return 0.0;
+        };
+        let Oscillator { start: (cos, sin), decay, .. } = analytic.expected;
+        (cos.hypot(sin) * (-decay * analytic.time).exp()).to_degrees()
+    }
+
+    /// A ghost of the bob where the textbook has it, on its own line from the
+    /// top, and for a spring a mark where it should come to rest.
+    fn draw_analytic(&self, analytic: &Analytic) {
+        let color = self.palette.accent;
+        let world = &self.world;
+        let (top, bob) = analytic.system.ends();
+        let Some(top) = world.arena.get(top).filter(|_| bob < world.arena.len()).map(|node| node.pos) else {
+            return;
+        };
+        let ghost = analytic.system.bob_pos(world, analytic.expected.at(analytic.time));
+        let scale = self.ui_scale();
+        draw_line(top.x, top.y, ghost.x, ghost.y, scale, color);
+        draw_circle_lines(ghost.x, ghost.y, NODE_RADIUS * 2.0 * scale, 2.0 * scale, color);
+        if let Canonical::Spring { .. } = analytic.system {
+            let rest = analytic.system.bob_pos(world, analytic.expected.equilibrium);
+            let reach = NODE_RADIUS * 3.0 * scale;
+            draw_line(rest.x - reach, rest.y, rest.x + reach, rest.y, 2.0 * scale, color);
+        }
+    }
+
+    /// The force each link would carry as a bar in a truss standing still
+    /// under its weight, with fixed nodes as pins, worked out every frame
+    /// while it's shown, or what each carries once the world has settled.
+    fn statics_ui(&mut self, ui: &mut egui::Ui) {
+        if self.app_state != AppState::Paused {
+            self.settled = None;
+        }
+        if self.settled.is_none() {
+            ui.checkbox(&mut self.show_statics, "Show member forces");
+            self.statics = self.show_statics.then(|| Truss::analyze(&self.world));
+        }
+        let can_settle = self.sim_thread.is_none() && !self.in_session();
+        let settle = ui.add_enabled(can_settle, egui::Button::new("Settle"));
+        if settle.on_hover_text("Damps out all motion until things stop, then pauses there").clicked() {
+            self.settled = Some(statics::settle(&mut self.world, statics::SETTLE_STEPS));
+            self.statics = Some(Truss::balance(&self.world));
+            self.show_statics = false;
+            self.set_app_state(AppState::Paused);
+        }
+        match self.settled {
+            Some(Settled { steps, still: true }) => {
+                ui.label(format!("Came to rest after {} steps. Save the scene to keep it that way.", steps));
+            }
+            Some(Settled { steps, still: false }) => {
+                ui.label(format!("Still moving after {} steps, and paused where it got to.", steps));
+            }
+            None => {}
+        }
+        let truss = match self.statics.as_ref() {
+            None => {
+                ui.label("Labels each link with what it carries at rest, in tension or compression.");
+                return;
+            }
+            Some(Err(reason)) => {
+                ui.label(reason.describe());
+                return;
+            }
+            Some(Ok(truss)) => truss,
+        };
+        let tension = truss.forces.iter().copied().fold(0.0, f32::max);
+        let compression = truss.forces.iter().copied().fold(0.0, f32::min);
+        ui.label(format!("Most tension: {:.1}", tension));
+        ui.label(format!("Most compression: {:.1}", -compression));
+        ui.label("Tension is drawn in the heatmap's hottest color and compression in its coolest.");
+    }
+
+    /// Each link over again in its statics color, stronger the more it carries.
+    fn draw_statics(&self, truss: &Truss) {
+        let largest = truss.largest();
+        for (constraint, &force) in self.world.constraints.iter().zip(&truss.forces) {
+            let (a, b) = (self.world.arena[constraint.a].pos, self.world.arena[constraint.b].pos);
+            let color = self.statics_color(force, largest);
+            let share = force.abs() / largest.max(f32::MIN_POSITIVE);
+            draw_line(a.x, a.y, b.x, b.y, 4.0, Palette::with_alpha(color, 0.3 + 0.7 * share));
+        }
+    }
+
+    /// Each link's force at its middle. Expects screen space.
+    fn draw_statics_labels(&self, truss: &Truss) {
+        let largest = truss.largest();
+        for (constraint, &force) in self.world.constraints.iter().zip(&truss.forces) {
+            let (a, b) = (self.world.arena[constraint.a].pos, self.world.arena[constraint.b].pos);
+            let middle = self.camera.world_to_screen((a + b) / 2.0);
+            let text = format!("{:.0}", force);
+            let size = measure_scaled_text(&text, 16.0, self.ui_scale());
+            let color = self.statics_color(force, largest);
+            let (x, y) = (middle.x - size.width / 2.0, middle.y + size.height / 2.0);
+            draw_scaled_text(&text, x, y, 16.0, self.ui_scale(), color);
+        }
+    }
+
+    /// A line for each measurement, with a tick across each end, and one from
+    /// where the next measurement starts to the cursor.
+    fn draw_measurements(&self, radius: f32, width: f32) {
+        let color = self.palette.accent;
+        let taking = self.measure_from.and_then(|start| start.pos(&self.world));
+        let pending = taking.map(|start| [start, self.camera.mouse_world_pos()]);
+        let lines = self.measurements.iter().filter_map(|m| m.points(&self.world)).chain(pending);
+        for [a, b] in lines {
+            draw_line(a.x, a.y, b.x, b.y, width, color);
+            let across = (b - a).perp().normalize_or_zero() * radius * 1.5;
+            for end in [a, b] {
+                let (from, to) = (end - across, end + across);
+                draw_line(from.x, from.y, to.x, to.y, width, color);
+            }
+        }
+    }
+
+    /// How long each measurement is, at its middle, and the one being taken.
+    /// Expects screen space.
+    fn draw_measurement_labels(&self) {
+        let scale = self.ui_scale();
+        let taking = self.measure_from.and_then(|start| start.pos(&self.world));
+        let pending = taking.map(|start| [start, self.camera.mouse_world_pos()]);
+        for [a, b] in self.measurements.iter().filter_map(|m| m.points(&self.world)).chain(pending) {
+            let middle = self.camera.world_to_screen((a + b) / 2.0);
+            let text = measure::label(a.distance(b), self.world.units);
+            let size = measure_scaled_text(&text, 18.0, scale);
+            let (x, y) = (middle.x - size.width / 2.0, middle.y - 6.0 * scale);
+            draw_scaled_text(&text, x, y, 18.0, scale, self.palette.accent);
+        }
+    }
+
+    /// Labels every shown node on screen with its index, above and to the right
+    /// of it, and every shown link with the nodes it joins, at its middle.
+    fn draw_indices(&self) {
+        let on_screen = |pos: Vec2| pos.x >= 0.0 && pos.y >= 0.0 && pos.x <= screen_width() && pos.y <= screen_height();
+        let scale = self.ui_scale();
+        let offset = Vec2::new(1.0, -1.0) * NODE_RADIUS * scale * self.camera.zoom;
+        let hidden = self.world.hidden();
+        for (i, node) in self.world.arena.iter().enumerate() {
+            let pos = self.camera.world_to_screen(node.pos) + offset;
+            if on_screen(pos) && !hidden[i] {
+                draw_scaled_text(&i.to_string(), pos.x, pos.y, 16.0, scale, self.palette.text);
+            }
+        }
+        for constraint in self.world.constraints.iter().filter(|c| !hidden[c.a] && !hidden[c.b]) {
+            let (a, b) = (self.world.arena[constraint.a].pos, self.world.arena[constraint.b].pos);
+            let middle = self.camera.world_to_screen((a + b) / 2.0);
+            if on_screen(middle) {
+                let text = format!("{}-{}", constraint.a, constraint.b);
+                let size = measure_scaled_text(&text, 14.0, scale);
+                let (x, y) = (middle.x - size.width / 2.0, middle.y + size.height / 2.0);
+                draw_scaled_text(&text, x, y, 14.0, scale, self.palette.accent);
+            }
+        }
+    }
+
+    /// The heatmap's hottest color for tension and its coolest for compression,
+    /// and the text color for links that carry next to nothing of the `largest`.
+    fn statics_color(&self, force: f32, largest: f32) -> Color {
+        let heatmap = self.palette.heatmap;
+        if force.abs() <= 1e-3 * largest {
+            self.palette.text
+        } else if force > 0.0 {
+            heatmap[heatmap.len() - 1]
+        } else {
+            heatmap[0]
+        }
+    }
+
+    fn tools_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            // the bridge game only builds
+            ui.set_enabled(self.bridge.is_none());
+            for tool in EditorTool::ALL {
+                ui.selectable_value(&mut self.editor_tool, tool, tool.name());
+            }
+        });
+        match self.editor_tool {
+            EditorTool::Grab | EditorTool::Pose => {}
+            EditorTool::Build if self.bridge.is_some() => {}
+            EditorTool::Build => {
+                let swatch = |material: &Material| {
+                    let [r, g, b] = material.color;
+                    egui::RichText::new(format!("■ {}", material.name)).color(egui::Color32::from_rgb(r, g, b))
+                };
+                egui::ComboBox::from_label("Material")
+                    .selected_text(swatch(&self.build_material))
+                    .show_ui(ui, |ui| {
+                        for material in MATERIALS {
+                            ui.selectable_value(&mut self.build_material, material, swatch(&material));
+                        }
+                    });
+            }
+            EditorTool::Well => {
+                ui.add(egui::Slider::new(&mut self.well_strength, 0.0..=100.0).text("Strength"));
+                ui.add(egui::Slider::new(&mut self.well_radius, 50.0..=500.0).text("Radius"));
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} wells", self.world.wells.len()));
+                    if ui.button("Remove all").clicked() {
+                        self.world.wells.clear();
+                    }
+                });
+            }
+            EditorTool::Cannon => {
+                ui.add(egui::Slider::new(&mut self.ball_mass, 1.0..=100.0).text("Ball mass"));
+                ui.add(egui::Slider::new(&mut self.ball_radius, NODE_RADIUS..=40.0).text("Ball radius"));
+            }
+            EditorTool::Measure => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} measurements", self.measurements.len()));
+                    if ui.button("Remove all").clicked() {
+                        self.measurements.clear();
+                    }
+                });
+            }
+            EditorTool::Sink => {
+                ui.add(egui::Slider::new(&mut self.sink_strength, 0.0..=100.0).text("Strength"));
+                ui.add(egui::Slider::new(&mut self.sink_radius, 5.0..=100.0).text("Radius"));
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} sinks", self.world.sinks.len()));
+                    if ui.button("Remove all").clicked() {
+                        self.world.sinks.clear();
+                    }
+                });
+            }
+        }
+    }
+
+    /// Picks what the cursor does while the simulation runs, with sliders for the wind brush and
+    /// the push tool's size.
+    fn cursor_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for tool in CursorTool::ALL {
+                ui.selectable_value(&mut self.cursor_tool, tool, tool.name());
+            }
+        });
+        match self.cursor_tool {
+            CursorTool::Off => {}
+            CursorTool::Wind => {
+                let brush = &mut self.wind_brush;
+                ui.add(egui::Slider::new(&mut brush.radius, 10.0..=300.0).text("Radius"));
+                ui.add(egui::Slider::new(&mut brush.strength, 0.0..=200.0).text("Strength"));
+                ui.add(egui::Slider::new(&mut brush.falloff, 0.0..=1.0).text("Falloff"));
+            }
+            CursorTool::Push => {
+                ui.add(egui::Slider::new(&mut self.push_radius, NODE_RADIUS..=150.0).text("Radius"));
+            }
+        }
+    }
+
+    /// Buttons that run the winches while held, as the arrow keys do.
+    fn winch_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Reel in").is_pointer_button_down_on() {
+                self.winch_button = -1.0;
+            }
+            if ui.button("Pay out").is_pointer_button_down_on() {
+                self.winch_button = 1.0;
+            }
+        });
+        ui.label("Or hold Up and Down.");
+    }
+
+    /// A button that feeds rope while held, as F does, and a slider for how fast,
+    /// which sets every dispenser's speed.
+    fn dispenser_ui(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Feed").is_pointer_button_down_on() {
+            self.dispense_button = true;
+        }
+        ui.label("Or hold F.");
+
+        let can_edit = self.can_edit();
+        let mut speed = self.world.dispensers[0].speed;
+        let slider = ui.add_enabled(can_edit, egui::Slider::new(&mut speed, 1.0..=100.0).text("Speed"));
+        if slider.changed() {
+            for dispenser in self.world.dispensers.iter_mut() {
+                dispenser.speed = speed;
+            }
+        }
+    }
+
+    /// Starts and stops the rain and the moving air, both across the view, and
+    /// sets how hard it rains and how the air drags and settles. Water is
+    /// poured in blobs from the top of the view.
+    fn weather_ui(&mut self, ui: &mut egui::Ui) {
+        let view = self.camera.visible_rect();
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            let mut raining = self.world.rain.is_some();
+            if ui.checkbox(&mut raining, "Rain").changed() {
+                self.world.rain = raining.then(|| Rain::new(view.left(), view.right(), view.top()));
+            }
+            if let Some(rain) = self.world.rain.as_mut() {
+                ui.add(egui::Slider::new(&mut rain.rate, 1.0..=200.0).logarithmic(true).text("Drops per unit time"));
+                ui.add(egui::Slider::new(&mut rain.drop_mass, 0.0..=0.2).text("Drop mass"));
+                ui.add(egui::Slider::new(&mut rain.soak, 0.0..=1.0).text("Soak"));
+            }
+
+            let mut air = self.world.fluid.is_some();
+            let toggle = ui.checkbox(&mut air, "Air").on_hover_text("Wind strokes stir the air, which drags the nodes");
+            if toggle.changed() {
+                let (min, max) = (Vec2::new(view.left(), view.top()), Vec2::new(view.right(), view.bottom()));
+                self.world.fluid = air.then(|| Fluid::covering(min, max, AIR_CELL));
+            }
+            if let Some(fluid) = self.world.fluid.as_mut() {
+                ui.add(egui::Slider::new(&mut fluid.drag, 0.0..=2.0).text("Air drag"));
+                ui.add(egui::Slider::new(&mut fluid.fade, 0.0..=1.0).text("Air fade"));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Pour water").clicked() {
+                    let spout = Vec2::new(view.center().x, view.top() + view.h * 0.15);
+                    self.world.water.get_or_insert_with(Water::default).pour(spout, POUR_COUNT);
+                }
+                if ui.add_enabled(self.world.water.is_some(), egui::Button::new("Drain")).clicked() {
+                    self.world.water = None;
+                }
+            });
+            if let Some(water) = self.world.water.as_mut() {
+                ui.add(egui::Slider::new(&mut water.viscosity, 0.0..=1.0).text("Viscosity"));
+                ui.add(egui::Slider::new(&mut water.droplet_mass, 0.01..=0.5).logarithmic(true).text("Droplet mass"));
+            }
+        });
+        let wettest = self.world.arena.iter().map(|node| node.wetness).fold(0.0, f32::max);
+        if wettest > 0.0 {
+            ui.label(format!("Wettest node holds {:.2} of water", wettest));
+        }
+    }
+
+    /// Pours sand from the top of the view, and shows how much of it has come to rest.
+    fn sand_ui(&mut self, ui: &mut egui::Ui) {
+        let view = self.camera.visible_rect();
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            if ui.button("Pour sand").clicked() {
+                let spout = Vec2::new(view.center().x, view.top() + view.h * 0.1);
+                let sand = self.world.sand.get_or_insert_with(|| Sand::new(spout));
+                sand.spout = spout;
+                sand.pouring += POUR_GRAINS;
+            }
+            if let Some(sand) = self.world.sand.as_mut() {
+                ui.add(egui::Slider::new(&mut sand.rate, 5.0..=200.0).logarithmic(true).text("Grains per unit time"));
+            }
+        });
+        if let Some(sand) = self.world.sand.as_ref() {
+            let asleep = sand.grains.iter().filter(|&&grain| self.world.arena[grain].asleep).count();
+            ui.label(format!("{} grains, {} asleep, {} still to pour", sand.grains.len(), asleep, sand.pouring));
+        }
+    }
+
+    /// Switches for each wall and how bouncy they are, for wrapping around the
+    /// edges, and how much friction there is against them and everything else.
+    /// Walls or wrapping switched on when none were go round the window, with
+    /// the ground along the bottom.
+    /// The clicked node's settings.
+    fn node_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(i) = self.entities.selected::<Inspected>() else {
+            return;
+        };
+        let mut fixed = self.world.arena[i].fixed;
+        ui.label(format!("Node {}", i));
+        let (units, node) = (self.world.units.unwrap_or_default(), &self.world.arena[i]);
+        let speed = units.meters_per_second(node.vel.length());
+        let height = units.meters(self.world.ground_y - node.pos.y);
+        ui.label(format!("Moving at {:.2} m/s, {:.2} m off the ground", speed, height));
+        if ui.checkbox(&mut fixed, "Pinned").changed() {
+            self.toggle_pin(i);
+        }
+        let node = &mut self.world.arena[i];
+        ui.add(egui::Slider::new(&mut node.mass, 0.1..=100.0).logarithmic(true).text("Mass"));
+        ui.add(egui::Slider::new(&mut node.restitution, 0.0..=1.0).text("Bounciness"));
+        let gravity = ui.add(egui::Slider::new(&mut node.gravity_scale, -2.0..=2.0).text("Gravity"));
+        gravity.on_hover_text("How much gravity pulls on it, negative to float up like a balloon");
+        let charge = ui.add(egui::Slider::new(&mut node.charge, -5.0..=5.0).text("Charge"));
+        charge.on_hover_text("Pushes away nodes charged the same way and pulls in opposite ones");
+        ui.checkbox(&mut node.magnetic, "Magnetic");
+        ui.horizontal(|ui| {
+            let layer = &mut self.world.arena[i].layer;
+            ui.add(egui::Slider::new(layer, 0..=LAYERS as u8 - 1).text("Layer"));
+            let layer = *layer;
+            let whole = ui.button("Whole piece").on_hover_text("Moves every node linked to this one onto its layer");
+            if whole.clicked() {
+                for j in self.world.piece(i) {
+                    self.world.arena[j].layer = layer;
+                }
+            }
+        });
+        let fringe = ui.add_enabled(self.can_edit(), egui::Button::new("Add fringe"));
+        if fringe.on_hover_text("Hangs short strands from the bottom edge of this node's piece").clicked() {
+            self.add_fringe(i);
+        }
+    }
+
+    /// Hangs a strand from every node along the bottom edge of the piece `i` is
+    /// in, within half a rest length of its lowest node.
+    fn add_fringe(&mut self, i: usize) {
+        let piece = self.world.piece(i);
+        let arena = &self.world.arena;
+        let bottom = piece.iter().map(|&j| arena[j].pos.y).fold(f32::NEG_INFINITY, f32::max);
+        let edge: Vec<usize> =
+            piece.into_iter().filter(|&j| arena[j].pos.y > bottom - clothsim::TARGET_DIST / 2.0).collect();
+        self.world.add_fringe(&edge, FRINGE_STRAND_NODES, FRINGE_LINK_LENGTH);
+    }
+
+    /// The links drawn as an abstract graph, apart from where the nodes are,
+    /// colored by strain, for making sense of generated or imported scenes.
+    /// The node under the pointer is ringed in the main view, and clicking
+    /// one picks it for the editor's Node window.
+    fn graph_ui(&mut self, ui: &mut egui::Ui) {
+        // laying out big scenes takes a while, so it's only done while asked for
+        let mut shown = self.graph.is_some();
+        if ui.checkbox(&mut shown, "Show the graph").changed() {
+            self.graph = shown.then(|| GraphLayout::new(&self.world));
+        }
+        let Some(layout) = self.graph.as_mut() else {
+            return;
+        };
+        layout.sync(&self.world);
+        for _ in 0..GRAPH_STEPS_PER_FRAME {
+            layout.step();
+        }
+
+        let (response, painter) = ui.allocate_painter(egui::vec2(GRAPH_SIZE, GRAPH_SIZE), egui::Sense::click());
+        let rect = response.rect.shrink(GRAPH_PICK_RADIUS);
+        let start = (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+        let (min, max) = layout.positions.iter().fold(start, |(min, max), &pos| (min.min(pos), max.max(pos)));
+        let scale = rect.width() / (max - min).max_element().max(1e-6);
+        let points: Vec<egui::Pos2> = layout
+            .positions
+            .iter()
+            .map(|&pos| rect.min + egui::vec2(pos.x - min.x, pos.y - min.y) * scale)
+            .collect();
+        let color = |color: Color| {
+            let [r, g, b, _]: [u8; 4] = color.into();
+            egui::Color32::from_rgb(r, g, b)
+        };
+
+        let (arena, wrap) = (&self.world.arena, &self.world.wrap);
+        for constraint in self.world.constraints.iter() {
+            let stroke = egui::Stroke::new(1.5, color(self.palette.heat(constraint.break_fraction(arena, wrap))));
+            painter.line_segment([points[constraint.a], points[constraint.b]], stroke);
+        }
+        for (point, node) in points.iter().zip(arena) {
+            let fill = if node.fixed { self.palette.fixed_node } else { self.palette.node };
+            painter.circle_filled(*point, 3.0, color(fill));
+        }
+        let hovered = response.hover_pos().and_then(|pointer| {
+            let distance = |i: &usize| points[*i].distance(pointer);
+            (0..points.len())
+                .filter(|i| distance(i) <= GRAPH_PICK_RADIUS)
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        });
+        let accent = egui::Stroke::new(2.0, color(self.palette.accent));
+        let inspected = self.entities.selected::<Inspected>();
+        for i in inspected.iter().chain(hovered.iter()).filter(|&&i| i < points.len()) {
+            painter.circle_stroke(points[*i], 6.0, accent);
+        }
+        if response.clicked() && hovered.is_some() {
+            self.entities.select::<Inspected>(hovered);
+        }
+        self.entities.select::<Hovered>(hovered);
+        ui.label(match hovered {
+            Some(i) => format!("Node {}", i),
+            None => self.entities.summary(),
+        });
+    }
+
+    /// Each layer's node count, whether it's shown, and which layers it collides with.
+    fn layers_ui(&mut self, ui: &mut egui::Ui) {
+        let mut counts = [0; LAYERS];
+        for node in self.world.arena.iter() {
+            counts[node.layer as usize] += 1;
+        }
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            egui::Grid::new("layers").show(ui, |ui| {
+                ui.label("Layer");
+                ui.label("Nodes");
+                ui.label("Shown");
+                ui.label("Collides with");
+                ui.label("Obstacles");
+                ui.label("Depth");
+                ui.end_row();
+                let layers = &mut self.world.layers;
+                for a in 0..LAYERS as u8 {
+                    ui.label(a.to_string());
+                    ui.label(counts[a as usize].to_string());
+                    ui.checkbox(&mut layers.0[a as usize].visible, "");
+                    ui.horizontal(|ui| {
+                        for b in 0..=a {
+                            let mut collide = layers.collide(a, b);
+                            if ui.checkbox(&mut collide, b.to_string()).changed() {
+                                layers.set_collide(a, b, collide);
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let obstacles = &mut layers.0[a as usize].obstacles;
+                        for (bit, name) in OBSTACLES {
+                            let mut hits = *obstacles & bit != 0;
+                            if ui.checkbox(&mut hits, name).changed() {
+                                *obstacles ^= bit;
+                            }
+                        }
+                    });
+                    ui.add(egui::Slider::new(&mut layers.0[a as usize].depth, MIN_DEPTH..=MAX_LAYER_DEPTH));
+                    ui.end_row();
+                }
+            });
+        });
+        ui.label("Pick a node in the editor to move it, or the whole piece it's part of, to another layer.");
+        ui.label("Layers farther back are drawn smaller and pan slower, but stay where they really are.");
+    }
+
+    fn edges_ui(&mut self, ui: &mut egui::Ui) {
+        let (mut walls, mut wrap) = (self.world.walls, self.world.wrap);
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            ui.label("Walls");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut walls.left, "Left");
+                ui.checkbox(&mut walls.right, "Right");
+                ui.checkbox(&mut walls.top, "Top");
+                ui.checkbox(&mut walls.bottom, "Bottom");
+            });
+            ui.add(egui::Slider::new(&mut walls.restitution, 0.0..=1.0).text("Bounciness"));
+
+            ui.separator();
+            ui.label("Wrap around");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut wrap.x, "Sideways");
+                ui.checkbox(&mut wrap.y, "Top to bottom");
+            });
+
+            ui.separator();
+            let friction = &mut self.world.friction;
+            ui.add(egui::Slider::new(&mut friction.static_friction, 0.0..=2.0).text("Static friction"));
+            ui.add(egui::Slider::new(&mut friction.dynamic_friction, 0.0..=2.0).text("Sliding friction"));
+
+            ui.separator();
+            let mut contacts = self.world.contacts.is_some();
+            if ui.checkbox(&mut contacts, "Nodes collide with each other").changed() {
+                self.world.contacts = contacts.then(Contacts::default);
+            }
+            if let Some(contacts) = self.world.contacts.as_mut() {
+                ui.add(egui::Slider::new(&mut contacts.stacking, 0.0..=0.5).text("Stacking"));
+            }
+
+            ui.separator();
+            let mut charged = self.world.electrostatics.is_some();
+            if ui.checkbox(&mut charged, "Charged nodes push and pull").changed() {
+                self.world.electrostatics = charged.then(Electrostatics::default);
+            }
+            if let Some(electrostatics) = self.world.electrostatics.as_mut() {
+                let strength = &mut electrostatics.strength;
+                ui.add(egui::Slider::new(strength, 1000.0..=500_000.0).logarithmic(true).text("Strength"));
+                ui.add(egui::Slider::new(&mut electrostatics.reach, 50.0..=500.0).text("Reach"));
+            }
+
+            ui.separator();
+            self.magnet_ui(ui);
+
+            ui.separator();
+            self.force_ui(ui);
+
+            ui.separator();
+            let mut snapping = self.world.break_rate.is_some();
+            if ui.checkbox(&mut snapping, "Links snap when jerked").changed() {
+                self.world.break_rate = snapping.then_some(clothsim::BREAK_RATE);
+            }
+            if let Some(rate) = self.world.break_rate.as_mut() {
+                ui.add(egui::Slider::new(rate, 0.5..=10.0).text("Snap rate"));
+            }
+            let mut shattering = self.world.shatter.is_some();
+            if ui.checkbox(&mut shattering, "Nodes shatter where links snap").changed() {
+                self.world.shatter = shattering.then(Shatter::default);
+            }
+            if let Some(shatter) = self.world.shatter.as_mut() {
+                ui.add(egui::Slider::new(&mut shatter.fragments, 2..=3).text("Fragments"));
+            }
+            let mut fraying = self.world.fray.is_some();
+            if ui.checkbox(&mut fraying, "Fray ends where links go").changed() {
+                self.world.fray = fraying.then(Fray::default);
+            }
+            if let Some(fray) = self.world.fray.as_mut() {
+                ui.add(egui::Slider::new(&mut fray.recoil, 0.0..=10.0).text("Recoil"));
+            }
+
+            ui.separator();
+            let mut coarse = self.world.multigrid.is_some();
+            if ui.checkbox(&mut coarse, "Coarse passes for long chains").changed() {
+                self.world.multigrid = coarse.then(Multigrid::default);
+            }
+            if let Some(multigrid) = self.world.multigrid.as_mut() {
+                ui.add(egui::Slider::new(&mut multigrid.levels, 1..=6).text("Levels"));
+                ui.add(egui::Slider::new(&mut multigrid.iterations, 1..=8).text("Iterations per level"));
+            }
+            egui::ComboBox::from_label("Link solve order")
+                .selected_text(self.world.solve_order.name())
+                .show_ui(ui, |ui| {
+                    for order in SolveOrder::ALL {
+                        ui.selectable_value(&mut self.world.solve_order, order, order.name());
+                    }
+                });
+
+            ui.separator();
+            let mut clearing = self.world.cleanup.is_some();
+            if ui.checkbox(&mut clearing, "Clear away lost nodes").changed() {
+                self.world.cleanup = clearing.then(Cleanup::default);
+            }
+            if let Some(cleanup) = self.world.cleanup.as_mut() {
+                // a scene file can turn either off with an infinite value, which the sliders leave alone
+                let depth = egui::Slider::new(&mut cleanup.depth, 100.0..=10_000.0).clamp_to_range(false);
+                ui.add(depth.logarithmic(true).text("Depth below ground"));
+                let delay = egui::Slider::new(&mut cleanup.delay, 1.0..=120.0).clamp_to_range(false);
+                ui.add(delay.logarithmic(true).text("Seconds without a link"));
+            }
+            let mut capped = self.world.budget.is_some();
+            let cap = ui.checkbox(&mut capped, "Cap the node count");
+            if cap.on_hover_text("For cannons, sand, and dispensers left running").changed() {
+                self.world.budget = capped.then(NodeBudget::default);
+            }
+            if let Some(budget) = self.world.budget.as_mut() {
+                ui.add(egui::Slider::new(&mut budget.max_nodes, 100..=20_000).logarithmic(true).text("Most nodes"));
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut budget.at_cap, AtCap::Recycle, "Reuse the oldest loose node");
+                    ui.radio_value(&mut budget.at_cap, AtCap::Refuse, "Add no more");
+                });
+            }
+        });
+
+        let window = (Vec2::ZERO, Vec2::new(screen_width(), self.world.ground_y));
+        if walls != self.world.walls && !self.world.walls.any() {
+            (walls.min, walls.max) = window;
+        }
+        if wrap != self.world.wrap && !self.world.wrap.any() {
+            (wrap.min, wrap.max) = window;
+        }
+        self.world.walls = walls;
+        self.world.wrap = wrap;
+    }
+
+    /// The field pushing on magnetic nodes: uniform, or a dipole in the middle
+    /// of the screen to start with, and how strong it is and which way it points.
+    fn magnet_ui(&mut self, ui: &mut egui::Ui) {
+        let mut on = self.world.magnet.is_some();
+        if ui.checkbox(&mut on, "Magnetic field").changed() {
+            self.world.magnet = on.then(Magnet::default);
+        }
+        let Some(magnet) = self.world.magnet.as_mut() else {
+            return;
+        };
+        let middle = Vec2::new(screen_width(), self.world.ground_y) / 2.0;
+        // switching kinds keeps the strength and direction
+        ui.horizontal(|ui| {
+            let is_uniform = matches!(magnet, Magnet::Uniform { .. });
+            let uniform = ui.radio(is_uniform, "Uniform").clicked();
+            let dipole = ui.radio(!is_uniform, "Dipole").clicked();
+            match *magnet {
+                Magnet::Uniform { force } if dipole => *magnet = Magnet::Dipole { center: middle, moment: force },
+                Magnet::Dipole { moment, .. } if uniform => *magnet = Magnet::Uniform { force: moment },
+                _ => {}
+            }
+        });
+        let vector = match magnet {
+            Magnet::Uniform { force } => force,
+            Magnet::Dipole { moment, .. } => moment,
+        };
+        let (mut strength, mut angle) = (vector.length(), vector.y.atan2(vector.x).to_degrees());
+        let strength_changed = ui.add(egui::Slider::new(&mut strength, 0.0..=200.0).text("Strength")).changed();
+        let angle_changed = ui.add(egui::Slider::new(&mut angle, -180.0..=180.0).text("Direction")).changed();
+        if strength_changed || angle_changed {
+            *vector = Vec2::new(angle.to_radians().cos(), angle.to_radians().sin()) * strength;
+        }
+        if let Magnet::Dipole { center, .. } = magnet {
+            ui.add(egui::Slider::new(&mut center.x, 0.0..=screen_width()).text("x"));
+            ui.add(egui::Slider::new(&mut center.y, 0.0..=self.world.ground_y).text("y"));
+        }
+        ui.checkbox(&mut self.show_field_lines, "Show field lines");
+    }
+
+    /// A formula for a force on every free node, acting from when it's applied until it's stopped or fails.
+    fn force_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Custom force on each free node, in x, y, vx, vy, mass, and t");
+        let field = egui::TextEdit::singleline(&mut self.force_text).hint_text("f = (-0.2*x, 10*sin(t))");
+        let entered = ui.add(field).lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+        ui.horizontal(|ui| {
+            if ui.button("Apply").clicked() || entered {
+                match ForceExpr::parse(&self.force_text) {
+                    Ok(expr) => (self.force_expr, self.force_error) = (Some(expr), None),
+                    Err(e) => (self.force_expr, self.force_error) = (None, Some(e)),
+                }
+            }
+            if ui.add_enabled(self.force_expr.is_some(), egui::Button::new("Stop")).clicked() {
+                self.force_expr = None;
+            }
+        });
+        if let Some(error) = self.force_error.as_ref() {
+            let [r, g, b, _]: [u8; 4] = self.palette.heat(1.0).into();
+            ui.colored_label(egui::Color32::from_rgb(r, g, b), error);
+        }
+    }
+
+    /// Sliders for the oscillating anchors, which all share the first one's settings.
+    fn drive_ui(&mut self, ui: &mut egui::Ui) {
+        let Some((mut amplitude, mut frequency)) = self.world.anchors.iter().find_map(|anchor| match anchor.path {
+            AnchorPath::Oscillate { amplitude, .. } => Some((amplitude.length(), anchor.speed)),
+            _ => None,
+        }) else {
+            return;
+        };
+
+        // changing the drive changes the world, like the editor does
+        let changed = ui
+            .add_enabled_ui(self.can_edit(), |ui| {
+                let amplitude = ui.add(egui::Slider::new(&mut amplitude, 0.0..=100.0).text("Amplitude"));
+                let frequency = egui::Slider::new(&mut frequency, 0.0..=0.2).logarithmic(true).text("Frequency");
+                let frequency = ui.add(frequency);
+                amplitude.changed() || frequency.changed()
+            })
+            .inner;
+        ui.label("Cycles per unit of simulated time. Lower drag in sim.toml for sharper resonance.");
+        if !changed {
+            return;
+        }
+
+        for anchor in self.world.anchors.iter_mut() {
+            if let AnchorPath::Oscillate { amplitude: old, .. } = &mut anchor.path {
+                *old = old.try_normalize().unwrap_or(Vec2::X) * amplitude;
+                anchor.speed = frequency;
+            }
+        }
+    }
+
+    /// How far the muscles have crawled the scene, and each one's swing, which
+    /// can be changed like the editor changes the world.
+    fn muscles_ui(&mut self, ui: &mut egui::Ui) {
+        let crawled = Momentum::of(&self.world).center_of_mass.x - self.crawl_start;
+        ui.label(format!("Crawled {:.0} to the {}", crawled.abs(), if crawled < 0.0 { "left" } else { "right" }));
+        let count = self.world.muscles.len();
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            for (i, muscle) in self.world.muscles.iter_mut().enumerate() {
+                ui.label(format!("Nodes {} to {}", muscle.a, muscle.b));
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut muscle.amplitude, 0.0..=0.5).text("Amplitude"));
+                    ui.add(egui::Slider::new(&mut muscle.frequency, 0.0..=0.5).logarithmic(true).text("Frequency"));
+                    ui.add(egui::Slider::new(&mut muscle.phase, 0.0..=1.0).text("Phase"));
+                });
+                if i + 1 < count {
+                    ui.separator();
+                }
+            }
+            // swapping which muscles lead and which follow runs the gait backwards
+            if ui.button("Reverse").clicked() {
+                for muscle in self.world.muscles.iter_mut() {
+                    muscle.phase = (1.0 - muscle.phase) % 1.0;
+                }
+            }
+        });
+        ui.separator();
+        self.evolution_ui(ui);
+    }
+
+    fn groups_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(self.can_edit(), |ui| {
+            ui.add(egui::Slider::new(&mut self.group_stiffness, 0.5..=50.0).logarithmic(true).text("Springiness"));
+            for i in 0..self.world.groups.len() {
+                ui.separator();
+                let group = &self.world.groups[i];
+                let links = group.constraints(&self.world).len() + group.bands(&self.world).len();
+                ui.label(format!("{}: {} links", group.name, links));
+                ui.horizontal(|ui| {
+                    let group = &mut self.world.groups[i];
+                    let mut colored = group.color.is_some();
+                    if ui.checkbox(&mut colored, "Color").changed() {
+                        group.color = colored.then_some(GROUP_COLOR);
+                    }
+                    if let Some(color) = group.color.as_mut() {
+                        ui.color_edit_button_srgb(color);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Rigid").clicked() {
+                        group::set_stiffness(&mut self.world, i, Links::Rigid);
+                    }
+                    if ui.button("Springy").clicked() {
+                        group::set_stiffness(&mut self.world, i, Links::Spring(self.group_stiffness));
+                    }
+                    if ui.button("Break all").clicked() {
+                        group::break_all(&mut self.world, i);
+                    }
+                    if self.world.groups[i].is_frozen() {
+                        if ui.button("Thaw").clicked() {
+                            group::thaw(&mut self.world, i);
+                        }
+                    } else if ui.button("Freeze").clicked() {
+                        group::freeze(&mut self.world, i);
+                    }
+                });
+            }
+        });
+    }
+
+    fn evolution_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(evolution) = self.evolution.as_ref() else {
+            ui.label("Breeds muscle settings that crawl the scene as it is now farther to the right.");
+            if ui.button("Evolve").clicked() {
+                self.evolution = Some(Evolution::new(self.world.clone(), EVOLVE_POPULATION, EVOLVE_STEPS, self.seed));
+                self.last_generation = None;
+            }
+            return;
+        };
+        let (generation, scored, size) = evolution.progress();
+        ui.label(format!("Generation {}: {} of {} walkers tried", generation + 1, scored, size));
+        if let Some(last) = self.last_generation {
+            ui.label(format!("Last generation went {:.0} at best, {:.0} on average", last.best, last.mean));
+        }
+        let best = evolution.best().map(|(genome, distance)| (genome.to_vec(), distance));
+        if let Some((_, distance)) = best {
+            ui.label(format!("Farthest so far: {:.0} in {} steps", distance, EVOLVE_STEPS));
+        }
+        ui.horizontal(|ui| {
+            // from where it was evolved, keeping the evolution going
+            if let Some((genome, _)) = best {
+                if ui.add_enabled(self.can_edit(), egui::Button::new("Watch the best")).clicked() {
+                    self.watch_best(&genome);
+                }
+            }
+            if ui.button("Stop").clicked() {
+                self.evolution = None;
+            }
+        });
+    }
+
+    /// Restarts the scene as it was evolved from with `genome`'s muscle
+    /// settings, keeping the evolution going.
+    fn watch_best(&mut self, genome: &[Gene]) {
+        let Some(evolution) = self.evolution.take() else {
+            return;
+        };
+        let mut world = evolution.body().clone();
+        evolution::apply(&mut world, genome);
+        self.reset_scene(world, self.seed);
+        self.evolution = Some(evolution);
+    }
+
+    fn trajectory_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Shift+click nodes in the editor to track them.");
+        ui.horizontal(|ui| {
+            ui.label(format!("Tracking {} nodes", self.entities.traced().len()));
+            if ui.button("Untrack all").clicked() {
+                self.entities.untrace_all();
+            }
+        });
+
+        // the background thread doesn't hand back every step
+        ui.add_enabled(
+            self.sim_thread.is_none(),
+            egui::Checkbox::new(&mut self.recording_trajectories, "Record"),
+        );
+        ui.horizontal(|ui| {
+            ui.label(format!("{} / {} steps", self.trajectories.len(), self.trajectories.capacity()));
+            if ui.button("Clear").clicked() {
+                self.trajectories.clear();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(&mut self.trajectory_path);
+        });
+        if ui.add_enabled(!self.trajectories.is_empty(), egui::Button::new("Export CSV")).clicked() {
+            if let Err(err) = save_trajectories(&self.trajectory_path, &self.trajectories) {
+                self.report(err);
+            }
+        }
+    }
+
+    fn export_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.export_name);
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("OBJ mesh").clicked() {
+                self.export(".obj", |state, out| export::write_obj(&state.world, out));
+            }
+            if ui.button("SVG frame").clicked() {
+                self.export(".svg", |state, mut out| {
+                    let mut renderer = SvgRenderer::new(&state.palette, state.camera.visible_rect());
+                    renderer.show_heatmap = state.show_heatmap;
+                    renderer.show_glow = state.show_glow;
+                    renderer.textured_ropes = state.rope_materials.selected_texture().is_some();
+                    if state.shade_cloth {
+                        renderer.draw_cloth(&shading::shade(&state.world));
+                    }
+                    renderer.draw_world(&state.world);
+                    out.write_all(renderer.finish().as_bytes())?;
+                    out.flush()
+                });
+            }
+            if ui.button("DOT graph").clicked() {
+                self.export(".dot", |state, out| {
+                    export::write_dot(&state.world, out, |strain| svg::hex(state.palette.heat(strain)))
+                });
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Every");
+            ui.add(egui::DragValue::new(&mut self.capture_every).clamp_range(1..=60));
+            ui.label("frames for");
+            ui.add(egui::DragValue::new(&mut self.capture_seconds).clamp_range(0.5..=120.0).suffix(" s"));
+        });
+        ui.checkbox(&mut self.capture_ui, "Include windows");
+        if let Some(capture) = self.capture.as_ref() {
+            let stop = ui
+                .horizontal(|ui| {
+                    ui.label(format!("Captured {} frames", capture.saved));
+                    ui.button("Stop").clicked()
+                })
+                .inner;
+            if stop {
+                self.export_status = format!("Saved {} frames to {}", capture.saved, capture.dir.display());
+                self.capture = None;
+            }
+        } else if ui.button("Capture PNGs").clicked() {
+            let dir = PathBuf::from(format!("{}_frames", self.export_name));
+            match FrameCapture::start(dir, self.capture_every, self.capture_seconds) {
+                Ok(capture) => self.capture = Some(capture),
+                Err(err) => self.report(err),
+            }
+        }
+
+        // no child processes in the browser
+        if cfg!(not(target_arch = "wasm32")) {
+            ui.separator();
+            self.video_ui(ui);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Bundle").on_hover_text("The scene, params, colors, and view in one file").clicked() {
+                let text = bundle::to_text(&self.world, &self.palette, &self.camera);
+                self.export(".bundle", |_, mut out| {
+                    out.write_all(text.as_bytes())?;
+                    out.flush()
+                });
+            }
+            if ui.button("Copy link").clicked() {
+                let text = bundle::to_text(&self.world, &self.palette, &self.camera);
+                ui.output().copied_text = bundle::link(&text);
+                self.export_status = "Copied a link to this setup".to_string();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.bundle_input).hint_text("Link, code, or .bundle file"));
+            let can_import = self.can_edit() && !self.bundle_input.trim().is_empty();
+            if ui.add_enabled(can_import, egui::Button::new("Import")).clicked() {
+                let input = std::mem::take(&mut self.bundle_input);
+                self.import_bundle(&input);
+            }
+        });
+
+        if !self.export_status.is_empty() {
+            ui.label(&self.export_status);
+        }
+    }
+
+    fn video_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(self.video.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.video_fps).clamp_range(1..=120).suffix(" fps"));
+                ui.label("at");
+                ui.add(
+                    egui::DragValue::new(&mut self.video_scale)
+                        .clamp_range(0.1..=2.0)
+                        .speed(0.05)
+                        .suffix("x"),
+                );
+                ui.label("window size");
+            });
+        });
+
+        let Some(video) = self.video.as_ref() else {
+            if ui.button("Record MP4").clicked() {
+                let path = format!("{}.mp4", self.export_name);
+                match VideoRecorder::start(path, self.video_fps, self.video_scale) {
+                    Ok(video) => self.video = Some(video),
+                    Err(err) => self.report(err),
+                }
+            }
+            return;
+        };
+
+        let stop = ui
+            .horizontal(|ui| {
+                ui.label(format!("Recorded {} frames", video.frames));
+                ui.button("Stop").clicked()
+            })
+            .inner;
+        if stop {
+            self.finish_video();
+        }
+    }
+
+    fn finish_video(&mut self) {
+        let Some(video) = self.video.take() else {
+            return;
+        };
+        let path = video.path.clone();
+        match video.finish() {
+            Ok(()) => self.export_status = format!("Saved {}", path),
+            Err(err) => self.report(err),
+        }
+    }
+
+    /// Hands the frame drawn so far to the captures in progress, if any.
+    fn capture_frame(&mut self) {
+        if let Some(Err(err)) = self.video.as_mut().map(VideoRecorder::capture) {
+            self.report(err);
+            self.finish_video();
+        }
+
+        let Some(capture) = self.capture.as_mut() else {
+            return;
+        };
+        match capture.capture() {
+            Ok(true) => {}
+            Ok(false) => {
+                self.export_status = format!("Saved {} frames to {}", capture.saved, capture.dir.display());
+                self.capture = None;
+            }
+            Err(err) => {
+                self.capture = None;
+                self.report(err);
+            }
+        }
+    }
+
+    /// Writes the current frame to `export_name` plus `extension` with `write`.
+    fn export(&mut self, extension: &str, write: impl FnOnce(&Self, BufWriter<File>) -> io::Result<()>) {
+        let path = format!("{}{}", self.export_name, extension);
+        let result = File::create(&path).and_then(|file| write(self, BufWriter::new(file)));
+        match result {
+            Ok(()) => self.export_status = format!("Wrote {}", path),
+            Err(source) => self.report(SimError::Io { action: "export", path, source }),
+        }
+    }
+}
+
+/// Scatters the strength of the links in `world`, just built from `scene`,
+/// by `spread` from `seed`, unless it's a scene file, whose links keep the
+/// strengths written in it.
+fn vary_links(scene: &SceneSource, world: &mut World, spread: f32, seed: u64) {
+    if !matches!(scene, SceneSource::File(_)) {
+        variance::vary(world, spread, seed);
+    }
+}
+
+fn save_trajectories(path: &str, trajectories: &Trajectories) -> Result<(), SimError> {
+    let error = |source| SimError::Io {
+        action: "export",
+        path: path.to_string(),
+        source,
+    };
+    let file = File::create(path).map_err(error)?;
+    trajectories.write_csv(BufWriter::new(file)).map_err(error)
+}
+
+fn save_replay(path: &str, replay: &Replay) -> Result<(), SimError> {
+    std::fs::write(path, replay.to_text()).map_err(|source| SimError::Io {
+        action: "save",
+        path: path.to_string(),
+        source,
+    })
+}
+
+fn load_replay(path: &str) -> Result<Replay, SimError> {
+    let text = std::fs::read_to_string(path).map_err(|source| SimError::Io {
+        action: "read",
+        path: path.to_string(),
+        source,
+    })?;
+    Replay::from_text(&text).map_err(|message| SimError::InvalidReplay {
+        path: path.to_string(),
+        message,
+    })
+}
+
+/// Draws attract mode's `caption` across the top of the screen, and how to get out of it along the bottom.
+/// Both are in `locale`'s language.
+fn draw_caption(caption: &str, locale: &Locale, scale: f32, color: Color) {
+    draw_centered(locale.tr(caption), 60.0 * scale, 36.0, scale, color);
+    let prompt = locale.tr("Move the mouse or press any key to play");
+    draw_centered(prompt, screen_height() - 30.0 * scale, 24.0, scale, color);
+}
+
+/// Keeps `input` for step `step` in `inputs` for a crash dump, dropping the oldest past `CRASH_INPUTS`.
+fn remember_input(inputs: &mut VecDeque<(u64, StepInput)>, step: u64, input: StepInput) {
+    if inputs.len() == CRASH_INPUTS {
+        inputs.pop_front();
+    }
+    inputs.push_back((step, input));
+}
+
+/// Where numbered save `slot` goes, or the autosave for `None`.
+fn save_path(slot: Option<usize>) -> PathBuf {
+    let name = slot.map_or_else(|| "autosave".to_string(), |slot| format!("slot{}", slot));
+    PathBuf::from(SAVES_DIR).join(name + ".save")
+}
+
+/// Draws `text` at `size` times `scale` centered across the screen with its baseline at `y`.
+fn draw_centered(text: &str, y: f32, size: f32, scale: f32, color: Color) {
+    let width = measure_scaled_text(text, size, scale).width;
+    draw_scaled_text(text, (screen_width() - width) / 2.0, y, size, scale, color);
+}
+
+/// Draws the path `cursor`'s knife is cutting along, as wide as it cuts and
+/// fading out towards its oldest end.
+fn draw_knife_trail(cursor: &CursorHistory, color: Color) {
+    let trail = &cursor.trail;
+    let thickness = (2.0 * cursor.knife_radius).max(3.0);
+    for (i, segment) in trail.windows(2).enumerate() {
+        let fade = (i + 1) as f32 / (trail.len() - 1) as f32;
+        let color = Color { a: color.a * fade, ..color };
+        draw_line(segment[0].x, segment[0].y, segment[1].x, segment[1].y, thickness, color);
+    }
+}
+
+/// One line on how a stability trial went.
+fn describe_trial(trial: &Trial) -> String {
+    if trial.diverged {
+        return format!("diverged after {} steps", trial.steps);
+    }
+    let verdict = if trial.stable() { "stable" } else { "unstable" };
+    format!(
+        "{}: {} broken, {:.0}% max stretch, energy x{:.2}",
+        verdict,
+        trial.broken,
+        trial.max_strain * 100.0,
+        trial.energy_growth
+    )
+}
+
+/// `substeps` updated for a step of `dt`, or back to one if `adaptive` is off.
+fn next_substeps(mut substeps: Substeps, adaptive: bool, world: &World, dt: f32) -> Substeps {
+    if !adaptive {
+        return Substeps::default();
+    }
+    substeps.adapt(world, dt);
+    substeps
+}
+
+/// Pushes every free node along `gust` per unit of its mass, for this step only.
+fn blow(world: &mut World, gust: Vec2) {
+    if gust == Vec2::ZERO {
+        return;
+    }
+    for node in world.arena.iter_mut().filter(|node| !node.fixed) {
+        node.force += gust * node.mass;
+    }
+}
+
+/// Runs one simulation step with each cursor's input, inline or on the background thread.
+/// `cursors` holds what each cursor did on earlier steps, and is moved along.
+///
+/// The step of `dt` is split into `substeps` equal parts. Forces already on the
+/// nodes, like scripts' and OSC's, act on every substep, and the knife cuts in each.
+/// Velocity of a ball fired by dragging the cannon from `start` to `end`.
+fn launch_velocity(start: Vec2, end: Vec2) -> Vec2 {
+    ((end - start) * LAUNCH_SCALE).clamp_length_max(MAX_LAUNCH_SPEED)
+}
+
+/// Steps `world` by `dt` in `substeps`, returning how much energy the solver added and took away. With `gpu`, the
+/// integration and solve run on it whenever it can take the world. With `times`, the time each phase takes is added
+/// to it.
+fn step_world(
+    world: &mut World,
+    inputs: &[StepInput],
+    cursors: &mut [CursorHistory],
+    dt: f32,
+    substeps: usize,
+    mut gpu: Option<&mut GpuSolver>,
+    times: Option<&mut FrameTimes>,
+) -> EnergyFlow {
+    profile_scope!("step_world");
+    let mut stopwatch = Stopwatch::start(times.is_some());
+    let mut untimed = FrameTimes::default();
+    let times = times.unwrap_or(&mut untimed);
+    let mut flow = EnergyFlow::default();
+    let external: Vec<Vec2> = match substeps {
+        1 => Vec::new(),
+        _ => world.arena.iter().map(|node| node.force).collect(),
+    };
+    let dt = dt / substeps as f32;
+    for substep in 0..substeps {
+        if substep > 0 {
+            for (node, force) in world.arena.iter_mut().zip(external.iter()) {
+                node.force += *force;
+            }
+        }
+        world.move_anchors(dt);
+        let winch: f32 = inputs.iter().map(|input| input.winch).sum();
+        if winch != 0.0 {
+            world.reel_winches(winch.clamp(-1.0, 1.0), dt);
+        }
+        if inputs.iter().any(|input| input.dispense) {
+            world.dispense(dt);
+        }
+        {
+            profile_scope!("apply_forces");
+            world.apply_forces();
+            for (input, cursor) in inputs.iter().zip(cursors.iter()).filter(|(input, _)| input.wind) {
+                // along each stretch of the cursor's way here, so a curved sweep blows each way it went
+                for stretch in cursor.moved(input.cursor).windows(2) {
+                    let (pos, delta) = (stretch[1], stretch[1] - stretch[0]);
+                    // with air to stir, the wind reaches the nodes through it
+                    match world.fluid.as_mut() {
+                        Some(fluid) => fluid.stir(pos, delta, &input.wind_brush, dt),
+                        None => world.apply_wind(pos, delta, &input.wind_brush),
+                    }
+                }
+            }
+        }
+        world.blow(dt);
+        world.rain(dt);
+        world.pour_sand(dt);
+        stopwatch.lap(times, Phase::Forces);
+        // the GPU integrates and solves at once, if it can
+        let on_gpu = match gpu.as_deref_mut() {
+            Some(gpu) => {
+                profile_scope!("gpu_solve");
+                gpu.integrate_and_solve(world, dt)
+            }
+            None => false,
+        };
+        stopwatch.lap(times, Phase::Solve);
+        if !on_gpu {
+            profile_scope!("integrate");
+            world.integrate(dt);
+        }
+        stopwatch.lap(times, Phase::Integrate);
+        // everything from here to the velocities being worked out is the solver's
+        // doing, or the push and knife tools'
+        let before = Energy::of(world);
+        world.sweep();
+        world.smash();
+        stopwatch.lap(times, Phase::Collide);
+        {
+            profile_scope!("solve_constraints");
+            if !on_gpu {
+                world.solve_constraints();
+            }
+            // after the solve, so the links can't drag nodes back inside
+            for input in inputs.iter().filter(|input| input.push_radius > 0.0) {
+                world.push(input.cursor, input.push_radius);
+            }
+        }
+        {
+            profile_scope!("break_and_cut");
+            world.break_overloaded(dt);
+            for (input, cursor) in inputs.iter().zip(cursors.iter_mut()) {
+                if !input.knife {
+                    continue;
+                }
+                let path = cursor.knife_path(input.cursor);
+                if input.split_nodes {
+                    world.split_path(&path, input.knife_radius);
+                } else if !input.single_cut {
+                    world.cut_path(&path, input.knife_radius);
+                } else if !cursor.swipe_cut {
+                    cursor.swipe_cut = world.cut_first(&path, input.knife_radius);
+                }
+            }
+        }
+        stopwatch.lap(times, Phase::Solve);
+        {
+            profile_scope!("differentiate");
+            world.differentiate(dt);
+        }
+        stopwatch.lap(times, Phase::Integrate);
+        flow.record(&before, &Energy::of(world));
+    }
+    // after every substep, so the external forces above still line up with the nodes
+    world.consume_sunk();
+    world.clean_up(dt * substeps as f32);
+    stopwatch.lap(times, Phase::Collide);
+    world.measure_tensions();
+    stopwatch.lap(times, Phase::Solve);
+    for (input, cursor) in inputs.iter().zip(cursors.iter_mut()) {
+        cursor.advance(input);
+    }
+    flow
+}
+
+impl MainState {
+    /// Starts simulating `scene` with `sim.toml`'s params and `param_overrides`,
+    /// drawing anything random from `seed`.
+    ///
+    /// Anything that goes wrong is reported once the window is up, falling back
+    /// to defaults and the built-in rope scene.
+    pub fn new(scene: SceneSource, param_overrides: ParamOverrides, seed: u64) -> Self {
+        let mut errors = Vec::new();
+
+        let sim_config = SimConfigWatcher::new();
+        let mut params = sim_config::load().unwrap_or_else(|err| {
+            errors.push(err);
+            Params::default()
+        });
+        param_overrides.apply(&mut params);
+
+        let (width, height) = (screen_width(), screen_height());
+        let (scene, mut world) = match scene.build(width, height, params) {
+            Ok(world) => (scene, world),
+            Err(err) => {
+                errors.push(err);
+                let fallback = SceneSource::Builtin("rope".to_string());
+                let world = fallback.build(width, height, params).expect("the rope scene is built in");
+                (fallback, world)
+            }
+        };
+        world.rng = Rng::new(seed);
+        let analytic = Analytic::new(&world);
+        let query = Query::new(&world);
+
+        let saved_settings = Settings::load().unwrap_or_else(|err| {
+            errors.push(err);
+            Settings::default()
+        });
+
+        let mut state = Self {
+            app_state: AppState::Simulating,
+            world,
+            scene,
+            seed,
+            link_variance: 0.0,
+            reanchor: true,
+            view_size: Vec2::new(screen_width(), screen_height()),
+            ui_scale: None,
+            tool_cursors: true,
+            mouse: MouseBindings::default(),
+            params,
+            param_overrides,
+            sim_config,
+            cursor: CursorHistory::default(),
+            fingers: Fingers::default(),
+            mouse_motion: MouseMotion::new(),
+            motion: Vec::new(),
+            knife_radius: 0.0,
+            single_cut: false,
+            split_nodes: false,
+            camera: Camera::default(),
+            viewports: Vec::new(),
+            show_grid: false,
+            entities: Entities::default(),
+            time_scale: TimeScale::default(),
+            audio: Audio::default(),
+            ui_wants_pointer: false,
+            query,
+            show_heatmap: false,
+            show_glow: true,
+            show_motion_blur: false,
+            shade_cloth: false,
+            show_field_lines: true,
+            strain_warnings: true,
+            strain_alert: StrainAlert::default(),
+            rope_materials: RopeMaterials::default(),
+            locales: Locales::load(),
+            palette: Palette::default(),
+            saved_settings,
+            step_count: 0,
+            show_checksum: false,
+            budget_warnings: false,
+            frame_times: FrameTimes::default(),
+            frame_budget: FrameBudget::default(),
+            show_indices: false,
+            show_minimap: true,
+            over_minimap: false,
+            adaptive_timestep: false,
+            level_of_detail: false,
+            lod: Lod::default(),
+            gpu_solve: false,
+            gpu_solver: GpuSolver::new(),
+            substeps: Substeps::default(),
+            recorder: None,
+            player: None,
+            replay_path: "replay.txt".to_string(),
+            replay_status: String::new(),
+            checkpoint: None,
+            sim_thread: None,
+            thread_start_step: 0,
+            editor_tool: EditorTool::Grab,
+            cursor_tool: CursorTool::Wind,
+            wind_brush: WindBrush::default(),
+            push_radius: DEFAULT_PUSH_RADIUS,
+            well_strength: DEFAULT_WELL_STRENGTH,
+            well_radius: DEFAULT_WELL_RADIUS,
+            sink_strength: DEFAULT_SINK_STRENGTH,
+            sink_radius: DEFAULT_SINK_RADIUS,
+            ball_mass: DEFAULT_BALL_MASS,
+            build_material: material::ROPE,
+            ball_radius: DEFAULT_BALL_RADIUS,
+            aim_start: None,
+            measurements: Vec::new(),
+            measure_from: None,
+            winch_button: 0.0,
+            dispense_button: false,
+            posed_chain: None,
+            energy_flow: EnergyFlow::default(),
+            drag_velocity: Vec2::ZERO,
+            toasts: Vec::new(),
+            show_profiler: false,
+            trajectories: Trajectories::new(TRAJECTORY_STEPS),
+            recording_trajectories: false,
+            trajectory_path: "trajectories.csv".to_string(),
+            export_name: "clothsim".to_string(),
+            export_status: String::new(),
+            bundle_input: String::new(),
+            capture: None,
+            state_dump: None,
+            capture_every: 2,
+            capture_seconds: 5.0,
+            capture_ui: false,
+            video: None,
+            video_fps: 30,
+            video_scale: 1.0,
+            script: None,
+            #[cfg(feature = "netplay")]
+            session: None,
+            #[cfg(feature = "osc")]
+            osc: None,
+            #[cfg(feature = "gamepad")]
+            gamepad: None,
+            #[cfg(feature = "gamepad")]
+            pad_cursor: None,
+            gust: Vec2::ZERO,
+            comparison: None,
+            comparison_params: Params { solver_iterations: 20, ..params },
+            comparison_compliance: None,
+            stability: None,
+            auto_apply_stability: false,
+            show_center_of_mass: false,
+            momentum_history: VecDeque::with_capacity(MOMENTUM_FRAMES),
+            measure_convergence: false,
+            convergence: Vec::new(),
+            analytic,
+            show_analytic: true,
+            bridge: None,
+            show_statics: false,
+            statics: None,
+            settled: None,
+            puzzle: None,
+            solved_levels: vec![false; puzzle::LEVELS.len()],
+            attract_after: None,
+            attract: None,
+            last_input_time: get_time(),
+            last_mouse: Vec2::from(mouse_position()),
+            crawl_start: 0.0,
+            force_text: String::new(),
+            force_expr: None,
+            force_error: None,
+            group_stiffness: 5.0,
+            evolution: None,
+            last_generation: None,
+            tutorial: None,
+            tutorial_done: false,
+            challenge: None,
+            best_times: BTreeMap::new(),
+            save_slot: 1,
+            autosave: true,
+            last_autosave: get_time(),
+            save_status: String::new(),
+            recent_inputs: VecDeque::new(),
+            before_step: None,
+            timeline: History::new(TIMELINE_SNAPSHOTS, TIMELINE_EVERY),
+            scrubbed: None,
+            rewinding: None,
+            graph: None,
+        };
+        state.apply_settings(&state.saved_settings.clone());
+        vary_links(&state.scene, &mut state.world, state.link_variance, seed);
+        // the benchmark scene is for comparing the solvers, so it starts out doing that on the same params
+        if state.scene == SceneSource::Builtin("solver_bench".to_string()) && state.sim_thread.is_none() {
+            state.comparison_params = state.world.params;
+            state.comparison_compliance = Some(XPBD_COMPLIANCE);
+            state.set_comparing(true);
+        }
+        state.show_statics = state.scene == SceneSource::Builtin("truss".to_string());
+        for err in errors {
+            state.report(err);
         }
+        state
     }
 }
@@ -0,0 +1,170 @@
+//! What ropes and cloth are made of: how heavy, how stretchy, how strong, and
+//! how much the air holds them back, bundled into presets so a steel cable or
+//! a silk scarf behaves plausibly without tuning each setting by hand.
+
+use crate::band::Band;
+use crate::constraint::Constraint;
+use crate::weave::Links;
+use crate::world::World;
+use crate::TARGET_DIST;
+use std::ops::Range;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: &'static str,
+    /// Whether its links are rigid constraints or springs, and how stiff.
+    pub stiffness: Links,
+    /// Force per unit of speed its springy links' ends move apart at, which keeps them from ringing.
+    pub damping: f32,
+    /// Mass per unit of rest length, spread over its nodes.
+    pub density: f32,
+    /// Drag on each of its nodes on top of the params', per unit of speed.
+    pub drag: f32,
+    /// Length its rigid links break at, in rest lengths.
+    pub break_threshold: f32,
+    /// The color the demo shows it in, as RGB.
+    pub color: [u8; 3],
+}
+
+/// Plain rope, as heavy and as strong as ropes are by default.
+pub const ROPE: Material = Material {
+    name: "Rope",
+    stiffness: Links::Rigid,
+    damping: 0.0,
+    density: 1.0 / TARGET_DIST,
+    drag: 0.0,
+    break_threshold: 5.0,
+    color: [181, 140, 90],
+};
+
+/// Heavy and much harder to snap.
+pub const STEEL_CABLE: Material = Material {
+    name: "Steel cable",
+    stiffness: Links::Rigid,
+    damping: 0.0,
+    density: 4.0 / TARGET_DIST,
+    drag: 0.0,
+    break_threshold: 12.0,
+    color: [160, 165, 175],
+};
+
+/// Stretchy bands that go slack when squeezed and never snap.
+pub const RUBBER: Material = Material {
+    name: "Rubber",
+    stiffness: Links::Spring(2.0),
+    damping: 0.5,
+    density: 1.5 / TARGET_DIST,
+    drag: 0.0,
+    break_threshold: 5.0,
+    color: [70, 70, 70],
+};
+
+/// Light and easily torn, and drifts down through the air.
+pub const SILK: Material = Material {
+    name: "Silk",
+    stiffness: Links::Rigid,
+    damping: 0.0,
+    density: 0.25 / TARGET_DIST,
+    drag: 1.0,
+    break_threshold: 2.0,
+    color: [240, 228, 250],
+};
+
+pub const MATERIALS: [Material; 4] = [ROPE, STEEL_CABLE, RUBBER, SILK];
+
+impl Material {
+    /// The preset called `name`, ignoring case.
+    pub fn named(name: &str) -> Option<Material> {
+        MATERIALS.into_iter().find(|material| material.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Mass of a node with a rest length of material to itself.
+    pub fn node_mass(&self) -> f32 {
+        self.density * TARGET_DIST
+    }
+
+    /// Links `a` and `b` at `rest_length` with this material, as a constraint
+    /// or a band as its stiffness says. Omitted links are left out.
+    pub fn link(&self, world: &mut World, a: usize, b: usize, rest_length: f32) {
+        match self.stiffness {
+            Links::Omitted => {}
+            Links::Rigid => {
+                let break_threshold = rest_length * self.break_threshold;
+                world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+            }
+            Links::Spring(stiffness) => {
+                let band = Band { a, b, rest_length, stiffness, pushes: false, damping: self.damping };
+                world.bands.push(band);
+            }
+        }
+    }
+
+    /// Remakes the nodes in `nodes`, and every constraint and band between two
+    /// of them, out of this material, keeping any water the nodes have soaked up.
+    pub fn apply(&self, world: &mut World, nodes: Range<usize>) {
+        for node in world.arena[nodes.clone()].iter_mut() {
+            node.mass = self.node_mass() + node.wetness;
+            node.drag = self.drag;
+        }
+        let inside = |a: usize, b: usize| nodes.contains(&a) && nodes.contains(&b);
+        let (links, kept): (Vec<Constraint>, Vec<Constraint>) =
+            world.constraints.iter().partition(|constraint| inside(constraint.a, constraint.b));
+        let (bands, kept_bands): (Vec<Band>, Vec<Band>) =
+            world.bands.iter().partition(|band| inside(band.a, band.b));
+        world.constraints = kept;
+        world.bands = kept_bands;
+        let pairs = links.iter().map(|link| (link.a, link.b, link.rest_length));
+        for (a, b, rest_length) in pairs.chain(bands.iter().map(|band| (band.a, band.b, band.rest_length))) {
+            self.link(world, a, b, rest_length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn presets_make_ropes_heavier_stretchier_or_floatier() {
+        assert_eq!(Material::named("steel CABLE"), Some(STEEL_CABLE));
+        assert_eq!(Material::named("tin foil"), None);
+
+        let hang = |material: &Material| {
+            let mut world = World::new(10_000.0);
+            world.add_rope(Vec2::new(100.0, 100.0), 5);
+            world.add_rope(Vec2::new(300.0, 100.0), 5);
+            material.apply(&mut world, 0..5);
+            world
+        };
+
+        // only the first rope's made of steel, and it's heavier and stronger for it
+        let steel = hang(&STEEL_CABLE);
+        assert_eq!(steel.arena[1].mass, 4.0);
+        assert_eq!(steel.arena[6].mass, 1.0);
+        assert_eq!(steel.constraints.len(), 8);
+        assert!(steel.constraints.iter().filter(|c| c.b < 5).all(|c| c.break_threshold == 12.0 * c.rest_length));
+
+        // rubber swaps its links for bands, which stretch under the rope's weight
+        let mut rubber = hang(&RUBBER);
+        assert_eq!((rubber.constraints.len(), rubber.bands.len()), (4, 4));
+        let dt = rubber.params.dt;
+        for _ in 0..600 {
+            rubber.step(dt);
+        }
+        assert!(rubber.arena[4].pos.y - rubber.arena[9].pos.y > TARGET_DIST);
+
+        // silk falls slower through the air than rope does
+        let fall = |material: &Material| {
+            let mut world = World::new(10_000.0);
+            world.add_ball(Vec2::ZERO, Vec2::ZERO, 1.0, 5.0);
+            material.apply(&mut world, 0..1);
+            let dt = world.params.dt;
+            for _ in 0..300 {
+                world.step(dt);
+            }
+            world.arena[0].pos.y
+        };
+        assert!(fall(&SILK) < 0.5 * fall(&ROPE), "{} {}", fall(&SILK), fall(&ROPE));
+    }
+}
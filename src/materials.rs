@@ -0,0 +1,240 @@
+use egui_macroquad::macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use egui_macroquad::macroquad::prelude::*;
+
+/// Rope segments, as `Mesh::push_segment` lays them out, carry per-vertex data the fragment shader can use:
+/// - `uv.x` runs along the segment, in rest lengths from its first node
+/// - `uv.y` is the constraint's break fraction, 0 at rest and 1 at the threshold,
+///   or for a textured material, how far across the segment, from 0 to 1
+/// - `color` is the color the default renderer would have used, its alpha
+///   fading to 0 at the segment's edges, so shaders should keep `color.a`
+///
+/// plus macroquad's `_Time` uniform, whose `x` is seconds since startup.
+const VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+const STRAIN_GRADIENT_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+void main() {
+    vec3 slack = vec3(0.2, 0.6, 1.0);
+    vec3 taut = vec3(1.0, 0.15, 0.1);
+    float shimmer = 0.9 + 0.1 * sin(uv.x * 12.0);
+    gl_FragColor = vec4(mix(slack, taut, uv.y) * shimmer, color.a);
+}"#;
+
+const SCROLLING_STRIPES_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform vec4 _Time;
+
+void main() {
+    float stripe = step(0.5, fract(uv.x * 4.0 - _Time.x * (1.0 + 4.0 * uv.y)));
+    gl_FragColor = vec4(color.rgb * mix(0.45, 1.0, stripe), color.a);
+}"#;
+
+/// Tiles a texture once per rest length along the segment, tinted the rope's color.
+const TEXTURED_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = texture2D(Texture, vec2(fract(uv.x), uv.y)) * color;
+}"#;
+
+/// Size of the generated rope and chain textures, one rest length long.
+const TEXTURE_WIDTH: u16 = 32;
+const TEXTURE_HEIGHT: u16 = 16;
+
+/// Shaders the user can drop next to the binary, one fragment shader per file.
+#[cfg(not(target_arch = "wasm32"))]
+const CUSTOM_SHADER_DIR: &str = "shaders";
+
+struct RopeMaterial {
+    name: String,
+    /// `None` for macroquad's default material.
+    material: Option<Material>,
+    /// Drawn along each segment, for the textured materials.
+    texture: Option<Texture2D>,
+}
+
+/// Materials available for drawing ropes. Index 0 is macroquad's default material.
+pub struct RopeMaterials {
+    materials: Vec<RopeMaterial>,
+    pub selected: usize,
+}
+
+impl Default for RopeMaterials {
+    fn default() -> Self {
+        let mut materials = Self {
+            materials: vec![RopeMaterial { name: "Flat".to_string(), material: None, texture: None }],
+            selected: 0,
+        };
+
+        for (name, source) in [
+            ("Strain gradient", STRAIN_GRADIENT_SHADER),
+            ("Scrolling stripes", SCROLLING_STRIPES_SHADER),
+        ] {
+            if let Err(e) = materials.add(name, source) {
+                eprintln!("failed to compile built-in shader {}: {:?}", name, e);
+            }
+        }
+        for (name, texture) in [("Rope texture", rope_texture()), ("Chain texture", chain_texture())] {
+            if let Err(e) = materials.add_textured(name, texture) {
+                eprintln!("failed to compile built-in shader {}: {:?}", name, e);
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        materials.load_dir(CUSTOM_SHADER_DIR);
+
+        materials
+    }
+}
+
+impl RopeMaterials {
+    /// Compiles `fragment_shader` against the rope vertex layout and makes it selectable.
+    pub fn add(&mut self, name: &str, fragment_shader: &str) -> Result<(), ShaderError> {
+        let material = Self::load(fragment_shader)?;
+        self.materials.push(RopeMaterial { name: name.to_string(), material: Some(material), texture: None });
+        Ok(())
+    }
+
+    /// Makes a material drawing `texture` along ropes selectable.
+    fn add_textured(&mut self, name: &str, texture: Texture2D) -> Result<(), ShaderError> {
+        let material = Self::load(TEXTURED_SHADER)?;
+        self.materials.push(RopeMaterial { name: name.to_string(), material: Some(material), texture: Some(texture) });
+        Ok(())
+    }
+
+    fn load(fragment_shader: &str) -> Result<Material, ShaderError> {
+        load_material(
+            VERTEX_SHADER,
+            fragment_shader,
+            MaterialParams {
+                pipeline_params: PipelineParams {
+                    color_blend: Some(BlendState::new(
+                        Equation::Add,
+                        BlendFactor::Value(BlendValue::SourceAlpha),
+                        BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                    )),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Adds every `*.frag` file in `dir`, named after the file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_dir(&mut self, dir: &str) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "frag") {
+                continue;
+            }
+
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| format!("{}", e))
+                .and_then(|source| self.add(&name, &source).map_err(|e| format!("{:?}", e)));
+            if let Err(e) = result {
+                eprintln!("failed to load shader {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.materials.iter().map(|material| material.name.as_str())
+    }
+
+    /// Selects the material called `name`, if there is one.
+    pub fn select_by_name(&mut self, name: &str) {
+        if let Some(i) = self.materials.iter().position(|material| material.name == name) {
+            self.selected = i;
+        }
+    }
+
+    pub fn selected_name(&self) -> &str {
+        &self.materials[self.selected].name
+    }
+
+    /// The texture the selected material draws along ropes, if it's a textured one.
+    pub fn selected_texture(&self) -> Option<Texture2D> {
+        self.materials[self.selected].texture
+    }
+
+    pub fn use_selected(&self) {
+        match self.materials[self.selected].material {
+            Some(material) => gl_use_material(material),
+            None => gl_use_default_material(),
+        }
+    }
+}
+
+/// Fills a generated texture from a function of where each pixel is, along and
+/// across, from 0 to 1.
+fn generate_texture(pixel: impl Fn(f32, f32) -> Color) -> Texture2D {
+    let mut image = Image::gen_image_color(TEXTURE_WIDTH, TEXTURE_HEIGHT, BLANK);
+    for y in 0..TEXTURE_HEIGHT {
+        for x in 0..TEXTURE_WIDTH {
+            let u = (x as f32 + 0.5) / TEXTURE_WIDTH as f32;
+            let v = (y as f32 + 0.5) / TEXTURE_HEIGHT as f32;
+            image.set_pixel(x as u32, y as u32, pixel(u, v));
+        }
+    }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Linear);
+    texture
+}
+
+/// Strands twisting diagonally across, with grooves between them and shaded
+/// darker toward the edges as if round, in grey for the rope color to tint.
+fn rope_texture() -> Texture2D {
+    generate_texture(|u, v| {
+        let strand = (std::f32::consts::PI * (3.0 * u + v).fract()).sin().sqrt();
+        let round = (std::f32::consts::PI * v).sin();
+        let shade = 0.35 + 0.65 * strand * round;
+        Color::new(shade, shade, shade, 1.0)
+    })
+}
+
+/// An oval link seen face on, holding the next one seen edge on, as a bar
+/// across the seam into the next tile, with nothing drawn in between.
+fn chain_texture() -> Texture2D {
+    generate_texture(|u, v| {
+        let (x, y) = ((u - 0.4) / 0.35, (v - 0.5) / 0.45);
+        let ring = (x * x + y * y).sqrt();
+        let face_on = (0.6..=1.0).contains(&ring);
+        let edge_on = (u >= 0.65 || u <= 0.15) && (v - 0.5).abs() < 0.1;
+        // lit from above
+        let shade = if face_on { 0.75 - 0.25 * y } else { 0.6 };
+        Color::new(shade, shade, shade, if face_on || edge_on { 1.0 } else { 0.0 })
+    })
+}
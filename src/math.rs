@@ -0,0 +1,78 @@
+//! The transcendental functions stepping uses.
+//!
+//! Rust never reorders or fuses float arithmetic on its own, and the solver
+//! visits everything in a fixed order, so additions and multiplications come
+//! out the same everywhere, and so does `sqrt`, which IEEE requires rounded
+//! exactly. `sin`, `cos`, `exp`, and `atan2` are another matter: the standard
+//! library hands them to the platform's math library, which may round the
+//! last bit differently from one OS or CPU to the next, and a replay or a
+//! shared session drifts apart from there. With the `deterministic` feature
+//! they come from `libm`'s software implementations instead, which give the
+//! same bits on every platform, at some cost in speed.
+
+use glam::Vec2;
+
+/// Which math this build steps with, recorded in replays: `"libm"` with the
+/// `deterministic` feature and `"native"` without.
+pub const MODE: &str = if cfg!(feature = "deterministic") { "libm" } else { "native" };
+
+#[cfg(feature = "deterministic")]
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(feature = "deterministic")]
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(feature = "deterministic")]
+pub fn exp(x: f32) -> f32 {
+    libm::expf(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn exp(x: f32) -> f32 {
+    x.exp()
+}
+
+#[cfg(feature = "deterministic")]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    libm::atan2f(y, x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+/// The unit vector `angle` radians clockwise on screen from the x axis, like `Vec2::from_angle`.
+pub fn from_angle(angle: f32) -> Vec2 {
+    Vec2::new(cos(angle), sin(angle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_the_standard_library() {
+        for x in [-10.0, -1.0, 0.0, 0.35, 1.0, std::f32::consts::PI, 50.0] {
+            assert!((sin(x) - x.sin()).abs() <= 1e-6, "sin {}", x);
+            assert!((cos(x) - x.cos()).abs() <= 1e-6, "cos {}", x);
+            assert!((exp(x) / x.exp() - 1.0).abs() <= 1e-6, "exp {}", x);
+            assert!((atan2(x, 2.0) - x.atan2(2.0)).abs() <= 1e-6, "atan2 {}", x);
+        }
+        assert_eq!(from_angle(0.0), Vec2::X);
+    }
+}
@@ -0,0 +1,54 @@
+//! The measuring tape: lines between two points or nodes labeled with how
+//! far apart they are, for laying a scene out with links of a particular
+//! length. An end on a node follows it as it moves, so a measurement between
+//! two nodes reads how far apart they are now. Measurements are only notes
+//! on the view and don't go in saves or scene files.
+
+use clothsim::units::Units;
+use clothsim::World;
+use egui_macroquad::macroquad::prelude::Vec2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum End {
+    Point(Vec2),
+    Node(usize),
+}
+
+impl End {
+    /// Where it is in `world`, or `None` for a node that's gone.
+    pub fn pos(self, world: &World) -> Option<Vec2> {
+        match self {
+            End::Point(pos) => Some(pos),
+            End::Node(i) => world.arena.get(i).map(|node| node.pos),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub ends: [End; 2],
+}
+
+impl Measurement {
+    /// Where its ends are in `world`, unless one was on a node that's gone.
+    pub fn points(&self, world: &World) -> Option<[Vec2; 2]> {
+        Some([self.ends[0].pos(world)?, self.ends[1].pos(world)?])
+    }
+
+    /// Whether `pos` is within `reach` of the line between its ends.
+    pub fn near(&self, world: &World, pos: Vec2, reach: f32) -> bool {
+        let Some([a, b]) = self.points(world) else {
+            return false;
+        };
+        let t = ((pos - a).dot(b - a) / (b - a).length_squared().max(f32::MIN_POSITIVE)).clamp(0.0, 1.0);
+        a.lerp(b, t).distance(pos) <= reach
+    }
+}
+
+/// How long `length` is, in world units, and meters too with `units`.
+pub fn label(length: f32, units: Option<Units>) -> String {
+    match units {
+        Some(units) => format!("{:.1} ({:.2} m)", length, units.meters(length)),
+        None => format!("{:.1}", length),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::palette::Palette;
+use clothsim::World;
+use egui_macroquad::macroquad::prelude::*;
+
+/// Longest side of the minimap on screen.
+const SIZE: f32 = 200.0;
+/// Gap between the minimap and the edges of the screen.
+const MARGIN: f32 = 10.0;
+/// World units of empty space kept around everything in the scene.
+const PADDING: f32 = 50.0;
+const BACKGROUND_ALPHA: f32 = 0.7;
+
+/// The whole scene shrunk into the bottom right corner, with the part on
+/// screen outlined, for finding the way around scenes too big to see at once.
+pub struct Minimap {
+    /// The part of the world it shows.
+    bounds: Rect,
+    /// Where it's drawn on screen.
+    screen: Rect,
+}
+
+impl Minimap {
+    /// Fits every shown node and every rotor into the corner, or `None` if
+    /// they're all in `view`, the part of the world on screen, already.
+    pub fn new(world: &World, view: Rect) -> Option<Self> {
+        let hidden = world.hidden();
+        let nodes = world.arena.iter().zip(hidden).filter(|(_, hidden)| !hidden).map(|(node, _)| node.pos);
+        let rotors = world.rotors.iter().filter(|rotor| world.layers.visible(rotor.layer)).flat_map(|rotor| {
+            let reach = Vec2::splat(rotor.blade_length.max(rotor.hub_radius));
+            [rotor.center - reach, rotor.center + reach]
+        });
+        let points: Vec<Vec2> = nodes.chain(rotors).collect();
+        if points.iter().all(|&point| view.contains(point)) {
+            return None;
+        }
+
+        // the view's left out so the map holds still while it's dragged across
+        let start = (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+        let (min, max) = points.iter().fold(start, |(min, max), &point| (min.min(point), max.max(point)));
+        let (min, max) = (min - PADDING, max + PADDING);
+        let bounds = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+        let size = bounds.size() * (SIZE / bounds.w.max(bounds.h));
+        let corner = Vec2::new(screen_width(), screen_height()) - size - MARGIN;
+        Some(Self { bounds, screen: Rect::new(corner.x, corner.y, size.x, size.y) })
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.screen.contains(point)
+    }
+
+    /// The point in the world at `point` on the minimap.
+    pub fn to_world(&self, point: Vec2) -> Vec2 {
+        self.bounds.point() + (point - self.screen.point()) * (self.bounds.w / self.screen.w)
+    }
+
+    fn to_screen(&self, point: Vec2) -> Vec2 {
+        self.screen.point() + (point - self.bounds.point()) * (self.screen.w / self.bounds.w)
+    }
+
+    /// Draws the ground, each shown link, rotor, and pinned node, and `view` outlined, as much of it as is on the map.
+    pub fn draw(&self, world: &World, view: Rect, palette: &Palette) {
+        let Rect { x, y, w, h } = self.screen;
+        draw_rectangle(x, y, w, h, Color { a: BACKGROUND_ALPHA, ..palette.background });
+        draw_rectangle_lines(x, y, w, h, 1.0, palette.text);
+
+        let ground = self.to_screen(Vec2::new(0.0, world.ground_y)).y;
+        if !world.wrap.y && ground > y && ground < y + h {
+            draw_line(x, ground, x + w, ground, 1.0, palette.ground);
+        }
+        let hidden = world.hidden();
+        for constraint in world.constraints.iter().filter(|c| !hidden[c.a] && !hidden[c.b]) {
+            let (a, b) = (self.to_screen(world.arena[constraint.a].pos), self.to_screen(world.arena[constraint.b].pos));
+            draw_line(a.x, a.y, b.x, b.y, 1.0, palette.rope);
+        }
+        for rotor in world.rotors.iter().filter(|rotor| world.layers.visible(rotor.layer)) {
+            let center = self.to_screen(rotor.center);
+            let radius = rotor.blade_length.max(rotor.hub_radius) * self.screen.w / self.bounds.w;
+            draw_circle_lines(center.x, center.y, radius, 1.0, palette.node);
+        }
+        for (node, _) in world.arena.iter().zip(&hidden).filter(|(node, hidden)| node.fixed && !**hidden) {
+            let pos = self.to_screen(node.pos);
+            draw_circle(pos.x, pos.y, 2.0, palette.fixed_node);
+        }
+
+        let (min, max) = (self.to_screen(view.point()), self.to_screen(view.point() + view.size()));
+        let (min, max) = (min.max(self.screen.point()), max.min(self.screen.point() + self.screen.size()));
+        if min.x < max.x && min.y < max.y {
+            draw_rectangle_lines(min.x, min.y, max.x - min.x, max.y - min.y, 2.0, palette.accent);
+        }
+    }
+}
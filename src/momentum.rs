@@ -0,0 +1,52 @@
+use crate::world::World;
+use glam::Vec2;
+
+/// The total linear momentum and center of mass of a world's free nodes.
+/// Fixed nodes are left out, since they act like infinitely heavy walls.
+/// With no gravity, wind, or fixed nodes, the solver should keep `linear`
+/// where it is, since every constraint pushes its two ends equally and oppositely.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Momentum {
+    pub linear: Vec2,
+    /// Where the free nodes balance, or the origin if there aren't any.
+    pub center_of_mass: Vec2,
+    /// Of all the free nodes together.
+    pub mass: f32,
+}
+
+impl Momentum {
+    pub fn of(world: &World) -> Self {
+        let free = world.arena.iter().filter(|node| !node.fixed);
+        let (mass, moment, linear) = free.fold((0.0, Vec2::ZERO, Vec2::ZERO), |(mass, moment, linear), node| {
+            (mass + node.mass, moment + node.pos * node.mass, linear + node.vel * node.mass)
+        });
+        let center_of_mass = if mass > 0.0 { moment / mass } else { Vec2::ZERO };
+        Self { linear, center_of_mass, mass }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::node::Node;
+    use crate::params::Params;
+
+    #[test]
+    fn links_keep_momentum_in_free_fall() {
+        let mut world = World::new(f32::INFINITY);
+        world.set_params(Params { gravity: 0.0, drag: 0.0, ..Params::default() });
+        world.arena.push(Node { vel: Vec2::new(10.0, 0.0), ..Node::with_pos_and_mass(Vec2::ZERO, 1.0) });
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(0.0, 80.0), 3.0));
+        world.constraints.push(Constraint { a: 0, b: 1, rest_length: 50.0, break_threshold: f32::INFINITY });
+
+        let before = Momentum::of(&world);
+        assert_eq!(before.center_of_mass, Vec2::new(0.0, 60.0));
+        assert_eq!(before.linear, Vec2::new(10.0, 0.0));
+        for _ in 0..20 {
+            world.step(world.params.dt);
+        }
+        let after = Momentum::of(&world);
+        assert!((after.linear - before.linear).length() < 1e-3, "{}", after.linear);
+    }
+}
@@ -0,0 +1,57 @@
+//! Every position the mouse passed through between frames, not just where
+//! it ended up. At a low frame rate a quick swipe covers a lot of ground in
+//! a frame, and a straight line from one frame's position to the next cuts
+//! the corner off a curved stroke and misses what the hand went around. The
+//! window system reports each movement as it happens, so replaying those
+//! reports gives the knife and the wind the stroke as it was drawn.
+
+use egui_macroquad::macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use egui_macroquad::macroquad::miniquad::{Context, EventHandler};
+use egui_macroquad::macroquad::prelude::Vec2;
+use egui_macroquad::macroquad::window::get_internal_gl;
+
+/// Most positions kept from one frame, so a mouse that reports a thousand
+/// times a second doesn't make every cut and gust that many segments.
+pub const MAX_SAMPLES: usize = 16;
+
+pub struct MouseMotion {
+    subscriber: usize,
+    /// What's been reported since the last `take`, in the window's physical pixels.
+    reported: Vec<Vec2>,
+}
+
+impl MouseMotion {
+    pub fn new() -> Self {
+        Self { subscriber: register_input_subscriber(), reported: Vec::new() }
+    }
+
+    /// Where the mouse went since the last call, oldest first in screen
+    /// space, leaving off where it is now. Has to be called every frame, or
+    /// the reports pile up.
+    pub fn take(&mut self) -> Vec<Vec2> {
+        let subscriber = self.subscriber;
+        repeat_all_miniquad_input(self, subscriber);
+        let dpi_scale = unsafe { get_internal_gl().quad_context.dpi_scale() };
+        let mut reported: Vec<Vec2> = self.reported.drain(..).map(|pos| pos / dpi_scale).collect();
+        reported.pop();
+        thin(&reported, MAX_SAMPLES)
+    }
+}
+
+impl EventHandler for MouseMotion {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, _ctx: &mut Context) {}
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32) {
+        self.reported.push(Vec2::new(x, y));
+    }
+}
+
+/// At most `max` of `points`, evenly spread through them.
+fn thin(points: &[Vec2], max: usize) -> Vec<Vec2> {
+    if points.len() <= max {
+        return points.to_vec();
+    }
+    (0..max).map(|i| points[(i + 1) * points.len() / max - 1]).collect()
+}
@@ -0,0 +1,181 @@
+//! A hierarchical pass for long chains. Projection moves each link's ends in
+//! turn, so a correction at one end of a rope only gets one link further along
+//! each iteration, and a 200-node rope with a weight on it stretches far
+//! before the pinned end hears about it. Before the usual iterations, this
+//! solves coarser versions of every chain first: every 16th node linked to the
+//! next by a link as long as the rope between them, then every 8th, and so on
+//! down, each moving the nodes between by their share of what moved the ones
+//! either side. The coarse links only pull, since the rope between them can
+//! go slack, and the usual iterations then settle the fine detail.
+
+use crate::constraint::Constraint;
+use crate::node::Node;
+use crate::wrap::Wrap;
+use glam::Vec2;
+
+/// Coarse levels solved, by default, linking every 2nd node up to every 16th.
+pub const MULTIGRID_LEVELS: usize = 4;
+/// Iterations each coarse level gets, by default.
+pub const MULTIGRID_ITERATIONS: usize = 2;
+/// Chains shorter than this aren't worth coarsening.
+const MIN_CHAIN_NODES: usize = 8;
+
+/// Solving coarsened chains before the usual iterations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Multigrid {
+    /// How many coarse levels, the coarsest linking every `2^levels`th node.
+    pub levels: usize,
+    pub iterations: usize,
+}
+
+impl Default for Multigrid {
+    fn default() -> Self {
+        Self { levels: MULTIGRID_LEVELS, iterations: MULTIGRID_ITERATIONS }
+    }
+}
+
+/// A run of links through nodes with no other links, from an end or a junction to the next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chain {
+    pub nodes: Vec<usize>,
+    /// Rest length from the first node to each node along it.
+    pub along: Vec<f32>,
+}
+
+impl Multigrid {
+    /// Every chain of at least `MIN_CHAIN_NODES` nodes in `constraints`.
+    /// Closed loops with no end or junction on them aren't found.
+    pub fn chains(constraints: &[Constraint], nodes: usize) -> Vec<Chain> {
+        let mut links = vec![Vec::new(); nodes];
+        for (i, constraint) in constraints.iter().enumerate() {
+            links[constraint.a].push(i);
+            links[constraint.b].push(i);
+        }
+        let other = |i: usize, node: usize| {
+            let constraint = &constraints[i];
+            if constraint.a == node {
+                constraint.b
+            } else {
+                constraint.a
+            }
+        };
+
+        let mut walked = vec![false; constraints.len()];
+        let mut chains = Vec::new();
+        for start in (0..nodes).filter(|&node| links[node].len() != 2) {
+            for &first in links[start].iter() {
+                if walked[first] {
+                    continue;
+                }
+                let mut chain = Chain { nodes: vec![start], along: vec![0.0] };
+                let (mut link, mut node) = (first, start);
+                loop {
+                    walked[link] = true;
+                    node = other(link, node);
+                    chain.nodes.push(node);
+                    chain.along.push(chain.along[chain.along.len() - 1] + constraints[link].rest_length);
+                    match links[node].as_slice() {
+                        &[a, b] => link = if a == link { b } else { a },
+                        _ => break,
+                    }
+                }
+                if chain.nodes.len() >= MIN_CHAIN_NODES {
+                    chains.push(chain);
+                }
+            }
+        }
+        chains
+    }
+
+    /// Solves every chain from the coarsest level to the finest, each link
+    /// pulling its ends `rigidity` of the way back in like a constraint.
+    pub fn solve(&self, constraints: &[Constraint], arena: &mut [Node], wrap: &Wrap, rigidity: f32) {
+        for chain in Self::chains(constraints, arena.len()) {
+            for level in (1..=self.levels).rev() {
+                self.solve_level(&chain, 1 << level, arena, wrap, rigidity);
+            }
+        }
+    }
+
+    /// Links every `stride`th node of `chain`, and its last, solves those
+    /// links, and spreads each stretch of the chain's corrections over the
+    /// nodes between.
+    fn solve_level(&self, chain: &Chain, stride: usize, arena: &mut [Node], wrap: &Wrap, rigidity: f32) {
+        let last = chain.nodes.len() - 1;
+        if last < 2 * stride {
+            return;
+        }
+        let mut picks: Vec<usize> = (0..last).step_by(stride).collect();
+        picks.push(last);
+        let before: Vec<Vec2> = picks.iter().map(|&k| arena[chain.nodes[k]].pos).collect();
+
+        for _ in 0..self.iterations {
+            for pair in picks.windows(2) {
+                let (a, b) = (chain.nodes[pair[0]], chain.nodes[pair[1]]);
+                let rest_length = chain.along[pair[1]] - chain.along[pair[0]];
+                let r = wrap.delta(arena[a].pos, arena[b].pos);
+                let dist = r.length();
+                if dist <= rest_length {
+                    continue;
+                }
+                let (a_weight, b_weight) = (1.0 / arena[a].mass, 1.0 / arena[b].mass);
+                let offs = r.normalize_or_zero() * (dist - rest_length) * rigidity / (a_weight + b_weight);
+                arena[a].add_offs(offs * a_weight);
+                arena[b].add_offs(-offs * b_weight);
+            }
+        }
+
+        for (i, pair) in picks.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            let moved_from = arena[chain.nodes[from]].pos - before[i];
+            let moved_to = arena[chain.nodes[to]].pos - before[i + 1];
+            let span = chain.along[to] - chain.along[from];
+            for k in from + 1..to {
+                let t = if span > 0.0 { (chain.along[k] - chain.along[from]) / span } else { 0.5 };
+                arena[chain.nodes[k]].add_offs(moved_from.lerp(moved_to, t));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+    use crate::TARGET_DIST;
+
+    #[test]
+    fn long_chains_stretch_less_with_coarse_levels() {
+        let hang = |multigrid: Option<Multigrid>| {
+            let mut world = World::new(100_000.0);
+            world.params.solver_iterations = 5;
+            world.params.break_threshold = 100.0;
+            world.multigrid = multigrid;
+            world.add_rope(Vec2::new(100.0, 100.0), 200);
+            let end = world.arena.len() - 1;
+            world.arena[end].mass = 20.0;
+            let dt = world.params.dt;
+            for _ in 0..300 {
+                world.step(dt);
+            }
+            world.arena[end].pos.y - world.arena[0].pos.y
+        };
+
+        let rope = 199.0 * TARGET_DIST;
+        let (plain, coarsened) = (hang(None), hang(Some(Multigrid::default())));
+        assert!(coarsened < plain - 0.1 * rope, "{} {}", coarsened, plain);
+        assert!(coarsened < 1.2 * rope, "{}", coarsened);
+
+        // a rope is one chain, and a junction splits chains where it joins them
+        let mut world = World::new(10_000.0);
+        world.add_rope(Vec2::ZERO, 20);
+        assert_eq!(Multigrid::chains(&world.constraints, world.arena.len())[0].nodes.len(), 20);
+        world.add_fringe(&[10], 10, TARGET_DIST);
+        let mut lengths: Vec<usize> = Multigrid::chains(&world.constraints, world.arena.len())
+            .iter()
+            .map(|chain| chain.nodes.len())
+            .collect();
+        lengths.sort();
+        assert_eq!(lengths, [10, 11, 11]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::constraint::Constraint;
+use crate::math;
+
+/// A link that tightens and slackens on its own, like a muscle: the rest
+/// length of the constraint between its nodes swings sinusoidally either
+/// side of `rest_length`. Muscles working out of step with each other are
+/// what make a body crawl.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Muscle {
+    /// The nodes whose link it drives. It does nothing while they aren't linked.
+    pub a: usize,
+    pub b: usize,
+    /// The link's rest length halfway through each swing.
+    pub rest_length: f32,
+    /// How far the rest length swings either side of `rest_length`, as a fraction of it.
+    pub amplitude: f32,
+    /// Cycles per unit of time.
+    pub frequency: f32,
+    /// Fraction of a cycle it's ahead of a muscle with no phase, so muscles can take turns.
+    pub phase: f32,
+    /// Cycles so far. Frequency changes only change how fast this grows, so the link never jumps.
+    pub cycles: f32,
+}
+
+impl Muscle {
+    pub fn new(a: usize, b: usize, rest_length: f32, amplitude: f32, frequency: f32, phase: f32) -> Self {
+        Self { a, b, rest_length, amplitude, frequency, phase, cycles: 0.0 }
+    }
+
+    /// Where it is in its swing, from -1 fully contracted to 1 fully stretched.
+    pub fn signal(&self) -> f32 {
+        math::sin((self.cycles + self.phase) * std::f32::consts::TAU)
+    }
+
+    /// The rest length it holds its link at now.
+    pub fn length(&self) -> f32 {
+        self.rest_length * (1.0 + self.amplitude * self.signal())
+    }
+
+    /// Moves `dt` further through the swing and sets the rest length of the
+    /// link between its nodes to match.
+    pub fn flex(&mut self, constraints: &mut [Constraint], dt: f32) {
+        self.cycles += self.frequency * dt;
+        let length = self.length();
+        let (a, b) = (self.a, self.b);
+        let linked = |c: &&mut Constraint| (c.a, c.b) == (a, b) || (c.b, c.a) == (a, b);
+        for constraint in constraints.iter_mut().filter(linked) {
+            constraint.rest_length = length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+    use crate::world::World;
+
+    #[test]
+    fn swings_its_link_and_takes_turns_to_crawl() {
+        let mut muscle = Muscle::new(0, 1, 100.0, 0.2, 0.25, 0.0);
+        let mut constraints = vec![Constraint { a: 1, b: 0, rest_length: 100.0, break_threshold: 500.0 }];
+        // a quarter of a cycle in, it's fully stretched
+        muscle.flex(&mut constraints, 1.0);
+        assert!((constraints[0].rest_length - 120.0).abs() < 1e-3, "{}", constraints[0].rest_length);
+        muscle.flex(&mut constraints, 2.0);
+        assert!((constraints[0].rest_length - 80.0).abs() < 1e-3, "{}", constraints[0].rest_length);
+
+        // the crawler heads right, and back the other way with its legs' turns swapped
+        let crawled = |mut world: World| {
+            let start = world.arena[0].pos.x;
+            for _ in 0..2000 {
+                world.step(world.params.dt);
+            }
+            world.arena[0].pos.x - start
+        };
+        let world = scene::crawler(1280.0, 720.0);
+        let mut backward = world.clone();
+        for muscle in backward.muscles.iter_mut().skip(1) {
+            muscle.phase = 1.0 - muscle.phase;
+        }
+        let (forward, backward) = (crawled(world), crawled(backward));
+        assert!(forward > 5.0 * crate::TARGET_DIST, "{}", forward);
+        assert!(backward < -5.0 * crate::TARGET_DIST, "{}", backward);
+    }
+}
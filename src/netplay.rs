@@ -0,0 +1,606 @@
+//! Collaborative sessions, where several windows share one simulation.
+//!
+//! Stepping is deterministic, so like replays, peers only need to agree on the
+//! starting scene and everyone's input. Each peer stamps its input for
+//! `INPUT_DELAY` steps ahead and sends it to the host, which relays it to the
+//! other clients. A peer runs step N once it has every peer's input for N, so
+//! everyone waits for the slowest. The host restarts the scene whenever someone
+//! joins, sending it along so clients don't need the same files.
+//!
+//! Messages are lines of text:
+//!
+//! - `restart <session> <your id> <peer ids, comma-separated> <seed> <params...> <lines>`,
+//!   followed by that many lines of the scene as written by `scene::to_text`
+//! - `input <session> <peer> <step> <x> <y> <wind 0/1> <knife 0/1> <knife radius> <single cut 0/1>
+//!   <split nodes 0/1> <winch> <dispense 0/1> <push radius> <wind radius> <wind strength>
+//!   <wind falloff>`
+//! - `leave <session> <peer> <step>`, after which the peer's cursor does nothing
+//!
+//! A restart with params the solver can't run with is refused like any other bad line.
+
+use crate::cursor::CursorHistory;
+use crate::error::SimError;
+use crate::replay::StepInput;
+use clothsim::{scene, Param, Params, WindBrush, World};
+use egui_macroquad::macroquad::prelude::Vec2;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use tracing::{info, warn};
+
+/// Steps between sampling input and running the step it's for, which covers
+/// the round trip through the host at two steps per frame.
+const INPUT_DELAY: u64 = 6;
+const HOST_ID: u32 = 0;
+
+/// A peer's side of a session: its connections and everyone's pending input.
+pub struct Session {
+    /// Address hosted on or joined.
+    addr: String,
+    link: Link,
+    /// Bumped by every restart, so input sent before one is ignored.
+    session: u32,
+    id: u32,
+    /// Everyone in the session, this peer included, in id order.
+    peers: Vec<Peer>,
+    /// What each peer's cursor did on earlier steps, parallel to `peers`.
+    cursors: Vec<CursorHistory>,
+    /// Next step to send this peer's own input for.
+    next_send: u64,
+    /// False on a client until the host's first restart.
+    started: bool,
+    /// Whether the last step had to wait for someone's input.
+    waiting: bool,
+}
+
+enum Link {
+    Host {
+        new_clients: Receiver<TcpStream>,
+        clients: Vec<(u32, Connection)>,
+        next_id: u32,
+    },
+    Client(Connection),
+}
+
+struct Peer {
+    id: u32,
+    /// Input for upcoming steps, in step order.
+    inputs: VecDeque<(u64, StepInput)>,
+    /// Step after the last input queued.
+    received: u64,
+    /// First step the peer has no input for, once it's gone.
+    left_at: Option<u64>,
+    last_input: StepInput,
+}
+
+impl Peer {
+    fn new(id: u32) -> Self {
+        Self {
+            id,
+            inputs: VecDeque::new(),
+            received: 0,
+            left_at: None,
+            last_input: StepInput {
+                cursor: Vec2::ZERO,
+                wind: false,
+                wind_brush: WindBrush::default(),
+                knife: false,
+                knife_radius: 0.0,
+                single_cut: false,
+                split_nodes: false,
+                winch: 0.0,
+                dispense: false,
+                push_radius: 0.0,
+            },
+        }
+    }
+}
+
+enum Message {
+    Restart { session: u32, you: u32, peers: Vec<u32>, seed: u64, world: Box<World> },
+    Input { session: u32, peer: u32, step: u64, input: StepInput },
+    Leave { session: u32, peer: u32, step: u64 },
+}
+
+impl Session {
+    /// Listens for clients on `addr`. The host plays alone until someone joins.
+    pub fn host(addr: &str) -> Result<Self, SimError> {
+        let error = |source| SimError::Io { action: "listen on", path: addr.to_string(), source };
+        let listener = TcpListener::bind(addr).map_err(error)?;
+        info!("hosting a session on {}", listener.local_addr().map_err(error)?);
+
+        let (sender, new_clients) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if sender.send(stream).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => warn!("failed to accept a peer: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            addr: addr.to_string(),
+            link: Link::Host { new_clients, clients: Vec::new(), next_id: HOST_ID + 1 },
+            session: 0,
+            id: HOST_ID,
+            peers: vec![Peer::new(HOST_ID)],
+            cursors: vec![CursorHistory::default()],
+            next_send: 0,
+            started: true,
+            waiting: false,
+        })
+    }
+
+    /// Connects to the host at `addr`. Nothing steps until the host sends the scene.
+    pub fn join(addr: &str) -> Result<Self, SimError> {
+        let error = |source| SimError::Io { action: "connect to", path: addr.to_string(), source };
+        let stream = TcpStream::connect(addr).map_err(error)?;
+        Ok(Self {
+            addr: addr.to_string(),
+            link: Link::Client(Connection::new(stream).map_err(error)?),
+            session: 0,
+            id: HOST_ID,
+            peers: Vec::new(),
+            cursors: Vec::new(),
+            next_send: 0,
+            started: false,
+            waiting: false,
+        })
+    }
+
+    /// One line about the session for the HUD.
+    pub fn status(&self) -> String {
+        let peers = self.peers.iter().filter(|peer| peer.left_at.is_none()).count();
+        let status = match (&self.link, self.started) {
+            (Link::Host { .. }, _) => format!("hosting on {} for {} peers", self.addr, peers - 1),
+            (Link::Client(_), false) => format!("joining {}", self.addr),
+            (Link::Client(_), true) => format!("peer {} of {} at {}", self.id, peers, self.addr),
+        };
+        if self.waiting {
+            status + ", waiting for input"
+        } else {
+            status
+        }
+    }
+
+    /// Handles what the other peers sent, returning the world and seed to start
+    /// over from when the session restarts. A host restarts with `fresh_scene`
+    /// and `seed` whenever someone joins.
+    pub fn poll(
+        &mut self,
+        fresh_scene: impl FnOnce() -> Result<World, SimError>,
+        seed: u64,
+    ) -> Result<Option<(World, u64)>, SimError> {
+        match self.link {
+            Link::Host { .. } => self.poll_host(fresh_scene, seed),
+            Link::Client(_) => self.poll_client(),
+        }
+    }
+
+    fn poll_host(
+        &mut self,
+        fresh_scene: impl FnOnce() -> Result<World, SimError>,
+        seed: u64,
+    ) -> Result<Option<(World, u64)>, SimError> {
+        let Link::Host { new_clients, clients, next_id } = &mut self.link else {
+            unreachable!("only called on hosts");
+        };
+
+        let mut joined = false;
+        while let Ok(stream) = new_clients.try_recv() {
+            let addr = stream.peer_addr().map_or_else(|_| "a peer".to_string(), |addr| addr.to_string());
+            match Connection::new(stream) {
+                Ok(connection) => {
+                    info!("peer {} joined from {}", next_id, addr);
+                    clients.push((*next_id, connection));
+                    *next_id += 1;
+                    joined = true;
+                }
+                Err(e) => warn!("failed to set up the connection from {}: {}", addr, e),
+            }
+        }
+
+        let mut relayed = Vec::new();
+        let mut left = Vec::new();
+        for (id, connection) in clients.iter() {
+            loop {
+                match connection.incoming.try_recv() {
+                    // a client only speaks for itself, and only about the current session
+                    Ok(Message::Input { session, peer, step, input }) if session == self.session && peer == *id => {
+                        relayed.push((peer, step, input));
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        left.push(*id);
+                        break;
+                    }
+                }
+            }
+        }
+
+        for (peer, step, input) in relayed {
+            let message = Message::Input { session: self.session, peer, step, input };
+            for (_, connection) in clients.iter().filter(|(id, _)| *id != peer) {
+                connection.send(&message);
+            }
+            queue(&mut self.peers, peer, step, input);
+        }
+
+        for id in left {
+            info!("peer {} left", id);
+            clients.retain(|(client, _)| *client != id);
+            if let Some(peer) = self.peers.iter_mut().find(|peer| peer.id == id && peer.left_at.is_none()) {
+                peer.left_at = Some(peer.received);
+                let message = Message::Leave { session: self.session, peer: id, step: peer.received };
+                for (_, connection) in clients.iter() {
+                    connection.send(&message);
+                }
+            }
+        }
+
+        if !joined {
+            return Ok(None);
+        }
+        let world = fresh_scene()?;
+        self.restart(&world, seed);
+        Ok(Some((world, seed)))
+    }
+
+    /// Starts everyone over from `world`, with whoever is connected now.
+    fn restart(&mut self, world: &World, seed: u64) {
+        let Link::Host { clients, .. } = &self.link else {
+            unreachable!("only hosts restart");
+        };
+        self.session += 1;
+        let ids: Vec<u32> = std::iter::once(HOST_ID).chain(clients.iter().map(|(id, _)| *id)).collect();
+        for (id, connection) in clients.iter() {
+            connection.send(&Message::Restart {
+                session: self.session,
+                you: *id,
+                peers: ids.clone(),
+                seed,
+                world: Box::new(world.clone()),
+            });
+        }
+        self.start(ids);
+    }
+
+    fn poll_client(&mut self) -> Result<Option<(World, u64)>, SimError> {
+        let Link::Client(host) = &self.link else {
+            unreachable!("only called on clients");
+        };
+
+        let mut messages = Vec::new();
+        loop {
+            match host.incoming.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(SimError::Disconnected { addr: self.addr.clone() }),
+            }
+        }
+
+        let mut restart = None;
+        for message in messages {
+            match message {
+                Message::Restart { session, you, peers, seed, world } => {
+                    self.session = session;
+                    self.id = you;
+                    self.start(peers);
+                    self.started = true;
+                    restart = Some((*world, seed));
+                }
+                Message::Input { session, peer, step, input } if session == self.session => {
+                    queue(&mut self.peers, peer, step, input);
+                }
+                Message::Leave { session, peer, step } if session == self.session => {
+                    if let Some(peer) = self.peers.iter_mut().find(|p| p.id == peer) {
+                        peer.left_at = Some(step);
+                    }
+                }
+                Message::Input { .. } | Message::Leave { .. } => {}
+            }
+        }
+        Ok(restart)
+    }
+
+    fn start(&mut self, ids: Vec<u32>) {
+        self.peers = ids.into_iter().map(Peer::new).collect();
+        self.cursors = vec![CursorHistory::default(); self.peers.len()];
+        self.next_send = 0;
+    }
+
+    /// Sends this peer's `input` for `INPUT_DELAY` steps after `step`, once per step.
+    /// The first steps after a restart get the cursor without wind or the knife.
+    pub fn send_input(&mut self, step: u64, input: StepInput) {
+        if !self.started {
+            return;
+        }
+        while self.next_send <= step + INPUT_DELAY {
+            let stamped = self.next_send;
+            let input = if stamped < INPUT_DELAY { calm(input) } else { input };
+            let message = Message::Input { session: self.session, peer: self.id, step: stamped, input };
+            match &self.link {
+                Link::Host { clients, .. } => clients.iter().for_each(|(_, connection)| connection.send(&message)),
+                Link::Client(host) => host.send(&message),
+            }
+            queue(&mut self.peers, self.id, stamped, input);
+            self.next_send += 1;
+        }
+    }
+
+    /// What each peer's cursor did on earlier steps, for drawing knife trails.
+    pub fn cursors(&self) -> &[CursorHistory] {
+        &self.cursors
+    }
+
+    /// Takes every peer's input for `step`, or `None` if some haven't arrived yet,
+    /// along with each peer's cursor history for `step_world` to update.
+    /// Peers that left keep their last cursor, with wind and the knife off.
+    pub fn take_inputs(&mut self, step: u64) -> Option<(Vec<StepInput>, &mut [CursorHistory])> {
+        let ready = self.started
+            && self.peers.iter().all(|peer| {
+                peer.left_at.is_some_and(|left_at| step >= left_at)
+                    || peer.inputs.front().is_some_and(|&(next, _)| next == step)
+            });
+        self.waiting = !ready;
+        if !ready {
+            return None;
+        }
+
+        let inputs = self
+            .peers
+            .iter_mut()
+            .map(|peer| match peer.left_at {
+                Some(left_at) if step >= left_at => calm(peer.last_input),
+                _ => {
+                    let (_, input) = peer.inputs.pop_front().expect("checked above");
+                    peer.last_input = input;
+                    input
+                }
+            })
+            .collect();
+        Some((inputs, &mut self.cursors))
+    }
+}
+
+fn queue(peers: &mut [Peer], peer: u32, step: u64, input: StepInput) {
+    if let Some(peer) = peers.iter_mut().find(|p| p.id == peer) {
+        peer.inputs.push_back((step, input));
+        peer.received = step + 1;
+    }
+}
+
+/// `input` without wind, the knife, the winch, the dispensers, or pushing.
+fn calm(input: StepInput) -> StepInput {
+    StepInput { wind: false, knife: false, winch: 0.0, dispense: false, push_radius: 0.0, ..input }
+}
+
+/// A peer on the other end of a TCP stream, with a thread each for reading and writing.
+struct Connection {
+    outgoing: Sender<String>,
+    /// Disconnects when the peer goes away or sends something unreadable.
+    incoming: Receiver<Message>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let mut writer = stream.try_clone()?;
+
+        let (outgoing, to_send) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for text in to_send {
+                if writer.write_all(text.as_bytes()).is_err() {
+                    break;
+                }
+            }
+            // ends the reader too, whether writing failed or the connection was dropped
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+
+        let (received, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let mut lines = BufReader::new(stream).lines();
+            while let Some(Ok(line)) = lines.next() {
+                match parse(&line, &mut lines) {
+                    Ok(message) => {
+                        if received.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("unreadable message from a peer: {}", e);
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { outgoing, incoming })
+    }
+
+    /// Queues `message`. Failures show up as the peer disconnecting.
+    fn send(&self, message: &Message) {
+        let _ = self.outgoing.send(to_text(message));
+    }
+}
+
+fn to_text(message: &Message) -> String {
+    let mut out = String::new();
+    match message {
+        Message::Restart { session, you, peers, seed, world } => {
+            let peers: Vec<String> = peers.iter().map(u32::to_string).collect();
+            let p = &world.params;
+            let scene = scene::to_text(world);
+            writeln!(
+                out,
+                "restart {} {} {} {} {} {} {} {} {} {} {}",
+                session,
+                you,
+                peers.join(","),
+                seed,
+                p.dt,
+                p.gravity,
+                p.drag,
+                p.rigidity,
+                p.solver_iterations,
+                p.break_threshold,
+                scene.lines().count()
+            )
+            .unwrap();
+            out.push_str(&scene);
+        }
+        Message::Input { session, peer, step, input } => writeln!(
+            out,
+            "input {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            session,
+            peer,
+            step,
+            input.cursor.x,
+            input.cursor.y,
+            input.wind as u8,
+            input.knife as u8,
+            input.knife_radius,
+            input.single_cut as u8,
+            input.split_nodes as u8,
+            input.winch,
+            input.dispense as u8,
+            input.push_radius,
+            input.wind_brush.radius,
+            input.wind_brush.strength,
+            input.wind_brush.falloff
+        )
+        .unwrap(),
+        Message::Leave { session, peer, step } => writeln!(out, "leave {} {} {}", session, peer, step).unwrap(),
+    }
+    out
+}
+
+/// Parses the message starting at `line`, reading a restart's scene from `rest`.
+fn parse(line: &str, rest: &mut impl Iterator<Item = io::Result<String>>) -> Result<Message, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let field = |i: usize| words.get(i).copied().ok_or_else(|| format!("too few fields in {:?}", line));
+    let num = |i: usize| -> Result<f32, String> { field(i)?.parse().map_err(|_| format!("bad number in {:?}", line)) };
+    let int = |i: usize| -> Result<u64, String> { field(i)?.parse().map_err(|_| format!("bad integer in {:?}", line)) };
+    let id = |i: usize| -> Result<u32, String> { field(i)?.parse().map_err(|_| format!("bad id in {:?}", line)) };
+    let flag = |i: usize| -> Result<bool, String> {
+        match field(i)? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(format!("expected 0 or 1 in {:?}", line)),
+        }
+    };
+
+    match words.first() {
+        Some(&"restart") => {
+            let peers = field(3)?
+                .split(',')
+                .map(|peer| peer.parse().map_err(|_| format!("bad peer list in {:?}", line)))
+                .collect::<Result<Vec<u32>, String>>()?;
+            let params = Params {
+                dt: num(5)?,
+                gravity: num(6)?,
+                drag: num(7)?,
+                rigidity: num(8)?,
+                solver_iterations: int(9)? as usize,
+                break_threshold: num(10)?,
+            };
+            Param::check(&params)?;
+            let mut text = String::new();
+            for _ in 0..int(11)? {
+                let line = rest.next().ok_or("the scene ended early")?.map_err(|e| e.to_string())?;
+                text.push_str(&line);
+                text.push('\n');
+            }
+            let mut world = scene::from_text(&text)?;
+            // the scene's break thresholds are explicit, so don't rescale them
+            world.params = params;
+            Ok(Message::Restart { session: id(1)?, you: id(2)?, peers, seed: int(4)?, world: Box::new(world) })
+        }
+        Some(&"input") => Ok(Message::Input {
+            session: id(1)?,
+            peer: id(2)?,
+            step: int(3)?,
+            input: StepInput {
+                cursor: Vec2::new(num(4)?, num(5)?),
+                wind: flag(6)?,
+                knife: flag(7)?,
+                knife_radius: num(8)?,
+                single_cut: flag(9)?,
+                split_nodes: flag(10)?,
+                winch: num(11)?,
+                dispense: flag(12)?,
+                push_radius: num(13)?,
+                wind_brush: WindBrush { radius: num(14)?, strength: num(15)?, falloff: num(16)? },
+            },
+        }),
+        Some(&"leave") => Ok(Message::Leave { session: id(1)?, peer: id(2)?, step: int(3)? }),
+        _ => Err(format!("unrecognized message {:?}", line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `text`, a message and whatever lines follow it.
+    fn parsed(text: &str) -> Result<Message, String> {
+        let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+        parse(first, &mut rest.lines().map(|line| Ok(line.to_string())))
+    }
+
+    #[test]
+    fn messages_survive_the_trip_and_bad_lines_are_refused() {
+        let mut world = World::new(500.0);
+        world.add_cloth(clothsim::Vec2::new(10.0, 20.0), 3, 2, 2);
+        world.params = Params { gravity: 3.5, solver_iterations: 7, ..Params::default() };
+        let world_sent = Box::new(world.clone());
+        let restart = Message::Restart { session: 4, you: 2, peers: vec![0, 2, 5], seed: 99, world: world_sent };
+        let Ok(Message::Restart { session, you, peers, seed, world: back }) = parsed(&to_text(&restart)) else {
+            panic!("the restart didn't parse");
+        };
+        assert_eq!((session, you, peers, seed, back.params), (4, 2, vec![0, 2, 5], 99, world.params));
+        assert_eq!(scene::to_text(&back), scene::to_text(&world));
+
+        let input = StepInput {
+            cursor: Vec2::new(1.5, -2.0),
+            wind: true,
+            wind_brush: WindBrush { radius: 40.0, strength: 2.0, falloff: 0.5 },
+            knife: false,
+            knife_radius: 3.0,
+            single_cut: true,
+            split_nodes: false,
+            winch: -0.25,
+            dispense: true,
+            push_radius: 0.0,
+        };
+        let text = to_text(&Message::Input { session: 4, peer: 5, step: 1234, input });
+        let Ok(Message::Input { session, peer, step, input: back }) = parsed(&text) else {
+            panic!("the input didn't parse");
+        };
+        assert_eq!((session, peer, step, back), (4, 5, 1234, input));
+        let text = to_text(&Message::Leave { session: 4, peer: 5, step: 1300 });
+        assert!(matches!(parsed(&text), Ok(Message::Leave { session: 4, peer: 5, step: 1300 })));
+
+        // cut short, garbled, or asking the solver for the impossible
+        let truncated = text.rsplit_once(' ').unwrap().0;
+        assert_eq!(parsed(truncated).err(), Some("too few fields in \"leave 4 5\"".to_string()));
+        assert!(parsed("hello there").is_err());
+        assert!(parsed("leave 4 five 1300").is_err());
+        assert!(parsed("input 4 5 1 0 0 2 0 0 0 0 0 0 0 0 0 0").is_err());
+        let scene = to_text(&restart);
+        let short: Vec<&str> = scene.lines().take(2).collect();
+        assert_eq!(parsed(&short.join("\n")).err(), Some("the scene ended early".to_string()));
+        let unsolvable = scene.replacen(" 0.15 ", " 0 ", 1);
+        assert_eq!(parsed(&unsolvable).err(), Some("dt must be above 0, got 0".to_string()));
+    }
+}
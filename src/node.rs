@@ -0,0 +1,141 @@
+use crate::friction::Friction;
+use crate::verlet;
+use crate::{CONTACT_SLOP, NODE_RADIUS};
+use glam::Vec2;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Node {
+    pub last_pos: Vec2,
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub force: Vec2,
+    pub mass: f32,
+    pub fixed: bool,
+    /// Whether the node is at rest, as it is when made: cleared when it moves,
+    /// and set again, with a `NodeSlept` event, once it slows back down.
+    pub asleep: bool,
+    /// Water soaked up from rain, already counted in `mass`.
+    pub wetness: f32,
+    /// Fraction of its speed into the ground, a wall, or a rotor the node
+    /// bounces back out with, from 0 for a thud to 1 for not losing any.
+    pub restitution: f32,
+    /// The layer it's on, below `LAYERS`, which decides what it collides with and whether it's shown.
+    pub layer: u8,
+    /// Drag of its own on top of the params', per unit of speed, for light things like silk that float.
+    pub drag: f32,
+    /// How much of the params' gravity pulls on it: 1 for everything by default, 0 to float in place, and
+    /// negative to rise like a helium balloon.
+    pub gravity_scale: f32,
+    /// Electric charge, pushing away nodes of the same sign and pulling in the
+    /// opposite if the world has electrostatics on.
+    pub charge: f32,
+    /// Whether the world's magnet pushes on it.
+    pub magnetic: bool,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            pos: Default::default(),
+            last_pos: Default::default(),
+            vel: Default::default(),
+            force: Default::default(),
+            mass: 1.0,
+            fixed: Default::default(),
+            asleep: true,
+            wetness: Default::default(),
+            restitution: Default::default(),
+            layer: Default::default(),
+            drag: Default::default(),
+            gravity_scale: 1.0,
+            charge: Default::default(),
+            magnetic: false,
+        }
+    }
+}
+
+impl Node {
+    pub fn with_pos_and_mass(pos: Vec2, mass: f32) -> Node {
+        Node {
+            pos,
+            last_pos: pos,
+            mass,
+            ..Node::default()
+        }
+    }
+
+    pub fn integrate(&mut self, dt: f32) {
+        if self.fixed {
+            return;
+        }
+
+        verlet::integrate(&mut self.pos, &mut self.last_pos, &mut self.vel, self.force, self.mass, dt);
+    }
+
+    pub fn differentiate(&mut self, dt: f32) {
+        if self.fixed {
+            return;
+        }
+
+        self.vel = verlet::velocity(self.pos, self.last_pos, dt);
+        self.force = Vec2::ZERO;
+    }
+
+    pub fn apply_gravity(&mut self, gravity: f32) {
+        if self.fixed {
+            return;
+        }
+
+        self.force += Vec2::new(0.0, self.weight(gravity));
+    }
+
+    /// The downward pull of `gravity` on the node, upward if its gravity scale is negative.
+    pub fn weight(&self, gravity: f32) -> f32 {
+        gravity * self.gravity_scale * self.mass
+    }
+
+    pub fn apply_drag(&mut self, drag: f32) {
+        if self.fixed {
+            return;
+        }
+
+        self.force += -self.vel * drag;
+    }
+
+    /// Keeps the node above `ground_y`, with `friction` against sliding along it.
+    pub fn collide_ground(&mut self, ground_y: f32, friction: &Friction) {
+        let depth = self.pos.y - (ground_y - NODE_RADIUS);
+        if self.fixed || depth <= 0.0 {
+            return;
+        }
+        self.pos.y = ground_y - NODE_RADIUS;
+        self.pos -= friction.grip(self.pos - self.last_pos, Vec2::NEG_Y, depth);
+    }
+
+    /// Sends the node back up if it's on the ground at `ground_y` and was
+    /// falling into it, with `restitution` of the speed it hit with. Like
+    /// `Walls::bounce`, it runs after the velocity is worked out and takes the
+    /// velocity from before the step's corrections as `incoming`.
+    pub fn bounce_ground(&mut self, ground_y: f32, incoming: Vec2) {
+        let on_ground = self.pos.y >= ground_y - NODE_RADIUS - CONTACT_SLOP;
+        if self.fixed || self.restitution == 0.0 || incoming.y <= 0.0 || !on_ground {
+            return;
+        }
+        self.vel.y = -incoming.y * self.restitution;
+    }
+
+    /// How easily corrections move the node: none at all if it's fixed.
+    pub fn inverse_mass(&self) -> f32 {
+        if self.fixed {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+
+    pub fn add_offs(&mut self, offs: Vec2) {
+        if !self.fixed {
+            self.pos += offs;
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Live control over OSC, so music software can drive the demo.
+//!
+//! Listens for UDP packets, on their own or in bundles, and understands these
+//! addresses, each with one number:
+//!
+//! - `/clothsim/wind`: a sideways gust on every node, from -1 (left) to 1 (right)
+//! - `/clothsim/gravity`: 0 to 1, from no gravity to twice the default
+//! - `/clothsim/time_scale`: 0 to 1, from a standstill to twice normal speed
+//! - `/clothsim/cut`: cuts straight across the view at that fraction of its height,
+//!   or the middle without a number. Zero is ignored, so a button's release doesn't cut.
+//!
+//! Numbers can be floats, ints, or doubles, and are clamped to their range.
+//! Messages with a NaN or infinite number are ignored.
+//! MIDI controllers work through any MIDI-to-OSC bridge.
+
+use std::io;
+use std::net::UdpSocket;
+use tracing::{info, warn};
+
+/// Largest packet read; OSC over UDP is usually far smaller.
+const MAX_PACKET: usize = 4096;
+
+/// A change requested over OSC.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Control {
+    Wind(f32),
+    Gravity(f32),
+    TimeScale(f32),
+    /// Where to cut, as a fraction of the view's height from the top.
+    Cut(f32),
+}
+
+pub struct OscListener {
+    socket: UdpSocket,
+}
+
+impl OscListener {
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        info!("listening for OSC on {}", socket.local_addr()?);
+        Ok(Self { socket })
+    }
+
+    /// Every control received since the last poll, in order.
+    pub fn poll(&self) -> Vec<Control> {
+        let mut controls = Vec::new();
+        let mut buf = [0; MAX_PACKET];
+        loop {
+            match self.socket.recv(&mut buf) {
+                Ok(len) => {
+                    if let Err(e) = decode(&buf[..len], &mut controls) {
+                        warn!("ignoring an OSC packet: {}", e);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return controls,
+                Err(e) => {
+                    warn!("failed to read OSC: {}", e);
+                    return controls;
+                }
+            }
+        }
+    }
+}
+
+/// Appends the controls in a message or bundle to `out`, skipping addresses it doesn't know.
+fn decode(packet: &[u8], out: &mut Vec<Control>) -> Result<(), String> {
+    let mut reader = Reader { bytes: packet };
+    let address = reader.string()?;
+
+    if address == "#bundle" {
+        reader.take(8)?; // the time tag; everything applies as soon as it arrives
+        while !reader.bytes.is_empty() {
+            let len = reader.int()?;
+            let len = usize::try_from(len).map_err(|_| format!("negative element size {}", len))?;
+            decode(reader.take(len)?, out)?;
+        }
+        return Ok(());
+    }
+
+    // messages from before type tags were standard have no arguments we can read
+    let tags = if reader.bytes.is_empty() { ",".to_string() } else { reader.string()? };
+    let mut args = Vec::new();
+    for tag in tags.chars().skip(1) {
+        match tag {
+            'f' => args.push(f32::from_be_bytes(reader.array()?)),
+            'i' => args.push(i32::from_be_bytes(reader.array()?) as f32),
+            'd' => args.push(f64::from_be_bytes(reader.array()?) as f32),
+            'T' => args.push(1.0),
+            'F' => args.push(0.0),
+            // everything else would need skipping by size, and nothing here uses it
+            _ => return Err(format!("unsupported argument type '{}' for {}", tag, address)),
+        }
+    }
+
+    // a NaN would get through the clamps below and into every node
+    if let Some(v) = args.iter().find(|v| !v.is_finite()) {
+        return Err(format!("{} isn't a usable number for {}", v, address));
+    }
+    let value = args.first().copied();
+    let control = match (address.as_str(), value) {
+        ("/clothsim/wind", Some(v)) => Control::Wind(v.clamp(-1.0, 1.0)),
+        ("/clothsim/gravity", Some(v)) => Control::Gravity(v.clamp(0.0, 1.0)),
+        ("/clothsim/time_scale", Some(v)) => Control::TimeScale(v.clamp(0.0, 1.0)),
+        ("/clothsim/cut", None) => Control::Cut(0.5),
+        ("/clothsim/cut", Some(v)) if v > 0.0 => Control::Cut(v.clamp(0.0, 1.0)),
+        _ => return Ok(()),
+    };
+    out.push(control);
+    Ok(())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("packet ended early".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn int(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    /// A null-terminated string, padded to a multiple of four bytes.
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.bytes.iter().position(|&b| b == 0).ok_or("unterminated string")?;
+        let text = std::str::from_utf8(&self.bytes[..len]).map_err(|_| "string isn't UTF-8")?.to_string();
+        self.take((len + 4) & !3)?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` null-terminated and padded to four bytes, as OSC strings are.
+    fn string(text: &str) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize((text.len() + 4) & !3, 0);
+        bytes
+    }
+
+    fn message(address: &str, tags: &str, args: &[u8]) -> Vec<u8> {
+        [string(address), string(tags), args.to_vec()].concat()
+    }
+
+    fn bundle(elements: &[Vec<u8>]) -> Vec<u8> {
+        let mut packet = [string("#bundle"), vec![0; 8]].concat();
+        for element in elements {
+            packet.extend((element.len() as i32).to_be_bytes());
+            packet.extend(element);
+        }
+        packet
+    }
+
+    fn decoded(packet: &[u8]) -> Result<Vec<Control>, String> {
+        let mut controls = Vec::new();
+        decode(packet, &mut controls).map(|()| controls)
+    }
+
+    #[test]
+    fn reads_messages_and_bundles_and_turns_away_bad_packets() {
+        let wind = message("/clothsim/wind", ",f", &2.5f32.to_be_bytes());
+        assert_eq!(decoded(&wind), Ok(vec![Control::Wind(1.0)]));
+        let gravity = message("/clothsim/gravity", ",i", &0i32.to_be_bytes());
+        assert_eq!(decoded(&gravity), Ok(vec![Control::Gravity(0.0)]));
+        assert_eq!(decoded(&message("/clothsim/cut", ",", &[])), Ok(vec![Control::Cut(0.5)]));
+        assert_eq!(decoded(&message("/clothsim/cut", ",F", &[])), Ok(vec![]));
+        assert_eq!(decoded(&message("/elsewhere", ",f", &1f32.to_be_bytes())), Ok(vec![]));
+
+        let scale = message("/clothsim/time_scale", ",d", &0.25f64.to_be_bytes());
+        assert_eq!(decoded(&bundle(&[wind.clone(), scale])), Ok(vec![Control::Wind(1.0), Control::TimeScale(0.25)]));
+
+        assert!(decoded(&wind[..wind.len() - 2]).is_err());
+        let mut negative = bundle(&[wind]);
+        negative[16..20].copy_from_slice(&(-4i32).to_be_bytes());
+        assert!(decoded(&negative).unwrap_err().contains("negative element size"));
+        assert!(decoded(&message("/clothsim/wind", ",s", &string("left"))).unwrap_err().contains("'s'"));
+        for nan in [f32::NAN, f32::INFINITY] {
+            assert!(decoded(&message("/clothsim/gravity", ",f", &nan.to_be_bytes())).is_err());
+        }
+        assert!(decoded(&message("/clothsim/wind", ",d", &f64::NAN.to_be_bytes())).is_err());
+    }
+}
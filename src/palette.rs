@@ -0,0 +1,185 @@
+use egui_macroquad::egui;
+use egui_macroquad::macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PaletteDef", into = "PaletteDef")]
+pub struct Palette {
+    pub background: Color,
+    pub rope: Color,
+    pub node: Color,
+    pub fixed_node: Color,
+    pub ground: Color,
+    pub text: Color,
+    /// Particles and UI highlights.
+    pub accent: Color,
+    /// Strain heatmap stops, from slack to about to break.
+    pub heatmap: [Color; 4],
+}
+
+/// `Palette` as plain RGBA arrays, which is how it's written to the settings file.
+#[derive(Serialize, Deserialize)]
+struct PaletteDef {
+    background: [f32; 4],
+    rope: [f32; 4],
+    node: [f32; 4],
+    fixed_node: [f32; 4],
+    ground: [f32; 4],
+    text: [f32; 4],
+    accent: [f32; 4],
+    heatmap: [[f32; 4]; 4],
+}
+
+impl From<Palette> for PaletteDef {
+    fn from(p: Palette) -> Self {
+        Self {
+            background: p.background.into(),
+            rope: p.rope.into(),
+            node: p.node.into(),
+            fixed_node: p.fixed_node.into(),
+            ground: p.ground.into(),
+            text: p.text.into(),
+            accent: p.accent.into(),
+            heatmap: p.heatmap.map(Into::into),
+        }
+    }
+}
+
+impl From<PaletteDef> for Palette {
+    fn from(p: PaletteDef) -> Self {
+        Self {
+            background: p.background.into(),
+            rope: p.rope.into(),
+            node: p.node.into(),
+            fixed_node: p.fixed_node.into(),
+            ground: p.ground.into(),
+            text: p.text.into(),
+            accent: p.accent.into(),
+            heatmap: p.heatmap.map(Into::into),
+        }
+    }
+}
+
+const CLASSIC: Palette = Palette {
+    background: BLACK,
+    rope: WHITE,
+    node: WHITE,
+    fixed_node: RED,
+    ground: GRAY,
+    text: WHITE,
+    accent: Color::new(1.0, 0.8, 0.3, 1.0),
+    heatmap: [BLUE, GREEN, YELLOW, RED],
+};
+
+/// Okabe-Ito colors for nodes and a viridis heatmap, which stay distinguishable
+/// under the common forms of color blindness.
+const COLORBLIND_SAFE: Palette = Palette {
+    background: Color::new(0.08, 0.08, 0.1, 1.0),
+    rope: Color::new(0.9, 0.9, 0.9, 1.0),
+    node: Color::new(0.34, 0.71, 0.91, 1.0),
+    fixed_node: Color::new(0.9, 0.62, 0.0, 1.0),
+    ground: Color::new(0.6, 0.6, 0.6, 1.0),
+    text: WHITE,
+    accent: Color::new(0.9, 0.62, 0.0, 1.0),
+    heatmap: [
+        Color::new(0.27, 0.0, 0.33, 1.0),
+        Color::new(0.23, 0.32, 0.55, 1.0),
+        Color::new(0.13, 0.57, 0.55, 1.0),
+        Color::new(0.99, 0.91, 0.14, 1.0),
+    ],
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    background: BLACK,
+    rope: WHITE,
+    node: WHITE,
+    fixed_node: Color::new(1.0, 1.0, 0.0, 1.0),
+    ground: WHITE,
+    text: WHITE,
+    accent: Color::new(0.0, 1.0, 1.0, 1.0),
+    heatmap: [
+        WHITE,
+        Color::new(1.0, 1.0, 0.0, 1.0),
+        Color::new(1.0, 0.5, 0.0, 1.0),
+        Color::new(1.0, 0.0, 1.0, 1.0),
+    ],
+};
+
+pub const PRESETS: [(&str, Palette); 3] = [
+    ("Classic", CLASSIC),
+    ("Colorblind safe", COLORBLIND_SAFE),
+    ("High contrast", HIGH_CONTRAST),
+];
+
+impl Default for Palette {
+    fn default() -> Self {
+        CLASSIC
+    }
+}
+
+impl Palette {
+    /// Interpolates the heatmap stops for a break fraction in [0, 1].
+    pub fn heat(&self, strain: f32) -> Color {
+        let stops = &self.heatmap;
+        let pos = strain.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (pos as usize).min(stops.len() - 2);
+        let t = pos - i as f32;
+        let (from, to) = (stops[i], stops[i + 1]);
+        Color::new(
+            from.r + (to.r - from.r) * t,
+            from.g + (to.g - from.g) * t,
+            from.b + (to.b - from.b) * t,
+            from.a + (to.a - from.a) * t,
+        )
+    }
+
+    /// `color` with its alpha replaced.
+    pub fn with_alpha(color: Color, alpha: f32) -> Color {
+        Color { a: alpha, ..color }
+    }
+
+    /// Color pickers for every entry, for the appearance panel.
+    pub fn edit(&mut self, ui: &mut egui::Ui) {
+        fn color_row(ui: &mut egui::Ui, label: &str, color: &mut Color) {
+            ui.horizontal(|ui| {
+                let mut rgba: [f32; 4] = (*color).into();
+                if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                    *color = rgba.into();
+                }
+                ui.label(label);
+            });
+        }
+
+        color_row(ui, "Background", &mut self.background);
+        color_row(ui, "Rope", &mut self.rope);
+        color_row(ui, "Node", &mut self.node);
+        color_row(ui, "Fixed node", &mut self.fixed_node);
+        color_row(ui, "Ground", &mut self.ground);
+        color_row(ui, "Text", &mut self.text);
+        color_row(ui, "Accent", &mut self.accent);
+
+        ui.label("Heatmap, slack to breaking");
+        ui.horizontal(|ui| {
+            for stop in self.heatmap.iter_mut() {
+                let mut rgba: [f32; 4] = (*stop).into();
+                if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                    *stop = rgba.into();
+                }
+            }
+        });
+    }
+
+    /// Tints egui's selection and hover highlights with the accent color.
+    pub fn apply_to_egui(&self, ctx: &egui::Context) {
+        let [r, g, b, _]: [u8; 4] = self.accent.into();
+        let accent = egui::Color32::from_rgb(r, g, b);
+
+        let mut visuals = egui::Visuals::dark();
+        visuals.selection.bg_fill = accent.linear_multiply(0.6);
+        visuals.selection.stroke.color = accent;
+        visuals.hyperlink_color = accent;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_stroke.color = accent;
+        ctx.set_visuals(visuals);
+    }
+}
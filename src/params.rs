@@ -0,0 +1,28 @@
+use crate::{BREAK_THRESHOLD, DRAG, DT, G, RIGIDITY, SOLVER_ITERATIONS};
+
+/// Physics constants that can be changed while running. The defaults are the
+/// crate-level constants of the same names.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Params {
+    pub dt: f32,
+    pub gravity: f32,
+    pub drag: f32,
+    /// Fraction of each constraint's error corrected per solver iteration.
+    pub rigidity: f32,
+    pub solver_iterations: usize,
+    /// Length at which new constraints break, in rest lengths.
+    pub break_threshold: f32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            dt: DT,
+            gravity: G,
+            drag: DRAG,
+            rigidity: RIGIDITY,
+            solver_iterations: SOLVER_ITERATIONS,
+            break_threshold: BREAK_THRESHOLD,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! `--profile-out`: a fixed run of the starting scene, timing each phase of
+//! every frame and writing them out as JSON, for comparing performance
+//! between commits.
+//!
+//! The run is the same every time: the scene and seed from the command line,
+//! no input, and `--profile-frames` frames of `STEPS_PER_FRAME` steps each,
+//! drawn the way the demo draws the world but without its UI. Each frame's
+//! time is split into
+//! - `forces`: moving anchors, gravity and drag, wind, rain, and sand,
+//! - `integrate`: moving nodes on by their velocity, and working the
+//!   velocity back out after the solve,
+//! - `collide`: stopping fast nodes at obstacles, balls smashing links, and
+//!   sinks swallowing nodes,
+//! - `solve`: the solver iterations, which push nodes out of the ground,
+//!   the walls, and each other as they go, breaking overloaded links, and
+//!   measuring tensions,
+//! - `draw`: drawing the world, up to handing it to the GPU.
+
+use crate::cli::Cli;
+use crate::main_state::STEPS_PER_FRAME;
+use crate::materials::RopeMaterials;
+use crate::palette::Palette;
+use crate::renderer::MacroquadRenderer;
+use clothsim::{Rng, SimRenderer};
+use egui_macroquad::macroquad::prelude::*;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Phase {
+    Forces,
+    Integrate,
+    Collide,
+    Solve,
+    Draw,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [Phase::Forces, Phase::Integrate, Phase::Collide, Phase::Solve, Phase::Draw];
+
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Forces => "forces",
+            Phase::Integrate => "integrate",
+            Phase::Collide => "collide",
+            Phase::Solve => "solve",
+            Phase::Draw => "draw",
+        }
+    }
+
+    /// Its name on screen, like "Solving".
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::Forces => "Forces",
+            Phase::Integrate => "Integration",
+            Phase::Collide => "Collisions",
+            Phase::Solve => "Solving",
+            Phase::Draw => "Drawing",
+        }
+    }
+}
+
+/// Seconds spent in each phase over a frame, by `Phase`.
+pub type FrameTimes = [f64; Phase::ALL.len()];
+
+/// Adds how long `f` takes to `times[phase]`.
+fn timed<T>(times: &mut FrameTimes, phase: Phase, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let out = f();
+    times[phase as usize] += start.elapsed().as_secs_f64();
+    out
+}
+
+/// Runs the profile and writes it to `path`, returning the process exit code.
+pub async fn run(cli: &Cli, path: &Path) -> i32 {
+    let (width, height) = (screen_width(), screen_height());
+    let world = cli.params().and_then(|params| cli.scene_source().build(width, height, params));
+    let mut world = match world {
+        Ok(world) => world,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    world.rng = Rng::new(cli.seed);
+
+    let (palette, materials) = (Palette::default(), RopeMaterials::default());
+    let view = Rect::new(0.0, 0.0, width, height);
+    let mut frames = Vec::with_capacity(cli.profile_frames);
+    for _ in 0..cli.profile_frames {
+        let mut times = FrameTimes::default();
+        for _ in 0..STEPS_PER_FRAME {
+            let dt = world.params.dt;
+            timed(&mut times, Phase::Forces, || {
+                world.move_anchors(dt);
+                world.apply_forces();
+                world.blow(dt);
+                world.rain(dt);
+                world.pour_sand(dt);
+            });
+            timed(&mut times, Phase::Integrate, || world.integrate(dt));
+            timed(&mut times, Phase::Collide, || {
+                world.sweep();
+                world.smash();
+            });
+            timed(&mut times, Phase::Solve, || {
+                world.solve_constraints();
+                world.break_overloaded(dt);
+            });
+            timed(&mut times, Phase::Integrate, || world.differentiate(dt));
+            timed(&mut times, Phase::Collide, || {
+                world.consume_sunk();
+                world.clean_up(dt);
+            });
+            timed(&mut times, Phase::Solve, || world.measure_tensions());
+            world.drain_events();
+        }
+
+        timed(&mut times, Phase::Draw, || {
+            set_camera(&Camera2D::from_display_rect(view));
+            clear_background(palette.background);
+            let mut renderer = MacroquadRenderer::new(&palette, &materials, view);
+            renderer.draw_world(&world);
+            renderer.flush();
+            set_default_camera();
+        });
+        frames.push(times);
+        next_frame().await;
+    }
+
+    let json = to_json(cli, &frames, world.checksum());
+    match std::fs::write(path, json) {
+        Ok(()) => {
+            let frame_ms = |times: &FrameTimes| 1000.0 * times.iter().sum::<f64>();
+            let mean = frames.iter().map(frame_ms).sum::<f64>() / frames.len().max(1) as f64;
+            println!("{} frames, {:.3} ms each on average, written to {}", frames.len(), mean, path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("failed to write {}: {}", path.display(), e);
+            1
+        }
+    }
+}
+
+/// Milliseconds: the total, mean, median, 95th percentile, and worst of `times`, in seconds.
+fn summarize(times: &[f64]) -> [f64; 5] {
+    let mut sorted: Vec<f64> = times.iter().map(|time| 1000.0 * time).collect();
+    sorted.sort_by(f64::total_cmp);
+    let Some(&max) = sorted.last() else {
+        return [0.0; 5];
+    };
+    let at = |fraction: f64| sorted[((sorted.len() - 1) as f64 * fraction).round() as usize];
+    let total: f64 = sorted.iter().sum();
+    [total, total / sorted.len() as f64, at(0.5), at(0.95), max]
+}
+
+/// The run's settings, the final checksum so two profiles can be checked
+/// for having run the same thing, a summary of each phase, and every
+/// frame's time in each phase, in milliseconds.
+fn to_json(cli: &Cli, frames: &[FrameTimes], checksum: u64) -> String {
+    let mut out = String::new();
+    write!(
+        out,
+        r#"{{"scene":"{}","seed":{},"frames":{},"steps_per_frame":{},"checksum":"{:016x}","summary":{{"#,
+        cli.scene_source().to_string().replace('\\', "\\\\").replace('"', "\\\""),
+        cli.seed,
+        frames.len(),
+        STEPS_PER_FRAME,
+        checksum
+    )
+    .unwrap();
+    let phase_times = |phase: Phase| -> Vec<f64> { frames.iter().map(|times| times[phase as usize]).collect() };
+    for (i, phase) in Phase::ALL.into_iter().enumerate() {
+        let [total, mean, median, p95, max] = summarize(&phase_times(phase));
+        write!(
+            out,
+            r#"{}"{}":{{"total_ms":{},"mean_ms":{},"median_ms":{},"p95_ms":{},"max_ms":{}}}"#,
+            if i == 0 { "" } else { "," },
+            phase.name(),
+            total,
+            mean,
+            median,
+            p95,
+            max
+        )
+        .unwrap();
+    }
+    out.push_str(r#"},"per_frame_ms":{"#);
+    for (i, phase) in Phase::ALL.into_iter().enumerate() {
+        let times: Vec<String> = phase_times(phase).iter().map(|time| (1000.0 * time).to_string()).collect();
+        write!(out, r#"{}"{}":[{}]"#, if i == 0 { "" } else { "," }, phase.name(), times.join(",")).unwrap();
+    }
+    out.push_str("}}\n");
+    out
+}
@@ -0,0 +1,76 @@
+use crate::anchor::AnchorPath;
+use crate::node::Node;
+use glam::Vec2;
+
+/// A rectangle moved along a path, like an elevator or a conveyor, carrying the
+/// fixed nodes pinned to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Platform {
+    /// Where the platform's center goes, as for an `Anchor`.
+    pub path: AnchorPath,
+    pub speed: f32,
+    pub travelled: f32,
+    /// Width and height.
+    pub size: Vec2,
+    /// Nodes pinned to the platform and where they sit relative to its center.
+    pub riders: Vec<(usize, Vec2)>,
+}
+
+impl Platform {
+    pub fn new(path: AnchorPath, speed: f32, size: Vec2) -> Self {
+        Self { path, speed, travelled: 0.0, size, riders: Vec::new() }
+    }
+
+    /// Where the platform's center is now.
+    pub fn position(&self) -> Vec2 {
+        self.path.at(self.travelled)
+    }
+
+    /// Whether `p` is on the platform.
+    pub fn contains(&self, p: Vec2) -> bool {
+        let offset = (p - self.position()).abs();
+        offset.x <= self.size.x / 2.0 && offset.y <= self.size.y / 2.0
+    }
+
+    /// Moves `dt` further along the path, taking the riders along with the
+    /// platform's velocity. Riders that have been unpinned since are left to fall
+    /// with whatever velocity they were last given.
+    pub fn advance(&mut self, arena: &mut [Node], dt: f32) {
+        self.travelled += self.speed * dt;
+        let center = self.position();
+        for &(i, offset) in self.riders.iter() {
+            let node = &mut arena[i];
+            if !node.fixed {
+                continue;
+            }
+            let pos = center + offset;
+            node.last_pos = node.pos;
+            node.vel = (pos - node.pos) / dt;
+            node.pos = pos;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_pinned_riders_at_its_velocity() {
+        let mut arena = vec![
+            Node { fixed: true, ..Node::with_pos_and_mass(Vec2::new(10.0, 5.0), 1.0) },
+            Node::with_pos_and_mass(Vec2::new(0.0, 5.0), 1.0),
+        ];
+        let line = AnchorPath::Line(Vec2::ZERO, Vec2::new(0.0, 100.0));
+        let mut platform = Platform::new(line, 20.0, Vec2::new(40.0, 10.0));
+        platform.riders = vec![(0, Vec2::new(10.0, 5.0)), (1, Vec2::new(0.0, 5.0))];
+
+        platform.advance(&mut arena, 0.5);
+        assert_eq!(arena[0].pos, Vec2::new(10.0, 15.0));
+        assert_eq!(arena[0].vel, Vec2::new(0.0, 20.0));
+        // the second rider isn't pinned, so it's left alone
+        assert_eq!(arena[1].pos, Vec2::new(0.0, 5.0));
+        assert!(platform.contains(Vec2::new(-20.0, 14.0)));
+        assert!(!platform.contains(Vec2::new(0.0, 16.0)));
+    }
+}
@@ -0,0 +1,68 @@
+//! Cursors drawn for the tool in hand, in place of the system's arrow, so
+//! what a click or drag will do shows where the eye already is: a blade while
+//! cutting, a spinning fan for wind, an open hand for grabbing and pushing,
+//! and a pin over a node a right click would pin.
+
+use crate::palette::Palette;
+use egui_macroquad::macroquad::prelude::*;
+
+/// Size of a cursor, in screen pixels at a drawing scale of 1.
+pub const POINTER_SIZE: f32 = 18.0;
+const FAN_BLADES: usize = 3;
+/// Turns a second of the wind cursor's fan.
+const FAN_SPEED: f32 = 1.5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pointer {
+    Blade,
+    Fan,
+    Hand,
+    Pin,
+}
+
+impl Pointer {
+    /// Draws it at `pos` on screen, `size` across, pointing or centered
+    /// where a click lands. Expects screen space.
+    pub fn draw(self, pos: Vec2, size: f32, color: Color, outline: Color) {
+        let width = (size / 9.0).max(1.0);
+        match self {
+            Pointer::Blade => {
+                // the edge runs up and right from its point at the cursor to a handle
+                let (heel, back) = (pos + Vec2::new(size * 0.7, -size * 0.7), pos + Vec2::new(size * 0.45, -size));
+                draw_triangle(pos, heel, back, color);
+                draw_triangle_lines(pos, heel, back, width / 2.0, outline);
+                let grip = Vec2::new(size * 0.4, -size * 0.4);
+                let middle = (heel + back) / 2.0;
+                draw_line(middle.x, middle.y, middle.x + grip.x, middle.y + grip.y, width * 2.5, outline);
+            }
+            Pointer::Fan => {
+                let turn = get_time() as f32 * FAN_SPEED * std::f32::consts::TAU;
+                for blade in 0..FAN_BLADES {
+                    let angle = turn + blade as f32 * std::f32::consts::TAU / FAN_BLADES as f32;
+                    let along = Vec2::new(angle.cos(), angle.sin()) * size / 2.0;
+                    let tip = pos + along;
+                    draw_triangle(pos, tip, tip + along.perp() * 0.6, Palette::with_alpha(color, 0.8));
+                }
+                draw_circle(pos.x, pos.y, width * 1.5, outline);
+                draw_circle_lines(pos.x, pos.y, size / 2.0, width / 2.0, Palette::with_alpha(outline, 0.4));
+            }
+            Pointer::Hand => {
+                let palm = size * 0.3;
+                for finger in 0..4 {
+                    let x = pos.x + (finger as f32 - 1.5) * palm * 0.6;
+                    draw_line(x, pos.y, x, pos.y - palm * 1.4, width * 1.8, color);
+                }
+                draw_line(pos.x - palm, pos.y + palm * 0.2, pos.x - palm * 1.6, pos.y - palm * 0.4, width * 1.8, color);
+                draw_circle(pos.x, pos.y + palm * 0.5, palm, color);
+                draw_circle_lines(pos.x, pos.y + palm * 0.5, palm, width / 2.0, outline);
+            }
+            Pointer::Pin => {
+                // its point is on the cursor and its head up and to the right
+                let head = pos + Vec2::new(size * 0.4, -size * 0.6);
+                draw_line(pos.x, pos.y, head.x, head.y, width, outline);
+                draw_circle(head.x, head.y, size * 0.25, color);
+                draw_circle_lines(head.x, head.y, size * 0.25, width / 2.0, outline);
+            }
+        }
+    }
+}
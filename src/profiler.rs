@@ -0,0 +1,41 @@
+//! Frame profiling with puffin, shown as a flame graph in an egui window.
+//! puffin can't tell the time in the browser, so everything here is a no-op there.
+
+use egui_macroquad::egui;
+
+/// Times the rest of the enclosing block as a scope named `$name`, if profiling is on.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(not(target_arch = "wasm32"))]
+        puffin::profile_scope!($name);
+    };
+}
+pub(crate) use profile_scope;
+
+/// Marks the start of a new frame. Call once per frame, outside any scope.
+pub fn new_frame() {
+    #[cfg(not(target_arch = "wasm32"))]
+    puffin::GlobalProfiler::lock().new_frame();
+}
+
+/// Turns scope recording on or off; it's off until the profiler window is first opened.
+pub fn set_enabled(enabled: bool) {
+    #[cfg(not(target_arch = "wasm32"))]
+    puffin::set_scopes_on(enabled);
+    #[cfg(target_arch = "wasm32")]
+    let _ = enabled;
+}
+
+/// Whether profiling works on this platform.
+pub const AVAILABLE: bool = cfg!(not(target_arch = "wasm32"));
+
+/// Shows the flame graph window, returning false once the user closes it.
+pub fn window(ctx: &egui::Context) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    return puffin_egui::profiler_window(ctx);
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = ctx;
+        false
+    }
+}
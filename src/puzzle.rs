@@ -0,0 +1,169 @@
+//! Puzzles solved with the knife alone: each level hangs a payload on ropes
+//! somewhere above a target, and the player cuts the ropes, at the right
+//! moments, to land it there.
+
+use crate::constraint::Constraint;
+use crate::event::SimEvent;
+use crate::node::Node;
+use crate::scene::GROUND_OFFSET;
+use crate::world::World;
+use crate::TARGET_DIST;
+use glam::Vec2;
+
+/// How long the payload has to stay in the target to count as landed.
+const SETTLE_TIME: f32 = 3.0;
+pub const PAYLOAD_MASS: f32 = 20.0;
+pub const PAYLOAD_RADIUS: f32 = 15.0;
+/// Height of the targets on the ground, enough to take the payload resting on it.
+const TARGET_HEIGHT: f32 = 4.0 * PAYLOAD_RADIUS;
+/// Length of the rope the payload swings on in `swing`.
+const SWING_LENGTH: f32 = 6.0 * TARGET_DIST;
+
+/// Builds a level for a `width` by `height` view.
+pub type LevelBuilder = fn(f32, f32) -> (World, Puzzle);
+
+/// Every level, in the order they're played.
+pub const LEVELS: &[(&str, LevelBuilder)] = &[("Drop", drop), ("Sideways", sideways), ("Swing", swing)];
+
+/// An upright rectangle the payload has to come to rest in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Target {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Target {
+    /// A target on the ground, from `left` to `right`.
+    fn on_ground(world: &World, left: f32, right: f32) -> Self {
+        Self { min: Vec2::new(left, world.ground_y - TARGET_HEIGHT), max: Vec2::new(right, world.ground_y) }
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.cmpge(self.min).all() && pos.cmple(self.max).all()
+    }
+}
+
+/// A level being played: where the payload has to go, and how it's doing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    /// The payload, by index into the arena.
+    pub payload: usize,
+    pub target: Target,
+    /// Links cut so far.
+    pub cuts: usize,
+    /// How long the payload has been in the target without leaving.
+    pub time_in_target: f32,
+    pub landed: bool,
+}
+
+impl Puzzle {
+    fn new(payload: usize, target: Target) -> Self {
+        Self { payload, target, cuts: 0, time_in_target: 0.0, landed: false }
+    }
+
+    /// Takes the world after a step of `dt`, before its events are drained,
+    /// returning whether the payload has landed, now or before.
+    pub fn record(&mut self, world: &World, dt: f32) -> bool {
+        if self.landed {
+            return true;
+        }
+        self.cuts += world.events.iter().filter(|event| matches!(event, SimEvent::ConstraintCut(_))).count();
+        let inside = world.arena.get(self.payload).is_some_and(|node| self.target.contains(node.pos));
+        self.time_in_target = if inside { self.time_in_target + dt } else { 0.0 };
+        self.landed = self.time_in_target >= SETTLE_TIME;
+        self.landed
+    }
+}
+
+/// A world to put a level in, with a payload at `pos` that doesn't bounce.
+fn level_world(height: f32, pos: Vec2) -> (World, usize) {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let payload = world.add_ball(pos, Vec2::ZERO, PAYLOAD_MASS, PAYLOAD_RADIUS);
+    world.arena[payload].restitution = 0.0;
+    (world, payload)
+}
+
+/// Hangs `node` from a fixed node at `pin` on a straight rope of `links`.
+fn hang(world: &mut World, pin: Vec2, node: usize, links: usize) {
+    let end = world.arena[node].pos;
+    let rest_length = pin.distance(end) / links as f32;
+    let break_threshold = rest_length * world.params.break_threshold;
+    let first = world.arena.len();
+    for i in 0..links {
+        let pos = pin.lerp(end, i as f32 / links as f32);
+        world.arena.push(Node { fixed: i == 0, ..Node::with_pos_and_mass(pos, 1.0) });
+        let b = if i + 1 == links { node } else { first + i + 1 };
+        world.constraints.push(Constraint { a: first + i, b, rest_length, break_threshold });
+    }
+}
+
+/// The payload hangs between two ropes right over the target, so it has to
+/// be let go of by both at once, with a single stroke across them.
+pub fn drop(width: f32, height: f32) -> (World, Puzzle) {
+    let (mut world, payload) = level_world(height, Vec2::new(width / 2.0, height * 0.4));
+    for side in [-1.0, 1.0] {
+        hang(&mut world, Vec2::new(width / 2.0 + side * 3.0 * TARGET_DIST, height * 0.15), payload, 5);
+    }
+    let target = Target::on_ground(&world, width / 2.0 - 1.5 * TARGET_DIST, width / 2.0 + 1.5 * TARGET_DIST);
+    (world, Puzzle::new(payload, target))
+}
+
+/// The payload hangs between two ropes like in `drop`, but the target is
+/// off to the left, so it has to swing on one rope before the other is cut.
+pub fn sideways(width: f32, height: f32) -> (World, Puzzle) {
+    let (world, puzzle) = drop(width, height);
+    let left = width / 2.0 - 6.0 * TARGET_DIST;
+    let target = Target::on_ground(&world, left, left + 2.0 * TARGET_DIST);
+    (world, Puzzle::new(puzzle.payload, target))
+}
+
+/// A pendulum held out to the left by a second rope, for cutting free and
+/// then cutting loose on the way up, so it flies out over the ground.
+pub fn swing(width: f32, height: f32) -> (World, Puzzle) {
+    let pivot = Vec2::new(width * 0.35, height * 0.1);
+    // 60 degrees out, so it swings through half its length of height
+    let start = pivot + Vec2::new(-(3f32.sqrt() / 2.0), 0.5) * SWING_LENGTH;
+    let (mut world, payload) = level_world(height, start);
+    hang(&mut world, pivot, payload, 3);
+    hang(&mut world, start - Vec2::new(3.0 * TARGET_DIST, 0.0), payload, 1);
+    let target = Target::on_ground(&world, width * 0.58, width * 0.68);
+    (world, Puzzle::new(payload, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays `puzzle` on `world` for `steps` steps, cutting the top link of
+    /// each rope but the first at the start and the first's after `wait`
+    /// steps, returning whether it landed.
+    fn play(mut world: World, mut puzzle: Puzzle, wait: Option<usize>, steps: usize) -> bool {
+        let dt = world.params.dt;
+        let pinned = |i: &usize| world.arena[world.constraints[*i].a].fixed;
+        let tops: Vec<usize> = (0..world.constraints.len()).filter(pinned).collect();
+        for &i in tops[1..].iter().rev() {
+            world.constraints.remove(i);
+        }
+        for step in 0..steps {
+            if Some(step) == wait {
+                world.constraints.remove(tops[0]);
+            }
+            world.step(dt);
+            if puzzle.record(&world, dt) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn every_level_takes_a_well_timed_last_cut() {
+        for (name, build) in LEVELS {
+            let (world, puzzle) = build(1280.0, 720.0);
+            assert!(!play(world.clone(), puzzle, None, 600), "{} solved without the last cut", name);
+            assert!(!play(world.clone(), puzzle, Some(150), 600), "{} solved by a cut any time", name);
+            let solution = (0..150).step_by(5).find(|&wait| play(world.clone(), puzzle, Some(wait), 600));
+            assert!(solution.is_some(), "{} can't be solved", name);
+        }
+    }
+}
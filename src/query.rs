@@ -0,0 +1,175 @@
+//! Where-questions about a world: the node nearest a point, the nodes
+//! within a radius of one, and the links a segment crosses. A `Query` buckets
+//! the nodes and links once, so each question after that only looks at what's
+//! near it instead of scanning them all, which adds up for tools and scripts
+//! asking several a frame. It's a snapshot, so build a new one once the world
+//! has moved. Nodes on hidden layers are left out, and so are their links,
+//! the same as the knife and wind leave them alone.
+
+use crate::geometry;
+use crate::spatial_hash::SpatialHash;
+use crate::world::World;
+use glam::Vec2;
+
+/// Side of the cells nodes and links are bucketed into, about as far as a
+/// tool reaches from the cursor.
+const QUERY_CELL: f32 = 32.0;
+
+pub struct Query {
+    /// The shown nodes, in `shown`'s order.
+    nodes: SpatialHash,
+    shown: Vec<usize>,
+    /// Where each shown node was, in `shown`'s order.
+    positions: Vec<Vec2>,
+    /// The shown links, by their middles, in `links`'s order.
+    middles: SpatialHash,
+    links: Vec<usize>,
+    /// Where each shown link ran from and to, in `links`'s order.
+    segments: Vec<(Vec2, Vec2)>,
+    /// Half the longest shown link, so every link touching a spot has its middle within this of it.
+    reach: f32,
+    /// The corners of the box around the shown nodes.
+    bounds: (Vec2, Vec2),
+}
+
+impl Query {
+    pub fn new(world: &World) -> Self {
+        let hidden = world.hidden();
+        let shown: Vec<usize> = (0..world.arena.len()).filter(|&i| !hidden[i]).collect();
+        let positions: Vec<Vec2> = shown.iter().map(|&i| world.arena[i].pos).collect();
+        let nodes = SpatialHash::new(QUERY_CELL, positions.iter().copied());
+        let bounds = positions.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), &pos| (min.min(pos), max.max(pos)),
+        );
+
+        let links: Vec<usize> = (0..world.constraints.len())
+            .filter(|&i| !hidden[world.constraints[i].a] && !hidden[world.constraints[i].b])
+            .collect();
+        let offsets: Vec<Vec2> =
+            links.iter().map(|&i| world.constraints[i].offset(&world.arena, &world.wrap)).collect();
+        let segments: Vec<(Vec2, Vec2)> = links
+            .iter()
+            .zip(&offsets)
+            .map(|(&i, &offset)| {
+                let start = world.arena[world.constraints[i].a].pos;
+                (start, start + offset)
+            })
+            .collect();
+        let middles = SpatialHash::new(QUERY_CELL, segments.iter().map(|&(start, end)| (start + end) / 2.0));
+        let reach = offsets.iter().map(|offset| offset.length() / 2.0).fold(0.0, f32::max);
+
+        Self { nodes, shown, positions, middles, links, segments, reach, bounds }
+    }
+
+    /// The shown node nearest `point`, or `None` if there aren't any.
+    pub fn nearest_node(&self, point: Vec2) -> Option<usize> {
+        if self.shown.is_empty() {
+            return None;
+        }
+        // wider and wider, until something's found or everything's been looked at
+        let (min, max) = self.bounds;
+        let farthest = [min, max, Vec2::new(min.x, max.y), Vec2::new(max.x, min.y)]
+            .into_iter()
+            .map(|corner| corner.distance(point))
+            .fold(0.0, f32::max);
+        let mut radius = QUERY_CELL;
+        loop {
+            if too_wide(Vec2::splat(2.0 * radius), self.shown.len()) {
+                let distance = |k: &usize| self.positions[*k].distance(point);
+                return (0..self.shown.len()).min_by(|k, l| distance(k).total_cmp(&distance(l))).map(|k| self.shown[k]);
+            }
+            let nearest = self.nodes_within(point, radius).first().copied();
+            if nearest.is_some() || radius > farthest {
+                return nearest;
+            }
+            radius *= 2.0;
+        }
+    }
+
+    /// The shown nodes within `radius` of `point`, nearest first.
+    pub fn nodes_within(&self, point: Vec2, radius: f32) -> Vec<usize> {
+        let candidates: Box<dyn Iterator<Item = usize>> = if too_wide(Vec2::splat(2.0 * radius), self.shown.len()) {
+            Box::new(0..self.shown.len())
+        } else {
+            Box::new(self.nodes.near(point, radius))
+        };
+        let mut found: Vec<(usize, f32)> = candidates
+            .map(|k| (self.shown[k], self.positions[k].distance(point)))
+            .filter(|&(_, distance)| distance <= radius)
+            .collect();
+        found.sort_by(|(i, a), (j, b)| a.total_cmp(b).then(i.cmp(j)));
+        found.into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// The shown links the segment from `a` to `b` crosses, each with how far
+    /// along the segment it's crossed, from 0 at `a` to 1 at `b`, in the order
+    /// they're crossed.
+    pub fn raycast_constraints(&self, a: Vec2, b: Vec2) -> Vec<(usize, f32)> {
+        let (min, max) = (a.min(b) - self.reach, a.max(b) + self.reach);
+        let candidates: Box<dyn Iterator<Item = usize>> = if too_wide(max - min, self.links.len()) {
+            Box::new(0..self.links.len())
+        } else {
+            Box::new(self.middles.within(min, max))
+        };
+        let mut crossed: Vec<(usize, f32)> = candidates
+            .filter_map(|k| {
+                let (start, end) = self.segments[k];
+                geometry::segment_crossing(a, b, start, end).map(|along| (self.links[k], along))
+            })
+            .collect();
+        crossed.sort_by(|(i, a), (j, b)| a.total_cmp(b).then(i.cmp(j)));
+        crossed
+    }
+}
+
+/// Whether a box `size` across covers more cells than there are `things`
+/// bucketed in them, so going through those one by one is quicker than the cells.
+fn too_wide(size: Vec2, things: usize) -> bool {
+    let cells = size / QUERY_CELL + 1.0;
+    cells.x * cells.y > things as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_match_checking_everything() {
+        let mut world = World::new(1000.0);
+        world.add_cloth(Vec2::new(-100.0, 50.0), 12, 9, 3);
+        let query = Query::new(&world);
+        let point = Vec2::new(37.0, 81.0);
+
+        let distance = |i: &usize| world.arena[*i].pos.distance(point);
+        let nearest = (0..world.arena.len()).min_by(|a, b| distance(a).total_cmp(&distance(b)));
+        assert_eq!(query.nearest_node(point), nearest);
+        assert!(query.nearest_node(Vec2::new(5000.0, -3000.0)).is_some());
+        // far enough out that going cell by cell would take minutes
+        assert_eq!(query.nearest_node(Vec2::new(-1e6, 50.0)), Some(0));
+        assert_eq!(query.nodes_within(Vec2::new(1e6, 0.0), 2e6).len(), world.arena.len());
+
+        let mut within = query.nodes_within(point, 60.0);
+        within.sort_unstable();
+        let expected: Vec<usize> = (0..world.arena.len()).filter(|i| distance(i) <= 60.0).collect();
+        assert!(expected.len() > 1);
+        assert_eq!(within, expected);
+
+        let (a, b) = (Vec2::new(-150.0, 120.0), Vec2::new(200.0, 140.0));
+        let crossed: Vec<usize> = query.raycast_constraints(a, b).into_iter().map(|(i, _)| i).collect();
+        let mut sorted = crossed.clone();
+        sorted.sort_unstable();
+        let expected: Vec<usize> = (0..world.constraints.len())
+            .filter(|&i| {
+                let constraint = &world.constraints[i];
+                let (c, d) = (world.arena[constraint.a].pos, world.arena[constraint.b].pos);
+                geometry::segment_crossing(a, b, c, d).is_some()
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(sorted, expected);
+        // starting far off to the left checks the links one by one, finding the same ones
+        let far: Vec<usize> = query.raycast_constraints(a - (b - a) * 1e5, b).into_iter().map(|(i, _)| i).collect();
+        assert_eq!(far, crossed);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::geometry;
+use crate::node::Node;
+use glam::Vec2;
+
+/// Straight pieces a Bézier rail is split into for projecting onto it.
+pub const BEZIER_SEGMENTS: usize = 32;
+
+/// The shape of a rail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Curve {
+    /// Straight lines through the points in order.
+    Polyline(Vec<Vec2>),
+    /// A cubic Bézier curve from the first point to the last, pulled toward the middle two.
+    Bezier([Vec2; 4]),
+}
+
+impl Curve {
+    /// Points along the curve that straight lines between approximate it.
+    pub fn points(&self) -> Vec<Vec2> {
+        match self {
+            Curve::Polyline(points) => points.clone(),
+            Curve::Bezier([a, b, c, d]) => (0..=BEZIER_SEGMENTS)
+                .map(|i| {
+                    let t = i as f32 / BEZIER_SEGMENTS as f32;
+                    let s = 1.0 - t;
+                    *a * (s * s * s) + *b * (3.0 * s * s * t) + *c * (3.0 * s * t * t) + *d * (t * t * t)
+                })
+                .collect(),
+        }
+    }
+
+    /// The point on the curve nearest `p`.
+    pub fn nearest(&self, p: Vec2) -> Vec2 {
+        let points = self.points();
+        if points.len() < 2 {
+            return points.first().copied().unwrap_or(p);
+        }
+        points
+            .windows(2)
+            .map(|pair| pair[0].lerp(pair[1], geometry::nearest_on_segment(p, pair[0], pair[1])))
+            .min_by(|a, b| a.distance_squared(p).total_cmp(&b.distance_squared(p)))
+            .unwrap()
+    }
+}
+
+/// Keeps a node on a curve, like a bead on a wire or a cable car on its cable,
+/// while it slides freely along it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rail {
+    pub node: usize,
+    pub curve: Curve,
+}
+
+impl Rail {
+    /// Moves the node onto the nearest point of the curve.
+    pub fn project(&self, arena: &mut [Node]) {
+        let node = &mut arena[self.node];
+        if !node.fixed {
+            node.pos = self.curve.nearest(node.pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_onto_the_nearest_piece() {
+        let curve = Curve::Polyline(vec![Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)]);
+        assert_eq!(curve.nearest(Vec2::new(4.0, 3.0)), Vec2::new(4.0, 0.0));
+        assert_eq!(curve.nearest(Vec2::new(13.0, 6.0)), Vec2::new(10.0, 6.0));
+        assert_eq!(curve.nearest(Vec2::new(-5.0, -5.0)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn bezier_passes_through_its_ends() {
+        let ends = [Vec2::ZERO, Vec2::new(0.0, 50.0), Vec2::new(100.0, 50.0), Vec2::new(100.0, 0.0)];
+        let points = Curve::Bezier(ends).points();
+        assert_eq!(points.len(), BEZIER_SEGMENTS + 1);
+        assert_eq!((points[0], points[BEZIER_SEGMENTS]), (ends[0], ends[3]));
+        // symmetric controls put the middle halfway across, three quarters of the way down to them
+        assert!(points[BEZIER_SEGMENTS / 2].distance(Vec2::new(50.0, 37.5)) < 1e-4);
+    }
+}
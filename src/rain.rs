@@ -0,0 +1,114 @@
+use crate::geometry;
+use crate::node::Node;
+use crate::rng::Rng;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+pub const RAIN_RATE: f32 = 20.0;
+pub const DROP_SPEED: f32 = 120.0;
+pub const DROP_MASS: f32 = 0.02;
+/// Fraction of each drop a node it hits soaks up.
+pub const SOAK: f32 = 0.5;
+/// Most water a node holds, as mass.
+pub const MAX_WETNESS: f32 = 1.0;
+
+/// A raindrop, falling straight through the scene at a steady speed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Drop {
+    pub pos: Vec2,
+    pub vel: Vec2,
+}
+
+/// Drops falling from a line across the top of the scene. Each one that hits
+/// a free node gives it the drop's momentum and leaves some of its water
+/// behind, so cloth ripples under the rain and gets heavier as it soaks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rain {
+    /// Where drops start, from `left` to `right` at height `top`.
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    /// Drops per unit of time.
+    pub rate: f32,
+    pub speed: f32,
+    pub drop_mass: f32,
+    /// Fraction of a drop's mass a node it hits soaks up.
+    pub soak: f32,
+    /// Most water a node soaks up, as mass.
+    pub max_wetness: f32,
+    pub drops: Vec<Drop>,
+    /// Drops due but not spawned yet, carried over so low rates still rain.
+    pub due: f32,
+}
+
+impl Rain {
+    pub fn new(left: f32, right: f32, top: f32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            rate: RAIN_RATE,
+            speed: DROP_SPEED,
+            drop_mass: DROP_MASS,
+            soak: SOAK,
+            max_wetness: MAX_WETNESS,
+            drops: Vec::new(),
+            due: 0.0,
+        }
+    }
+
+    /// Spawns the drops due in the next `dt` along the top, moves every drop,
+    /// and lands the ones that hit a free node or fell past `floor`. Nodes are
+    /// hit by drops whose path this step passes within `NODE_RADIUS` of them.
+    pub fn fall(&mut self, arena: &mut [Node], floor: f32, rng: &mut Rng, dt: f32) {
+        self.due += self.rate * dt;
+        while self.due >= 1.0 {
+            self.due -= 1.0;
+            let pos = Vec2::new(rng.range(self.left, self.right), self.top);
+            self.drops.push(Drop { pos, vel: Vec2::new(0.0, self.speed) });
+        }
+
+        let Rain { drop_mass, soak, max_wetness, .. } = *self;
+        self.drops.retain_mut(|drop| {
+            let start = drop.pos;
+            drop.pos += drop.vel * dt;
+            let hit = arena
+                .iter_mut()
+                .find(|node| !node.fixed && geometry::point_segment_distance(node.pos, start, drop.pos) < NODE_RADIUS);
+            if let Some(node) = hit {
+                node.vel += drop.vel * drop_mass / node.mass;
+                let water = (drop_mass * soak).min(max_wetness - node.wetness).max(0.0);
+                node.wetness += water;
+                node.mass += water;
+                return false;
+            }
+            drop.pos.y < floor
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_push_and_soak_the_nodes_they_hit() {
+        let mut rain = Rain { rate: 0.0, max_wetness: 0.015, ..Rain::new(0.0, 100.0, 0.0) };
+        rain.drops.push(Drop { pos: Vec2::new(50.0, 0.0), vel: Vec2::new(0.0, 100.0) });
+        rain.drops.push(Drop { pos: Vec2::new(50.0, -10.0), vel: Vec2::new(0.0, 100.0) });
+        let mut arena = [Node::with_pos_and_mass(Vec2::new(52.0, 12.0), 1.0)];
+
+        // the first drop reaches the node this step and the second the next
+        let mut rng = Rng::default();
+        rain.fall(&mut arena, 500.0, &mut rng, 0.1);
+        assert_eq!(rain.drops.len(), 1);
+        assert!((arena[0].vel.y - 2.0).abs() < 1e-6, "{}", arena[0].vel);
+        assert!((arena[0].wetness - 0.01).abs() < 1e-6);
+
+        // and soaks it up to its limit
+        rain.fall(&mut arena, 500.0, &mut rng, 0.1);
+        assert!(rain.drops.is_empty());
+        assert!((arena[0].wetness - 0.015).abs() < 1e-6);
+        assert!((arena[0].mass - 1.015).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::anchor::Anchor;
+use crate::ball::Ball;
+use crate::dispenser::Dispenser;
+use crate::fluid::Fluid;
+use crate::magnet::Magnet;
+use crate::muscle::Muscle;
+use crate::node::Node;
+use crate::platform::Platform;
+use crate::rail::Rail;
+use crate::rain::Drop;
+use crate::rotor::Rotor;
+use crate::slider::Slider;
+use crate::walls::Walls;
+use crate::water::Water;
+use crate::well::{Sink, Well};
+use crate::winch::Winch;
+use crate::world::World;
+use glam::Vec2;
+
+/// A corner of a `Mesh`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshVertex {
+    pub pos: Vec2,
+    /// What a shader or texture reads at the corner; see `Mesh::push_segment`.
+    pub uv: Vec2,
+    /// RGBA from 0 to 1, blended across each triangle by backends that can.
+    pub color: [f32; 4],
+}
+
+/// Triangles to fill, three `indices` into `vertices` each, for `SimRenderer::draw_mesh`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    /// Whether its ropes' `uv.y` runs across them, to lay a texture along
+    /// them by, rather than being their break fraction.
+    pub textured: bool,
+}
+
+impl Mesh {
+    pub fn new(textured: bool) -> Self {
+        Self { textured, ..Self::default() }
+    }
+
+    /// Empties it, keeping `textured` and the buffers for reuse.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// A triangle all in `color`.
+    pub fn push_triangle(&mut self, corners: [Vec2; 3], color: [f32; 4]) {
+        let first = self.vertices.len() as u32;
+        self.vertices.extend(corners.map(|pos| MeshVertex { pos, uv: Vec2::ZERO, color }));
+        self.indices.extend([first, first + 1, first + 2]);
+    }
+
+    /// A rope segment `width` wide from `a` to `b`, as three quads side by
+    /// side: a solid core in `color`, and a strip `feather` wide straddling
+    /// each edge, fading to transparent so the edge doesn't stair-step however
+    /// it's angled or zoomed. `uv.x` runs along it in `rest_length`s from `a`,
+    /// and `uv.y` is `strain`, or from 0 to 1 across it if `textured`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn push_segment(
+        &mut self,
+        a: Vec2,
+        b: Vec2,
+        rest_length: f32,
+        width: f32,
+        feather: f32,
+        strain: f32,
+        color: [f32; 4],
+    ) {
+        let across = (b - a).perp().normalize_or_zero();
+        if across == Vec2::ZERO {
+            return;
+        }
+        // the fade straddles the edge, so the segment looks as wide as it would without
+        let core = across * (width - feather).max(0.0) / 2.0;
+        let outer = across * (width + feather) / 2.0;
+
+        let length = (b - a).length() / rest_length;
+        let first = self.vertices.len() as u32;
+        // across the segment from one outer edge to the other, at each end
+        let clear = [color[0], color[1], color[2], 0.0];
+        let rows = [(outer, clear), (core, color), (-core, color), (-outer, clear)];
+        let outer_width = 2.0 * outer.length();
+        for (end, u) in [(a, 0.0), (b, length)] {
+            for (offset, color) in rows {
+                let v = if self.textured { 0.5 - offset.dot(across) / outer_width } else { strain };
+                self.vertices.push(MeshVertex { pos: end + offset, uv: Vec2::new(u, v), color });
+            }
+        }
+        for strip in 0..3 {
+            self.indices.extend([0, 1, 4, 4, 1, 5].map(|i| first + strip + i));
+        }
+    }
+}
+
+/// Drawing backend for a world. Implementors only need the primitives;
+/// `draw_world` walks the world in back-to-front order and calls them.
+pub trait SimRenderer {
+    /// The ground line at height `y`.
+    fn draw_ground(&mut self, y: f32);
+
+    /// The walls that are on, which can be skipped.
+    fn draw_walls(&mut self, _walls: &Walls) {}
+
+    /// The moving air behind everything else, which can be skipped as well.
+    fn draw_fluid(&mut self, _fluid: &Fluid) {}
+
+    /// The magnet and its field, which can be skipped as well.
+    fn draw_magnet(&mut self, _magnet: &Magnet) {}
+
+    /// A constraint from `a` to `b`, with its break fraction as `strain`.
+    fn draw_segment(&mut self, a: Vec2, b: Vec2, strain: f32);
+
+    fn draw_node(&mut self, node: &Node);
+
+    /// An elastic band from `a` to `b`, drawn like an unstrained constraint unless overridden.
+    fn draw_band(&mut self, a: Vec2, b: Vec2) {
+        self.draw_segment(a, b, 0.0);
+    }
+
+    /// A link from `a` to `b` in a group with its own `color`, as RGB, over the
+    /// link, which can be skipped too.
+    fn draw_group_link(&mut self, _color: [u8; 3], _a: Vec2, _b: Vec2) {}
+
+    /// A muscle working the link from `a` to `b`, over the link, which can be skipped too.
+    fn draw_muscle(&mut self, _muscle: &Muscle, _a: Vec2, _b: Vec2) {}
+
+    /// A ball at its full size, over its node. Renderers can skip it to leave the plain node.
+    fn draw_ball(&mut self, _ball: &Ball, _node: &Node) {}
+
+    /// The path an anchor follows, which can be skipped too.
+    fn draw_anchor_path(&mut self, _anchor: &Anchor) {}
+
+    /// A moving platform, which can be skipped too.
+    fn draw_platform(&mut self, _platform: &Platform) {}
+
+    /// A spinning rotor, which can be skipped too.
+    fn draw_rotor(&mut self, _rotor: &Rotor) {}
+
+    /// The curve a rail keeps its node on, which can be skipped too.
+    fn draw_rail(&mut self, _rail: &Rail) {}
+
+    /// A node riding a rope, over the plain node, which can be skipped too.
+    fn draw_slider(&mut self, _slider: &Slider, _node: &Node) {}
+
+    /// The drum of a winch hanging from `anchor`, which can be skipped too.
+    fn draw_winch(&mut self, _winch: &Winch, _anchor: &Node) {}
+
+    /// The nozzle of a dispenser feeding rope out of `anchor`, which can be skipped too.
+    fn draw_dispenser(&mut self, _dispenser: &Dispenser, _anchor: &Node) {}
+
+    /// A falling raindrop, which can be skipped too.
+    fn draw_drop(&mut self, _drop: &Drop) {}
+
+    /// Every droplet of water, which can also be skipped.
+    fn draw_water(&mut self, _water: &Water) {}
+
+    /// A gravity well. Renderers without a marker for them can skip it.
+    fn draw_well(&mut self, _well: &Well) {}
+
+    /// A sink, which can likewise be skipped.
+    fn draw_sink(&mut self, _sink: &Sink) {}
+
+    /// Filled triangles, such as shaded cloth or ropes drawn as quads, over
+    /// whatever's been drawn so far. `draw_world` doesn't draw any; they come
+    /// from whoever's driving the renderer.
+    fn draw_mesh(&mut self, mesh: &Mesh);
+
+    /// Everything drawn from here on is at `depth`, as `Layer::depth` measures it. Renderers without parallax can skip
+    /// it and draw everything where it is.
+    fn set_depth(&mut self, _depth: f32) {}
+
+    /// Draws the ground, walls, air, magnet, platforms, wells, and sinks, then for each depth layers are at, farthest
+    /// first, every anchor path, rotor, rail, constraint, band, colored group's link, muscle, node, ball, slider,
+    /// winch, and dispenser on layers at that depth, and last the droplets and raindrops. Links across wrapped edges
+    /// are drawn going out of one side and again coming in the other. Anything on a hidden layer, or linked to a node
+    /// that is, is left out.
+    fn draw_world(&mut self, world: &World) {
+        if !world.wrap.y {
+            self.draw_ground(world.ground_y);
+        }
+        if world.walls.any() {
+            self.draw_walls(&world.walls);
+        }
+        if let Some(fluid) = world.fluid.as_ref() {
+            self.draw_fluid(fluid);
+        }
+        if let Some(magnet) = world.magnet.as_ref() {
+            self.draw_magnet(magnet);
+        }
+        for platform in world.platforms.iter() {
+            self.draw_platform(platform);
+        }
+        for well in world.wells.iter() {
+            self.draw_well(well);
+        }
+        for sink in world.sinks.iter() {
+            self.draw_sink(sink);
+        }
+
+        for depth in world.layers.depths() {
+            self.set_depth(depth);
+            draw_layers_at(self, world, depth);
+        }
+        self.set_depth(0.0);
+
+        if let Some(water) = world.water.as_ref() {
+            self.draw_water(water);
+        }
+        for drop in world.rain.iter().flat_map(|rain| rain.drops.iter()) {
+            self.draw_drop(drop);
+        }
+    }
+}
+
+/// Draws what's on the shown layers at `depth`, for `draw_world`.
+fn draw_layers_at<R: SimRenderer + ?Sized>(renderer: &mut R, world: &World, depth: f32) {
+    let on = |layer: u8| world.layers.visible(layer) && world.layers.0[layer as usize].depth == depth;
+    let shown = |node: usize| on(world.arena[node].layer);
+    for anchor in world.anchors.iter().filter(|anchor| shown(anchor.node)) {
+        renderer.draw_anchor_path(anchor);
+    }
+    for rotor in world.rotors.iter().filter(|rotor| on(rotor.layer)) {
+        renderer.draw_rotor(rotor);
+    }
+    for rail in world.rails.iter().filter(|rail| shown(rail.node)) {
+        renderer.draw_rail(rail);
+    }
+
+    let wrapped = |a: Vec2, b: Vec2| {
+        let delta = world.wrap.delta(a, b);
+        let across = b - a != delta;
+        std::iter::once((a, a + delta)).chain(across.then_some((b - delta, b)))
+    };
+    for constraint in world.constraints.iter().filter(|constraint| shown(constraint.a) && shown(constraint.b)) {
+        let strain = constraint.break_fraction(&world.arena, &world.wrap);
+        for (a, b) in wrapped(world.arena[constraint.a].pos, world.arena[constraint.b].pos) {
+            renderer.draw_segment(a, b, strain);
+        }
+    }
+    for band in world.bands.iter().filter(|band| shown(band.a) && shown(band.b)) {
+        for (a, b) in wrapped(world.arena[band.a].pos, world.arena[band.b].pos) {
+            renderer.draw_band(a, b);
+        }
+    }
+    for group in world.groups.iter() {
+        let Some(color) = group.color else { continue };
+        let links = world.constraints.iter().map(|constraint| (constraint.a, constraint.b));
+        let links = links.chain(world.bands.iter().map(|band| (band.a, band.b)));
+        for (a, b) in links.filter(|&(a, b)| shown(a) && shown(b) && group.joins(a, b)) {
+            for (a, b) in wrapped(world.arena[a].pos, world.arena[b].pos) {
+                renderer.draw_group_link(color, a, b);
+            }
+        }
+    }
+    for muscle in world.muscles.iter().filter(|muscle| shown(muscle.a) && shown(muscle.b)) {
+        for (a, b) in wrapped(world.arena[muscle.a].pos, world.arena[muscle.b].pos) {
+            renderer.draw_muscle(muscle, a, b);
+        }
+    }
+
+    for node in world.arena.iter().filter(|node| on(node.layer)) {
+        renderer.draw_node(node);
+    }
+
+    for ball in world.balls.iter().filter(|ball| shown(ball.node)) {
+        renderer.draw_ball(ball, &world.arena[ball.node]);
+    }
+    for slider in world.sliders.iter().filter(|slider| shown(slider.node)) {
+        renderer.draw_slider(slider, &world.arena[slider.node]);
+    }
+
+    for winch in world.winches.iter().filter(|winch| shown(winch.anchor)) {
+        renderer.draw_winch(winch, &world.arena[winch.anchor]);
+    }
+    for dispenser in world.dispensers.iter().filter(|dispenser| shown(dispenser.anchor)) {
+        renderer.draw_dispenser(dispenser, &world.arena[dispenser.anchor]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shading;
+
+    /// Keeps what it's asked to draw, as a backend with no screen would.
+    #[derive(Default)]
+    struct Recorder {
+        segments: Vec<(Vec2, Vec2)>,
+        nodes: usize,
+        meshes: Vec<Mesh>,
+    }
+
+    impl SimRenderer for Recorder {
+        fn draw_ground(&mut self, _y: f32) {}
+
+        fn draw_segment(&mut self, a: Vec2, b: Vec2, _strain: f32) {
+            self.segments.push((a, b));
+        }
+
+        fn draw_node(&mut self, _node: &Node) {
+            self.nodes += 1;
+        }
+
+        fn draw_mesh(&mut self, mesh: &Mesh) {
+            self.meshes.push(mesh.clone());
+        }
+    }
+
+    #[test]
+    fn cloth_and_rope_quads_reach_the_backend_as_meshes() {
+        let mut world = World::new(500.0);
+        world.add_cloth(Vec2::ZERO, 2, 2, 1);
+        let mut recorder = Recorder::default();
+        recorder.draw_world(&world);
+        assert_eq!((recorder.segments.len(), recorder.nodes), (4, 4));
+        assert!(recorder.meshes.is_empty());
+
+        // shaded cloth: a triangle to each half of the square, lit evenly while it's flat
+        recorder.draw_mesh(&shading::mesh(&shading::shade(&world), [0.5, 0.5, 0.5, 0.6]));
+        let cloth = &recorder.meshes[0];
+        assert_eq!((cloth.vertices.len(), cloth.indices.len()), (6, 6));
+        assert!(cloth.vertices.iter().all(|vertex| (vertex.color[0] - 0.5).abs() < 1e-5 && vertex.color[3] == 0.6));
+
+        // a rope two rest lengths long, 6 wide with a 2 wide fade, textured across
+        let mut rope = Mesh::new(true);
+        rope.push_segment(Vec2::ZERO, Vec2::new(0.0, 100.0), 50.0, 6.0, 2.0, 0.3, [1.0; 4]);
+        assert_eq!((rope.vertices.len(), rope.indices.len()), (8, 18));
+        let far_end: Vec<_> =
+            rope.vertices[4..].iter().map(|vertex| (vertex.pos.x, vertex.uv, vertex.color[3])).collect();
+        assert_eq!(
+            far_end,
+            [
+                (-4.0, Vec2::new(2.0, 0.0), 0.0),
+                (-2.0, Vec2::new(2.0, 0.25), 1.0),
+                (2.0, Vec2::new(2.0, 0.75), 1.0),
+                (4.0, Vec2::new(2.0, 1.0), 0.0),
+            ]
+        );
+        rope.textured = false;
+        rope.clear();
+        rope.push_segment(Vec2::ZERO, Vec2::new(0.0, 100.0), 50.0, 6.0, 2.0, 0.3, [1.0; 4]);
+        assert!(rope.vertices.iter().all(|vertex| vertex.uv.y == 0.3));
+        recorder.draw_mesh(&rope);
+        assert_eq!(recorder.meshes.len(), 2);
+    }
+}
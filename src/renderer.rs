@@ -0,0 +1,419 @@
+use crate::materials::RopeMaterials;
+use crate::palette::Palette;
+use clothsim::layer::Layer;
+use clothsim::rain::Drop;
+use clothsim::shading::{self, ShadedFace};
+use clothsim::water::DROPLET_RADIUS;
+use clothsim::{
+    Anchor, AnchorPath, Ball, Dispenser, Fluid, Magnet, Mesh, Muscle, Node, Platform, Rail, Rotor, SimRenderer, Sink,
+    Slider, Walls, Water, Well, Winch, NODE_RADIUS, TARGET_DIST,
+};
+use egui_macroquad::macroquad::models::{self, Vertex};
+use egui_macroquad::macroquad::prelude::*;
+
+pub const ROPE_WIDTH: f32 = 4.0;
+/// Width of ropes drawn with a texture, wide enough to make out its strands or links.
+pub const TEXTURED_ROPE_WIDTH: f32 = 8.0;
+/// Width of the fade along a rope's edges, in pixels.
+const ROPE_FEATHER: f32 = 1.5;
+/// Break fraction above which constraints glow.
+pub const GLOW_START: f32 = 0.9;
+pub const GLOW_LAYERS: usize = 3;
+/// Size of a gravity well's marker, which is also how close a click must be to pick it.
+pub const WELL_MARKER_RADIUS: f32 = 18.0;
+const WELL_ARMS: usize = 3;
+const WELL_ARM_POINTS: usize = 8;
+/// How long a fast node's streak trails back, in seconds of its velocity, like a camera's shutter open for a frame.
+const BLUR_SECONDS: f32 = 1.0 / 60.0;
+/// Longest a node's streak gets, so one flung across the screen doesn't smear over everything.
+const MAX_BLUR_LENGTH: f32 = 200.0;
+const BLUR_ALPHA: f32 = 0.4;
+pub const CLOTH_ALPHA: f32 = 0.6;
+/// Most indices drawn at once, a whole number of triangles, since macroquad clamps a single draw to 5000.
+const MAX_DRAW_INDICES: usize = 4998;
+/// Length of a raindrop's streak.
+const DROP_STREAK: f32 = 8.0;
+/// Length of the air's streaks per unit of its speed.
+const AIR_STREAK_SCALE: f32 = 0.3;
+/// Air speed at which its streaks are fully drawn in.
+const AIR_VISIBLE_SPEED: f32 = 40.0;
+/// Distance between a magnet's field lines on screen, in pixels.
+const FIELD_LINE_SPACING: f32 = 60.0;
+/// Length of a dipole magnet's bar.
+const MAGNET_LENGTH: f32 = 30.0;
+
+/// Draws the world with macroquad, in whatever camera is currently set.
+pub struct MacroquadRenderer<'a> {
+    pub palette: &'a Palette,
+    pub materials: &'a RopeMaterials,
+    pub show_heatmap: bool,
+    pub show_glow: bool,
+    /// Streaks nodes moving farther than their radius in a frame back along their velocity.
+    pub show_motion_blur: bool,
+    /// Traces the magnet's field lines across the view.
+    pub show_field_lines: bool,
+    /// Visible world rect, which the ground line spans.
+    pub view: Rect,
+    /// How much larger than at 1 to draw node radii and line widths, so they keep their size on high-DPI displays.
+    pub scale: f32,
+    /// Rope segments waiting to be drawn together.
+    ropes: Mesh,
+    /// Width of the fade along a rope's edges, in world units, so it's about a pixel.
+    feather: f32,
+    /// Faces of cloth waiting for their depth to be drawn at.
+    cloth: Vec<ShadedFace>,
+    /// Whether a model matrix for a depth other than the screen's is pushed.
+    depth_pushed: bool,
+}
+
+impl<'a> MacroquadRenderer<'a> {
+    pub fn new(palette: &'a Palette, materials: &'a RopeMaterials, view: Rect) -> Self {
+        Self {
+            palette,
+            materials,
+            show_heatmap: false,
+            show_glow: false,
+            show_motion_blur: false,
+            show_field_lines: false,
+            view,
+            scale: 1.0,
+            ropes: Mesh::new(materials.selected_texture().is_some()),
+            feather: ROPE_FEATHER * view.w / screen_width(),
+            cloth: Vec::new(),
+            depth_pushed: false,
+        }
+    }
+
+    /// Fills in each face of cloth in the rope color, lit by its shading, under whatever's drawn next at its depth.
+    pub fn draw_cloth(&mut self, faces: &[ShadedFace]) {
+        self.cloth.extend_from_slice(faces);
+    }
+
+    /// Draws the waiting faces of cloth at `depth`, or all of them.
+    fn fill_cloth(&mut self, depth: Option<f32>) {
+        self.flush_ropes();
+        let rope = self.palette.rope;
+        let (faces, rest): (Vec<_>, _) =
+            self.cloth.drain(..).partition(|face| depth.is_none_or(|depth| face.depth == depth));
+        self.cloth = rest;
+        if !faces.is_empty() {
+            self.draw_mesh(&shading::mesh(&faces, [rope.r, rope.g, rope.b, CLOTH_ALPHA]));
+        }
+    }
+
+    /// Draws any batched rope segments, so whatever comes next lands on top of them, then goes back to drawing at the
+    /// screen's depth.
+    pub fn flush(&mut self) {
+        self.flush_ropes();
+        self.fill_cloth(None);
+        self.set_depth(0.0);
+    }
+
+    fn flush_ropes(&mut self) {
+        if self.ropes.is_empty() {
+            return;
+        }
+        let mut ropes = std::mem::take(&mut self.ropes);
+        self.materials.use_selected();
+        self.draw_mesh(&ropes);
+        gl_use_default_material();
+        ropes.clear();
+        self.ropes = ropes;
+    }
+}
+
+impl SimRenderer for MacroquadRenderer<'_> {
+    /// Scales everything drawn about the middle of the view, shrinking it toward there and panning it less the
+    /// farther back it is, then fills in the cloth at that depth.
+    fn set_depth(&mut self, depth: f32) {
+        self.flush_ropes();
+        let gl = unsafe { &mut get_internal_gl().quad_gl };
+        if self.depth_pushed {
+            gl.pop_model_matrix();
+            self.depth_pushed = false;
+        }
+        let scale = Layer { depth, ..Layer::default() }.scale();
+        if scale != 1.0 {
+            let center = self.view.center().extend(0.0);
+            let model = Mat4::from_translation(center)
+                * Mat4::from_scale(Vec3::new(scale, scale, 1.0))
+                * Mat4::from_translation(-center);
+            gl.push_model_matrix(model);
+            self.depth_pushed = true;
+        }
+        self.fill_cloth(Some(depth));
+    }
+
+    fn draw_ground(&mut self, y: f32) {
+        draw_line(self.view.left(), y, self.view.right(), y, ROPE_WIDTH * self.scale, self.palette.ground);
+    }
+
+    fn draw_walls(&mut self, walls: &Walls) {
+        let (min, max) = (walls.min, walls.max);
+        let sides = [
+            (walls.left, min, Vec2::new(min.x, max.y)),
+            (walls.right, Vec2::new(max.x, min.y), max),
+            (walls.top, min, Vec2::new(max.x, min.y)),
+            (walls.bottom, Vec2::new(min.x, max.y), max),
+        ];
+        for (_, a, b) in sides.into_iter().filter(|(on, _, _)| *on) {
+            draw_line(a.x, a.y, b.x, b.y, ROPE_WIDTH * self.scale, self.palette.ground);
+        }
+    }
+
+    fn draw_fluid(&mut self, fluid: &Fluid) {
+        self.flush_ropes();
+
+        for (center, vel) in fluid.cells() {
+            let alpha = (vel.length() / AIR_VISIBLE_SPEED).min(1.0) * 0.4;
+            if alpha < 0.02 {
+                continue;
+            }
+            let tip = center + (vel * AIR_STREAK_SCALE).clamp_length_max(fluid.cell);
+            draw_line(center.x, center.y, tip.x, tip.y, self.scale, Palette::with_alpha(self.palette.accent, alpha));
+        }
+    }
+
+    fn draw_magnet(&mut self, magnet: &Magnet) {
+        self.flush_ropes();
+
+        if self.show_field_lines {
+            let spacing = FIELD_LINE_SPACING * self.view.w / screen_width();
+            let min = Vec2::new(self.view.left(), self.view.top());
+            let max = Vec2::new(self.view.right(), self.view.bottom());
+            let color = Palette::with_alpha(self.palette.accent, 0.3);
+            for line in magnet.field_lines(min, max, spacing) {
+                for pair in line.windows(2) {
+                    draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, self.scale, color);
+                }
+            }
+        }
+        if let Magnet::Dipole { center, moment } = *magnet {
+            // the north half in the hottest heatmap color, the south half in the coolest
+            let north = moment.normalize_or_zero() * MAGNET_LENGTH / 2.0;
+            let (tip, tail) = (center + north, center - north);
+            draw_line(center.x, center.y, tip.x, tip.y, ROPE_WIDTH * 2.0 * self.scale, self.palette.heat(1.0));
+            draw_line(tail.x, tail.y, center.x, center.y, ROPE_WIDTH * 2.0 * self.scale, self.palette.heat(0.0));
+        }
+    }
+
+    fn draw_segment(&mut self, a: Vec2, b: Vec2, strain: f32) {
+        if self.show_glow && strain > GLOW_START {
+            let glow_color = self.palette.heat(1.0);
+            draw_glow(a, b, ROPE_WIDTH * self.scale, (strain - GLOW_START) / (1.0 - GLOW_START), glow_color);
+        }
+
+        let color = if self.show_heatmap { self.palette.heat(strain) } else { self.palette.rope };
+        let width = if self.materials.selected_texture().is_some() { TEXTURED_ROPE_WIDTH } else { ROPE_WIDTH };
+        let width = width * self.scale;
+        self.ropes.push_segment(a, b, TARGET_DIST, width, self.feather, strain, [color.r, color.g, color.b, color.a]);
+    }
+
+    fn draw_band(&mut self, a: Vec2, b: Vec2) {
+        self.flush_ropes();
+
+        draw_line(a.x, a.y, b.x, b.y, ROPE_WIDTH / 2.0 * self.scale, self.palette.accent);
+    }
+
+    fn draw_group_link(&mut self, [r, g, b]: [u8; 3], from: Vec2, to: Vec2) {
+        self.flush_ropes();
+
+        draw_line(from.x, from.y, to.x, to.y, ROPE_WIDTH * self.scale, Color::from_rgba(r, g, b, 255));
+    }
+
+    fn draw_muscle(&mut self, muscle: &Muscle, a: Vec2, b: Vec2) {
+        self.flush_ropes();
+
+        // along the middle of the link, bulging as it contracts
+        let (a, b) = (a.lerp(b, 0.2), a.lerp(b, 0.8));
+        let contraction = 1.0 - muscle.length() / muscle.rest_length;
+        let thickness = ROPE_WIDTH * self.scale * (2.0 + 5.0 * contraction).max(1.0);
+        draw_line(a.x, a.y, b.x, b.y, thickness, Palette::with_alpha(self.palette.accent, 0.6));
+    }
+
+    fn draw_node(&mut self, node: &Node) {
+        self.flush_ropes();
+
+        let radius = NODE_RADIUS * self.scale;
+        let streak = (node.vel * BLUR_SECONDS).clamp_length_max(MAX_BLUR_LENGTH);
+        if self.show_motion_blur && !node.fixed && streak.length() > NODE_RADIUS {
+            // tapering from the node's full width to a point where it was
+            let side = streak.perp().normalize() * radius;
+            let color = Palette::with_alpha(self.palette.node, BLUR_ALPHA);
+            draw_triangle(node.pos + side, node.pos - side, node.pos - streak, color);
+        }
+        if node.fixed {
+            // a ring as well as a color, so fixed nodes don't rely on hue alone
+            draw_circle(node.pos.x, node.pos.y, radius, self.palette.fixed_node);
+            draw_circle_lines(node.pos.x, node.pos.y, radius * 1.6, 2.0 * self.scale, self.palette.fixed_node);
+        } else {
+            draw_circle(node.pos.x, node.pos.y, radius, self.palette.node);
+        }
+        if node.magnetic {
+            draw_circle(node.pos.x, node.pos.y, radius / 2.0, self.palette.accent);
+        }
+    }
+
+    fn draw_ball(&mut self, ball: &Ball, node: &Node) {
+        self.flush_ropes();
+
+        draw_circle(node.pos.x, node.pos.y, ball.radius, self.palette.node);
+        draw_circle_lines(node.pos.x, node.pos.y, ball.radius, 2.0 * self.scale, self.palette.rope);
+    }
+
+    fn draw_anchor_path(&mut self, anchor: &Anchor) {
+        self.flush_ropes();
+
+        let (color, width) = (Palette::with_alpha(self.palette.fixed_node, 0.4), 1.5 * self.scale);
+        match &anchor.path {
+            AnchorPath::Line(a, b) => draw_line(a.x, a.y, b.x, b.y, width, color),
+            AnchorPath::Circle { center, radius } => draw_circle_lines(center.x, center.y, *radius, width, color),
+            AnchorPath::Waypoints(points) => {
+                for pair in points.windows(2) {
+                    draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, width, color);
+                }
+            }
+            AnchorPath::Oscillate { center, amplitude } => {
+                let (a, b) = (*center - *amplitude, *center + *amplitude);
+                draw_line(a.x, a.y, b.x, b.y, width, color);
+            }
+        }
+    }
+
+    fn draw_platform(&mut self, platform: &Platform) {
+        self.flush_ropes();
+
+        let corner = platform.position() - platform.size / 2.0;
+        let (width, height) = (platform.size.x, platform.size.y);
+        draw_rectangle(corner.x, corner.y, width, height, Palette::with_alpha(self.palette.ground, 0.3));
+        draw_rectangle_lines(corner.x, corner.y, width, height, 2.0 * self.scale, self.palette.ground);
+    }
+
+    fn draw_rotor(&mut self, rotor: &Rotor) {
+        self.flush_ropes();
+
+        let (center, color) = (rotor.center, self.palette.ground);
+        for tip in rotor.tips() {
+            draw_line(center.x, center.y, tip.x, tip.y, rotor.blade_width * 2.0, color);
+            draw_circle(tip.x, tip.y, rotor.blade_width, color);
+        }
+        draw_circle(center.x, center.y, rotor.hub_radius, color);
+    }
+
+    fn draw_rail(&mut self, rail: &Rail) {
+        self.flush_ropes();
+
+        for pair in rail.curve.points().windows(2) {
+            draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, 3.0 * self.scale, self.palette.ground);
+        }
+    }
+
+    fn draw_slider(&mut self, _slider: &Slider, node: &Node) {
+        self.flush_ropes();
+
+        // a pulley wheel around the node
+        let radius = NODE_RADIUS * 2.0 * self.scale;
+        draw_circle_lines(node.pos.x, node.pos.y, radius, 3.0 * self.scale, self.palette.accent);
+    }
+
+    fn draw_winch(&mut self, _winch: &Winch, anchor: &Node) {
+        self.flush_ropes();
+
+        let size = NODE_RADIUS * 4.0 * self.scale;
+        let (x, y) = (anchor.pos.x - size / 2.0, anchor.pos.y - size / 2.0);
+        draw_rectangle_lines(x, y, size, size, 3.0 * self.scale, self.palette.fixed_node);
+    }
+
+    fn draw_dispenser(&mut self, _dispenser: &Dispenser, anchor: &Node) {
+        self.flush_ropes();
+
+        // a funnel narrowing down onto the node the rope comes out of
+        let size = NODE_RADIUS * 2.0 * self.scale;
+        let top = anchor.pos - Vec2::new(0.0, size * 1.5);
+        let (left, right) = (top - Vec2::new(size, 0.0), top + Vec2::new(size, 0.0));
+        draw_triangle_lines(left, right, anchor.pos, 3.0 * self.scale, self.palette.fixed_node);
+    }
+
+    fn draw_drop(&mut self, drop: &Drop) {
+        self.flush_ropes();
+
+        // a streak back along the way it came
+        let tail = drop.pos - drop.vel.normalize_or_zero() * DROP_STREAK;
+        let color = Palette::with_alpha(self.palette.accent, 0.6);
+        draw_line(drop.pos.x, drop.pos.y, tail.x, tail.y, 1.5 * self.scale, color);
+    }
+
+    fn draw_water(&mut self, water: &Water) {
+        self.flush_ropes();
+
+        // drawn a little larger than they collide, so a pool reads as one body of water
+        let color = Palette::with_alpha(self.palette.accent, 0.5);
+        for droplet in water.droplets.iter() {
+            draw_circle(droplet.pos.x, droplet.pos.y, DROPLET_RADIUS * 1.5, color);
+        }
+    }
+
+    fn draw_well(&mut self, well: &Well) {
+        self.flush_ropes();
+
+        let color = self.palette.accent;
+        draw_circle_lines(well.pos.x, well.pos.y, well.radius, self.scale, Palette::with_alpha(color, 0.2));
+        draw_swirl(well.pos, WELL_MARKER_RADIUS, well.strength, 2.0 * self.scale, color);
+        draw_circle(well.pos.x, well.pos.y, 3.0 * self.scale, color);
+    }
+
+    /// Fills `mesh` with whatever material is in use, laying the selected rope texture along it if it's `textured`.
+    fn draw_mesh(&mut self, mesh: &Mesh) {
+        self.flush_ropes();
+
+        let texture = if mesh.textured { self.materials.selected_texture() } else { None };
+        for indices in mesh.indices.chunks(MAX_DRAW_INDICES) {
+            let vertices = indices
+                .iter()
+                .map(|&i| {
+                    let vertex = mesh.vertices[i as usize];
+                    let [r, g, b, a] = vertex.color;
+                    Vertex { position: vertex.pos.extend(0.0), uv: vertex.uv, color: Color::new(r, g, b, a) }
+                })
+                .collect();
+            models::draw_mesh(&models::Mesh { vertices, indices: (0..indices.len() as u16).collect(), texture });
+        }
+    }
+
+    fn draw_sink(&mut self, sink: &Sink) {
+        self.flush_ropes();
+
+        let color = self.palette.accent;
+        let reach = sink.pull().radius;
+        draw_circle_lines(sink.pos.x, sink.pos.y, reach, self.scale, Palette::with_alpha(color, 0.2));
+        draw_swirl(sink.pos, sink.radius.max(WELL_MARKER_RADIUS) * 1.5, sink.strength, 2.0 * self.scale, color);
+        draw_circle(sink.pos.x, sink.pos.y, sink.radius, self.palette.background);
+        draw_circle_lines(sink.pos.x, sink.pos.y, sink.radius, 2.0 * self.scale, color);
+    }
+}
+
+/// Spiral arms winding in to `center` from `radius` out, turning faster the stronger `strength` is.
+fn draw_swirl(center: Vec2, radius: f32, strength: f32, width: f32, color: Color) {
+    let spin = get_time() as f32 * (1.0 + strength.abs() / 10.0);
+    for arm in 0..WELL_ARMS {
+        let start = spin + arm as f32 * std::f32::consts::TAU / WELL_ARMS as f32;
+        let point = |i: usize| {
+            let t = i as f32 / WELL_ARM_POINTS as f32;
+            center + Vec2::from_angle(start + t * std::f32::consts::PI) * radius * (1.0 - t)
+        };
+        for i in 0..WELL_ARM_POINTS {
+            let (a, b) = (point(i), point(i + 1));
+            draw_line(a.x, a.y, b.x, b.y, width, color);
+        }
+    }
+}
+
+/// Layered translucent lines around a rope `width` wide that pulse faster and brighter as `intensity` goes to 1.
+fn draw_glow(a: Vec2, b: Vec2, width: f32, intensity: f32, color: Color) {
+    let pulse = 0.5 + 0.5 * (get_time() as f32 * (6.0 + 10.0 * intensity)).sin();
+    for layer in 1..=GLOW_LAYERS {
+        let width = width * (1.0 + layer as f32 * 1.5);
+        let alpha = (0.15 + 0.25 * intensity) * pulse / layer as f32;
+        draw_line(a.x, a.y, b.x, b.y, width, Palette::with_alpha(color, alpha));
+    }
+}
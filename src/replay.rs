@@ -0,0 +1,319 @@
+//! Input recording and playback.
+//!
+//! Since stepping is deterministic, a replay only needs the scene setup and
+//! whatever the user did, keyed by simulation step. Events are stored only
+//! when something changes, so an idle run costs nothing.
+
+use crate::cli::SceneSource;
+use clothsim::math;
+use clothsim::scene::PinEdge;
+use clothsim::{Params, WindBrush};
+use egui_macroquad::macroquad::prelude::Vec2;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+const HEADER: &str = "clothsim-replay 1";
+
+/// Everything the user controls that feeds into a simulation step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StepInput {
+    /// Cursor position in world space.
+    pub cursor: Vec2,
+    pub wind: bool,
+    /// The size and strength of the wind.
+    pub wind_brush: WindBrush,
+    pub knife: bool,
+    /// How far from its path the knife reaches, in world units; zero cuts only what it crosses.
+    pub knife_radius: f32,
+    /// Whether each swipe of the knife cuts only the first constraint it reaches.
+    pub single_cut: bool,
+    /// Whether the knife splits nodes along its path, leaving a clean slit in cloth.
+    pub split_nodes: bool,
+    /// How hard to run the scene's winches, from -1 reeling in to 1 paying out.
+    pub winch: f32,
+    /// Whether the scene's dispensers are feeding out rope.
+    pub dispense: bool,
+    /// Radius of the solid disc the cursor pushes nodes out of, zero when it isn't pushing.
+    pub push_radius: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReplayEvent {
+    Input(StepInput),
+    HitStop(bool),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub scene: SceneSource,
+    /// Size of the view the scene was built for.
+    pub view: Vec2,
+    pub params: Params,
+    pub seed: u64,
+    /// How much the scene's link strengths were scattered, from `seed`; see `clothsim::variance::vary`.
+    pub variance: f32,
+    /// Events in step order, applied just before the step they're keyed by.
+    pub events: Vec<(u64, ReplayEvent)>,
+    /// Number of steps recorded.
+    pub length: u64,
+    /// The `clothsim::math::MODE` it was recorded with, which playback needs to match.
+    pub math: String,
+}
+
+impl Replay {
+    pub fn new(scene: SceneSource, view: Vec2, params: Params, seed: u64) -> Self {
+        Self {
+            scene,
+            view,
+            params,
+            seed,
+            variance: 0.0,
+            events: Vec::new(),
+            length: 0,
+            math: math::MODE.to_string(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{}", HEADER).unwrap();
+        match &self.scene {
+            SceneSource::Builtin(name) => writeln!(out, "scene builtin {}", name),
+            SceneSource::File(path) => writeln!(out, "scene file {}", path.display()),
+            // a tab between the paths, since either may have spaces
+            SceneSource::Csv { nodes, edges } => {
+                writeln!(out, "scene csv {}\t{}", nodes.display(), edges.display())
+            }
+            SceneSource::Image { path, pin } => {
+                writeln!(out, "scene image {} {}", pin.name(), path.display())
+            }
+            SceneSource::Stress { nodes } => writeln!(out, "scene stress {}", nodes),
+        }
+        .unwrap();
+        writeln!(out, "view {} {}", self.view.x, self.view.y).unwrap();
+        let p = &self.params;
+        writeln!(
+            out,
+            "params {} {} {} {} {} {}",
+            p.dt, p.gravity, p.drag, p.rigidity, p.solver_iterations, p.break_threshold
+        )
+        .unwrap();
+        writeln!(out, "seed {}", self.seed).unwrap();
+        if self.variance != 0.0 {
+            writeln!(out, "variance {}", self.variance).unwrap();
+        }
+        writeln!(out, "math {}", self.math).unwrap();
+        for (step, event) in self.events.iter() {
+            match event {
+                ReplayEvent::Input(input) => writeln!(out, "{}", input_line(*step, input)),
+                ReplayEvent::HitStop(enabled) => writeln!(out, "{} hit_stop {}", step, *enabled as u8),
+            }
+            .unwrap();
+        }
+        writeln!(out, "length {}", self.length).unwrap();
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line, line.split_whitespace().collect::<Vec<_>>()));
+
+        if text.lines().next() != Some(HEADER) {
+            return Err(format!("missing \"{}\" header", HEADER));
+        }
+        lines.next();
+
+        // replays from before scenes and params were recorded used the defaults
+        let scene = SceneSource::Builtin("rope".to_string());
+        let mut replay = Replay::new(scene, Vec2::ZERO, Params::default(), 0);
+        // and before the math was, native math
+        replay.math = "native".to_string();
+        for (line_number, line, words) in lines {
+            let err = |what: &str| format!("line {}: {}", line_number, what);
+            let num = |i: usize| -> Result<f32, String> {
+                words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a number"))
+            };
+            let flag = |i: usize| -> Result<bool, String> {
+                match words.get(i) {
+                    Some(&"0") => Ok(false),
+                    Some(&"1") => Ok(true),
+                    _ => Err(err("expected 0 or 1")),
+                }
+            };
+            let int = |i: usize| -> Result<u64, String> {
+                words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected an integer"))
+            };
+
+            match words.as_slice() {
+                [] => {}
+                ["scene", "builtin", name] => replay.scene = SceneSource::Builtin(name.to_string()),
+                ["scene", "file", ..] => {
+                    // the path is the rest of the line, spaces and all
+                    let path = line.trim_start()[words[0].len()..].trim_start()[words[1].len()..].trim();
+                    replay.scene = SceneSource::File(PathBuf::from(path));
+                }
+                ["scene", "csv", ..] => {
+                    let paths = line.trim_start()[words[0].len()..].trim_start()[words[1].len()..].trim();
+                    let (nodes, edges) =
+                        paths.split_once('\t').ok_or_else(|| err("expected two tab-separated paths"))?;
+                    let (nodes, edges) = (PathBuf::from(nodes), PathBuf::from(edges));
+                    replay.scene = SceneSource::Csv { nodes, edges };
+                }
+                ["scene", "image", edge, ..] => {
+                    let pin =
+                        PinEdge::from_name(edge).ok_or_else(|| err("expected top, bottom, left, or right"))?;
+                    let rest = &line.trim_start()[words[0].len()..].trim_start()[words[1].len()..];
+                    let path = rest.trim_start()[edge.len()..].trim();
+                    replay.scene = SceneSource::Image { path: PathBuf::from(path), pin };
+                }
+                ["scene", "stress", _] => replay.scene = SceneSource::Stress { nodes: int(2)? as usize },
+                ["view", ..] => replay.view = Vec2::new(num(1)?, num(2)?),
+                ["params", ..] => {
+                    replay.params = Params {
+                        dt: num(1)?,
+                        gravity: num(2)?,
+                        drag: num(3)?,
+                        rigidity: num(4)?,
+                        solver_iterations: int(5)? as usize,
+                        break_threshold: num(6)?,
+                    }
+                }
+                ["seed", ..] => replay.seed = int(1)?,
+                ["variance", ..] => replay.variance = num(1)?,
+                ["math", mode] => replay.math = mode.to_string(),
+                ["length", ..] => replay.length = int(1)?,
+                [_, "input", ..] => {
+                    let input = StepInput {
+                        cursor: Vec2::new(num(2)?, num(3)?),
+                        wind: flag(4)?,
+                        knife: flag(5)?,
+                        // replays from before the knife had a radius cut along a line, every crossing
+                        knife_radius: if words.len() > 6 { num(6)? } else { 0.0 },
+                        single_cut: if words.len() > 7 { flag(7)? } else { false },
+                        split_nodes: if words.len() > 8 { flag(8)? } else { false },
+                        winch: if words.len() > 9 { num(9)? } else { 0.0 },
+                        dispense: if words.len() > 10 { flag(10)? } else { false },
+                        push_radius: if words.len() > 11 { num(11)? } else { 0.0 },
+                        // wind used to stop dead at its edge
+                        wind_brush: if words.len() > 14 {
+                            WindBrush { radius: num(12)?, strength: num(13)?, falloff: num(14)? }
+                        } else {
+                            WindBrush { falloff: 0.0, ..WindBrush::default() }
+                        },
+                    };
+                    replay.events.push((int(0)?, ReplayEvent::Input(input)));
+                }
+                [_, "hit_stop", ..] => replay.events.push((int(0)?, ReplayEvent::HitStop(flag(2)?))),
+                _ => return Err(err("unrecognized line")),
+            }
+        }
+
+        Ok(replay)
+    }
+}
+
+/// `input` as a replay writes it for `step`, without the newline.
+pub fn input_line(step: u64, input: &StepInput) -> String {
+    format!(
+        "{} input {} {} {} {} {} {} {} {} {} {} {} {} {}",
+        step,
+        input.cursor.x,
+        input.cursor.y,
+        input.wind as u8,
+        input.knife as u8,
+        input.knife_radius,
+        input.single_cut as u8,
+        input.split_nodes as u8,
+        input.winch,
+        input.dispense as u8,
+        input.push_radius,
+        input.wind_brush.radius,
+        input.wind_brush.strength,
+        input.wind_brush.falloff
+    )
+}
+
+/// Appends events to a replay as they happen, skipping repeats.
+pub struct Recorder {
+    pub replay: Replay,
+    last_input: Option<StepInput>,
+    last_hit_stop: Option<bool>,
+}
+
+impl Recorder {
+    pub fn new(scene: SceneSource, view: Vec2, params: Params, seed: u64) -> Self {
+        Self {
+            replay: Replay::new(scene, view, params, seed),
+            last_input: None,
+            last_hit_stop: None,
+        }
+    }
+
+    pub fn record(&mut self, step: u64, input: StepInput, hit_stop: bool) {
+        if self.last_hit_stop != Some(hit_stop) {
+            self.replay.events.push((step, ReplayEvent::HitStop(hit_stop)));
+            self.last_hit_stop = Some(hit_stop);
+        }
+
+        if self.last_input != Some(input) {
+            self.replay.events.push((step, ReplayEvent::Input(input)));
+            self.last_input = Some(input);
+        }
+
+        self.replay.length = step + 1;
+    }
+}
+
+/// Feeds a replay's events back one step at a time.
+pub struct Player {
+    replay: Replay,
+    next_event: usize,
+    input: StepInput,
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_event: 0,
+            input: StepInput {
+                cursor: Vec2::ZERO,
+                wind: false,
+                wind_brush: WindBrush::default(),
+                knife: false,
+                knife_radius: 0.0,
+                single_cut: false,
+                split_nodes: false,
+                winch: 0.0,
+                dispense: false,
+                push_radius: 0.0,
+            },
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn is_finished(&self, step: u64) -> bool {
+        step >= self.replay.length
+    }
+
+    /// Applies the events for `step`, returning the step's input and any hit-stop change.
+    pub fn advance(&mut self, step: u64) -> (StepInput, Option<bool>) {
+        let mut hit_stop = None;
+        while let Some(&(event_step, event)) = self.replay.events.get(self.next_event) {
+            if event_step > step {
+                break;
+            }
+            match event {
+                ReplayEvent::Input(input) => self.input = input,
+                ReplayEvent::HitStop(enabled) => hit_stop = Some(enabled),
+            }
+            self.next_event += 1;
+        }
+        (self.input, hit_stop)
+    }
+}
@@ -0,0 +1,128 @@
+//! Keeping a scene in place when the window changes size. Built-in scenes
+//! are laid out once for the window they start in, a rope a third of the way
+//! across and the ground near the bottom, so widening the window leaves the
+//! rope off to one side and the ground floating above the new bottom edge.
+//! `reanchor` moves what holds the scene up along with the window.
+
+use crate::anchor::AnchorPath;
+use crate::rail::Curve;
+use crate::world::World;
+use glam::Vec2;
+
+/// Moves what holds `world` up as a window of `old` size becomes `new`. Each
+/// pinned node, moving anchor, platform, rotor, well, sink, and rail keeps
+/// its fraction of the way across and down, moving whole without changing
+/// shape, and the ground keeps its height above the bottom edge. Walls and
+/// wrapping edges along the old window's right or bottom edge, or the
+/// ground, go to the new one's. Free nodes are left for the links to pull
+/// along.
+pub fn reanchor(world: &mut World, old: Vec2, new: Vec2) {
+    if old == new || old.x <= 0.0 || old.y <= 0.0 {
+        return;
+    }
+    let ratio = new / old;
+    let shift = |pos: Vec2| pos * ratio - pos;
+    let mut moved = vec![false; world.arena.len()];
+    let arena = &mut world.arena;
+    let mut move_node = |node: usize, by: Vec2| {
+        arena[node].pos += by;
+        arena[node].last_pos += by;
+        moved[node] = true;
+    };
+
+    for anchor in world.anchors.iter_mut() {
+        let by = shift(anchor.path.at(anchor.travelled));
+        translate_path(&mut anchor.path, by);
+        move_node(anchor.node, by);
+    }
+    for platform in world.platforms.iter_mut() {
+        let by = shift(platform.position());
+        translate_path(&mut platform.path, by);
+        platform.riders.iter().for_each(|&(rider, _)| move_node(rider, by));
+    }
+    let pinned = world.arena.iter_mut().zip(moved).filter(|(node, moved)| node.fixed && !moved);
+    for (node, _) in pinned {
+        let by = shift(node.pos);
+        node.pos += by;
+        node.last_pos += by;
+    }
+
+    for rotor in world.rotors.iter_mut() {
+        rotor.center += shift(rotor.center);
+    }
+    for well in world.wells.iter_mut() {
+        well.pos += shift(well.pos);
+    }
+    for sink in world.sinks.iter_mut() {
+        sink.pos += shift(sink.pos);
+    }
+    for rail in world.rails.iter_mut() {
+        let points = match &mut rail.curve {
+            Curve::Polyline(points) => points.as_mut_slice(),
+            Curve::Bezier(points) => points.as_mut_slice(),
+        };
+        let by = points.first().map_or(Vec2::ZERO, |&first| shift(first));
+        points.iter_mut().for_each(|point| *point += by);
+    }
+
+    let (old_ground, ground) = (world.ground_y, world.ground_y + new.y - old.y);
+    let edge = |corner: Vec2| {
+        let x = if corner.x == old.x { new.x } else { corner.x };
+        let y = match corner.y {
+            y if y == old_ground => ground,
+            y if y == old.y => new.y,
+            y => y,
+        };
+        Vec2::new(x, y)
+    };
+    world.walls.max = edge(world.walls.max);
+    world.wrap.max = edge(world.wrap.max);
+    world.ground_y = ground;
+}
+
+/// Moves every point of `path` by `by`.
+fn translate_path(path: &mut AnchorPath, by: Vec2) {
+    match path {
+        AnchorPath::Line(a, b) => (*a, *b) = (*a + by, *b + by),
+        AnchorPath::Circle { center, .. } | AnchorPath::Oscillate { center, .. } => *center += by,
+        AnchorPath::Waypoints(points) => points.iter_mut().for_each(|point| *point += by),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::Anchor;
+    use crate::scene;
+    use crate::walls::Walls;
+
+    #[test]
+    fn pins_and_the_ground_follow_the_window() {
+        let (old, new) = (Vec2::new(900.0, 600.0), Vec2::new(1800.0, 900.0));
+        let mut world = scene::default_rope(old.x, old.y);
+        world.walls = Walls { max: Vec2::new(old.x, world.ground_y), right: true, ..Walls::default() };
+        let line = AnchorPath::Line(Vec2::new(100.0, 100.0), Vec2::new(200.0, 100.0));
+        let end = world.arena.len() - 1;
+        world.anchors.push(Anchor::new(end, line, 1.0));
+        world.arena[end].fixed = true;
+        (world.arena[end].pos, world.arena[end].last_pos) = (Vec2::new(100.0, 100.0), Vec2::new(100.0, 100.0));
+        let before = world.clone();
+
+        reanchor(&mut world, old, new);
+        assert_eq!(world.arena[0].pos, before.arena[0].pos * Vec2::new(2.0, 1.5));
+        assert_eq!(world.arena[0].last_pos, world.arena[0].pos);
+        // free nodes stay for the rope to pull along
+        assert_eq!(world.arena[1].pos, before.arena[1].pos);
+        assert_eq!(world.ground_y, new.y - scene::GROUND_OFFSET);
+        assert_eq!(world.walls.max, Vec2::new(new.x, world.ground_y));
+        // the anchor's line moves with where it is, keeping its length
+        assert_eq!(world.anchors[0].path, AnchorPath::Line(Vec2::new(200.0, 150.0), Vec2::new(300.0, 150.0)));
+        assert_eq!(world.arena[end].pos, Vec2::new(200.0, 150.0));
+
+        // and sizing it back puts everything where it was
+        reanchor(&mut world, new, old);
+        let back = world.arena.iter().zip(before.arena.iter()).all(|(node, was)| node.pos.distance(was.pos) < 1e-3);
+        assert!(back);
+        assert_eq!((world.ground_y, world.walls), (before.ground_y, before.walls));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::geometry;
+use crate::math;
+use crate::node::Node;
+use crate::{CONTACT_SLOP, NODE_RADIUS};
+use glam::Vec2;
+
+/// A spinning obstacle, like a paddle wheel or a gear: a hub with blades
+/// sticking straight out of it, evenly spaced. Nodes can't pass through it,
+/// and friction drags the ones touching it along with its surface.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rotor {
+    pub center: Vec2,
+    pub hub_radius: f32,
+    pub blades: usize,
+    /// From the center to the tip of each blade.
+    pub blade_length: f32,
+    /// Half the thickness of each blade.
+    pub blade_width: f32,
+    /// Turn of the first blade from pointing right, clockwise on screen.
+    pub angle: f32,
+    /// Radians per unit of time, clockwise on screen for positive speeds.
+    pub spin: f32,
+    /// How much of the slip between a touching node and the surface goes away
+    /// each time it's solved, from 0 for ice to 1 for nodes that don't slip at all.
+    pub friction: f32,
+    /// Angle turned in the last step, for working out how far the surface moved.
+    pub turned: f32,
+    /// The layer it's on, so it only touches nodes on layers that collide with it.
+    pub layer: u8,
+}
+
+impl Rotor {
+    pub fn new(center: Vec2, hub_radius: f32, blades: usize, blade_length: f32, spin: f32, friction: f32) -> Self {
+        Self {
+            center,
+            hub_radius,
+            blades,
+            blade_length,
+            blade_width: NODE_RADIUS,
+            angle: 0.0,
+            spin,
+            friction,
+            turned: 0.0,
+            layer: 0,
+        }
+    }
+
+    /// Turns the rotor `dt` further.
+    pub fn advance(&mut self, dt: f32) {
+        self.turned = self.spin * dt;
+        self.angle += self.turned;
+    }
+
+    /// The tip of each blade.
+    pub fn tips(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.blades).map(move |i| {
+            let angle = self.angle + std::f32::consts::TAU * i as f32 / self.blades as f32;
+            self.center + math::from_angle(angle) * self.blade_length
+        })
+    }
+
+    /// The deepest of the hub and the blades a node at `pos` is in, or within
+    /// `slop` of touching, as the point on its axis nearest the node and how
+    /// far inside the surface the node is.
+    fn contact(&self, pos: Vec2, slop: f32) -> Option<(Vec2, f32)> {
+        let hub = (self.center, self.hub_radius + NODE_RADIUS);
+        let blades = self.tips().map(|tip| {
+            let t = geometry::nearest_on_segment(pos, self.center, tip);
+            (self.center.lerp(tip, t), self.blade_width + NODE_RADIUS)
+        });
+        std::iter::once(hub)
+            .chain(blades)
+            .map(|(axis, reach)| (axis, reach - pos.distance(axis)))
+            .filter(|&(_, depth)| depth > -slop)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// How far along the path from `from` to `to` a node would first touch the
+    /// rotor as it is now, or `None` if it wouldn't or already does.
+    pub fn sweep(&self, from: Vec2, to: Vec2) -> Option<f32> {
+        if self.contact(from, 0.0).is_some() {
+            return None;
+        }
+        let hub = geometry::sweep_circle(from, to, self.center, self.hub_radius + NODE_RADIUS);
+        let reach = self.blade_width + NODE_RADIUS;
+        let blades = self.tips().map(|tip| geometry::sweep_capsule(from, to, self.center, tip, reach));
+        std::iter::once(hub).chain(blades).flatten().min_by(f32::total_cmp)
+    }
+
+    /// Which way is out of the surface at `pos`, from the point `axis` on it.
+    fn normal(&self, pos: Vec2, axis: Vec2) -> Vec2 {
+        (pos - axis).try_normalize().unwrap_or_else(|| (pos - self.center).normalize_or_zero())
+    }
+
+    /// Pushes `node` out of the rotor if it's inside, then takes away `friction`
+    /// of its slip along the surface it's touching since its last position.
+    pub fn collide(&self, node: &mut Node) {
+        if node.fixed {
+            return;
+        }
+        let Some((axis, depth)) = self.contact(node.pos, 0.0) else {
+            return;
+        };
+
+        let normal = self.normal(node.pos, axis);
+        node.pos += normal * depth;
+
+        // where the bit of surface now under the node was a step ago
+        let before = self.center + math::from_angle(-self.turned).rotate(node.pos - self.center);
+        let slip = (node.pos - node.last_pos) - (node.pos - before);
+        let tangent = normal.perp();
+        node.pos -= tangent * slip.dot(tangent) * self.friction;
+    }
+
+    /// Sends `node` back off the rotor if it's touching it and was heading
+    /// into it, with its `restitution` of the speed it hit with, measured
+    /// against the surface's own movement. Runs after its velocity is worked
+    /// out, with the velocity from before the step's corrections as `incoming`.
+    pub fn bounce(&self, node: &mut Node, incoming: Vec2) {
+        if node.fixed || node.restitution == 0.0 {
+            return;
+        }
+        let Some((axis, _)) = self.contact(node.pos, CONTACT_SLOP) else {
+            return;
+        };
+
+        let normal = self.normal(node.pos, axis);
+        let surface = (node.pos - self.center).perp() * self.spin;
+        let hit = (incoming - surface).dot(normal);
+        if hit < 0.0 {
+            let now = (node.vel - surface).dot(normal);
+            node.vel += normal * (-hit * node.restitution - now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushes_nodes_out_and_drags_them_round() {
+        let mut rotor = Rotor::new(Vec2::ZERO, 10.0, 0, 0.0, 0.1, 1.0);
+        rotor.advance(1.0);
+
+        // a node at rest just inside the hub's right edge
+        let mut node = Node::with_pos_and_mass(Vec2::new(12.0, 0.0), 1.0);
+        rotor.collide(&mut node);
+        assert!((node.pos.x - (10.0 + NODE_RADIUS)).abs() < 1e-4, "{}", node.pos);
+        // with full friction it's carried down, clockwise on screen, as far as the surface moved
+        let carried = (10.0 + NODE_RADIUS) * 0.1_f32.sin();
+        assert!((node.pos.y - carried).abs() < 0.05, "{}", node.pos);
+
+        // nodes clear of it are left alone
+        let mut clear = Node::with_pos_and_mass(Vec2::new(30.0, 0.0), 1.0);
+        rotor.collide(&mut clear);
+        assert_eq!(clear.pos, Vec2::new(30.0, 0.0));
+
+        // a bouncy node that hit the still edge of the hub comes back off it
+        let still = Rotor { spin: 0.0, ..rotor };
+        let mut ball = Node { restitution: 0.5, ..Node::with_pos_and_mass(Vec2::new(10.0 + NODE_RADIUS, 0.0), 1.0) };
+        still.bounce(&mut ball, Vec2::new(-8.0, 1.0));
+        assert!((ball.vel - Vec2::new(4.0, 0.0)).length() < 1e-4, "{}", ball.vel);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::constraint::Constraint;
+use crate::friction::Friction;
+use crate::geometry;
+use crate::layer::Layers;
+use crate::node::Node;
+use crate::rng::Rng;
+use crate::spatial_hash::SpatialHash;
+use crate::wrap::Wrap;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+pub const GRAIN_MASS: f32 = 0.5;
+/// Grains poured per unit of time.
+pub const POUR_RATE: f32 = 40.0;
+/// Grains poured at a time from the Sand window.
+pub const POUR_GRAINS: usize = 300;
+/// How far to either side of the spout grains start, so they don't stack in a column.
+const SPOUT_WIDTH: f32 = 2.0 * NODE_RADIUS;
+
+/// Sand, as free nodes that collide with each other and with links, with
+/// the world's friction, so they pile up instead of flowing flat and can be caught in a
+/// net or a bucket made of rope. Grains are ordinary nodes otherwise, so they
+/// fall, sleep, and get consumed like any other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sand {
+    /// The grains, by index into the arena.
+    pub grains: Vec<usize>,
+    /// Where poured grains start.
+    pub spout: Vec2,
+    /// Grains poured per unit of time.
+    pub rate: f32,
+    pub grain_mass: f32,
+    /// Grains still to be poured.
+    pub pouring: usize,
+    /// Grains due but not poured yet, carried over so low rates still pour.
+    pub due: f32,
+}
+
+impl Sand {
+    /// Sand that pours from `spout`, once given grains to pour.
+    pub fn new(spout: Vec2) -> Self {
+        Self {
+            grains: Vec::new(),
+            spout,
+            rate: POUR_RATE,
+            grain_mass: GRAIN_MASS,
+            pouring: 0,
+            due: 0.0,
+        }
+    }
+
+    /// Pours the grains due in the next `dt` at the spout, each added with
+    /// `spawn`, which returns the new node's index or None if there's no room.
+    pub fn pour(&mut self, rng: &mut Rng, dt: f32, mut spawn: impl FnMut(Node) -> Option<usize>) {
+        if self.pouring == 0 {
+            self.due = 0.0;
+            return;
+        }
+        self.due += self.rate * dt;
+        while self.due >= 1.0 && self.pouring > 0 {
+            let pos = self.spout + Vec2::new(rng.range(-SPOUT_WIDTH, SPOUT_WIDTH), 0.0);
+            let Some(grain) = spawn(Node::with_pos_and_mass(pos, self.grain_mass)) else {
+                // wait with one grain due, rather than a heap of them all at once when there's room
+                self.due = 1.0;
+                return;
+            };
+            self.due -= 1.0;
+            self.pouring -= 1;
+            // a recycled node may have been a grain already
+            self.grains.retain(|&other| other != grain);
+            self.grains.push(grain);
+        }
+    }
+
+    /// Pushes overlapping grains apart, and grains out of links, each side by
+    /// its inverse mass, with `friction` against them sliding past each other.
+    /// Grains that moved right across a link this step are sent back the way
+    /// they came. Grains and links on layers that don't collide pass through.
+    pub fn collide(
+        &self,
+        arena: &mut [Node],
+        constraints: &[Constraint],
+        wrap: &Wrap,
+        friction: &Friction,
+        layers: &Layers,
+    ) {
+        let touching = 2.0 * NODE_RADIUS;
+        let hash = SpatialHash::new(touching, self.grains.iter().map(|&grain| arena[grain].pos));
+
+        for (i, &a) in self.grains.iter().enumerate() {
+            for j in hash.near(arena[a].pos, touching).filter(|&j| j > i) {
+                let b = self.grains[j];
+                if !layers.collide(arena[a].layer, arena[b].layer) {
+                    continue;
+                }
+                let offset = arena[a].pos - arena[b].pos;
+                let distance = offset.length();
+                if distance >= touching || distance == 0.0 {
+                    continue;
+                }
+                let normal = offset / distance;
+                let (a_weight, b_weight) = (arena[a].inverse_mass(), arena[b].inverse_mass());
+                let total = a_weight + b_weight;
+                if total == 0.0 {
+                    continue;
+                }
+                let depth = touching - distance;
+                let slip = (arena[a].pos - arena[a].last_pos) - (arena[b].pos - arena[b].last_pos);
+                let push = (normal * depth - friction.grip(slip, normal, depth)) / total;
+                arena[a].add_offs(push * a_weight);
+                arena[b].add_offs(-push * b_weight);
+            }
+        }
+
+        for constraint in constraints.iter() {
+            let (a, b) = (constraint.a, constraint.b);
+            let start = arena[a].pos;
+            let end = start + constraint.offset(arena, wrap);
+            for i in hash.within(start.min(end) - touching, start.max(end) + touching) {
+                let grain = self.grains[i];
+                let apart = !layers.collide_link(arena[grain].layer, arena[a].layer, arena[b].layer);
+                if grain == a || grain == b || apart {
+                    continue;
+                }
+                let node = arena[grain];
+                let t = geometry::nearest_on_segment(node.pos, start, end);
+                let offset = node.pos - start.lerp(end, t);
+                let distance = offset.length();
+                let (normal, depth) = if geometry::segments_intersect(node.last_pos, node.pos, start, end) {
+                    let side = geometry::orientation(start, end, node.last_pos).signum();
+                    ((end - start).perp().normalize_or_zero() * side, NODE_RADIUS + distance)
+                } else if distance < NODE_RADIUS && distance > 0.0 {
+                    (offset / distance, NODE_RADIUS - distance)
+                } else {
+                    continue;
+                };
+
+                let (a_weight, b_weight) = ((1.0 - t) * arena[a].inverse_mass(), t * arena[b].inverse_mass());
+                let total = node.inverse_mass() + (1.0 - t) * a_weight + t * b_weight;
+                if total == 0.0 {
+                    continue;
+                }
+                let link_moved = (arena[a].pos - arena[a].last_pos).lerp(arena[b].pos - arena[b].last_pos, t);
+                let slip = (node.pos - node.last_pos) - link_moved;
+                let push = (normal * depth - friction.grip(slip, normal, depth)) / total;
+                arena[grain].add_offs(push * node.inverse_mass());
+                arena[a].add_offs(-push * a_weight);
+                arena[b].add_offs(-push * b_weight);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grains_push_apart_and_grip() {
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(0.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(0.0, 10.0), 1.0),
+        ];
+        // the top grain slid a little sideways onto the bottom one this step
+        arena[0].last_pos = Vec2::new(-0.5, 0.0);
+        let sand = Sand { grains: vec![0, 1], ..Sand::new(Vec2::ZERO) };
+        sand.collide(&mut arena, &[], &Wrap::default(), &Friction::default(), &Layers::default());
+
+        // they're pushed apart, the 2 unit overlap split evenly
+        assert!((arena[1].pos.y - arena[0].pos.y - 2.0 * NODE_RADIUS).abs() < 1e-5);
+        // and a half unit of slip under a 2 unit overlap is held by static friction
+        assert!((arena[0].pos.x - arena[0].last_pos.x - (arena[1].pos.x - arena[1].last_pos.x)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pours_from_the_spout_until_done() {
+        let mut sand = Sand { pouring: 3, rate: 10.0, ..Sand::new(Vec2::new(100.0, 0.0)) };
+        let (mut arena, mut rng) = (Vec::new(), Rng::default());
+        for _ in 0..10 {
+            sand.pour(&mut rng, 0.15, |node| {
+                arena.push(node);
+                Some(arena.len() - 1)
+            });
+        }
+        assert_eq!(arena.len(), 3);
+        assert_eq!(sand.grains, vec![0, 1, 2]);
+        assert!(arena.iter().all(|node| (node.pos.x - 100.0).abs() <= SPOUT_WIDTH && node.mass == GRAIN_MASS));
+    }
+}
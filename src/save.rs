@@ -0,0 +1,119 @@
+//! Saved games: a world mid-run, with what a scene file leaves out so it
+//! carries on where it left off. The text starts with a `clothsim-save 1`
+//! header, then `step <steps run>`,
+//! `params <dt> <gravity> <drag> <rigidity> <solver iterations> <break threshold>`,
+//! `rng <state>`, a `vel <node> <x> <y>` line for each moving node, and
+//! finally a `scene` line followed by the world's layout as `scene::to_text`
+//! writes it. Anything the scene format starts over, like a muscle's swing
+//! or the air's currents, starts over on loading too.
+
+use crate::params::Params;
+use crate::rng::Rng;
+use crate::scene;
+use crate::world::World;
+use glam::Vec2;
+use std::fmt::Write as _;
+
+const HEADER: &str = "clothsim-save 1";
+
+/// Writes `world`, `step` steps into its run.
+pub fn to_text(world: &World, step: u64) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER).unwrap();
+    writeln!(out, "step {}", step).unwrap();
+    let p = &world.params;
+    writeln!(
+        out,
+        "params {} {} {} {} {} {}",
+        p.dt, p.gravity, p.drag, p.rigidity, p.solver_iterations, p.break_threshold
+    )
+    .unwrap();
+    writeln!(out, "rng {}", world.rng.state()).unwrap();
+    for (i, node) in world.arena.iter().enumerate().filter(|(_, node)| node.vel != Vec2::ZERO) {
+        writeln!(out, "vel {} {} {}", i, node.vel.x, node.vel.y).unwrap();
+    }
+    writeln!(out, "scene").unwrap();
+    out += &scene::to_text(world);
+    out
+}
+
+/// Reads a saved world back, with the number of steps it had run.
+pub fn from_text(text: &str) -> Result<(World, u64), String> {
+    if text.lines().next() != Some(HEADER) {
+        return Err(format!("missing \"{}\" header", HEADER));
+    }
+    let (mut step, mut params, mut rng) = (0, Params::default(), Rng::default());
+    let mut velocities = Vec::new();
+    let mut lines = text.lines().enumerate().skip(1);
+    for (i, line) in lines.by_ref() {
+        let err = |what: &str| format!("line {}: {}", i + 1, what);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let num = |i: usize| -> Result<f32, String> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a number"))
+        };
+        let int = |i: usize| -> Result<u64, String> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a whole number"))
+        };
+        match words.as_slice() {
+            [] => {}
+            ["step", ..] => step = int(1)?,
+            ["params", ..] => {
+                params = Params {
+                    dt: num(1)?,
+                    gravity: num(2)?,
+                    drag: num(3)?,
+                    rigidity: num(4)?,
+                    solver_iterations: int(5)? as usize,
+                    break_threshold: num(6)?,
+                }
+            }
+            ["rng", ..] => rng = Rng::new(int(1)?),
+            ["vel", ..] => velocities.push((int(1)? as usize, Vec2::new(num(2)?, num(3)?), i)),
+            ["scene"] => break,
+            _ => return Err(err("expected step, params, rng, vel, or scene")),
+        }
+    }
+
+    // the scene's line numbers count from its own header
+    let start = lines.clone().next().map_or(0, |(i, _)| i + 1);
+    let scene: Vec<&str> = lines.map(|(_, line)| line).collect();
+    let mut world =
+        scene::from_text(&scene.join("\n")).map_err(|e| format!("in the scene from line {}: {}", start, e))?;
+    // the links' break thresholds were saved already scaled to these params
+    world.params = params;
+    world.rng = rng;
+    for (node, vel, i) in velocities {
+        world.arena.get_mut(node).ok_or_else(|| format!("line {}: no node {}", i + 1, node))?.vel = vel;
+    }
+    Ok((world, step))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+
+    #[test]
+    fn a_loaded_game_carries_on_the_same() {
+        let mut world = scene::cloth(800.0, 600.0);
+        world.params.gravity *= 2.0;
+        for _ in 0..50 {
+            world.step(world.params.dt);
+        }
+        // crash dumps add comments at the end
+        let (mut loaded, step) = from_text(&(to_text(&world, 50) + "# failed\n")).unwrap();
+        assert_eq!(step, 50);
+        assert_eq!(loaded.params, world.params);
+        assert_eq!(loaded.rng, world.rng);
+        for _ in 0..50 {
+            world.step(world.params.dt);
+            loaded.step(loaded.params.dt);
+        }
+        for (a, b) in world.arena.iter().zip(&loaded.arena) {
+            assert!(a.pos.distance(b.pos) < 1e-2, "{} vs {}", a.pos, b.pos);
+        }
+
+        assert!(from_text("clothsim-scene 1\n").is_err());
+        assert!(from_text("clothsim-save 1\nvel 99999 1 0\nscene\nclothsim-scene 1\n").is_err());
+    }
+}
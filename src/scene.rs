@@ -0,0 +1,1250 @@
+//! Built-in scenes and the plain-text scene format.
+
+use crate::anchor::{Anchor, AnchorPath};
+use crate::ball::Ball;
+use crate::band::Band;
+use crate::budget::{AtCap, NodeBudget};
+use crate::charge::Electrostatics;
+use crate::cleanup::Cleanup;
+use crate::constraint::Constraint;
+use crate::contact::Contacts;
+use crate::fluid::Fluid;
+use crate::fray::Fray;
+use crate::friction::Friction;
+use crate::group;
+use crate::layer::{Layer, LAYERS};
+use crate::magnet::Magnet;
+use crate::math;
+use crate::multigrid::Multigrid;
+use crate::shatter::Shatter;
+use crate::muscle::Muscle;
+use crate::dispenser::Dispenser;
+use crate::node::Node;
+use crate::params::Params;
+use crate::platform::Platform;
+use crate::rail::{Curve, Rail};
+use crate::rain::Rain;
+use crate::rotor::Rotor;
+use crate::sand::Sand;
+use crate::slider::Slider;
+use crate::solve_order::SolveOrder;
+use crate::units::Units;
+use crate::walls::Walls;
+use crate::water::{Droplet, Water};
+use crate::weave::Weave;
+use crate::well::{Sink, Well};
+use crate::winch::Winch;
+use crate::wrap::Wrap;
+use crate::world::World;
+use crate::xpbd::Xpbd;
+use crate::{NODE_RADIUS, TARGET_DIST};
+use glam::Vec2;
+use std::fmt::Write as _;
+
+const ROPE_POINTS: usize = 10;
+const CLOTH_COLUMNS: usize = 12;
+const CLOTH_ROWS: usize = 8;
+const CLOTH_PIN_EVERY: usize = 3;
+const RESONANCE_POINTS: usize = 8;
+const RESONANCE_AMPLITUDE: f32 = 20.0;
+/// Cycles per unit of time, a little under the chain's slowest swing.
+const RESONANCE_FREQUENCY: f32 = 0.03;
+const ZIPLINE_POINTS: usize = 16;
+/// Mass of the node riding the zip-line, heavy enough to pull a clear dip into it.
+const ZIPLINE_LOAD: f32 = 10.0;
+/// Half the gap between the slingshot's prongs.
+const SLINGSHOT_FORK: f32 = 50.0;
+const SLINGSHOT_BAND_STIFFNESS: f32 = 16.0;
+/// Mass of each end of the pouch, together as heavy as the stone so it hands on most of its speed.
+const SLINGSHOT_POUCH_MASS: f32 = 2.0;
+const SLINGSHOT_STONE_RADIUS: f32 = 12.0;
+const SLINGSHOT_STONE_MASS: f32 = 4.0;
+const WALL_COLUMNS: usize = 4;
+const WALL_ROWS: usize = 7;
+/// Speed into the wall at which the stone breaks its links.
+const SLINGSHOT_SMASH_SPEED: f32 = 60.0;
+const PENDULUM_LENGTH: f32 = 4.0 * TARGET_DIST;
+/// Angle the pendulum is let go from, about 20 degrees.
+const PENDULUM_ANGLE: f32 = 0.35;
+/// Mass of the pendulum's and the spring's bobs, heavy so drag takes a while to stop them.
+const BOB_MASS: f32 = 10.0;
+const SPRING_REST_LENGTH: f32 = 2.0 * TARGET_DIST;
+/// Stiff enough for a bob of `BOB_MASS` to bounce about once every 12 units of time.
+const SPRING_STIFFNESS: f32 = 2.5;
+const BENCH_ROPE_POINTS: usize = 10;
+/// Mass of the weight on the end of the benchmark rope, enough to stretch it visibly under projection.
+const BENCH_LOAD: f32 = 100.0;
+const TRUSS_PANELS: usize = 6;
+/// Mass of each node along the truss's bottom chord, which carries the deck.
+const TRUSS_DECK_MASS: f32 = 5.0;
+const CRAWLER_LENGTH: f32 = 2.0 * TARGET_DIST;
+const CRAWLER_HEIGHT: f32 = 60.0;
+/// Mass of the crawler's back, heavy so leaning it over a foot pins that foot down.
+const CRAWLER_BACK_MASS: f32 = 5.0;
+/// Cycles per unit of time of the crawler's muscles, slow enough for its feet to grip.
+const CRAWLER_FREQUENCY: f32 = 0.05;
+const CURTAIN_ROWS: usize = 5;
+/// Nodes in each strand of a fringe, and how far apart.
+pub const FRINGE_STRAND_NODES: usize = 5;
+pub const FRINGE_LINK_LENGTH: f32 = TARGET_DIST / 3.0;
+const HAIR_STRANDS: usize = 24;
+const HAIR_STRAND_NODES: usize = 10;
+const HAIR_LINK_LENGTH: f32 = TARGET_DIST / 4.0;
+const HEAD_RADIUS: f32 = 60.0;
+/// Columns around the sleeve and the torus, and rows down each.
+const TUBE_COLUMNS: usize = 12;
+const SLEEVE_ROWS: usize = 8;
+const TORUS_ROWS: usize = 6;
+/// Nodes in the stress scene's cloth unless asked for another count.
+pub const STRESS_NODES: usize = 10_000;
+const STRESS_PIN_EVERY: usize = 8;
+
+const HEADER: &str = "clothsim-scene 1";
+
+/// Height of the ground above the bottom of the view in generated scenes.
+pub const GROUND_OFFSET: f32 = 80.0;
+
+/// Builds a scene for a `width` by `height` view.
+pub type SceneBuilder = fn(f32, f32) -> World;
+
+/// Every built-in scene by name.
+pub const BUILTIN: &[(&str, SceneBuilder)] = &[
+    ("rope", default_rope),
+    ("cloth", cloth),
+    ("resonance", resonance),
+    ("zipline", zipline),
+    ("slingshot", slingshot),
+    ("pendulum", pendulum),
+    ("spring", spring),
+    ("solver_bench", solver_bench),
+    ("truss", truss),
+    ("crawler", crawler),
+    ("fringe", fringe),
+    ("tube", tube),
+    ("stress", stress),
+];
+
+/// Builds the built-in scene called `name`.
+pub fn builtin(name: &str, width: f32, height: f32) -> Option<World> {
+    BUILTIN
+        .iter()
+        .find(|(builtin_name, _)| *builtin_name == name)
+        .map(|(_, build)| build(width, height))
+}
+
+/// The demo's starting scene: a single rope hanging a third of the way across
+/// a `width` by `height` view, above a ground near its bottom edge.
+pub fn default_rope(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    world.add_rope(Vec2::new(width / 3.0, height / 5.0), ROPE_POINTS);
+    world
+}
+
+/// A sheet of cloth centered across the top of the view, pinned along its top edge.
+pub fn cloth(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let cloth_width = (CLOTH_COLUMNS - 1) as f32 * TARGET_DIST;
+    let top_left = Vec2::new((width - cloth_width) / 2.0, height / 8.0);
+    world.add_cloth(top_left, CLOTH_COLUMNS, CLOTH_ROWS, CLOTH_PIN_EVERY);
+    world
+}
+
+/// A long chain hanging from an anchor that swings it side to side slowly
+/// enough for its lowest modes, for watching resonance as the drive's
+/// frequency changes.
+pub fn resonance(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let top = Vec2::new(width / 2.0, height / 8.0);
+    world.add_rope(top, RESONANCE_POINTS);
+    let drive = AnchorPath::Oscillate { center: top, amplitude: Vec2::new(RESONANCE_AMPLITUDE, 0.0) };
+    world.anchors.push(Anchor::new(0, drive, RESONANCE_FREQUENCY));
+    world
+}
+
+/// A rope pulled taut between a high anchor and a low one, with a heavy node
+/// sliding down it that drags a dip along with it as it goes.
+pub fn zipline(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let (top, bottom) = (Vec2::new(width * 0.1, height * 0.2), Vec2::new(width * 0.9, height * 0.5));
+    let rest_length = top.distance(bottom) / (ZIPLINE_POINTS - 1) as f32;
+    for i in 0..ZIPLINE_POINTS {
+        let mut node = Node::with_pos_and_mass(top.lerp(bottom, i as f32 / (ZIPLINE_POINTS - 1) as f32), 1.0);
+        node.fixed = i == 0 || i == ZIPLINE_POINTS - 1;
+        world.arena.push(node);
+        if i > 0 {
+            let break_threshold = TARGET_DIST * world.params.break_threshold;
+            world.constraints.push(Constraint { a: i - 1, b: i, rest_length, break_threshold });
+        }
+    }
+
+    let start = top.lerp(bottom, 0.5 / (ZIPLINE_POINTS - 1) as f32);
+    world.arena.push(Node::with_pos_and_mass(start, ZIPLINE_LOAD));
+    world.sliders.push(Slider { node: ZIPLINE_POINTS, rope: (0..ZIPLINE_POINTS).collect() });
+    world
+}
+
+/// A single heavy bob on a rigid link, let go from the side, for timing
+/// against the textbook pendulum.
+pub fn pendulum(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let pivot = Vec2::new(width / 2.0, height / 8.0);
+    let bob = pivot + Vec2::new(math::sin(PENDULUM_ANGLE), math::cos(PENDULUM_ANGLE)) * PENDULUM_LENGTH;
+    world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pivot, 1.0) });
+    world.arena.push(Node::with_pos_and_mass(bob, BOB_MASS));
+    let break_threshold = PENDULUM_LENGTH * world.params.break_threshold;
+    world.constraints.push(Constraint { a: 0, b: 1, rest_length: PENDULUM_LENGTH, break_threshold });
+    world
+}
+
+/// A single heavy bob hung from a band and let go at its rest length, so it
+/// bounces about where its weight stretches the band to.
+pub fn spring(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let top = Vec2::new(width / 2.0, height / 8.0);
+    world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(top, 1.0) });
+    world.arena.push(Node::with_pos_and_mass(top + Vec2::new(0.0, SPRING_REST_LENGTH), BOB_MASS));
+    let band = Band { a: 0, b: 1, rest_length: SPRING_REST_LENGTH, stiffness: SPRING_STIFFNESS, ..Band::default() };
+    world.bands.push(band);
+    world
+}
+
+/// A long rope with a heavy weight on its end, let go from the side, for
+/// comparing how the solvers hold up under load. The demo runs it side by
+/// side with XPBD on the right.
+pub fn solver_bench(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let top = Vec2::new(width * 0.3, height / 10.0);
+    world.add_rope(top, BENCH_ROPE_POINTS);
+    // swung out to the side, so it has to hold the weight through the bottom of the swing too
+    for (i, node) in world.arena.iter_mut().enumerate() {
+        node.pos = top + Vec2::new(TARGET_DIST * i as f32, 0.0);
+        node.last_pos = node.pos;
+    }
+    world.arena.last_mut().unwrap().mass = BENCH_LOAD;
+    world
+}
+
+/// A Pratt truss bridge pinned at both ends of its bottom chord, with the
+/// deck's weight on the bottom nodes, for reading off its member forces: the
+/// demo starts it with the statics overlay on. Its members are grouped as
+/// the `deck`, `top_chord`, `hangers`, and `diagonals`.
+pub fn truss(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let left = Vec2::new((width - TRUSS_PANELS as f32 * TARGET_DIST) / 2.0, height / 2.0);
+    for i in 0..=TRUSS_PANELS {
+        let mut node = Node::with_pos_and_mass(left + Vec2::new(TARGET_DIST * i as f32, 0.0), TRUSS_DECK_MASS);
+        node.fixed = i == 0 || i == TRUSS_PANELS;
+        world.arena.push(node);
+    }
+    // the top chord is a panel shorter at each end, where the end posts slope up to it
+    for i in 1..TRUSS_PANELS {
+        world.arena.push(Node::with_pos_and_mass(left + Vec2::new(TARGET_DIST * i as f32, -TARGET_DIST), 1.0));
+    }
+    let (bottom, top) = (|i: usize| i, |i: usize| TRUSS_PANELS + i);
+    let mut members = Vec::new();
+    for i in 0..TRUSS_PANELS {
+        members.push(("deck", bottom(i), bottom(i + 1)));
+        if (1..TRUSS_PANELS - 1).contains(&i) {
+            members.push(("top_chord", top(i), top(i + 1)));
+        }
+        if i > 0 {
+            members.push(("hangers", bottom(i), top(i)));
+        }
+        // diagonals slope down toward the middle, so they're in tension
+        let (a, b) = match i {
+            0 => (bottom(0), top(1)),
+            i if i == TRUSS_PANELS - 1 => (top(i), bottom(i + 1)),
+            i if i < TRUSS_PANELS / 2 => (top(i), bottom(i + 1)),
+            i => (bottom(i), top(i + 1)),
+        };
+        members.push(("diagonals", a, b));
+    }
+    for (name, a, b) in members {
+        group::tag(&mut world, name, [(a, b)]);
+        let rest_length = world.arena[a].pos.distance(world.arena[b].pos);
+        let break_threshold = rest_length * world.params.break_threshold;
+        world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+    }
+    world
+}
+
+/// A creature of two feet and a heavy back, every link a muscle. The one
+/// between the feet stretches and contracts while the legs lean the back
+/// over whichever foot should stay put, the back one as it stretches and the
+/// front one as it contracts, so friction holds that foot and it crawls right.
+pub fn crawler(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let feet = world.ground_y - NODE_RADIUS;
+    let rear = Vec2::new(width * 0.2, feet);
+    let front = rear + Vec2::new(CRAWLER_LENGTH, 0.0);
+    let positions = [rear, front, rear.lerp(front, 0.5) - Vec2::new(0.0, CRAWLER_HEIGHT)];
+    for (i, pos) in positions.into_iter().enumerate() {
+        world.arena.push(Node::with_pos_and_mass(pos, if i == 2 { CRAWLER_BACK_MASS } else { 1.0 }));
+    }
+    // the stride, then the back leg leaning in as it starts and the front leg as it ends
+    for (a, b, amplitude, phase) in [(0, 1, 0.2, 0.0), (0, 2, 0.15, 0.75), (1, 2, 0.15, 0.25)] {
+        let rest_length = positions[a].distance(positions[b]);
+        let break_threshold = rest_length * world.params.break_threshold;
+        world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+        world.muscles.push(Muscle::new(a, b, rest_length, amplitude, CRAWLER_FREQUENCY, phase));
+    }
+    world
+}
+
+/// A curtain with a fringe along its bottom edge, beside a head of hair, for
+/// trying the solver's level of detail out on lots of short strands.
+pub fn fringe(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    world.add_cloth(Vec2::new(width / 10.0, height / 8.0), CLOTH_COLUMNS, CURTAIN_ROWS, CLOTH_PIN_EVERY);
+    let bottom_edge: Vec<usize> = (world.arena.len() - CLOTH_COLUMNS..world.arena.len()).collect();
+    world.add_fringe(&bottom_edge, FRINGE_STRAND_NODES, FRINGE_LINK_LENGTH);
+
+    // pinned roots from one side of the head over the top to the other, with a strand from each
+    let center = Vec2::new(width * 0.8, height / 4.0);
+    let first = world.arena.len();
+    for i in 0..HAIR_STRANDS {
+        let angle = std::f32::consts::PI * (1.0 + i as f32 / (HAIR_STRANDS - 1) as f32);
+        let pos = center + Vec2::new(angle.cos(), angle.sin()) * HEAD_RADIUS;
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pos, 1.0) });
+    }
+    let roots: Vec<usize> = (first..world.arena.len()).collect();
+    world.add_fringe(&roots, HAIR_STRAND_NODES, HAIR_LINK_LENGTH);
+    world
+}
+
+/// A sleeve of cloth hanging from its top edge, laid flat with its sides
+/// joined, and a torus, a sleeve with its ends joined too, dropped beside it.
+/// Neither has a free edge, so every link is part of a loop.
+pub fn tube(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let sleeve = Weave { join_sides: true, ..Weave::default() };
+    world.add_woven_cloth(Vec2::new(width / 4.0, height / 8.0), TUBE_COLUMNS, SLEEVE_ROWS, CLOTH_PIN_EVERY, &sleeve);
+
+    let first = world.arena.len();
+    let torus = Weave { join_ends: true, ..sleeve };
+    world.add_woven_cloth(Vec2::new(width * 0.6, height / 4.0), TUBE_COLUMNS, TORUS_ROWS, 1, &torus);
+    world.arena[first..].iter_mut().for_each(|node| node.fixed = false);
+    world
+}
+
+/// A cloth of `STRESS_NODES` nodes, for seeing how the solver and renderer
+/// hold up at scale; `stress_cloth` makes one of any size.
+pub fn stress(width: f32, height: f32) -> World {
+    stress_cloth(width, height, STRESS_NODES)
+}
+
+/// A cloth of about `nodes` nodes, three columns for every two rows, pinned
+/// every few nodes along its top and shrunk to fit across the view.
+pub fn stress_cloth(width: f32, height: f32, nodes: usize) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let columns = ((nodes as f32 * 1.5).sqrt().round() as usize).max(2);
+    let rows = (nodes / columns).max(2);
+    let top_left = Vec2::new(width * 0.05, height / 10.0);
+    world.add_cloth(top_left, columns, rows, STRESS_PIN_EVERY);
+
+    // laid out at the usual spacing, then everything shrunk about the top corner
+    let scale = (width * 0.9 / (columns - 1) as f32).min(TARGET_DIST) / TARGET_DIST;
+    for node in world.arena.iter_mut() {
+        node.pos = top_left + (node.pos - top_left) * scale;
+        node.last_pos = node.pos;
+    }
+    for constraint in world.constraints.iter_mut() {
+        constraint.rest_length *= scale;
+        constraint.break_threshold *= scale;
+    }
+    world
+}
+
+/// A slingshot aimed at a wall across the view. The pouch is a short link held
+/// between the prongs by two bands, with a stone sitting on a tee just in front
+/// of it: drag the pouch back in the editor and let go, and once the simulation
+/// resumes it springs forward and knocks the stone through the wall.
+pub fn slingshot(width: f32, height: f32) -> World {
+    let mut world = World::new(height - GROUND_OFFSET);
+    let fork = Vec2::new(width * 0.2, height * 0.45);
+    let break_threshold = TARGET_DIST * world.params.break_threshold;
+    let link = |a, b, rest_length| Constraint { a, b, rest_length, break_threshold };
+
+    // the prongs, on a handle down to the ground
+    let handle = Vec2::new(fork.x, world.ground_y);
+    for pos in [fork - Vec2::new(0.0, SLINGSHOT_FORK), fork + Vec2::new(0.0, SLINGSHOT_FORK), handle] {
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pos, 1.0) });
+    }
+    world.constraints.push(link(1, 2, handle.y - fork.y - SLINGSHOT_FORK));
+
+    // the pouch, pulled a little taut between them
+    let pouch = SLINGSHOT_STONE_RADIUS;
+    world.arena.push(Node::with_pos_and_mass(fork - Vec2::new(0.0, pouch), SLINGSHOT_POUCH_MASS));
+    world.arena.push(Node::with_pos_and_mass(fork + Vec2::new(0.0, pouch), SLINGSHOT_POUCH_MASS));
+    world.constraints.push(link(3, 4, 2.0 * pouch));
+    let rest_length = (SLINGSHOT_FORK - pouch) * 0.8;
+    world.bands.push(Band { a: 0, b: 3, rest_length, stiffness: SLINGSHOT_BAND_STIFFNESS, ..Band::default() });
+    world.bands.push(Band { a: 1, b: 4, rest_length, stiffness: SLINGSHOT_BAND_STIFFNESS, ..Band::default() });
+
+    // the stone on its tee
+    let stone = fork + Vec2::new(SLINGSHOT_STONE_RADIUS * 1.5, 0.0);
+    let tee = stone + Vec2::new(0.0, SLINGSHOT_STONE_RADIUS + 1.0);
+    for pos in [tee - Vec2::new(SLINGSHOT_STONE_RADIUS, 0.0), tee + Vec2::new(SLINGSHOT_STONE_RADIUS, 0.0)] {
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pos, 1.0) });
+    }
+    world.constraints.push(link(5, 6, 2.0 * SLINGSHOT_STONE_RADIUS));
+    world.add_ball(stone, Vec2::ZERO, SLINGSHOT_STONE_MASS, SLINGSHOT_STONE_RADIUS);
+    world.balls.last_mut().unwrap().smash_speed = SLINGSHOT_SMASH_SPEED;
+
+    // the wall, pinned along its top and bottom so it can't swing out of the way
+    let wall_width = (WALL_COLUMNS - 1) as f32 * TARGET_DIST;
+    world.add_cloth(Vec2::new(width * 0.7 - wall_width / 2.0, height * 0.3), WALL_COLUMNS, WALL_ROWS, 1);
+    let bottom_row = world.arena.len() - WALL_COLUMNS;
+    world.arena[bottom_row..].iter_mut().for_each(|node| node.fixed = true);
+    world
+}
+
+/// Writes the world's layout, without velocities or params, after
+/// `units <pixels per meter>` if it was given in meters, as lines like
+/// `node <x> <y> <mass> <fixed> [<restitution> [<layer> [<drag> [<gravity scale> [<charge> [<magnetic>]]]]]]`,
+/// `link <a> <b> <break threshold> <rest length>`,
+/// `band <a> <b> <rest length> <stiffness> [<pushes> [<damping>]]`, `ball <node> <radius> [<smash speed>]`,
+/// `well <x> <y> <strength> <radius>`, `sink <x> <y> <strength> <radius>`, and anchors as one of
+/// `anchor <node> line <x1> <y1> <x2> <y2> <speed>`,
+/// `anchor <node> circle <center x> <center y> <radius> <speed>`,
+/// `anchor <node> waypoints <speed> <x1> <y1> <x2> <y2> ...`, or
+/// `anchor <node> oscillate <center x> <center y> <amplitude x> <amplitude y> <frequency>`,
+/// platforms as `platform <width> <height>` and then a path like an anchor's, each with
+/// `rider <platform> <node> <offset x> <offset y>` lines for the nodes pinned to it, rails as
+/// `rail <node> polyline <x1> <y1> <x2> <y2> ...` or
+/// `rail <node> bezier <x1> <y1> <x2> <y2> <x3> <y3> <x4> <y4>`, `winch <anchor> <speed>`, and
+/// `dispenser <anchor> <speed> [<feed>]`, where the feed is the newest node it's fed out,
+/// `muscle <a> <b> <rest length> <amplitude> <frequency> <phase>`, starting its swing over,
+/// `slider <node> <rope node> <rope node> ...`, groups of links as `group <name> <a1> <b1> <a2> <b2> ...`,
+/// each with a `group_color <name> <r> <g> <b>` line if it has its own color,
+/// `rotor <x> <y> <hub radius> <blades> <blade length> <spin> <friction> [<layer>]`, layers set other
+/// than shown and colliding with every layer and obstacle at depth 0 as
+/// `layer <index> <visible> <mask of layers it collides with> [<depth> [<mask of obstacles it collides with>]]`,
+/// and, if any are on,
+/// `walls <left> <top> <right> <bottom> <restitution>` followed by the sides that are on and
+/// `wrap <left> <top> <right> <bottom>` followed by `x`, `y`, or both for the axes that wrap, and
+/// if it's raining `rain <left> <right> <top> <rate> <speed> <drop mass> <soak> <max wetness>`,
+/// without the drops in the air. Soaked-up water is saved as part of the nodes' mass. Moving air is
+/// `air <left> <top> <columns> <rows> <cell size> <drag> <fade>`, starting out still, and water is
+/// `water <reach> <rest density> <stiffness> <near stiffness> <viscosity> <droplet mass>` followed by a
+/// `droplet <x> <y>` line for each droplet, also still. Sand is
+/// `sand <spout x> <spout y> <rate> <grain mass> <grains still to pour>` with a
+/// `grain <node>` line for each grain poured so far. Friction other than the default is
+/// `friction <static> <dynamic>`, nodes colliding with each other is `contacts <stacking>`, and
+/// links snapping when jerked is `break_rate <rate>`, nodes shattering where links snap is `shatter <fragments>`,
+/// fraying the ends of severed links is `fray <recoil>`, solving links with XPBD is `xpbd <compliance>`, solving
+/// coarsened long chains first is `multigrid <levels> <iterations>`, solving links other than first to last is
+/// `solve_order alternating` or `solve_order shuffled`, clearing away lost nodes is `cleanup <depth> <delay>`, a
+/// cap on nodes is `budget <most nodes> recycle` or `budget <most nodes> refuse`, and
+/// charged nodes pushing and pulling is `electrostatics <strength> <reach>`. A magnet pushing on magnetic nodes is
+/// `magnet uniform <force x> <force y>` or `magnet dipole <x> <y> <moment x> <moment y>`.
+/// With `units`, the ground and node positions and link and band lengths are in meters, and the rest in pixels.
+pub fn to_text(world: &World) -> String {
+    let mut out = String::new();
+    let meters = |pixels: f32| world.units.map_or(pixels, |units| units.meters(pixels));
+    writeln!(out, "{}", HEADER).unwrap();
+    if let Some(units) = world.units {
+        writeln!(out, "units {}", units.pixels_per_meter).unwrap();
+    }
+    writeln!(out, "ground {}", meters(world.ground_y)).unwrap();
+    if world.friction != Friction::default() {
+        let Friction { static_friction, dynamic_friction } = world.friction;
+        writeln!(out, "friction {} {}", static_friction, dynamic_friction).unwrap();
+    }
+    if let Some(contacts) = world.contacts {
+        writeln!(out, "contacts {}", contacts.stacking).unwrap();
+    }
+    if let Some(rate) = world.break_rate {
+        writeln!(out, "break_rate {}", rate).unwrap();
+    }
+    if let Some(shatter) = world.shatter {
+        writeln!(out, "shatter {}", shatter.fragments).unwrap();
+    }
+    if let Some(fray) = world.fray {
+        writeln!(out, "fray {}", fray.recoil).unwrap();
+    }
+    if let Some(xpbd) = world.xpbd.as_ref() {
+        writeln!(out, "xpbd {}", xpbd.compliance).unwrap();
+    }
+    if let Some(Multigrid { levels, iterations }) = world.multigrid {
+        writeln!(out, "multigrid {} {}", levels, iterations).unwrap();
+    }
+    if world.solve_order != SolveOrder::Forward {
+        writeln!(out, "solve_order {}", world.solve_order.name()).unwrap();
+    }
+    if let Some(cleanup) = world.cleanup.as_ref() {
+        writeln!(out, "cleanup {} {}", cleanup.depth, cleanup.delay).unwrap();
+    }
+    if let Some(NodeBudget { max_nodes, at_cap }) = world.budget {
+        writeln!(out, "budget {} {}", max_nodes, at_cap.name()).unwrap();
+    }
+    if let Some(Electrostatics { strength, reach }) = world.electrostatics {
+        writeln!(out, "electrostatics {} {}", strength, reach).unwrap();
+    }
+    match world.magnet {
+        Some(Magnet::Uniform { force }) => writeln!(out, "magnet uniform {} {}", force.x, force.y).unwrap(),
+        Some(Magnet::Dipole { center, moment }) => {
+            writeln!(out, "magnet dipole {} {} {} {}", center.x, center.y, moment.x, moment.y).unwrap()
+        }
+        None => {}
+    }
+    let walls = &world.walls;
+    if walls.any() {
+        let Walls { min, max, restitution, .. } = walls;
+        write!(out, "walls {} {} {} {} {}", min.x, min.y, max.x, max.y, restitution).unwrap();
+        let sides = [(walls.left, "left"), (walls.right, "right"), (walls.top, "top"), (walls.bottom, "bottom")];
+        sides.iter().filter(|(on, _)| *on).try_for_each(|(_, side)| write!(out, " {}", side)).unwrap();
+        writeln!(out).unwrap();
+    }
+    if let Some(fluid) = world.fluid.as_ref() {
+        let Fluid { origin, columns, rows, cell, drag, fade, .. } = fluid;
+        writeln!(out, "air {} {} {} {} {} {} {}", origin.x, origin.y, columns, rows, cell, drag, fade).unwrap();
+    }
+    if let Some(rain) = world.rain.as_ref() {
+        let Rain { left, right, top, rate, speed, drop_mass, soak, max_wetness, .. } = rain;
+        write!(out, "rain {} {} {} {}", left, right, top, rate).unwrap();
+        writeln!(out, " {} {} {} {}", speed, drop_mass, soak, max_wetness).unwrap();
+    }
+    if let Some(water) = world.water.as_ref() {
+        let Water { reach, rest_density, stiffness, near_stiffness, viscosity, droplet_mass, .. } = water;
+        write!(out, "water {} {} {}", reach, rest_density, stiffness).unwrap();
+        writeln!(out, " {} {} {}", near_stiffness, viscosity, droplet_mass).unwrap();
+        for droplet in water.droplets.iter() {
+            writeln!(out, "droplet {} {}", droplet.pos.x, droplet.pos.y).unwrap();
+        }
+    }
+    let wrap = &world.wrap;
+    if wrap.any() {
+        write!(out, "wrap {} {} {} {}", wrap.min.x, wrap.min.y, wrap.max.x, wrap.max.y).unwrap();
+        let axes = [(wrap.x, "x"), (wrap.y, "y")];
+        axes.iter().filter(|(on, _)| *on).try_for_each(|(_, axis)| write!(out, " {}", axis)).unwrap();
+        writeln!(out).unwrap();
+    }
+    for (i, layer) in world.layers.0.iter().enumerate().filter(|(_, layer)| **layer != Layer::default()) {
+        write!(out, "layer {} {} {}", i, layer.visible as u8, layer.collides_with).unwrap();
+        if layer.depth != 0.0 || layer.obstacles != u8::MAX {
+            write!(out, " {}", layer.depth).unwrap();
+        }
+        if layer.obstacles != u8::MAX {
+            write!(out, " {}", layer.obstacles).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    for node in world.arena.iter() {
+        let (x, y) = (meters(node.pos.x), meters(node.pos.y));
+        write!(out, "node {} {} {} {}", x, y, node.mass, node.fixed as u8).unwrap();
+        // the optional values, up to the last one that isn't its default
+        let optional = [
+            node.restitution,
+            node.layer as f32,
+            node.drag,
+            node.gravity_scale,
+            node.charge,
+            node.magnetic as u8 as f32,
+        ];
+        let defaults = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        let changed = optional.iter().zip(defaults).rposition(|(value, default)| *value != default);
+        let written = changed.map_or(0, |i| i + 1);
+        for value in &optional[..written] {
+            write!(out, " {}", value).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    for constraint in world.constraints.iter() {
+        let Constraint { a, b, rest_length, break_threshold } = constraint;
+        writeln!(out, "link {} {} {} {}", a, b, meters(*break_threshold), meters(*rest_length)).unwrap();
+    }
+    for band in world.bands.iter() {
+        write!(out, "band {} {} {} {}", band.a, band.b, meters(band.rest_length), band.stiffness).unwrap();
+        if band.pushes || band.damping != 0.0 {
+            write!(out, " {}", band.pushes as u8).unwrap();
+        }
+        if band.damping != 0.0 {
+            write!(out, " {}", band.damping).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    for ball in world.balls.iter() {
+        write!(out, "ball {} {}", ball.node, ball.radius).unwrap();
+        if ball.smash_speed.is_finite() {
+            write!(out, " {}", ball.smash_speed).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    for anchor in world.anchors.iter() {
+        write!(out, "anchor {}", anchor.node).unwrap();
+        write_path(&mut out, &anchor.path, anchor.speed);
+    }
+    for platform in world.platforms.iter() {
+        write!(out, "platform {} {}", platform.size.x, platform.size.y).unwrap();
+        write_path(&mut out, &platform.path, platform.speed);
+    }
+    for (i, platform) in world.platforms.iter().enumerate() {
+        for (node, offset) in platform.riders.iter() {
+            writeln!(out, "rider {} {} {} {}", i, node, offset.x, offset.y).unwrap();
+        }
+    }
+    for rail in world.rails.iter() {
+        let (kind, points) = match &rail.curve {
+            Curve::Polyline(points) => ("polyline", points.as_slice()),
+            Curve::Bezier(points) => ("bezier", points.as_slice()),
+        };
+        write!(out, "rail {} {}", rail.node, kind).unwrap();
+        points.iter().try_for_each(|point| write!(out, " {} {}", point.x, point.y)).unwrap();
+        writeln!(out).unwrap();
+    }
+    for slider in world.sliders.iter() {
+        write!(out, "slider {}", slider.node).unwrap();
+        slider.rope.iter().try_for_each(|node| write!(out, " {}", node)).unwrap();
+        writeln!(out).unwrap();
+    }
+    for rotor in world.rotors.iter() {
+        let Rotor { center, hub_radius, blades, blade_length, spin, friction, layer, .. } = rotor;
+        write!(out, "rotor {} {} {} {}", center.x, center.y, hub_radius, blades).unwrap();
+        write!(out, " {} {} {}", blade_length, spin, friction).unwrap();
+        if *layer != 0 {
+            write!(out, " {}", layer).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    if let Some(sand) = world.sand.as_ref() {
+        let Sand { spout, rate, grain_mass, pouring, .. } = sand;
+        writeln!(out, "sand {} {} {} {} {}", spout.x, spout.y, rate, grain_mass, pouring).unwrap();
+        sand.grains.iter().try_for_each(|grain| writeln!(out, "grain {}", grain)).unwrap();
+    }
+    for winch in world.winches.iter() {
+        writeln!(out, "winch {} {}", winch.anchor, winch.speed).unwrap();
+    }
+    for dispenser in world.dispensers.iter() {
+        write!(out, "dispenser {} {}", dispenser.anchor, dispenser.speed).unwrap();
+        if let Some(feed) = dispenser.feed {
+            write!(out, " {}", feed).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    for muscle in world.muscles.iter() {
+        let Muscle { a, b, rest_length, amplitude, frequency, phase, .. } = muscle;
+        writeln!(out, "muscle {} {} {} {} {} {}", a, b, rest_length, amplitude, frequency, phase).unwrap();
+    }
+    for group in world.groups.iter() {
+        write!(out, "group {}", group.name).unwrap();
+        group.links.iter().try_for_each(|(a, b)| write!(out, " {} {}", a, b)).unwrap();
+        writeln!(out).unwrap();
+        if let Some([r, g, b]) = group.color {
+            writeln!(out, "group_color {} {} {} {}", group.name, r, g, b).unwrap();
+        }
+    }
+    for well in world.wells.iter() {
+        writeln!(out, "well {} {} {} {}", well.pos.x, well.pos.y, well.strength, well.radius).unwrap();
+    }
+    for sink in world.sinks.iter() {
+        writeln!(out, "sink {} {} {} {}", sink.pos.x, sink.pos.y, sink.strength, sink.radius).unwrap();
+    }
+    out
+}
+
+/// Finishes an `anchor` or `platform` line with its path and speed, as `parse_path` reads them.
+fn write_path(out: &mut String, path: &AnchorPath, speed: f32) {
+    match path {
+        AnchorPath::Line(a, b) => writeln!(out, " line {} {} {} {} {}", a.x, a.y, b.x, b.y, speed),
+        AnchorPath::Circle { center, radius } => {
+            writeln!(out, " circle {} {} {} {}", center.x, center.y, radius, speed)
+        }
+        AnchorPath::Waypoints(points) => {
+            write!(out, " waypoints {}", speed).unwrap();
+            points.iter().try_for_each(|point| write!(out, " {} {}", point.x, point.y)).unwrap();
+            writeln!(out)
+        }
+        AnchorPath::Oscillate { center, amplitude } => {
+            writeln!(out, " oscillate {} {} {} {} {}", center.x, center.y, amplitude.x, amplitude.y, speed)
+        }
+    }
+    .unwrap();
+}
+
+/// Parses a scene written by `to_text`. Blank lines and lines starting with `#` are skipped. A `units` line
+/// only changes the lines after it.
+pub fn from_text(text: &str) -> Result<World, String> {
+    if text.lines().next() != Some(HEADER) {
+        return Err(format!("missing \"{}\" header", HEADER));
+    }
+
+    let mut world = World::new(0.0);
+    for (i, line) in text.lines().enumerate().skip(1) {
+        let err = |what: &str| format!("line {}: {}", i + 1, what);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let num = |i: usize| -> Result<f32, String> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a number"))
+        };
+        let index = |i: usize| -> Result<usize, String> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a node index"))
+        };
+        let layer = |i: usize| -> Result<u8, String> {
+            let layer = words.get(i).and_then(|w| w.parse().ok()).filter(|&layer: &u8| (layer as usize) < LAYERS);
+            layer.ok_or_else(|| err(&format!("expected a layer below {}", LAYERS)))
+        };
+        let scale = world.units.map_or(1.0, |units| units.pixels_per_meter);
+        let length = |i: usize| -> Result<f32, String> { Ok(num(i)? * scale) };
+
+        match words.as_slice() {
+            [] => {}
+            [comment, ..] if comment.starts_with('#') => {}
+            ["units", ..] => {
+                let pixels_per_meter = num(1)?;
+                if pixels_per_meter <= 0.0 {
+                    return Err(err("expected a positive number of pixels per meter"));
+                }
+                world.units = Some(Units { pixels_per_meter });
+            }
+            ["ground", ..] => world.ground_y = length(1)?,
+            ["layer", ..] => {
+                let visible = match words.get(2) {
+                    Some(&"0") => false,
+                    Some(&"1") => true,
+                    _ => return Err(err("expected 0 or 1")),
+                };
+                let collides_with = words.get(3).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a mask"))?;
+                let depth = if words.len() > 4 { num(4)? } else { 0.0 };
+                let obstacles = match words.get(5) {
+                    Some(word) => word.parse().map_err(|_| err("expected a mask"))?,
+                    None => u8::MAX,
+                };
+                world.layers.0[layer(1)? as usize] = Layer { visible, collides_with, obstacles, depth };
+            }
+            ["walls", ..] => {
+                let mut walls = Walls {
+                    min: Vec2::new(num(1)?, num(2)?),
+                    max: Vec2::new(num(3)?, num(4)?),
+                    restitution: num(5)?,
+                    ..Walls::default()
+                };
+                for side in words.iter().skip(6) {
+                    match *side {
+                        "left" => walls.left = true,
+                        "right" => walls.right = true,
+                        "top" => walls.top = true,
+                        "bottom" => walls.bottom = true,
+                        _ => return Err(err("expected left, right, top, or bottom")),
+                    }
+                }
+                world.walls = walls;
+            }
+            ["air", ..] => {
+                let (columns, rows, cell) = (index(3)?, index(4)?, num(5)?);
+                if columns == 0 || rows == 0 || cell <= 0.0 {
+                    return Err(err("air needs at least one cell, of a positive size"));
+                }
+                world.fluid = Some(Fluid {
+                    drag: num(6)?,
+                    fade: num(7)?,
+                    ..Fluid::new(Vec2::new(num(1)?, num(2)?), columns, rows, cell)
+                });
+            }
+            ["water", ..] => {
+                world.water = Some(Water {
+                    reach: num(1)?,
+                    rest_density: num(2)?,
+                    stiffness: num(3)?,
+                    near_stiffness: num(4)?,
+                    viscosity: num(5)?,
+                    droplet_mass: num(6)?,
+                    ..Water::default()
+                });
+            }
+            ["droplet", ..] => {
+                let pos = Vec2::new(num(1)?, num(2)?);
+                let water = world.water.as_mut().ok_or_else(|| err("droplet comes before a water line"))?;
+                water.droplets.push(Droplet { pos, last_pos: pos, vel: Vec2::ZERO });
+            }
+            ["rain", ..] => {
+                world.rain = Some(Rain {
+                    rate: num(4)?,
+                    speed: num(5)?,
+                    drop_mass: num(6)?,
+                    soak: num(7)?,
+                    max_wetness: num(8)?,
+                    ..Rain::new(num(1)?, num(2)?, num(3)?)
+                });
+            }
+            ["friction", ..] => {
+                world.friction = Friction { static_friction: num(1)?, dynamic_friction: num(2)? };
+            }
+            ["contacts", ..] => world.contacts = Some(Contacts { stacking: num(1)? }),
+            ["break_rate", ..] => world.break_rate = Some(num(1)?),
+            ["shatter", ..] => world.shatter = Some(Shatter { fragments: index(1)? }),
+            ["fray", ..] => world.fray = Some(Fray { recoil: num(1)? }),
+            ["xpbd", ..] => world.xpbd = Some(Xpbd::new(num(1)?)),
+            ["budget", _, at_cap, ..] => {
+                let at_cap = AtCap::from_name(at_cap).ok_or_else(|| err("expected recycle or refuse"))?;
+                world.budget = Some(NodeBudget { max_nodes: index(1)?, at_cap });
+            }
+            ["cleanup", ..] => world.cleanup = Some(Cleanup::new(num(1)?, num(2)?)),
+            ["solve_order", name, ..] => {
+                world.solve_order = SolveOrder::from_name(name).ok_or_else(|| err("expected a solve order"))?;
+            }
+            ["multigrid", ..] => world.multigrid = Some(Multigrid { levels: index(1)?, iterations: index(2)? }),
+            ["electrostatics", ..] => {
+                world.electrostatics = Some(Electrostatics { strength: num(1)?, reach: num(2)? });
+            }
+            ["magnet", "uniform", ..] => world.magnet = Some(Magnet::Uniform { force: Vec2::new(num(2)?, num(3)?) }),
+            ["magnet", "dipole", ..] => {
+                let (center, moment) = (Vec2::new(num(2)?, num(3)?), Vec2::new(num(4)?, num(5)?));
+                world.magnet = Some(Magnet::Dipole { center, moment });
+            }
+            ["magnet", ..] => return Err(err("expected uniform or dipole")),
+            ["wrap", ..] => {
+                let mut wrap = Wrap {
+                    min: Vec2::new(num(1)?, num(2)?),
+                    max: Vec2::new(num(3)?, num(4)?),
+                    ..Wrap::default()
+                };
+                for axis in words.iter().skip(5) {
+                    match *axis {
+                        "x" => wrap.x = true,
+                        "y" => wrap.y = true,
+                        _ => return Err(err("expected x or y")),
+                    }
+                }
+                world.wrap = wrap;
+            }
+            ["node", ..] => {
+                let mut node = Node::with_pos_and_mass(Vec2::new(length(1)?, length(2)?), num(3)?);
+                node.fixed = match words.get(4) {
+                    Some(&"0") => false,
+                    Some(&"1") => true,
+                    _ => return Err(err("expected 0 or 1")),
+                };
+                if words.len() > 5 {
+                    node.restitution = num(5)?;
+                }
+                if words.len() > 6 {
+                    node.layer = layer(6)?;
+                }
+                if words.len() > 7 {
+                    node.drag = num(7)?;
+                }
+                if words.len() > 8 {
+                    node.gravity_scale = num(8)?;
+                }
+                if words.len() > 9 {
+                    node.charge = num(9)?;
+                }
+                node.magnetic = match words.get(10) {
+                    None | Some(&"0") => false,
+                    Some(&"1") => true,
+                    _ => return Err(err("expected 0 or 1")),
+                };
+                world.arena.push(node);
+            }
+            ["link", ..] => {
+                let (a, b) = (index(1)?, index(2)?);
+                if a.max(b) >= world.arena.len() {
+                    return Err(err("link refers to a node that isn't defined yet"));
+                }
+                // scenes from before rest lengths were stored all used the default
+                let rest_length = if words.len() > 4 { length(4)? } else { TARGET_DIST };
+                world.constraints.push(Constraint { a, b, rest_length, break_threshold: length(3)? });
+            }
+            ["band", ..] => {
+                let (a, b) = (index(1)?, index(2)?);
+                if a.max(b) >= world.arena.len() {
+                    return Err(err("band refers to a node that isn't defined yet"));
+                }
+                let pushes = match words.get(5) {
+                    None | Some(&"0") => false,
+                    Some(&"1") => true,
+                    _ => return Err(err("expected 0 or 1")),
+                };
+                let damping = if words.len() > 6 { num(6)? } else { 0.0 };
+                world.bands.push(Band { a, b, rest_length: length(3)?, stiffness: num(4)?, pushes, damping });
+            }
+            ["ball", ..] => {
+                let node = index(1)?;
+                if node >= world.arena.len() {
+                    return Err(err("ball refers to a node that isn't defined yet"));
+                }
+                let smash_speed = if words.len() > 3 { num(3)? } else { f32::INFINITY };
+                world.balls.push(Ball { node, radius: num(2)?, smash_speed });
+            }
+            ["anchor", _, _, ..] => {
+                let node = index(1)?;
+                if node >= world.arena.len() {
+                    return Err(err("anchor refers to a node that isn't defined yet"));
+                }
+                let (path, speed) = parse_path(&words[2..]).map_err(|what| err(&what))?;
+                // only fixed nodes stay where they're put
+                world.arena[node].fixed = true;
+                world.anchors.push(Anchor::new(node, path, speed));
+            }
+            ["platform", _, _, _, ..] => {
+                let size = Vec2::new(num(1)?, num(2)?);
+                let (path, speed) = parse_path(&words[3..]).map_err(|what| err(&what))?;
+                world.platforms.push(Platform::new(path, speed, size));
+            }
+            ["rider", ..] => {
+                let (platform, node) = (index(1)?, index(2)?);
+                if platform >= world.platforms.len() || node >= world.arena.len() {
+                    return Err(err("rider refers to a platform or node that isn't defined yet"));
+                }
+                // riders without an offset keep where they are relative to the platform's start
+                let offset = match words.len() {
+                    3 => world.arena[node].pos - world.platforms[platform].position(),
+                    _ => Vec2::new(num(3)?, num(4)?),
+                };
+                world.arena[node].fixed = true;
+                world.platforms[platform].riders.push((node, offset));
+            }
+            ["rail", _, kind, ..] => {
+                let node = index(1)?;
+                if node >= world.arena.len() {
+                    return Err(err("rail refers to a node that isn't defined yet"));
+                }
+                if words.len() % 2 != 1 {
+                    return Err(err("expected x y pairs"));
+                }
+                let points = (3..words.len())
+                    .step_by(2)
+                    .map(|i| Ok(Vec2::new(num(i)?, num(i + 1)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                let curve = match *kind {
+                    "polyline" if !points.is_empty() => Curve::Polyline(points),
+                    "bezier" => Curve::Bezier(points.try_into().map_err(|_| err("expected four points"))?),
+                    "polyline" => return Err(err("expected at least one point")),
+                    _ => return Err(err("expected polyline or bezier")),
+                };
+                world.rails.push(Rail { node, curve });
+            }
+            ["slider", ..] => {
+                let nodes = (1..words.len()).map(index).collect::<Result<Vec<_>, String>>()?;
+                if nodes.len() < 3 {
+                    return Err(err("expected a node and at least two rope nodes"));
+                }
+                if nodes.iter().any(|&node| node >= world.arena.len()) {
+                    return Err(err("slider refers to a node that isn't defined yet"));
+                }
+                world.sliders.push(Slider { node: nodes[0], rope: nodes[1..].to_vec() });
+            }
+            ["sand", ..] => {
+                world.sand = Some(Sand {
+                    rate: num(3)?,
+                    grain_mass: num(4)?,
+                    pouring: index(5)?,
+                    ..Sand::new(Vec2::new(num(1)?, num(2)?))
+                });
+            }
+            ["grain", ..] => {
+                let grain = index(1)?;
+                if grain >= world.arena.len() {
+                    return Err(err("grain refers to a node that isn't defined yet"));
+                }
+                let sand = world.sand.as_mut().ok_or_else(|| err("grain comes before a sand line"))?;
+                sand.grains.push(grain);
+            }
+            ["rotor", ..] => {
+                let center = Vec2::new(num(1)?, num(2)?);
+                let (hub_radius, blades, blade_length) = (num(3)?, index(4)?, num(5)?);
+                let mut rotor = Rotor::new(center, hub_radius, blades, blade_length, num(6)?, num(7)?);
+                if words.len() > 8 {
+                    rotor.layer = layer(8)?;
+                }
+                world.rotors.push(rotor);
+            }
+            ["winch", ..] => {
+                let anchor = index(1)?;
+                if anchor >= world.arena.len() {
+                    return Err(err("winch refers to a node that isn't defined yet"));
+                }
+                world.winches.push(Winch { anchor, speed: num(2)? });
+            }
+            ["dispenser", ..] => {
+                let anchor = index(1)?;
+                let feed = if words.len() > 3 { Some(index(3)?) } else { None };
+                if anchor >= world.arena.len() || feed.is_some_and(|feed| feed >= world.arena.len()) {
+                    return Err(err("dispenser refers to a node that isn't defined yet"));
+                }
+                world.dispensers.push(Dispenser { anchor, speed: num(2)?, feed });
+            }
+            ["muscle", ..] => {
+                let (a, b) = (index(1)?, index(2)?);
+                if a.max(b) >= world.arena.len() {
+                    return Err(err("muscle refers to a node that isn't defined yet"));
+                }
+                world.muscles.push(Muscle::new(a, b, num(3)?, num(4)?, num(5)?, num(6)?));
+            }
+            ["group", name, ..] => {
+                if !words.len().is_multiple_of(2) {
+                    return Err(err("expected a name and then a b pairs"));
+                }
+                let links = (2..words.len()).step_by(2).map(|i| Ok((index(i)?, index(i + 1)?)));
+                let links: Vec<(usize, usize)> = links.collect::<Result<_, String>>()?;
+                if links.iter().any(|&(a, b)| a.max(b) >= world.arena.len()) {
+                    return Err(err("group refers to a node that isn't defined yet"));
+                }
+                group::tag(&mut world, name, links);
+            }
+            ["group_color", name, ..] => {
+                let channel = |i: usize| -> Result<u8, String> {
+                    words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| err("expected a color channel"))
+                };
+                let color = [channel(2)?, channel(3)?, channel(4)?];
+                let group = group::find(&world, name).ok_or_else(|| err("group_color names a group not defined yet"))?;
+                world.groups[group].color = Some(color);
+            }
+            ["well", ..] => {
+                let pos = Vec2::new(num(1)?, num(2)?);
+                world.wells.push(Well { pos, strength: num(3)?, radius: num(4)? });
+            }
+            ["sink", ..] => {
+                let pos = Vec2::new(num(1)?, num(2)?);
+                world.sinks.push(Sink { pos, strength: num(3)?, radius: num(4)? });
+            }
+            _ => return Err(err("unrecognized line")),
+        }
+    }
+
+    Ok(world)
+}
+
+/// Reads a path and speed from the words after an `anchor` line's node or a
+/// `platform` line's size, starting with the kind of path.
+fn parse_path(words: &[&str]) -> Result<(AnchorPath, f32), String> {
+    let num = |i: usize| -> Result<f32, String> {
+        words.get(i).and_then(|w| w.parse().ok()).ok_or_else(|| "expected a number".to_string())
+    };
+    let point = |i: usize| -> Result<Vec2, String> { Ok(Vec2::new(num(i)?, num(i + 1)?)) };
+    Ok(match words.first().copied() {
+        Some("line") => (AnchorPath::Line(point(1)?, point(3)?), num(5)?),
+        Some("circle") => (AnchorPath::Circle { center: point(1)?, radius: num(3)? }, num(4)?),
+        Some("waypoints") => {
+            if words.len() < 4 || !words.len().is_multiple_of(2) {
+                return Err("expected a speed and then x y pairs".to_string());
+            }
+            let points = (2..words.len()).step_by(2).map(point);
+            (AnchorPath::Waypoints(points.collect::<Result<_, String>>()?), num(1)?)
+        }
+        Some("oscillate") => (AnchorPath::Oscillate { center: point(1)?, amplitude: point(3)? }, num(5)?),
+        _ => return Err("expected line, circle, waypoints, or oscillate".to_string()),
+    })
+}
+
+/// Builds a world from a nodes CSV with `x,y,mass,fixed` rows and an edges CSV
+/// with `a,b,rest,threshold` rows, numbering nodes from 0 in file order. `fixed`
+/// is `0`/`1` or `true`/`false`, and `threshold` is the length the link breaks
+/// at. A blank `rest` uses the distance between the nodes as given, and a blank
+/// `threshold` uses `params.break_threshold` rest lengths. Each file may start
+/// with a header row, which is skipped. Masses, rest lengths, and thresholds
+/// must be positive and positions finite.
+pub fn from_csv(nodes: &str, edges: &str, ground_y: f32, params: Params) -> Result<World, String> {
+    let mut world = World::new(ground_y);
+    world.params = params;
+
+    for (line_number, fields) in csv_rows(nodes) {
+        let err = |what: &str| format!("nodes line {}: {}", line_number, what);
+        let num = |i: usize| -> Result<f32, String> {
+            fields.get(i).and_then(|f| f.parse().ok()).ok_or_else(|| err("expected a number"))
+        };
+
+        let mut node = Node::with_pos_and_mass(Vec2::new(num(0)?, num(1)?), num(2)?);
+        if !node.pos.is_finite() {
+            return Err(err("position must be finite"));
+        }
+        if !(node.mass.is_finite() && node.mass > 0.0) {
+            return Err(err("mass must be positive"));
+        }
+        node.fixed = match fields.get(3).map(|f| f.to_ascii_lowercase()).as_deref() {
+            Some("0" | "false") => false,
+            Some("1" | "true") => true,
+            _ => return Err(err("expected 0, 1, true, or false")),
+        };
+        world.arena.push(node);
+    }
+
+    for (line_number, fields) in csv_rows(edges) {
+        let err = |what: &str| format!("edges line {}: {}", line_number, what);
+        let optional = |i: usize| -> Result<Option<f32>, String> {
+            match fields.get(i).filter(|f| !f.is_empty()) {
+                Some(f) => f.parse().map(Some).map_err(|_| err("expected a number or nothing")),
+                None => Ok(None),
+            }
+        };
+        let index = |i: usize| -> Result<usize, String> {
+            let index: usize =
+                fields.get(i).and_then(|f| f.parse().ok()).ok_or_else(|| err("expected a node index"))?;
+            if index >= world.arena.len() {
+                return Err(err(&format!("there's no node {}", index)));
+            }
+            Ok(index)
+        };
+
+        let (a, b) = (index(0)?, index(1)?);
+        if a == b {
+            return Err(err("links a node to itself"));
+        }
+        let rest_length = match optional(2)? {
+            Some(rest_length) => rest_length,
+            None => world.arena[a].pos.distance(world.arena[b].pos),
+        };
+        if !(rest_length.is_finite() && rest_length > 0.0) {
+            return Err(err("rest length must be positive"));
+        }
+        let break_threshold = optional(3)?.unwrap_or(rest_length * params.break_threshold);
+        if break_threshold.is_nan() || break_threshold <= 0.0 {
+            return Err(err("break threshold must be positive"));
+        }
+        world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+    }
+
+    Ok(world)
+}
+
+/// Non-blank rows of `text` with their 1-based line numbers, split on commas and
+/// trimmed, minus the first row if it's a header.
+fn csv_rows(text: &str) -> impl Iterator<Item = (usize, Vec<&str>)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line.split(',').map(str::trim).collect::<Vec<_>>()))
+        .enumerate()
+        .filter(|(row, (_, fields))| *row > 0 || fields[0].parse::<f32>().is_ok())
+        .map(|(_, row)| row)
+}
+
+/// Side of a shape to hang it from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PinEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl PinEdge {
+    pub const ALL: [PinEdge; 4] = [PinEdge::Top, PinEdge::Bottom, PinEdge::Left, PinEdge::Right];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PinEdge::Top => "top",
+            PinEdge::Bottom => "bottom",
+            PinEdge::Left => "left",
+            PinEdge::Right => "right",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|edge| edge.name() == name)
+    }
+}
+
+/// A cloth with a node for every `true` cell of the row-major `mask`, `columns`
+/// cells wide, each linked to its right and lower neighbors. On every row or
+/// column, the node closest to `pin` is fixed, so ragged shapes hang from their
+/// outline. The cloth is centered across the top of a `width` by `height` view.
+pub fn from_mask(
+    mask: &[bool],
+    columns: usize,
+    pin: PinEdge,
+    width: f32,
+    height: f32,
+    params: Params,
+) -> World {
+    let columns = columns.max(1);
+    let rows = mask.len().div_ceil(columns);
+    let filled = |column: usize, row: usize| mask.get(row * columns + column).copied().unwrap_or(false);
+
+    let mut world = World::new(height - GROUND_OFFSET);
+    world.params = params;
+    let cloth_width = (columns - 1) as f32 * TARGET_DIST;
+    let top_left = Vec2::new((width - cloth_width) / 2.0, height / 8.0);
+
+    // the fixed cell of each row or column along `pin`
+    let pinned: Vec<Option<usize>> = match pin {
+        PinEdge::Top => (0..columns).map(|c| (0..rows).find(|&r| filled(c, r))).collect(),
+        PinEdge::Bottom => (0..columns).map(|c| (0..rows).rev().find(|&r| filled(c, r))).collect(),
+        PinEdge::Left => (0..rows).map(|r| (0..columns).find(|&c| filled(c, r))).collect(),
+        PinEdge::Right => (0..rows).map(|r| (0..columns).rev().find(|&c| filled(c, r))).collect(),
+    };
+    let is_pinned = |column: usize, row: usize| match pin {
+        PinEdge::Top | PinEdge::Bottom => pinned[column] == Some(row),
+        PinEdge::Left | PinEdge::Right => pinned[row] == Some(column),
+    };
+
+    let mut index = vec![None; columns * rows];
+    let break_threshold = TARGET_DIST * params.break_threshold;
+    for row in 0..rows {
+        for column in (0..columns).filter(|&column| filled(column, row)) {
+            let offset = Vec2::new(column as f32, row as f32) * TARGET_DIST;
+            let mut node = Node::with_pos_and_mass(top_left + offset, 1.0);
+            node.fixed = is_pinned(column, row);
+            let i = world.arena.len();
+            world.arena.push(node);
+            index[row * columns + column] = Some(i);
+
+            let neighbors = [
+                column.checked_sub(1).map(|left| row * columns + left),
+                row.checked_sub(1).map(|up| up * columns + column),
+            ];
+            for a in neighbors.into_iter().flatten().filter_map(|cell| index[cell]) {
+                world.constraints.push(Constraint { a, b: i, rest_length: TARGET_DIST, break_threshold });
+            }
+        }
+    }
+
+    world
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_csvs_and_turns_away_unusable_rows() {
+        let params = Params::default();
+        let nodes = "x,y,mass,fixed\n0,0,1,true\n30,40,2,0\n";
+        let world = from_csv(nodes, "a,b,rest,threshold\n0,1,,\n", 500.0, params).unwrap();
+        assert_eq!(world.arena.len(), 2);
+        assert!(world.arena[0].fixed && !world.arena[1].fixed);
+        assert_eq!((world.arena[1].pos, world.arena[1].mass), (Vec2::new(30.0, 40.0), 2.0));
+        let link = world.constraints[0];
+        assert_eq!((link.a, link.b, link.rest_length), (0, 1, 50.0));
+        assert_eq!(link.break_threshold, 50.0 * params.break_threshold);
+
+        let refused = |nodes: &str, edges: &str| from_csv(nodes, edges, 500.0, params).err();
+        for mass in ["0", "-1", "NaN", "inf"] {
+            let nodes = format!("0,0,{},0\n", mass);
+            assert_eq!(refused(&nodes, ""), Some("nodes line 1: mass must be positive".to_string()));
+        }
+        for (x, y) in [("NaN", "0"), ("0", "inf")] {
+            let nodes = format!("0,0,1,0\n{},{},1,0\n", x, y);
+            assert_eq!(refused(&nodes, ""), Some("nodes line 2: position must be finite".to_string()));
+        }
+        assert_eq!(refused(nodes, "0,1,NaN\n"), Some("edges line 1: rest length must be positive".to_string()));
+        assert_eq!(refused(nodes, "0,1,,0\n"), Some("edges line 1: break threshold must be positive".to_string()));
+        assert_eq!(refused(nodes, "1,1\n"), Some("edges line 1: links a node to itself".to_string()));
+        assert_eq!(refused(nodes, "0,2\n"), Some("edges line 1: there's no node 2".to_string()));
+        assert!(refused("0,0,1,maybe\n", "").is_some());
+    }
+
+    #[test]
+    fn masks_hang_from_the_chosen_edge() {
+        // an upside-down L, three cells wide:
+        //   # . #
+        //   # # #
+        let mask = [true, false, true, true, true, true];
+        let world = from_mask(&mask, 3, PinEdge::Top, 400.0, 800.0, Params::default());
+        assert_eq!(world.arena.len(), 5);
+        assert_eq!(world.arena[0].pos, Vec2::new(200.0 - TARGET_DIST, 100.0));
+        let links: Vec<_> = world.constraints.iter().map(|link| (link.a, link.b)).collect();
+        assert_eq!(links, [(0, 2), (2, 3), (3, 4), (1, 4)]);
+
+        let fixed = |pin| -> Vec<usize> {
+            let world = from_mask(&mask, 3, pin, 400.0, 800.0, Params::default());
+            (0..world.arena.len()).filter(|&i| world.arena[i].fixed).collect()
+        };
+        // the middle column's top is its lower cell
+        assert_eq!(fixed(PinEdge::Top), [0, 1, 3]);
+        assert_eq!(fixed(PinEdge::Bottom), [2, 3, 4]);
+        assert_eq!(fixed(PinEdge::Left), [0, 2]);
+        assert_eq!(fixed(PinEdge::Right), [1, 4]);
+        assert!(PinEdge::ALL.into_iter().all(|edge| PinEdge::from_name(edge.name()) == Some(edge)));
+    }
+}
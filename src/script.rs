@@ -0,0 +1,380 @@
+//! Rhai scripts that build scenes and push them around while they run.
+//!
+//! A script's top level runs first, then its `init()` if it defines one, each
+//! time the scene starts. `on_frame(t)` then runs before every step with the
+//! simulated time so far. Both can call:
+//!
+//! - `add_node(x, y)` or `add_node(x, y, mass, fixed)`, returning the node's index
+//! - `add_constraint(a, b)` or `add_constraint(a, b, rest_length)`
+//! - `add_rope(x, y, nodes)` and `add_cloth(x, y, columns, rows, pin_every)`
+//! - `add_muscle(a, b, amplitude, frequency, phase)`, linking two nodes at
+//!   the distance between them with a link that swings its length by
+//!   `amplitude` of it either way, `frequency` times per unit of time, and
+//!   returning the muscle's index
+//! - `set_muscle(muscle, amplitude, frequency, phase)`, to change how it swings
+//! - `apply_force(node, fx, fy)`, which acts on the next step only
+//! - `set_position(node, x, y)`, which also stops the node, for moving anchors
+//! - `set_fixed(node, fixed)`
+//! - `set_layer(node, layer)`, to put the node on one of the layers from 0 up to 7
+//! - `set_gravity_scale(node, scale)`, negative for a node that floats up like a balloon
+//! - `set_charge(node, charge)`, which only acts if the scene has electrostatics on
+//! - `set_magnetic(node, magnetic)`, for whether the scene's magnet pushes on it
+//! - `cut(x0, y0, x1, y1)`, returning how many constraints it cut
+//! - `node_x(node)`, `node_y(node)`, `node_count()`, `constraint_count()` and `ground_y()`
+//! - `nearest_node(x, y)`, or -1 if there are no nodes, and `nodes_within(x, y, radius)`,
+//!   an array of nodes nearest first, both leaving out hidden layers as `query::Query` does
+//! - `raycast(x0, y0, x1, y1)`, an array of the constraints the line crosses, in the order it crosses them
+//!
+//! Numbers can be written as integers or decimals; y points down, as everywhere else.
+//!
+//! Each call is stopped with an error once it's run too long, counted in Rhai
+//! operations: `INIT_OPERATIONS` for the top level and `init()`, and
+//! `FRAME_OPERATIONS` for each `on_frame`.
+
+use crate::constraint::Constraint;
+use crate::event::SimEvent;
+use crate::layer::LAYERS;
+use crate::muscle::Muscle;
+use crate::node::Node;
+use crate::query::Query;
+use crate::world::World;
+use glam::Vec2;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Position, Scope, AST, INT};
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Most operations the top level and `init()` get between them, plenty for
+/// building a big scene node by node.
+const INIT_OPERATIONS: u64 = 10_000_000;
+/// Most operations one `on_frame` gets, so a loop that never ends is stopped
+/// instead of freezing the window or a headless run.
+const FRAME_OPERATIONS: u64 = 1_000_000;
+
+/// A compiled script and the state its top level set up.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    /// The world being scripted, swapped in for the length of each call.
+    world: Rc<Scripted>,
+    has_on_frame: bool,
+    time: f32,
+}
+
+impl Script {
+    /// Compiles `source`, returning the first syntax error with its line number.
+    pub fn new(source: &str) -> Result<Self, String> {
+        let world = Rc::new(Scripted { world: RefCell::new(World::new(0.0)), query: RefCell::new(None) });
+        let engine = engine(&world);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let has_on_frame = ast.iter_functions().any(|f| f.name == "on_frame" && f.params.len() == 1);
+        Ok(Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+            world,
+            has_on_frame,
+            time: 0.0,
+        })
+    }
+
+    /// Runs the top level and `init()` against a freshly built `world`, starting
+    /// the script's state and clock over.
+    pub fn init(&mut self, world: &mut World) -> Result<(), String> {
+        self.scope = Scope::new();
+        self.time = 0.0;
+        let has_init = self.ast.iter_functions().any(|f| f.name == "init" && f.params.is_empty());
+        self.with_world(world, INIT_OPERATIONS, |script| {
+            script.engine.run_ast_with_scope(&mut script.scope, &script.ast)?;
+            if has_init {
+                let _: Dynamic = script.engine.call_fn(&mut script.scope, &script.ast, "init", ())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Calls `on_frame(t)` before a step of `dt`, then advances the script's clock.
+    pub fn on_frame(&mut self, world: &mut World, dt: f32) -> Result<(), String> {
+        let t = self.time;
+        self.time += dt;
+        if !self.has_on_frame {
+            return Ok(());
+        }
+        self.with_world(world, FRAME_OPERATIONS, |script| {
+            let _: Dynamic = script.engine.call_fn(&mut script.scope, &script.ast, "on_frame", (t,))?;
+            Ok(())
+        })
+    }
+
+    /// Runs `f` against `world`, stopping it after `operations`, which each
+    /// call counts afresh.
+    fn with_world(
+        &mut self,
+        world: &mut World,
+        operations: u64,
+        f: impl FnOnce(&mut Self) -> ScriptResult<()>,
+    ) -> Result<(), String> {
+        self.engine.set_max_operations(operations);
+        std::mem::swap(world, &mut self.world.edit());
+        let result = f(self);
+        std::mem::swap(world, &mut self.world.edit());
+        result.map_err(|e| match *e {
+            EvalAltResult::ErrorTooManyOperations(_) => {
+                format!("ran past {} operations; does a loop never end?", operations)
+            }
+            e => e.to_string(),
+        })
+    }
+}
+
+/// The world a script's functions act on, and a `Query` of it kept from one
+/// question about where things are to the next until the script moves or
+/// links nodes, so a frame asking many builds it once.
+struct Scripted {
+    world: RefCell<World>,
+    query: RefCell<Option<Query>>,
+}
+
+impl Scripted {
+    /// The world, to change in a way that leaves the query out of date.
+    fn edit(&self) -> RefMut<'_, World> {
+        self.query.take();
+        self.world.borrow_mut()
+    }
+
+    /// Asks `f` of the query, building it first if there isn't one yet.
+    fn query<T>(&self, f: impl FnOnce(&Query) -> T) -> T {
+        f(self.query.borrow_mut().get_or_insert_with(|| Query::new(&self.world.borrow())))
+    }
+}
+
+fn engine(world: &Rc<Scripted>) -> Engine {
+    let mut engine = Engine::new();
+
+    let w = Rc::clone(world);
+    engine.register_fn("add_node", move |x: Dynamic, y: Dynamic| -> ScriptResult<INT> {
+        add_node(&mut w.edit(), point(x, y)?, 1.0, false)
+    });
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "add_node",
+        move |x: Dynamic, y: Dynamic, mass: Dynamic, fixed: bool| -> ScriptResult<INT> {
+            add_node(&mut w.edit(), point(x, y)?, number(mass)?, fixed)
+        },
+    );
+
+    let w = Rc::clone(world);
+    engine.register_fn("add_constraint", move |a: INT, b: INT| -> ScriptResult<()> {
+        add_constraint(&mut w.edit(), a, b, None)
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("add_constraint", move |a: INT, b: INT, rest: Dynamic| -> ScriptResult<()> {
+        add_constraint(&mut w.edit(), a, b, Some(number(rest)?))
+    });
+
+    let w = Rc::clone(world);
+    engine.register_fn("add_rope", move |x: Dynamic, y: Dynamic, nodes: INT| -> ScriptResult<()> {
+        w.edit().add_rope(point(x, y)?, count(nodes)?);
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "add_cloth",
+        move |x: Dynamic, y: Dynamic, columns: INT, rows: INT, pin_every: INT| -> ScriptResult<()> {
+            let (columns, rows, pin_every) = (count(columns)?, count(rows)?, count(pin_every)?);
+            w.edit().add_cloth(point(x, y)?, columns, rows, pin_every);
+            Ok(())
+        },
+    );
+
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "add_muscle",
+        move |a: INT, b: INT, amplitude: Dynamic, frequency: Dynamic, phase: Dynamic| -> ScriptResult<INT> {
+            let mut world = w.edit();
+            add_constraint(&mut world, a, b, None)?;
+            let Constraint { a, b, rest_length, .. } = *world.constraints.last().expect("just added");
+            let muscle = Muscle::new(a, b, rest_length, number(amplitude)?, number(frequency)?, number(phase)?);
+            world.muscles.push(muscle);
+            Ok(world.muscles.len() as INT - 1)
+        },
+    );
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "set_muscle",
+        move |muscle: INT, amplitude: Dynamic, frequency: Dynamic, phase: Dynamic| -> ScriptResult<()> {
+            let mut world = w.world.borrow_mut();
+            let muscle = usize::try_from(muscle)
+                .ok()
+                .and_then(|i| world.muscles.get_mut(i))
+                .ok_or_else(|| error(format!("no muscle {}", muscle)))?;
+            muscle.amplitude = number(amplitude)?;
+            muscle.frequency = number(frequency)?;
+            muscle.phase = number(phase)?;
+            Ok(())
+        },
+    );
+
+    let w = Rc::clone(world);
+    engine.register_fn("apply_force", move |node: INT, fx: Dynamic, fy: Dynamic| -> ScriptResult<()> {
+        let mut world = w.world.borrow_mut();
+        let i = index(&world, node)?;
+        world.arena[i].force += point(fx, fy)?;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_position", move |node: INT, x: Dynamic, y: Dynamic| -> ScriptResult<()> {
+        let mut world = w.edit();
+        let i = index(&world, node)?;
+        let pos = point(x, y)?;
+        let node = &mut world.arena[i];
+        node.pos = pos;
+        node.last_pos = pos;
+        node.vel = Vec2::ZERO;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_fixed", move |node: INT, fixed: bool| -> ScriptResult<()> {
+        let mut world = w.world.borrow_mut();
+        let i = index(&world, node)?;
+        world.arena[i].fixed = fixed;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_layer", move |node: INT, layer: INT| -> ScriptResult<()> {
+        let mut world = w.edit();
+        let i = index(&world, node)?;
+        world.arena[i].layer = u8::try_from(layer)
+            .ok()
+            .filter(|&layer| (layer as usize) < LAYERS)
+            .ok_or_else(|| error(format!("no layer {}", layer)))?;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_gravity_scale", move |node: INT, scale: Dynamic| -> ScriptResult<()> {
+        let mut world = w.world.borrow_mut();
+        let i = index(&world, node)?;
+        world.arena[i].gravity_scale = number(scale)?;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_charge", move |node: INT, charge: Dynamic| -> ScriptResult<()> {
+        let mut world = w.world.borrow_mut();
+        let i = index(&world, node)?;
+        world.arena[i].charge = number(charge)?;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("set_magnetic", move |node: INT, magnetic: bool| -> ScriptResult<()> {
+        let mut world = w.world.borrow_mut();
+        let i = index(&world, node)?;
+        world.arena[i].magnetic = magnetic;
+        Ok(())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "cut",
+        move |x0: Dynamic, y0: Dynamic, x1: Dynamic, y1: Dynamic| -> ScriptResult<INT> {
+            let mut world = w.edit();
+            let before = world.events.len();
+            world.cut(point(x0, y0)?, point(x1, y1)?);
+            let cut = world.events[before..].iter().filter(|e| matches!(e, SimEvent::ConstraintCut(_))).count();
+            Ok(cut as INT)
+        },
+    );
+
+    let w = Rc::clone(world);
+    engine.register_fn("node_x", move |node: INT| -> ScriptResult<f32> {
+        let world = w.world.borrow();
+        Ok(world.arena[index(&world, node)?].pos.x)
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("node_y", move |node: INT| -> ScriptResult<f32> {
+        let world = w.world.borrow();
+        Ok(world.arena[index(&world, node)?].pos.y)
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("node_count", move || w.world.borrow().arena.len() as INT);
+    let w = Rc::clone(world);
+    engine.register_fn("constraint_count", move || w.world.borrow().constraints.len() as INT);
+    let w = Rc::clone(world);
+    engine.register_fn("ground_y", move || w.world.borrow().ground_y);
+    let w = Rc::clone(world);
+    engine.register_fn("nearest_node", move |x: Dynamic, y: Dynamic| -> ScriptResult<INT> {
+        let point = point(x, y)?;
+        Ok(w.query(|query| query.nearest_node(point)).map_or(-1, |i| i as INT))
+    });
+    let w = Rc::clone(world);
+    engine.register_fn("nodes_within", move |x: Dynamic, y: Dynamic, radius: Dynamic| -> ScriptResult<Array> {
+        let (point, radius) = (point(x, y)?, number(radius)?);
+        let nodes = w.query(|query| query.nodes_within(point, radius));
+        Ok(nodes.into_iter().map(|i| Dynamic::from(i as INT)).collect())
+    });
+    let w = Rc::clone(world);
+    engine.register_fn(
+        "raycast",
+        move |x0: Dynamic, y0: Dynamic, x1: Dynamic, y1: Dynamic| -> ScriptResult<Array> {
+            let (a, b) = (point(x0, y0)?, point(x1, y1)?);
+            let crossed = w.query(|query| query.raycast_constraints(a, b));
+            Ok(crossed.into_iter().map(|(i, _)| Dynamic::from(i as INT)).collect())
+        },
+    );
+
+    engine
+}
+
+fn add_node(world: &mut World, pos: Vec2, mass: f32, fixed: bool) -> ScriptResult<INT> {
+    if mass <= 0.0 {
+        return Err(error(format!("mass must be positive, got {}", mass)));
+    }
+    let mut node = Node::with_pos_and_mass(pos, mass);
+    node.fixed = fixed;
+    world.arena.push(node);
+    Ok(world.arena.len() as INT - 1)
+}
+
+fn add_constraint(world: &mut World, a: INT, b: INT, rest_length: Option<f32>) -> ScriptResult<()> {
+    let (a, b) = (index(world, a)?, index(world, b)?);
+    if a == b {
+        return Err(error(format!("can't link node {} to itself", a)));
+    }
+    let rest_length = rest_length.unwrap_or_else(|| world.arena[a].pos.distance(world.arena[b].pos));
+    if rest_length <= 0.0 {
+        return Err(error(format!("rest length must be positive, got {}", rest_length)));
+    }
+    let break_threshold = rest_length * world.params.break_threshold;
+    world.constraints.push(Constraint { a, b, rest_length, break_threshold });
+    Ok(())
+}
+
+fn index(world: &World, node: INT) -> ScriptResult<usize> {
+    usize::try_from(node)
+        .ok()
+        .filter(|&i| i < world.arena.len())
+        .ok_or_else(|| error(format!("no node {}", node)))
+}
+
+fn count(n: INT) -> ScriptResult<usize> {
+    usize::try_from(n).map_err(|_| error(format!("expected a count, got {}", n)))
+}
+
+fn point(x: Dynamic, y: Dynamic) -> ScriptResult<Vec2> {
+    Ok(Vec2::new(number(x)?, number(y)?))
+}
+
+/// Accepts integers as well as decimals, so `add_node(100, 50)` works.
+pub(crate) fn number(value: Dynamic) -> ScriptResult<f32> {
+    if let Some(x) = value.clone().try_cast::<f32>() {
+        Ok(x)
+    } else if let Some(n) = value.clone().try_cast::<INT>() {
+        Ok(n as f32)
+    } else {
+        Err(error(format!("expected a number, got {}", value.type_name())))
+    }
+}
+
+fn error(message: String) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorRuntime(message.into(), Position::NONE).into()
+}
@@ -0,0 +1,144 @@
+use crate::bindings::MouseBindings;
+use crate::error::SimError;
+use crate::palette::Palette;
+use clothsim::alert::STRAIN_ALERT;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_PATH: &str = "settings.toml";
+
+/// Everything in the settings window that persists between runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub show_grid: bool,
+    pub camera_shake: bool,
+    pub hit_stop: bool,
+    pub show_heatmap: bool,
+    pub show_glow: bool,
+    /// Streaks fast nodes along their velocity.
+    pub motion_blur: bool,
+    /// Fills cloth in, lit as if its wrinkles stood out of the screen.
+    pub shade_cloth: bool,
+    /// Traces the magnet's field lines over the scene.
+    pub field_lines: bool,
+    /// Warns on screen when a constraint gets `strain_alert` of the way to breaking.
+    pub strain_warnings: bool,
+    pub strain_alert: f32,
+    pub rope_shader: String,
+    /// The code of the language hints and captions are drawn in, like "es"; see `locale`.
+    pub language: String,
+    pub volume: f32,
+    pub muted: bool,
+    pub rope_creak: bool,
+    pub palette: Palette,
+    /// Shows the step counter and world checksum, for comparing runs.
+    pub show_checksum: bool,
+    /// Warns on screen when a phase of the frame takes more than its share of it; see `frame_budget`.
+    pub budget_warnings: bool,
+    /// Labels each node with its index and each link with the nodes it joins, for writing scene files.
+    pub show_indices: bool,
+    /// Shows the whole scene in a corner while part of it is off screen.
+    pub show_minimap: bool,
+    pub background_physics: bool,
+    /// Splits steps into substeps while nodes move fast.
+    pub adaptive_timestep: bool,
+    /// Gives links far from the cursor fewer solver iterations.
+    pub level_of_detail: bool,
+    /// Integrates and relaxes plain cloth and rope on the GPU, where it can.
+    pub gpu_solve: bool,
+    /// How much to scatter the strength of links in built scenes, from 0 for not at all.
+    pub link_variance: f32,
+    /// Moves pins and the ground along with the window when it's resized.
+    pub reanchor: bool,
+    /// What to scale node radii, line widths, and text by, or `None` to follow the display.
+    pub ui_scale: Option<f32>,
+    /// Draws a cursor for the tool in hand in place of the system's.
+    pub tool_cursors: bool,
+    /// Which mouse button cuts, grabs, pins, and pans.
+    pub mouse: MouseBindings,
+    /// Each knife swipe cuts only the first constraint it reaches.
+    pub single_cut: bool,
+    /// The knife splits nodes along its path instead, for clean slits in cloth.
+    pub split_nodes: bool,
+    /// The tutorial's been finished or skipped, so it doesn't start again.
+    pub tutorial_done: bool,
+    /// Fastest time to finish the cut challenge on each scene, by the scene's name.
+    pub best_times: BTreeMap<String, f32>,
+    /// Saves the game to the autosave slot every so often.
+    pub autosave: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            camera_shake: true,
+            hit_stop: true,
+            show_heatmap: false,
+            show_glow: true,
+            motion_blur: false,
+            shade_cloth: false,
+            field_lines: true,
+            strain_warnings: true,
+            strain_alert: STRAIN_ALERT,
+            rope_shader: "Flat".to_string(),
+            language: "en".to_string(),
+            volume: 0.5,
+            muted: false,
+            rope_creak: false,
+            palette: Palette::default(),
+            show_checksum: false,
+            budget_warnings: false,
+            show_indices: false,
+            show_minimap: true,
+            background_physics: false,
+            adaptive_timestep: false,
+            level_of_detail: false,
+            gpu_solve: false,
+            link_variance: 0.0,
+            reanchor: true,
+            ui_scale: None,
+            tool_cursors: true,
+            mouse: MouseBindings::default(),
+            single_cut: false,
+            split_nodes: false,
+            tutorial_done: false,
+            best_times: BTreeMap::new(),
+            autosave: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, or the defaults if there isn't one.
+    pub fn load() -> Result<Self, SimError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(text) = std::fs::read_to_string(SETTINGS_PATH) {
+            return toml::from_str(&text).map_err(|e| SimError::Config {
+                path: SETTINGS_PATH.to_string(),
+                message: e.to_string(),
+            });
+        }
+
+        Ok(Self::default())
+    }
+
+    pub fn save(&self) -> Result<(), SimError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let text = toml::to_string_pretty(self).map_err(|e| SimError::Config {
+                path: SETTINGS_PATH.to_string(),
+                message: e.to_string(),
+            })?;
+            std::fs::write(SETTINGS_PATH, text).map_err(|source| SimError::Io {
+                action: "save",
+                path: SETTINGS_PATH.to_string(),
+                source,
+            })?;
+        }
+
+        Ok(())
+    }
+}
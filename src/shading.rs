@@ -0,0 +1,108 @@
+//! Shading for cloth drawn flat. A triangle of cloth squeezed smaller than
+//! its links' rest lengths must be tilted out of the screen, since links
+//! don't shrink, so each face gets a pseudo-normal tipped over by as much as
+//! its most squeezed link is short, and is lit by a light up and to the left
+//! of the viewer. Wrinkles and billows then show as bands of light and shade.
+
+use crate::export::faces;
+use crate::render::Mesh;
+use crate::world::World;
+use glam::{Vec2, Vec3};
+use std::collections::HashMap;
+
+/// Where the light comes from, in screen space with y down and z toward the viewer.
+const LIGHT: Vec3 = Vec3::new(-1.0, -1.0, 2.0);
+/// Brightest a face gets, relative to one lying flat.
+const MAX_BRIGHTNESS: f32 = 1.25;
+
+/// A triangle of cloth and how brightly it's lit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadedFace {
+    pub corners: [Vec2; 3],
+    /// 1 lying flat, darker tipped away from the light and brighter toward it.
+    pub brightness: f32,
+    /// The depth its first corner's layer is drawn at.
+    pub depth: f32,
+}
+
+/// Every face spanned by the links, as `export::faces` finds them, that's on
+/// shown layers and doesn't cross a wrapped edge, with its shading.
+pub fn shade(world: &World) -> Vec<ShadedFace> {
+    let rest_lengths: HashMap<(usize, usize), f32> = world
+        .constraints
+        .iter()
+        .map(|constraint| ((constraint.a.min(constraint.b), constraint.a.max(constraint.b)), constraint.rest_length))
+        .collect();
+    let light = LIGHT.normalize();
+    let flat = light.z;
+    let hidden = world.hidden();
+
+    faces(world)
+        .into_iter()
+        .filter(|face| face.iter().all(|&i| !hidden[i]))
+        .filter_map(|face| {
+            let corners = face.map(|i| world.arena[i].pos);
+            let edges = [(0, 1), (1, 2), (2, 0)];
+            if edges.iter().any(|&(a, b)| world.wrap.delta(corners[a], corners[b]) != corners[b] - corners[a]) {
+                return None;
+            }
+
+            // the link squeezed shortest for its rest length, if any is
+            let squeezed = edges
+                .iter()
+                .filter_map(|&(a, b)| {
+                    let rest = rest_lengths.get(&(face[a].min(face[b]), face[a].max(face[b])))?;
+                    Some(((corners[b] - corners[a]).length() / rest, (a, b)))
+                })
+                .min_by(|x, y| x.0.total_cmp(&y.0));
+            let normal = match squeezed {
+                Some((ratio, (a, b))) if ratio < 1.0 => {
+                    // tipped up along the short side, away from the middle of the face
+                    let centroid = (corners[0] + corners[1] + corners[2]) / 3.0;
+                    let out = ((corners[a] + corners[b]) / 2.0 - centroid).normalize_or_zero();
+                    let tilt = (1.0 - ratio * ratio).sqrt();
+                    Vec3::new(out.x * tilt, out.y * tilt, ratio)
+                }
+                _ => Vec3::Z,
+            };
+            let brightness = (normal.dot(light) / flat).clamp(0.0, MAX_BRIGHTNESS);
+            let depth = world.layers.0[world.arena[face[0]].layer as usize].depth;
+            Some(ShadedFace { corners, brightness, depth })
+        })
+        .collect()
+}
+
+/// `faces` filled in `color`, RGBA from 0 to 1, lit by their shading, for `SimRenderer::draw_mesh`.
+pub fn mesh<'a>(faces: impl IntoIterator<Item = &'a ShadedFace>, color: [f32; 4]) -> Mesh {
+    let mut mesh = Mesh::default();
+    for face in faces {
+        let lit = |channel: f32| (channel * face.brightness).min(1.0);
+        mesh.push_triangle(face.corners, [lit(color[0]), lit(color[1]), lit(color[2]), color[3]]);
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_cloth_is_evenly_lit_and_squeezed_cloth_is_shaded() {
+        let mut world = World::new(1000.0);
+        world.add_cloth(Vec2::new(100.0, 100.0), 4, 3, 1);
+        let faces = shade(&world);
+        // two triangles per grid square
+        assert_eq!(faces.len(), 2 * 3 * 2);
+        assert!(faces.iter().all(|face| (face.brightness - 1.0).abs() < 1e-5));
+
+        // squeezing the middle column in from both sides tips the faces next to it
+        for row in 0..3 {
+            world.arena[row * 4 + 1].pos.x += 5.0;
+            world.arena[row * 4 + 2].pos.x -= 5.0;
+        }
+        let faces = shade(&world);
+        assert!(faces.iter().any(|face| face.brightness < 0.99));
+        assert!(faces.iter().any(|face| face.brightness > 1.01));
+        assert!(faces.iter().all(|face| face.brightness <= MAX_BRIGHTNESS));
+    }
+}
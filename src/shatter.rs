@@ -0,0 +1,129 @@
+//! Nodes that break apart along with their links. A link stretched past its
+//! threshold normally just goes, leaving a clean gap with both ends intact;
+//! with `shatter` on, the end of it with more links left also comes apart
+//! into two or three lighter fragments, each taking the links on one side of
+//! it, so a torn net throws off chips and ragged ends instead.
+
+use crate::constraint::Constraint;
+use crate::math;
+use crate::world::World;
+use glam::Vec2;
+
+/// Fragments a node shatters into, by default.
+pub const SHATTER_FRAGMENTS: usize = 3;
+/// How far from where the node was each fragment starts, toward its links.
+pub const FRAGMENT_GAP: f32 = 2.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shatter {
+    /// Two or three.
+    pub fragments: usize,
+}
+
+impl Default for Shatter {
+    fn default() -> Self {
+        Self { fragments: SHATTER_FRAGMENTS }
+    }
+}
+
+/// One piece of a shattered node: the links it takes, by index into
+/// `constraints`, and which way from the node it goes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fragment {
+    pub links: Vec<usize>,
+    pub heading: Vec2,
+}
+
+impl Shatter {
+    /// The end of `broken`, which has just snapped, that shatters: whichever
+    /// of the two has more links left in `world`, as long as it has any and
+    /// isn't fixed.
+    pub fn breaking_end(world: &World, broken: &Constraint) -> Option<usize> {
+        let links = |node: usize| world.constraints.iter().filter(|c| c.a == node || c.b == node).count();
+        let (a, b) = ((broken.a, links(broken.a)), (broken.b, links(broken.b)));
+        let (node, count) = if b.1 > a.1 { b } else { a };
+        (count > 0 && !world.arena[node].fixed).then_some(node)
+    }
+
+    /// Splits the links on `node` into `fragments` pieces, going around it
+    /// so each piece gets neighboring links. With fewer links than pieces,
+    /// the pieces left over are loose chips, heading off evenly around it.
+    pub fn fragments(&self, world: &World, node: usize) -> Vec<Fragment> {
+        let mut around: Vec<(f32, usize, Vec2)> = world
+            .constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| constraint.a == node || constraint.b == node)
+            .map(|(i, constraint)| {
+                let offset = constraint.offset(&world.arena, &world.wrap);
+                let toward = if constraint.a == node { offset } else { -offset };
+                (math::atan2(toward.y, toward.x), i, toward.normalize_or_zero())
+            })
+            .collect();
+        around.sort_by(|(x, ..), (y, ..)| x.total_cmp(y));
+
+        let count = self.fragments.max(1);
+        (0..count)
+            .map(|k| {
+                let group = &around[(k * around.len()).div_ceil(count)..((k + 1) * around.len()).div_ceil(count)];
+                let heading = group.iter().fold(Vec2::ZERO, |sum, &(_, _, toward)| sum + toward).normalize_or_zero();
+                let angle = std::f32::consts::TAU * k as f32 / count as f32;
+                let heading = if heading == Vec2::ZERO { math::from_angle(angle) } else { heading };
+                Fragment { links: group.iter().map(|&(_, i, _)| i).collect(), heading }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::SimEvent;
+    use crate::node::Node;
+    use crate::TARGET_DIST;
+
+    #[test]
+    fn an_overloaded_junction_shatters_into_lighter_fragments() {
+        // a node with four links, one of them pulled far past its threshold
+        let mut world = World::new(10_000.0);
+        world.shatter = Some(Shatter::default());
+        world.arena.push(Node::with_pos_and_mass(Vec2::ZERO, 3.0));
+        for direction in [Vec2::X, Vec2::Y, -Vec2::X, -Vec2::Y] {
+            world.arena.push(Node::with_pos_and_mass(direction * TARGET_DIST, 1.0));
+            let end = world.arena.len() - 1;
+            let break_threshold = TARGET_DIST * world.params.break_threshold;
+            world.constraints.push(Constraint { a: 0, b: end, rest_length: TARGET_DIST, break_threshold });
+        }
+        world.arena[1].pos.x = 100.0 * TARGET_DIST;
+        world.break_overloaded(world.params.dt);
+
+        assert_eq!(world.constraints.len(), 3);
+        assert_eq!(world.arena.len(), 7);
+        let masses: Vec<f32> = [0, 5, 6].iter().map(|&i| world.arena[i].mass).collect();
+        assert_eq!(masses, [1.0, 1.0, 1.0]);
+        // each fragment took one of the links left, and moved off toward it
+        for fragment in [0, 5, 6] {
+            let links: Vec<&Constraint> =
+                world.constraints.iter().filter(|c| c.a == fragment || c.b == fragment).collect();
+            assert_eq!(links.len(), 1);
+            let other = if links[0].a == fragment { links[0].b } else { links[0].a };
+            let toward = world.arena[other].pos - world.arena[fragment].pos;
+            assert!(toward.length() < TARGET_DIST, "{}", toward);
+        }
+        let events = world.drain_events();
+        let shattered = events.iter().filter(|e| matches!(e, SimEvent::NodeShattered { node: 0, .. })).count();
+        assert_eq!(shattered, 2);
+
+        // pulling a rope's end off shatters the node above it, not the end with no links left
+        let mut world = World::new(10_000.0);
+        world.shatter = Some(Shatter { fragments: 2 });
+        world.add_rope(Vec2::ZERO, 3);
+        world.arena[2].pos.y = 100.0 * TARGET_DIST;
+        world.break_overloaded(world.params.dt);
+        // into a fragment still hanging from the top and a loose chip
+        assert_eq!(world.arena.len(), 4);
+        assert_eq!(world.constraints.len(), 1);
+        assert_eq!((world.arena[1].mass, world.arena[3].mass), (0.5, 0.5));
+        assert!(world.constraints.iter().all(|c| c.a != 3 && c.b != 3));
+    }
+}
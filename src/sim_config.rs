@@ -0,0 +1,108 @@
+use crate::error::SimError;
+use clothsim::sim_world::Param;
+use clothsim::units::Units;
+use clothsim::Params;
+use serde::Deserialize;
+use std::time::SystemTime;
+
+const SIM_CONFIG_PATH: &str = "sim.toml";
+/// Seconds between checks for changes to the file.
+const POLL_INTERVAL: f64 = 0.5;
+
+/// `sim.toml`'s layout. Missing keys keep their defaults.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SimFile {
+    dt: f32,
+    /// In meters per second squared if `pixels_per_meter` is given.
+    gravity: Option<f32>,
+    pixels_per_meter: Option<f32>,
+    drag: f32,
+    rigidity: f32,
+    iterations: usize,
+    break_threshold: f32,
+}
+
+impl Default for SimFile {
+    fn default() -> Self {
+        let params = Params::default();
+        Self {
+            dt: params.dt,
+            gravity: None,
+            pixels_per_meter: None,
+            drag: params.drag,
+            rigidity: params.rigidity,
+            iterations: params.solver_iterations,
+            break_threshold: params.break_threshold,
+        }
+    }
+}
+
+impl From<SimFile> for Params {
+    fn from(file: SimFile) -> Self {
+        let gravity = match (file.gravity, file.pixels_per_meter) {
+            (Some(gravity), Some(pixels_per_meter)) => Units { pixels_per_meter }.acceleration(gravity),
+            (Some(gravity), None) => gravity,
+            (None, _) => Params::default().gravity,
+        };
+        Self {
+            dt: file.dt,
+            gravity,
+            drag: file.drag,
+            rigidity: file.rigidity,
+            solver_iterations: file.iterations,
+            break_threshold: file.break_threshold,
+        }
+    }
+}
+
+/// Physics params from `sim.toml`, or the defaults if there isn't one.
+/// Values the solver can't run with, as `Param::check` finds them, are an error.
+pub fn load() -> Result<Params, SimError> {
+    let error = |message: String| SimError::Config { path: SIM_CONFIG_PATH.to_string(), message };
+    match std::fs::read_to_string(SIM_CONFIG_PATH) {
+        Ok(text) => {
+            let params = toml::from_str::<SimFile>(&text).map(Params::from).map_err(|e| error(e.to_string()))?;
+            Param::check(&params).map_err(error)?;
+            Ok(params)
+        }
+        Err(_) => Ok(Params::default()),
+    }
+}
+
+/// Notices when `sim.toml` is edited. There's no file to watch in the browser,
+/// so it never reports a change there.
+pub struct SimConfigWatcher {
+    modified: Option<SystemTime>,
+    last_poll: f64,
+}
+
+impl SimConfigWatcher {
+    /// Starts watching from the file as it is now.
+    pub fn new() -> Self {
+        Self {
+            modified: modified_time(),
+            last_poll: 0.0,
+        }
+    }
+
+    /// The file's params if it changed since the last call, checking at most
+    /// every `POLL_INTERVAL` seconds of `now`.
+    pub fn poll(&mut self, now: f64) -> Option<Result<Params, SimError>> {
+        if now - self.last_poll < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = now;
+
+        let modified = modified_time();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(load())
+    }
+}
+
+fn modified_time() -> Option<SystemTime> {
+    std::fs::metadata(SIM_CONFIG_PATH).and_then(|meta| meta.modified()).ok()
+}
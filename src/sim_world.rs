@@ -0,0 +1,593 @@
+//! A checked front end to `World` for embedding the solver in other programs.
+//!
+//! `World` exposes its arrays directly, which is what the phases and the demo
+//! want but makes it easy to build an invalid scene. `SimWorld` validates
+//! everything going in, addresses constraints by their node pair so broken
+//! links don't shift anyone's handles, and hands out read-only snapshots.
+//! Host programs hook into each step with `on_pre_step`, `on_post_step`, and
+//! `on_constraint_broken` rather than stepping the world themselves.
+//!
+//! ```
+//! use clothsim::{Param, SimWorld, Vec2};
+//!
+//! let mut sim = SimWorld::new(500.0);
+//! let anchor = sim.add_node(Vec2::new(0.0, 0.0), 1.0, true).unwrap();
+//! let bob = sim.add_node(Vec2::new(50.0, 0.0), 1.0, false).unwrap();
+//! sim.add_constraint(anchor, bob, None, None).unwrap();
+//! sim.set_param(Param::Gravity, 9.8).unwrap();
+//! for _ in 0..100 {
+//!     sim.step();
+//! }
+//! assert!(sim.node(bob).unwrap().pos.y > 0.0);
+//! ```
+
+use crate::constraint::{Constraint, SeveredLink};
+use crate::event::SimEvent;
+use crate::node::Node;
+use crate::params::Params;
+use crate::weave::{Links, Weave};
+use crate::world::{index_after_removal, World};
+use glam::Vec2;
+use std::fmt;
+
+/// A node's index. Stepping can remove nodes, when a sink consumes one or
+/// `World::cleanup` despawns one, and every later node moves down an index;
+/// `after` follows an id through the events the step returned. Nodes added by
+/// fraying or shattering go on the end, so they move nobody.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+impl NodeId {
+    /// Where this node is once the removals among `events`, as `step` returns
+    /// them, have been made, or `None` if it was one of them.
+    pub fn after(self, events: &[SimEvent]) -> Option<NodeId> {
+        events.iter().try_fold(self, |id, event| match *event {
+            SimEvent::NodeConsumed { node, .. } | SimEvent::NodeDespawned { node, .. } => {
+                index_after_removal(id.0, node).map(NodeId)
+            }
+            _ => Some(id),
+        })
+    }
+}
+
+/// One of the `Params` fields, for setting them individually or by name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Param {
+    Dt,
+    Gravity,
+    Drag,
+    Rigidity,
+    SolverIterations,
+    BreakThreshold,
+}
+
+impl Param {
+    pub const ALL: [Param; 6] = [
+        Param::Dt,
+        Param::Gravity,
+        Param::Drag,
+        Param::Rigidity,
+        Param::SolverIterations,
+        Param::BreakThreshold,
+    ];
+
+    /// The param's key in `sim.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Param::Dt => "dt",
+            Param::Gravity => "gravity",
+            Param::Drag => "drag",
+            Param::Rigidity => "rigidity",
+            Param::SolverIterations => "iterations",
+            Param::BreakThreshold => "break_threshold",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|param| param.name() == name)
+    }
+
+    /// Whether the solver can run with the param at `value`: a finite number,
+    /// with a timestep and break threshold above zero, drag not negative,
+    /// rigidity in (0, 1], and a whole number of iterations, at least one.
+    pub fn accepts(self, value: f32) -> bool {
+        value.is_finite()
+            && match self {
+                Param::Dt | Param::BreakThreshold => value > 0.0,
+                Param::Gravity => true,
+                Param::Drag => value >= 0.0,
+                Param::Rigidity => value > 0.0 && value <= 1.0,
+                Param::SolverIterations => value >= 1.0 && value.fract() == 0.0,
+            }
+    }
+
+    /// What `accepts` wants of the param, to finish "dt must be ...".
+    pub fn requirement(self) -> &'static str {
+        match self {
+            Param::Dt | Param::BreakThreshold => "above 0",
+            Param::Gravity => "a finite number",
+            Param::Drag => "0 or more",
+            Param::Rigidity => "above 0 and at most 1",
+            Param::SolverIterations => "a whole number, at least 1",
+        }
+    }
+
+    /// The param's value in `params`.
+    pub fn get(self, params: &Params) -> f32 {
+        match self {
+            Param::Dt => params.dt,
+            Param::Gravity => params.gravity,
+            Param::Drag => params.drag,
+            Param::Rigidity => params.rigidity,
+            Param::SolverIterations => params.solver_iterations as f32,
+            Param::BreakThreshold => params.break_threshold,
+        }
+    }
+
+    /// Why the solver can't run with `params`, naming the first param it
+    /// doesn't `accept`, if there is one.
+    pub fn check(params: &Params) -> Result<(), String> {
+        match Self::ALL.into_iter().find(|param| !param.accepts(param.get(params))) {
+            Some(param) => Err(format!("{} must be {}, got {}", param.name(), param.requirement(), param.get(params))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Why a `SimWorld` call was refused. The world is unchanged when one is returned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimWorldError {
+    NoSuchNode(NodeId),
+    /// A constraint from a node to itself.
+    SelfLink(NodeId),
+    NonPositiveMass(f32),
+    NonPositiveRestLength(f32),
+    /// A spring in a cloth's weave that wouldn't pull back.
+    NonPositiveStiffness(f32),
+    /// A param value the solver can't run with, like a zero timestep.
+    InvalidParam { param: Param, value: f32 },
+}
+
+impl fmt::Display for SimWorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimWorldError::NoSuchNode(NodeId(node)) => write!(f, "no node {}", node),
+            SimWorldError::SelfLink(NodeId(node)) => write!(f, "can't link node {} to itself", node),
+            SimWorldError::NonPositiveMass(mass) => write!(f, "mass must be positive, got {}", mass),
+            SimWorldError::NonPositiveRestLength(length) => {
+                write!(f, "rest length must be positive, got {}", length)
+            }
+            SimWorldError::NonPositiveStiffness(stiffness) => {
+                write!(f, "stiffness must be positive, got {}", stiffness)
+            }
+            SimWorldError::InvalidParam { param, value } => write!(f, "invalid {}: {}", param.name(), value),
+        }
+    }
+}
+
+impl std::error::Error for SimWorldError {}
+
+/// A node's state at the time it was queried.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeState {
+    pub id: NodeId,
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub mass: f32,
+    pub fixed: bool,
+}
+
+impl NodeState {
+    fn new(id: NodeId, node: &Node) -> Self {
+        Self {
+            id,
+            pos: node.pos,
+            vel: node.vel,
+            mass: node.mass,
+            fixed: node.fixed,
+        }
+    }
+}
+
+/// A constraint's state at the time it was queried.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ConstraintState {
+    pub a: NodeId,
+    pub b: NodeId,
+    pub rest_length: f32,
+    pub break_threshold: f32,
+    /// How close it is to breaking, from 0 at rest length to 1 at the threshold.
+    pub break_fraction: f32,
+    /// How hard it pulls on its ends, negative when pushing; see `Constraint::tension`.
+    pub tension: f32,
+}
+
+type StepHook = Box<dyn FnMut(&mut SimWorld) + Send + Sync>;
+type PostStepHook = Box<dyn FnMut(&mut SimWorld, &[SimEvent]) + Send + Sync>;
+type BrokenHook = Box<dyn FnMut(&SimWorld, &SeveredLink) + Send + Sync>;
+
+/// The callbacks registered on a `SimWorld`, in the order they run.
+#[derive(Default)]
+struct Hooks {
+    pre_step: Vec<StepHook>,
+    post_step: Vec<PostStepHook>,
+    constraint_broken: Vec<BrokenHook>,
+}
+
+impl Hooks {
+    /// Puts back hooks `step` took out to run, ahead of any registered while they ran.
+    fn restore(&mut self, mut taken: Hooks) {
+        taken.pre_step.append(&mut self.pre_step);
+        taken.post_step.append(&mut self.post_step);
+        taken.constraint_broken.append(&mut self.constraint_broken);
+        *self = taken;
+    }
+}
+
+/// Callbacks can't be copied, so a clone starts without any.
+impl Clone for Hooks {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("pre_step", &self.pre_step.len())
+            .field("post_step", &self.post_step.len())
+            .field("constraint_broken", &self.constraint_broken.len())
+            .finish()
+    }
+}
+
+/// A `World` that can only be changed in ways that keep it valid.
+#[derive(Clone, Debug)]
+pub struct SimWorld {
+    world: World,
+    hooks: Hooks,
+    /// How many times `clear_hooks` has run, so `step` can tell whether a hook
+    /// cleared them and not put back the ones it took out to run.
+    clears: u64,
+}
+
+impl SimWorld {
+    /// An empty world with the ground at `ground_y` and the default params.
+    pub fn new(ground_y: f32) -> Self {
+        Self::wrap(World::new(ground_y))
+    }
+
+    fn wrap(world: World) -> Self {
+        Self { world, hooks: Hooks::default(), clears: 0 }
+    }
+
+    /// Wraps an existing world, such as a built-in scene, after checking its constraints.
+    pub fn from_world(world: World) -> Result<Self, SimWorldError> {
+        for constraint in world.constraints.iter() {
+            for node in [constraint.a, constraint.b] {
+                if node >= world.arena.len() {
+                    return Err(SimWorldError::NoSuchNode(NodeId(node)));
+                }
+            }
+        }
+        Ok(Self::wrap(world))
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn into_world(self) -> World {
+        self.world
+    }
+
+    /// Adds a node at `pos`, pinned in place if `fixed`.
+    pub fn add_node(&mut self, pos: Vec2, mass: f32, fixed: bool) -> Result<NodeId, SimWorldError> {
+        if mass.is_nan() || mass <= 0.0 {
+            return Err(SimWorldError::NonPositiveMass(mass));
+        }
+        let mut node = Node::with_pos_and_mass(pos, mass);
+        node.fixed = fixed;
+        self.world.arena.push(node);
+        Ok(NodeId(self.world.arena.len() - 1))
+    }
+
+    /// Links `a` and `b`. The rest length defaults to their current distance, and
+    /// the break threshold to the params' `break_threshold` rest lengths.
+    pub fn add_constraint(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        rest_length: Option<f32>,
+        break_threshold: Option<f32>,
+    ) -> Result<(), SimWorldError> {
+        let (pos_a, pos_b) = (self.node(a)?.pos, self.node(b)?.pos);
+        if a == b {
+            return Err(SimWorldError::SelfLink(a));
+        }
+        let rest_length = rest_length.unwrap_or_else(|| pos_a.distance(pos_b));
+        if rest_length.is_nan() || rest_length <= 0.0 {
+            return Err(SimWorldError::NonPositiveRestLength(rest_length));
+        }
+        let break_threshold = break_threshold.unwrap_or(rest_length * self.world.params.break_threshold);
+        self.world.constraints.push(Constraint { a: a.0, b: b.0, rest_length, break_threshold });
+        Ok(())
+    }
+
+    pub fn params(&self) -> Params {
+        self.world.params
+    }
+
+    /// Sets one param, rejecting values the solver can't run with, as `Param::accepts` says.
+    pub fn set_param(&mut self, param: Param, value: f32) -> Result<(), SimWorldError> {
+        if !param.accepts(value) {
+            return Err(SimWorldError::InvalidParam { param, value });
+        }
+
+        let mut params = self.world.params;
+        match param {
+            Param::Dt => params.dt = value,
+            Param::Gravity => params.gravity = value,
+            Param::Drag => params.drag = value,
+            Param::Rigidity => params.rigidity = value,
+            Param::SolverIterations => params.solver_iterations = value as usize,
+            Param::BreakThreshold => params.break_threshold = value,
+        }
+        self.world.set_params(params);
+        Ok(())
+    }
+
+    /// Pins or releases a node.
+    pub fn set_fixed(&mut self, node: NodeId, fixed: bool) -> Result<(), SimWorldError> {
+        self.node_mut(node)?.fixed = fixed;
+        Ok(())
+    }
+
+    /// Moves a node to `pos` and stops it, for dragging it from outside the simulation.
+    pub fn set_position(&mut self, node: NodeId, pos: Vec2) -> Result<(), SimWorldError> {
+        let node = self.node_mut(node)?;
+        node.pos = pos;
+        node.last_pos = pos;
+        node.vel = Vec2::ZERO;
+        Ok(())
+    }
+
+    /// Hangs a rope of `num_points` nodes from a fixed node at `anchor`, as `World::add_rope`.
+    pub fn add_rope(&mut self, anchor: Vec2, num_points: usize) {
+        self.world.add_rope(anchor, num_points);
+    }
+
+    /// Hangs a `columns` by `rows` cloth from `top_left`, as `World::add_cloth`.
+    pub fn add_cloth(&mut self, top_left: Vec2, columns: usize, rows: usize, pin_every: usize) {
+        self.world.add_cloth(top_left, columns, rows, pin_every);
+    }
+
+    /// Hangs a cloth woven as `weave` says, as `World::add_woven_cloth`.
+    pub fn add_woven_cloth(
+        &mut self,
+        top_left: Vec2,
+        columns: usize,
+        rows: usize,
+        pin_every: usize,
+        weave: &Weave,
+    ) -> Result<(), SimWorldError> {
+        for links in [weave.warp, weave.weft, weave.shear, weave.bend] {
+            if let Links::Spring(stiffness) = links {
+                if stiffness.is_nan() || stiffness <= 0.0 {
+                    return Err(SimWorldError::NonPositiveStiffness(stiffness));
+                }
+            }
+        }
+        self.world.add_woven_cloth(top_left, columns, rows, pin_every, weave);
+        Ok(())
+    }
+
+    /// Runs one step of the params' `dt`, returning what happened during it,
+    /// including any nodes removed; see `NodeId::after`.
+    /// The pre-step hooks run first, then once the world has moved the
+    /// constraint-broken hooks for each link that snapped, and last the
+    /// post-step hooks. Hooks that step the world themselves do so without
+    /// the hooks.
+    pub fn step(&mut self) -> Vec<SimEvent> {
+        let (mut hooks, clears) = (std::mem::take(&mut self.hooks), self.clears);
+        for hook in hooks.pre_step.iter_mut() {
+            hook(self);
+        }
+        let events = self.world.step(self.world.params.dt);
+        for event in events.iter() {
+            if let SimEvent::ConstraintBroken(link) = event {
+                hooks.constraint_broken.iter_mut().for_each(|hook| hook(self, link));
+            }
+        }
+        for hook in hooks.post_step.iter_mut() {
+            hook(self, &events);
+        }
+        if self.clears == clears {
+            self.hooks.restore(hooks);
+        }
+        events
+    }
+
+    /// Calls `hook` at the start of every step, free to change the world the
+    /// same ways its caller can, such as pinning nodes or setting params.
+    pub fn on_pre_step(&mut self, hook: impl FnMut(&mut SimWorld) + Send + Sync + 'static) {
+        self.hooks.pre_step.push(Box::new(hook));
+    }
+
+    /// Calls `hook` at the end of every step with what happened during it.
+    pub fn on_post_step(&mut self, hook: impl FnMut(&mut SimWorld, &[SimEvent]) + Send + Sync + 'static) {
+        self.hooks.post_step.push(Box::new(hook));
+    }
+
+    /// Calls `hook` for every constraint that snaps while stepping, once the
+    /// step is over, with the world as it was left. Links cut with `cut`
+    /// don't count.
+    pub fn on_constraint_broken(&mut self, hook: impl FnMut(&SimWorld, &SeveredLink) + Send + Sync + 'static) {
+        self.hooks.constraint_broken.push(Box::new(hook));
+    }
+
+    /// Unregisters every hook. From inside a hook, that includes the ones
+    /// running this step, which don't run again.
+    pub fn clear_hooks(&mut self) {
+        self.hooks = Hooks::default();
+        self.clears += 1;
+    }
+
+    /// Removes constraints crossing the segment from `from` to `to`, returning how many were cut.
+    pub fn cut(&mut self, from: Vec2, to: Vec2) -> usize {
+        self.world.cut(from, to);
+        let events = self.world.drain_events();
+        events.iter().filter(|event| matches!(event, SimEvent::ConstraintCut(_))).count()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.world.arena.len()
+    }
+
+    pub fn constraint_count(&self) -> usize {
+        self.world.constraints.len()
+    }
+
+    pub fn node(&self, id: NodeId) -> Result<NodeState, SimWorldError> {
+        let node = self.world.arena.get(id.0).ok_or(SimWorldError::NoSuchNode(id))?;
+        Ok(NodeState::new(id, node))
+    }
+
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = NodeState> + '_ {
+        self.world.arena.iter().enumerate().map(|(i, node)| NodeState::new(NodeId(i), node))
+    }
+
+    /// The intact constraint between `a` and `b`, in either order, if there is one.
+    pub fn constraint(&self, a: NodeId, b: NodeId) -> Option<ConstraintState> {
+        self.constraints().find(|constraint| {
+            let pair = (constraint.a, constraint.b);
+            pair == (a, b) || pair == (b, a)
+        })
+    }
+
+    pub fn constraints(&self) -> impl ExactSizeIterator<Item = ConstraintState> + '_ {
+        self.world.constraints.iter().map(|constraint| ConstraintState {
+            a: NodeId(constraint.a),
+            b: NodeId(constraint.b),
+            rest_length: constraint.rest_length,
+            break_threshold: constraint.break_threshold,
+            break_fraction: constraint.break_fraction(&self.world.arena, &self.world.wrap),
+            tension: constraint.tension(&self.world.arena, &self.world.wrap),
+        })
+    }
+
+    /// The node closest to `pos` within `radius`, if any.
+    pub fn nearest_node(&self, pos: Vec2, radius: f32) -> Option<NodeId> {
+        self.world
+            .arena
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (i, node.pos.distance(pos)))
+            .filter(|&(_, distance)| distance <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| NodeId(i))
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, SimWorldError> {
+        self.world.arena.get_mut(id.0).ok_or(SimWorldError::NoSuchNode(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_bad_input_without_changing_the_world() {
+        let mut sim = SimWorld::new(500.0);
+        let a = sim.add_node(Vec2::ZERO, 1.0, true).unwrap();
+        assert_eq!(sim.add_node(Vec2::ZERO, 0.0, false), Err(SimWorldError::NonPositiveMass(0.0)));
+        assert_eq!(sim.add_constraint(a, a, None, None), Err(SimWorldError::SelfLink(a)));
+        assert_eq!(sim.add_constraint(a, NodeId(7), None, None), Err(SimWorldError::NoSuchNode(NodeId(7))));
+        let invalid = SimWorldError::InvalidParam { param: Param::Dt, value: 0.0 };
+        assert_eq!(sim.set_param(Param::Dt, 0.0), Err(invalid));
+        assert!(sim.set_param(Param::SolverIterations, 2.5).is_err());
+        assert_eq!(Param::check(&Params::default()), Ok(()));
+        let params = Params { rigidity: 2.0, break_threshold: 0.0, ..Params::default() };
+        assert_eq!(Param::check(&params), Err("rigidity must be above 0 and at most 1, got 2".to_string()));
+        assert_eq!((sim.node_count(), sim.constraint_count()), (1, 0));
+        assert_eq!(sim.params(), Params::default());
+    }
+
+    #[test]
+    fn constraints_are_found_by_either_order_of_their_nodes() {
+        let mut sim = SimWorld::new(500.0);
+        let a = sim.add_node(Vec2::ZERO, 1.0, true).unwrap();
+        let b = sim.add_node(Vec2::new(30.0, 40.0), 1.0, false).unwrap();
+        sim.add_constraint(a, b, None, None).unwrap();
+        let constraint = sim.constraint(b, a).unwrap();
+        assert_eq!(constraint.rest_length, 50.0);
+        assert_eq!(constraint.break_threshold, 50.0 * crate::BREAK_THRESHOLD);
+        assert_eq!(sim.cut(Vec2::new(0.0, 30.0), Vec2::new(30.0, 0.0)), 1);
+        assert_eq!(sim.constraint(a, b), None);
+    }
+
+    #[test]
+    fn ids_follow_nodes_past_removals() {
+        let mut world = World::new(500.0);
+        world.sinks.push(crate::well::Sink { pos: Vec2::new(100.0, 0.0), strength: 0.0, radius: 10.0 });
+        let mut sim = SimWorld::from_world(world).unwrap();
+        let ids: Vec<_> = [0.0, 100.0, 200.0].map(|x| sim.add_node(Vec2::new(x, 0.0), 1.0, false).unwrap()).into();
+        let events = sim.step();
+        assert_eq!(sim.node_count(), 2);
+        let moved: Vec<_> = ids.iter().map(|id| id.after(&events)).collect();
+        assert_eq!(moved, [Some(NodeId(0)), None, Some(NodeId(1))]);
+        assert_eq!(sim.node(ids[2].after(&events).unwrap()).unwrap().pos.x, 200.0);
+    }
+
+    #[test]
+    fn hooks_run_around_each_step() {
+        use std::sync::{Arc, Mutex};
+
+        let mut sim = SimWorld::new(500.0);
+        let anchor = sim.add_node(Vec2::ZERO, 1.0, true).unwrap();
+        let bob = sim.add_node(Vec2::new(20.0, 0.0), 1.0, false).unwrap();
+        sim.add_constraint(anchor, bob, None, Some(25.0)).unwrap();
+        // one soft iteration, so the solver can't pull a yanked bob all the way back
+        sim.set_param(Param::SolverIterations, 1.0).unwrap();
+        sim.set_param(Param::Rigidity, 0.5).unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let pre = log.clone();
+        sim.on_pre_step(move |sim| {
+            pre.lock().unwrap().push("pre");
+            // yanked hard on the second step, which snaps the link
+            if sim.node(bob).unwrap().vel != Vec2::ZERO {
+                sim.set_position(bob, Vec2::new(0.0, 400.0)).unwrap();
+            }
+        });
+        let broken = log.clone();
+        sim.on_constraint_broken(move |sim, link| {
+            assert_eq!((link.a, link.b), (anchor.0, bob.0));
+            assert_eq!(sim.constraint_count(), 0);
+            broken.lock().unwrap().push("broken");
+        });
+        let post = log.clone();
+        sim.on_post_step(move |_, events| post.lock().unwrap().push(if events.is_empty() { "post" } else { "post!" }));
+
+        sim.step();
+        sim.step();
+        assert_eq!(*log.lock().unwrap(), ["pre", "post", "pre", "broken", "post!"]);
+        assert_eq!(format!("{:?}", sim.clone().hooks), "Hooks { pre_step: 0, post_step: 0, constraint_broken: 0 }");
+        sim.clear_hooks();
+        sim.step();
+        assert_eq!(log.lock().unwrap().len(), 5);
+
+        // clearing from inside a hook sticks, apart from hooks registered after it
+        let (once, again) = (log.clone(), log.clone());
+        sim.on_post_step(move |sim, _| {
+            once.lock().unwrap().push("once");
+            sim.clear_hooks();
+            let again = again.clone();
+            sim.on_post_step(move |_, _| again.lock().unwrap().push("again"));
+        });
+        sim.step();
+        sim.step();
+        assert_eq!(log.lock().unwrap()[5..], ["once", "again"]);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::constraint::Constraint;
+use crate::geometry;
+use crate::node::Node;
+
+/// Keeps a node on a rope, like a trolley on a zip-line: it slides freely
+/// along the rope while its weight drags the rope down with it. Unlike a
+/// `Rail`, the track is made of nodes, so both sides give way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slider {
+    pub node: usize,
+    /// The rope's nodes in order. Pieces between neighbours that are no longer
+    /// linked, like after a cut, are left out.
+    pub rope: Vec<usize>,
+}
+
+impl Slider {
+    /// Pulls the node and the nearest piece of rope together until the node is
+    /// on it, each giving way in proportion to its inverse mass like a `Ball`.
+    pub fn project(&self, constraints: &[Constraint], arena: &mut [Node]) {
+        let p = arena[self.node].pos;
+        let linked = |a: usize, b: usize| constraints.iter().any(|c| (c.a, c.b) == (a, b) || (c.a, c.b) == (b, a));
+        let nearest = self
+            .rope
+            .windows(2)
+            .filter(|pair| !pair.contains(&self.node) && linked(pair[0], pair[1]))
+            .map(|pair| {
+                let (a, b) = (arena[pair[0]].pos, arena[pair[1]].pos);
+                let t = geometry::nearest_on_segment(p, a, b);
+                (pair[0], pair[1], t, p - a.lerp(b, t))
+            })
+            .min_by(|x, y| x.3.length_squared().total_cmp(&y.3.length_squared()));
+        let Some((a, b, t, offset)) = nearest else {
+            return;
+        };
+
+        let (node, a_node, b_node) = (arena[self.node], arena[a], arena[b]);
+        let (a_weight, b_weight) = ((1.0 - t) * a_node.inverse_mass(), t * b_node.inverse_mass());
+        let total = node.inverse_mass() + (1.0 - t) * a_weight + t * b_weight;
+        if total == 0.0 {
+            return;
+        }
+        let pull = offset / total;
+        arena[self.node].add_offs(-pull * node.inverse_mass());
+        arena[a].add_offs(pull * a_weight);
+        arena[b].add_offs(pull * b_weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn meets_the_rope_by_mass_and_skips_cut_pieces() {
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(0.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(10.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(20.0, 0.0), 1.0),
+            Node::with_pos_and_mass(Vec2::new(5.0, 4.0), 2.0),
+        ];
+        let link = |a, b| Constraint { a, b, rest_length: 10.0, break_threshold: 50.0 };
+        let slider = Slider { node: 3, rope: vec![0, 1, 2] };
+
+        // the middle of a piece between two unit masses weighs as much as the
+        // node, so they meet halfway
+        slider.project(&[link(0, 1), link(1, 2)], &mut arena);
+        assert!((arena[3].pos.y - 2.0).abs() < 1e-5, "{}", arena[3].pos.y);
+        assert!((arena[0].pos.y - 2.0).abs() < 1e-5, "{}", arena[0].pos.y);
+
+        // with the first piece cut, the nearest one left is the second
+        arena[3].pos = Vec2::new(5.0, 4.0);
+        slider.project(&[link(1, 2)], &mut arena);
+        assert!(arena[3].pos.x > 6.0, "{}", arena[3].pos);
+    }
+}
@@ -0,0 +1,102 @@
+//! The order links are projected in each iteration. Each projection sees
+//! where the ones before it left things, so going through them the same way
+//! every time favors one end of a structure over the other: a level rope
+//! sagged with few iterations sits lower on the side solved last, and cloth
+//! creeps sideways. Going back and forth, or through them in a fresh
+//! shuffle each iteration from the world's `rng`, evens that out while
+//! keeping every step reproducible from the seed.
+
+use crate::rng::Rng;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SolveOrder {
+    /// First to last every iteration.
+    #[default]
+    Forward,
+    /// First to last, then last to first, and so on.
+    Alternating,
+    /// A different shuffle every iteration.
+    Shuffled,
+}
+
+impl SolveOrder {
+    pub const ALL: [SolveOrder; 3] = [SolveOrder::Forward, SolveOrder::Alternating, SolveOrder::Shuffled];
+
+    /// The word for it in a scene file.
+    pub fn name(self) -> &'static str {
+        match self {
+            SolveOrder::Forward => "forward",
+            SolveOrder::Alternating => "alternating",
+            SolveOrder::Shuffled => "shuffled",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.name() == name)
+    }
+
+    /// Fills `order` with the indices of `count` links in the order to solve
+    /// them on `iteration`, drawing from `rng` only if shuffled.
+    pub fn arrange(self, order: &mut Vec<usize>, count: usize, iteration: usize, rng: &mut Rng) {
+        order.clear();
+        order.extend(0..count);
+        match self {
+            SolveOrder::Forward => {}
+            SolveOrder::Alternating => {
+                if iteration % 2 == 1 {
+                    order.reverse();
+                }
+            }
+            SolveOrder::Shuffled => {
+                for i in (1..count).rev() {
+                    order.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+    use crate::TARGET_DIST;
+    use glam::Vec2;
+
+    #[test]
+    fn going_back_and_forth_evens_out_a_sagging_rope() {
+        let mut order = Vec::new();
+        SolveOrder::Alternating.arrange(&mut order, 4, 1, &mut Rng::default());
+        assert_eq!(order, [3, 2, 1, 0]);
+        let mut rng = Rng::new(7);
+        SolveOrder::Shuffled.arrange(&mut order, 50, 0, &mut rng);
+        let mut again = Vec::new();
+        SolveOrder::Shuffled.arrange(&mut again, 50, 0, &mut Rng::new(7));
+        assert_eq!(order, again);
+        assert_ne!(order, (0..50).collect::<Vec<_>>());
+        again.sort();
+        assert_eq!(again, (0..50).collect::<Vec<_>>());
+
+        // a level rope between two pins, with too few iterations to pull it taut
+        let lopsided = |solve_order: SolveOrder| {
+            let mut world = World::new(100_000.0);
+            world.params.solver_iterations = 2;
+            world.solve_order = solve_order;
+            world.add_rope(Vec2::ZERO, 21);
+            for (i, node) in world.arena.iter_mut().enumerate() {
+                node.pos = Vec2::new(i as f32 * TARGET_DIST, 0.0);
+                node.last_pos = node.pos;
+            }
+            world.arena[20].fixed = true;
+            let dt = world.params.dt;
+            for _ in 0..300 {
+                world.step(dt);
+            }
+            (0..10).map(|i| (world.arena[i].pos.y - world.arena[20 - i].pos.y).abs()).sum::<f32>()
+        };
+        let (forward, alternating, shuffled) =
+            (lopsided(SolveOrder::Forward), lopsided(SolveOrder::Alternating), lopsided(SolveOrder::Shuffled));
+        assert!(alternating < 0.5 * forward, "{} {}", alternating, forward);
+        assert!(shuffled < forward, "{} {}", shuffled, forward);
+    }
+}
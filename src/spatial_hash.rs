@@ -0,0 +1,63 @@
+use glam::Vec2;
+use std::collections::HashMap;
+
+/// Points bucketed into square cells, for finding the ones near a spot
+/// without checking them all. Buckets keep the order points were added in
+/// and are visited in a fixed order, so queries are deterministic.
+#[derive(Clone, Debug, Default)]
+pub struct SpatialHash {
+    cell: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Buckets `points` by index into cells of side `cell`, which queries
+    /// are fastest for when it's about the radius they search.
+    pub fn new(cell: f32, points: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut hash = Self { cell, cells: HashMap::new() };
+        for (i, point) in points.into_iter().enumerate() {
+            hash.cells.entry(hash.key(point)).or_default().push(i);
+        }
+        hash
+    }
+
+    fn key(&self, point: Vec2) -> (i32, i32) {
+        let cell = (point / self.cell).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+
+    /// Every point in a cell touching the rectangle from `min` to `max`, which
+    /// includes all of those inside it and some nearby.
+    pub fn within(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = usize> + '_ {
+        let ((x0, y0), (x1, y1)) = (self.key(min), self.key(max));
+        (y0..=y1)
+            .flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .copied()
+    }
+
+    /// Every point in a cell touching the square around `pos` out to `radius`,
+    /// which includes all of those within `radius` of it.
+    pub fn near(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        self.within(pos - radius, pos + radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_every_point_in_reach() {
+        let points: Vec<Vec2> = (0..50).map(|i| Vec2::new(i as f32 * 7.3 - 100.0, (i * i % 31) as f32 * 5.1)).collect();
+        let hash = SpatialHash::new(10.0, points.iter().copied());
+        let pos = Vec2::new(20.0, 40.0);
+
+        let mut found: Vec<usize> = hash.near(pos, 30.0).filter(|&i| points[i].distance(pos) < 30.0).collect();
+        found.sort_unstable();
+        let expected: Vec<usize> = (0..points.len()).filter(|&i| points[i].distance(pos) < 30.0).collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+}
@@ -0,0 +1,158 @@
+//! Recommends params that keep a scene stable, by kicking copies of it and
+//! watching whether they settle.
+//!
+//! Each trial gives every free node a small random velocity and runs for the
+//! same stretch of simulated time. It fails if any node stops being finite, a
+//! constraint breaks, the energy ends up higher than the kick left it (the
+//! solver is adding energy instead of losing it to drag), or constraints
+//! stretch past `MAX_STRAIN` (too few iterations to hold them together).
+
+use crate::energy::Energy;
+use crate::math;
+use crate::params::Params;
+use crate::rng::Rng;
+use crate::world::World;
+use crate::SimEvent;
+use crate::{DT, TARGET_DIST};
+
+/// Simulated time each trial runs for, so trials with a smaller dt take more steps.
+pub const TRIAL_TIME: f32 = 300.0 * DT;
+/// Largest stretch past rest length, as a fraction of it, a stable trial allows.
+pub const MAX_STRAIN: f32 = 0.25;
+/// Speed of the kick given to every free node, a tenth of a rest length per default step.
+const KICK_SPEED: f32 = 0.1 * TARGET_DIST / DT;
+/// Iteration counts tried, on top of the current one.
+const ITERATIONS: [usize; 4] = [5, 10, 20, 40];
+/// Fractions of the current dt tried.
+const DT_FRACTIONS: [f32; 3] = [1.0, 0.5, 0.25];
+
+/// How one set of params coped with a kick.
+#[derive(Copy, Clone, Debug)]
+pub struct Trial {
+    pub params: Params,
+    /// Steps run, fewer than the trial's length if it diverged.
+    pub steps: usize,
+    pub diverged: bool,
+    pub broken: usize,
+    /// Largest stretch of any constraint past its rest length, as a fraction of it.
+    pub max_strain: f32,
+    /// Energy at the end over energy just after the kick; above 1 means the
+    /// solver added energy.
+    pub energy_growth: f32,
+}
+
+impl Trial {
+    pub fn stable(&self) -> bool {
+        !self.diverged && self.broken == 0 && self.energy_growth <= 1.0 && self.max_strain <= MAX_STRAIN
+    }
+}
+
+/// The outcome of `analyze`.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// The params the scene already had.
+    pub current: Trial,
+    /// The cheapest stable trial, `current` itself if that was stable.
+    pub recommended: Option<Trial>,
+}
+
+/// Kicks copies of `world` with its own params and with more iterations and
+/// smaller timesteps, cheapest first, until one is stable. Only ever suggests
+/// more iterations and a smaller dt than `world` has, so every other param and
+/// the scene's look stay the same.
+pub fn analyze(world: &World, seed: u64) -> Report {
+    let base = world.params;
+    let current = trial(world, base, seed);
+    if current.stable() {
+        return Report { current, recommended: Some(current) };
+    }
+
+    let mut candidates = Vec::new();
+    for fraction in DT_FRACTIONS {
+        let iterations = ITERATIONS.iter().copied().filter(|&i| i > base.solver_iterations);
+        for solver_iterations in std::iter::once(base.solver_iterations).chain(iterations) {
+            let params = Params { solver_iterations, dt: base.dt * fraction, ..base };
+            if params != base {
+                candidates.push(params);
+            }
+        }
+    }
+    candidates.sort_by(|a, b| cost(a).total_cmp(&cost(b)));
+
+    let recommended = candidates.into_iter().map(|params| trial(world, params, seed)).find(Trial::stable);
+    Report { current, recommended }
+}
+
+/// Solver iterations per unit of simulated time, what `params` cost to run.
+fn cost(params: &Params) -> f32 {
+    params.solver_iterations as f32 / params.dt
+}
+
+/// Kicks a copy of `world` and runs it with `params` for `TRIAL_TIME`.
+pub fn trial(world: &World, params: Params, seed: u64) -> Trial {
+    let mut world = world.clone();
+    world.set_params(params);
+    let mut rng = Rng::new(seed);
+    for node in world.arena.iter_mut().filter(|node| !node.fixed) {
+        let angle = rng.range(0.0, std::f32::consts::TAU);
+        node.vel += math::from_angle(angle) * KICK_SPEED;
+        node.last_pos = node.pos - node.vel * params.dt;
+    }
+
+    let start = Energy::of(&world).total();
+    let mut result = Trial {
+        params,
+        steps: 0,
+        diverged: false,
+        broken: 0,
+        max_strain: 0.0,
+        energy_growth: 0.0,
+    };
+    let steps = (TRIAL_TIME / params.dt).ceil() as usize;
+    for _ in 0..steps {
+        let events = world.step(params.dt);
+        result.steps += 1;
+        result.broken += events.iter().filter(|e| matches!(e, SimEvent::ConstraintBroken(_))).count();
+        if world.arena.iter().any(|node| !node.pos.is_finite() || !node.vel.is_finite()) {
+            result.diverged = true;
+            return result;
+        }
+        let strain = world.constraints.iter().map(|c| c.strain(&world.arena, &world.wrap)).fold(0.0, f32::max);
+        result.max_strain = result.max_strain.max(strain);
+    }
+
+    result.energy_growth = if start > 0.0 { Energy::of(&world).total() / start } else { 0.0 };
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    fn cloth(params: Params, columns: usize, rows: usize) -> World {
+        let mut world = World::new(800.0);
+        world.set_params(params);
+        world.add_cloth(Vec2::new(100.0, 50.0), columns, rows, 4);
+        world
+    }
+
+    #[test]
+    fn default_cloth_needs_nothing_changed() {
+        let report = analyze(&cloth(Params::default(), 12, 8), 1);
+        assert!(report.current.stable(), "{:?}", report.current);
+        assert_eq!(report.recommended.map(|trial| trial.params), Some(Params::default()));
+    }
+
+    #[test]
+    fn recommends_cheapest_stable_params() {
+        // a single iteration holds a small cloth together, but not a big one
+        let params = Params { solver_iterations: 1, ..Params::default() };
+        assert!(analyze(&cloth(params, 12, 8), 1).current.stable());
+        let report = analyze(&cloth(params, 24, 16), 1);
+        assert!(!report.current.stable(), "{:?}", report.current);
+        let recommended = report.recommended.expect("a stable candidate");
+        assert!(recommended.stable());
+        assert!(recommended.params.solver_iterations > 1 || recommended.params.dt < params.dt);
+    }
+}
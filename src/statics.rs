@@ -0,0 +1,368 @@
+//! Statics for pinned trusses: what each link carries with the structure
+//! standing still under its own weight, worked out the way an engineer would
+//! by hand rather than the way the solver gets there.
+//!
+//! Ropes and nets aren't trusses, since they only hang still in the shape
+//! their weight pulls them into, so for them `settle` runs the solver with
+//! the motion damped out until they stop, and `Truss::balance` then finds
+//! what each link carries in the shape they stopped in.
+
+use crate::energy::Energy;
+use crate::world::World;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+/// Most free nodes `Truss::analyze` and `Truss::balance` take on, so analyzing every frame stays cheap.
+pub const MAX_TRUSS_NODES: usize = 100;
+/// Most steps `settle` takes waiting for things to stop.
+pub const SETTLE_STEPS: usize = 20_000;
+/// Fastest a node can be moving, in units a second, with the world counted as still.
+pub const SETTLED_SPEED: f32 = 0.5;
+/// Steps in a row nothing can go faster than `SETTLED_SPEED` for, since
+/// everything starts off slowly each time `settle` stops it.
+const STILL_STEPS: usize = 50;
+/// How much `Truss::balance` favors smaller forces, against how closely they
+/// balance the weights, so it still has an answer where links could share a
+/// load any number of ways.
+const BALANCE_DAMPING: f64 = 1e-6;
+
+/// Why a world couldn't be analyzed as a truss.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NotATruss {
+    /// No link is pinned to a fixed node, so there's nothing to hold it up.
+    Unsupported,
+    /// More than `MAX_TRUSS_NODES` free nodes.
+    TooLarge,
+    /// Some part of it would swing or fold under its weight rather than
+    /// stand, like a node hung on a single link or in a straight line.
+    Mechanism,
+}
+
+impl NotATruss {
+    pub fn describe(self) -> &'static str {
+        match self {
+            NotATruss::Unsupported => "Nothing is pinned, so nothing holds it up",
+            NotATruss::TooLarge => "Too many nodes to analyze",
+            NotATruss::Mechanism => "It would fold or swing under its weight rather than stand",
+        }
+    }
+}
+
+/// The force in every member of a truss standing still under its own weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Truss {
+    /// Each constraint's axial force, by index into the world's constraints:
+    /// positive for tension, pulling its ends together, and negative for
+    /// compression, pushing them apart.
+    pub forces: Vec<f32>,
+}
+
+impl Truss {
+    /// Works out the forces in `world`'s links as bars of equal stiffness,
+    /// with fixed nodes as pins and each free node loaded with its weight,
+    /// by the direct stiffness method on the truss as it stands now. Bars
+    /// share the load in a truss with more than it needs the way they would
+    /// in a real one, and in one with just enough it's the same answer as
+    /// the method of joints. Nodes no link touches are left out.
+    pub fn analyze(world: &World) -> Result<Self, NotATruss> {
+        let (arena, constraints) = (&world.arena, &world.constraints);
+        if !constraints.iter().any(|c| arena[c.a].fixed != arena[c.b].fixed) {
+            return Err(NotATruss::Unsupported);
+        }
+        // each free node in a member is free to move in x and y, by index into the unknowns
+        let mut unknowns = vec![None; arena.len()];
+        let mut count = 0;
+        for constraint in constraints {
+            for i in [constraint.a, constraint.b] {
+                if !arena[i].fixed && unknowns[i].is_none() {
+                    unknowns[i] = Some(2 * count);
+                    count += 1;
+                }
+            }
+        }
+        if count > MAX_TRUSS_NODES {
+            return Err(NotATruss::TooLarge);
+        }
+
+        let n = 2 * count;
+        let mut stiffness = vec![0.0f64; n * n];
+        let mut loads = vec![0.0f64; n];
+        for (node, unknown) in arena.iter().zip(&unknowns) {
+            if let Some(i) = unknown {
+                // y grows downward, so weight is positive
+                loads[i + 1] = node.weight(world.params.gravity) as f64;
+            }
+        }
+        let directions: Vec<_> = constraints.iter().map(|c| c.offset(arena, &world.wrap)).collect();
+        for (constraint, r) in constraints.iter().zip(&directions) {
+            let length = r.length();
+            if length == 0.0 {
+                continue;
+            }
+            let u = [(r.x / length) as f64, (r.y / length) as f64];
+            let k = 1.0 / length as f64;
+            let ends = [(unknowns[constraint.a], -1.0), (unknowns[constraint.b], 1.0)];
+            for (row, row_sign) in ends {
+                for (col, col_sign) in ends {
+                    let (Some(row), Some(col)) = (row, col) else {
+                        continue;
+                    };
+                    for p in 0..2 {
+                        for q in 0..2 {
+                            stiffness[(row + p) * n + col + q] += row_sign * col_sign * k * u[p] * u[q];
+                        }
+                    }
+                }
+            }
+        }
+
+        let moved = solve(stiffness, loads).ok_or(NotATruss::Mechanism)?;
+        let displacement = |i: usize| unknowns[i].map_or([0.0; 2], |j| [moved[j], moved[j + 1]]);
+        let forces = constraints
+            .iter()
+            .zip(&directions)
+            .map(|(constraint, r)| {
+                let length = r.length();
+                if length == 0.0 {
+                    return 0.0;
+                }
+                let (a, b) = (displacement(constraint.a), displacement(constraint.b));
+                let stretch = ((b[0] - a[0]) * r.x as f64 + (b[1] - a[1]) * r.y as f64) / length as f64;
+                (stretch / length as f64) as f32
+            })
+            .collect();
+        Ok(Self { forces })
+    }
+
+    /// Works out the forces in `world`'s links that hold each free node in
+    /// them still against its weight, in the shape the world is in now, which
+    /// only makes sense once it's stopped moving. Unlike `analyze`, this
+    /// takes the shape as given, so it has an answer for a hanging rope or a
+    /// sagging net, which would fold if they were bars but are already hanging
+    /// where their weight takes them. Where the links could share a load more
+    /// than one way it picks the smallest forces that balance it. Nodes
+    /// resting on the ground have it holding them up instead.
+    pub fn balance(world: &World) -> Result<Self, NotATruss> {
+        let (arena, constraints) = (&world.arena, &world.constraints);
+        if !constraints.iter().any(|c| arena[c.a].fixed != arena[c.b].fixed) {
+            return Err(NotATruss::Unsupported);
+        }
+        let grounded = world.ground_y - NODE_RADIUS - 1e-2;
+        let held = |i: usize| arena[i].fixed || arena[i].pos.y >= grounded;
+        // each link's pull on each free node at its ends, for the equations that node balances
+        let mut pulls: Vec<Vec<(usize, Vec2)>> = vec![Vec::new(); arena.len()];
+        for (i, constraint) in constraints.iter().enumerate() {
+            let toward_b = constraint.offset(arena, &world.wrap).normalize_or_zero();
+            pulls[constraint.a].push((i, toward_b));
+            pulls[constraint.b].push((i, -toward_b));
+        }
+        let free = (0..arena.len()).filter(|&i| !held(i) && !pulls[i].is_empty()).count();
+        if free > MAX_TRUSS_NODES {
+            return Err(NotATruss::TooLarge);
+        }
+
+        // the least squares fit of the forces to the weights, with a little damping toward zero
+        let n = constraints.len();
+        let mut normal = vec![0.0f64; n * n];
+        let mut loads = vec![0.0f64; n];
+        for (i, pulls) in pulls.iter().enumerate().filter(|&(i, _)| !held(i)) {
+            let weight = Vec2::new(0.0, arena[i].weight(world.params.gravity));
+            for &(row, pull) in pulls {
+                loads[row] -= pull.dot(weight) as f64;
+                for &(col, other) in pulls {
+                    normal[row * n + col] += pull.dot(other) as f64;
+                }
+            }
+        }
+        for i in 0..n {
+            normal[i * n + i] += BALANCE_DAMPING;
+        }
+        let forces = solve(normal, loads).ok_or(NotATruss::Mechanism)?;
+        Ok(Self { forces: forces.into_iter().map(|force| force as f32).collect() })
+    }
+
+    /// The largest force either way, for scaling colors by.
+    pub fn largest(&self) -> f32 {
+        self.forces.iter().fold(0.0, |largest, force| force.abs().max(largest))
+    }
+}
+
+/// How `settle` went.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Settled {
+    pub steps: usize,
+    /// Whether everything stopped, rather than `steps` running out first.
+    pub still: bool,
+}
+
+/// Steps `world` until no node has moved faster than `SETTLED_SPEED` for
+/// `STILL_STEPS` steps, or for `max_steps`, stopping every node each time their kinetic energy passes a
+/// peak so it comes to rest where it would have swung through rather than
+/// swinging on. Everything is left stopped, so it stays put when stepped on.
+pub fn settle(world: &mut World, max_steps: usize) -> Settled {
+    let dt = world.params.dt;
+    let (mut last_kinetic, mut still_for) = (0.0, 0);
+    let mut settled = Settled { steps: max_steps, still: false };
+    for steps in 1..=max_steps {
+        world.step(dt);
+        let fastest = world.arena.iter().filter(|node| !node.fixed).fold(0.0, |fastest: f32, node| {
+            fastest.max(node.vel.length())
+        });
+        still_for = if fastest < SETTLED_SPEED { still_for + 1 } else { 0 };
+        if still_for == STILL_STEPS {
+            settled = Settled { steps, still: true };
+            break;
+        }
+        let kinetic = Energy::of(world).kinetic;
+        if kinetic < last_kinetic {
+            world.arena.iter_mut().for_each(|node| node.vel = Vec2::ZERO);
+            last_kinetic = 0.0;
+        } else {
+            last_kinetic = kinetic;
+        }
+    }
+    world.arena.iter_mut().for_each(|node| node.vel = Vec2::ZERO);
+    settled
+}
+
+/// Solves `a x = b` for the square `a`, stored row by row, by Gaussian
+/// elimination with partial pivoting. `None` if `a` is singular.
+fn solve(mut a: Vec<f64>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    let scale = (0..n).map(|i| a[i * n + i].abs()).fold(0.0, f64::max);
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i * n + col].abs().total_cmp(&a[j * n + col].abs()))?;
+        if a[pivot * n + col].abs() <= 1e-9 * scale {
+            return None;
+        }
+        for k in 0..n {
+            a.swap(col * n + k, pivot * n + k);
+        }
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in col..n {
+                a[row * n + k] -= factor * a[col * n + k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[row * n + row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::node::Node;
+    use crate::TARGET_DIST;
+
+    /// A node of mass 2 on two links from pins either side, at `apex` from halfway between them.
+    fn two_bar(apex: Vec2) -> World {
+        let mut world = World::new(1000.0);
+        for pos in [Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0)] {
+            world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(pos, 1.0) });
+        }
+        world.arena.push(Node::with_pos_and_mass(apex, 2.0));
+        for a in [0, 1] {
+            let rest_length = world.arena[a].pos.distance(apex);
+            world.constraints.push(Constraint { a, b: 2, rest_length, break_threshold: 2.0 * rest_length });
+        }
+        world
+    }
+
+    #[test]
+    fn hanging_bars_pull_and_standing_ones_push() {
+        // at 45 degrees, each bar carries the weight over root two
+        let expected = 2.0 * World::new(1000.0).params.gravity / 2f32.sqrt();
+        let hanging = Truss::analyze(&two_bar(Vec2::new(0.0, 10.0))).unwrap();
+        let standing = Truss::analyze(&two_bar(Vec2::new(0.0, -10.0))).unwrap();
+        for force in hanging.forces.iter().copied() {
+            assert!((force - expected).abs() < 1e-3 * expected, "{}", force);
+        }
+        for force in standing.forces.iter().copied() {
+            assert!((force + expected).abs() < 1e-3 * expected, "{}", force);
+        }
+        assert!((hanging.largest() - expected).abs() < 1e-3 * expected);
+
+        // two bars in a straight line can't hold a weight up, and neither can nothing
+        assert_eq!(Truss::analyze(&two_bar(Vec2::ZERO)), Err(NotATruss::Mechanism));
+        let mut loose = two_bar(Vec2::new(0.0, 10.0));
+        loose.arena[0].fixed = false;
+        loose.arena[1].fixed = false;
+        assert_eq!(Truss::analyze(&loose), Err(NotATruss::Unsupported));
+
+        // every joint of a truss bridge balances, and the top chord is squeezed
+        let world = crate::scene::truss(1280.0, 720.0);
+        let truss = Truss::analyze(&world).unwrap();
+        let weight = |node: &Node| Vec2::new(0.0, node.weight(world.params.gravity));
+        let mut net: Vec<Vec2> = world.arena.iter().map(weight).collect();
+        for (constraint, &force) in world.constraints.iter().zip(&truss.forces) {
+            let pull = constraint.offset(&world.arena, &world.wrap).normalize() * force;
+            net[constraint.a] += pull;
+            net[constraint.b] -= pull;
+            let (a, b) = (world.arena[constraint.a].pos, world.arena[constraint.b].pos);
+            if a.y == b.y && a.y < world.arena[0].pos.y {
+                assert!(force < 0.0, "{} to {}: {}", a, b, force);
+            }
+        }
+        for (node, net) in world.arena.iter().zip(net).filter(|(node, _)| !node.fixed) {
+            assert!(net.length() < 1e-2, "{}: {}", node.pos, net);
+        }
+    }
+
+    #[test]
+    fn settled_ropes_carry_the_weight_hung_on_them() {
+        // balancing a truss that stands gives the same forces as analyzing it
+        let world = two_bar(Vec2::new(0.0, 10.0));
+        let (analyzed, balanced) = (Truss::analyze(&world).unwrap(), Truss::balance(&world).unwrap());
+        for (a, b) in analyzed.forces.iter().zip(&balanced.forces) {
+            assert!((a - b).abs() < 1e-3 * a, "{} {}", a, b);
+        }
+
+        // a rope laid out level between two pins sags into a curve and stops
+        let mut world = World::new(10_000.0);
+        world.add_rope(Vec2::ZERO, 11);
+        for (i, node) in world.arena.iter_mut().enumerate() {
+            node.pos = Vec2::new(i as f32 * TARGET_DIST * 0.9, 0.0);
+            node.last_pos = node.pos;
+        }
+        world.arena[10].fixed = true;
+        let settled = settle(&mut world, SETTLE_STEPS);
+        assert!(settled.still, "{:?}", settled);
+        assert!(world.arena[5].pos.y > TARGET_DIST, "{}", world.arena[5].pos);
+        assert!(world.arena.iter().all(|node| node.vel == Vec2::ZERO));
+
+        // every link pulls, hardest at the pins, and every node balances
+        let forces = Truss::balance(&world).unwrap().forces;
+        assert!(forces.iter().all(|&force| force > 0.0), "{:?}", forces);
+        assert!(forces[0] > forces[4] && forces[9] > forces[5], "{:?}", forces);
+        let gravity = world.params.gravity;
+        let mut net: Vec<Vec2> = world.arena.iter().map(|node| Vec2::new(0.0, node.weight(gravity))).collect();
+        for (constraint, &force) in world.constraints.iter().zip(&forces) {
+            let pull = constraint.offset(&world.arena, &world.wrap).normalize() * force;
+            net[constraint.a] += pull;
+            net[constraint.b] -= pull;
+        }
+        // to within what the solver leaves of each node's weight when it stops
+        for net in net[1..10].iter() {
+            assert!(net.length() < 0.05 * gravity, "{}", net);
+        }
+
+        // a rope hanging straight down would fold as bars, but its top link carries all of it below
+        let mut hanging = World::new(10_000.0);
+        hanging.add_rope(Vec2::ZERO, 6);
+        assert_eq!(Truss::analyze(&hanging), Err(NotATruss::Mechanism));
+        let forces = Truss::balance(&hanging).unwrap().forces;
+        assert!((forces[0] - 5.0 * gravity).abs() < 1e-3 * gravity, "{:?}", forces);
+        assert!((forces[4] - gravity).abs() < 1e-3 * gravity, "{:?}", forces);
+    }
+}
@@ -0,0 +1,72 @@
+//! Streaming the world to WebSocket clients, for external visualizers and notebooks.
+
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tracing::{info, warn};
+use tungstenite::Message;
+
+/// Frames a client can fall behind by before new ones are dropped for it.
+const CLIENT_BACKLOG: usize = 4;
+
+/// Accepts WebSocket connections in the background and sends every client each
+/// broadcast message. Each client gets its own thread, so a slow one only
+/// misses frames rather than stalling the simulation.
+pub struct StateServer {
+    clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+}
+
+impl StateServer {
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        info!("streaming state on ws://{}", listener.local_addr()?);
+
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let (sender, receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+                        accepted.lock().unwrap().push(sender);
+                        thread::spawn(move || serve(stream, receiver));
+                    }
+                    Err(e) => warn!("failed to accept a WebSocket client: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    /// Queues `message` for every connected client, forgetting ones that have gone.
+    pub fn broadcast(&self, message: String) {
+        let message: Arc<str> = message.into();
+        self.clients.lock().unwrap().retain(|client| {
+            !matches!(client.try_send(Arc::clone(&message)), Err(TrySendError::Disconnected(_)))
+        });
+    }
+
+    pub fn has_clients(&self) -> bool {
+        !self.clients.lock().unwrap().is_empty()
+    }
+}
+
+fn serve(stream: TcpStream, messages: mpsc::Receiver<Arc<str>>) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+
+    // returning drops the receiver, which `broadcast` sees as a disconnect
+    for message in messages {
+        if socket.send(Message::text(message.to_string())).is_err() {
+            return;
+        }
+    }
+}
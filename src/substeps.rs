@@ -0,0 +1,68 @@
+//! Splitting steps into substeps when nodes move fast.
+
+use crate::world::World;
+use crate::TARGET_DIST;
+
+/// Furthest a node may move in one substep, as a fraction of `TARGET_DIST`.
+pub const MAX_TRAVEL: f32 = 0.25;
+/// Most substeps a step is split into.
+pub const MAX_SUBSTEPS: usize = 16;
+
+/// Picks how many substeps to split each step into, like a CFL condition: as
+/// soon as the fastest node would move more than `MAX_TRAVEL` rest lengths in a
+/// substep there are more of them, and once things calm down they drop back one
+/// step at a time, so a single fast frame doesn't slow the next ones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Substeps {
+    count: usize,
+}
+
+impl Default for Substeps {
+    fn default() -> Self {
+        Self { count: 1 }
+    }
+}
+
+impl Substeps {
+    /// Substeps to split the next step into.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Updates the count from the nodes' speeds before a step of `dt`.
+    pub fn adapt(&mut self, world: &World, dt: f32) {
+        let fastest = world
+            .arena
+            .iter()
+            .filter(|node| !node.fixed)
+            .map(|node| node.vel.length())
+            .fold(0.0, f32::max);
+        let travel = fastest * dt / (MAX_TRAVEL * TARGET_DIST);
+        let needed = (travel.ceil() as usize).clamp(1, MAX_SUBSTEPS);
+
+        self.count = if needed >= self.count { needed } else { self.count - 1 };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    #[test]
+    fn grows_at_once_and_shrinks_gradually() {
+        let mut world = World::new(500.0);
+        world.arena.push(Node::with_pos_and_mass(Vec2::ZERO, 1.0));
+        let mut substeps = Substeps::default();
+
+        // 1.5 rest lengths in one step is six substeps' worth
+        world.arena[0].vel = Vec2::new(1.5 * TARGET_DIST, 0.0);
+        substeps.adapt(&world, 1.0);
+        assert_eq!(substeps.count(), 6);
+
+        world.arena[0].vel = Vec2::ZERO;
+        substeps.adapt(&world, 1.0);
+        assert_eq!(substeps.count(), 5);
+    }
+}
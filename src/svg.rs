@@ -0,0 +1,249 @@
+//! Vector export of the current frame, drawn the same way `MacroquadRenderer` draws it.
+
+use crate::palette::Palette;
+use crate::renderer::{CLOTH_ALPHA, GLOW_LAYERS, GLOW_START, ROPE_WIDTH, TEXTURED_ROPE_WIDTH};
+use clothsim::layer::Layer;
+use clothsim::shading::{self, ShadedFace};
+use clothsim::{Ball, Mesh, Node, SimRenderer, NODE_RADIUS, TARGET_DIST};
+use egui_macroquad::macroquad::prelude::{Color, Rect, Vec2};
+use std::fmt::Write as _;
+
+/// Builds an SVG document whose viewBox is `view`, in world coordinates.
+///
+/// Rope shaders can't be reproduced, so ropes use the flat or heatmap color,
+/// and glow is drawn at the peak of its pulse. Meshes have no texture, and
+/// each triangle is filled with the average of its corners' colors.
+pub struct SvgRenderer<'a> {
+    pub palette: &'a Palette,
+    pub show_heatmap: bool,
+    pub show_glow: bool,
+    /// Draws ropes as wide quads, as they are with a rope texture selected, in place of lines.
+    pub textured_ropes: bool,
+    view: Rect,
+    out: String,
+    /// Glow and rope lines since the last node, kept back like
+    /// `MacroquadRenderer`'s batch so layering matches the screen.
+    glow: String,
+    ropes: String,
+    /// Rope quads since the last node, when `textured_ropes` is on.
+    rope_quads: Mesh,
+    /// Faces of cloth waiting for their depth to be drawn at.
+    cloth: Vec<ShadedFace>,
+    /// Whether a group scaling a depth other than the screen's is open.
+    in_depth: bool,
+}
+
+impl<'a> SvgRenderer<'a> {
+    pub fn new(palette: &'a Palette, view: Rect) -> Self {
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            view.x, view.y, view.w, view.h, view.w, view.h
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            view.x, view.y, view.w, view.h, fill(palette.background)
+        )
+        .unwrap();
+
+        Self {
+            palette,
+            show_heatmap: false,
+            show_glow: false,
+            textured_ropes: false,
+            view,
+            out,
+            glow: String::new(),
+            ropes: String::new(),
+            rope_quads: Mesh::new(true),
+            cloth: Vec::new(),
+            in_depth: false,
+        }
+    }
+
+    /// Fills in each face of cloth in the rope color, lit by its shading, under whatever's drawn next at its depth.
+    pub fn draw_cloth(&mut self, faces: &[ShadedFace]) {
+        self.cloth.extend_from_slice(faces);
+    }
+
+    fn flush(&mut self) {
+        self.out.push_str(&self.glow);
+        self.out.push_str(&self.ropes);
+        self.glow.clear();
+        self.ropes.clear();
+        if !self.rope_quads.is_empty() {
+            let mut quads = std::mem::take(&mut self.rope_quads);
+            self.draw_mesh(&quads);
+            quads.clear();
+            self.rope_quads = quads;
+        }
+    }
+
+    /// Draws the waiting faces of cloth at `depth`, or all of them.
+    fn fill_cloth(&mut self, depth: Option<f32>) {
+        self.flush();
+        let rope = self.palette.rope;
+        let (faces, rest): (Vec<_>, _) =
+            self.cloth.drain(..).partition(|face| depth.is_none_or(|depth| face.depth == depth));
+        self.cloth = rest;
+        if !faces.is_empty() {
+            self.draw_mesh(&shading::mesh(&faces, [rope.r, rope.g, rope.b, CLOTH_ALPHA]));
+        }
+    }
+
+    pub fn finish(mut self) -> String {
+        self.set_depth(0.0);
+        self.fill_cloth(None);
+        self.out.push_str("</svg>\n");
+        self.out
+    }
+}
+
+impl SimRenderer for SvgRenderer<'_> {
+    /// Groups what's drawn at `depth` under the same scaling about the middle of the view `MacroquadRenderer` uses.
+    fn set_depth(&mut self, depth: f32) {
+        self.flush();
+        if self.in_depth {
+            self.out.push_str("</g>\n");
+            self.in_depth = false;
+        }
+        let scale = Layer { depth, ..Layer::default() }.scale();
+        if scale != 1.0 {
+            let center = self.view.center();
+            writeln!(
+                self.out,
+                r#"<g transform="translate({} {}) scale({}) translate({} {})">"#,
+                center.x, center.y, scale, -center.x, -center.y
+            )
+            .unwrap();
+            self.in_depth = true;
+        }
+        self.fill_cloth(Some(depth));
+    }
+
+    fn draw_ground(&mut self, y: f32) {
+        let (left, right) = (self.view.left(), self.view.right());
+        line(&mut self.out, Vec2::new(left, y), Vec2::new(right, y), ROPE_WIDTH, self.palette.ground);
+    }
+
+    fn draw_segment(&mut self, a: Vec2, b: Vec2, strain: f32) {
+        if self.show_glow && strain > GLOW_START {
+            let intensity = (strain - GLOW_START) / (1.0 - GLOW_START);
+            let color = self.palette.heat(1.0);
+            for layer in 1..=GLOW_LAYERS {
+                let width = ROPE_WIDTH * (1.0 + layer as f32 * 1.5);
+                let alpha = (0.15 + 0.25 * intensity) / layer as f32;
+                line(&mut self.glow, a, b, width, Palette::with_alpha(color, alpha));
+            }
+        }
+
+        let color = if self.show_heatmap { self.palette.heat(strain) } else { self.palette.rope };
+        if self.textured_ropes {
+            let color = [color.r, color.g, color.b, color.a];
+            self.rope_quads.push_segment(a, b, TARGET_DIST, TEXTURED_ROPE_WIDTH, 0.0, strain, color);
+        } else {
+            line(&mut self.ropes, a, b, ROPE_WIDTH, color);
+        }
+    }
+
+    fn draw_group_link(&mut self, [r, g, b]: [u8; 3], from: Vec2, to: Vec2) {
+        self.flush();
+
+        line(&mut self.out, from, to, ROPE_WIDTH, Color::from_rgba(r, g, b, 255));
+    }
+
+    fn draw_node(&mut self, node: &Node) {
+        self.flush();
+
+        let (x, y) = (node.pos.x, node.pos.y);
+        if node.fixed {
+            let color = self.palette.fixed_node;
+            writeln!(self.out, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, x, y, NODE_RADIUS, fill(color)).unwrap();
+            writeln!(
+                self.out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" {} stroke-width="2"/>"#,
+                x,
+                y,
+                NODE_RADIUS * 1.6,
+                stroke(color)
+            )
+            .unwrap();
+        } else {
+            let color = self.palette.node;
+            writeln!(self.out, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, x, y, NODE_RADIUS, fill(color)).unwrap();
+        }
+    }
+
+    fn draw_mesh(&mut self, mesh: &Mesh) {
+        self.flush();
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| mesh.vertices[triangle[k] as usize]);
+            // zero-width strips, such as the fade along an unfeathered rope's edges, cover nothing
+            if (b.pos - a.pos).perp_dot(c.pos - a.pos) == 0.0 {
+                continue;
+            }
+            let channel = |k: usize| (a.color[k] + b.color[k] + c.color[k]) / 3.0;
+            let color = Color::new(channel(0), channel(1), channel(2), channel(3));
+            writeln!(
+                self.out,
+                r#"<polygon points="{},{} {},{} {},{}" {}/>"#,
+                a.pos.x,
+                a.pos.y,
+                b.pos.x,
+                b.pos.y,
+                c.pos.x,
+                c.pos.y,
+                fill(color)
+            )
+            .unwrap();
+        }
+    }
+
+    fn draw_ball(&mut self, ball: &Ball, node: &Node) {
+        self.flush();
+
+        let (x, y, r) = (node.pos.x, node.pos.y, ball.radius);
+        writeln!(
+            self.out,
+            r#"<circle cx="{}" cy="{}" r="{}" {} {} stroke-width="2"/>"#,
+            x,
+            y,
+            r,
+            fill(self.palette.node),
+            stroke(self.palette.rope)
+        )
+        .unwrap();
+    }
+}
+
+fn line(out: &mut String, a: Vec2, b: Vec2, width: f32, color: Color) {
+    writeln!(
+        out,
+        r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-width="{}"/>"#,
+        a.x,
+        a.y,
+        b.x,
+        b.y,
+        stroke(color),
+        width
+    )
+    .unwrap();
+}
+
+/// `#rrggbb` for a color, ignoring alpha.
+pub fn hex(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn fill(color: Color) -> String {
+    format!(r#"fill="{}" fill-opacity="{}""#, hex(color), color.a)
+}
+
+fn stroke(color: Color) -> String {
+    format!(r#"stroke="{}" stroke-opacity="{}""#, hex(color), color.a)
+}
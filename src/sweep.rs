@@ -0,0 +1,131 @@
+use crate::cli::Cli;
+use clothsim::sim_world::Param;
+use clothsim::{Params, Rng, SimEvent, World};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// How one run of a sweep went.
+struct Summary {
+    /// Steps run, fewer than asked for if the run diverged.
+    steps: usize,
+    diverged: bool,
+    broken: usize,
+    /// Largest stretch of any constraint past its rest length, as a fraction of it.
+    max_strain: f32,
+    /// Average stretch over the constraints left at the end.
+    final_mean_strain: f32,
+    max_speed: f32,
+}
+
+/// Runs the starting scene once for every combination of `--sweep-iters`,
+/// `--sweep-rigidity`, and `--sweep-dt` (axes left out keep the configured
+/// value), writing one CSV row of stability and strain metrics per run to
+/// `--out` or stdout. Returns the process exit code.
+pub fn run(cli: &Cli) -> i32 {
+    let base = match cli.params() {
+        Ok(params) => params,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let iterations = cli.sweep_iters.clone().unwrap_or_else(|| vec![base.solver_iterations]);
+    let rigidities = cli.sweep_rigidity.clone().unwrap_or_else(|| vec![base.rigidity]);
+    let dts = cli.sweep_dt.clone().unwrap_or_else(|| vec![base.dt]);
+
+    let mut runs = Vec::new();
+    for &solver_iterations in iterations.iter() {
+        for &rigidity in rigidities.iter() {
+            for &dt in dts.iter() {
+                runs.push(Params { solver_iterations, rigidity, dt, ..base });
+            }
+        }
+    }
+    // checked before the output is created, so a bad value leaves no half-written table
+    if let Some(e) = runs.iter().find_map(|params| Param::check(params).err()) {
+        eprintln!("can't sweep over that: {}", e);
+        return 1;
+    }
+
+    let out: Box<dyn Write> = match &cli.out {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("failed to create {}: {}", path.display(), e);
+                return 1;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    match write_summaries(cli, &runs, out) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn write_summaries(cli: &Cli, runs: &[Params], mut out: impl Write) -> Result<(), String> {
+    let write_error = |e: io::Error| format!("failed to write output: {}", e);
+    writeln!(out, "iterations,rigidity,dt,steps,diverged,broken,max_strain,final_mean_strain,max_speed")
+        .map_err(write_error)?;
+    for params in runs {
+        let mut world = cli
+            .scene_source()
+            .build(cli.width as f32, cli.height as f32, *params)
+            .map_err(|e| e.to_string())?;
+        world.rng = Rng::new(cli.seed);
+
+        let summary = simulate(world, cli.steps);
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{}",
+            params.solver_iterations,
+            params.rigidity,
+            params.dt,
+            summary.steps,
+            summary.diverged as u8,
+            summary.broken,
+            summary.max_strain,
+            summary.final_mean_strain,
+            summary.max_speed
+        )
+        .map_err(write_error)?;
+    }
+    out.flush().map_err(write_error)
+}
+
+/// Steps `world` up to `steps` times, stopping early if any node stops being finite.
+fn simulate(mut world: World, steps: usize) -> Summary {
+    let mut summary = Summary {
+        steps: 0,
+        diverged: false,
+        broken: 0,
+        max_strain: 0.0,
+        final_mean_strain: 0.0,
+        max_speed: 0.0,
+    };
+
+    for _ in 0..steps {
+        let events = world.step(world.params.dt);
+        summary.steps += 1;
+        summary.broken += events.iter().filter(|e| matches!(e, SimEvent::ConstraintBroken(_))).count();
+
+        if world.arena.iter().any(|node| !node.pos.is_finite() || !node.vel.is_finite()) {
+            summary.diverged = true;
+            break;
+        }
+        let speed = world.arena.iter().map(|node| node.vel.length()).fold(0.0, f32::max);
+        summary.max_speed = summary.max_speed.max(speed);
+        let strain = world.constraints.iter().map(|c| c.strain(&world.arena, &world.wrap)).fold(0.0, f32::max);
+        summary.max_strain = summary.max_strain.max(strain);
+    }
+
+    if !summary.diverged && !world.constraints.is_empty() {
+        let total: f32 = world.constraints.iter().map(|c| c.strain(&world.arena, &world.wrap)).sum();
+        summary.final_mean_strain = total / world.constraints.len() as f32;
+    }
+    summary
+}
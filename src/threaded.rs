@@ -0,0 +1,129 @@
+//! Stepping a world on a worker thread.
+
+use crate::event::SimEvent;
+use crate::world::World;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+struct Shared<I> {
+    input: I,
+    previous: World,
+    latest: World,
+    latest_at: Instant,
+    steps: u64,
+    events: Vec<SimEvent>,
+    paused: bool,
+    running: bool,
+}
+
+/// Steps a world at a fixed rate on its own thread, keeping the last two states
+/// so a renderer can draw between them instead of waiting on the solver.
+pub struct SimThread<I> {
+    shared: Arc<Mutex<Shared<I>>>,
+    period: Duration,
+    handle: Option<JoinHandle<World>>,
+}
+
+impl<I: Clone + Send + 'static> SimThread<I> {
+    /// Starts stepping `world` `rate` times a second, calling `step` with the latest input.
+    pub fn spawn(
+        world: World,
+        input: I,
+        rate: f32,
+        mut step: impl FnMut(&mut World, &I) + Send + 'static,
+    ) -> Self {
+        let period = Duration::from_secs_f32(1.0 / rate);
+        let shared = Arc::new(Mutex::new(Shared {
+            input,
+            previous: world.clone(),
+            latest: world.clone(),
+            latest_at: Instant::now(),
+            steps: 0,
+            events: Vec::new(),
+            paused: false,
+            running: true,
+        }));
+
+        let worker_shared = Arc::clone(&shared);
+        let handle = thread::spawn(move || {
+            let mut world = world;
+            let mut next_step = Instant::now();
+            loop {
+                let input = {
+                    let shared = worker_shared.lock().unwrap();
+                    if !shared.running {
+                        break;
+                    }
+                    (!shared.paused).then(|| shared.input.clone())
+                };
+
+                if let Some(input) = input {
+                    step(&mut world, &input);
+
+                    let mut guard = worker_shared.lock().unwrap();
+                    let shared = &mut *guard;
+                    shared.previous = std::mem::replace(&mut shared.latest, world.clone());
+                    shared.latest_at = Instant::now();
+                    shared.steps += 1;
+                    shared.events.append(&mut world.events);
+                }
+
+                // after a stall, carry on from now rather than rushing to catch up
+                next_step = (next_step + period).max(Instant::now());
+                thread::sleep(next_step - Instant::now());
+            }
+            world
+        });
+
+        Self {
+            shared,
+            period,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn set_input(&self, input: I) {
+        self.shared.lock().unwrap().input = input;
+    }
+
+    /// Stops or resumes stepping, keeping the thread alive.
+    pub fn set_paused(&self, paused: bool) {
+        self.shared.lock().unwrap().paused = paused;
+    }
+
+    /// Steps taken since the thread started.
+    pub fn steps(&self) -> u64 {
+        self.shared.lock().unwrap().steps
+    }
+
+    /// Takes the events queued by every step since the last drain.
+    pub fn drain_events(&self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.shared.lock().unwrap().events)
+    }
+
+    /// The latest state with node positions blended back toward the one before,
+    /// by how far into the next step the clock is.
+    pub fn interpolated(&self) -> World {
+        let shared = self.shared.lock().unwrap();
+        let alpha = (shared.latest_at.elapsed().as_secs_f32() / self.period.as_secs_f32()).min(1.0);
+        shared.latest.blended_from(&shared.previous, alpha)
+    }
+
+    /// Stops the thread after its current step and hands back the world and
+    /// how many steps it took.
+    pub fn stop(mut self) -> (World, u64) {
+        self.shared.lock().unwrap().running = false;
+        let world = self.handle.take().unwrap().join().unwrap();
+        (world, self.steps())
+    }
+}
+
+impl<I> Drop for SimThread<I> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shared.lock().unwrap().running = false;
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,57 @@
+const HIT_STOP_SCALE: f32 = 0.1;
+const HIT_STOP_HOLD_FRAMES: u32 = 6;
+const HIT_STOP_RAMP_FRAMES: u32 = 12;
+
+/// Multiplier applied to the simulation timestep, ticked once per rendered frame.
+pub struct TimeScale {
+    pub hit_stop_enabled: bool,
+    /// Overall speed, which hit-stop slows further.
+    pub speed: f32,
+    hold_frames: u32,
+    ramp_frames: u32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self {
+            hit_stop_enabled: true,
+            speed: 1.0,
+            hold_frames: 0,
+            ramp_frames: 0,
+        }
+    }
+}
+
+impl TimeScale {
+    /// Briefly slows the simulation down, then ramps back to full speed.
+    pub fn hit_stop(&mut self) {
+        if self.hit_stop_enabled {
+            self.hold_frames = HIT_STOP_HOLD_FRAMES;
+            self.ramp_frames = HIT_STOP_RAMP_FRAMES;
+        }
+    }
+
+    /// Cancels any hit-stop in progress.
+    pub fn reset(&mut self) {
+        self.hold_frames = 0;
+        self.ramp_frames = 0;
+    }
+
+    pub fn tick(&mut self) {
+        if self.hold_frames > 0 {
+            self.hold_frames -= 1;
+        } else if self.ramp_frames > 0 {
+            self.ramp_frames -= 1;
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        let hit_stop = if self.hold_frames > 0 {
+            HIT_STOP_SCALE
+        } else {
+            let t = 1.0 - self.ramp_frames as f32 / HIT_STOP_RAMP_FRAMES as f32;
+            HIT_STOP_SCALE + (1.0 - HIT_STOP_SCALE) * t
+        };
+        hit_stop * self.speed
+    }
+}
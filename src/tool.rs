@@ -0,0 +1,78 @@
+/// What a left click does in the editor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EditorTool {
+    /// Drags and throws nodes, and tracks them with shift.
+    Grab,
+    /// Drags the end of a chain, bending the rest of it to follow without stretching.
+    Pose,
+    /// Drops gravity wells, or removes the one under the cursor.
+    Well,
+    /// Drops sinks that swallow nodes, or removes the one under the cursor.
+    Sink,
+    /// Fires balls, dragging from the muzzle to aim and set the speed.
+    Cannon,
+    /// Lays links between nodes, and new nodes to link to.
+    Build,
+    /// Measures between two points or nodes.
+    Measure,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 7] = [
+        EditorTool::Grab,
+        EditorTool::Pose,
+        EditorTool::Well,
+        EditorTool::Sink,
+        EditorTool::Cannon,
+        EditorTool::Build,
+        EditorTool::Measure,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EditorTool::Grab => "Grab",
+            EditorTool::Pose => "Pose",
+            EditorTool::Well => "Gravity well",
+            EditorTool::Sink => "Sink",
+            EditorTool::Cannon => "Cannon",
+            EditorTool::Build => "Build",
+            EditorTool::Measure => "Measure",
+        }
+    }
+
+    /// One-line hint about what the mouse does with this tool, for the HUD, with `{grab}` and `{pin}` for the buttons.
+    pub fn hint(self) -> &'static str {
+        match self {
+            EditorTool::Grab => "{grab} Drag to Move or Throw Nodes, {pin} Click to Pin",
+            EditorTool::Pose => "{grab} Drag a Chain's End to Pose It, {pin} Click to Pin",
+            EditorTool::Well => "{grab} Click to Drop or Remove a Well, {pin} Click to Pin",
+            EditorTool::Sink => "{grab} Click to Drop or Remove a Sink, {pin} Click to Pin",
+            EditorTool::Cannon => "{grab} Drag to Aim and Fire a Ball, {pin} Click to Pin",
+            EditorTool::Build => "{grab} Click Nodes or Empty Space to Lay Links, {pin} Click to Stop",
+            EditorTool::Measure => "{grab} Click Two Points or Nodes to Measure, or a Measurement to Remove It",
+        }
+    }
+}
+
+/// What the cursor does to the running simulation, without a button held.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CursorTool {
+    /// Nothing, so the cursor can move over the scene without disturbing it.
+    Off,
+    /// Blows nodes along with the cursor's movement.
+    Wind,
+    /// Shoves nodes out of the way like a solid disc.
+    Push,
+}
+
+impl CursorTool {
+    pub const ALL: [CursorTool; 3] = [CursorTool::Off, CursorTool::Wind, CursorTool::Push];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CursorTool::Off => "Off",
+            CursorTool::Wind => "Wind",
+            CursorTool::Push => "Push",
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Fingers on a touch screen, told apart by their ids so that several can
+//! do different things at once. The first finger down cuts, and each one
+//! that lands while it's down blows wind or pushes, whichever cursor tool is
+//! picked, so one hand can slice through cloth while the other blows it
+//! about. Two fingers that land together instead pinch to zoom and drag to
+//! pan. A finger keeps what it does until it's lifted.
+
+use crate::cursor::CursorHistory;
+use egui_macroquad::macroquad::prelude::{Touch, TouchPhase, Vec2};
+
+/// How soon after one finger another has to land for the two to pinch. A
+/// finger does nothing for this long, so a pinch doesn't start with a cut.
+pub const PINCH_SECONDS: f64 = 0.12;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    Knife,
+    /// Whatever the cursor tool is.
+    Tool,
+    /// Zooms and pans along with the other finger it landed with.
+    Pinch,
+}
+
+#[derive(Clone, Debug)]
+pub struct Finger {
+    pub id: u64,
+    pub role: Role,
+    /// Where it is, in screen space.
+    pub pos: Vec2,
+    /// Where it was last frame.
+    pub last_pos: Vec2,
+    /// When it landed, by `get_time`.
+    pub landed_at: f64,
+    /// Where it's been, for all but the first finger, whose steps go through
+    /// the main cursor so recordings and the background thread see them.
+    pub cursor: CursorHistory,
+}
+
+/// The fingers down, in the order they landed.
+#[derive(Clone, Debug, Default)]
+pub struct Fingers {
+    pub down: Vec<Finger>,
+}
+
+impl Finger {
+    /// Whether it's been down long enough at `now` to do anything. The wait
+    /// also keeps the jump from wherever the last finger lifted from being a
+    /// gust or a cut.
+    pub fn settled(&self, now: f64) -> bool {
+        now - self.landed_at >= PINCH_SECONDS
+    }
+}
+
+impl Fingers {
+    /// Catches up with this frame's `touches` at time `now`, dropping fingers
+    /// that have lifted and adding ones that have landed. Returns whether the
+    /// first finger is a different one than it was, or there's none now.
+    pub fn update(&mut self, touches: &[Touch], now: f64) -> bool {
+        let first = self.down.first().map(|finger| finger.id);
+        let lifted = |touch: &Touch| matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled);
+        self.down.retain(|finger| touches.iter().any(|touch| touch.id == finger.id && !lifted(touch)));
+        for touch in touches.iter().filter(|touch| !lifted(touch)) {
+            if let Some(finger) = self.down.iter_mut().find(|finger| finger.id == touch.id) {
+                (finger.last_pos, finger.pos) = (finger.pos, touch.position);
+                continue;
+            }
+            // a second finger landing right after the only one pinches with it
+            let role = match self.down.as_mut_slice() {
+                [only] if !only.settled(now) => {
+                    only.role = Role::Pinch;
+                    Role::Pinch
+                }
+                down if down.iter().any(|finger| finger.role == Role::Knife) => Role::Tool,
+                _ => Role::Knife,
+            };
+            let (pos, cursor) = (touch.position, CursorHistory::default());
+            self.down.push(Finger { id: touch.id, role, pos, last_pos: pos, landed_at: now, cursor });
+        }
+        self.down.first().map(|finger| finger.id) != first
+    }
+
+    /// Where the two pinching fingers were last frame and are now, while both are down.
+    pub fn pinch(&self) -> Option<([Vec2; 2], [Vec2; 2])> {
+        match self.down.iter().filter(|finger| finger.role == Role::Pinch).collect::<Vec<_>>()[..] {
+            [a, b] => Some(([a.last_pos, b.last_pos], [a.pos, b.pos])),
+            _ => None,
+        }
+    }
+}
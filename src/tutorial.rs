@@ -0,0 +1,145 @@
+//! The first-run tutorial: three ropes to try each tool on in turn, with a
+//! prompt for each lesson and the rope it's about picked out, moving on as
+//! soon as the world shows it's been done.
+
+use crate::event::SimEvent;
+use crate::scene::GROUND_OFFSET;
+use crate::world::World;
+use crate::TARGET_DIST;
+use glam::Vec2;
+
+const ROPE_POINTS: usize = 6;
+/// How far sideways the left rope's end has to swing to count as blown.
+const BLOWN_SWING: f32 = TARGET_DIST;
+
+/// Something the tutorial teaches, in the order it teaches them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lesson {
+    Cut,
+    Wind,
+    Grab,
+    Pin,
+}
+
+impl Lesson {
+    pub const ALL: [Lesson; 4] = [Lesson::Cut, Lesson::Wind, Lesson::Grab, Lesson::Pin];
+
+    pub fn prompt(self) -> &'static str {
+        match self {
+            Lesson::Cut => "Hold the right mouse button and swipe across the middle rope to cut it",
+            Lesson::Wind => "Sweep the cursor quickly past the left rope to blow it aside",
+            Lesson::Grab => "Press E for the editor, then drag the end of the right rope up",
+            Lesson::Pin => "Right click the end of the right rope to pin it in place",
+        }
+    }
+}
+
+/// The ropes the tutorial is played on, and how far through it the player is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tutorial {
+    /// The left, middle, and right ropes' nodes, each from its pinned top down.
+    pub ropes: [Vec<usize>; 3],
+    /// Lessons done so far, so the one being taught is `Lesson::ALL[done]`.
+    pub done: usize,
+}
+
+impl Tutorial {
+    /// Three ropes hanging side by side across a `width` by `height` view.
+    pub fn new(width: f32, height: f32) -> (World, Self) {
+        let mut world = World::new(height - GROUND_OFFSET);
+        let ropes = [0.3, 0.5, 0.7].map(|across| {
+            let first = world.arena.len();
+            world.add_rope(Vec2::new(width * across, height * 0.15), ROPE_POINTS);
+            (first..world.arena.len()).collect()
+        });
+        (world, Self { ropes, done: 0 })
+    }
+
+    /// The lesson being taught, or `None` once they're all done.
+    pub fn lesson(&self) -> Option<Lesson> {
+        Lesson::ALL.get(self.done).copied()
+    }
+
+    /// The nodes the lesson being taught is about, to pick out on screen.
+    pub fn highlighted(&self) -> &[usize] {
+        let [left, middle, right] = &self.ropes;
+        match self.lesson() {
+            Some(Lesson::Cut) => middle,
+            Some(Lesson::Wind) => left,
+            Some(Lesson::Grab | Lesson::Pin) => &right[right.len() - 1..],
+            None => &[],
+        }
+    }
+
+    /// Takes the world, along with the events from the step just run, or
+    /// none between steps, moving on to the next lesson if this one's done
+    /// and returning whether it did.
+    pub fn record(&mut self, world: &World, events: &[SimEvent]) -> bool {
+        // a sink dropped in the editor may have swallowed some of them
+        if self.ropes.iter().flatten().any(|&i| i >= world.arena.len()) {
+            return false;
+        }
+        let [left, middle, right] = &self.ropes;
+        let (pin, end) = (world.arena[right[0]].pos, world.arena[right[right.len() - 1]]);
+        let done = match self.lesson() {
+            None => return false,
+            Some(Lesson::Cut) => events.iter().any(|event| match *event {
+                SimEvent::ConstraintCut(link) => middle.contains(&link.a) || middle.contains(&link.b),
+                SimEvent::NodeSplit { node, .. } => middle.contains(&node),
+                _ => false,
+            }),
+            Some(Lesson::Wind) => {
+                let (top, bottom) = (world.arena[left[0]].pos, world.arena[left[left.len() - 1]].pos);
+                (bottom.x - top.x).abs() >= BLOWN_SWING
+            }
+            // at least halfway up to where it hangs from
+            Some(Lesson::Grab) => end.pos.y <= pin.y + (ROPE_POINTS - 1) as f32 * TARGET_DIST / 2.0,
+            Some(Lesson::Pin) => end.fixed,
+        };
+        if done {
+            self.done += 1;
+        }
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_lesson_waits_for_its_own_tool() {
+        let (mut world, mut tutorial) = Tutorial::new(1280.0, 720.0);
+        let dt = world.params.dt;
+        let step = |world: &mut World, tutorial: &mut Tutorial| {
+            world.step(dt);
+            let events = world.drain_events();
+            tutorial.record(world, &events)
+        };
+
+        let [left, middle, right] = tutorial.ropes.clone();
+        assert!(!step(&mut world, &mut tutorial));
+        let between = world.arena[middle[2]].pos.lerp(world.arena[middle[3]].pos, 0.5);
+        world.cut(between - Vec2::new(10.0, 0.0), between + Vec2::new(10.0, 0.0));
+        let events = world.drain_events();
+        assert!(tutorial.record(&world, &events));
+        assert_eq!(tutorial.lesson(), Some(Lesson::Wind));
+        assert_eq!(tutorial.highlighted(), &left[..]);
+
+        let end = left[left.len() - 1];
+        assert!(!step(&mut world, &mut tutorial));
+        world.arena[end].pos.x += BLOWN_SWING;
+        assert!(tutorial.record(&world, &[]));
+
+        // between steps, like in the editor
+        let end = right[right.len() - 1];
+        assert!(!tutorial.record(&world, &[]));
+        world.arena[end].pos.y = world.arena[right[0]].pos.y;
+        assert!(tutorial.record(&world, &[]));
+        assert!(!tutorial.record(&world, &[]));
+        world.arena[end].fixed = true;
+        assert!(tutorial.record(&world, &[]));
+        assert_eq!(tutorial.lesson(), None);
+        assert!(tutorial.highlighted().is_empty());
+    }
+}
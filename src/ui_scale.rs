@@ -0,0 +1,32 @@
+//! Sizes for high-DPI displays. Nodes, lines, and text are drawn a fixed
+//! number of window pixels across, which comes out smaller on a display that
+//! packs more pixels into an inch, so a 6 pixel node or 36 point hint is
+//! half the size on a laptop's screen that it is on a desktop monitor. The
+//! scale multiplies them back up: the display's own factor by default, or
+//! one picked in the settings window. egui follows the display by itself.
+
+use egui_macroquad::macroquad::prelude::*;
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 3.0;
+
+/// The display's scale factor, which is 2 on most high-DPI screens and 1 elsewhere.
+pub fn detected() -> f32 {
+    let dpi_scale = unsafe { get_internal_gl().quad_context.dpi_scale() };
+    dpi_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+}
+
+/// `chosen`, or the display's scale factor if it's `None`.
+pub fn resolve(chosen: Option<f32>) -> f32 {
+    chosen.unwrap_or_else(detected)
+}
+
+/// Draws `text` at `size` times `scale`, with its baseline starting at `x`, `y`.
+pub fn draw_scaled_text(text: &str, x: f32, y: f32, size: f32, scale: f32, color: Color) {
+    draw_text(text, x, y, size * scale, color);
+}
+
+/// How big `text` comes out at `size` times `scale`.
+pub fn measure_scaled_text(text: &str, size: f32, scale: f32) -> TextDimensions {
+    measure_text(text, None, (size * scale).round() as u16, 1.0)
+}
@@ -0,0 +1,75 @@
+//! Meters and seconds for the simulation's pixels and time units. The world
+//! works in screen pixels and in the time units `dt` is measured in, which
+//! makes a gravity of 18 or a link 50 long hard to picture. `Units` says how
+//! many pixels make a meter, so scenes and `sim.toml` can give lengths,
+//! positions, and gravity in SI units and have them turned into what the
+//! world runs on. Masses need no converting: a mass of 1 is a kilogram.
+
+use crate::{DT, TARGET_DIST};
+
+/// Pixels to a meter, by default, which makes a default link a meter long.
+pub const PIXELS_PER_METER: f32 = TARGET_DIST;
+/// Time units to a second of real time: the demo runs two steps of `DT` a
+/// frame at 60 frames a second.
+pub const TIME_UNITS_PER_SECOND: f32 = 2.0 * 60.0 * DT;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Units {
+    pub pixels_per_meter: f32,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self { pixels_per_meter: PIXELS_PER_METER }
+    }
+}
+
+impl Units {
+    pub fn pixels(&self, meters: f32) -> f32 {
+        meters * self.pixels_per_meter
+    }
+
+    pub fn meters(&self, pixels: f32) -> f32 {
+        pixels / self.pixels_per_meter
+    }
+
+    /// An acceleration in meters per second squared, like gravity, in pixels per time unit squared.
+    pub fn acceleration(&self, meters_per_second_squared: f32) -> f32 {
+        self.pixels(meters_per_second_squared) / (TIME_UNITS_PER_SECOND * TIME_UNITS_PER_SECOND)
+    }
+
+    /// An acceleration in pixels per time unit squared in meters per second squared.
+    pub fn meters_per_second_squared(&self, acceleration: f32) -> f32 {
+        self.meters(acceleration) * TIME_UNITS_PER_SECOND * TIME_UNITS_PER_SECOND
+    }
+
+    /// A speed in pixels per time unit in meters per second.
+    pub fn meters_per_second(&self, speed: f32) -> f32 {
+        self.meters(speed) * TIME_UNITS_PER_SECOND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+    use glam::Vec2;
+
+    #[test]
+    fn scenes_in_meters_load_in_pixels_and_save_back_in_meters() {
+        let units = Units { pixels_per_meter: 100.0 };
+        assert_eq!(units.pixels(1.5), 150.0);
+        assert!((units.meters_per_second_squared(units.acceleration(9.81)) - 9.81).abs() < 1e-4);
+        // the default gravity is strong: about twelve times earth's at a meter a link
+        let default = Units::default().meters_per_second_squared(crate::G);
+        assert!((default - 116.64).abs() < 1e-2, "{}", default);
+
+        let text = "clothsim-scene 1\nunits 100\nground 4\nnode 1 0.5 2 1\nnode 1 1.5 2 0\nlink 0 1 5 1\n";
+        let world = scene::from_text(text).unwrap();
+        assert_eq!(world.units, Some(units));
+        assert_eq!(world.ground_y, 400.0);
+        assert_eq!((world.arena[1].pos, world.arena[1].mass), (Vec2::new(100.0, 150.0), 2.0));
+        assert_eq!((world.constraints[0].rest_length, world.constraints[0].break_threshold), (100.0, 500.0));
+        assert_eq!(scene::to_text(&world), text);
+    }
+}
@@ -0,0 +1,62 @@
+//! Scatter in how strong and stiff links are. A scene builds every link of a
+//! kind the same, so a cloth pulled evenly tears a whole row at once, on the
+//! same step; real material is weaker in some places than others and fails
+//! there first. `vary` gives each link its own strength, drawn from a seed so
+//! the same scene and seed always come out the same.
+
+use crate::rng::Rng;
+use crate::world::World;
+
+/// Widest spread `vary` takes, so no link's margin before breaking goes to nothing.
+pub const MAX_SPREAD: f32 = 0.9;
+
+/// Scales how far each constraint stretches before it breaks, and each
+/// band's stiffness, by a random factor up to `spread` either side of 1, so
+/// at 0.2 a link that broke at double its rest length breaks somewhere from
+/// 1.8 to 2.2 times it. Rigid links all share the world's rigidity, so it's
+/// only their strength that varies. Nothing changes at 0.
+pub fn vary(world: &mut World, spread: f32, seed: u64) {
+    let spread = spread.clamp(0.0, MAX_SPREAD);
+    if spread == 0.0 {
+        return;
+    }
+    let mut rng = Rng::new(seed);
+    for constraint in world.constraints.iter_mut() {
+        let margin = constraint.break_threshold - constraint.rest_length;
+        constraint.break_threshold = constraint.rest_length + margin * rng.range(1.0 - spread, 1.0 + spread);
+    }
+    for band in world.bands.iter_mut() {
+        band.stiffness *= rng.range(1.0 - spread, 1.0 + spread);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn links_vary_within_the_spread_and_the_same_way_from_the_same_seed() {
+        let mut world = World::new(10_000.0);
+        world.add_cloth(Vec2::ZERO, 10, 10, 3);
+        let even = world.clone();
+        vary(&mut world, 0.0, 1);
+        assert_eq!(format!("{:?}", world.constraints), format!("{:?}", even.constraints));
+
+        let mut again = even.clone();
+        vary(&mut world, 0.2, 1);
+        vary(&mut again, 0.2, 1);
+        assert_eq!(format!("{:?}", world.constraints), format!("{:?}", again.constraints));
+        let mut other_seed = even.clone();
+        vary(&mut other_seed, 0.2, 2);
+        assert_ne!(format!("{:?}", world.constraints), format!("{:?}", other_seed.constraints));
+
+        let margins = world.constraints.iter().zip(even.constraints.iter()).map(|(varied, even)| {
+            (varied.break_threshold - varied.rest_length) / (even.break_threshold - even.rest_length)
+        });
+        let (low, high) = margins.fold((f32::MAX, f32::MIN), |(low, high), m| (low.min(m), high.max(m)));
+        assert!(low >= 0.8 && high <= 1.2, "{} {}", low, high);
+        // spread out across the range, not all bunched together
+        assert!(low < 0.85 && high > 1.15, "{} {}", low, high);
+    }
+}
@@ -0,0 +1,118 @@
+//! The stepping math `World` and the `sim3d` feature's `World3` share,
+//! written once over the vector type: moving nodes on under their forces,
+//! working their velocities back out, projecting links toward their rest
+//! lengths, and deciding when one has stretched too far. Each world keeps its
+//! own node and link types and calls these from their methods, so a change to
+//! the solver lands in both.
+
+use glam::{Vec2, Vec3};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub};
+
+/// What the shared math needs of a vector, which `Vec2` and `Vec3` both have.
+pub trait Vector:
+    Copy
+    + Add<Output = Self>
+    + AddAssign
+    + Sub<Output = Self>
+    + Mul<f32, Output = Self>
+    + MulAssign<f32>
+    + Div<f32, Output = Self>
+    + Neg<Output = Self>
+{
+    fn length(self) -> f32;
+    fn normalize_or_zero(self) -> Self;
+}
+
+impl Vector for Vec2 {
+    fn length(self) -> f32 {
+        Vec2::length(self)
+    }
+
+    fn normalize_or_zero(self) -> Self {
+        Vec2::normalize_or_zero(self)
+    }
+}
+
+impl Vector for Vec3 {
+    fn length(self) -> f32 {
+        Vec3::length(self)
+    }
+
+    fn normalize_or_zero(self) -> Self {
+        Vec3::normalize_or_zero(self)
+    }
+}
+
+/// Moves a free node at `pos` on by `dt`, `force` speeding it up by however
+/// much its `mass` lets it, and keeps where it was in `last_pos`.
+pub fn integrate<V: Vector>(pos: &mut V, last_pos: &mut V, vel: &mut V, force: V, mass: f32, dt: f32) {
+    let acc = force / mass;
+
+    *last_pos = *pos;
+    *vel += acc * dt;
+    *pos += *vel * dt;
+}
+
+/// The velocity that took a node from `last_pos` to `pos` over `dt`, which
+/// takes in everything the solver moved it by as well.
+pub fn velocity<V: Vector>(pos: V, last_pos: V, dt: f32) -> V {
+    (pos - last_pos) / dt
+}
+
+/// How far each end of a link reaching `offset` from `a` to `b` moves toward
+/// `rest_length` apart, by `rigidity` of the way split by the ends' inverse
+/// masses, and only half as hard when squeezed: `a`'s move, then `b`'s. A
+/// fixed end's inverse mass is 0, leaving the whole move to the other, and
+/// neither moves if both are fixed.
+pub fn correction<V: Vector>(offset: V, rest_length: f32, a_weight: f32, b_weight: f32, rigidity: f32) -> (V, V) {
+    if a_weight + b_weight == 0.0 {
+        return (offset * 0.0, offset * 0.0);
+    }
+    let dist = offset.length();
+
+    // each end moves in proportion to its inverse mass, so together
+    // they cover exactly `diff` at full rigidity
+    let norm = offset.normalize_or_zero();
+    let diff = dist - rest_length;
+    let mut offs = norm * diff * rigidity / (a_weight + b_weight);
+
+    if dist < rest_length {
+        offs *= 0.5;
+    }
+
+    (offs * a_weight, -offs * b_weight)
+}
+
+/// Whether a link reaching `offset` from one end to the other has stretched to its `break_threshold`.
+pub fn snaps<V: Vector>(offset: V, break_threshold: f32) -> bool {
+    offset.length() >= break_threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_and_solid_links_move_alike() {
+        let (flat, solid) = (Vec2::new(3.0, 4.0), Vec3::new(3.0, 4.0, 0.0));
+        let (a, b) = correction(flat, 4.0, 1.0, 1.0 / 3.0, 1.0);
+        let (c, d) = correction(solid, 4.0, 1.0, 1.0 / 3.0, 1.0);
+        assert_eq!((a.extend(0.0), b.extend(0.0)), (c, d));
+        // the lighter end covers three quarters of the stretch, and the two cover all of it
+        assert!(a.abs_diff_eq(Vec2::new(0.45, 0.6), 1e-6));
+        assert!(((flat + b - a).length() - 4.0).abs() < 1e-6);
+        // squeezed, they push apart half as hard
+        let (a, b) = correction(flat, 7.0, 1.0, 1.0, 1.0);
+        assert!(a.abs_diff_eq(Vec2::new(-0.3, -0.4), 1e-6) && b == -a);
+        // a fixed end stays put and the free one covers the whole stretch, unless both are fixed
+        let (a, b) = correction(flat, 4.0, 0.0, 1.0, 1.0);
+        assert!(a == Vec2::ZERO && b.abs_diff_eq(Vec2::new(-0.6, -0.8), 1e-6));
+        assert_eq!(correction(flat, 4.0, 0.0, 0.0, 1.0), (Vec2::ZERO, Vec2::ZERO));
+
+        let (mut pos, mut last_pos, mut vel) = (Vec3::ZERO, Vec3::ZERO, Vec3::X);
+        integrate(&mut pos, &mut last_pos, &mut vel, Vec3::new(0.0, 0.0, 4.0), 2.0, 0.5);
+        assert_eq!((pos, vel), (Vec3::new(0.5, 0.0, 0.5), Vec3::new(1.0, 0.0, 1.0)));
+        assert_eq!(velocity(pos, last_pos, 0.5), vel);
+        assert!(snaps(flat, 5.0) && !snaps(solid, 5.5));
+    }
+}
@@ -0,0 +1,81 @@
+//! Recording the screen to a video by piping raw frames to `ffmpeg`.
+
+use crate::error::SimError;
+use egui_macroquad::macroquad::prelude::*;
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+
+/// An `ffmpeg` process encoding frames written to its stdin as H.264.
+pub struct VideoRecorder {
+    pub path: String,
+    /// With its stdin piped, which `wait` closes to end the video.
+    child: Child,
+    /// Size of the frames being fed in; the screen can't change size mid-recording.
+    size: (u16, u16),
+    fps: u32,
+    started: f64,
+    pub frames: u64,
+}
+
+impl VideoRecorder {
+    /// Starts `ffmpeg` writing to `path` at `fps`, scaling the screen by `scale`.
+    pub fn start(path: String, fps: u32, scale: f32) -> Result<Self, SimError> {
+        let image = get_screen_data();
+        let size = (image.width, image.height);
+        // H.264 wants even dimensions
+        let even = |x: u16| ((x as f32 * scale) as u32).max(2) / 2 * 2;
+
+        let error = |source| SimError::Io { action: "start ffmpeg for", path: path.clone(), source };
+        let child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", size.0, size.1), "-r", &fps.to_string(), "-i", "-"])
+            // screen rows come bottom-up
+            .args(["-vf", &format!("vflip,scale={}:{}", even(size.0), even(size.1))])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", &path])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(error)?;
+
+        Ok(Self {
+            path,
+            child,
+            size,
+            fps,
+            started: get_time(),
+            frames: 0,
+        })
+    }
+
+    /// Sends the screen as it is now for however many frames are due, so the
+    /// video plays back in real time even when rendering runs slower than `fps`.
+    pub fn capture(&mut self) -> Result<(), SimError> {
+        let due = ((get_time() - self.started) * self.fps as f64) as u64 + 1;
+        if due <= self.frames {
+            return Ok(());
+        }
+
+        let image = get_screen_data();
+        let stdin = self.child.stdin.as_mut().expect("stdin is piped");
+        let result = if (image.width, image.height) != self.size {
+            Err(io::Error::other("the window was resized"))
+        } else {
+            (self.frames..due).try_for_each(|_| stdin.write_all(&image.bytes))
+        };
+        self.frames = due;
+        result.map_err(|source| SimError::Io { action: "record", path: self.path.clone(), source })
+    }
+
+    /// Closes the pipe and waits for `ffmpeg` to finish writing the file.
+    pub fn finish(self) -> Result<(), SimError> {
+        let Self { path, mut child, .. } = self;
+        let status = child
+            .wait()
+            .map_err(|source| SimError::Io { action: "finish", path: path.clone(), source })?;
+        if status.success() {
+            Ok(())
+        } else {
+            let source = io::Error::other(format!("ffmpeg exited with {}", status));
+            Err(SimError::Io { action: "finish", path, source })
+        }
+    }
+}
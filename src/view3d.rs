@@ -0,0 +1,146 @@
+//! `--3d`: cloth and a rope in `clothsim::world3d`, watched with a camera
+//! orbiting the scene. Dragging with the left button turns it, the scroll
+//! wheel moves it in and out, holding space blows wind across the view, and R
+//! starts over.
+
+use crate::cli::Cli;
+use crate::main_state::STEPS_PER_FRAME;
+use crate::palette::Palette;
+use clothsim::world3d::World3;
+use clothsim::{NODE_RADIUS, TARGET_DIST};
+use egui_macroquad::macroquad::prelude::*;
+use tracing::warn;
+
+const CLOTH_COLUMNS: usize = 12;
+const CLOTH_ROWS: usize = 10;
+const CLOTH_PIN_EVERY: usize = 3;
+const ROPE_POINTS: usize = 12;
+/// Half the width of the square of ground drawn, and the gap between its lines.
+const GROUND_REACH: f32 = 600.0;
+const GROUND_SPACING: f32 = TARGET_DIST;
+/// Vertical field of view, in radians; macroquad takes it as radians whatever its docs say.
+const FIELD_OF_VIEW: f32 = 0.9;
+/// Radians the camera turns per pixel dragged.
+const ORBIT_SPEED: f32 = 0.01;
+/// How far the camera can tip over or under the scene, short of straight down so `up` stays meaningful.
+const MAX_PITCH: f32 = 1.5;
+const ZOOM_STEP: f32 = 1.1;
+const MIN_DISTANCE: f32 = 100.0;
+const MAX_DISTANCE: f32 = 5000.0;
+/// Force on each node while the wind blows.
+const WIND_FORCE: f32 = 15.0;
+
+/// A camera on a sphere around `target`, looking at it.
+struct Orbit {
+    target: Vec3,
+    /// Turn around the vertical, in radians, from looking along +z.
+    yaw: f32,
+    /// Tilt up from level, in radians.
+    pitch: f32,
+    distance: f32,
+    last_mouse_pos: Vec2,
+}
+
+impl Orbit {
+    fn update(&mut self) {
+        let mouse_pos: Vec2 = mouse_position().into();
+        if is_mouse_button_down(MouseButton::Left) {
+            let moved = mouse_pos - self.last_mouse_pos;
+            self.yaw -= moved.x * ORBIT_SPEED;
+            self.pitch = (self.pitch + moved.y * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        self.last_mouse_pos = mouse_pos;
+
+        let (_, scroll) = mouse_wheel();
+        if scroll != 0.0 {
+            let factor = if scroll > 0.0 { 1.0 / ZOOM_STEP } else { ZOOM_STEP };
+            self.distance = (self.distance * factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+        }
+    }
+
+    /// Where the camera is, up being -y since the world's y points down.
+    fn position(&self) -> Vec3 {
+        let level = Vec3::new(self.yaw.sin(), 0.0, -self.yaw.cos()) * self.pitch.cos();
+        self.target + (level - Vec3::Y * self.pitch.sin()) * self.distance
+    }
+
+    fn camera(&self) -> Camera3D {
+        Camera3D {
+            position: self.position(),
+            target: self.target,
+            up: -Vec3::Y,
+            fovy: FIELD_OF_VIEW,
+            ..Default::default()
+        }
+    }
+
+    /// Across the screen to the right, along the ground.
+    fn right(&self) -> Vec3 {
+        Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin())
+    }
+}
+
+/// A sheet of cloth laid out flat and pinned along its back edge, and a rope beside it.
+fn scene(cli: &Cli) -> World3 {
+    let height = cli.height as f32;
+    let mut world = World3::new(height / 2.0);
+    world.params = cli.params().unwrap_or_else(|err| {
+        warn!("{}", err);
+        world.params
+    });
+    let width = (CLOTH_COLUMNS - 1) as f32 * TARGET_DIST;
+    world.add_cloth(Vec3::new(-width / 2.0, -height / 3.0, 0.0), CLOTH_COLUMNS, CLOTH_ROWS, CLOTH_PIN_EVERY);
+    world.add_rope(Vec3::new(width / 2.0 + 2.0 * TARGET_DIST, -height / 3.0, 0.0), ROPE_POINTS);
+    world
+}
+
+pub async fn run(cli: &Cli) {
+    let palette = Palette::default();
+    let mut world = scene(cli);
+    let mut orbit = Orbit {
+        target: Vec3::new(0.0, 0.0, (CLOTH_ROWS as f32 - 1.0) * TARGET_DIST / 2.0),
+        yaw: 0.5,
+        pitch: 0.4,
+        distance: 1500.0,
+        last_mouse_pos: mouse_position().into(),
+    };
+
+    loop {
+        orbit.update();
+        if is_key_pressed(KeyCode::R) {
+            world = scene(cli);
+        }
+        for _ in 0..STEPS_PER_FRAME {
+            if is_key_down(KeyCode::Space) {
+                let wind = orbit.right() * WIND_FORCE;
+                world.arena.iter_mut().for_each(|node| node.force += wind);
+            }
+            let dt = world.params.dt;
+            world.step(dt);
+        }
+
+        clear_background(palette.background);
+        set_camera(&orbit.camera());
+        let ground = Palette::with_alpha(palette.ground, 0.5);
+        let lines = (GROUND_REACH / GROUND_SPACING) as i32;
+        for i in -lines..=lines {
+            let along = i as f32 * GROUND_SPACING;
+            let (near, far) = (-GROUND_REACH + orbit.target.z, GROUND_REACH + orbit.target.z);
+            draw_line_3d(vec3(along, world.ground_y, near), vec3(along, world.ground_y, far), ground);
+            let z = along + orbit.target.z;
+            draw_line_3d(vec3(-GROUND_REACH, world.ground_y, z), vec3(GROUND_REACH, world.ground_y, z), ground);
+        }
+        for link in world.constraints.iter() {
+            draw_line_3d(world.arena[link.a].pos, world.arena[link.b].pos, palette.rope);
+        }
+        for node in world.arena.iter() {
+            let color = if node.fixed { palette.fixed_node } else { palette.node };
+            draw_sphere(node.pos, NODE_RADIUS, None, color);
+        }
+
+        set_default_camera();
+        let help = "Drag to orbit, scroll to zoom, hold space for wind, R to restart";
+        draw_text(help, 10.0, 20.0, 20.0, palette.text);
+        next_frame().await;
+    }
+}
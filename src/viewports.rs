@@ -0,0 +1,103 @@
+use crate::camera::Camera;
+use clothsim::World;
+use egui_macroquad::macroquad::prelude::*;
+
+/// Most extra views at once, stacked down the right of the window.
+pub const MAX_VIEWPORTS: usize = 3;
+/// How much of the window's width the extra views take between them.
+const STRIP_SHARE: f32 = 0.3;
+/// Zoom a new view that follows something starts at.
+const FOLLOW_ZOOM: f32 = 3.0;
+/// How fast a view catches up with what it follows, per second.
+const CATCH_UP_RATE: f32 = 6.0;
+/// World units of empty space kept around the scene in a view fitting all of it.
+const PADDING: f32 = 50.0;
+
+/// What an extra view keeps in the middle of its frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Follow {
+    /// All of it, zoomed to fit.
+    Scene,
+    /// The link closest to breaking.
+    Strained,
+    /// The node open in the Node window.
+    Inspected,
+    /// The cursor, like a magnifying glass.
+    Cursor,
+}
+
+impl Follow {
+    pub const ALL: [Follow; 4] = [Follow::Scene, Follow::Strained, Follow::Inspected, Follow::Cursor];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Follow::Scene => "Whole scene",
+            Follow::Strained => "Most strained link",
+            Follow::Inspected => "Inspected node",
+            Follow::Cursor => "Cursor",
+        }
+    }
+}
+
+/// An extra view of the world with a camera of its own, drawn from the same
+/// state as the main view every frame.
+pub struct Viewport {
+    pub follow: Follow,
+    /// How close it looks, unless it fits the whole scene.
+    pub zoom: f32,
+    pub camera: Camera,
+}
+
+impl Viewport {
+    pub fn new(follow: Follow) -> Self {
+        let mut camera = Camera::default();
+        camera.shake_enabled = false;
+        Self { follow, zoom: FOLLOW_ZOOM, camera }
+    }
+
+    /// Eases the camera, in `area` on screen, toward what it follows, which
+    /// stays put while there's nothing to follow.
+    pub fn update(&mut self, area: Rect, world: &World, inspected: Option<usize>, cursor: Vec2, dt: f32) {
+        self.camera.area = Some(area);
+        let blend = 1.0 - (-CATCH_UP_RATE * dt).exp();
+        let target = match self.follow {
+            Follow::Scene => fit(world, area),
+            Follow::Strained => {
+                let strains = world.strains();
+                let most = (0..strains.len()).max_by(|&a, &b| strains[a].total_cmp(&strains[b]));
+                most.map(|i| {
+                    let constraint = &world.constraints[i];
+                    let middle = world.arena[constraint.a].pos + constraint.offset(&world.arena, &world.wrap) / 2.0;
+                    (middle, self.zoom)
+                })
+            }
+            Follow::Inspected => inspected.and_then(|i| world.arena.get(i)).map(|node| (node.pos, self.zoom)),
+            Follow::Cursor => Some((cursor, self.zoom)),
+        };
+        if let Some((pos, zoom)) = target {
+            self.camera.approach(pos, zoom, blend);
+        }
+    }
+}
+
+/// Where the main view goes with `count` extra views down the right, and where each of those goes, top first.
+pub fn layout(count: usize) -> (Rect, Vec<Rect>) {
+    let (width, height) = (screen_width(), screen_height());
+    if count == 0 {
+        return (Rect::new(0.0, 0.0, width, height), Vec::new());
+    }
+    let strip = width * STRIP_SHARE;
+    let each = height / count as f32;
+    let views = (0..count).map(|i| Rect::new(width - strip, each * i as f32, strip, each)).collect();
+    (Rect::new(0.0, 0.0, width - strip, height), views)
+}
+
+/// The middle of every shown node and the zoom fitting them all into `area`.
+fn fit(world: &World, area: Rect) -> Option<(Vec2, f32)> {
+    let hidden = world.hidden();
+    let mut shown = world.arena.iter().zip(hidden).filter(|(_, hidden)| !hidden).map(|(node, _)| node.pos);
+    let first = shown.next()?;
+    let (min, max) = shown.fold((first, first), |(min, max), pos| (min.min(pos), max.max(pos)));
+    let size = max - min + Vec2::splat(2.0 * PADDING);
+    Some(((min + max) / 2.0, (area.w / size.x).min(area.h / size.y)))
+}
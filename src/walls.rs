@@ -0,0 +1,137 @@
+use crate::friction::Friction;
+use crate::node::Node;
+use crate::{CONTACT_SLOP, NODE_RADIUS};
+use glam::Vec2;
+
+/// Walls along the sides of a rectangle, usually the window, that nodes bounce
+/// off instead of leaving it. Each side can be switched on by itself.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Walls {
+    /// Top left corner.
+    pub min: Vec2,
+    /// Bottom right corner.
+    pub max: Vec2,
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
+    /// Fraction of a node's speed into a wall it bounces back out with, from 0
+    /// for stopping dead to 1 for not losing any. Bouncier nodes use their own.
+    pub restitution: f32,
+}
+
+impl Default for Walls {
+    fn default() -> Self {
+        Self {
+            min: Vec2::ZERO,
+            max: Vec2::ZERO,
+            left: false,
+            right: false,
+            top: false,
+            bottom: false,
+            restitution: 0.8,
+        }
+    }
+}
+
+impl Walls {
+    pub fn any(&self) -> bool {
+        self.left || self.right || self.top || self.bottom
+    }
+
+    /// How far in from each wall the center of something `radius` wide can
+    /// go: `(min, max)`, with infinities on the sides that are switched off.
+    fn limits(&self, radius: f32) -> (Vec2, Vec2) {
+        let low = |on: bool, wall: f32| if on { wall + radius } else { f32::NEG_INFINITY };
+        let high = |on: bool, wall: f32| if on { wall - radius } else { f32::INFINITY };
+        let min = Vec2::new(low(self.left, self.min.x), low(self.top, self.min.y));
+        let max = Vec2::new(high(self.right, self.max.x), high(self.bottom, self.max.y));
+        (min, max)
+    }
+
+    /// Keeps `node` inside the walls that are on, with `friction` against sliding along them.
+    pub fn collide(&self, node: &mut Node, friction: &Friction) {
+        if node.fixed {
+            return;
+        }
+        let push = self.clamp(node.pos, NODE_RADIUS) - node.pos;
+        let depth = push.length();
+        if depth == 0.0 {
+            return;
+        }
+        node.pos += push;
+        node.pos -= friction.grip(node.pos - node.last_pos, push / depth, depth);
+    }
+
+    /// The nearest spot to `pos` inside the walls that are on for something `radius` wide.
+    pub fn clamp(&self, pos: Vec2, radius: f32) -> Vec2 {
+        let (min, max) = self.limits(radius);
+        pos.clamp(min, max)
+    }
+
+    /// How far along the path from `from` to `to` a node would first reach a
+    /// wall that's on, or `None` if it wouldn't or is already past one.
+    pub fn sweep(&self, from: Vec2, to: Vec2) -> Option<f32> {
+        let (min, max) = self.limits(NODE_RADIUS);
+        (0..2)
+            .filter_map(|axis| {
+                let crosses_min = from[axis] >= min[axis] && to[axis] < min[axis];
+                let crosses_max = from[axis] <= max[axis] && to[axis] > max[axis];
+                let wall = if crosses_min { min[axis] } else { max[axis] };
+                (crosses_min || crosses_max).then(|| (wall - from[axis]) / (to[axis] - from[axis]))
+            })
+            .min_by(f32::total_cmp)
+    }
+
+    /// Sends `node` back out if it's against a wall it was heading into, with
+    /// the walls' `restitution` or its own, whichever is more, of the speed it
+    /// hit with. Runs after its velocity is worked out, since `collide` only
+    /// stops it, and takes the velocity it had before the step's corrections
+    /// as `incoming`, since stopping at the wall partway through the step took
+    /// most of that speed away.
+    pub fn bounce(&self, node: &mut Node, incoming: Vec2) {
+        if node.fixed {
+            return;
+        }
+        let (min, max) = self.limits(NODE_RADIUS);
+        let restitution = self.restitution.max(node.restitution);
+        for axis in 0..2 {
+            let against_min = node.pos[axis] <= min[axis] + CONTACT_SLOP && incoming[axis] < 0.0;
+            let against_max = node.pos[axis] >= max[axis] - CONTACT_SLOP && incoming[axis] > 0.0;
+            if against_min || against_max {
+                node.vel[axis] = -incoming[axis] * restitution;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounces_off_walls_that_are_on() {
+        let walls = Walls {
+            max: Vec2::new(100.0, 100.0),
+            left: true,
+            restitution: 0.5,
+            ..Walls::default()
+        };
+
+        // it bounces with the speed it hit with, not the little it had left once stopped
+        let mut node = Node::with_pos_and_mass(Vec2::new(-10.0, 50.0), 1.0);
+        walls.collide(&mut node, &Friction::default());
+        node.vel = Vec2::new(-2.0, 4.0);
+        walls.bounce(&mut node, Vec2::new(-20.0, 4.0));
+        assert_eq!(node.pos, Vec2::new(NODE_RADIUS, 50.0));
+        assert_eq!(node.vel, Vec2::new(10.0, 4.0));
+
+        // the right wall is off, so nothing stops it going out that side
+        let mut node = Node::with_pos_and_mass(Vec2::new(150.0, 50.0), 1.0);
+        node.vel = Vec2::new(20.0, 0.0);
+        walls.collide(&mut node, &Friction::default());
+        walls.bounce(&mut node, Vec2::new(20.0, 0.0));
+        assert_eq!(node.pos, Vec2::new(150.0, 50.0));
+        assert_eq!(node.vel, Vec2::new(20.0, 0.0));
+    }
+}
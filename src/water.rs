@@ -0,0 +1,252 @@
+use crate::constraint::Constraint;
+use crate::geometry;
+use crate::layer::{Layers, WATER};
+use crate::node::Node;
+use crate::spatial_hash::SpatialHash;
+use crate::walls::Walls;
+use crate::wrap::Wrap;
+use crate::NODE_RADIUS;
+use glam::Vec2;
+
+/// Size of a droplet where it touches nodes, links, the ground, and walls.
+pub const DROPLET_RADIUS: f32 = 4.0;
+/// Distance over which droplets push on each other.
+pub const WATER_REACH: f32 = 16.0;
+/// How crowded droplets settle at, measured as the sum over the neighbours in
+/// reach of `(1 - distance / reach)²`.
+pub const REST_DENSITY: f32 = 1.5;
+pub const WATER_STIFFNESS: f32 = 0.08;
+pub const NEAR_STIFFNESS: f32 = 0.2;
+pub const WATER_VISCOSITY: f32 = 0.1;
+pub const DROPLET_MASS: f32 = 0.05;
+/// Droplets in a blob poured from the Weather window.
+pub const POUR_COUNT: usize = 120;
+
+/// One particle of water.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Droplet {
+    pub pos: Vec2,
+    pub last_pos: Vec2,
+    pub vel: Vec2,
+}
+
+/// Water as particles, smoothed-particle hydrodynamics style, stepped
+/// alongside the nodes so the two push on each other through the same
+/// position corrections as everything else.
+///
+/// Droplets keep apart with the double density relaxation from Clavet et al.'s
+/// "Particle-based Viscoelastic Fluid Simulation": each one measures how
+/// crowded it is by its neighbours within `reach`, and the pressure from that
+/// moves them apart, with a second, nearer pressure that stops them clumping.
+/// It works on positions, like the constraints, so it stays stable at the
+/// demo's large timesteps. Droplets collide with nodes and links, and each
+/// side gives way by its inverse mass, so water pours into a net, weighs it
+/// down, and is flung off when it swings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Water {
+    pub droplets: Vec<Droplet>,
+    pub reach: f32,
+    pub rest_density: f32,
+    /// How hard droplets push apart when more crowded than `rest_density`, and
+    /// pull together when less.
+    pub stiffness: f32,
+    /// How hard droplets very close together push apart.
+    pub near_stiffness: f32,
+    /// Fraction of the speed at which droplets close in on each other that they lose.
+    pub viscosity: f32,
+    pub droplet_mass: f32,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self {
+            droplets: Vec::new(),
+            reach: WATER_REACH,
+            rest_density: REST_DENSITY,
+            stiffness: WATER_STIFFNESS,
+            near_stiffness: NEAR_STIFFNESS,
+            viscosity: WATER_VISCOSITY,
+            droplet_mass: DROPLET_MASS,
+        }
+    }
+}
+
+impl Water {
+    /// Adds `count` still droplets in a square blob around `center`, half
+    /// their reach apart.
+    pub fn pour(&mut self, center: Vec2, count: usize) {
+        let side = (count as f32).sqrt().ceil() as usize;
+        let spacing = self.reach / 2.0;
+        let corner = center - Vec2::splat((side - 1) as f32 * spacing / 2.0);
+        for i in 0..count {
+            let pos = corner + Vec2::new((i % side) as f32, (i / side) as f32) * spacing;
+            self.droplets.push(Droplet { pos, last_pos: pos, vel: Vec2::ZERO });
+        }
+    }
+
+    fn hash(&self) -> SpatialHash {
+        SpatialHash::new(self.reach, self.droplets.iter().map(|droplet| droplet.pos))
+    }
+
+    /// Other droplets within reach of droplet `i`, with the direction to each
+    /// and how close it is, from 1 touching to 0 at the edge of reach.
+    fn neighbours(&self, hash: &SpatialHash, i: usize) -> Vec<(usize, Vec2, f32)> {
+        let pos = self.droplets[i].pos;
+        hash.near(pos, self.reach)
+            .filter(|&j| j != i)
+            .filter_map(|j| {
+                let offset = self.droplets[j].pos - pos;
+                let distance = offset.length();
+                (distance < self.reach && distance > 0.0).then(|| (j, offset / distance, 1.0 - distance / self.reach))
+            })
+            .collect()
+    }
+
+    /// Pulls the droplets down, evens out their speeds with viscosity, and
+    /// moves them by their velocity.
+    pub fn integrate(&mut self, gravity: f32, dt: f32) {
+        for droplet in self.droplets.iter_mut() {
+            droplet.vel.y += gravity * dt;
+        }
+
+        let hash = self.hash();
+        for i in 0..self.droplets.len() {
+            for (j, direction, closeness) in self.neighbours(&hash, i) {
+                let closing = (self.droplets[i].vel - self.droplets[j].vel).dot(direction);
+                if j > i && closing > 0.0 {
+                    let impulse = direction * closing * closeness * self.viscosity / 2.0;
+                    self.droplets[i].vel -= impulse;
+                    self.droplets[j].vel += impulse;
+                }
+            }
+        }
+
+        for droplet in self.droplets.iter_mut() {
+            droplet.last_pos = droplet.pos;
+            droplet.pos += droplet.vel * dt;
+        }
+    }
+
+    /// Moves crowded droplets apart and sparse ones together, once a step.
+    pub fn relax(&mut self) {
+        let hash = self.hash();
+        for i in 0..self.droplets.len() {
+            let neighbours = self.neighbours(&hash, i);
+            let density: f32 = neighbours.iter().map(|(_, _, q)| q * q).sum();
+            let near_density: f32 = neighbours.iter().map(|(_, _, q)| q * q * q).sum();
+            let pressure = self.stiffness * (density - self.rest_density);
+            let near_pressure = self.near_stiffness * near_density;
+
+            let mut moved = Vec2::ZERO;
+            for (j, direction, q) in neighbours {
+                let push = direction * (pressure * q + near_pressure * q * q) * self.reach / 2.0;
+                self.droplets[j].pos += push;
+                moved -= push;
+            }
+            self.droplets[i].pos += moved;
+        }
+    }
+
+    /// Pushes droplets and the nodes and links they overlap apart, each by its
+    /// inverse mass, and keeps droplets from slipping through links they moved
+    /// across this step, above `floor` if there is one, and inside `walls`.
+    /// Nodes on layers that don't collide with water, and links with neither
+    /// end on one that does, are left alone.
+    pub fn collide(
+        &mut self,
+        arena: &mut [Node],
+        constraints: &[Constraint],
+        wrap: &Wrap,
+        walls: &Walls,
+        floor: Option<f32>,
+        layers: &Layers,
+    ) {
+        let droplet_weight = 1.0 / self.droplet_mass;
+        let hash = self.hash();
+
+        let touching = NODE_RADIUS + DROPLET_RADIUS;
+        for node in arena.iter_mut().filter(|node| layers.hits(node.layer, WATER)) {
+            for i in hash.near(node.pos, touching) {
+                let droplet = &mut self.droplets[i];
+                let offset = droplet.pos - node.pos;
+                let distance = offset.length();
+                if distance >= touching || distance == 0.0 {
+                    continue;
+                }
+                let push = offset / distance * (touching - distance) / (droplet_weight + node.inverse_mass());
+                droplet.pos += push * droplet_weight;
+                node.add_offs(-push * node.inverse_mass());
+            }
+        }
+
+        for constraint in constraints.iter() {
+            if !layers.hits(arena[constraint.a].layer, WATER) && !layers.hits(arena[constraint.b].layer, WATER) {
+                continue;
+            }
+            let a = arena[constraint.a].pos;
+            let b = a + constraint.offset(arena, wrap);
+            for i in hash.within(a.min(b) - self.reach, a.max(b) + self.reach) {
+                let droplet = &mut self.droplets[i];
+                let t = geometry::nearest_on_segment(droplet.pos, a, b);
+                let offset = droplet.pos - a.lerp(b, t);
+                let distance = offset.length();
+                let (normal, depth) = if geometry::segments_intersect(droplet.last_pos, droplet.pos, a, b) {
+                    // back out the side it came from, however far it went
+                    let side = geometry::orientation(a, b, droplet.last_pos).signum();
+                    ((b - a).perp().normalize_or_zero() * side, DROPLET_RADIUS + distance)
+                } else if distance < DROPLET_RADIUS && distance > 0.0 {
+                    (offset / distance, DROPLET_RADIUS - distance)
+                } else {
+                    continue;
+                };
+
+                let a_weight = (1.0 - t) * arena[constraint.a].inverse_mass();
+                let b_weight = t * arena[constraint.b].inverse_mass();
+                let push = normal * depth / (droplet_weight + (1.0 - t) * a_weight + t * b_weight);
+                droplet.pos += push * droplet_weight;
+                arena[constraint.a].add_offs(-push * a_weight);
+                arena[constraint.b].add_offs(-push * b_weight);
+            }
+        }
+
+        for droplet in self.droplets.iter_mut() {
+            if let Some(floor) = floor {
+                droplet.pos.y = droplet.pos.y.min(floor - DROPLET_RADIUS);
+            }
+            droplet.pos = walls.clamp(droplet.pos, DROPLET_RADIUS);
+        }
+    }
+
+    /// Works out each droplet's velocity from how far it moved over `dt`.
+    pub fn differentiate(&mut self, dt: f32) {
+        for droplet in self.droplets.iter_mut() {
+            droplet.vel = (droplet.pos - droplet.last_pos) / dt;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_and_rope_push_each_other_apart() {
+        let mut arena = vec![
+            Node::with_pos_and_mass(Vec2::new(-20.0, 0.0), DROPLET_MASS),
+            Node::with_pos_and_mass(Vec2::new(30.0, 0.0), DROPLET_MASS),
+        ];
+        let constraints = [Constraint { a: 0, b: 1, rest_length: 50.0, break_threshold: 250.0 }];
+        let mut water = Water::default();
+        water.droplets.push(Droplet { pos: Vec2::new(5.0, -2.0), last_pos: Vec2::new(5.0, -2.0), vel: Vec2::ZERO });
+        water.collide(&mut arena, &constraints, &Wrap::default(), &Walls::default(), None, &Layers::default());
+
+        // the middle of a rope of two droplet masses gives way half as much as the droplet
+        assert!((water.droplets[0].pos.y + 3.333_333).abs() < 1e-4, "{}", water.droplets[0].pos);
+        assert!((arena[0].pos.y - 0.666_667).abs() < 1e-4, "{}", arena[0].pos);
+
+        // a droplet that moved right through the rope is sent back the way it came
+        water.droplets[0] = Droplet { pos: Vec2::new(5.0, 6.0), last_pos: Vec2::new(5.0, -6.0), vel: Vec2::ZERO };
+        water.collide(&mut arena, &constraints, &Wrap::default(), &Walls::default(), None, &Layers::default());
+        assert!(water.droplets[0].pos.y < arena[0].pos.y, "{}", water.droplets[0].pos);
+    }
+}
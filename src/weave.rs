@@ -0,0 +1,185 @@
+//! How a cloth is woven: its warp threads running down, its weft threads
+//! running across, the shear links across each square's diagonals, and the
+//! bend links skipping a node along each thread, each set made rigid, springy,
+//! or left out. Springy warp and rigid weft make a fabric that stretches as it
+//! hangs but not across, and springy shear and bend links let it keep its
+//! shape a little without going stiff. Joining its sides, or its top and
+//! bottom, makes it a tube, and joining both a torus, with no free edges.
+
+/// How one set of a cloth's links is made.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Links {
+    Omitted,
+    /// Constraints, which break like any others.
+    Rigid,
+    /// Bands of this stiffness that push back when squeezed as well as
+    /// pulling when stretched, and don't break.
+    Spring(f32),
+}
+
+/// Every set of a cloth's links, for `World::add_woven_cloth`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Weave {
+    /// Down each column.
+    pub warp: Links,
+    /// Across each row.
+    pub weft: Links,
+    /// Both diagonals of each square, which keep it from folding over into a parallelogram.
+    pub shear: Links,
+    /// Rest length of the shear links, the squares' diagonal unless set.
+    pub shear_rest_length: Option<f32>,
+    /// Length rigid shear links break at, `params.break_threshold` of their rest length unless set.
+    pub shear_break_threshold: Option<f32>,
+    /// From each node to the one two along, down columns and across rows.
+    pub bend: Links,
+    /// Links its right edge to its left with every set of links, as if it
+    /// went on around, making a tube laid flat. Needs at least three columns.
+    pub join_sides: bool,
+    /// Links its bottom edge to its top the same way. Needs at least three rows.
+    pub join_ends: bool,
+}
+
+impl Default for Weave {
+    /// A rigid grid with nothing across its squares or skipping nodes, as `World::add_cloth` weaves.
+    fn default() -> Self {
+        Self {
+            warp: Links::Rigid,
+            weft: Links::Rigid,
+            shear: Links::Omitted,
+            shear_rest_length: None,
+            shear_break_threshold: None,
+            bend: Links::Omitted,
+            join_sides: false,
+            join_ends: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+    use crate::TARGET_DIST;
+    use glam::Vec2;
+
+    #[test]
+    fn springy_warp_stretches_while_rigid_weft_holds() {
+        let hang = |weave: Weave| {
+            let mut world = World::new(10_000.0);
+            world.add_woven_cloth(Vec2::new(100.0, 100.0), 5, 4, 1, &weave);
+            let dt = world.params.dt;
+            for _ in 0..600 {
+                world.step(dt);
+            }
+            let bottom = &world.arena[15..];
+            (bottom[0].pos.y - 100.0, bottom[4].pos.x - bottom[0].pos.x, world)
+        };
+
+        let (rigid_drop, rigid_width, plain) = hang(Weave::default());
+        let mut reference = World::new(10_000.0);
+        reference.add_cloth(Vec2::new(100.0, 100.0), 5, 4, 1);
+        assert_eq!(reference.constraints.len(), 5 * 3 + 4 * 4);
+        assert!(plain.bands.is_empty());
+
+        let stretchy = Weave { warp: Links::Spring(5.0), ..Weave::default() };
+        let (drop, width, world) = hang(stretchy);
+        assert_eq!((world.constraints.len(), world.bands.len()), (4 * 4, 5 * 3));
+        assert!(drop > rigid_drop + 0.25 * TARGET_DIST, "{} {}", drop, rigid_drop);
+        assert!((width - rigid_width).abs() < 0.1 * TARGET_DIST, "{} {}", width, rigid_width);
+
+        // shear and bend links across the grid
+        let braced = Weave { shear: Links::Rigid, bend: Links::Spring(50.0), ..Weave::default() };
+        let mut world = World::new(10_000.0);
+        world.add_woven_cloth(Vec2::ZERO, 5, 4, 1, &braced);
+        assert_eq!(world.constraints.len(), 5 * 3 + 4 * 4 + 2 * 4 * 3);
+        assert_eq!(world.bands.len(), 5 * 2 + 4 * 3);
+        assert!(world.bands.iter().all(|band| band.pushes && band.rest_length == 2.0 * TARGET_DIST));
+    }
+
+    #[test]
+    fn shear_links_keep_squares_square_until_they_break() {
+        // a single square pinned along the top, its bottom shoved sideways in zero gravity
+        let shove = |weave: Weave| {
+            let mut world = World::new(10_000.0);
+            world.params.gravity = 0.0;
+            world.add_woven_cloth(Vec2::ZERO, 2, 2, 1, &weave);
+            world.arena[2].pos.x += 30.0;
+            world.arena[3].pos.x += 30.0;
+            let dt = world.params.dt;
+            for _ in 0..60 {
+                world.step(dt);
+            }
+            world
+        };
+        let lean = |world: &World| world.arena[2].pos.x - world.arena[0].pos.x;
+
+        assert!(lean(&shove(Weave::default())) > 10.0);
+        let braced = shove(Weave { shear: Links::Rigid, ..Weave::default() });
+        assert!(lean(&braced).abs() < 1.0, "{}", lean(&braced));
+        assert_eq!(braced.constraints.len(), 6);
+
+        // shear links with their own rest length and threshold, the grid's left as it was
+        let diagonal = TARGET_DIST * std::f32::consts::SQRT_2;
+        let own = Weave {
+            shear: Links::Rigid,
+            shear_rest_length: Some(diagonal * 0.9),
+            shear_break_threshold: Some(diagonal * 1.05),
+            ..Weave::default()
+        };
+        let mut world = World::new(10_000.0);
+        world.add_woven_cloth(Vec2::ZERO, 2, 2, 1, &own);
+        let (grid, shear) = world.constraints.split_at(4);
+        assert!(shear.iter().all(|c| (c.rest_length, c.break_threshold) == (diagonal * 0.9, diagonal * 1.05)));
+        assert!(grid.iter().all(|c| c.rest_length == TARGET_DIST));
+    }
+
+    #[test]
+    fn joined_edges_close_the_cloth_into_a_tube_or_torus() {
+        let length = |world: &World, i: usize| {
+            let constraint = &world.constraints[i];
+            world.arena[constraint.a].pos.distance(world.arena[constraint.b].pos)
+        };
+
+        // a tube laid flat starts with every link at its rest length, and hangs without tearing
+        let mut tube = World::new(10_000.0);
+        tube.add_woven_cloth(Vec2::ZERO, 6, 4, 1, &Weave { join_sides: true, ..Weave::default() });
+        assert_eq!(tube.constraints.len(), 6 * 3 + 5 * 4 + 4);
+        assert!((0..tube.constraints.len()).all(|i| (length(&tube, i) - TARGET_DIST).abs() < 1e-3));
+        let dt = tube.params.dt;
+        for _ in 0..300 {
+            tube.step(dt);
+        }
+        assert_eq!(tube.constraints.len(), 6 * 3 + 5 * 4 + 4);
+
+        // a torus has no edges: every node has four neighbors, and as many diagonals and bends
+        let braced = Weave {
+            shear: Links::Rigid,
+            bend: Links::Spring(50.0),
+            join_sides: true,
+            join_ends: true,
+            ..Weave::default()
+        };
+        let mut torus = World::new(10_000.0);
+        torus.add_woven_cloth(Vec2::ZERO, 6, 5, 1, &braced);
+        assert_eq!(torus.constraints.len(), 4 * 6 * 5);
+        assert_eq!(torus.bands.len(), 2 * 6 * 5);
+        let mut links = vec![0; torus.arena.len()];
+        for constraint in torus.constraints.iter() {
+            links[constraint.a] += 1;
+            links[constraint.b] += 1;
+        }
+        assert!(links.iter().all(|&links| links == 8));
+
+        // an odd number of rows folds with the links at the folds squashed, which the solver opens back up
+        let mut torus = World::new(10_000.0);
+        torus.add_woven_cloth(Vec2::ZERO, 6, 5, 1, &Weave { join_sides: true, join_ends: true, ..Weave::default() });
+        torus.arena.iter_mut().for_each(|node| node.fixed = false);
+        torus.params.gravity = 0.0;
+        let shortest = |world: &World| (0..world.constraints.len()).map(|i| length(world, i)).fold(f32::MAX, f32::min);
+        assert!(shortest(&torus) < 0.6 * TARGET_DIST);
+        for _ in 0..300 {
+            torus.step(dt);
+        }
+        assert!(shortest(&torus) > 0.9 * TARGET_DIST, "{}", shortest(&torus));
+    }
+}
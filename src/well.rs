@@ -0,0 +1,67 @@
+use crate::node::Node;
+use glam::Vec2;
+
+/// A point that pulls nearby free nodes toward it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Well {
+    pub pos: Vec2,
+    /// Acceleration toward `pos` of a node right next to it, fading linearly to
+    /// nothing at `radius` so there's no singularity at the center.
+    pub strength: f32,
+    pub radius: f32,
+}
+
+impl Well {
+    /// The pull on `node`, zero if it's fixed or out of reach.
+    pub fn force_on(&self, node: &Node) -> Vec2 {
+        let offset = self.pos - node.pos;
+        let distance = offset.length();
+        if node.fixed || distance >= self.radius {
+            return Vec2::ZERO;
+        }
+        offset.normalize_or_zero() * self.strength * node.mass * (1.0 - distance / self.radius)
+    }
+}
+
+/// How far past its radius a sink pulls, as a multiple of the radius.
+pub const SINK_REACH: f32 = 6.0;
+
+/// A well that deletes the free nodes it pulls in, along with their constraints.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sink {
+    pub pos: Vec2,
+    /// Like a well's, over `SINK_REACH` times the radius.
+    pub strength: f32,
+    /// Free nodes closer than this are consumed.
+    pub radius: f32,
+}
+
+impl Sink {
+    /// The well this sink pulls like.
+    pub fn pull(&self) -> Well {
+        Well { pos: self.pos, strength: self.strength, radius: self.radius * SINK_REACH }
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.pos.distance(pos) < self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_fades_with_distance() {
+        let well = Well { pos: Vec2::ZERO, strength: 10.0, radius: 100.0 };
+        let near = Node::with_pos_and_mass(Vec2::new(25.0, 0.0), 2.0);
+        let far = Node::with_pos_and_mass(Vec2::new(75.0, 0.0), 2.0);
+        assert_eq!(well.force_on(&near), Vec2::new(-15.0, 0.0));
+        assert_eq!(well.force_on(&far), Vec2::new(-5.0, 0.0));
+
+        let mut fixed = near;
+        fixed.fixed = true;
+        assert_eq!(well.force_on(&fixed), Vec2::ZERO);
+        assert_eq!(well.force_on(&Node::with_pos_and_mass(Vec2::new(0.0, 150.0), 1.0)), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::world::World;
+use crate::TARGET_DIST;
+
+/// Shortest a winch reels a link in to.
+pub const MIN_LENGTH: f32 = 0.1 * TARGET_DIST;
+/// Longest a winch pays a link out to, well short of the default break threshold.
+pub const MAX_LENGTH: f32 = 2.0 * TARGET_DIST;
+
+/// Reels in or pays out the rope hanging from a node by changing its links' rest lengths.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Winch {
+    /// The node the rope hangs from, usually a fixed one.
+    pub anchor: usize,
+    /// Change in the rope's total rest length per unit of time at full power.
+    pub speed: f32,
+}
+
+impl Winch {
+    /// Indices of the constraints making up the rope: the anchor's first link,
+    /// then on from node to node for as long as each has exactly two links.
+    pub fn rope(&self, world: &World) -> Vec<usize> {
+        let mut links = vec![Vec::new(); world.arena.len()];
+        for (i, constraint) in world.constraints.iter().enumerate() {
+            links[constraint.a].push(i);
+            links[constraint.b].push(i);
+        }
+
+        let mut rope = Vec::new();
+        let (mut node, mut link) = (self.anchor, links[self.anchor].first().copied());
+        while let Some(i) = link {
+            rope.push(i);
+            let constraint = &world.constraints[i];
+            node = if constraint.a == node { constraint.b } else { constraint.a };
+            link = match links[node].as_slice() {
+                // stop at a loop back to the start rather than go round forever
+                &[x, y] if node != self.anchor => Some(if x == i { y } else { x }),
+                _ => None,
+            };
+        }
+        rope
+    }
+
+    /// Spreads a change of `direction * speed * dt` in the rope's length evenly
+    /// over its links, keeping each between `MIN_LENGTH` and `MAX_LENGTH`.
+    /// Negative directions reel in and positive ones pay out.
+    pub fn reel(&self, world: &mut World, direction: f32, dt: f32) {
+        let rope = self.rope(world);
+        if rope.is_empty() {
+            return;
+        }
+        let change = direction * self.speed * dt / rope.len() as f32;
+        for i in rope {
+            let constraint = &mut world.constraints[i];
+            constraint.rest_length = (constraint.rest_length + change).clamp(MIN_LENGTH, MAX_LENGTH);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    #[test]
+    fn reels_the_rope_down_to_where_it_branches() {
+        let mut world = World::new(500.0);
+        world.add_rope(Vec2::ZERO, 5);
+        // a branch off the third node, which the rope stops at
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(50.0, 100.0), 1.0));
+        world.constraints.push(Constraint { a: 2, b: 5, rest_length: TARGET_DIST, break_threshold: 250.0 });
+        let winch = Winch { anchor: 0, speed: 40.0 };
+        assert_eq!(winch.rope(&world), vec![0, 1]);
+
+        winch.reel(&mut world, -1.0, 1.0);
+        let lengths: Vec<f32> = world.constraints.iter().map(|c| c.rest_length).collect();
+        assert_eq!(lengths, [30.0, 30.0, 50.0, 50.0, 50.0]);
+    }
+}
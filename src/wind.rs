@@ -0,0 +1,54 @@
+use crate::{WIND_FALLOFF, WIND_RADIUS, WIND_STRENGTH};
+
+/// The patch of wind the cursor blows, full strength in the middle and fading
+/// smoothly to nothing at its edge.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WindBrush {
+    pub radius: f32,
+    /// Force per unit of cursor movement on a node in the middle.
+    pub strength: f32,
+    /// How much of the radius, from the edge in, the wind fades over: 0 for a
+    /// hard edge, 1 for fading all the way from the middle.
+    pub falloff: f32,
+}
+
+impl Default for WindBrush {
+    fn default() -> Self {
+        Self { radius: WIND_RADIUS, strength: WIND_STRENGTH, falloff: WIND_FALLOFF }
+    }
+}
+
+impl WindBrush {
+    /// Fraction of the full strength felt `distance` from the middle.
+    pub fn weight(&self, distance: f32) -> f32 {
+        if distance >= self.radius {
+            return 0.0;
+        }
+        let fade = self.radius * self.falloff;
+        let inner = self.radius - fade;
+        if distance <= inner {
+            return 1.0;
+        }
+        // smoothstep down across the fading ring
+        let t = (self.radius - distance) / fade;
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_smoothly_over_the_falloff() {
+        let brush = WindBrush { radius: 100.0, strength: 1.0, falloff: 0.5 };
+        assert_eq!(brush.weight(0.0), 1.0);
+        assert_eq!(brush.weight(50.0), 1.0);
+        assert_eq!(brush.weight(75.0), 0.5);
+        assert_eq!(brush.weight(100.0), 0.0);
+
+        let hard = WindBrush { falloff: 0.0, ..brush };
+        assert_eq!(hard.weight(99.0), 1.0);
+        assert_eq!(hard.weight(100.0), 0.0);
+    }
+}
@@ -0,0 +1,1466 @@
+use crate::anchor::{Anchor, AnchorPath};
+use crate::ball::{Ball, BALL_RESTITUTION};
+use crate::band::Band;
+use crate::budget::{AtCap, NodeBudget};
+use crate::charge::Electrostatics;
+use crate::cleanup::Cleanup;
+use crate::lod::Lod;
+use crate::multigrid::Multigrid;
+use crate::magnet::Magnet;
+use crate::constraint::{Constraint, SeveredLink};
+use crate::contact::{Circles, Contacts};
+use crate::dispenser::Dispenser;
+use crate::event::SimEvent;
+use crate::fluid::Fluid;
+use crate::fray::Fray;
+use crate::friction::Friction;
+use crate::geometry;
+use crate::group::LinkGroup;
+use crate::layer::{Layers, GROUND, WALLS};
+use crate::muscle::Muscle;
+use crate::node::Node;
+use crate::params::Params;
+use crate::platform::Platform;
+use crate::rail::{Curve, Rail};
+use crate::rain::Rain;
+use crate::rng::Rng;
+use crate::solve_order::SolveOrder;
+use crate::units::Units;
+use crate::rotor::Rotor;
+use crate::sand::Sand;
+use crate::shatter::{Fragment, Shatter, FRAGMENT_GAP};
+use crate::slider::Slider;
+use crate::walls::Walls;
+use crate::water::Water;
+use crate::weave::{Links, Weave};
+use crate::well::{Sink, Well};
+use crate::winch::Winch;
+use crate::wind::WindBrush;
+use crate::wrap::Wrap;
+use crate::xpbd::Xpbd;
+use crate::{CONTACT_SLOP, NODE_RADIUS, SLEEP_SPEED, SPLIT_REACH, TARGET_DIST};
+use glam::Vec2;
+use tracing::trace_span;
+
+/// Nodes, the constraints between them, and the ground they rest on.
+///
+/// A step is split into phases so callers can inject input between them;
+/// `step` runs them all in order for simulations without input. Phases
+/// queue what happened in `events` for the caller to drain.
+///
+/// Stepping is deterministic: nodes and constraints are always visited in
+/// `Vec` order, the timestep is passed in rather than read from a clock, and
+/// anything random draws from `rng`. The same scene, seed, and inputs give
+/// bit-identical results, which `checksum` makes cheap to compare.
+#[derive(Clone, Debug)]
+pub struct World {
+    pub arena: Vec<Node>,
+    pub constraints: Vec<Constraint>,
+    /// Each constraint's `tension` at the end of the last step, by index into
+    /// `constraints`, so everything reading it in a frame shares one measurement.
+    /// Out of date, and maybe the wrong length, until the next step after editing.
+    pub tensions: Vec<f32>,
+    /// Elastic links, which pull like springs instead of being solved like constraints.
+    pub bands: Vec<Band>,
+    pub ground_y: f32,
+    /// How many pixels make a meter in the scene file the world came from, if
+    /// it gave lengths in meters. The world itself is always in pixels.
+    pub units: Option<Units>,
+    /// Bouncy walls around the scene, none of them on unless asked for.
+    pub walls: Walls,
+    /// Edges that nodes wrap around, none of them unless asked for.
+    pub wrap: Wrap,
+    /// Friction where nodes touch the ground, walls, balls, and each other.
+    pub friction: Friction,
+    /// Collisions between nodes, so they pile up, if the scene has them on.
+    pub contacts: Option<Contacts>,
+    /// Strain rate, in rest lengths per unit time, past which taut constraints
+    /// snap however little they're stretched, if the scene has it on.
+    pub break_rate: Option<f32>,
+    /// Breaks the busier end of each link that snaps into fragments, if the scene has it on.
+    pub shatter: Option<Shatter>,
+    /// Splits ends of severed links still holding both sides, and springs them back, if the scene has it on.
+    pub fray: Option<Fray>,
+    /// Solves constraints with XPBD instead of projecting them by `rigidity`, if the scene has it on.
+    pub xpbd: Option<Xpbd>,
+    /// Forces between charged nodes, if the scene has them on.
+    pub electrostatics: Option<Electrostatics>,
+    /// The field pushing on magnetic nodes, if the scene has one.
+    pub magnet: Option<Magnet>,
+    /// Fewer solver iterations for links far from a focus, if the viewer has it on.
+    pub lod: Option<Lod>,
+    /// Solves coarsened long chains before the usual iterations, if the scene has it on. XPBD doesn't use it.
+    pub multigrid: Option<Multigrid>,
+    /// The order links are projected in each iteration. XPBD keeps to its own.
+    pub solve_order: SolveOrder,
+    /// Removes nodes lost for good, if the scene has it on.
+    pub cleanup: Option<Cleanup>,
+    /// The most nodes `spawn` adds up to, if the scene has a cap.
+    pub budget: Option<NodeBudget>,
+    /// Moving air that drags nodes along with it, if the scene has any.
+    pub fluid: Option<Fluid>,
+    pub rain: Option<Rain>,
+    /// Droplets of water that slosh around and push on nodes, if the scene has any.
+    pub water: Option<Water>,
+    /// Free nodes that pile up like sand, if the scene has any.
+    pub sand: Option<Sand>,
+    pub wells: Vec<Well>,
+    pub sinks: Vec<Sink>,
+    /// Nodes that collide with constraints, by index into `arena`.
+    pub balls: Vec<Ball>,
+    /// Fixed nodes moved along paths, by index into `arena`.
+    pub anchors: Vec<Anchor>,
+    /// Rectangles moved along paths, carrying the fixed nodes pinned to them.
+    pub platforms: Vec<Platform>,
+    /// Nodes kept on curves, by index into `arena`.
+    pub rails: Vec<Rail>,
+    /// Nodes kept on ropes, by index into `arena`.
+    pub sliders: Vec<Slider>,
+    /// Spinning obstacles that drag the nodes touching them along.
+    pub rotors: Vec<Rotor>,
+    pub winches: Vec<Winch>,
+    pub dispensers: Vec<Dispenser>,
+    /// Links that tighten and slacken on their own.
+    pub muscles: Vec<Muscle>,
+    /// Named sets of links to change all at once; see `group`.
+    pub groups: Vec<LinkGroup>,
+    /// Which layers are shown and which collide with each other.
+    pub layers: Layers,
+    /// Change with `set_params`, which keeps existing constraints in step.
+    pub params: Params,
+    pub rng: Rng,
+    pub events: Vec<SimEvent>,
+}
+
+impl World {
+    pub fn new(ground_y: f32) -> Self {
+        Self {
+            arena: Vec::new(),
+            constraints: Vec::new(),
+            tensions: Vec::new(),
+            bands: Vec::new(),
+            ground_y,
+            units: None,
+            walls: Walls::default(),
+            wrap: Wrap::default(),
+            friction: Friction::default(),
+            contacts: None,
+            break_rate: None,
+            shatter: None,
+            fray: None,
+            xpbd: None,
+            electrostatics: None,
+            magnet: None,
+            lod: None,
+            multigrid: None,
+            solve_order: SolveOrder::Forward,
+            cleanup: None,
+            budget: None,
+            fluid: None,
+            rain: None,
+            water: None,
+            sand: None,
+            wells: Vec::new(),
+            sinks: Vec::new(),
+            balls: Vec::new(),
+            anchors: Vec::new(),
+            platforms: Vec::new(),
+            rails: Vec::new(),
+            sliders: Vec::new(),
+            rotors: Vec::new(),
+            winches: Vec::new(),
+            dispensers: Vec::new(),
+            muscles: Vec::new(),
+            groups: Vec::new(),
+            layers: Layers::default(),
+            params: Params::default(),
+            rng: Rng::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Copies `source` into this world, reusing its buffers instead of
+    /// allocating new ones as `clone` would. Meant for a world kept around to
+    /// be overwritten often.
+    pub fn copy_from(&mut self, source: &World) {
+        // spelled out so a new field can't be left behind
+        let World { arena, constraints, tensions, bands, ground_y, units, walls, wrap, friction, contacts, break_rate,
+            shatter, fray, xpbd, electrostatics, magnet, lod, multigrid, solve_order, cleanup, budget, fluid, rain,
+            water, sand, wells, sinks, balls, anchors, platforms, rails, sliders, rotors, winches, dispensers,
+            muscles, groups, layers, params, rng, events,
+        } = source;
+        self.arena.clone_from(arena);
+        self.constraints.clone_from(constraints);
+        self.tensions.clone_from(tensions);
+        self.bands.clone_from(bands);
+        self.ground_y.clone_from(ground_y);
+        self.units.clone_from(units);
+        self.walls.clone_from(walls);
+        self.wrap.clone_from(wrap);
+        self.friction.clone_from(friction);
+        self.contacts.clone_from(contacts);
+        self.break_rate.clone_from(break_rate);
+        self.shatter.clone_from(shatter);
+        self.fray.clone_from(fray);
+        self.xpbd.clone_from(xpbd);
+        self.electrostatics.clone_from(electrostatics);
+        self.magnet.clone_from(magnet);
+        self.lod.clone_from(lod);
+        self.multigrid.clone_from(multigrid);
+        self.solve_order.clone_from(solve_order);
+        self.cleanup.clone_from(cleanup);
+        self.budget.clone_from(budget);
+        self.fluid.clone_from(fluid);
+        self.rain.clone_from(rain);
+        self.water.clone_from(water);
+        self.sand.clone_from(sand);
+        self.wells.clone_from(wells);
+        self.sinks.clone_from(sinks);
+        self.balls.clone_from(balls);
+        self.anchors.clone_from(anchors);
+        self.platforms.clone_from(platforms);
+        self.rails.clone_from(rails);
+        self.sliders.clone_from(sliders);
+        self.rotors.clone_from(rotors);
+        self.winches.clone_from(winches);
+        self.dispensers.clone_from(dispensers);
+        self.muscles.clone_from(muscles);
+        self.groups.clone_from(groups);
+        self.layers.clone_from(layers);
+        self.params.clone_from(params);
+        self.rng.clone_from(rng);
+        self.events.clone_from(events);
+    }
+
+    /// Hangs a chain of `num_points` nodes straight down from a fixed node at `anchor`.
+    pub fn add_rope(&mut self, anchor: Vec2, num_points: usize) {
+        let first = self.arena.len();
+
+        for i in 0..num_points {
+            self.arena.push(Node::with_pos_and_mass(
+                anchor + Vec2::new(0.0, TARGET_DIST * i as f32),
+                1.0 + (i as f32 / 20.0).powi(2) * 0.0,
+            ));
+
+            if i == 0 {
+                self.arena[first].fixed = true;
+            }
+
+            if i > 0 {
+                self.constraints.push(Constraint {
+                    a: first + i - 1,
+                    b: first + i,
+                    rest_length: TARGET_DIST,
+                    break_threshold: TARGET_DIST * self.params.break_threshold,
+                });
+            }
+        }
+    }
+
+    /// Hangs a strand of `strand_nodes` nodes, `link_length` apart, straight
+    /// down from each of `roots`, like a fringe along a curtain's edge or hair.
+    /// Strands are as heavy for their length as ropes are, and on their roots' layers.
+    pub fn add_fringe(&mut self, roots: &[usize], strand_nodes: usize, link_length: f32) {
+        for &root in roots {
+            let (top, layer) = (self.arena[root].pos, self.arena[root].layer);
+            let mut above = root;
+            for i in 1..=strand_nodes {
+                let pos = top + Vec2::new(0.0, link_length * i as f32);
+                self.arena.push(Node { layer, ..Node::with_pos_and_mass(pos, link_length / TARGET_DIST) });
+                let below = self.arena.len() - 1;
+                let break_threshold = link_length * self.params.break_threshold;
+                self.constraints.push(Constraint { a: above, b: below, rest_length: link_length, break_threshold });
+                above = below;
+            }
+        }
+    }
+
+    /// Hangs a `columns` by `rows` grid of nodes from `top_left`, pinned at every
+    /// `pin_every`th node along the top row (and at both top corners).
+    pub fn add_cloth(&mut self, top_left: Vec2, columns: usize, rows: usize, pin_every: usize) {
+        self.add_woven_cloth(top_left, columns, rows, pin_every, &Weave::default());
+    }
+
+    /// Hangs a cloth like `add_cloth`, with each set of its links made as `weave` says. Joined sides or ends are laid
+    /// flat, folded in half at the middle column or row, so with an even count of them every link starts at its rest
+    /// length; with an odd count the links at the folds start squashed to half.
+    pub fn add_woven_cloth(&mut self, top_left: Vec2, columns: usize, rows: usize, pin_every: usize, weave: &Weave) {
+        let first = self.arena.len();
+        let index = |column: usize, row: usize| first + row * columns + column;
+        let (sides, ends) = (weave.join_sides && columns > 2, weave.join_ends && rows > 2);
+        // how far along its row or column a node lies, coming back along the far side of a joined one
+        let fold = |i: usize, count: usize, joined: bool| match joined && 2 * i > count {
+            true => (count - i) as f32 - (count % 2) as f32 / 2.0,
+            false => i as f32,
+        };
+        // the one `step` before `i` along a line of `count`, around the join if there is one and it doesn't double up
+        let before = |i: usize, step: usize, count: usize, joined: bool| match i.checked_sub(step) {
+            Some(before) => Some(before),
+            None => (joined && count > 2 * step).then_some(i + count - step),
+        };
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let offset = Vec2::new(fold(column, columns, sides), fold(row, rows, ends)) * TARGET_DIST;
+                let mut node = Node::with_pos_and_mass(top_left + offset, 1.0);
+                node.fixed = row == 0 && (column % pin_every.max(1) == 0 || column == columns - 1);
+                self.arena.push(node);
+
+                if column > 0 {
+                    self.add_links(weave.weft, index(column - 1, row), index(column, row), TARGET_DIST, None);
+                }
+                if row > 0 {
+                    self.add_links(weave.warp, index(column, row - 1), index(column, row), TARGET_DIST, None);
+                }
+            }
+        }
+
+        // after the grid, so a plain weave's links come out in the same order as ever
+        if sides {
+            for row in 0..rows {
+                self.add_links(weave.weft, index(columns - 1, row), index(0, row), TARGET_DIST, None);
+            }
+        }
+        if ends {
+            for column in 0..columns {
+                self.add_links(weave.warp, index(column, rows - 1), index(column, 0), TARGET_DIST, None);
+            }
+        }
+        for row in 0..rows {
+            for column in 0..columns {
+                let (left, up) = (before(column, 1, columns, sides), before(row, 1, rows, ends));
+                if let (Some(left), Some(up)) = (left, up) {
+                    let diagonal = weave.shear_rest_length.unwrap_or(TARGET_DIST * std::f32::consts::SQRT_2);
+                    let threshold = weave.shear_break_threshold;
+                    self.add_links(weave.shear, index(left, up), index(column, row), diagonal, threshold);
+                    self.add_links(weave.shear, index(column, up), index(left, row), diagonal, threshold);
+                }
+                if let Some(left) = before(column, 2, columns, sides) {
+                    self.add_links(weave.bend, index(left, row), index(column, row), 2.0 * TARGET_DIST, None);
+                }
+                if let Some(up) = before(row, 2, rows, ends) {
+                    self.add_links(weave.bend, index(column, up), index(column, row), 2.0 * TARGET_DIST, None);
+                }
+            }
+        }
+    }
+
+    /// Links `a` and `b` at `rest_length` as `links` says, if at all, a rigid
+    /// link breaking at `break_threshold` or the params' default.
+    fn add_links(&mut self, links: Links, a: usize, b: usize, rest_length: f32, break_threshold: Option<f32>) {
+        match links {
+            Links::Omitted => {}
+            Links::Rigid => {
+                let break_threshold = break_threshold.unwrap_or(rest_length * self.params.break_threshold);
+                self.constraints.push(Constraint { a, b, rest_length, break_threshold });
+            }
+            Links::Spring(stiffness) => {
+                self.bands.push(Band { a, b, rest_length, stiffness, pushes: true, ..Band::default() });
+            }
+        }
+    }
+
+    /// Adds `node` and returns its index, within `budget` if there is one: at
+    /// the cap, it either takes over the oldest free node, queuing a
+    /// `SimEvent::NodeRecycled`, or adds nothing and returns None. Either way
+    /// no other node's index changes.
+    pub fn spawn(&mut self, node: Node) -> Option<usize> {
+        let Some(budget) = self.budget.filter(|budget| self.arena.len() >= budget.max_nodes) else {
+            self.arena.push(node);
+            return Some(self.arena.len() - 1);
+        };
+        if budget.at_cap == AtCap::Refuse {
+            return None;
+        }
+        let i = NodeBudget::recyclable(self)?;
+        self.balls.retain(|ball| ball.node != i);
+        if let Some(sand) = self.sand.as_mut() {
+            sand.grains.retain(|&grain| grain != i);
+        }
+        for platform in self.platforms.iter_mut() {
+            platform.riders.retain(|&(rider, _)| rider != i);
+        }
+        if let Some(loose_for) = self.cleanup.as_mut().and_then(|cleanup| cleanup.loose_for.get_mut(i)) {
+            *loose_for = 0.0;
+        }
+        let pos = std::mem::replace(&mut self.arena[i], node).pos;
+        self.events.push(SimEvent::NodeRecycled { node: i, pos });
+        Some(i)
+    }
+
+    /// `add_ball` within `budget`, as `spawn` adds nodes, for firing balls
+    /// into a running scene. None if there was no room.
+    pub fn spawn_ball(&mut self, pos: Vec2, vel: Vec2, mass: f32, radius: f32) -> Option<usize> {
+        let node = self.spawn(Node { vel, restitution: BALL_RESTITUTION, ..Node::with_pos_and_mass(pos, mass) })?;
+        self.balls.push(Ball { node, radius, smash_speed: f32::INFINITY });
+        Some(node)
+    }
+
+    /// Adds a free node of `mass` at `pos`, moving at `vel`, that collides with
+    /// constraints as a ball of `radius` and bounces with `BALL_RESTITUTION`.
+    /// Returns its index.
+    pub fn add_ball(&mut self, pos: Vec2, vel: Vec2, mass: f32, radius: f32) -> usize {
+        let mut node = Node::with_pos_and_mass(pos, mass);
+        node.vel = vel;
+        node.restitution = BALL_RESTITUTION;
+        self.arena.push(node);
+        let index = self.arena.len() - 1;
+        self.balls.push(Ball { node: index, radius, smash_speed: f32::INFINITY });
+        index
+    }
+
+    /// Replaces the params, rescaling existing break thresholds if that one changed.
+    pub fn set_params(&mut self, params: Params) {
+        let scale = params.break_threshold / self.params.break_threshold;
+        if scale != 1.0 {
+            for constraint in self.constraints.iter_mut() {
+                constraint.break_threshold *= scale;
+            }
+        }
+        self.params = params;
+    }
+
+    /// Runs a full step with no user input, returning its events.
+    pub fn step(&mut self, dt: f32) -> Vec<SimEvent> {
+        let _span = trace_span!("step", dt).entered();
+        self.move_anchors(dt);
+        self.apply_forces();
+        self.blow(dt);
+        self.rain(dt);
+        self.pour_sand(dt);
+        self.integrate(dt);
+        self.sweep();
+        self.smash();
+        self.solve_constraints();
+        self.break_overloaded(dt);
+        self.differentiate(dt);
+        self.consume_sunk();
+        self.clean_up(dt);
+        self.measure_tensions();
+        self.drain_events()
+    }
+
+    /// Takes everything queued since the last drain.
+    pub fn drain_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Moves each anchor's node and each platform's riders `dt` further along
+    /// their paths, with the velocity that took them there, turns the rotors,
+    /// and works the muscles.
+    pub fn move_anchors(&mut self, dt: f32) {
+        for rotor in self.rotors.iter_mut() {
+            rotor.advance(dt);
+        }
+        for muscle in self.muscles.iter_mut() {
+            muscle.flex(&mut self.constraints, dt);
+        }
+        for platform in self.platforms.iter_mut() {
+            platform.advance(&mut self.arena, dt);
+        }
+        for anchor in self.anchors.iter_mut() {
+            anchor.advance(dt);
+            let node = &mut self.arena[anchor.node];
+            let pos = anchor.position();
+            node.last_pos = node.pos;
+            node.vel = (pos - node.pos) / dt;
+            node.pos = pos;
+        }
+    }
+
+    /// Runs every winch for `dt` at `direction` times its speed: negative reels
+    /// in and positive pays out. `step` leaves winches alone, since they're
+    /// driven by input.
+    pub fn reel_winches(&mut self, direction: f32, dt: f32) {
+        for winch in self.winches.clone() {
+            winch.reel(self, direction, dt);
+        }
+    }
+
+    /// Feeds `dt` worth of rope out of every dispenser. Like winches, `step`
+    /// leaves them alone.
+    pub fn dispense(&mut self, dt: f32) {
+        for i in 0..self.dispensers.len() {
+            let mut dispenser = self.dispensers[i];
+            dispenser.dispense(self, dt);
+            self.dispensers[i] = dispenser;
+        }
+    }
+
+    pub fn apply_forces(&mut self) {
+        let _span = trace_span!("apply_forces").entered();
+        let Params { gravity, drag, .. } = self.params;
+        for node in self.arena.iter_mut() {
+            node.apply_gravity(gravity);
+            node.apply_drag(drag + node.drag);
+            for well in self.wells.iter() {
+                node.force += well.force_on(node);
+            }
+            for sink in self.sinks.iter() {
+                node.force += sink.pull().force_on(node);
+            }
+            if let Some(magnet) = self.magnet.as_ref() {
+                node.force += magnet.force_on(node);
+            }
+        }
+        for band in self.bands.iter() {
+            let force = band.force(&self.arena, &self.wrap);
+            self.arena[band.a].force += force;
+            self.arena[band.b].force -= force;
+        }
+        if let Some(electrostatics) = self.electrostatics.as_ref() {
+            let forces = electrostatics.forces(&self.arena, &self.wrap);
+            for (node, force) in self.arena.iter_mut().filter(|node| !node.fixed).zip(forces) {
+                node.force += force;
+            }
+        }
+    }
+
+    /// Moves the air along for `dt`, if there is any, dragging the nodes in it.
+    pub fn blow(&mut self, dt: f32) {
+        if let Some(fluid) = self.fluid.as_mut() {
+            let _span = trace_span!("blow", cells = fluid.vel.len()).entered();
+            fluid.advance(dt);
+            fluid.blow(&mut self.arena);
+        }
+    }
+
+    /// Lets the rain fall for `dt`, if it's raining, knocking and soaking the nodes it hits.
+    pub fn rain(&mut self, dt: f32) {
+        if let Some(rain) = self.rain.as_mut() {
+            let _span = trace_span!("rain", drops = rain.drops.len()).entered();
+            rain.fall(&mut self.arena, self.ground_y, &mut self.rng, dt);
+        }
+    }
+
+    /// Pours the sand due in the next `dt`, if any is still pouring.
+    pub fn pour_sand(&mut self, dt: f32) {
+        let Some(mut sand) = self.sand.take() else {
+            return;
+        };
+        let mut rng = self.rng;
+        sand.pour(&mut rng, dt, |node| self.spawn(node));
+        self.rng = rng;
+        self.sand = Some(sand);
+    }
+
+    /// Pushes nodes under `brush` at `pos` along `delta`, the cursor movement
+    /// since the last step, leaving those on hidden layers alone.
+    pub fn apply_wind(&mut self, pos: Vec2, delta: Vec2, brush: &WindBrush) {
+        let layers = self.layers;
+        for node in self.arena.iter_mut().filter(|node| layers.visible(node.layer)) {
+            node.force += delta * brush.strength * brush.weight(node.pos.distance(pos));
+        }
+    }
+
+    /// Moves free nodes on shown layers out of the disc of `radius` around `pos`, as if it were solid.
+    pub fn push(&mut self, pos: Vec2, radius: f32) {
+        let (reach, layers) = (radius + NODE_RADIUS, self.layers);
+        for node in self.arena.iter_mut().filter(|node| !node.fixed && layers.visible(node.layer)) {
+            let offset = node.pos - pos;
+            if offset.length() < reach {
+                node.pos = pos + offset.try_normalize().unwrap_or(Vec2::NEG_Y) * reach;
+            }
+        }
+    }
+
+    /// Moves nodes by their velocity, queueing a `Collision` for each that reaches the ground.
+    pub fn integrate(&mut self, dt: f32) {
+        let _span = trace_span!("integrate").entered();
+        self.arena.iter_mut().for_each(|node| node.integrate(dt));
+        if let Some(xpbd) = self.xpbd.as_mut() {
+            xpbd.begin(self.constraints.len(), dt);
+        }
+        if let Some(water) = self.water.as_mut() {
+            water.integrate(self.params.gravity, dt);
+        }
+
+        if self.wrap.y {
+            return;
+        }
+        let _collision = trace_span!("collision").entered();
+        let floor = self.ground_y - NODE_RADIUS;
+        for (i, node) in self.arena.iter().enumerate().filter(|(_, node)| self.layers.hits(node.layer, GROUND)) {
+            if !node.fixed && node.last_pos.y < floor && node.pos.y >= floor {
+                self.events.push(SimEvent::Collision {
+                    node: i,
+                    pos: Vec2::new(node.pos.x, floor),
+                    speed: node.vel.y,
+                });
+            }
+        }
+    }
+
+    /// Stops free nodes that moved farther than their radius this step at the
+    /// first thing in their way, the ground, a wall, or a rotor, so fast cut
+    /// pieces can't pass through thin blades between steps or land far from
+    /// where they hit. Rotors are taken where they are at the end of the step.
+    pub fn sweep(&mut self) {
+        let _span = trace_span!("sweep").entered();
+        let layers = self.layers;
+        let floor = (!self.wrap.y).then_some(self.ground_y - NODE_RADIUS);
+        for node in self.arena.iter_mut().filter(|node| !node.fixed) {
+            let (from, to) = (node.last_pos, node.pos);
+            if from.distance(to) <= NODE_RADIUS {
+                continue;
+            }
+            let ground = floor
+                .filter(|&floor| layers.hits(node.layer, GROUND) && from.y <= floor && to.y > floor)
+                .map(|floor| (floor - from.y) / (to.y - from.y));
+            let walls = layers.hits(node.layer, WALLS).then(|| self.walls.sweep(from, to)).flatten();
+            let touching = |rotor: &&Rotor| layers.collide(rotor.layer, node.layer);
+            let rotors = self.rotors.iter().filter(touching).map(|rotor| rotor.sweep(from, to));
+            let first = [ground, walls].into_iter().chain(rotors).flatten().min_by(f32::total_cmp);
+            if let Some(t) = first {
+                node.pos = from.lerp(to, t);
+            }
+        }
+    }
+
+    /// Derives velocities from the step's movement, turning nodes around at the walls, bringing
+    /// them back round the wrapped edges, and queueing `NodeSlept` for nodes that were moving and came to rest.
+    pub fn differentiate(&mut self, dt: f32) {
+        let _span = trace_span!("differentiate").entered();
+        if let Some(water) = self.water.as_mut() {
+            water.differentiate(dt);
+        }
+        let floor = (!self.wrap.y).then_some(self.ground_y);
+        let layers = self.layers;
+        for (i, node) in self.arena.iter_mut().enumerate() {
+            let incoming = node.vel;
+            node.differentiate(dt);
+            let layer = node.layer;
+            if let Some(floor) = floor.filter(|_| layers.hits(layer, GROUND)) {
+                node.bounce_ground(floor, incoming);
+            }
+            if layers.hits(layer, WALLS) {
+                self.walls.bounce(node, incoming);
+            }
+            let touching = |rotor: &&Rotor| layers.collide(rotor.layer, layer);
+            self.rotors.iter().filter(touching).for_each(|rotor| rotor.bounce(node, incoming));
+            self.wrap.wrap(node);
+            if node.fixed {
+                continue;
+            }
+
+            let resting = node.vel.length() < SLEEP_SPEED;
+            if resting && !node.asleep {
+                self.events.push(SimEvent::NodeSlept { node: i });
+            }
+            node.asleep = resting;
+        }
+    }
+
+    /// Refreshes `tensions` from the constraints as they are now.
+    pub fn measure_tensions(&mut self) {
+        let (arena, wrap) = (&self.arena, &self.wrap);
+        self.tensions.clear();
+        self.tensions.extend(self.constraints.iter().map(|constraint| constraint.tension(arena, wrap)));
+    }
+
+    /// Removes every free node inside a sink, last first, queueing `NodeConsumed` for each.
+    pub fn consume_sunk(&mut self) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let _span = trace_span!("consume_sunk").entered();
+        for i in (0..self.arena.len()).rev() {
+            let pos = self.arena[i].pos;
+            if !self.arena[i].fixed && self.sinks.iter().any(|sink| sink.contains(pos)) {
+                self.remove_node(i);
+                self.events.push(SimEvent::NodeConsumed { node: i, pos });
+            }
+        }
+    }
+
+    /// Removes the nodes `cleanup` says are lost, after `dt` more.
+    pub fn clean_up(&mut self, dt: f32) {
+        let Some(mut cleanup) = self.cleanup.take() else {
+            return;
+        };
+        for i in cleanup.due(self, dt) {
+            let pos = self.remove_node(i).pos;
+            cleanup.loose_for.remove(i);
+            self.events.push(SimEvent::NodeDespawned { node: i, pos });
+        }
+        self.cleanup = Some(cleanup);
+    }
+
+    /// Removes node `i` and its constraints, moving later nodes down an index to fill the gap.
+    pub fn remove_node(&mut self, i: usize) -> Node {
+        self.constraints.retain(|constraint| constraint.a != i && constraint.b != i);
+        for constraint in self.constraints.iter_mut() {
+            constraint.a = index_after_removal(constraint.a, i).unwrap();
+            constraint.b = index_after_removal(constraint.b, i).unwrap();
+        }
+        self.bands.retain(|band| band.a != i && band.b != i);
+        for band in self.bands.iter_mut() {
+            band.a = index_after_removal(band.a, i).unwrap();
+            band.b = index_after_removal(band.b, i).unwrap();
+        }
+        if let Some(sand) = self.sand.as_mut() {
+            sand.grains.retain(|&grain| grain != i);
+            for grain in sand.grains.iter_mut() {
+                *grain = index_after_removal(*grain, i).unwrap();
+            }
+        }
+        self.balls.retain(|ball| ball.node != i);
+        for ball in self.balls.iter_mut() {
+            ball.node = index_after_removal(ball.node, i).unwrap();
+        }
+        self.anchors.retain(|anchor| anchor.node != i);
+        for anchor in self.anchors.iter_mut() {
+            anchor.node = index_after_removal(anchor.node, i).unwrap();
+        }
+        for platform in self.platforms.iter_mut() {
+            platform.riders.retain(|&(node, _)| node != i);
+            for (node, _) in platform.riders.iter_mut() {
+                *node = index_after_removal(*node, i).unwrap();
+            }
+        }
+        self.rails.retain(|rail| rail.node != i);
+        for rail in self.rails.iter_mut() {
+            rail.node = index_after_removal(rail.node, i).unwrap();
+        }
+        self.sliders.retain(|slider| slider.node != i);
+        for slider in self.sliders.iter_mut() {
+            slider.node = index_after_removal(slider.node, i).unwrap();
+            slider.rope = slider.rope.iter().filter_map(|&node| index_after_removal(node, i)).collect();
+        }
+        self.winches.retain(|winch| winch.anchor != i);
+        for winch in self.winches.iter_mut() {
+            winch.anchor = index_after_removal(winch.anchor, i).unwrap();
+        }
+        self.muscles.retain(|muscle| muscle.a != i && muscle.b != i);
+        for muscle in self.muscles.iter_mut() {
+            muscle.a = index_after_removal(muscle.a, i).unwrap();
+            muscle.b = index_after_removal(muscle.b, i).unwrap();
+        }
+        for group in self.groups.iter_mut() {
+            group.links.retain(|&(a, b)| a != i && b != i);
+            for (a, b) in group.links.iter_mut() {
+                (*a, *b) = (index_after_removal(*a, i).unwrap(), index_after_removal(*b, i).unwrap());
+            }
+            group.frozen = group.frozen.iter().filter_map(|&node| index_after_removal(node, i)).collect();
+        }
+        if let Some(cleanup) = self.cleanup.as_mut().filter(|cleanup| i < cleanup.loose_for.len()) {
+            cleanup.loose_for.remove(i);
+        }
+        self.dispensers.retain(|dispenser| dispenser.anchor != i);
+        for dispenser in self.dispensers.iter_mut() {
+            dispenser.anchor = index_after_removal(dispenser.anchor, i).unwrap();
+            dispenser.feed = dispenser.feed.and_then(|feed| index_after_removal(feed, i));
+        }
+        self.arena.remove(i)
+    }
+
+    /// Every node as a circle for `contacts`, balls with their own radius,
+    /// leaving out sand grains, pairs joined by a link or band, and pairs on layers that don't collide.
+    fn circles(&self) -> Circles {
+        let radii = self.balls.iter().map(|ball| (ball.node, ball.radius));
+        let grains = self.sand.as_ref().map_or(&[][..], |sand| &sand.grains);
+        let links = self.constraints.iter().map(|constraint| (constraint.a, constraint.b));
+        let bands = self.bands.iter().map(|band| (band.a, band.b));
+        Circles { layers: self.layers, ..Circles::new(&self.arena, radii, grains, links.chain(bands)) }
+    }
+
+    pub fn solve_constraints(&mut self) {
+        self.solve_constraints_with(|_| {});
+    }
+
+    /// `solve_constraints`, calling `after_iteration` with the world after each
+    /// solver iteration, for watching it converge.
+    pub fn solve_constraints_with(&mut self, mut after_iteration: impl FnMut(&World)) {
+        let _span = trace_span!("solve_constraints", iterations = self.params.solver_iterations).entered();
+        if let Some(water) = self.water.as_mut() {
+            water.relax();
+        }
+        let floor = (!self.wrap.y).then_some(self.ground_y);
+        let circles = self.contacts.map(|contacts| {
+            let circles = self.circles();
+            contacts.stabilize(&mut self.arena, &circles, floor, self.params.solver_iterations);
+            circles
+        });
+        // which links are far is settled once per solve, so a link doesn't flicker in and out mid-solve
+        let far = self.lod.map(|lod| (lod, lod.far(&self.constraints, &self.arena)));
+        if let (Some(multigrid), None) = (self.multigrid, self.xpbd.as_ref()) {
+            multigrid.solve(&self.constraints, &mut self.arena, &self.wrap, self.params.rigidity);
+        }
+        let mut order = Vec::new();
+        for iteration in 0..self.params.solver_iterations {
+            match self.xpbd.as_mut() {
+                Some(xpbd) => xpbd.solve(&self.constraints, &mut self.arena, &self.wrap),
+                None => {
+                    self.solve_order.arrange(&mut order, self.constraints.len(), iteration, &mut self.rng);
+                    for (i, constraint) in order.iter().map(|&i| (i, &self.constraints[i])) {
+                        if far.as_ref().is_none_or(|(lod, far)| lod.solves(far[i], iteration)) {
+                            constraint.solve(&mut self.arena, self.params.rigidity, &self.wrap);
+                        }
+                    }
+                }
+            }
+            for ball in self.balls.iter() {
+                for constraint in self.constraints.iter() {
+                    if touches(&self.layers, &self.arena, ball, constraint) {
+                        ball.collide(constraint, &mut self.arena, &self.friction);
+                    }
+                }
+            }
+            for rail in self.rails.iter() {
+                rail.project(&mut self.arena);
+            }
+            for slider in self.sliders.iter() {
+                slider.project(&self.constraints, &mut self.arena);
+            }
+            for rotor in self.rotors.iter() {
+                let touching = |node: &&mut Node| self.layers.collide(rotor.layer, node.layer);
+                self.arena.iter_mut().filter(touching).for_each(|node| rotor.collide(node));
+            }
+            if let Some(water) = self.water.as_mut() {
+                water.collide(&mut self.arena, &self.constraints, &self.wrap, &self.walls, floor, &self.layers);
+            }
+            if let Some(sand) = self.sand.as_ref() {
+                sand.collide(&mut self.arena, &self.constraints, &self.wrap, &self.friction, &self.layers);
+            }
+            if let (Some(contacts), Some(circles)) = (self.contacts.as_ref(), circles.as_ref()) {
+                contacts.solve(&mut self.arena, circles, floor, &self.friction);
+            }
+            for node in self.arena.iter_mut() {
+                if let Some(floor) = floor.filter(|_| self.layers.hits(node.layer, GROUND)) {
+                    node.collide_ground(floor, &self.friction);
+                }
+                if self.layers.hits(node.layer, WALLS) {
+                    self.walls.collide(node, &self.friction);
+                }
+            }
+            after_iteration(self);
+        }
+    }
+
+    /// Removes constraints stretched past their break threshold, and taut ones
+    /// jerked faster than `break_rate` over the step's `dt`, queueing
+    /// `ConstraintBroken`, and with `shatter` on, shattering an end of each,
+    /// then with `fray` on, fraying their ends.
+    /// Runs after the solve, while free nodes' `vel` is still what they were moved with.
+    pub fn break_overloaded(&mut self, dt: f32) {
+        let _span = trace_span!("break_overloaded").entered();
+        let first_event = self.events.len();
+        let events = &mut self.events;
+        let mut broken = Vec::new();
+        let (arena, wrap) = (&self.arena, &self.wrap);
+        let jerked = |constraint: &Constraint, length: f32| {
+            let taut = length >= constraint.rest_length - CONTACT_SLOP;
+            self.break_rate.is_some_and(|rate| taut && constraint.strain_rate(arena, wrap, dt) > rate)
+        };
+        self.constraints.retain(|constraint| {
+            let length = constraint.offset(arena, wrap).length();
+            let intact = !constraint.snaps(arena, wrap) && !jerked(constraint, length);
+            if !intact {
+                events.push(SimEvent::ConstraintBroken(SeveredLink::new(constraint, &self.arena, &self.wrap)));
+                broken.push(*constraint);
+            }
+            intact
+        });
+
+        if let Some(shatter) = self.shatter {
+            let mut shattered = vec![false; self.arena.len()];
+            for constraint in broken {
+                if let Some(node) = Shatter::breaking_end(self, &constraint).filter(|&node| !shattered[node]) {
+                    shattered[node] = true;
+                    let fragments = shatter.fragments(self, node);
+                    self.shatter_node(node, fragments);
+                }
+            }
+        }
+        self.fray_severed(first_event);
+    }
+
+    /// With `fray` on, goes through the links severed in the events queued
+    /// from `first_event` on and splits each end still linked to both sides
+    /// in two, as `Fray::across` finds them, half its mass each, the new node
+    /// appended to `arena` taking the links across. Every end, old and new,
+    /// then moves `recoil` along its `Fray::heading`. Queues a `NodeFrayed`
+    /// for each split.
+    fn fray_severed(&mut self, first_event: usize) {
+        let Some(fray) = self.fray else {
+            return;
+        };
+        let severed: Vec<(usize, usize)> = self.events[first_event..]
+            .iter()
+            .filter_map(|event| match event {
+                SimEvent::ConstraintBroken(link) | SimEvent::ConstraintCut(link) => Some((link.a, link.b)),
+                _ => None,
+            })
+            .collect();
+        let mut ends = Vec::new();
+        for (node, other) in severed.iter().flat_map(|&(a, b)| [(a, b), (b, a)]) {
+            if ends.contains(&node) {
+                continue;
+            }
+            ends.push(node);
+            let across = Fray::across(self, node, other);
+            if across.is_empty() || self.arena[node].fixed {
+                continue;
+            }
+            let copy = self.arena.len();
+            self.arena[node].mass /= 2.0;
+            self.arena[node].wetness /= 2.0;
+            self.arena.push(self.arena[node]);
+            for i in across {
+                self.move_link_end(i, node, copy);
+            }
+            self.events.push(SimEvent::NodeFrayed { node, copy, pos: self.arena[node].pos });
+            ends.push(copy);
+        }
+        // after splitting, so each end springs back into the piece it ended up in
+        let headings: Vec<Vec2> = ends.iter().map(|&node| Fray::heading(self, node)).collect();
+        for (node, heading) in ends.into_iter().zip(headings) {
+            self.arena[node].pos += heading * fray.recoil;
+        }
+    }
+
+    /// Breaks `node` into `fragments`, sharing its mass and wetness evenly,
+    /// the first keeping its index and the rest appended to `arena`, each
+    /// nudged `FRAGMENT_GAP` along its heading and taking its links. Queues a
+    /// `NodeShattered` for each one appended.
+    fn shatter_node(&mut self, node: usize, fragments: Vec<Fragment>) {
+        let pos = self.arena[node].pos;
+        let share = fragments.len() as f32;
+        self.arena[node].mass /= share;
+        self.arena[node].wetness /= share;
+        let whole = self.arena[node];
+        for (k, fragment) in fragments.into_iter().enumerate() {
+            let i = if k == 0 {
+                node
+            } else {
+                self.arena.push(whole);
+                self.events.push(SimEvent::NodeShattered { node, fragment: self.arena.len() - 1, pos });
+                self.arena.len() - 1
+            };
+            let nudge = fragment.heading * FRAGMENT_GAP;
+            self.arena[i].pos += nudge;
+            self.arena[i].last_pos += nudge;
+            for link in fragment.links {
+                self.move_link_end(link, node, i);
+            }
+        }
+    }
+
+    /// Moves constraint `link`'s end at `node` over to `to`, a piece split off
+    /// it, renumbering the pair naming the link in each group and muscle to
+    /// match. A group that froze `node` counts `to` as frozen too.
+    fn move_link_end(&mut self, link: usize, node: usize, to: usize) {
+        let constraint = &mut self.constraints[link];
+        let other = if constraint.a == node {
+            constraint.a = to;
+            constraint.b
+        } else {
+            constraint.b = to;
+            constraint.a
+        };
+        for group in self.groups.iter_mut() {
+            for pair in group.links.iter_mut() {
+                if *pair == (node, other) {
+                    *pair = (to, other);
+                } else if *pair == (other, node) {
+                    *pair = (other, to);
+                }
+            }
+            if group.frozen.contains(&node) && !group.frozen.contains(&to) {
+                group.frozen.push(to);
+            }
+        }
+        for muscle in self.muscles.iter_mut() {
+            if (muscle.a, muscle.b) == (node, other) {
+                muscle.a = to;
+            } else if (muscle.a, muscle.b) == (other, node) {
+                muscle.b = to;
+            }
+        }
+    }
+
+    /// Removes constraints that balls hit at their smash speed, queueing
+    /// `ConstraintBroken`, and with `fray` on, frays their ends.
+    pub fn smash(&mut self) {
+        if self.balls.is_empty() {
+            return;
+        }
+        let _span = trace_span!("smash").entered();
+        let first_event = self.events.len();
+        let events = &mut self.events;
+        self.constraints.retain(|constraint| {
+            let (layers, arena) = (&self.layers, &self.arena);
+            let hits = |ball: &Ball| touches(layers, arena, ball, constraint) && ball.smashes(constraint, arena);
+            let intact = !self.balls.iter().any(hits);
+            if !intact {
+                events.push(SimEvent::ConstraintBroken(SeveredLink::new(constraint, &self.arena, &self.wrap)));
+            }
+            intact
+        });
+        self.fray_severed(first_event);
+    }
+
+    /// Whether each node, by index into `arena`, is on a hidden layer.
+    pub fn hidden(&self) -> Vec<bool> {
+        self.arena.iter().map(|node| !self.layers.visible(node.layer)).collect()
+    }
+
+    /// Node `i` and every node linked to it through any number of links, in order.
+    pub fn piece(&self, i: usize) -> Vec<usize> {
+        let mut reached = vec![false; self.arena.len()];
+        reached[i] = true;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in &self.constraints {
+                if reached[constraint.a] != reached[constraint.b] {
+                    (reached[constraint.a], reached[constraint.b]) = (true, true);
+                    changed = true;
+                }
+            }
+        }
+        (0..self.arena.len()).filter(|&i| reached[i]).collect()
+    }
+
+    /// Removes constraints crossing the segment from `c` to `d`, queueing `ConstraintCut`.
+    pub fn cut(&mut self, c: Vec2, d: Vec2) {
+        self.cut_path(&[c, d], 0.0);
+    }
+
+    /// Removes constraints within `radius` of any segment of the polyline through
+    /// `points`, queueing one `ConstraintCut` for each. A radius of zero only cuts
+    /// constraints the polyline crosses. Links to nodes on hidden layers are left alone.
+    /// With `fray` on, the cut links' ends are frayed.
+    pub fn cut_path(&mut self, points: &[Vec2], radius: f32) {
+        self.cut_path_sparing(points, radius, &self.hidden());
+    }
+
+    /// `cut_path`, leaving links to nodes marked in `spared` alone.
+    fn cut_path_sparing(&mut self, points: &[Vec2], radius: f32, spared: &[bool]) {
+        let _span = trace_span!("cut", points = points.len(), radius).entered();
+        let first_event = self.events.len();
+        let events = &mut self.events;
+        let is_spared = |node: usize| spared.get(node).copied().unwrap_or(false);
+        self.constraints.retain(|constraint| {
+            if is_spared(constraint.a) || is_spared(constraint.b) {
+                return true;
+            }
+            let a = self.arena[constraint.a].pos;
+            let b = a + constraint.offset(&self.arena, &self.wrap);
+            let crosses = |segment: &[Vec2]| geometry::segment_distance(a, b, segment[0], segment[1]) <= radius;
+            let intersects = points.windows(2).any(crosses);
+            if intersects {
+                events.push(SimEvent::ConstraintCut(SeveredLink::new(constraint, &self.arena, &self.wrap)));
+            }
+            !intersects
+        });
+        self.fray_severed(first_event);
+    }
+
+    /// Cuts cloth cleanly along the polyline through `points`: every node within
+    /// `radius` (or `SPLIT_REACH`, if that's further) of it with links on both
+    /// sides is split in two, half its mass each, with the links on the right of
+    /// the blade moving to the new node, appended to `arena`. Links between
+    /// nodes that weren't split are then cut as by `cut_path`. Queues a
+    /// `NodeSplit` for each split. Nodes on hidden layers and their links are left alone.
+    pub fn split_path(&mut self, points: &[Vec2], radius: f32) {
+        let _span = trace_span!("split_path", points = points.len(), radius).entered();
+        let reach = radius.max(SPLIT_REACH);
+        // hidden nodes are spared along with the ones split
+        let mut split = self.hidden();
+        for node in 0..self.arena.len() {
+            if split[node] {
+                continue;
+            }
+            let pos = self.arena[node].pos;
+            let nearest = points
+                .windows(2)
+                .map(|segment| (segment, geometry::point_segment_distance(pos, segment[0], segment[1])))
+                .filter(|&(_, distance)| distance <= reach)
+                .min_by(|(_, x), (_, y)| x.total_cmp(y));
+            let Some((segment, _)) = nearest else {
+                continue;
+            };
+
+            // which links reach across to the blade's right, seen from this node's nearest segment
+            let right: Vec<usize> = self
+                .constraints
+                .iter()
+                .enumerate()
+                .filter_map(|(i, constraint)| {
+                    let other = match (constraint.a == node, constraint.b == node) {
+                        (true, _) => constraint.b,
+                        (_, true) => constraint.a,
+                        _ => return None,
+                    };
+                    let on_right = geometry::orientation(segment[0], segment[1], self.arena[other].pos) < 0.0;
+                    on_right.then_some(i)
+                })
+                .collect();
+            let links = self.constraints.iter().filter(|c| c.a == node || c.b == node).count();
+            if right.is_empty() || right.len() == links {
+                continue;
+            }
+
+            let copy = self.arena.len();
+            self.arena[node].mass /= 2.0;
+            self.arena[node].wetness /= 2.0;
+            self.arena.push(self.arena[node]);
+            for i in right {
+                self.move_link_end(i, node, copy);
+            }
+            self.events.push(SimEvent::NodeSplit { node, copy, pos });
+            split[node] = true;
+            split.push(true);
+        }
+        self.cut_path_sparing(points, radius, &split);
+    }
+
+    /// Like `cut_path`, but removes only the first constraint the polyline reaches,
+    /// going along it from its start, returning whether there was one.
+    pub fn cut_first(&mut self, points: &[Vec2], radius: f32) -> bool {
+        let _span = trace_span!("cut_first", points = points.len(), radius).entered();
+        let hidden = self.hidden();
+        for segment in points.windows(2) {
+            let (c, d) = (segment[0], segment[1]);
+            let first = self
+                .constraints
+                .iter()
+                .enumerate()
+                .filter(|(_, constraint)| !hidden[constraint.a] && !hidden[constraint.b])
+                .filter_map(|(i, constraint)| {
+                    let a = self.arena[constraint.a].pos;
+                    let b = a + constraint.offset(&self.arena, &self.wrap);
+                    let hit = geometry::segment_distance(a, b, c, d) <= radius;
+                    hit.then(|| (i, geometry::point_segment_distance(c, a, b)))
+                })
+                .min_by(|(_, x), (_, y)| x.total_cmp(y));
+
+            if let Some((i, _)) = first {
+                let constraint = self.constraints.remove(i);
+                let link = SeveredLink::new(&constraint, &self.arena, &self.wrap);
+                self.events.push(SimEvent::ConstraintCut(link));
+                self.fray_severed(self.events.len() - 1);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// FNV-1a hash of the exact bits of every node, constraint, band, ball, anchor, platform,
+    /// rail, slider, rotor, winch, dispenser, muscle, well, and sink, the walls, wrapped edges, layers, friction,
+    /// contacts, break rate, fraying, XPBD compliance, electrostatics, magnet, air, rain, water, and sand, and the
+    /// RNG state.
+    pub fn checksum(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET;
+        let mut write = |bits: u64| {
+            for byte in bits.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+
+        for node in self.arena.iter() {
+            for v in [node.pos, node.last_pos, node.vel, node.force] {
+                write(v.x.to_bits() as u64);
+                write(v.y.to_bits() as u64);
+            }
+            write(node.mass.to_bits() as u64);
+            write(node.wetness.to_bits() as u64);
+            write(node.restitution.to_bits() as u64);
+            write(node.fixed as u64);
+            write(node.layer as u64);
+            write(node.drag.to_bits() as u64);
+            write(node.gravity_scale.to_bits() as u64);
+            write(node.charge.to_bits() as u64);
+            write(node.magnetic as u64);
+        }
+
+        for constraint in self.constraints.iter() {
+            write(constraint.a as u64);
+            write(constraint.b as u64);
+            write(constraint.rest_length.to_bits() as u64);
+            write(constraint.break_threshold.to_bits() as u64);
+        }
+
+        for band in self.bands.iter() {
+            write(band.a as u64);
+            write(band.b as u64);
+            write(band.rest_length.to_bits() as u64);
+            write(band.stiffness.to_bits() as u64);
+            write(band.pushes as u64);
+            write(band.damping.to_bits() as u64);
+        }
+
+        for ball in self.balls.iter() {
+            write(ball.node as u64);
+            write(ball.radius.to_bits() as u64);
+            write(ball.smash_speed.to_bits() as u64);
+        }
+
+        let path_points = |path: &AnchorPath| match path {
+            AnchorPath::Line(a, b) => vec![*a, *b],
+            AnchorPath::Circle { center, radius } => vec![*center, Vec2::splat(*radius)],
+            AnchorPath::Waypoints(points) => points.clone(),
+            AnchorPath::Oscillate { center, amplitude } => vec![*center, *amplitude],
+        };
+        for anchor in self.anchors.iter() {
+            write(anchor.node as u64);
+            write(anchor.speed.to_bits() as u64);
+            write(anchor.travelled.to_bits() as u64);
+            for point in path_points(&anchor.path) {
+                write(point.x.to_bits() as u64);
+                write(point.y.to_bits() as u64);
+            }
+        }
+
+        for platform in self.platforms.iter() {
+            write(platform.speed.to_bits() as u64);
+            write(platform.travelled.to_bits() as u64);
+            for point in path_points(&platform.path).into_iter().chain([platform.size]) {
+                write(point.x.to_bits() as u64);
+                write(point.y.to_bits() as u64);
+            }
+            for &(node, offset) in platform.riders.iter() {
+                write(node as u64);
+                write(offset.x.to_bits() as u64);
+                write(offset.y.to_bits() as u64);
+            }
+        }
+
+        for rail in self.rails.iter() {
+            write(rail.node as u64);
+            let points = match &rail.curve {
+                Curve::Polyline(points) => points.clone(),
+                Curve::Bezier(points) => points.to_vec(),
+            };
+            for point in points {
+                write(point.x.to_bits() as u64);
+                write(point.y.to_bits() as u64);
+            }
+        }
+
+        for slider in self.sliders.iter() {
+            write(slider.node as u64);
+            slider.rope.iter().for_each(|&node| write(node as u64));
+        }
+
+        for rotor in self.rotors.iter() {
+            write(rotor.blades as u64);
+            write(rotor.layer as u64);
+            let Rotor { center, hub_radius, blade_length, blade_width, angle, spin, friction, turned, .. } = *rotor;
+            for bits in [center.x, center.y, hub_radius, blade_length, blade_width, angle, spin, friction, turned] {
+                write(bits.to_bits() as u64);
+            }
+        }
+
+        for winch in self.winches.iter() {
+            write(winch.anchor as u64);
+            write(winch.speed.to_bits() as u64);
+        }
+
+        for dispenser in self.dispensers.iter() {
+            write(dispenser.anchor as u64);
+            write(dispenser.speed.to_bits() as u64);
+            write(dispenser.feed.map_or(u64::MAX, |feed| feed as u64));
+        }
+
+        for muscle in self.muscles.iter() {
+            write(muscle.a as u64);
+            write(muscle.b as u64);
+            let Muscle { rest_length, amplitude, frequency, phase, cycles, .. } = *muscle;
+            for bits in [rest_length, amplitude, frequency, phase, cycles].map(f32::to_bits) {
+                write(bits as u64);
+            }
+        }
+
+        for well in self.wells.iter() {
+            for bits in [well.pos.x, well.pos.y, well.strength, well.radius].map(f32::to_bits) {
+                write(bits as u64);
+            }
+        }
+
+        for sink in self.sinks.iter() {
+            for bits in [sink.pos.x, sink.pos.y, sink.strength, sink.radius].map(f32::to_bits) {
+                write(bits as u64);
+            }
+        }
+
+        for layer in self.layers.0.iter() {
+            write(layer.visible as u64);
+            write(layer.collides_with as u64);
+            write(layer.obstacles as u64);
+            write(layer.depth.to_bits() as u64);
+        }
+
+        let Walls { min, max, left, right, top, bottom, restitution } = self.walls;
+        [left, right, top, bottom].into_iter().for_each(|on| write(on as u64));
+        for bits in [min.x, min.y, max.x, max.y, restitution].map(f32::to_bits) {
+            write(bits as u64);
+        }
+
+        if let Some(fluid) = self.fluid.as_ref() {
+            write(fluid.columns as u64);
+            write(fluid.rows as u64);
+            let Fluid { origin, cell, drag, fade, .. } = *fluid;
+            for bits in [origin.x, origin.y, cell, drag, fade].map(f32::to_bits) {
+                write(bits as u64);
+            }
+            for vel in fluid.vel.iter() {
+                write(vel.x.to_bits() as u64);
+                write(vel.y.to_bits() as u64);
+            }
+        }
+
+        if let Some(rain) = self.rain.as_ref() {
+            let Rain { left, right, top, rate, speed, drop_mass, soak, max_wetness, due, .. } = *rain;
+            for bits in [left, right, top, rate, speed, drop_mass, soak, max_wetness, due].map(f32::to_bits) {
+                write(bits as u64);
+            }
+            for drop in rain.drops.iter() {
+                for bits in [drop.pos.x, drop.pos.y, drop.vel.x, drop.vel.y].map(f32::to_bits) {
+                    write(bits as u64);
+                }
+            }
+        }
+
+        if let Some(water) = self.water.as_ref() {
+            let Water { reach, rest_density, stiffness, near_stiffness, viscosity, droplet_mass, .. } = *water;
+            for bits in [reach, rest_density, stiffness, near_stiffness, viscosity, droplet_mass].map(f32::to_bits) {
+                write(bits as u64);
+            }
+            for droplet in water.droplets.iter() {
+                for v in [droplet.pos, droplet.last_pos, droplet.vel] {
+                    write(v.x.to_bits() as u64);
+                    write(v.y.to_bits() as u64);
+                }
+            }
+        }
+
+        if let Some(sand) = self.sand.as_ref() {
+            sand.grains.iter().for_each(|&grain| write(grain as u64));
+            write(sand.pouring as u64);
+            let Sand { spout, rate, grain_mass, due, .. } = *sand;
+            for bits in [spout.x, spout.y, rate, grain_mass, due].map(f32::to_bits) {
+                write(bits as u64);
+            }
+        }
+
+        let Wrap { min, max, x, y } = self.wrap;
+        [x, y].into_iter().for_each(|on| write(on as u64));
+        for bits in [min.x, min.y, max.x, max.y].map(f32::to_bits) {
+            write(bits as u64);
+        }
+
+        let Friction { static_friction, dynamic_friction } = self.friction;
+        write(static_friction.to_bits() as u64);
+        write(dynamic_friction.to_bits() as u64);
+        if let Some(contacts) = self.contacts {
+            write(contacts.stacking.to_bits() as u64);
+        }
+        if let Some(rate) = self.break_rate {
+            write(rate.to_bits() as u64);
+        }
+        if let Some(shatter) = self.shatter {
+            write(shatter.fragments as u64);
+        }
+        if let Some(fray) = self.fray {
+            write(fray.recoil.to_bits() as u64);
+        }
+        if let Some(xpbd) = self.xpbd.as_ref() {
+            write(xpbd.compliance.to_bits() as u64);
+        }
+        if let Some(Multigrid { levels, iterations }) = self.multigrid {
+            write(levels as u64);
+            write(iterations as u64);
+        }
+        write(self.solve_order as u64);
+        if let Some(cleanup) = self.cleanup.as_ref() {
+            write(cleanup.depth.to_bits() as u64);
+            write(cleanup.delay.to_bits() as u64);
+        }
+        if let Some(NodeBudget { max_nodes, at_cap }) = self.budget {
+            write(max_nodes as u64);
+            write(at_cap as u64);
+        }
+        if let Some(Electrostatics { strength, reach }) = self.electrostatics {
+            write(strength.to_bits() as u64);
+            write(reach.to_bits() as u64);
+        }
+        let magnet = match self.magnet {
+            Some(Magnet::Uniform { force }) => vec![force.x, force.y],
+            Some(Magnet::Dipole { center, moment }) => vec![center.x, center.y, moment.x, moment.y],
+            None => Vec::new(),
+        };
+        for value in magnet {
+            write(value.to_bits() as u64);
+        }
+
+        let mut rng = self.rng;
+        write(rng.next_u64());
+        hash
+    }
+
+    /// Break fraction of each constraint, parallel to `constraints`.
+    pub fn strains(&self) -> Vec<f32> {
+        self.constraints
+            .iter()
+            .map(|constraint| constraint.break_fraction(&self.arena, &self.wrap))
+            .collect()
+    }
+
+    /// A copy with node positions blended from where they were in `previous`,
+    /// `alpha` of the way to where they are now, for drawing between two states.
+    pub fn blended_from(&self, previous: &World, alpha: f32) -> World {
+        let mut world = self.clone();
+        // nodes are only ever appended or removed, and removal shifts the later
+        // ones, so the old indices still line up unless some have gone
+        if previous.arena.len() <= world.arena.len() {
+            let wrap = world.wrap;
+            for (node, previous) in world.arena.iter_mut().zip(previous.arena.iter()) {
+                node.pos = previous.pos + wrap.delta(previous.pos, node.pos) * alpha;
+            }
+        }
+        world
+    }
+}
+
+/// Where node `index` ends up once node `removed` is taken out, or `None` if it's the one removed.
+pub fn index_after_removal(index: usize, removed: usize) -> Option<usize> {
+    match index.cmp(&removed) {
+        std::cmp::Ordering::Less => Some(index),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(index - 1),
+    }
+}
+
+/// Whether `ball` collides with `constraint`, going by their nodes' layers.
+fn touches(layers: &Layers, arena: &[Node], ball: &Ball, constraint: &Constraint) -> bool {
+    let layer = |node: usize| arena[node].layer;
+    layers.collide_link(layer(ball.node), layer(constraint.a), layer(constraint.b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_only_fall_asleep_after_moving() {
+        let mut world = World::new(500.0);
+        world.params.gravity = 0.0;
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(100.0, 100.0), 1.0));
+        let slept = |events: Vec<SimEvent>| events.iter().any(|event| matches!(event, SimEvent::NodeSlept { node: 0 }));
+        // made at rest, it's been asleep all along
+        assert!(!slept(world.step(world.params.dt)));
+
+        world.arena[0].vel = Vec2::new(10.0, 0.0);
+        assert!(!slept(world.step(world.params.dt)));
+        assert!(!world.arena[0].asleep);
+        world.arena[0].vel = Vec2::ZERO;
+        assert!(slept(world.step(world.params.dt)));
+        assert!(!slept(world.step(world.params.dt)));
+    }
+}
@@ -0,0 +1,197 @@
+//! The solver in three dimensions, behind the `sim3d` feature, so cloth and
+//! ropes can swing toward and away from the viewer instead of staying flat
+//! in the screen. It's a separate, smaller world with the core of `World`
+//! over `Vec3`s: gravity, drag, integration, projection of links by
+//! `rigidity`, breaking, and a ground plane at `ground_y`, with y down like
+//! the 2D world. The math of each of those steps is `verlet`'s, shared with
+//! `World`. Everything else in the crate, from wind to water, works on
+//! `Vec2`s and stays in 2D.
+
+use crate::params::Params;
+use crate::verlet;
+use crate::{NODE_RADIUS, TARGET_DIST};
+use glam::Vec3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Node3 {
+    pub pos: Vec3,
+    pub last_pos: Vec3,
+    pub vel: Vec3,
+    /// Forces for the next step, cleared once it's taken.
+    pub force: Vec3,
+    pub mass: f32,
+    pub fixed: bool,
+}
+
+impl Node3 {
+    pub fn with_pos_and_mass(pos: Vec3, mass: f32) -> Self {
+        Self { pos, last_pos: pos, vel: Vec3::ZERO, force: Vec3::ZERO, mass, fixed: false }
+    }
+
+    pub fn integrate(&mut self, dt: f32) {
+        if !self.fixed {
+            verlet::integrate(&mut self.pos, &mut self.last_pos, &mut self.vel, self.force, self.mass, dt);
+        }
+    }
+
+    pub fn differentiate(&mut self, dt: f32) {
+        if !self.fixed {
+            self.vel = verlet::velocity(self.pos, self.last_pos, dt);
+        }
+        self.force = Vec3::ZERO;
+    }
+
+    pub fn add_offs(&mut self, offs: Vec3) {
+        if !self.fixed {
+            self.pos += offs;
+        }
+    }
+
+    /// Like `Node::inverse_mass`: none at all if it's fixed.
+    pub fn inverse_mass(&self) -> f32 {
+        if self.fixed {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+}
+
+/// A link between two nodes, solved like `Constraint`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Constraint3 {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub break_threshold: f32,
+}
+
+impl Constraint3 {
+    /// Moves both ends toward `rest_length` apart, by `rigidity` of the way
+    /// split by inverse mass, and only half as hard when squeezed.
+    pub fn solve(&self, arena: &mut [Node3], rigidity: f32) {
+        let (a, b) = (&arena[self.a], &arena[self.b]);
+        let (a_offs, b_offs) =
+            verlet::correction(b.pos - a.pos, self.rest_length, a.inverse_mass(), b.inverse_mass(), rigidity);
+        arena[self.a].add_offs(a_offs);
+        arena[self.b].add_offs(b_offs);
+    }
+
+    /// Whether it's stretched to its break threshold, like `Constraint::snaps`.
+    pub fn snaps(&self, arena: &[Node3]) -> bool {
+        verlet::snaps(arena[self.b].pos - arena[self.a].pos, self.break_threshold)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct World3 {
+    pub arena: Vec<Node3>,
+    pub constraints: Vec<Constraint3>,
+    pub params: Params,
+    pub ground_y: f32,
+}
+
+impl World3 {
+    pub fn new(ground_y: f32) -> Self {
+        Self { arena: Vec::new(), constraints: Vec::new(), params: Params::default(), ground_y }
+    }
+
+    /// Hangs a chain of `num_points` nodes straight down from a fixed node at `anchor`.
+    pub fn add_rope(&mut self, anchor: Vec3, num_points: usize) {
+        let first = self.arena.len();
+        for i in 0..num_points {
+            let mut node = Node3::with_pos_and_mass(anchor + Vec3::new(0.0, TARGET_DIST * i as f32, 0.0), 1.0);
+            node.fixed = i == 0;
+            self.arena.push(node);
+            if i > 0 {
+                self.link(first + i - 1, first + i);
+            }
+        }
+    }
+
+    /// Lays a `columns` by `rows` grid of nodes out flat, level with `corner`
+    /// and going off along x and z from it, pinned at every `pin_every`th node
+    /// along its first row and at both ends of it, so it swings down and
+    /// drapes from that edge.
+    pub fn add_cloth(&mut self, corner: Vec3, columns: usize, rows: usize, pin_every: usize) {
+        let first = self.arena.len();
+        let index = |column: usize, row: usize| first + row * columns + column;
+        for row in 0..rows {
+            for column in 0..columns {
+                let pos = corner + Vec3::new(column as f32, 0.0, row as f32) * TARGET_DIST;
+                let mut node = Node3::with_pos_and_mass(pos, 1.0);
+                node.fixed = row == 0 && (column % pin_every.max(1) == 0 || column == columns - 1);
+                self.arena.push(node);
+                if column > 0 {
+                    self.link(index(column - 1, row), index(column, row));
+                }
+                if row > 0 {
+                    self.link(index(column, row - 1), index(column, row));
+                }
+            }
+        }
+    }
+
+    fn link(&mut self, a: usize, b: usize) {
+        let rest_length = self.arena[a].pos.distance(self.arena[b].pos);
+        let break_threshold = rest_length * self.params.break_threshold;
+        self.constraints.push(Constraint3 { a, b, rest_length, break_threshold });
+    }
+
+    /// Advances the world by `dt`, with any forces already on the nodes added
+    /// to gravity and drag.
+    pub fn step(&mut self, dt: f32) {
+        let Params { gravity, drag, rigidity, solver_iterations, .. } = self.params;
+        for node in self.arena.iter_mut().filter(|node| !node.fixed) {
+            node.force += Vec3::new(0.0, gravity * node.mass, 0.0) - node.vel * drag;
+            node.integrate(dt);
+        }
+
+        let floor = self.ground_y - NODE_RADIUS;
+        for _ in 0..solver_iterations {
+            for constraint in self.constraints.iter() {
+                constraint.solve(&mut self.arena, rigidity);
+            }
+            for node in self.arena.iter_mut().filter(|node| !node.fixed) {
+                node.pos.y = node.pos.y.min(floor);
+            }
+        }
+
+        let arena = &self.arena;
+        self.constraints.retain(|link| !link.snaps(arena));
+
+        for node in self.arena.iter_mut() {
+            node.differentiate(dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_cloth_swings_down_out_of_its_plane() {
+        let mut world = World3::new(10_000.0);
+        world.add_cloth(Vec3::ZERO, 4, 4, 1);
+        assert_eq!(world.constraints.len(), 2 * 4 * 3);
+        // a sideways shove on a rope moves it out of the plane it hangs in
+        world.add_rope(Vec3::new(500.0, 0.0, 0.0), 4);
+        world.arena[19].force = Vec3::new(0.0, 0.0, 1000.0);
+
+        let dt = world.params.dt;
+        world.step(dt);
+        assert!(world.arena[19].pos.z > 1.0);
+        for _ in 0..600 {
+            world.step(dt);
+        }
+        // the free edge hangs below the pinned one, about as far as the cloth is long
+        let drop = world.arena[12..16].iter().map(|node| node.pos.y).sum::<f32>() / 4.0;
+        assert!(drop > 2.5 * TARGET_DIST, "{}", drop);
+        assert!(world.arena[..4].iter().all(|node| node.pos.y == 0.0));
+        for link in world.constraints.iter() {
+            let length = world.arena[link.a].pos.distance(world.arena[link.b].pos);
+            assert!((length - link.rest_length).abs() < 0.1 * link.rest_length);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::node::Node;
+use glam::Vec2;
+
+/// Edges of a rectangle, usually the window, that nodes leaving through one
+/// side come back in through the opposite one, like on a torus. Links take the
+/// short way round, across the edges if that's nearer.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Wrap {
+    /// Top left corner.
+    pub min: Vec2,
+    /// Bottom right corner.
+    pub max: Vec2,
+    /// Whether the left and right edges wrap.
+    pub x: bool,
+    /// Whether the top and bottom edges wrap. There's no ground while they do,
+    /// so things can fall forever.
+    pub y: bool,
+}
+
+impl Wrap {
+    pub fn any(&self) -> bool {
+        self.x || self.y
+    }
+
+    /// Width and height along the axes that wrap, zero along the others.
+    fn period(&self) -> Vec2 {
+        let size = self.max - self.min;
+        Vec2::new(if self.x { size.x } else { 0.0 }, if self.y { size.y } else { 0.0 })
+    }
+
+    /// The offset from `a` to `b`, the short way round.
+    pub fn delta(&self, a: Vec2, b: Vec2) -> Vec2 {
+        let mut delta = b - a;
+        let period = self.period();
+        for axis in 0..2 {
+            if period[axis] > 0.0 {
+                delta[axis] -= period[axis] * (delta[axis] / period[axis]).round();
+            }
+        }
+        delta
+    }
+
+    /// Brings `node` back in through the opposite edge if it's gone out through
+    /// one, moving its last position with it so its velocity doesn't change.
+    pub fn wrap(&self, node: &mut Node) {
+        if node.fixed {
+            return;
+        }
+        let period = self.period();
+        for axis in 0..2 {
+            if period[axis] > 0.0 {
+                let shift = period[axis] * ((node.pos[axis] - self.min[axis]) / period[axis]).floor();
+                node.pos[axis] -= shift;
+                node.last_pos[axis] -= shift;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_nodes_and_takes_the_short_way_round() {
+        let wrap = Wrap { max: Vec2::new(100.0, 100.0), x: true, ..Wrap::default() };
+
+        let mut node = Node::with_pos_and_mass(Vec2::new(105.0, 150.0), 1.0);
+        node.last_pos = Vec2::new(95.0, 140.0);
+        wrap.wrap(&mut node);
+        // only sideways, and still moving the same way
+        assert_eq!(node.pos, Vec2::new(5.0, 150.0));
+        assert_eq!(node.last_pos, Vec2::new(-5.0, 140.0));
+
+        assert_eq!(wrap.delta(Vec2::new(95.0, 0.0), Vec2::new(5.0, 0.0)), Vec2::new(10.0, 0.0));
+        assert_eq!(wrap.delta(Vec2::new(50.0, 0.0), Vec2::new(5.0, 90.0)), Vec2::new(-45.0, 90.0));
+    }
+}
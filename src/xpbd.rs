@@ -0,0 +1,96 @@
+//! Extended position-based dynamics, from Macklin, Müller, and Chentanez's
+//! "XPBD: Position-Based Simulation of Compliant Constrained Dynamics".
+//!
+//! Projecting constraints by `rigidity` makes how stiff links are depend on
+//! the solver iterations and the timestep: more of either and the same rope
+//! stretches less. XPBD gives each link a compliance, the inverse of a
+//! spring's stiffness, and keeps a running multiplier per link over the
+//! iterations of a step, so a link stretches the same under the same load
+//! however it's stepped, once the iterations have converged.
+
+use crate::constraint::Constraint;
+use crate::node::Node;
+use crate::wrap::Wrap;
+use crate::DT;
+
+/// Compliance XPBD starts at unless told otherwise: rigid, like projection at full rigidity.
+pub const XPBD_COMPLIANCE: f32 = 0.0;
+
+/// Solves constraints the XPBD way instead of projecting them by `rigidity`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Xpbd {
+    /// How far a link gives per unit of force, the same for every link. Zero is rigid.
+    pub compliance: f32,
+    /// Each constraint's multiplier so far this step, by index into the world's constraints.
+    pub lambdas: Vec<f32>,
+    /// The timestep of the step being solved.
+    pub dt: f32,
+}
+
+impl Xpbd {
+    pub fn new(compliance: f32) -> Self {
+        Self { compliance, lambdas: Vec::new(), dt: DT }
+    }
+
+    /// Starts a step of `dt` over `count` constraints, forgetting the last step's multipliers.
+    pub fn begin(&mut self, count: usize, dt: f32) {
+        self.lambdas.clear();
+        self.lambdas.resize(count, 0.0);
+        self.dt = dt;
+    }
+
+    /// One pass over `constraints`, each pulling or pushing its ends by their
+    /// inverse mass, fixed nodes staying put.
+    pub fn solve(&mut self, constraints: &[Constraint], arena: &mut [Node], wrap: &Wrap) {
+        // a constraint added since `begin` starts from nothing
+        self.lambdas.resize(constraints.len(), 0.0);
+        let alpha = self.compliance / (self.dt * self.dt);
+        for (constraint, lambda) in constraints.iter().zip(self.lambdas.iter_mut()) {
+            let (a_weight, b_weight) = (arena[constraint.a].inverse_mass(), arena[constraint.b].inverse_mass());
+            let r = constraint.offset(arena, wrap);
+            let dist = r.length();
+            let weights = a_weight + b_weight + alpha;
+            if dist == 0.0 || weights == alpha {
+                continue;
+            }
+            let delta = (constraint.rest_length - dist - alpha * *lambda) / weights;
+            *lambda += delta;
+            let offs = r / dist * delta;
+            arena[constraint.a].add_offs(-offs * a_weight);
+            arena[constraint.b].add_offs(offs * b_weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+    use glam::Vec2;
+
+    #[test]
+    fn stretches_by_load_times_compliance() {
+        let compliance = 0.01;
+        let mut world = World::new(1000.0);
+        world.arena.push(Node { fixed: true, ..Node::with_pos_and_mass(Vec2::ZERO, 1.0) });
+        world.arena.push(Node::with_pos_and_mass(Vec2::new(0.0, 50.0), 5.0));
+        world.constraints.push(Constraint { a: 0, b: 1, rest_length: 50.0, break_threshold: 500.0 });
+        world.params.drag = 2.0;
+        world.xpbd = Some(Xpbd::new(compliance));
+        for _ in 0..400 {
+            world.step(world.params.dt);
+        }
+
+        // hanging still, it holds up the bob's weight like a spring of stiffness `1 / compliance`
+        let stretch = world.arena[1].pos.y - 50.0;
+        let expected = 5.0 * world.params.gravity * compliance;
+        assert!((stretch - expected).abs() < 0.02 * expected, "{} against {}", stretch, expected);
+
+        // and it's the same with more iterations
+        world.params.solver_iterations *= 4;
+        for _ in 0..10 {
+            world.step(world.params.dt);
+        }
+        assert!((world.arena[1].pos.y - 50.0 - expected).abs() < 0.02 * expected);
+    }
+}
@@ -0,0 +1,223 @@
+//! Property tests for invariants every step should keep, whatever the scene.
+
+use clothsim::{Constraint, Node, Params, SimEvent, Sink, World, Wrap, NODE_RADIUS, TARGET_DIST};
+use glam::Vec2;
+use proptest::prelude::*;
+
+fn vec2(range: f32) -> impl Strategy<Value = Vec2> {
+    (-range..range, -range..range).prop_map(|(x, y)| Vec2::new(x, y))
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    // log-uniform masses, so light pairs come up as often as heavy ones
+    (vec2(500.0), -2.3f32..2.3, any::<bool>()).prop_map(|(pos, log_mass, fixed)| {
+        let mut node = Node::with_pos_and_mass(pos, log_mass.exp());
+        node.fixed = fixed;
+        node
+    })
+}
+
+/// Up to 40 nodes above a ground at 600, linked at random.
+fn world() -> impl Strategy<Value = World> {
+    prop::collection::vec(node(), 2..40)
+        .prop_flat_map(|arena| {
+            let n = arena.len();
+            let links = prop::collection::vec((0..n, 0..n, 1.0f32..10.0), 0..80);
+            (Just(arena), links)
+        })
+        .prop_map(|(arena, links)| {
+            let mut world = World::new(600.0);
+            world.arena = arena;
+            world.constraints = links
+                .into_iter()
+                .filter(|(a, b, _)| a != b)
+                .map(|(a, b, threshold)| Constraint {
+                    a,
+                    b,
+                    rest_length: TARGET_DIST,
+                    break_threshold: TARGET_DIST * threshold,
+                })
+                .collect();
+            world
+        })
+}
+
+/// Closest distance between segments `ab` and `cd`, in f64 so rounding can't hide a miss.
+fn segment_distance(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> f64 {
+    let p = |v: Vec2| (v.x as f64, v.y as f64);
+    let (a, b, c, d) = (p(a), p(b), p(c), p(d));
+    let cross = |o: (f64, f64), u: (f64, f64), v: (f64, f64)| {
+        (u.0 - o.0) * (v.1 - o.1) - (u.1 - o.1) * (v.0 - o.0)
+    };
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return 0.0;
+    }
+
+    let point_to_segment = |q: (f64, f64), s: (f64, f64), e: (f64, f64)| {
+        let (dx, dy) = (e.0 - s.0, e.1 - s.1);
+        let len_sq = dx * dx + dy * dy;
+        let t = if len_sq == 0.0 {
+            0.0
+        } else {
+            (((q.0 - s.0) * dx + (q.1 - s.1) * dy) / len_sq).clamp(0.0, 1.0)
+        };
+        ((q.0 - s.0 - t * dx).powi(2) + (q.1 - s.1 - t * dy).powi(2)).sqrt()
+    };
+    point_to_segment(a, c, d)
+        .min(point_to_segment(b, c, d))
+        .min(point_to_segment(c, a, b))
+        .min(point_to_segment(d, a, b))
+}
+
+proptest! {
+    #[test]
+    fn fixed_nodes_never_move(mut world in world(), steps in 1usize..20) {
+        let before = world.arena.clone();
+        for _ in 0..steps {
+            world.step(world.params.dt);
+        }
+        for (old, new) in before.iter().zip(world.arena.iter()) {
+            if old.fixed {
+                prop_assert_eq!(old.pos, new.pos);
+            }
+        }
+    }
+
+    #[test]
+    fn masses_stay_positive(mut world in world(), steps in 1usize..20) {
+        for _ in 0..steps {
+            world.step(world.params.dt);
+        }
+        prop_assert!(world.arena.iter().all(|node| node.mass > 0.0));
+    }
+
+    #[test]
+    fn solving_never_increases_violation(
+        a in node(),
+        b in node(),
+        rigidity in 0.0f32..=1.0,
+    ) {
+        let mut arena = [a, b];
+        let constraint = Constraint { a: 0, b: 1, rest_length: TARGET_DIST, break_threshold: f32::INFINITY };
+        let violation = |arena: &[Node]| ((arena[1].pos - arena[0].pos).length() - TARGET_DIST).abs();
+
+        let before = violation(&arena);
+        constraint.solve(&mut arena, rigidity, &Wrap::default());
+        prop_assert!(violation(&arena) <= before + 1e-3, "{} -> {}", before, violation(&arena));
+    }
+
+    #[test]
+    fn tensions_follow_each_step(mut world in world()) {
+        world.set_params(Params::default());
+        world.step(world.params.dt);
+
+        prop_assert_eq!(world.tensions.len(), world.constraints.len());
+        for (constraint, &tension) in world.constraints.iter().zip(world.tensions.iter()) {
+            let stretch = constraint.offset(&world.arena, &world.wrap).length() - constraint.rest_length;
+            // pulling while stretched, pushing while squashed
+            prop_assert!(tension * stretch >= 0.0, "{} at {} stretch", tension, stretch);
+        }
+    }
+
+    #[test]
+    fn knife_only_removes_what_it_crosses(mut world in world(), c in vec2(600.0), d in vec2(600.0)) {
+        world.set_params(Params::default());
+        let before = world.constraints.clone();
+        world.cut(c, d);
+
+        // cutting only ever removes, so the survivors are a subsequence of `before`
+        let mut survivors = world.constraints.iter().peekable();
+        for constraint in before.iter() {
+            let kept = survivors
+                .peek()
+                .is_some_and(|kept| (kept.a, kept.b) == (constraint.a, constraint.b));
+            if kept {
+                survivors.next();
+                continue;
+            }
+
+            let (a, b) = (world.arena[constraint.a].pos, world.arena[constraint.b].pos);
+            let distance = segment_distance(a, b, c, d);
+            prop_assert!(distance < 1e-3, "cut {:?}..{:?}, {} from the knife", a, b, distance);
+        }
+        prop_assert!(survivors.next().is_none());
+    }
+
+    #[test]
+    fn first_cut_removes_one_of_what_a_full_cut_would(mut world in world(), c in vec2(600.0), d in vec2(600.0)) {
+        world.set_params(Params::default());
+        let mut full = world.clone();
+        full.cut_path(&[c, d], 0.0);
+        let could_cut = full.constraints.len() < world.constraints.len();
+
+        let before = world.constraints.len();
+        prop_assert_eq!(world.cut_first(&[c, d], 0.0), could_cut);
+        prop_assert_eq!(world.constraints.len(), before - could_cut as usize);
+    }
+
+    #[test]
+    fn splitting_keeps_mass_and_links(mut world in world(), c in vec2(600.0), d in vec2(600.0)) {
+        world.set_params(Params::default());
+        let mass = |world: &World| world.arena.iter().map(|node| node.mass as f64).sum::<f64>();
+        let (before_mass, before_nodes) = (mass(&world), world.arena.len());
+        world.split_path(&[c, d], 0.0);
+
+        prop_assert!((mass(&world) - before_mass).abs() < 1e-3);
+        let splits = world.events.iter().filter(|e| matches!(e, SimEvent::NodeSplit { .. })).count();
+        prop_assert_eq!(world.arena.len(), before_nodes + splits);
+        prop_assert!(world.constraints.iter().all(|c| c.a < world.arena.len() && c.b < world.arena.len()));
+    }
+
+    #[test]
+    fn pushing_clears_free_nodes_out_of_the_disc(mut world in world(), pos in vec2(500.0), radius in 1.0f32..200.0) {
+        let before = world.arena.clone();
+        world.push(pos, radius);
+        for (old, new) in before.iter().zip(world.arena.iter()) {
+            if old.fixed {
+                prop_assert_eq!(old.pos, new.pos);
+            } else {
+                prop_assert!(new.pos.distance(pos) >= radius + NODE_RADIUS - 1e-3, "{:?} left inside", new.pos);
+            }
+        }
+    }
+
+    #[test]
+    fn sinks_remove_nodes_and_their_links(mut world in world(), pos in vec2(500.0), radius in 10.0f32..300.0) {
+        world.sinks.push(Sink { pos, strength: 0.0, radius });
+        let survivors: Vec<Vec2> = world
+            .arena
+            .iter()
+            .filter(|node| node.fixed || node.pos.distance(pos) >= radius)
+            .map(|node| node.pos)
+            .collect();
+        let links = |world: &World| {
+            let mut links: Vec<(u32, u32, u32, u32)> = world
+                .constraints
+                .iter()
+                .map(|c| {
+                    let (a, b) = (world.arena[c.a].pos, world.arena[c.b].pos);
+                    (a.x.to_bits(), a.y.to_bits(), b.x.to_bits(), b.y.to_bits())
+                })
+                .collect();
+            links.sort_unstable();
+            links
+        };
+        let expected_links: Vec<_> = {
+            let mut kept = world.clone();
+            kept.constraints.retain(|c| {
+                let survives = |node: &Node| node.fixed || node.pos.distance(pos) >= radius;
+                survives(&world.arena[c.a]) && survives(&world.arena[c.b])
+            });
+            links(&kept)
+        };
+
+        let before = world.arena.len();
+        world.consume_sunk();
+        let consumed = world.events.iter().filter(|e| matches!(e, SimEvent::NodeConsumed { .. })).count();
+        prop_assert_eq!(consumed, before - survivors.len());
+        prop_assert_eq!(world.arena.iter().map(|node| node.pos).collect::<Vec<_>>(), survivors);
+        prop_assert_eq!(links(&world), expected_links);
+    }
+}